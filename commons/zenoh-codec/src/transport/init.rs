@@ -52,6 +52,8 @@ where
             ext_mlink,
             ext_lowlatency,
             ext_compression,
+            ext_arq,
+            ext_compression_algorithms,
         } = x;
 
         // Header
@@ -64,7 +66,9 @@ where
             + (ext_auth.is_some() as u8)
             + (ext_mlink.is_some() as u8)
             + (ext_lowlatency.is_some() as u8)
-            + (ext_compression.is_some() as u8);
+            + (ext_compression.is_some() as u8)
+            + (ext_arq.is_some() as u8)
+            + (ext_compression_algorithms.is_some() as u8);
 
         #[cfg(feature = "shared-memory")]
         {
@@ -125,6 +129,14 @@ where
             n_exts -= 1;
             self.write(&mut *writer, (compression, n_exts != 0))?;
         }
+        if let Some(arq) = ext_arq.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (arq, n_exts != 0))?;
+        }
+        if let Some(algorithms) = ext_compression_algorithms.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (algorithms, n_exts != 0))?;
+        }
 
         Ok(())
     }
@@ -186,6 +198,8 @@ where
         let mut ext_mlink = None;
         let mut ext_lowlatency = None;
         let mut ext_compression = None;
+        let mut ext_arq = None;
+        let mut ext_compression_algorithms = None;
 
         let mut has_ext = imsg::has_flag(self.header, flag::Z);
         while has_ext {
@@ -228,6 +242,16 @@ where
                     ext_compression = Some(q);
                     has_ext = ext;
                 }
+                ext::Arq::ID => {
+                    let (a, ext): (ext::Arq, bool) = eodec.read(&mut *reader)?;
+                    ext_arq = Some(a);
+                    has_ext = ext;
+                }
+                ext::CompressionAlgorithms::ID => {
+                    let (a, ext): (ext::CompressionAlgorithms, bool) = eodec.read(&mut *reader)?;
+                    ext_compression_algorithms = Some(a);
+                    has_ext = ext;
+                }
                 _ => {
                     has_ext = extension::skip(reader, "InitSyn", ext)?;
                 }
@@ -248,6 +272,8 @@ where
             ext_mlink,
             ext_lowlatency,
             ext_compression,
+            ext_arq,
+            ext_compression_algorithms,
         })
     }
 }
@@ -275,6 +301,8 @@ where
             ext_mlink,
             ext_lowlatency,
             ext_compression,
+            ext_arq,
+            ext_compression_algorithms,
         } = x;
 
         // Header
@@ -287,7 +315,9 @@ where
             + (ext_auth.is_some() as u8)
             + (ext_mlink.is_some() as u8)
             + (ext_lowlatency.is_some() as u8)
-            + (ext_compression.is_some() as u8);
+            + (ext_compression.is_some() as u8)
+            + (ext_arq.is_some() as u8)
+            + (ext_compression_algorithms.is_some() as u8);
 
         #[cfg(feature = "shared-memory")]
         {
@@ -351,6 +381,14 @@ where
             n_exts -= 1;
             self.write(&mut *writer, (compression, n_exts != 0))?;
         }
+        if let Some(arq) = ext_arq.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (arq, n_exts != 0))?;
+        }
+        if let Some(algorithms) = ext_compression_algorithms.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (algorithms, n_exts != 0))?;
+        }
 
         Ok(())
     }
@@ -415,6 +453,8 @@ where
         let mut ext_mlink = None;
         let mut ext_lowlatency = None;
         let mut ext_compression = None;
+        let mut ext_arq = None;
+        let mut ext_compression_algorithms = None;
 
         let mut has_ext = imsg::has_flag(self.header, flag::Z);
         while has_ext {
@@ -457,6 +497,16 @@ where
                     ext_compression = Some(q);
                     has_ext = ext;
                 }
+                ext::Arq::ID => {
+                    let (a, ext): (ext::Arq, bool) = eodec.read(&mut *reader)?;
+                    ext_arq = Some(a);
+                    has_ext = ext;
+                }
+                ext::CompressionAlgorithms::ID => {
+                    let (a, ext): (ext::CompressionAlgorithms, bool) = eodec.read(&mut *reader)?;
+                    ext_compression_algorithms = Some(a);
+                    has_ext = ext;
+                }
                 _ => {
                     has_ext = extension::skip(reader, "InitAck", ext)?;
                 }
@@ -478,6 +528,8 @@ where
             ext_mlink,
             ext_lowlatency,
             ext_compression,
+            ext_arq,
+            ext_compression_algorithms,
         })
     }
 }
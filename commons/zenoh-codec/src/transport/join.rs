@@ -150,6 +150,7 @@ where
             next_sn,
             ext_qos,
             ext_shm,
+            ext_fec,
         } = x;
 
         // Header
@@ -160,7 +161,8 @@ where
         if resolution != &Resolution::default() || batch_size != &batch_size::MULTICAST {
             header |= flag::S;
         }
-        let mut n_exts = (ext_qos.is_some() as u8) + (ext_shm.is_some() as u8);
+        let mut n_exts =
+            (ext_qos.is_some() as u8) + (ext_shm.is_some() as u8) + (ext_fec.is_some() as u8);
         if n_exts != 0 {
             header |= flag::Z;
         }
@@ -201,6 +203,10 @@ where
             n_exts -= 1;
             self.write(&mut *writer, (shm, n_exts != 0))?;
         }
+        if let Some(fec) = ext_fec.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (fec, n_exts != 0))?;
+        }
 
         Ok(())
     }
@@ -264,6 +270,7 @@ where
         // Extensions
         let mut ext_qos = None;
         let mut ext_shm = None;
+        let mut ext_fec = None;
 
         let mut has_ext = imsg::has_flag(self.header, flag::Z);
         while has_ext {
@@ -280,6 +287,11 @@ where
                     ext_shm = Some(s);
                     has_ext = ext;
                 }
+                ext::Fec::ID => {
+                    let (f, ext): (ext::Fec, bool) = eodec.read(&mut *reader)?;
+                    ext_fec = Some(f);
+                    has_ext = ext;
+                }
                 _ => {
                     has_ext = extension::skip(reader, "Join", ext)?;
                 }
@@ -296,6 +308,7 @@ where
             next_sn,
             ext_qos,
             ext_shm,
+            ext_fec,
         })
     }
 }
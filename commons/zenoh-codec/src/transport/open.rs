@@ -48,6 +48,7 @@ where
             ext_mlink,
             ext_lowlatency,
             ext_compression,
+            ext_metadata,
         } = x;
 
         // Header
@@ -59,7 +60,8 @@ where
             + (ext_auth.is_some() as u8)
             + (ext_mlink.is_some() as u8)
             + (ext_lowlatency.is_some() as u8)
-            + (ext_compression.is_some() as u8);
+            + (ext_compression.is_some() as u8)
+            + (ext_metadata.is_some() as u8);
 
         #[cfg(feature = "shared-memory")]
         {
@@ -106,6 +108,10 @@ where
             n_exts -= 1;
             self.write(&mut *writer, (compression, n_exts != 0))?;
         }
+        if let Some(metadata) = ext_metadata.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (metadata, n_exts != 0))?;
+        }
 
         Ok(())
     }
@@ -153,6 +159,7 @@ where
         let mut ext_mlink = None;
         let mut ext_lowlatency = None;
         let mut ext_compression = None;
+        let mut ext_metadata = None;
 
         let mut has_ext = imsg::has_flag(self.header, flag::Z);
         while has_ext {
@@ -190,6 +197,11 @@ where
                     ext_compression = Some(q);
                     has_ext = ext;
                 }
+                ext::Metadata::ID => {
+                    let (m, ext): (ext::Metadata, bool) = eodec.read(&mut *reader)?;
+                    ext_metadata = Some(m);
+                    has_ext = ext;
+                }
                 _ => {
                     has_ext = extension::skip(reader, "OpenSyn", ext)?;
                 }
@@ -207,6 +219,7 @@ where
             ext_mlink,
             ext_lowlatency,
             ext_compression,
+            ext_metadata,
         })
     }
 }
@@ -229,6 +242,7 @@ where
             ext_mlink,
             ext_lowlatency,
             ext_compression,
+            ext_metadata,
         } = x;
 
         // Header
@@ -242,7 +256,8 @@ where
             + (ext_auth.is_some() as u8)
             + (ext_mlink.is_some() as u8)
             + (ext_lowlatency.is_some() as u8)
-            + (ext_compression.is_some() as u8);
+            + (ext_compression.is_some() as u8)
+            + (ext_metadata.is_some() as u8);
 
         #[cfg(feature = "shared-memory")]
         {
@@ -288,6 +303,10 @@ where
             n_exts -= 1;
             self.write(&mut *writer, (compression, n_exts != 0))?;
         }
+        if let Some(metadata) = ext_metadata.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (metadata, n_exts != 0))?;
+        }
 
         Ok(())
     }
@@ -334,6 +353,7 @@ where
         let mut ext_mlink = None;
         let mut ext_lowlatency = None;
         let mut ext_compression = None;
+        let mut ext_metadata = None;
 
         let mut has_ext = imsg::has_flag(self.header, flag::Z);
         while has_ext {
@@ -371,6 +391,11 @@ where
                     ext_compression = Some(q);
                     has_ext = ext;
                 }
+                ext::Metadata::ID => {
+                    let (m, ext): (ext::Metadata, bool) = eodec.read(&mut *reader)?;
+                    ext_metadata = Some(m);
+                    has_ext = ext;
+                }
                 _ => {
                     has_ext = extension::skip(reader, "OpenAck", ext)?;
                 }
@@ -387,6 +412,7 @@ where
             ext_mlink,
             ext_lowlatency,
             ext_compression,
+            ext_metadata,
         })
     }
 }
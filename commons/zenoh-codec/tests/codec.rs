@@ -466,6 +466,22 @@ fn codec_init_ack() {
     run!(InitAck, InitAck::rand());
 }
 
+#[test]
+fn codec_init_ext_ids() {
+    // The IDs of the extensions are part of the wire protocol: the new ones are appended
+    let ids = [
+        transport::init::ext::QoS::ID,
+        transport::init::ext::Auth::ID,
+        transport::init::ext::MultiLink::ID,
+        transport::init::ext::LowLatency::ID,
+        transport::init::ext::Compression::ID,
+        transport::init::ext::Arq::ID,
+        transport::init::ext::CompressionAlgorithms::ID,
+    ];
+    let ids: Vec<u8> = ids.iter().map(|id| iext::mid(*id)).collect();
+    assert_eq!(ids, [0x1, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8]);
+}
+
 #[test]
 fn codec_open_syn() {
     run!(OpenSyn, OpenSyn::rand());
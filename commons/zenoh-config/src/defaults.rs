@@ -161,8 +161,11 @@ impl Default for TransportUnicastConf {
             max_sessions: 1_000,
             max_links: 1,
             lowlatency: false,
+            advertise_metadata: false,
             qos: QoSUnicastConf::default(),
             compression: CompressionUnicastConf::default(),
+            multilink: MultilinkUnicastConf::default(),
+            arq: ArqUnicastConf::default(),
         }
     }
 }
@@ -174,27 +177,85 @@ impl Default for TransportMulticastConf {
             max_sessions: Some(1000),
             qos: QoSMulticastConf::default(),
             compression: CompressionMulticastConf::default(),
+            fec: FecMulticastConf::default(),
         }
     }
 }
 
 impl Default for QoSUnicastConf {
     fn default() -> Self {
-        Self { enabled: true }
+        Self {
+            enabled: true,
+            latency_budget: LatencyBudgetConf::default(),
+        }
     }
 }
 
 #[allow(clippy::derivable_impls)]
 impl Default for QoSMulticastConf {
     fn default() -> Self {
-        Self { enabled: false }
+        Self {
+            enabled: false,
+            latency_budget: LatencyBudgetConf::default(),
+        }
+    }
+}
+
+impl Default for LatencyBudgetConf {
+    fn default() -> Self {
+        Self {
+            control: 0,
+            real_time: 0,
+            interactive_high: 100,
+            interactive_low: 500,
+            data_high: 1000,
+            data: 1000,
+            data_low: 2000,
+            background: 5000,
+        }
     }
 }
 
 #[allow(clippy::derivable_impls)]
 impl Default for CompressionUnicastConf {
     fn default() -> Self {
-        Self { enabled: false }
+        Self {
+            enabled: false,
+            algorithms: vec![CompressionAlgorithmConf::Lz4],
+            zstd: CompressionZstdConf::default(),
+            priorities: None,
+            protocols: None,
+            threshold: 0,
+        }
+    }
+}
+
+impl Default for CompressionZstdConf {
+    fn default() -> Self {
+        Self {
+            level: 3,
+            dictionary: None,
+        }
+    }
+}
+
+impl Default for MultilinkUnicastConf {
+    fn default() -> Self {
+        Self {
+            scheduling: MultilinkScheduling::First,
+            failover: true,
+        }
+    }
+}
+
+impl Default for ArqUnicastConf {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window: 64,
+            ack_interval: 10,
+            retransmission_timeout: 100,
+        }
     }
 }
 
@@ -205,6 +266,17 @@ impl Default for CompressionMulticastConf {
     }
 }
 
+impl Default for FecMulticastConf {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            data_shards: 8,
+            parity_shards: 2,
+            timeout: 10,
+        }
+    }
+}
+
 impl Default for LinkTxConf {
     #[allow(clippy::unnecessary_cast)]
     fn default() -> Self {
@@ -261,6 +333,7 @@ impl Default for BatchingConf {
         BatchingConf {
             enabled: true,
             time_limit: 1,
+            adaptive: false,
         }
     }
 }
@@ -290,6 +363,7 @@ impl Default for AclConfig {
             rules: None,
             subjects: None,
             policies: None,
+            audit: None,
         }
     }
 }
@@ -26,10 +26,12 @@ pub mod wrappers;
 #[allow(unused_imports)]
 use std::convert::TryFrom; // This is a false positive from the rust analyser
 use std::{
-    any::Any, collections::HashSet, fmt, io::Read, net::SocketAddr, ops, path::Path, sync::Weak,
+    any::Any, collections::HashSet, fmt, io::Read, net::SocketAddr, ops, path::Path, str::FromStr,
+    sync::Weak,
 };
 
 use include::recursive_include;
+pub use ipnetwork::IpNetwork;
 use secrecy::{CloneableSecret, DebugSecret, Secret, SerializableSecret, Zeroize};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
@@ -40,7 +42,7 @@ pub use zenoh_protocol::core::{
     whatami, EndPoint, Locator, WhatAmI, WhatAmIMatcher, WhatAmIMatcherVisitor,
 };
 use zenoh_protocol::{
-    core::{key_expr::OwnedKeyExpr, Bits},
+    core::{key_expr::OwnedKeyExpr, Bits, CongestionControl, Priority},
     transport::{BatchSize, TransportSn},
 };
 use zenoh_result::{bail, zerror, ZResult};
@@ -75,7 +77,7 @@ impl Zeroize for SecretString {
 
 pub type SecretValue = Secret<SecretString>;
 
-#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum InterceptorFlow {
     Egress,
@@ -102,6 +104,311 @@ pub struct DownsamplingItemConf {
     pub flow: InterceptorFlow,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitedMessage {
+    Push,
+    Request,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitAction {
+    /// The messages exceeding a budget are dropped.
+    #[default]
+    Drop,
+    /// The messages exceeding a budget are delayed until the budget allows them, without blocking
+    /// the routing of the other messages. The messages to delay more than `max_delay_ms` are
+    /// dropped.
+    Delay,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitScope {
+    /// Each face has its own budgets.
+    #[default]
+    Face,
+    /// The faces authenticated with the same username, or with the same certificate common name
+    /// if they have no username, share the same budgets. The other faces have their own budgets.
+    Subject,
+    /// All the faces matching the item share the same budgets.
+    Shared,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RateLimitBudgetConf {
+    /// The number of tokens (messages or bytes) added to the budget per second.
+    pub rate: f64,
+    /// The maximum number of tokens the budget can accumulate, allowing bursts above the rate.
+    /// Defaults to one second worth of tokens.
+    pub burst: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RateLimitingRuleConf {
+    /// The key-expression to which the rule applies.
+    pub key_expr: OwnedKeyExpr,
+    /// The budget of messages.
+    pub messages: Option<RateLimitBudgetConf>,
+    /// The budget of payload bytes.
+    pub bytes: Option<RateLimitBudgetConf>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RateLimitingItemConf {
+    /// A list of interfaces to which the rate limiting will be applied.
+    /// Rate limiting will be applied for all interfaces if the parameter is None.
+    pub interfaces: Option<Vec<String>>,
+    /// A list of certificate common names to which the rate limiting will be applied.
+    /// Rate limiting will be applied for all common names if the parameter is None.
+    pub cert_common_names: Option<Vec<String>>,
+    /// A list of usernames to which the rate limiting will be applied.
+    /// Rate limiting will be applied for all usernames if the parameter is None.
+    pub usernames: Option<Vec<String>>,
+    /// The messages to which the rate limiting will be applied: push, request.
+    /// Rate limiting will be applied to both if the parameter is None.
+    pub messages: Option<Vec<RateLimitedMessage>>,
+    /// Rate limiting flow direction: egress, ingress
+    pub flow: InterceptorFlow,
+    /// The action taken on the messages exceeding a budget: drop (default), delay
+    /// The delay action is only supported on the ingress flow.
+    #[serde(default)]
+    pub action: RateLimitAction,
+    /// The maximum delay in milliseconds of a message with the `delay` action.
+    pub max_delay_ms: Option<u64>,
+    /// Whether the budgets apply to each face (default), to the faces of the same subject or to
+    /// all the matching faces together: face, subject, shared
+    #[serde(default)]
+    pub scope: RateLimitScope,
+    /// A list of rate limiting rules: key_expression and its budgets.
+    pub rules: Vec<RateLimitingRuleConf>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaScope {
+    /// Each face has its own declarations count.
+    #[default]
+    Face,
+    /// All the faces matching the quota share the same declarations count.
+    Subject,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+pub struct DeclarationLimitsConf {
+    /// The maximum number of subscribers.
+    pub subscribers: Option<usize>,
+    /// The maximum number of queryables.
+    pub queryables: Option<usize>,
+    /// The maximum number of liveliness tokens.
+    pub tokens: Option<usize>,
+    /// The maximum number of distinct key expressions declared, either as key expression
+    /// declarations or by the subscribers, queryables and liveliness tokens.
+    pub resources: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct QuotaItemConf {
+    /// A list of interfaces to which the quota will be applied.
+    /// The quota will be applied for all interfaces if the parameter is None.
+    pub interfaces: Option<Vec<String>>,
+    /// A list of certificate common names to which the quota will be applied.
+    /// The quota will be applied for all common names if the parameter is None.
+    pub cert_common_names: Option<Vec<String>>,
+    /// A list of usernames to which the quota will be applied.
+    /// The quota will be applied for all usernames if the parameter is None.
+    pub usernames: Option<Vec<String>>,
+    /// Whether the limits apply to each face (default) or to all the matching faces: face, subject
+    #[serde(default)]
+    pub scope: QuotaScope,
+    /// The limits on the declarations received from the faces.
+    pub limits: DeclarationLimitsConf,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QosOverwriteMessage {
+    Push,
+    Request,
+}
+
+/// The priority a message is overwritten with.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum QosOverwritePriority {
+    RealTime,
+    InteractiveHigh,
+    InteractiveLow,
+    DataHigh,
+    Data,
+    DataLow,
+    Background,
+}
+
+impl From<QosOverwritePriority> for Priority {
+    fn from(priority: QosOverwritePriority) -> Self {
+        match priority {
+            QosOverwritePriority::RealTime => Priority::RealTime,
+            QosOverwritePriority::InteractiveHigh => Priority::InteractiveHigh,
+            QosOverwritePriority::InteractiveLow => Priority::InteractiveLow,
+            QosOverwritePriority::DataHigh => Priority::DataHigh,
+            QosOverwritePriority::Data => Priority::Data,
+            QosOverwritePriority::DataLow => Priority::DataLow,
+            QosOverwritePriority::Background => Priority::Background,
+        }
+    }
+}
+
+/// The congestion control a message is overwritten with.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QosOverwriteCongestionControl {
+    Drop,
+    Block,
+}
+
+impl From<QosOverwriteCongestionControl> for CongestionControl {
+    fn from(congestion_control: QosOverwriteCongestionControl) -> Self {
+        match congestion_control {
+            QosOverwriteCongestionControl::Drop => CongestionControl::Drop,
+            QosOverwriteCongestionControl::Block => CongestionControl::Block,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+pub struct QosOverwrites {
+    /// The priority of the messages, unchanged if None.
+    pub priority: Option<QosOverwritePriority>,
+    /// The congestion control of the messages, unchanged if None.
+    pub congestion_control: Option<QosOverwriteCongestionControl>,
+    /// The express flag of the messages, unchanged if None.
+    pub express: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct QosOverwriteItemConf {
+    /// A list of interfaces to which the QoS overwrite will be applied.
+    /// The QoS overwrite will be applied for all interfaces if the parameter is None.
+    pub interfaces: Option<Vec<String>>,
+    /// A list of certificate common names to which the QoS overwrite will be applied.
+    /// The QoS overwrite will be applied for all common names if the parameter is None.
+    pub cert_common_names: Option<Vec<String>>,
+    /// A list of usernames to which the QoS overwrite will be applied.
+    /// The QoS overwrite will be applied for all usernames if the parameter is None.
+    pub usernames: Option<Vec<String>>,
+    /// A list of modes of the remote nodes to which the QoS overwrite will be applied.
+    /// The QoS overwrite will be applied for all modes if the parameter is None.
+    pub whatamis: Option<Vec<WhatAmI>>,
+    /// The messages to which the QoS overwrite will be applied: push, request.
+    /// The QoS overwrite will be applied to both if the parameter is None.
+    pub messages: Option<Vec<QosOverwriteMessage>>,
+    /// QoS overwrite flow directions: egress, ingress.
+    /// The QoS overwrite will be applied in both directions if the parameter is None.
+    pub flows: Option<Vec<InterceptorFlow>>,
+    /// The key expressions of the messages to which the QoS overwrite will be applied.
+    pub key_exprs: Vec<OwnedKeyExpr>,
+    /// The QoS the messages are overwritten with.
+    pub overwrite: QosOverwrites,
+}
+
+/// The action taken on the messages matching a content filter.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentFilterAction {
+    /// Only the matching messages are forwarded.
+    Forward,
+    /// The matching messages are dropped.
+    Drop,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentFilterOperator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// The value at the pointer exists, whatever it is.
+    Exists,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct JsonPredicateConf {
+    /// The JSON pointer (RFC 6901) of the compared value in the payload, e.g. "/sensor/level".
+    pub pointer: String,
+    /// The comparison operator: eq, ne, lt, le, gt, ge, exists
+    pub operator: ContentFilterOperator,
+    /// The value compared to, required by all the operators but `exists`.
+    pub value: Option<Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentPredicateConf {
+    /// A comparison on a value of a JSON payload.
+    Json(JsonPredicateConf),
+    /// The presence of a key in an attachment made, as `zenoh_ext` serializes a map of strings or
+    /// bytes, of the LEB128 number of entries followed by the LEB128 length prefixed bytes of each
+    /// key and value. The attachments which are not exactly in this format never match.
+    AttachmentKey(String),
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ContentFilterItemConf {
+    /// A list of interfaces to which the content filter will be applied.
+    /// The content filter will be applied for all interfaces if the parameter is None.
+    pub interfaces: Option<Vec<String>>,
+    /// A list of certificate common names to which the content filter will be applied.
+    /// The content filter will be applied for all common names if the parameter is None.
+    pub cert_common_names: Option<Vec<String>>,
+    /// A list of usernames to which the content filter will be applied.
+    /// The content filter will be applied for all usernames if the parameter is None.
+    pub usernames: Option<Vec<String>>,
+    /// Content filter flow directions: egress, ingress.
+    /// The content filter will be applied in both directions if the parameter is None.
+    pub flows: Option<Vec<InterceptorFlow>>,
+    /// The key expressions of the publications to which the content filter will be applied.
+    pub key_exprs: Vec<OwnedKeyExpr>,
+    /// The encodings of the matching publications, e.g. "application/json".
+    /// Publications of any encoding match if the parameter is None.
+    pub encodings: Option<Vec<String>>,
+    /// The predicates all verified by the matching publications.
+    #[serde(default)]
+    pub predicates: Vec<ContentPredicateConf>,
+    /// The maximum size in bytes of the payloads parsed by the JSON predicates (default `65536`).
+    /// The larger payloads are not parsed and don't match the JSON predicates.
+    pub max_payload_size: Option<usize>,
+    /// The action taken on the matching publications: forward, drop
+    pub action: ContentFilterAction,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct KeyRemappingRuleConf {
+    /// The prefix of the key expressions on the remote side of the interfaces.
+    /// An empty prefix matches all the key expressions.
+    pub remote_prefix: String,
+    /// The prefix replacing the remote prefix on the local side.
+    /// An empty prefix strips the remote prefix.
+    pub local_prefix: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct KeyRemappingItemConf {
+    /// A list of interfaces to which the key remapping will be applied.
+    /// Key remapping will be applied for all interfaces if the parameter is None.
+    pub interfaces: Option<Vec<String>>,
+    /// Key remapping flow directions: egress, ingress.
+    /// Key remapping will be applied in both directions if the parameter is None.
+    pub flows: Option<Vec<InterceptorFlow>>,
+    /// A list of key remapping rules, the first matching one applies.
+    pub rules: Vec<KeyRemappingRuleConf>,
+}
+
 #[derive(Serialize, Debug, Deserialize, Clone)]
 pub struct AclConfigRule {
     pub id: String,
@@ -117,6 +424,68 @@ pub struct AclConfigSubjects {
     pub interfaces: Option<Vec<Interface>>,
     pub cert_common_names: Option<Vec<CertCommonName>>,
     pub usernames: Option<Vec<Username>>,
+    pub whatamis: Option<Vec<WhatAmI>>,
+    pub zids: Option<Vec<ZenohId>>,
+    pub link_protocols: Option<Vec<LinkProtocol>>,
+    pub source_ips: Option<Vec<IpNetwork>>,
+    /// The metadata entries the remote node must advertise, each value being matched exactly.
+    pub metadata: Option<Map<String, Value>>,
+}
+
+/// The protocol of the link a remote node is connected through.
+#[derive(Serialize, Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkProtocol {
+    Tcp,
+    Udp,
+    Tls,
+    Quic,
+    Ws,
+    Serial,
+    UnixsockStream,
+    Unixpipe,
+    Vsock,
+}
+
+impl LinkProtocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkProtocol::Tcp => "tcp",
+            LinkProtocol::Udp => "udp",
+            LinkProtocol::Tls => "tls",
+            LinkProtocol::Quic => "quic",
+            LinkProtocol::Ws => "ws",
+            LinkProtocol::Serial => "serial",
+            LinkProtocol::UnixsockStream => "unixsock-stream",
+            LinkProtocol::Unixpipe => "unixpipe",
+            LinkProtocol::Vsock => "vsock",
+        }
+    }
+}
+
+impl FromStr for LinkProtocol {
+    type Err = zenoh_result::Error;
+
+    fn from_str(s: &str) -> ZResult<Self> {
+        match s {
+            "tcp" => Ok(LinkProtocol::Tcp),
+            "udp" => Ok(LinkProtocol::Udp),
+            "tls" => Ok(LinkProtocol::Tls),
+            "quic" => Ok(LinkProtocol::Quic),
+            "ws" => Ok(LinkProtocol::Ws),
+            "serial" => Ok(LinkProtocol::Serial),
+            "unixsock-stream" => Ok(LinkProtocol::UnixsockStream),
+            "unixpipe" => Ok(LinkProtocol::Unixpipe),
+            "vsock" => Ok(LinkProtocol::Vsock),
+            _ => bail!("Unknown link protocol: {}", s),
+        }
+    }
+}
+
+impl std::fmt::Display for LinkProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LinkProtocol({})", self.as_str())
+    }
 }
 
 #[derive(Serialize, Debug, Deserialize, Clone, PartialEq, Eq, Hash)]
@@ -154,6 +523,7 @@ pub struct AclConfigPolicyEntry {
 
 #[derive(Clone, Serialize, Debug, Deserialize)]
 pub struct PolicyRule {
+    pub rule_id: String,
     pub subject_id: usize,
     pub key_expr: String,
     pub message: AclMessage,
@@ -170,6 +540,10 @@ pub enum AclMessage {
     Query,
     DeclareQueryable,
     Reply,
+    LivelinessToken,
+    DeclareLivelinessSubscriber,
+    LivelinessQuery,
+    Interest,
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, Hash, PartialEq)]
@@ -179,6 +553,32 @@ pub enum Permission {
     Deny,
 }
 
+#[derive(Serialize, Debug, Deserialize, Clone)]
+pub struct AclAuditConf {
+    /// The decisions to record: deny (default), all.
+    #[serde(default)]
+    pub decisions: AclAuditDecisions,
+    /// The sink of the records.
+    pub sink: AclAuditSink,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AclAuditDecisions {
+    #[default]
+    Deny,
+    All,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AclAuditSink {
+    /// Appends the records, in JSON lines, to the file at the given path.
+    File(String),
+    /// Publishes the records, in JSON, on `@/<zid>/<whatami>/access_control/audit`.
+    AdminSpace,
+}
+
 pub trait ConfigValidator: Send + Sync {
     fn check_config(
         &self,
@@ -191,6 +591,45 @@ pub trait ConfigValidator: Send + Sync {
     }
 }
 
+/// The scheduling of the best-effort messages over the links of a multilink transport matching
+/// equally well their reliability and priority. The reliable messages are always sent on the first
+/// matching link.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MultilinkScheduling {
+    /// The messages are always sent on the first matching link.
+    #[default]
+    First,
+    /// The messages are sent on each matching link in turn.
+    RoundRobin,
+    /// The messages are sent on the matching link with the fewest batches waiting for transmission.
+    LeastQueued,
+    /// The messages are spread over the matching links inversely to their round-trip time.
+    WeightedByRtt,
+}
+
+/// The maximum time in microseconds a message of each priority may be retained in the transmission
+/// queue for batching.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct LatencyBudgetConf {
+    pub control: u64,
+    pub real_time: u64,
+    pub interactive_high: u64,
+    pub interactive_low: u64,
+    pub data_high: u64,
+    pub data: u64,
+    pub data_low: u64,
+    pub background: u64,
+}
+
+/// The algorithms used to compress the batches of unicast links.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionAlgorithmConf {
+    Lz4,
+    Zstd,
+}
+
 // Necessary to allow to set default emplty weak reference value to plugin.validator field
 // because empty weak value is not allowed for Arc<dyn Trait>
 impl ConfigValidator for () {}
@@ -358,15 +797,55 @@ validated_struct::validator! {
                 /// This option does not make LowLatency transport mandatory, the actual implementation of transport
                 /// used will depend on Establish procedure and other party's settings
                 lowlatency: bool,
+                /// Whether the `metadata` of this node is sent to the remote nodes when opening a session,
+                /// e.g. to be matched by their access control subjects (default `false`).
+                /// The metadata is not authenticated: any node can advertise any metadata.
+                advertise_metadata: bool,
                 pub qos: QoSUnicastConf {
                     /// Whether QoS is enabled or not.
                     /// If set to `false`, the QoS will be disabled. (default `true`).
-                    enabled: bool
+                    enabled: bool,
+                    /// The maximum time in microseconds a message of each priority may be retained for adaptive batching.
+                    latency_budget: LatencyBudgetConf,
                 },
                 pub compression: CompressionUnicastConf {
                     /// You must compile zenoh with "transport_compression" feature to be able to enable compression.
                     /// When enabled is true, batches will be sent compressed. (default `false`).
                     enabled: bool,
+                    /// The compression algorithms in order of preference: lz4, zstd (default `["lz4"]`).
+                    /// The first algorithm of the accepting side supported by both sides is used.
+                    /// You must compile zenoh with "transport_compression_zstd" feature to be able to use zstd.
+                    algorithms: Vec<CompressionAlgorithmConf>,
+                    pub zstd: CompressionZstdConf {
+                        /// The zstd compression level, from 1 (fastest) to 22 (smallest) (default `3`).
+                        level: i32,
+                        /// The path of a zstd dictionary file. zstd is used only if both sides have the same dictionary.
+                        dictionary: Option<String>,
+                    },
+                    /// The range of priorities whose batches are compressed, e.g. "4-7" (default: all priorities).
+                    priorities: Option<String>,
+                    /// The link protocols on which compression is used, e.g. ["tcp", "quic"] (default: all protocols).
+                    protocols: Option<Vec<String>>,
+                    /// The minimum size in bytes of the batches to compress (default `0`).
+                    threshold: usize,
+                },
+                pub multilink: MultilinkUnicastConf {
+                    /// The scheduling of the best-effort messages over the links matching equally well their
+                    /// reliability and priority: first, round-robin, least-queued, weighted-by-rtt (default `first`).
+                    scheduling: MultilinkScheduling,
+                    /// Whether the batches pending on a failed link are sent on the remaining links (default `true`).
+                    failover: bool,
+                },
+                pub arq: ArqUnicastConf {
+                    /// Whether the reliable messages are acknowledged and retransmitted on the links which are not
+                    /// reliable, e.g. UDP or serial links. It is used only if enabled on both sides (default `false`).
+                    enabled: bool,
+                    /// The maximum number of reliable frames waiting for acknowledgement per priority (at most and default `64`).
+                    window: usize,
+                    /// The interval in milliseconds between the acknowledgements sent by the receiver (default `10`).
+                    ack_interval: u64,
+                    /// The delay in milliseconds after which an unacknowledged frame is retransmitted (default `100`).
+                    retransmission_timeout: u64,
                 },
             },
             pub multicast: TransportMulticastConf {
@@ -377,13 +856,27 @@ validated_struct::validator! {
                 pub qos: QoSMulticastConf {
                     /// Whether QoS is enabled or not.
                     /// If set to `false`, the QoS will be disabled. (default `false`).
-                    enabled: bool
+                    enabled: bool,
+                    /// The maximum time in microseconds a message of each priority may be retained for adaptive batching.
+                    latency_budget: LatencyBudgetConf,
                 },
                 pub compression: CompressionMulticastConf {
                     /// You must compile zenoh with "transport_compression" feature to be able to enable compression.
                     /// When enabled is true, batches will be sent compressed. (default `false`).
                     enabled: bool,
                 },
+                pub fec: FecMulticastConf {
+                    /// When enabled is true, Reed-Solomon parity shards are sent after each group of batches,
+                    /// so that receivers can rebuild the lost ones. (default `false`).
+                    enabled: bool,
+                    /// The number of batches of a parity group (default `8`).
+                    data_shards: u8,
+                    /// The number of parity shards sent for each group (default `2`).
+                    parity_shards: u8,
+                    /// The maximum time in milliseconds a parity group is kept open before its parity shards are sent (default `10`).
+                    /// The receivers give up a group twice this timeout after receiving its first shard.
+                    timeout: u64,
+                },
             },
             pub link: #[derive(Default)]
             TransportLinkConf {
@@ -443,7 +936,14 @@ validated_struct::validator! {
                             /// scenario mainly composed of small messages. In other words, batching is activated by the network back-pressure.
                             enabled: bool,
                             /// The maximum time limit (in ms) a message should be retained for batching when back-pressure happens.
+                            /// It is not used when adaptive is true.
                             time_limit: u64,
+                            /// Adapt the time a message is retained for batching to the load of the link, measured from the
+                            /// throughput of the link and the rate the batches are transmitted at: messages are sent immediately
+                            /// while the link keeps up with them and retained longer while it is busy, up to the latency budget
+                            /// of their priority configured in the QoS of the transport and to the round-trip time of the link
+                            /// (default `false`).
+                            adaptive: bool,
                         },
                     },
                     // Number of threads used for TX
@@ -543,6 +1043,21 @@ validated_struct::validator! {
         /// Configuration of the downsampling.
         downsampling: Vec<DownsamplingItemConf>,
 
+        /// Configuration of the rate limiting.
+        rate_limiting: Vec<RateLimitingItemConf>,
+
+        /// Configuration of the key remapping.
+        key_remapping: Vec<KeyRemappingItemConf>,
+
+        /// Configuration of the quotas on the declarations.
+        quotas: Vec<QuotaItemConf>,
+
+        /// Configuration of the QoS overwrite.
+        qos_overwrite: Vec<QosOverwriteItemConf>,
+
+        /// Configuration of the content filters.
+        content_filters: Vec<ContentFilterItemConf>,
+
         ///Configuration of the access control (ACL)
         pub access_control: AclConfig {
             pub enabled: bool,
@@ -550,6 +1065,7 @@ validated_struct::validator! {
             pub rules: Option<Vec<AclConfigRule>>,
             pub subjects: Option<Vec<AclConfigSubjects>>,
             pub policies: Option<Vec<AclConfigPolicyEntry>>,
+            pub audit: Option<AclAuditConf>,
         },
 
         /// A list of directories where plugins may be searched for if no `__path__` was specified for them.
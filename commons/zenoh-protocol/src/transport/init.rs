@@ -122,6 +122,8 @@ pub struct InitSyn {
     pub ext_mlink: Option<ext::MultiLink>,
     pub ext_lowlatency: Option<ext::LowLatency>,
     pub ext_compression: Option<ext::Compression>,
+    pub ext_arq: Option<ext::Arq>,
+    pub ext_compression_algorithms: Option<ext::CompressionAlgorithms>,
 }
 
 // Extensions
@@ -156,6 +158,17 @@ pub mod ext {
     /// # Compression extension
     /// Used to negotiate the use of compression on the link
     pub type Compression = zextunit!(0x6, false);
+
+    /// # ARQ extension
+    /// Used to negotiate the retransmission of the reliable messages on a best-effort link.
+    /// The value is the retransmission window in sequence numbers.
+    pub type Arq = zextz64!(0x7, false);
+
+    /// # Compression algorithms extension
+    /// Used to negotiate the compression algorithm on the link. The 8 least significant bits are
+    /// the mask of the supported algorithms, the 32 most significant bits the fingerprint of the
+    /// zstd dictionary, if any.
+    pub type CompressionAlgorithms = zextz64!(0x8, false);
 }
 
 impl InitSyn {
@@ -180,6 +193,8 @@ impl InitSyn {
         let ext_mlink = rng.gen_bool(0.5).then_some(ZExtZBuf::rand());
         let ext_lowlatency = rng.gen_bool(0.5).then_some(ZExtUnit::rand());
         let ext_compression = rng.gen_bool(0.5).then_some(ZExtUnit::rand());
+        let ext_arq = rng.gen_bool(0.5).then_some(ZExtZ64::rand());
+        let ext_compression_algorithms = rng.gen_bool(0.5).then_some(ZExtZ64::rand());
 
         Self {
             version,
@@ -195,6 +210,8 @@ impl InitSyn {
             ext_mlink,
             ext_lowlatency,
             ext_compression,
+            ext_arq,
+            ext_compression_algorithms,
         }
     }
 }
@@ -215,6 +232,8 @@ pub struct InitAck {
     pub ext_mlink: Option<ext::MultiLink>,
     pub ext_lowlatency: Option<ext::LowLatency>,
     pub ext_compression: Option<ext::Compression>,
+    pub ext_arq: Option<ext::Arq>,
+    pub ext_compression_algorithms: Option<ext::CompressionAlgorithms>,
 }
 
 impl InitAck {
@@ -244,6 +263,8 @@ impl InitAck {
         let ext_mlink = rng.gen_bool(0.5).then_some(ZExtZBuf::rand());
         let ext_lowlatency = rng.gen_bool(0.5).then_some(ZExtUnit::rand());
         let ext_compression = rng.gen_bool(0.5).then_some(ZExtUnit::rand());
+        let ext_arq = rng.gen_bool(0.5).then_some(ZExtZ64::rand());
+        let ext_compression_algorithms = rng.gen_bool(0.5).then_some(ZExtZ64::rand());
 
         Self {
             version,
@@ -260,6 +281,8 @@ impl InitAck {
             ext_mlink,
             ext_lowlatency,
             ext_compression,
+            ext_arq,
+            ext_compression_algorithms,
         }
     }
 }
@@ -102,6 +102,7 @@ pub struct Join {
     pub next_sn: PrioritySn,
     pub ext_qos: Option<ext::QoSType>,
     pub ext_shm: Option<ext::Shm>,
+    pub ext_fec: Option<ext::Fec>,
 }
 
 pub mod flag {
@@ -115,7 +116,10 @@ pub mod ext {
     use alloc::boxed::Box;
 
     use super::{Priority, PrioritySn};
-    use crate::{common::ZExtZBuf, zextzbuf};
+    use crate::{
+        common::{ZExtZ64, ZExtZBuf},
+        zextz64, zextzbuf,
+    };
 
     /// # QoS extension
     /// Used to announce next sn when QoS is enabled
@@ -125,6 +129,12 @@ pub mod ext {
     /// # Shm extension
     /// Used to advertise shared memory capabilities
     pub type Shm = zextzbuf!(0x2, true);
+
+    /// # FEC extension
+    /// Used to advertise the forward error correction of the batches: the low 8 bits are the
+    /// number of data shards of a parity group, the next 8 bits the number of parity shards and
+    /// the next 32 bits the maximum time in milliseconds a parity group is kept open
+    pub type Fec = zextz64!(0x3, false);
 }
 
 impl Join {
@@ -151,6 +161,7 @@ impl Join {
             .gen_bool(0.5)
             .then_some(Box::new([PrioritySn::rand(); Priority::NUM]));
         let ext_shm = rng.gen_bool(0.5).then_some(ZExtZBuf::rand());
+        let ext_fec = rng.gen_bool(0.5).then_some(ext::Fec::rand());
 
         Self {
             version,
@@ -162,6 +173,7 @@ impl Join {
             next_sn,
             ext_qos,
             ext_shm,
+            ext_fec,
         }
     }
 }
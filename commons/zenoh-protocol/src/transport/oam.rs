@@ -21,6 +21,25 @@ pub mod flag {
     pub const Z: u8 = 1 << 7; // 0x80 Extensions    if Z==1 then an extension will follow
 }
 
+pub mod id {
+    use super::OamId;
+
+    /// Acknowledges the reliable messages received on a best-effort link using ARQ.
+    /// The body is a ZBuf carrying the next expected sequence number and a 64-bit mask
+    /// of the missing sequence numbers following it.
+    pub const OAM_ARQ_ACK: OamId = 0x0001;
+
+    /// Tags a multicast batch as a data shard of a FEC parity group.
+    /// The body is a u64 carrying the group number in the high bits and the index of the
+    /// shard in the group in the low 8 bits.
+    pub const OAM_FEC_SHARD: OamId = 0x0002;
+
+    /// Carries a parity shard of a FEC parity group on a multicast link.
+    /// The body is a ZBuf carrying the group number, the index of the parity shard, the
+    /// lengths of the data shards of the group and the parity shard itself.
+    pub const OAM_FEC_PARITY: OamId = 0x0003;
+}
+
 /// ```text
 /// Flags:
 /// - E |: Encoding     The encoding of the extension
@@ -86,6 +86,7 @@ pub struct OpenSyn {
     pub ext_mlink: Option<ext::MultiLinkSyn>,
     pub ext_lowlatency: Option<ext::LowLatency>,
     pub ext_compression: Option<ext::Compression>,
+    pub ext_metadata: Option<ext::Metadata>,
 }
 
 // Extensions
@@ -124,6 +125,10 @@ pub mod ext {
     /// # Compression extension
     /// Used to negotiate the use of compression on the link
     pub type Compression = zextunit!(0x6, false);
+
+    /// # Metadata extension
+    /// Used to advertise the metadata of the node, in JSON
+    pub type Metadata = zextzbuf!(0x7, false);
 }
 
 impl OpenSyn {
@@ -155,6 +160,7 @@ impl OpenSyn {
         let ext_mlink = rng.gen_bool(0.5).then_some(ZExtZBuf::rand());
         let ext_lowlatency = rng.gen_bool(0.5).then_some(ZExtUnit::rand());
         let ext_compression = rng.gen_bool(0.5).then_some(ZExtUnit::rand());
+        let ext_metadata = rng.gen_bool(0.5).then_some(ZExtZBuf::rand());
 
         Self {
             lease,
@@ -167,6 +173,7 @@ impl OpenSyn {
             ext_mlink,
             ext_lowlatency,
             ext_compression,
+            ext_metadata,
         }
     }
 }
@@ -182,6 +189,7 @@ pub struct OpenAck {
     pub ext_mlink: Option<ext::MultiLinkAck>,
     pub ext_lowlatency: Option<ext::LowLatency>,
     pub ext_compression: Option<ext::Compression>,
+    pub ext_metadata: Option<ext::Metadata>,
 }
 
 impl OpenAck {
@@ -209,6 +217,7 @@ impl OpenAck {
         let ext_mlink = rng.gen_bool(0.5).then_some(ZExtUnit::rand());
         let ext_lowlatency = rng.gen_bool(0.5).then_some(ZExtUnit::rand());
         let ext_compression = rng.gen_bool(0.5).then_some(ZExtUnit::rand());
+        let ext_metadata = rng.gen_bool(0.5).then_some(ZExtZBuf::rand());
 
         Self {
             lease,
@@ -220,6 +229,7 @@ impl OpenAck {
             ext_mlink,
             ext_lowlatency,
             ext_compression,
+            ext_metadata,
         }
     }
 }
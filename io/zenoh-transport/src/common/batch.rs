@@ -15,7 +15,7 @@ use std::num::NonZeroUsize;
 
 use zenoh_buffers::{
     buffer::Buffer,
-    reader::{DidntRead, HasReader},
+    reader::{DidntRead, HasReader, Reader},
     writer::{DidntWrite, HasWriter, Writer},
     BBuf, ZBufReader, ZSlice, ZSliceBuffer,
 };
@@ -24,12 +24,15 @@ use zenoh_codec::{
     RCodec, WCodec,
 };
 use zenoh_protocol::{
+    core::{Priority, Reliability},
     network::NetworkMessage,
-    transport::{fragment::FragmentHeader, frame::FrameHeader, BatchSize, TransportMessage},
+    transport::{
+        fragment::FragmentHeader, frame::FrameHeader, BatchSize, TransportMessage, TransportSn,
+    },
 };
 use zenoh_result::{zerror, ZResult};
 #[cfg(feature = "transport_compression")]
-use {std::sync::Arc, zenoh_protocol::common::imsg};
+use {super::compression::BatchCompression, std::sync::Arc, zenoh_protocol::common::imsg};
 
 const L_LEN: usize = (BatchSize::BITS / 8) as usize;
 const H_LEN: usize = BatchHeader::SIZE;
@@ -78,12 +81,13 @@ macro_rules! zsplit_mut {
 }
 
 // Batch config
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct BatchConfig {
     pub mtu: BatchSize,
     pub is_streamed: bool,
+    // The compression negotiated on the link, if any
     #[cfg(feature = "transport_compression")]
-    pub is_compression: bool,
+    pub compression: Option<BatchCompression>,
 }
 
 impl Default for BatchConfig {
@@ -92,7 +96,7 @@ impl Default for BatchConfig {
             mtu: BatchSize::MAX,
             is_streamed: false,
             #[cfg(feature = "transport_compression")]
-            is_compression: false,
+            compression: None,
         }
     }
 }
@@ -105,7 +109,7 @@ impl BatchConfig {
         }
         #[cfg(feature = "transport_compression")]
         {
-            self.is_compression
+            self.compression.is_some()
         }
     }
 
@@ -116,9 +120,26 @@ impl BatchConfig {
         }
         #[cfg(feature = "transport_compression")]
         {
-            self.is_compression
-                .then_some(BatchHeader::new(BatchHeader::COMPRESSION))
+            self.compression.as_ref().map(|c| {
+                BatchHeader::new(if c.is_enabled() {
+                    BatchHeader::COMPRESSION
+                } else {
+                    0
+                })
+            })
+        }
+    }
+
+    /// Returns the config of the batches of `priority`, which are sent uncompressed if the
+    /// compression policy excludes `priority`.
+    pub fn for_priority(&self, #[allow(unused_variables)] priority: Priority) -> Self {
+        #[allow(unused_mut)]
+        let mut config = self.clone();
+        #[cfg(feature = "transport_compression")]
+        {
+            config.compression = config.compression.map(|c| c.for_priority(priority));
         }
+        config
     }
 }
 
@@ -164,6 +185,30 @@ impl WBatchStats {
     }
 }
 
+/// The sequence numbers of the reliable frames and fragments serialized on a [`WBatch`], which are
+/// consecutive since a batch is filled with the messages of a single priority.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReliableSn {
+    pub priority: Priority,
+    pub first: TransportSn,
+    pub latest: TransportSn,
+}
+
+impl ReliableSn {
+    fn update(reliable: &mut Option<ReliableSn>, priority: Priority, sn: TransportSn) {
+        match reliable {
+            Some(r) => r.latest = sn,
+            None => {
+                *reliable = Some(ReliableSn {
+                    priority,
+                    first: sn,
+                    latest: sn,
+                })
+            }
+        }
+    }
+}
+
 #[repr(u8)]
 #[derive(Debug)]
 pub enum Finalize {
@@ -198,6 +243,8 @@ pub struct WBatch {
     pub codec: Zenoh080Batch,
     // It contains 1 byte as additional header, e.g. to signal the batch is compressed
     pub config: BatchConfig,
+    // The reliable frames and fragments serialized on this batch
+    pub reliable: Option<ReliableSn>,
     // Statistics related to this batch
     #[cfg(feature = "stats")]
     pub stats: WBatchStats,
@@ -209,6 +256,7 @@ impl WBatch {
             buffer: BBuf::with_capacity(config.mtu as usize),
             codec: Zenoh080Batch::new(),
             config,
+            reliable: None,
             #[cfg(feature = "stats")]
             stats: WBatchStats::default(),
         };
@@ -237,6 +285,7 @@ impl WBatch {
     pub fn clear(&mut self) {
         self.buffer.clear();
         self.codec.clear();
+        self.reliable = None;
         #[cfg(feature = "stats")]
         {
             self.stats.clear();
@@ -250,6 +299,26 @@ impl WBatch {
         self.buffer.as_slice()
     }
 
+    /// Decode the [`TransportMessage`]s serialized on the [`WBatch`], e.g. to send them on another link.
+    pub fn decode(&self) -> Result<Vec<TransportMessage>, DidntRead> {
+        let (_l, _h, p) = Self::split(self.buffer.as_slice(), &self.config);
+        let mut payload = ZSlice::from(p.to_vec());
+        let mut reader = payload.reader();
+        let mut codec = Zenoh080Batch::new();
+        let mut messages = vec![];
+        while reader.can_read() {
+            messages.push((&mut codec).read(&mut reader)?);
+        }
+        Ok(messages)
+    }
+
+    /// Append the messages serialized on `other`, e.g. to send them after another message.
+    pub fn append(&mut self, other: &WBatch) -> Result<(), DidntWrite> {
+        let (_l, _h, p) = Self::split(other.buffer.as_slice(), &other.config);
+        let mut writer = self.buffer.writer();
+        writer.write_exact(p)
+    }
+
     fn init(buffer: &mut BBuf, config: &BatchConfig) {
         let mut writer = buffer.writer();
         if config.is_streamed {
@@ -307,25 +376,35 @@ impl WBatch {
 
     #[cfg(feature = "transport_compression")]
     fn compress(&mut self, support: &mut BBuf) -> ZResult<Finalize> {
-        // Write the initial bytes for the batch
-        support.clear();
-        Self::init(support, &self.config);
-
-        // Compress the actual content
+        let compression = self
+            .config
+            .compression
+            .as_ref()
+            .ok_or_else(|| zerror!("Compression not configured"))?;
         let (_length, _header, payload) = Self::split(self.buffer.as_slice(), &self.config);
-        let mut writer = support.writer();
-        // SAFETY: assertion ensures `with_slot` precondition
-        unsafe {
-            writer.with_slot(writer.remaining(), |b| {
-                let len = lz4_flex::block::compress_into(payload, b).unwrap_or(0);
-                assert!(len <= b.len());
-                len
-            })
-        }
-        .map_err(|_| zerror!("Compression error"))?;
+
+        // Small batches are not worth compressing
+        let compressed = payload.len() >= compression.threshold() && {
+            // Write the initial bytes for the batch
+            support.clear();
+            Self::init(support, &self.config);
+
+            // Compress the actual content
+            let mut writer = support.writer();
+            // SAFETY: assertion ensures `with_slot` precondition
+            // The batch is sent uncompressed if the compression fails
+            unsafe {
+                writer.with_slot(writer.remaining(), |b| {
+                    let len = compression.compress(payload, b).unwrap_or(0);
+                    assert!(len <= b.len());
+                    len
+                })
+            }
+            .is_ok()
+        };
 
         // Verify whether the resulting compressed data is smaller than the initial input
-        if support.len() < self.buffer.len() {
+        if compressed && support.len() < self.buffer.len() {
             Ok(Finalize::Buffer)
         } else {
             // Keep the original uncompressed buffer and unset the compression flag from the header
@@ -366,7 +445,12 @@ impl Encode<(&NetworkMessage, &FrameHeader)> for &mut WBatch {
 
     fn encode(self, x: (&NetworkMessage, &FrameHeader)) -> Self::Output {
         let mut writer = self.buffer.writer();
-        self.codec.write(&mut writer, x)
+        self.codec.write(&mut writer, x)?;
+        let (_, f) = x;
+        if f.reliability == Reliability::Reliable {
+            ReliableSn::update(&mut self.reliable, f.ext_qos.priority(), f.sn);
+        }
+        Ok(())
     }
 }
 
@@ -375,7 +459,12 @@ impl Encode<(&mut ZBufReader<'_>, &mut FragmentHeader)> for &mut WBatch {
 
     fn encode(self, x: (&mut ZBufReader<'_>, &mut FragmentHeader)) -> Self::Output {
         let mut writer = self.buffer.writer();
-        self.codec.write(&mut writer, x)
+        let (r, f) = x;
+        let n = self.codec.write(&mut writer, (r, &mut *f))?;
+        if f.reliability == Reliability::Reliable {
+            ReliableSn::update(&mut self.reliable, f.ext_qos.priority(), f.sn);
+        }
+        Ok(n)
     }
 }
 
@@ -454,9 +543,13 @@ impl RBatch {
     where
         T: AsMut<[u8]> + ZSliceBuffer + 'static,
     {
+        let compression = self
+            .config
+            .compression
+            .as_ref()
+            .ok_or_else(|| zerror!("Compression not configured"))?;
         let mut into = (buff)();
-        let n = lz4_flex::block::decompress_into(payload, into.as_mut())
-            .map_err(|_| zerror!("Decompression error"))?;
+        let n = compression.decompress(payload, into.as_mut())?;
         let zslice = ZSlice::new(Arc::new(into), 0, n)
             .map_err(|_| zerror!("Invalid decompression buffer length"))?;
         Ok(zslice)
@@ -501,13 +594,27 @@ mod tests {
         core::{CongestionControl, Encoding, Priority, Reliability, WireExpr},
         network::{ext, Push},
         transport::{
+            fragment,
             frame::{self, FrameHeader},
-            Fragment, KeepAlive, TransportMessage,
+            Fragment, Frame, KeepAlive, TransportMessage,
         },
         zenoh::{PushBody, Put},
     };
 
     use super::*;
+    #[cfg(feature = "transport_compression")]
+    use crate::common::compression::{
+        CompressionAlgorithm, CompressionConfig, CompressionDictionary,
+    };
+
+    #[cfg(feature = "transport_compression")]
+    fn rand_compression() -> Option<BatchCompression> {
+        let mut rng = rand::thread_rng();
+        let algorithm =
+            CompressionAlgorithm::ALL[rng.gen_range(0..CompressionAlgorithm::ALL.len())];
+        rng.gen_bool(0.5)
+            .then(|| CompressionConfig::default().batch(algorithm).unwrap())
+    }
 
     #[test]
     fn rw_batch() {
@@ -524,16 +631,16 @@ mod tests {
                     mtu: BatchSize::MAX,
                     is_streamed: rng.gen_bool(0.5),
                     #[cfg(feature = "transport_compression")]
-                    is_compression: rng.gen_bool(0.5),
+                    compression: rand_compression(),
                 };
-                let mut wbatch = WBatch::new(config);
+                let mut wbatch = WBatch::new(config.clone());
                 wbatch.encode(&msg_in).unwrap();
                 println!("Encoded WBatch: {:?}", wbatch);
 
                 let mut buffer = zcondfeat!(
                     "transport_compression",
-                    config.is_compression.then_some(BBuf::with_capacity(
-                        lz4_flex::block::get_maximum_output_size(wbatch.as_slice().len()),
+                    config.compression.as_ref().map(|c| BBuf::with_capacity(
+                        c.max_compressed_len(wbatch.as_slice().len())
                     )),
                     None
                 );
@@ -545,7 +652,7 @@ mod tests {
                 };
                 println!("Finalized WBatch: {:02x?}", bytes);
 
-                let mut rbatch = RBatch::new(config, bytes.to_vec().into_boxed_slice());
+                let mut rbatch = RBatch::new(config.clone(), bytes.to_vec().into_boxed_slice());
                 println!("Decoded RBatch: {:?}", rbatch);
                 rbatch
                     .initialize(|| {
@@ -559,13 +666,52 @@ mod tests {
         }
     }
 
+    #[test]
+    fn decode_wbatch() {
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..1_000 {
+            // A fragment takes the remaining of a batch
+            let msgs_in: [TransportMessage; 2] = [Frame::rand().into(), {
+                let mut msg_in = Fragment::rand();
+                msg_in.payload = vec![0u8; rng.gen_range(8..1_024)].into();
+                msg_in.into()
+            }];
+            let config = BatchConfig {
+                mtu: BatchSize::MAX,
+                is_streamed: rng.gen_bool(0.5),
+                #[cfg(feature = "transport_compression")]
+                compression: rand_compression(),
+            };
+            let mut wbatch = WBatch::new(config.clone());
+            for msg_in in msgs_in.iter() {
+                wbatch.encode(msg_in).unwrap();
+            }
+
+            // The batch may have been finalized before failing to be sent
+            let mut buffer = zcondfeat!(
+                "transport_compression",
+                config
+                    .compression
+                    .as_ref()
+                    .map(|c| BBuf::with_capacity(c.max_compressed_len(wbatch.as_slice().len()))),
+                None
+            );
+            if rng.gen_bool(0.5) {
+                wbatch.finalize(buffer.as_mut()).unwrap();
+            }
+
+            assert_eq!(wbatch.decode().unwrap(), msgs_in);
+        }
+    }
+
     #[test]
     fn serialization_batch() {
         let config = BatchConfig {
             mtu: BatchSize::MAX,
             is_streamed: false,
             #[cfg(feature = "transport_compression")]
-            is_compression: false,
+            compression: None,
         };
         let mut batch = WBatch::new(config);
 
@@ -628,4 +774,141 @@ mod tests {
         assert_ne!(batch.len(), 0);
         nmsgs_in.push(nmsg.clone());
     }
+
+    #[test]
+    fn reliable_sn_batch() {
+        let config = BatchConfig {
+            mtu: BatchSize::MAX,
+            is_streamed: false,
+            #[cfg(feature = "transport_compression")]
+            compression: None,
+        };
+        let mut batch = WBatch::new(config);
+
+        let mut nmsg: NetworkMessage = Push {
+            wire_expr: WireExpr::empty(),
+            ext_qos: ext::QoSType::new(Priority::DataHigh, CongestionControl::Block, false),
+            ext_tstamp: None,
+            ext_nodeid: ext::NodeIdType::DEFAULT,
+            payload: PushBody::Put(Put {
+                timestamp: None,
+                encoding: Encoding::empty(),
+                ext_sinfo: None,
+                #[cfg(feature = "shared-memory")]
+                ext_shm: None,
+                ext_attachment: None,
+                ext_unknown: vec![],
+                payload: ZBuf::from(vec![0u8; 8]),
+            }),
+        }
+        .into();
+        let mut frame = FrameHeader {
+            reliability: Reliability::BestEffort,
+            sn: 7,
+            ext_qos: frame::ext::QoSType::new(Priority::DataHigh),
+        };
+
+        // Best-effort frames are not tracked
+        nmsg.reliability = frame.reliability;
+        batch.encode((&nmsg, &frame)).unwrap();
+        assert_eq!(batch.reliable, None);
+
+        frame.reliability = Reliability::Reliable;
+        nmsg.reliability = frame.reliability;
+        for sn in 3..6 {
+            frame.sn = sn;
+            batch.encode((&nmsg, &frame)).unwrap();
+        }
+        assert_eq!(
+            batch.reliable,
+            Some(ReliableSn {
+                priority: Priority::DataHigh,
+                first: 3,
+                latest: 5,
+            })
+        );
+
+        batch.clear();
+        assert_eq!(batch.reliable, None);
+
+        // A reliable fragment takes the remaining of the batch
+        let payload = ZBuf::from(vec![0u8; 2 * BatchSize::MAX as usize]);
+        let mut reader = payload.reader();
+        let mut fragment = FragmentHeader {
+            reliability: Reliability::Reliable,
+            more: true,
+            sn: 6,
+            ext_qos: fragment::ext::QoSType::new(Priority::DataHigh),
+        };
+        batch.encode((&mut reader, &mut fragment)).unwrap();
+        assert_eq!(
+            batch.reliable,
+            Some(ReliableSn {
+                priority: Priority::DataHigh,
+                first: 6,
+                latest: 6,
+            })
+        );
+    }
+
+    #[cfg(feature = "transport_compression_zstd")]
+    #[test]
+    fn compression_policy() {
+        let msg_in: TransportMessage = {
+            let mut msg_in = Fragment::rand();
+            msg_in.payload = b"zenoh ".repeat(128).into();
+            msg_in.into()
+        };
+        let dictionary = CompressionDictionary::new(b"zenoh ".repeat(16));
+        let compression = CompressionConfig {
+            algorithms: vec![CompressionAlgorithm::Zstd],
+            zstd_level: 9,
+            zstd_dictionary: Some(dictionary),
+            priorities: Some("4-7".parse().unwrap()),
+            protocols: None,
+            threshold: 512,
+        }
+        .batch(CompressionAlgorithm::Zstd)
+        .unwrap();
+        let config = BatchConfig {
+            mtu: BatchSize::MAX,
+            is_streamed: true,
+            compression: Some(compression),
+        };
+
+        // Returns the finalized bytes of a batch with the message and whether they are compressed
+        let send = |config: BatchConfig, msg: &TransportMessage| {
+            let mut wbatch = WBatch::new(config.clone());
+            wbatch.encode(msg).unwrap();
+            let mut buffer = BBuf::with_capacity(
+                config
+                    .compression
+                    .as_ref()
+                    .unwrap()
+                    .max_compressed_len(wbatch.as_slice().len()),
+            );
+            let bytes = match wbatch.finalize(Some(&mut buffer)).unwrap() {
+                Finalize::Batch => wbatch.as_slice().to_vec(),
+                Finalize::Buffer => buffer.as_slice().to_vec(),
+            };
+            let is_compressed = BatchHeader::new(bytes[L_LEN]).is_compression();
+
+            let mut rbatch = RBatch::new(config.clone(), bytes.into_boxed_slice());
+            rbatch
+                .initialize(|| zenoh_buffers::vec::uninit(config.mtu as usize).into_boxed_slice())
+                .unwrap();
+            let msg_out: TransportMessage = rbatch.decode().unwrap();
+            assert_eq!(msg, &msg_out);
+            is_compressed
+        };
+
+        assert!(send(config.for_priority(Priority::Data), &msg_in));
+        // The priority is not compressed by the policy
+        assert!(!send(config.for_priority(Priority::RealTime), &msg_in));
+        // The batch is smaller than the threshold
+        assert!(!send(
+            config.for_priority(Priority::Data),
+            &KeepAlive.into()
+        ));
+    }
 }
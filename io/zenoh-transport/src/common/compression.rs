@@ -0,0 +1,335 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake128,
+};
+use zenoh_config::{CompressionAlgorithmConf, CompressionUnicastConf};
+use zenoh_core::zlock;
+use zenoh_protocol::core::{Priority, PriorityRange};
+use zenoh_result::{bail, zerror, ZResult};
+
+/// The algorithms compressing the batches.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CompressionAlgorithm {
+    Lz4 = 0,
+    Zstd = 1,
+}
+
+impl CompressionAlgorithm {
+    /// The algorithms supported by this build, zstd requiring the `transport_compression_zstd`
+    /// feature.
+    pub const ALL: &'static [Self] = &[
+        Self::Lz4,
+        #[cfg(feature = "transport_compression_zstd")]
+        Self::Zstd,
+    ];
+
+    /// The bit identifying the algorithm in the negotiation of the compression.
+    pub(crate) const fn mask(&self) -> u8 {
+        1 << *self as u8
+    }
+}
+
+impl TryFrom<u8> for CompressionAlgorithm {
+    type Error = zenoh_result::Error;
+
+    fn try_from(id: u8) -> Result<Self, Self::Error> {
+        match id {
+            0 => Ok(Self::Lz4),
+            1 => Ok(Self::Zstd),
+            unknown => Err(zerror!("Unknown compression algorithm: {}", unknown).into()),
+        }
+    }
+}
+
+impl From<CompressionAlgorithmConf> for CompressionAlgorithm {
+    fn from(algorithm: CompressionAlgorithmConf) -> Self {
+        match algorithm {
+            CompressionAlgorithmConf::Lz4 => Self::Lz4,
+            CompressionAlgorithmConf::Zstd => Self::Zstd,
+        }
+    }
+}
+
+/// A zstd dictionary, which must be the same on both sides of a link.
+#[derive(Clone, PartialEq, Eq)]
+pub struct CompressionDictionary(Arc<[u8]>);
+
+impl CompressionDictionary {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self(bytes.into())
+    }
+
+    /// Identifies the dictionary in the negotiation of zstd.
+    pub(crate) fn fingerprint(&self) -> u32 {
+        let mut hasher = Shake128::default();
+        hasher.update(&self.0);
+        let mut fingerprint = [0u8; 4];
+        hasher.finalize_xof().read(&mut fingerprint);
+        u32::from_le_bytes(fingerprint)
+    }
+}
+
+impl fmt::Debug for CompressionDictionary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CompressionDictionary({} bytes)", self.0.len())
+    }
+}
+
+/// The compression settings of the unicast links.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompressionConfig {
+    // The algorithms in order of preference
+    pub algorithms: Vec<CompressionAlgorithm>,
+    pub zstd_level: i32,
+    pub zstd_dictionary: Option<CompressionDictionary>,
+    // The priorities whose batches are compressed, all of them if None
+    pub priorities: Option<PriorityRange>,
+    // The link protocols on which compression is used, all of them if None
+    pub protocols: Option<Vec<String>>,
+    // The minimum size of the batches to compress
+    pub threshold: usize,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            algorithms: vec![CompressionAlgorithm::Lz4],
+            zstd_level: 3,
+            zstd_dictionary: None,
+            priorities: None,
+            protocols: None,
+            threshold: 0,
+        }
+    }
+}
+
+impl CompressionConfig {
+    pub async fn from_config(config: &CompressionUnicastConf) -> ZResult<Self> {
+        const S: &str = "Compression - From config.";
+
+        let zstd_dictionary = match config.zstd().dictionary() {
+            Some(path) => {
+                let bytes = tokio::fs::read(path)
+                    .await
+                    .map_err(|e| zerror!("{S} Invalid zstd dictionary file: {}.", e))?;
+                Some(CompressionDictionary::new(bytes))
+            }
+            None => None,
+        };
+        let priorities = config
+            .priorities()
+            .as_deref()
+            .map(str::parse)
+            .transpose()
+            .map_err(|e| zerror!("{S} Invalid priorities: {}.", e))?;
+        let algorithms: Vec<CompressionAlgorithm> = config
+            .algorithms()
+            .iter()
+            .copied()
+            .map(Into::into)
+            .collect();
+        if let Some(a) = algorithms
+            .iter()
+            .find(|a| !CompressionAlgorithm::ALL.contains(a))
+        {
+            bail!(
+                "{S} Unsupported algorithm {:?}: enable the transport_compression_zstd feature.",
+                a
+            );
+        }
+
+        Ok(Self {
+            algorithms,
+            zstd_level: *config.zstd().level(),
+            zstd_dictionary,
+            priorities,
+            protocols: config.protocols().clone(),
+            threshold: *config.threshold(),
+        })
+    }
+
+    /// Returns `true` if compression is used on the links of `protocol`.
+    pub(crate) fn is_used_on(&self, protocol: &str) -> bool {
+        self.protocols
+            .as_ref()
+            .map_or(true, |protocols| protocols.iter().any(|p| p == protocol))
+    }
+
+    /// Returns the fingerprint of the zstd dictionary, 0 if there is none.
+    pub(crate) fn fingerprint(&self) -> u32 {
+        self.zstd_dictionary
+            .as_ref()
+            .map_or(0, CompressionDictionary::fingerprint)
+    }
+
+    /// Creates the compression of the batches of a link with the negotiated `algorithm`.
+    pub(crate) fn batch(&self, algorithm: CompressionAlgorithm) -> ZResult<BatchCompression> {
+        let codec = match algorithm {
+            CompressionAlgorithm::Lz4 => Codec::Lz4,
+            #[cfg(feature = "transport_compression_zstd")]
+            CompressionAlgorithm::Zstd => {
+                let dictionary = self
+                    .zstd_dictionary
+                    .as_ref()
+                    .map_or(&[][..], |d| d.0.as_ref());
+                Codec::Zstd {
+                    compressor: Mutex::new(zstd::bulk::Compressor::with_dictionary(
+                        self.zstd_level,
+                        dictionary,
+                    )?),
+                    decompressor: Mutex::new(zstd::bulk::Decompressor::with_dictionary(
+                        dictionary,
+                    )?),
+                }
+            }
+            #[cfg(not(feature = "transport_compression_zstd"))]
+            CompressionAlgorithm::Zstd => {
+                bail!("zstd compression requires the transport_compression_zstd feature")
+            }
+        };
+        Ok(BatchCompression {
+            enabled: true,
+            inner: Arc::new(BatchCompressionInner {
+                algorithm,
+                priorities: self.priorities.clone(),
+                threshold: self.threshold,
+                codec,
+            }),
+        })
+    }
+}
+
+enum Codec {
+    Lz4,
+    #[cfg(feature = "transport_compression_zstd")]
+    Zstd {
+        compressor: Mutex<zstd::bulk::Compressor<'static>>,
+        decompressor: Mutex<zstd::bulk::Decompressor<'static>>,
+    },
+}
+
+struct BatchCompressionInner {
+    algorithm: CompressionAlgorithm,
+    priorities: Option<PriorityRange>,
+    threshold: usize,
+    codec: Codec,
+}
+
+/// The compression of the batches of a link.
+#[derive(Clone)]
+pub struct BatchCompression {
+    // The batches are sent uncompressed if disabled, e.g. for the priorities excluded by the policy
+    enabled: bool,
+    inner: Arc<BatchCompressionInner>,
+}
+
+impl BatchCompression {
+    /// The LZ4 compression of all the batches, as used on multicast links.
+    pub(crate) fn lz4() -> Self {
+        Self {
+            enabled: true,
+            inner: Arc::new(BatchCompressionInner {
+                algorithm: CompressionAlgorithm::Lz4,
+                priorities: None,
+                threshold: 0,
+                codec: Codec::Lz4,
+            }),
+        }
+    }
+
+    pub fn algorithm(&self) -> CompressionAlgorithm {
+        self.inner.algorithm
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(crate) fn threshold(&self) -> usize {
+        self.inner.threshold
+    }
+
+    /// Returns the compression of the batches of `priority`.
+    pub(crate) fn for_priority(&self, priority: Priority) -> Self {
+        Self {
+            enabled: self.enabled
+                && self
+                    .inner
+                    .priorities
+                    .as_ref()
+                    .map_or(true, |r| r.contains(&priority)),
+            inner: self.inner.clone(),
+        }
+    }
+
+    /// The maximum size of `len` bytes once compressed.
+    pub(crate) fn max_compressed_len(&self, len: usize) -> usize {
+        match self.inner.codec {
+            Codec::Lz4 => lz4_flex::block::get_maximum_output_size(len),
+            #[cfg(feature = "transport_compression_zstd")]
+            Codec::Zstd { .. } => zstd::zstd_safe::compress_bound(len),
+        }
+    }
+
+    /// Compresses `input` into `output` and returns the compressed size.
+    pub(crate) fn compress(&self, input: &[u8], output: &mut [u8]) -> ZResult<usize> {
+        match &self.inner.codec {
+            Codec::Lz4 => lz4_flex::block::compress_into(input, output)
+                .map_err(|e| zerror!("LZ4 compression error: {}", e).into()),
+            #[cfg(feature = "transport_compression_zstd")]
+            Codec::Zstd { compressor, .. } => zlock!(compressor)
+                .compress_to_buffer(input, output)
+                .map_err(|e| zerror!("zstd compression error: {}", e).into()),
+        }
+    }
+
+    /// Decompresses `input` into `output` and returns the decompressed size.
+    pub(crate) fn decompress(&self, input: &[u8], output: &mut [u8]) -> ZResult<usize> {
+        match &self.inner.codec {
+            Codec::Lz4 => lz4_flex::block::decompress_into(input, output)
+                .map_err(|e| zerror!("LZ4 decompression error: {}", e).into()),
+            #[cfg(feature = "transport_compression_zstd")]
+            Codec::Zstd { decompressor, .. } => zlock!(decompressor)
+                .decompress_to_buffer(input, output)
+                .map_err(|e| zerror!("zstd decompression error: {}", e).into()),
+        }
+    }
+}
+
+impl PartialEq for BatchCompression {
+    fn eq(&self, other: &Self) -> bool {
+        self.enabled == other.enabled && self.algorithm() == other.algorithm()
+    }
+}
+
+impl Eq for BatchCompression {}
+
+impl fmt::Debug for BatchCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchCompression")
+            .field("algorithm", &self.inner.algorithm)
+            .field("enabled", &self.enabled)
+            .field("priorities", &self.inner.priorities)
+            .field("threshold", &self.inner.threshold)
+            .finish()
+    }
+}
@@ -14,7 +14,7 @@
 use std::{
     ops::Add,
     sync::{
-        atomic::{AtomicBool, AtomicU32, Ordering},
+        atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
         Arc, Mutex, MutexGuard,
     },
     time::{Duration, Instant},
@@ -46,6 +46,8 @@ use super::{
     priority::{TransportChannelTx, TransportPriorityTx},
 };
 use crate::common::batch::BatchConfig;
+#[cfg(feature = "stats")]
+use crate::stats::TransportStats;
 
 const RBLEN: usize = QueueSizeConf::MAX;
 
@@ -85,8 +87,10 @@ struct AtomicBackoff {
 // Inner structure to link the initial stage with the final stage of the pipeline
 struct StageInOut {
     n_out_w: Notifier,
-    s_out_w: RingBufferWriter<WBatch, RBLEN>,
+    // Each batch is moved out along with the reason it was moved out
+    s_out_w: RingBufferWriter<(WBatch, Flush), RBLEN>,
     atomic_backoff: Arc<AtomicBackoff>,
+    queued: Arc<AtomicUsize>,
 }
 
 impl StageInOut {
@@ -99,11 +103,22 @@ impl StageInOut {
     }
 
     #[inline]
-    fn move_batch(&mut self, batch: WBatch) {
-        let _ = self.s_out_w.push(batch);
+    fn push(&mut self, batch: WBatch, flush: Flush) {
+        let _ = self.s_out_w.push((batch, flush));
+        self.queued.fetch_add(1, Ordering::Relaxed);
         self.atomic_backoff.bytes.store(0, Ordering::Relaxed);
         let _ = self.n_out_w.notify();
     }
+
+    #[inline]
+    fn move_batch(&mut self, batch: WBatch) {
+        self.push(batch, Flush::Idle);
+    }
+
+    #[inline]
+    fn move_full_batch(&mut self, batch: WBatch) {
+        self.push(batch, Flush::Full);
+    }
 }
 
 // Inner structure containing mutexes for current serialization batch and SNs
@@ -291,7 +306,7 @@ impl StageIn {
 
         if !batch.is_empty() {
             // Move out existing batch
-            self.s_out.move_batch(batch);
+            self.s_out.move_full_batch(batch);
             batch = zgetbatch_rets!(tch.sn.set(sn).unwrap());
         }
 
@@ -410,7 +425,7 @@ impl StageIn {
             Ok(_) => zretok!(batch),
             Err(_) => {
                 if !batch.is_empty() {
-                    self.s_out.move_batch(batch);
+                    self.s_out.move_full_batch(batch);
                     batch = zgetbatch_rets!();
                 }
             }
@@ -429,13 +444,149 @@ enum Pull {
     Backoff(MicroSeconds),
 }
 
+// The reason a batch has been pulled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flush {
+    // The batch was full
+    Full,
+    // No more messages were being written on the batch, or it was sent right away (e.g. express
+    // messages or fragments)
+    Idle,
+    // The batching time limit expired while messages were still being written on the batch
+    Expired,
+}
+
+// The load of the link the batches are transmitted on, measured by the consumer of the pipeline
+// from the time each batch takes to be transmitted, between its pull and its refill.
+struct LinkLoad {
+    // The time the batch being transmitted has been pulled at
+    pulled: Option<Instant>,
+    // The time the last transmitted batch has been refilled at
+    refilled: Instant,
+    // The moving averages of the size of the transmitted batches, of the time spent transmitting
+    // each of them and of the time elapsed between their refills
+    bytes: f64,
+    transmitting: f64,
+    elapsed: f64,
+}
+
+impl LinkLoad {
+    // The weight of the last transmitted batch in the moving averages
+    const WEIGHT: f64 = 0.125;
+
+    fn new() -> Self {
+        Self {
+            pulled: None,
+            refilled: Instant::now(),
+            bytes: 0.0,
+            transmitting: 0.0,
+            elapsed: 0.0,
+        }
+    }
+
+    fn pulled(&mut self, now: Instant) {
+        self.pulled = Some(now);
+    }
+
+    // Returns the time spent transmitting the batch of `bytes`
+    fn refilled(&mut self, bytes: BatchSize, now: Instant) -> Duration {
+        let Some(pulled) = self.pulled.take() else {
+            return Duration::ZERO;
+        };
+        let transmitting = now.saturating_duration_since(pulled);
+        let elapsed = now.saturating_duration_since(self.refilled);
+        self.refilled = now;
+
+        let average =
+            |average: &mut f64, sample: f64| *average += Self::WEIGHT * (sample - *average);
+        average(&mut self.bytes, bytes as f64);
+        average(&mut self.transmitting, transmitting.as_micros() as f64);
+        average(&mut self.elapsed, elapsed.as_micros() as f64);
+        transmitting
+    }
+
+    // The throughput of the link while transmitting, in bytes per microsecond
+    fn throughput(&self) -> f64 {
+        self.bytes / self.transmitting.max(1.0)
+    }
+
+    // The rate the batches are transmitted at, in bytes per microsecond
+    fn rate(&self) -> f64 {
+        self.bytes / self.elapsed.max(1.0)
+    }
+
+    // The share of the throughput of the link used to transmit the batches, between 0 and 1
+    fn utilization(&self) -> f64 {
+        if self.bytes == 0.0 {
+            return 0.0;
+        }
+        (self.rate() / self.throughput()).min(1.0)
+    }
+}
+
+// The batching time limit of a priority queue adapted to the load of the link and bounded by the
+// latency budget of the priority and the round-trip time of the link.
+//
+// While the link keeps up with the batches, the messages are sent right away. Once the link is
+// busy transmitting most of the time, the messages are retained longer for fewer and larger
+// batches to be transmitted, as long as the batches are pulled before filling up.
+#[derive(Clone)]
+struct AdaptiveBackoff {
+    budget: MicroSeconds,
+    limit: MicroSeconds,
+}
+
+impl AdaptiveBackoff {
+    // The smallest non-zero batching time limit
+    const STEP: MicroSeconds = 10;
+    // The utilization of the link from which it is considered busy
+    const BUSY: f64 = 0.5;
+
+    // Retaining the messages longer than the round-trip time of the link would more than double
+    // their latency: the time limit is bounded by the RTT as well, if it is known.
+    fn new(budget: Duration, rtt: Option<Duration>) -> Self {
+        let budget = rtt.map_or(budget, |rtt| budget.min(rtt));
+        Self {
+            budget: budget.as_micros().min(MicroSeconds::MAX as u128) as MicroSeconds,
+            limit: 0,
+        }
+    }
+
+    // Returns `true` if the time limit has changed
+    fn adapt(&mut self, flush: Flush, utilization: f64) -> bool {
+        let limit = if utilization < Self::BUSY {
+            // The link keeps up with the batches: send the messages sooner
+            if self.limit / 2 < Self::STEP {
+                0
+            } else {
+                self.limit / 2
+            }
+        } else {
+            match flush {
+                // The link is busy and the batches are sent before filling up: retain the
+                // messages longer
+                Flush::Idle | Flush::Expired => self
+                    .limit
+                    .saturating_mul(2)
+                    .max(Self::STEP)
+                    .min(self.budget),
+                // The batches already fill up
+                Flush::Full => self.limit,
+            }
+        };
+        let changed = limit != self.limit;
+        self.limit = limit;
+        changed
+    }
+}
+
 // Inner structure to keep track and signal backoff operations
 #[derive(Clone)]
 struct Backoff {
     threshold: Duration,
     last_bytes: BatchSize,
     atomic: Arc<AtomicBackoff>,
-    // active: bool,
+    adaptive: Option<AdaptiveBackoff>,
 }
 
 impl Backoff {
@@ -444,31 +595,63 @@ impl Backoff {
             threshold,
             last_bytes: 0,
             atomic,
-            // active: false,
+            adaptive: None,
+        }
+    }
+
+    fn adaptive(budget: Duration, rtt: Option<Duration>, atomic: Arc<AtomicBackoff>) -> Self {
+        Self {
+            threshold: budget,
+            last_bytes: 0,
+            atomic,
+            adaptive: Some(AdaptiveBackoff::new(budget, rtt)),
+        }
+    }
+
+    #[inline]
+    fn threshold(&self) -> MicroSeconds {
+        match self.adaptive.as_ref() {
+            Some(adaptive) => adaptive.limit,
+            None => self.threshold.as_micros() as MicroSeconds,
         }
     }
 }
 
 // Inner structure to link the final stage with the initial stage of the pipeline
 struct StageOutIn {
-    s_out_r: RingBufferReader<WBatch, RBLEN>,
+    s_out_r: RingBufferReader<(WBatch, Flush), RBLEN>,
     current: Arc<Mutex<Option<WBatch>>>,
     backoff: Backoff,
+    queued: Arc<AtomicUsize>,
+    #[cfg(feature = "stats")]
+    stats: Arc<TransportStats>,
 }
 
 impl StageOutIn {
     #[inline]
-    fn try_pull(&mut self) -> Pull {
-        if let Some(batch) = self.s_out_r.pull() {
+    fn pull_out(&mut self) -> Option<(WBatch, Flush)> {
+        let out = self.s_out_r.pull()?;
+        self.queued.fetch_sub(1, Ordering::Relaxed);
+        Some(out)
+    }
+
+    #[inline]
+    fn try_pull(&mut self, utilization: f64) -> Pull {
+        if let Some((batch, flush)) = self.pull_out() {
+            self.flushed(flush, utilization);
             return Pull::Some(batch);
         }
 
-        self.try_pull_deep()
+        self.try_pull_deep(utilization)
     }
 
-    fn try_pull_deep(&mut self) -> Pull {
-        // Verify first backoff is not active
-        let mut pull = !self.backoff.atomic.active.load(Ordering::Relaxed);
+    fn try_pull_deep(&mut self, utilization: f64) -> Pull {
+        let threshold = self.backoff.threshold();
+        let active = self.backoff.atomic.active.load(Ordering::Relaxed);
+
+        // Verify first backoff is not active. With adaptive batching, a zero time limit means
+        // the load is light enough to not retain the messages at all.
+        let mut pull = !active && (self.backoff.adaptive.is_none() || threshold == 0);
 
         // If backoff is active, verify the current number of bytes is equal to the old number
         // of bytes seen in the previous backoff iteration
@@ -477,15 +660,15 @@ impl StageOutIn {
             let old_bytes = self.backoff.last_bytes;
             self.backoff.last_bytes = new_bytes;
 
-            pull = new_bytes == old_bytes;
+            pull = active && new_bytes == old_bytes;
         }
+        let flush = if pull { Flush::Idle } else { Flush::Expired };
 
         // Verify that we have not been doing backoff for too long
         let mut backoff = 0;
         if !pull {
             let diff = LOCAL_EPOCH.elapsed().as_micros() as MicroSeconds
                 - self.backoff.atomic.first_write.load(Ordering::Relaxed);
-            let threshold = self.backoff.threshold.as_micros() as MicroSeconds;
 
             if diff >= threshold {
                 pull = true;
@@ -496,24 +679,33 @@ impl StageOutIn {
 
         if pull {
             // It seems no new bytes have been written on the batch, try to pull
-            if let Ok(mut g) = self.current.try_lock() {
-                self.backoff.atomic.active.store(false, Ordering::Relaxed);
+            let pulled = match self.current.try_lock() {
+                Ok(mut g) => {
+                    self.backoff.atomic.active.store(false, Ordering::Relaxed);
+
+                    // First try to pull from stage OUT to make sure we are not in the case
+                    // where new_bytes == old_bytes are because of two identical serializations
+                    match self.s_out_r.pull() {
+                        Some((batch, flush)) => {
+                            self.queued.fetch_sub(1, Ordering::Relaxed);
+                            Some((Some(batch), flush))
+                        }
+                        // An incomplete (non-empty) batch may be available in the state IN pipeline.
+                        None => Some((g.take(), flush)),
+                    }
+                }
+                Err(_) => None,
+            };
 
-                // First try to pull from stage OUT to make sure we are not in the case
-                // where new_bytes == old_bytes are because of two identical serializations
-                if let Some(batch) = self.s_out_r.pull() {
+            match pulled {
+                Some((Some(batch), flush)) => {
+                    self.flushed(flush, utilization);
                     return Pull::Some(batch);
                 }
-
-                // An incomplete (non-empty) batch may be available in the state IN pipeline.
-                match g.take() {
-                    Some(batch) => {
-                        return Pull::Some(batch);
-                    }
-                    None => {
-                        return Pull::None;
-                    }
+                Some((None, _)) => {
+                    return Pull::None;
                 }
+                None => {}
             }
         }
 
@@ -523,6 +715,34 @@ impl StageOutIn {
         // Do backoff
         Pull::Backoff(backoff)
     }
+
+    // Adapts the batching time limit to the reason a batch has been pulled and to the utilization
+    // of the link
+    fn flushed(&mut self, flush: Flush, utilization: f64) {
+        #[cfg(feature = "stats")]
+        match flush {
+            Flush::Full => self.stats.inc_tx_pipeline_full_batches(1),
+            Flush::Idle => self.stats.inc_tx_pipeline_idle_batches(1),
+            Flush::Expired => self.stats.inc_tx_pipeline_expired_batches(1),
+        }
+
+        if let Some(adaptive) = self.backoff.adaptive.as_mut() {
+            let limit = adaptive.limit;
+            if adaptive.adapt(flush, utilization) {
+                tracing::trace!(
+                    "Batching time limit adapted to {}us (link utilization: {:.2})",
+                    adaptive.limit,
+                    utilization
+                );
+                #[cfg(feature = "stats")]
+                if adaptive.limit > limit {
+                    self.stats.inc_tx_pipeline_limit_increases(1);
+                } else {
+                    self.stats.inc_tx_pipeline_limit_decreases(1);
+                }
+            }
+        }
+    }
 }
 
 struct StageOutRefill {
@@ -544,8 +764,8 @@ struct StageOut {
 
 impl StageOut {
     #[inline]
-    fn try_pull(&mut self) -> Pull {
-        self.s_in.try_pull()
+    fn try_pull(&mut self, utilization: f64) -> Pull {
+        self.s_in.try_pull(utilization)
     }
 
     #[inline]
@@ -556,7 +776,7 @@ impl StageOut {
     fn drain(&mut self, guard: &mut MutexGuard<'_, Option<WBatch>>) -> Vec<WBatch> {
         let mut batches = vec![];
         // Empty the ring buffer
-        while let Some(batch) = self.s_in.s_out_r.pull() {
+        while let Some((batch, _)) = self.s_in.pull_out() {
             batches.push(batch);
         }
         // Take the current batch
@@ -575,6 +795,9 @@ pub(crate) struct TransmissionPipelineConf {
     pub(crate) wait_before_close: Duration,
     pub(crate) batching_enabled: bool,
     pub(crate) batching_time_limit: Duration,
+    pub(crate) batching_adaptive: bool,
+    pub(crate) latency_budget: [Duration; Priority::NUM],
+    pub(crate) rtt: Option<Duration>,
 }
 
 // A 2-stage transmission pipeline
@@ -584,30 +807,43 @@ impl TransmissionPipeline {
     pub(crate) fn make(
         config: TransmissionPipelineConf,
         priority: &[TransportPriorityTx],
+        #[cfg(feature = "stats")] stats: Arc<TransportStats>,
     ) -> (TransmissionPipelineProducer, TransmissionPipelineConsumer) {
         let mut stage_in = vec![];
         let mut stage_out = vec![];
 
         let default_queue_size = [config.queue_size[Priority::DEFAULT as usize]];
-        let size_iter = if priority.len() == 1 {
-            default_queue_size.iter()
+        let default_latency_budget = [config.latency_budget[Priority::DEFAULT as usize]];
+        let (size_iter, budget_iter) = if priority.len() == 1 {
+            (default_queue_size.iter(), default_latency_budget.iter())
         } else {
-            config.queue_size.iter()
+            (config.queue_size.iter(), config.latency_budget.iter())
         };
 
         // Create the channel for notifying that new batches are in the out ring buffer
         // This is a MPSC channel
         let (n_out_w, n_out_r) = event::new();
+        // The number of batches waiting for transmission in all the priority queues
+        let queued = Arc::new(AtomicUsize::new(0));
 
-        for (prio, num) in size_iter.enumerate() {
+        for (prio, (num, budget)) in size_iter.zip(budget_iter).enumerate() {
             assert!(*num != 0 && *num <= RBLEN);
 
+            // The messages of all the priorities share the queue of the default priority without QoS
+            let batch_config = if priority.len() == 1 {
+                config.batch.for_priority(Priority::DEFAULT)
+            } else {
+                config
+                    .batch
+                    .for_priority(Priority::try_from(prio as u8).unwrap_or_default())
+            };
+
             // Create the refill ring buffer
             // This is a SPSC ring buffer
             let (mut s_ref_w, s_ref_r) = RingBuffer::<WBatch, RBLEN>::init();
             // Fill the refill ring buffer with batches
             for _ in 0..*num {
-                let batch = WBatch::new(config.batch);
+                let batch = WBatch::new(batch_config.clone());
                 assert!(s_ref_w.push(batch).is_none());
             }
             // Create the channel for notifying that new batches are in the refill ring buffer
@@ -616,7 +852,7 @@ impl TransmissionPipeline {
 
             // Create the refill ring buffer
             // This is a SPSC ring buffer
-            let (s_out_w, s_out_r) = RingBuffer::<WBatch, RBLEN>::init();
+            let (s_out_w, s_out_r) = RingBuffer::<(WBatch, Flush), RBLEN>::init();
             let current = Arc::new(Mutex::new(None));
             let bytes = Arc::new(AtomicBackoff {
                 active: CachePadded::new(AtomicBool::new(false)),
@@ -625,6 +861,11 @@ impl TransmissionPipeline {
                     LOCAL_EPOCH.elapsed().as_micros() as MicroSeconds,
                 )),
             });
+            let backoff = if config.batching_adaptive {
+                Backoff::adaptive(*budget, config.rtt, bytes.clone())
+            } else {
+                Backoff::new(config.batching_time_limit, bytes.clone())
+            };
 
             stage_in.push(Mutex::new(StageIn {
                 s_ref: StageInRefill { n_ref_r, s_ref_r },
@@ -632,6 +873,7 @@ impl TransmissionPipeline {
                     n_out_w: n_out_w.clone(),
                     s_out_w,
                     atomic_backoff: bytes.clone(),
+                    queued: queued.clone(),
                 },
                 mutex: StageInMutex {
                     current: current.clone(),
@@ -646,7 +888,10 @@ impl TransmissionPipeline {
                 s_in: StageOutIn {
                     s_out_r,
                     current,
-                    backoff: Backoff::new(config.batching_time_limit, bytes),
+                    backoff,
+                    queued: queued.clone(),
+                    #[cfg(feature = "stats")]
+                    stats: stats.clone(),
                 },
                 s_ref: StageOutRefill { n_ref_w, s_ref_w },
             });
@@ -658,11 +903,15 @@ impl TransmissionPipeline {
             active: active.clone(),
             wait_before_drop: config.wait_before_drop,
             wait_before_close: config.wait_before_close,
+            queued,
         };
         let consumer = TransmissionPipelineConsumer {
             stage_out: stage_out.into_boxed_slice(),
             n_out_r,
             active,
+            load: LinkLoad::new(),
+            #[cfg(feature = "stats")]
+            stats,
         };
 
         (producer, consumer)
@@ -676,6 +925,8 @@ pub(crate) struct TransmissionPipelineProducer {
     active: Arc<AtomicBool>,
     wait_before_drop: Duration,
     wait_before_close: Duration,
+    #[allow(dead_code)] // When feature "transport_multilink" is not enabled
+    queued: Arc<AtomicUsize>,
 }
 
 impl TransmissionPipelineProducer {
@@ -713,6 +964,13 @@ impl TransmissionPipelineProducer {
         queue.push_transport_message(msg)
     }
 
+    /// Returns the number of full batches waiting for transmission.
+    #[allow(dead_code)] // When feature "transport_multilink" is not enabled
+    #[inline]
+    pub(crate) fn queued(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
     pub(crate) fn disable(&self) {
         self.active.store(false, Ordering::Relaxed);
 
@@ -733,16 +991,22 @@ pub(crate) struct TransmissionPipelineConsumer {
     stage_out: Box<[StageOut]>,
     n_out_r: Waiter,
     active: Arc<AtomicBool>,
+    // The load of the link, measured to adapt the batching time limits
+    load: LinkLoad,
+    #[cfg(feature = "stats")]
+    stats: Arc<TransportStats>,
 }
 
 impl TransmissionPipelineConsumer {
     pub(crate) async fn pull(&mut self) -> Option<(WBatch, usize)> {
         while self.active.load(Ordering::Relaxed) {
             let mut backoff = MicroSeconds::MAX;
+            let utilization = self.load.utilization();
             // Calculate the backoff maximum
             for (prio, queue) in self.stage_out.iter_mut().enumerate() {
-                match queue.try_pull() {
+                match queue.try_pull(utilization) {
                     Pull::Some(batch) => {
+                        self.load.pulled(Instant::now());
                         return Some((batch, prio));
                     }
                     Pull::Backoff(deadline) => {
@@ -783,7 +1047,13 @@ impl TransmissionPipelineConsumer {
         None
     }
 
+    /// Reinserts the `batch` pulled from the queue of `priority` once it has been transmitted.
     pub(crate) fn refill(&mut self, batch: WBatch, priority: usize) {
+        #[allow(unused_variables)] // Used when stats feature is enabled
+        let transmitting = self.load.refilled(batch.len(), Instant::now());
+        #[cfg(feature = "stats")]
+        self.stats
+            .inc_tx_pipeline_transmission_time(transmitting.as_micros() as usize);
         self.stage_out[priority].refill(batch);
     }
 
@@ -838,36 +1108,46 @@ mod tests {
     use zenoh_result::ZResult;
 
     use super::*;
+    #[cfg(feature = "transport_compression")]
+    use crate::common::compression::BatchCompression;
 
     const SLEEP: Duration = Duration::from_millis(100);
     const TIMEOUT: Duration = Duration::from_secs(60);
 
-    const CONFIG_STREAMED: TransmissionPipelineConf = TransmissionPipelineConf {
-        batch: BatchConfig {
-            mtu: BatchSize::MAX,
-            is_streamed: true,
-            #[cfg(feature = "transport_compression")]
-            is_compression: true,
-        },
-        queue_size: [1; Priority::NUM],
-        batching_enabled: true,
-        wait_before_drop: Duration::from_millis(1),
-        wait_before_close: Duration::from_secs(5),
-        batching_time_limit: Duration::from_micros(1),
-    };
+    fn config_streamed() -> TransmissionPipelineConf {
+        TransmissionPipelineConf {
+            batch: BatchConfig {
+                mtu: BatchSize::MAX,
+                is_streamed: true,
+                #[cfg(feature = "transport_compression")]
+                compression: Some(BatchCompression::lz4()),
+            },
+            queue_size: [1; Priority::NUM],
+            batching_enabled: true,
+            wait_before_drop: Duration::from_millis(1),
+            wait_before_close: Duration::from_secs(5),
+            batching_time_limit: Duration::from_micros(1),
+            batching_adaptive: false,
+            latency_budget: [Duration::ZERO; Priority::NUM],
+            rtt: None,
+        }
+    }
 
     const CONFIG_NOT_STREAMED: TransmissionPipelineConf = TransmissionPipelineConf {
         batch: BatchConfig {
             mtu: BatchSize::MAX,
             is_streamed: false,
             #[cfg(feature = "transport_compression")]
-            is_compression: false,
+            compression: None,
         },
         queue_size: [1; Priority::NUM],
         batching_enabled: true,
         wait_before_drop: Duration::from_millis(1),
         wait_before_close: Duration::from_secs(5),
         batching_time_limit: Duration::from_micros(1),
+        batching_adaptive: false,
+        latency_budget: [Duration::ZERO; Priority::NUM],
+        rtt: None,
     };
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
@@ -974,8 +1254,12 @@ mod tests {
             // Compute the number of messages to send
             let num_msg = max_msgs.min(bytes / ps);
 
-            let (producer, consumer) =
-                TransmissionPipeline::make(CONFIG_NOT_STREAMED, priorities.as_slice());
+            let (producer, consumer) = TransmissionPipeline::make(
+                CONFIG_NOT_STREAMED,
+                priorities.as_slice(),
+                #[cfg(feature = "stats")]
+                Arc::new(TransportStats::default()),
+            );
 
             let t_c = task::spawn(async move {
                 consume(consumer, num_msg).await;
@@ -999,7 +1283,7 @@ mod tests {
             // Make sure to put only one message per batch: set the payload size
             // to half of the batch in such a way the serialized zenoh message
             // will be larger then half of the batch size (header + payload).
-            let payload_size = (CONFIG_STREAMED.batch.mtu / 2) as usize;
+            let payload_size = (config_streamed().batch.mtu / 2) as usize;
 
             // Send reliable messages
             let key = "test".into();
@@ -1025,7 +1309,7 @@ mod tests {
 
             // The last push should block since there shouldn't any more batches
             // available for serialization.
-            let num_msg = 1 + CONFIG_STREAMED.queue_size[0];
+            let num_msg = 1 + config_streamed().queue_size[0];
             for i in 0..num_msg {
                 println!(
                     "Pipeline Blocking [>>>]: ({id}) Scheduling message #{i} with payload size of {payload_size} bytes"
@@ -1043,8 +1327,12 @@ mod tests {
         // Pipeline
         let tct = TransportPriorityTx::make(Bits::from(TransportSn::MAX))?;
         let priorities = vec![tct];
-        let (producer, mut consumer) =
-            TransmissionPipeline::make(CONFIG_NOT_STREAMED, priorities.as_slice());
+        let (producer, mut consumer) = TransmissionPipeline::make(
+            CONFIG_NOT_STREAMED,
+            priorities.as_slice(),
+            #[cfg(feature = "stats")]
+            Arc::new(TransportStats::default()),
+        );
 
         let counter = Arc::new(AtomicUsize::new(0));
 
@@ -1062,11 +1350,11 @@ mod tests {
         // Wait to have sent enough messages and to have blocked
         println!(
             "Pipeline Blocking [---]: waiting to have {} messages being scheduled",
-            CONFIG_STREAMED.queue_size[Priority::MAX as usize]
+            config_streamed().queue_size[Priority::MAX as usize]
         );
         let check = async {
             while counter.load(Ordering::Acquire)
-                < CONFIG_STREAMED.queue_size[Priority::MAX as usize]
+                < config_streamed().queue_size[Priority::MAX as usize]
             {
                 tokio::time::sleep(SLEEP).await;
             }
@@ -1093,14 +1381,174 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn tx_pipeline_link_load() {
+        let mut load = LinkLoad::new();
+        assert_eq!(load.utilization(), 0.0);
+
+        // The link transmits 1 KB per millisecond, continuously
+        let mut now = load.refilled;
+        for _ in 0..32 {
+            load.pulled(now);
+            now += Duration::from_millis(1);
+            assert_eq!(load.refilled(1_024, now), Duration::from_millis(1));
+        }
+        assert!((load.throughput() - 1.024).abs() < 0.1);
+        assert!(load.utilization() > 0.9);
+
+        // The link transmits 1 KB per millisecond, a tenth of the time
+        for _ in 0..32 {
+            now += Duration::from_millis(9);
+            load.pulled(now);
+            now += Duration::from_millis(1);
+            load.refilled(1_024, now);
+        }
+        assert!((load.throughput() - 1.024).abs() < 0.1);
+        assert!((load.rate() - 0.1024).abs() < 0.02);
+        assert!(load.utilization() < 0.2);
+    }
+
+    #[test]
+    fn tx_pipeline_adaptive_limit() {
+        let mut adaptive = AdaptiveBackoff::new(Duration::from_micros(100), None);
+        assert_eq!(adaptive.limit, 0);
+
+        // The time limit does not grow while the link keeps up with the batches
+        assert!(!adaptive.adapt(Flush::Idle, 0.2));
+        assert_eq!(adaptive.limit, 0);
+
+        // The time limit grows up to the latency budget while the link is busy and the batches
+        // are sent before filling up
+        for limit in [10, 20, 40, 80, 100, 100] {
+            adaptive.adapt(Flush::Idle, 0.9);
+            assert_eq!(adaptive.limit, limit);
+        }
+        assert!(!adaptive.adapt(Flush::Full, 0.9));
+        assert!(!adaptive.adapt(Flush::Expired, 0.9));
+        assert_eq!(adaptive.limit, 100);
+
+        // The time limit shrinks down to zero once the link keeps up with the batches
+        for limit in [50, 25, 12, 0, 0] {
+            adaptive.adapt(Flush::Full, 0.2);
+            assert_eq!(adaptive.limit, limit);
+        }
+
+        // The time limit is bounded by the round-trip time of the link
+        let mut adaptive =
+            AdaptiveBackoff::new(Duration::from_micros(100), Some(Duration::from_micros(30)));
+        for limit in [10, 20, 30, 30] {
+            adaptive.adapt(Flush::Expired, 0.9);
+            assert_eq!(adaptive.limit, limit);
+        }
+
+        // Messages are never retained with a zero latency budget
+        let mut adaptive = AdaptiveBackoff::new(Duration::ZERO, None);
+        assert!(!adaptive.adapt(Flush::Idle, 1.0));
+        assert_eq!(adaptive.limit, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn tx_pipeline_adaptive() -> ZResult<()> {
+        const BUDGET: Duration = Duration::from_millis(1);
+
+        let config = TransmissionPipelineConf {
+            batch: BatchConfig {
+                mtu: 1_024,
+                is_streamed: true,
+                #[cfg(feature = "transport_compression")]
+                compression: None,
+            },
+            queue_size: [4; Priority::NUM],
+            batching_enabled: true,
+            wait_before_drop: Duration::from_millis(1),
+            wait_before_close: Duration::from_secs(5),
+            batching_time_limit: Duration::from_micros(1),
+            batching_adaptive: true,
+            latency_budget: [BUDGET; Priority::NUM],
+            rtt: None,
+        };
+        let message: NetworkMessage = Push {
+            wire_expr: "pipeline/adaptive".into(),
+            ext_qos: ext::QoSType::new(Priority::Data, CongestionControl::Block, false),
+            ext_tstamp: None,
+            ext_nodeid: ext::NodeIdType::DEFAULT,
+            payload: PushBody::Put(Put {
+                timestamp: None,
+                encoding: Encoding::empty(),
+                ext_sinfo: None,
+                #[cfg(feature = "shared-memory")]
+                ext_shm: None,
+                ext_attachment: None,
+                ext_unknown: vec![],
+                payload: ZBuf::from(vec![0_u8; 128]),
+            }),
+        }
+        .into();
+
+        let tct = TransportPriorityTx::make(Bits::from(TransportSn::MAX))?;
+        let priorities = vec![tct];
+        let (producer, mut consumer) = TransmissionPipeline::make(
+            config,
+            priorities.as_slice(),
+            #[cfg(feature = "stats")]
+            Arc::new(TransportStats::default()),
+        );
+        let limit = |consumer: &TransmissionPipelineConsumer| {
+            consumer.stage_out[0]
+                .s_in
+                .backoff
+                .adaptive
+                .as_ref()
+                .unwrap()
+                .limit
+        };
+
+        // Under light load, the messages are not retained
+        assert!(producer.push_network_message(message.clone()));
+        let (batch, priority) = timeout(TIMEOUT, consumer.pull()).await?.unwrap();
+        assert!(!batch.is_empty());
+        consumer.refill(batch, priority);
+        assert_eq!(limit(&consumer), 0);
+
+        // Once the link is busy transmitting the batches, the messages are retained, at the
+        // latest until the time limit expires
+        for _ in 0..4 {
+            assert!(producer.push_network_message(message.clone()));
+            let (batch, priority) = timeout(TIMEOUT, consumer.pull()).await?.unwrap();
+            assert!(!batch.is_empty());
+            tokio::time::sleep(Duration::from_millis(2)).await;
+            consumer.refill(batch, priority);
+        }
+        assert!(consumer.load.utilization() >= AdaptiveBackoff::BUSY);
+        assert!(limit(&consumer) > 0);
+        assert!(limit(&consumer) <= BUDGET.as_micros() as MicroSeconds);
+
+        // Once the link keeps up with the batches again, the messages are not retained anymore
+        timeout(TIMEOUT, async {
+            while limit(&consumer) > 0 {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                assert!(producer.push_network_message(message.clone()));
+                let (batch, priority) = consumer.pull().await.unwrap();
+                consumer.refill(batch, priority);
+            }
+        })
+        .await?;
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
     #[ignore]
     async fn tx_pipeline_thr() {
         // Queue
         let tct = TransportPriorityTx::make(Bits::from(TransportSn::MAX)).unwrap();
         let priorities = vec![tct];
-        let (producer, mut consumer) =
-            TransmissionPipeline::make(CONFIG_STREAMED, priorities.as_slice());
+        let (producer, mut consumer) = TransmissionPipeline::make(
+            config_streamed(),
+            priorities.as_slice(),
+            #[cfg(feature = "stats")]
+            Arc::new(TransportStats::default()),
+        );
         let count = Arc::new(AtomicUsize::new(0));
         let size = Arc::new(AtomicUsize::new(0));
 
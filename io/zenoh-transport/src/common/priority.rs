@@ -43,9 +43,14 @@ impl TransportChannelTx {
     }
 }
 
+// The number of SNs preceding the latest received one that may still be received
+const REORDERING_WINDOW: TransportSn = u64::BITS as TransportSn;
+
 #[derive(Debug)]
 pub(crate) struct TransportChannelRx {
     pub(crate) sn: SeqNum,
+    // The bit `i` is set if the SN `sn - 1 - i` has been received
+    received: u64,
     pub(crate) defrag: DefragBuffer,
 }
 
@@ -57,7 +62,11 @@ impl TransportChannelRx {
     ) -> ZResult<TransportChannelRx> {
         let sn = SeqNum::make(0, resolution)?;
         let defrag = DefragBuffer::make(reliability, resolution, defrag_buff_size)?;
-        let tch = TransportChannelRx { sn, defrag };
+        let tch = TransportChannelRx {
+            sn,
+            received: u64::MAX,
+            defrag,
+        };
         Ok(tch)
     }
 
@@ -70,8 +79,44 @@ impl TransportChannelRx {
         };
 
         self.sn.set(sn)?;
+        self.received = u64::MAX;
         self.defrag.sync(sn)
     }
+
+    /// Returns `true` if the frame with `sn` has not been received yet.
+    ///
+    /// Besides the SNs following the latest received one, the SNs within [`REORDERING_WINDOW`]
+    /// preceding it are accepted once: the best-effort frames sent on different links of a
+    /// multilink transport, or sent again on another link after a failure, may be received out of
+    /// order. The other channels only accept the SNs following the latest received one.
+    pub(crate) fn roll(&mut self, sn: TransportSn) -> ZResult<bool> {
+        fn shift(bits: u64, n: TransportSn) -> u64 {
+            if n < REORDERING_WINDOW {
+                bits << n
+            } else {
+                0
+            }
+        }
+
+        let latest = self.sn.get();
+        if self.sn.roll(sn)? {
+            // The SNs up to the previously latest one are now in the window
+            let gap = sn.wrapping_sub(latest) & self.sn.resolution();
+            self.received = shift(self.received, gap) | shift(1, gap - 1);
+            return Ok(true);
+        }
+
+        let back = latest.wrapping_sub(sn) & self.sn.resolution();
+        if back == 0 || back > REORDERING_WINDOW {
+            return Ok(false);
+        }
+        let bit = 1 << (back - 1);
+        if self.received & bit != 0 {
+            return Ok(false);
+        }
+        self.received |= bit;
+        Ok(true)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -119,3 +164,36 @@ impl TransportPriorityRx {
         zlock!(self.best_effort).sync(sn.best_effort)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use zenoh_protocol::core::{Bits, Reliability};
+
+    use super::*;
+
+    #[test]
+    fn channel_rx_reordering() {
+        let mut channel =
+            TransportChannelRx::make(Reliability::Reliable, Bits::U32, 1_024).unwrap();
+        channel.sync(10).unwrap();
+
+        // The SNs preceding the initial one are not received
+        assert!(!channel.roll(9).unwrap());
+        assert!(!channel.roll(5).unwrap());
+
+        assert!(channel.roll(10).unwrap());
+        assert!(channel.roll(12).unwrap());
+        // Late SNs are received once
+        assert!(channel.roll(11).unwrap());
+        assert!(!channel.roll(11).unwrap());
+        assert!(!channel.roll(12).unwrap());
+        assert!(!channel.roll(10).unwrap());
+
+        // Only the late SNs within the window are received
+        let latest = 12 + REORDERING_WINDOW + 2;
+        assert!(channel.roll(latest).unwrap());
+        assert!(channel.roll(latest - REORDERING_WINDOW).unwrap());
+        assert!(!channel.roll(latest - REORDERING_WINDOW - 1).unwrap());
+        assert!(!channel.roll(12).unwrap());
+    }
+}
@@ -229,6 +229,30 @@ stats_struct! {
         # TYPE "counter"
         pub tx_z_reply_pl_bytes DiscriminatedStats,
 
+        # HELP "Counter of batches pulled from the transmission queue once full."
+        # TYPE "counter"
+        pub tx_pipeline_full_batches,
+
+        # HELP "Counter of batches pulled from the transmission queue when no more messages were written on them."
+        # TYPE "counter"
+        pub tx_pipeline_idle_batches,
+
+        # HELP "Counter of batches pulled from the transmission queue when their batching time limit expired."
+        # TYPE "counter"
+        pub tx_pipeline_expired_batches,
+
+        # HELP "Counter of increases of the adaptive batching time limits."
+        # TYPE "counter"
+        pub tx_pipeline_limit_increases,
+
+        # HELP "Counter of decreases of the adaptive batching time limits."
+        # TYPE "counter"
+        pub tx_pipeline_limit_decreases,
+
+        # HELP "Counter of the time (in microseconds) spent transmitting the batches pulled from the transmission queue."
+        # TYPE "counter"
+        pub tx_pipeline_transmission_time,
+
         # HELP "Counter of received bytes."
         # TYPE "counter"
         pub rx_bytes,
@@ -15,7 +15,7 @@ use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use rand::{RngCore, SeedableRng};
 use tokio::sync::Mutex as AsyncMutex;
-use zenoh_config::{Config, LinkRxConf, QueueConf, QueueSizeConf};
+use zenoh_config::{Config, LatencyBudgetConf, LinkRxConf, QueueConf, QueueSizeConf};
 use zenoh_crypto::{BlockCipher, PseudoRng};
 use zenoh_link::NewLinkChannelSender;
 use zenoh_protocol::{
@@ -49,6 +49,20 @@ fn duration_from_i64us(us: i64) -> Duration {
     }
 }
 
+pub(crate) fn latency_budget_from_conf(conf: &LatencyBudgetConf) -> [Duration; Priority::NUM] {
+    let mut latency_budget = [Duration::ZERO; Priority::NUM];
+    latency_budget[Priority::Control as usize] = Duration::from_micros(conf.control);
+    latency_budget[Priority::RealTime as usize] = Duration::from_micros(conf.real_time);
+    latency_budget[Priority::InteractiveHigh as usize] =
+        Duration::from_micros(conf.interactive_high);
+    latency_budget[Priority::InteractiveLow as usize] = Duration::from_micros(conf.interactive_low);
+    latency_budget[Priority::DataHigh as usize] = Duration::from_micros(conf.data_high);
+    latency_budget[Priority::Data as usize] = Duration::from_micros(conf.data);
+    latency_budget[Priority::DataLow as usize] = Duration::from_micros(conf.data_low);
+    latency_budget[Priority::Background as usize] = Duration::from_micros(conf.background);
+    latency_budget
+}
+
 /// # Examples
 /// ```
 /// use std::sync::Arc;
@@ -109,6 +123,7 @@ pub struct TransportManagerConfig {
     pub resolution: Resolution,
     pub batch_size: BatchSize,
     pub batching: bool,
+    pub batching_adaptive: bool,
     pub wait_before_drop: Duration,
     pub wait_before_close: Duration,
     pub queue_size: [usize; Priority::NUM],
@@ -121,6 +136,7 @@ pub struct TransportManagerConfig {
     pub handler: Arc<dyn TransportEventHandler>,
     pub tx_threads: usize,
     pub protocols: Vec<String>,
+    pub metadata: Option<String>,
 }
 
 pub struct TransportManagerState {
@@ -141,6 +157,7 @@ pub struct TransportManagerBuilder {
     batch_size: BatchSize,
     batching_enabled: bool,
     batching_time_limit: Duration,
+    batching_adaptive: bool,
     wait_before_drop: Duration,
     wait_before_close: Duration,
     queue_size: QueueSizeConf,
@@ -151,6 +168,7 @@ pub struct TransportManagerBuilder {
     endpoints: HashMap<String, String>, // (protocol, config)
     tx_threads: usize,
     protocols: Option<Vec<String>>,
+    metadata: Option<String>,
     #[cfg(feature = "shared-memory")]
     shm_reader: Option<ShmReader>,
 }
@@ -192,6 +210,11 @@ impl TransportManagerBuilder {
         self
     }
 
+    pub fn batching_adaptive(mut self, batching_adaptive: bool) -> Self {
+        self.batching_adaptive = batching_adaptive;
+        self
+    }
+
     pub fn wait_before_drop(mut self, wait_before_drop: Duration) -> Self {
         self.wait_before_drop = wait_before_drop;
         self
@@ -242,6 +265,13 @@ impl TransportManagerBuilder {
         self
     }
 
+    /// Sets the metadata advertised to the peers during the transport establishment, in JSON.
+    /// It's only set from the configuration if `transport/unicast/advertise_metadata` is enabled.
+    pub fn metadata(mut self, metadata: Option<String>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
     pub async fn from_config(mut self, config: &Config) -> ZResult<TransportManagerBuilder> {
         self = self.zid((*config.id()).into());
         if let Some(v) = config.mode() {
@@ -257,6 +287,7 @@ impl TransportManagerBuilder {
         self = self.batching_time_limit(Duration::from_millis(
             *link.tx().queue().batching().time_limit(),
         ));
+        self = self.batching_adaptive(*link.tx().queue().batching().adaptive());
         self = self.defrag_buff_size(*link.rx().max_message_size());
         self = self.link_rx_buffer_size(*link.rx().buffer_size());
         self = self.wait_before_drop(duration_from_i64us(
@@ -278,6 +309,10 @@ impl TransportManagerBuilder {
         self = self.queue_size(link.tx().queue().size().clone());
         self = self.tx_threads(*link.tx().threads());
         self = self.protocols(link.protocols().clone());
+        let metadata = config.metadata();
+        if *config.transport().unicast().advertise_metadata() && !metadata.is_null() {
+            self = self.metadata(Some(metadata.to_string()));
+        }
 
         let (c, errors) = zenoh_link::LinkConfigurator::default().configurations(config);
         if !errors.is_empty() {
@@ -332,6 +367,7 @@ impl TransportManagerBuilder {
             resolution: self.resolution,
             batch_size: self.batch_size,
             batching: self.batching_enabled,
+            batching_adaptive: self.batching_adaptive,
             wait_before_drop: self.wait_before_drop,
             wait_before_close: self.wait_before_close,
             queue_size,
@@ -349,6 +385,7 @@ impl TransportManagerBuilder {
                     .map(|x| x.to_string())
                     .collect()
             }),
+            metadata: self.metadata,
         };
 
         let state = TransportManagerState {
@@ -372,6 +409,7 @@ impl Default for TransportManagerBuilder {
         let link_rx = LinkRxConf::default();
         let queue = QueueConf::default();
         let backoff = *queue.batching().time_limit();
+        let adaptive = *queue.batching().adaptive();
         let wait_before_drop = *queue.congestion_control().drop().wait_before_drop();
         let wait_before_close = *queue.congestion_control().block().wait_before_close();
         Self {
@@ -385,6 +423,7 @@ impl Default for TransportManagerBuilder {
             wait_before_close: duration_from_i64us(wait_before_close),
             queue_size: queue.size,
             batching_time_limit: Duration::from_millis(backoff),
+            batching_adaptive: adaptive,
             defrag_buff_size: *link_rx.max_message_size(),
             link_rx_buffer_size: *link_rx.buffer_size(),
             endpoints: HashMap::new(),
@@ -392,6 +431,7 @@ impl Default for TransportManagerBuilder {
             multicast: TransportManagerBuilderMulticast::default(),
             tx_threads: 1,
             protocols: None,
+            metadata: None,
             #[cfg(feature = "shared-memory")]
             shm_reader: None,
         }
@@ -22,6 +22,8 @@ use zenoh_protocol::{
 };
 use zenoh_result::{bail, ZResult};
 
+#[cfg(feature = "transport_compression")]
+use crate::common::compression::BatchCompression;
 use crate::{
     common::{batch::BatchConfig, seq_num},
     multicast::{
@@ -67,9 +69,14 @@ pub(crate) async fn open_link(
         batch: BatchConfig {
             mtu: link.get_mtu(),
             #[cfg(feature = "transport_compression")]
-            is_compression: manager.config.multicast.is_compression,
+            compression: manager
+                .config
+                .multicast
+                .is_compression
+                .then(BatchCompression::lz4),
             ..Default::default()
         },
+        fec: manager.config.multicast.fec,
     };
     let link = TransportLinkMulticast::new(link, config);
 
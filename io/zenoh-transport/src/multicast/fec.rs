@@ -0,0 +1,772 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{
+    collections::BTreeMap,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use reed_solomon_erasure::galois_8::ReedSolomon;
+use zenoh_buffers::{
+    reader::{HasReader, Reader},
+    writer::{DidntWrite, HasWriter, Writer},
+    ZBuf, ZSlice,
+};
+use zenoh_codec::{RCodec, WCodec, Zenoh080};
+use zenoh_protocol::{
+    common::ZExtBody,
+    core::Priority,
+    transport::{
+        join,
+        oam::{self, Oam},
+        BatchSize, TransportBody, TransportMessage,
+    },
+};
+use zenoh_result::{zerror, ZResult};
+
+use crate::common::batch::{BatchConfig, Decode, Encode, RBatch, WBatch};
+
+// The maximum size of the tag of a data shard: OAM header, id and u64 body
+const SHARD_TAG_MAX_LEN: usize = 16;
+// The maximum size of a parity shard message besides the lengths of the data shards and the
+// parity shard itself: batch header, OAM header, id, body length, group, index and count
+const PARITY_HEADER_MAX_LEN: usize = 16;
+
+/// The forward error correction of the batches sent on a multicast link: a Reed-Solomon parity
+/// group of `parity_shards` parity shards is sent every `data_shards` batches.
+///
+/// The batches of the control and real-time priorities are sent out of the parity groups, so
+/// that they never wait for a lost batch to be rebuilt. The groups span all the other priorities:
+/// a batch following a lost one of another priority waits for it to be rebuilt as well.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FecConfig {
+    data_shards: u8,
+    parity_shards: u8,
+    timeout: Duration,
+}
+
+impl FecConfig {
+    /// Up to `parity_shards` lost batches of a group of `data_shards` batches can be rebuilt.
+    /// A group is closed and its parity sent after `timeout` if it is not complete.
+    pub fn new(data_shards: u8, parity_shards: u8, timeout: Duration) -> ZResult<Self> {
+        ReedSolomon::new(data_shards as usize, parity_shards as usize)
+            .map_err(|e| zerror!("Invalid FEC configuration: {}", e))?;
+        Ok(Self {
+            data_shards,
+            parity_shards,
+            timeout,
+        })
+    }
+
+    /// The extension advertising the FEC in the Join messages.
+    pub(crate) fn ext(&self) -> join::ext::Fec {
+        let timeout = self.timeout.as_millis().min(u32::MAX as u128) as u64;
+        join::ext::Fec::new(
+            self.data_shards as u64 | (self.parity_shards as u64) << 8 | timeout << 16,
+        )
+    }
+
+    /// Whether the batches of `priority` are sent in the parity groups.
+    pub(crate) fn protects(priority: Priority) -> bool {
+        priority > Priority::RealTime
+    }
+
+    /// The maximum size of the batches of the pipeline, leaving room for the tag of the data
+    /// shards and the header of the parity shards in the `mtu` of the link.
+    pub(crate) fn payload_mtu(&self, mtu: BatchSize) -> BatchSize {
+        let overhead = SHARD_TAG_MAX_LEN + PARITY_HEADER_MAX_LEN + 3 * self.data_shards as usize;
+        mtu.saturating_sub(overhead as BatchSize)
+    }
+}
+
+/// Tags a batch as the data shard `index` of a parity `group`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct FecShard {
+    pub(super) group: u32,
+    pub(super) index: u8,
+}
+
+impl From<FecShard> for TransportMessage {
+    fn from(shard: FecShard) -> Self {
+        TransportBody::OAM(Oam {
+            id: oam::id::OAM_FEC_SHARD,
+            body: ZExtBody::Z64((shard.group as u64) << 8 | shard.index as u64),
+            ext_qos: oam::ext::QoSType::DEFAULT,
+        })
+        .into()
+    }
+}
+
+impl TryFrom<&Oam> for FecShard {
+    type Error = zenoh_result::Error;
+
+    fn try_from(oam: &Oam) -> Result<Self, Self::Error> {
+        let ZExtBody::Z64(tag) = oam.body else {
+            return Err(zerror!("Invalid FEC shard body: {:?}", oam.body).into());
+        };
+        Ok(FecShard {
+            group: (tag >> 8) as u32,
+            index: tag as u8,
+        })
+    }
+}
+
+/// The parity shard `index` of a parity `group`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct FecParity {
+    pub(super) group: u32,
+    pub(super) index: u8,
+    // The lengths of the data shards of the group, which may be less than the data shards
+    // of the configuration if the group has been closed on timeout
+    pub(super) lengths: Vec<BatchSize>,
+    pub(super) shard: Vec<u8>,
+}
+
+impl From<FecParity> for TransportMessage {
+    fn from(parity: FecParity) -> Self {
+        let codec = Zenoh080::new();
+        let mut zbuf = ZBuf::empty();
+        let mut writer = zbuf.writer();
+        // Writing on a ZBuf does not fail
+        let _ = codec.write(&mut writer, parity.group);
+        let _ = codec.write(&mut writer, parity.index);
+        let _ = codec.write(&mut writer, parity.lengths.len() as u8);
+        for length in parity.lengths.iter() {
+            let _ = codec.write(&mut writer, *length);
+        }
+        let _ = writer.write_exact(&parity.shard);
+
+        TransportBody::OAM(Oam {
+            id: oam::id::OAM_FEC_PARITY,
+            body: ZExtBody::ZBuf(zbuf),
+            ext_qos: oam::ext::QoSType::DEFAULT,
+        })
+        .into()
+    }
+}
+
+impl TryFrom<&Oam> for FecParity {
+    type Error = zenoh_result::Error;
+
+    fn try_from(oam: &Oam) -> Result<Self, Self::Error> {
+        const ERR: &str = "Invalid FEC parity";
+
+        let ZExtBody::ZBuf(zbuf) = &oam.body else {
+            return Err(zerror!("{ERR} body: {:?}", oam.body).into());
+        };
+        let codec = Zenoh080::new();
+        let mut reader = zbuf.reader();
+        let group: u32 = codec
+            .read(&mut reader)
+            .map_err(|_| zerror!("{ERR} group"))?;
+        let index: u8 = codec
+            .read(&mut reader)
+            .map_err(|_| zerror!("{ERR} index"))?;
+        let count: u8 = codec
+            .read(&mut reader)
+            .map_err(|_| zerror!("{ERR} count"))?;
+        let lengths = (0..count)
+            .map(|_| codec.read(&mut reader))
+            .collect::<Result<Vec<BatchSize>, _>>()
+            .map_err(|_| zerror!("{ERR} lengths"))?;
+        let mut shard = vec![0; reader.remaining()];
+        reader
+            .read_exact(&mut shard)
+            .map_err(|_| zerror!("{ERR} shard"))?;
+        Ok(FecParity {
+            group,
+            index,
+            lengths,
+            shard,
+        })
+    }
+}
+
+/*************************************/
+/*                TX                 */
+/*************************************/
+/// Protects the batches sent on a multicast link with the parity shards of their group.
+pub(super) struct FecTx {
+    codec: ReedSolomon,
+    timeout: Duration,
+    group: u32,
+    // The data shards of the current group, as sent on the link
+    shards: Vec<Vec<u8>>,
+    // The time the current group has to be closed at
+    deadline: Option<Instant>,
+    // The batch the data shards are serialized on
+    batch: WBatch,
+}
+
+impl FecTx {
+    pub(super) fn new(config: &FecConfig, batch: BatchConfig) -> ZResult<Self> {
+        let codec = ReedSolomon::new(config.data_shards as usize, config.parity_shards as usize)
+            .map_err(|e| zerror!("Invalid FEC configuration: {}", e))?;
+        Ok(Self {
+            codec,
+            timeout: config.timeout,
+            group: 0,
+            shards: Vec::with_capacity(config.data_shards as usize),
+            deadline: None,
+            batch: WBatch::new(batch),
+        })
+    }
+
+    /// Serializes the messages of `batch` as the next data shard of the group, i.e. after its tag.
+    pub(super) fn shard(&mut self, batch: &WBatch) -> Result<&mut WBatch, DidntWrite> {
+        let tag: TransportMessage = FecShard {
+            group: self.group,
+            index: self.shards.len() as u8,
+        }
+        .into();
+        self.batch.clear();
+        self.batch.encode(&tag)?;
+        self.batch.append(batch)?;
+        Ok(&mut self.batch)
+    }
+
+    /// Records the `bytes` of the data shard sent on the link and returns `true` if the group is
+    /// complete.
+    pub(super) fn push(&mut self, bytes: Vec<u8>) -> bool {
+        if self.shards.is_empty() {
+            self.deadline = Some(Instant::now() + self.timeout);
+        }
+        self.shards.push(bytes);
+        self.shards.len() == self.codec.data_shard_count()
+    }
+
+    pub(super) fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Closes the current group and returns its parity shards.
+    pub(super) fn parity(&mut self) -> ZResult<Vec<TransportMessage>> {
+        if self.shards.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let len = self.shards.iter().map(Vec::len).max().unwrap_or(0);
+        let lengths: Vec<BatchSize> = self.shards.iter().map(|s| s.len() as BatchSize).collect();
+        // The missing data shards of a group closed on timeout are empty
+        let mut data = std::mem::take(&mut self.shards);
+        data.resize_with(self.codec.data_shard_count(), Vec::new);
+        for shard in data.iter_mut() {
+            shard.resize(len, 0);
+        }
+        let mut parity = vec![vec![0; len]; self.codec.parity_shard_count()];
+        self.codec
+            .encode_sep(&data, &mut parity)
+            .map_err(|e| zerror!("FEC encoding error: {}", e))?;
+
+        let group = self.group;
+        self.group = self.group.wrapping_add(1);
+        self.deadline = None;
+        data.clear();
+        self.shards = data;
+
+        Ok(parity
+            .into_iter()
+            .enumerate()
+            .map(|(index, shard)| {
+                FecParity {
+                    group,
+                    index: index as u8,
+                    lengths: lengths.clone(),
+                    shard,
+                }
+                .into()
+            })
+            .collect())
+    }
+}
+
+impl fmt::Debug for FecTx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FecTx")
+            .field("data_shards", &self.codec.data_shard_count())
+            .field("parity_shards", &self.codec.parity_shard_count())
+            .field("group", &self.group)
+            .field("shards", &self.shards.len())
+            .finish()
+    }
+}
+
+/*************************************/
+/*                RX                 */
+/*************************************/
+struct FecRxGroup {
+    id: u32,
+    // The data shards followed by the parity shards, as received or rebuilt
+    shards: Vec<Option<ZSlice>>,
+    // The lengths of the data shards, as advertised by the parity shards
+    lengths: Option<Vec<BatchSize>>,
+    // The index of the next data shard to deliver
+    next: usize,
+    // The data batches received after a missing one, waiting for it to be rebuilt
+    pending: BTreeMap<usize, RBatch>,
+    // The time the group is given up at if its parity shards are lost
+    deadline: Instant,
+}
+
+impl FecRxGroup {
+    fn new(id: u32, total: usize, deadline: Instant) -> Self {
+        Self {
+            id,
+            shards: vec![None; total],
+            lengths: None,
+            next: 0,
+            pending: BTreeMap::new(),
+            deadline,
+        }
+    }
+
+    // Delivers the pending batches following the delivered ones
+    fn release(&mut self, ready: &mut Vec<RBatch>) {
+        while let Some(batch) = self.pending.remove(&self.next) {
+            ready.push(batch);
+            self.next += 1;
+        }
+    }
+
+    // Gives up the missing data shards and delivers all the pending batches
+    fn flush(&mut self, ready: &mut Vec<RBatch>) {
+        let pending = std::mem::take(&mut self.pending);
+        ready.extend(pending.into_values());
+        self.next = usize::MAX;
+    }
+}
+
+/// Rebuilds the batches lost on a multicast link from the parity shards of their group, and
+/// delivers the batches of a group in order.
+pub(super) struct FecRx {
+    codec: ReedSolomon,
+    ext: join::ext::Fec,
+    // The time a group is given up after its first received shard: twice the time the sender
+    // keeps it open, leaving as much for the transmission of its parity shards
+    timeout: Duration,
+    batch: BatchConfig,
+    group: Option<FecRxGroup>,
+}
+
+impl FecRx {
+    /// Creates the FEC of the batches received from a peer advertising `ext` in its Join.
+    pub(super) fn new(ext: join::ext::Fec, batch: BatchConfig) -> ZResult<Self> {
+        let data_shards = (ext.value & 0xff) as usize;
+        let parity_shards = ((ext.value >> 8) & 0xff) as usize;
+        let timeout = 2 * Duration::from_millis((ext.value >> 16) & 0xffff_ffff);
+        let codec = ReedSolomon::new(data_shards, parity_shards)
+            .map_err(|e| zerror!("Invalid FEC extension {:?}: {}", ext, e))?;
+        Ok(Self {
+            codec,
+            ext,
+            timeout,
+            batch,
+            group: None,
+        })
+    }
+
+    pub(super) fn ext(&self) -> join::ext::Fec {
+        self.ext
+    }
+
+    // Returns the group `id` if it is the current one or a following one, which closes the
+    // current one. Returns `None` for the shards of a previous group.
+    fn group(&mut self, id: u32, ready: &mut Vec<RBatch>) -> Option<&mut FecRxGroup> {
+        match self.group.as_ref().map(|g| id.wrapping_sub(g.id)) {
+            Some(0) => {}
+            Some(d) if d > u32::MAX / 2 => return None,
+            _ => {
+                if let Some(g) = self.group.as_mut() {
+                    g.flush(ready);
+                }
+                self.group = Some(FecRxGroup::new(
+                    id,
+                    self.codec.total_shard_count(),
+                    Instant::now() + self.timeout,
+                ));
+            }
+        }
+        self.group.as_mut()
+    }
+
+    /// Handles the data `batch` tagged with `shard`, whose bytes as received are `raw`, and
+    /// returns the batches to be delivered in order.
+    pub(super) fn shard(&mut self, shard: FecShard, raw: ZSlice, batch: RBatch) -> Vec<RBatch> {
+        let mut ready = vec![];
+        let index = shard.index as usize;
+        if index >= self.codec.data_shard_count() {
+            tracing::trace!("Invalid FEC data shard: {:?}", shard);
+            ready.push(batch);
+            return ready;
+        }
+
+        match self.group(shard.group, &mut ready) {
+            Some(g) if index >= g.next && g.shards[index].is_none() => {
+                g.shards[index] = Some(raw);
+                g.pending.insert(index, batch);
+                g.release(&mut ready);
+            }
+            Some(_) => tracing::trace!("Duplicate FEC data shard: {:?}", shard),
+            // A late batch of a previous group is delivered as is
+            None => ready.push(batch),
+        }
+        ready
+    }
+
+    /// Handles the `parity` shard and returns the batches to be delivered in order, including the
+    /// rebuilt ones.
+    pub(super) fn parity(&mut self, parity: FecParity) -> Vec<RBatch> {
+        let mut ready = vec![];
+        let data_shards = self.codec.data_shard_count();
+        let parity_shards = self.codec.parity_shard_count();
+        if parity.index as usize >= parity_shards
+            || parity.lengths.is_empty()
+            || parity.lengths.len() > data_shards
+            || parity
+                .lengths
+                .iter()
+                .any(|l| *l as usize > parity.shard.len())
+        {
+            tracing::trace!(
+                "Invalid FEC parity shard: group {}, index {}",
+                parity.group,
+                parity.index
+            );
+            return ready;
+        }
+
+        let is_last = parity.index as usize + 1 == parity_shards;
+        let Some(g) = self.group(parity.group, &mut ready) else {
+            return ready;
+        };
+        g.lengths.get_or_insert(parity.lengths);
+        g.shards[data_shards + parity.index as usize] = Some(parity.shard.into());
+
+        self.rebuild();
+
+        if let Some(g) = self.group.as_mut() {
+            g.release(&mut ready);
+            // No more parity shards are expected to rebuild the missing data shards
+            if is_last {
+                g.flush(&mut ready);
+            }
+        }
+        ready
+    }
+
+    /// Delivers the batches held by the current group, giving up the missing ones.
+    pub(super) fn flush(&mut self) -> Vec<RBatch> {
+        let mut ready = vec![];
+        if let Some(g) = self.group.as_mut() {
+            g.flush(&mut ready);
+        }
+        ready
+    }
+
+    /// The time the batches held by the current group have to be delivered at, if any.
+    pub(super) fn deadline(&self) -> Option<Instant> {
+        self.group
+            .as_ref()
+            .filter(|g| !g.pending.is_empty())
+            .map(|g| g.deadline)
+    }
+
+    /// Delivers the batches held by the current group if it has expired at `now`, giving up the
+    /// missing ones.
+    pub(super) fn expire(&mut self, now: Instant) -> Vec<RBatch> {
+        match self.deadline() {
+            Some(deadline) if deadline <= now => self.flush(),
+            _ => vec![],
+        }
+    }
+
+    // Rebuilds the missing data shards of the current group if enough shards have been received
+    fn rebuild(&mut self) {
+        let Some(g) = self.group.as_mut() else {
+            return;
+        };
+        let Some(lengths) = g.lengths.as_ref() else {
+            return;
+        };
+        let count = lengths.len();
+        let missing: Vec<usize> = (g.next.min(count)..count)
+            .filter(|i| g.shards[*i].is_none())
+            .collect();
+        let data_shards = self.codec.data_shard_count();
+        let received = g.shards.iter().filter(|s| s.is_some()).count() + data_shards - count;
+        if missing.is_empty() || received < data_shards {
+            return;
+        }
+
+        // The parity shards have the length of the longest data shard
+        let Some(len) = g.shards[data_shards..]
+            .iter()
+            .flatten()
+            .map(|s| s.len())
+            .next()
+        else {
+            return;
+        };
+        let mut shards: Vec<Option<Vec<u8>>> = g
+            .shards
+            .iter()
+            .enumerate()
+            .map(|(i, s)| match s {
+                Some(s) if i < data_shards => {
+                    let mut shard = s.as_slice().to_vec();
+                    shard.resize(len, 0);
+                    Some(shard)
+                }
+                Some(s) => Some(s.as_slice().to_vec()),
+                // The data shards following the ones of a group closed on timeout are empty
+                None if (count..data_shards).contains(&i) => Some(vec![0; len]),
+                None => None,
+            })
+            .collect();
+        if let Err(e) = self.codec.reconstruct_data(&mut shards) {
+            tracing::trace!("FEC group {} can not be rebuilt: {}", g.id, e);
+            return;
+        }
+
+        for i in missing {
+            let Some(mut bytes) = shards[i].take() else {
+                continue;
+            };
+            bytes.truncate(lengths[i] as usize);
+            let raw = ZSlice::from(bytes);
+            match Self::batch(&self.batch, raw.clone()) {
+                Ok(batch) => {
+                    g.shards[i] = Some(raw);
+                    g.pending.insert(i, batch);
+                }
+                Err(e) => tracing::trace!("FEC group {}: invalid rebuilt shard: {}", g.id, e),
+            }
+        }
+    }
+
+    // Reads a rebuilt data shard, whose tag has already been handled
+    fn batch(config: &BatchConfig, raw: ZSlice) -> ZResult<RBatch> {
+        let mtu = config.mtu as usize;
+        let mut batch = RBatch::new(config.clone(), raw);
+        batch.initialize(|| zenoh_buffers::vec::uninit(mtu).into_boxed_slice())?;
+        let _: TransportMessage = batch
+            .decode()
+            .map_err(|_| zerror!("Invalid data shard tag"))?;
+        Ok(batch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zenoh_buffers::buffer::SplitBuffer;
+    use zenoh_protocol::{
+        core::{Priority, Reliability},
+        network::{push::ext::QoSType, NetworkMessage, Push},
+        transport::frame::{self, Frame},
+        zenoh::Put,
+    };
+
+    use super::*;
+    use crate::common::batch::Finalize;
+
+    fn batches(n: usize) -> Vec<WBatch> {
+        (0..n)
+            .map(|i| {
+                let mut batch = WBatch::new(BatchConfig::default());
+                let msg: NetworkMessage = Push {
+                    wire_expr: "test".into(),
+                    ext_qos: QoSType::DEFAULT,
+                    ext_tstamp: None,
+                    ext_nodeid: Default::default(),
+                    payload: Put {
+                        payload: vec![i as u8; 64 * (i + 1)].into(),
+                        ..Put::rand()
+                    }
+                    .into(),
+                }
+                .into();
+                let frame: TransportMessage = Frame {
+                    reliability: Reliability::BestEffort,
+                    sn: i as u32,
+                    ext_qos: frame::ext::QoSType::new(Priority::DEFAULT),
+                    payload: vec![msg],
+                }
+                .into();
+                batch.encode(&frame).unwrap();
+                batch
+            })
+            .collect()
+    }
+
+    // Sends the `batches` as data shards and returns the datagrams, including the parity shards
+    fn send(fec: &mut FecTx, batches: &[WBatch]) -> Vec<Vec<u8>> {
+        let mut datagrams = vec![];
+        for batch in batches.iter() {
+            let shard = fec.shard(batch).unwrap();
+            assert!(matches!(shard.finalize(None).unwrap(), Finalize::Batch));
+            let bytes = shard.as_slice().to_vec();
+            datagrams.push(bytes.clone());
+            let is_full = fec.push(bytes);
+            if is_full {
+                datagrams.extend(parity(fec));
+            }
+        }
+        datagrams.extend(parity(fec));
+        datagrams
+    }
+
+    fn parity(fec: &mut FecTx) -> Vec<Vec<u8>> {
+        fec.parity()
+            .unwrap()
+            .iter()
+            .map(|msg| {
+                let mut batch = WBatch::new(BatchConfig::default());
+                batch.encode(msg).unwrap();
+                batch.as_slice().to_vec()
+            })
+            .collect()
+    }
+
+    // Receives the datagrams and returns the payloads of the batches delivered in order
+    fn recv(fec: &mut FecRx, datagrams: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+        let mut delivered = vec![];
+        for datagram in datagrams {
+            let raw = ZSlice::from(datagram);
+            let mut batch = RBatch::new(BatchConfig::default(), raw.clone());
+            batch
+                .initialize(|| vec![0u8; 0].into_boxed_slice())
+                .unwrap();
+            let msg: TransportMessage = batch.decode().unwrap();
+            let TransportBody::OAM(oam) = &msg.body else {
+                panic!("Not an OAM message: {:?}", msg);
+            };
+            let ready = match oam.id {
+                oam::id::OAM_FEC_SHARD => fec.shard(FecShard::try_from(oam).unwrap(), raw, batch),
+                oam::id::OAM_FEC_PARITY => fec.parity(FecParity::try_from(oam).unwrap()),
+                _ => panic!("Unexpected OAM message: {:?}", oam),
+            };
+            for mut batch in ready {
+                let msg: TransportMessage = batch.decode().unwrap();
+                let TransportBody::Frame(mut frame) = msg.body else {
+                    panic!("Not a frame: {:?}", msg);
+                };
+                let msg = frame.payload.pop().unwrap();
+                let zenoh_protocol::network::NetworkBody::Push(push) = msg.body else {
+                    panic!("Not a push: {:?}", msg);
+                };
+                let zenoh_protocol::zenoh::PushBody::Put(put) = push.payload else {
+                    panic!("Not a put: {:?}", push);
+                };
+                delivered.push(put.payload.contiguous().to_vec());
+            }
+        }
+        delivered
+    }
+
+    fn lose(datagrams: &[Vec<u8>], lost: &[usize]) -> Vec<Vec<u8>> {
+        datagrams
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !lost.contains(i))
+            .map(|(_, d)| d.clone())
+            .collect()
+    }
+
+    fn expected(n: usize) -> Vec<Vec<u8>> {
+        (0..n).map(|i| vec![i as u8; 64 * (i + 1)]).collect()
+    }
+
+    #[test]
+    fn fec_codec() {
+        let shard = FecShard {
+            group: 0xabcdef,
+            index: 7,
+        };
+        let msg: TransportMessage = shard.into();
+        let TransportBody::OAM(oam) = &msg.body else {
+            panic!("FEC shard is not an OAM message: {:?}", msg);
+        };
+        assert_eq!(oam.id, oam::id::OAM_FEC_SHARD);
+        assert_eq!(FecShard::try_from(oam).unwrap(), shard);
+
+        let parity = FecParity {
+            group: 42,
+            index: 1,
+            lengths: vec![12, 1_024, 8_192],
+            shard: vec![3; 8_192],
+        };
+        let msg: TransportMessage = parity.clone().into();
+        let TransportBody::OAM(oam) = &msg.body else {
+            panic!("FEC parity is not an OAM message: {:?}", msg);
+        };
+        assert_eq!(oam.id, oam::id::OAM_FEC_PARITY);
+        assert_eq!(FecParity::try_from(oam).unwrap(), parity);
+    }
+
+    #[test]
+    fn fec_rebuild() {
+        let config = FecConfig::new(4, 2, Duration::from_millis(10)).unwrap();
+        assert!(FecConfig::new(0, 2, Duration::ZERO).is_err());
+        assert!(FecConfig::new(255, 2, Duration::ZERO).is_err());
+
+        // 10 batches in groups of 4 data shards and 2 parity shards: the last group is closed
+        // on timeout with 2 data shards only
+        let batches = batches(10);
+        let mut tx = FecTx::new(&config, BatchConfig::default()).unwrap();
+        let datagrams = send(&mut tx, &batches);
+        assert_eq!(datagrams.len(), 10 + 3 * 2);
+
+        let new_rx = || FecRx::new(config.ext(), BatchConfig::default()).unwrap();
+
+        // No loss
+        let mut rx = new_rx();
+        assert_eq!(recv(&mut rx, datagrams.clone()), expected(10));
+
+        // Up to 2 lost data shards per group are rebuilt and delivered in order
+        let lost = [0, 2, 7, 8, 13];
+        let mut rx = new_rx();
+        assert_eq!(recv(&mut rx, lose(&datagrams, &lost)), expected(10));
+
+        // 3 lost data shards of a group can not be rebuilt: the others are delivered in order
+        let lost = [0, 1, 2];
+        let mut rx = new_rx();
+        assert_eq!(
+            recv(&mut rx, lose(&datagrams, &lost)),
+            expected(10)[3..].to_vec()
+        );
+    }
+
+    #[test]
+    fn fec_expire() {
+        let config = FecConfig::new(4, 2, Duration::from_secs(1)).unwrap();
+        let batches = batches(4);
+        let mut tx = FecTx::new(&config, BatchConfig::default()).unwrap();
+        let datagrams = send(&mut tx, &batches);
+        assert_eq!(datagrams.len(), 4 + 2);
+
+        // The batches following a lost one are held until the group expires if its parity
+        // shards are lost as well
+        let mut rx = FecRx::new(config.ext(), BatchConfig::default()).unwrap();
+        let lost = [1, 4, 5];
+        assert_eq!(
+            recv(&mut rx, lose(&datagrams, &lost)),
+            expected(4)[..1].to_vec()
+        );
+        let deadline = rx.deadline().unwrap();
+        assert!(deadline > Instant::now() + Duration::from_secs(1));
+        assert!(rx.expire(Instant::now()).is_empty());
+        assert_eq!(rx.expire(deadline).len(), 2);
+        assert!(rx.deadline().is_none());
+    }
+}
@@ -40,15 +40,20 @@ use crate::{
         },
         priority::TransportPriorityTx,
     },
-    multicast::transport::TransportMulticastInner,
+    multicast::{
+        fec::{FecConfig, FecTx},
+        transport::TransportMulticastInner,
+    },
 };
 
 /****************************/
 /* TRANSPORT MULTICAST LINK */
 /****************************/
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub(crate) struct TransportLinkMulticastConfig {
     pub(crate) batch: BatchConfig,
+    // The FEC of the batches sent on the link, if any
+    pub(crate) fec: Option<FecConfig>,
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -71,12 +76,14 @@ impl TransportLinkMulticast {
                 "transport_compression",
                 self.config
                     .batch
-                    .is_compression
-                    .then_some(BBuf::with_capacity(
-                        lz4_flex::block::get_maximum_output_size(self.config.batch.mtu as usize),
+                    .compression
+                    .as_ref()
+                    .map(|c| BBuf::with_capacity(
+                        c.max_compressed_len(self.config.batch.mtu as usize)
                     )),
                 None
             ),
+            fec: None,
         }
     }
 
@@ -129,39 +136,86 @@ impl fmt::Debug for TransportLinkMulticast {
 pub(crate) struct TransportLinkMulticastTx {
     pub(crate) inner: TransportLinkMulticast,
     pub(crate) buffer: Option<BBuf>,
+    // The FEC of the batches of the pipeline
+    pub(super) fec: Option<FecTx>,
 }
 
 impl TransportLinkMulticastTx {
-    pub(crate) async fn send_batch(&mut self, batch: &mut WBatch) -> ZResult<()> {
+    pub(crate) async fn send_batch(
+        &mut self,
+        batch: &mut WBatch,
+        priority: Priority,
+    ) -> ZResult<()> {
         const ERR: &str = "Write error on link: ";
 
+        let Some(fec) = self.fec.as_mut().filter(|_| FecConfig::protects(priority)) else {
+            Self::write_batch(&self.inner, self.buffer.as_mut(), batch).await?;
+            return Ok(());
+        };
+
+        // Send the batch as the next data shard of the FEC group
+        let shard = fec
+            .shard(batch)
+            .map_err(|_| zerror!("{ERR}{}", self.inner))?;
+        let bytes = Self::write_batch(&self.inner, self.buffer.as_mut(), shard)
+            .await?
+            .to_vec();
+        if fec.push(bytes) {
+            self.send_fec_parity().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Closes the current FEC group and sends its parity shards.
+    pub(crate) async fn send_fec_parity(&mut self) -> ZResult<()> {
+        let Some(fec) = self.fec.as_mut() else {
+            return Ok(());
+        };
+        for msg in fec.parity()? {
+            self.send(&msg).await?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn fec_deadline(&self) -> Option<Instant> {
+        self.fec.as_ref().and_then(|fec| fec.deadline())
+    }
+
+    // Finalizes the batch and writes it on the link, returning the bytes written
+    async fn write_batch<'a>(
+        inner: &TransportLinkMulticast,
+        buffer: Option<&'a mut BBuf>,
+        batch: &'a mut WBatch,
+    ) -> ZResult<&'a [u8]> {
+        const ERR: &str = "Write error on link: ";
+
+        let mut buffer = buffer;
         let res = batch
-            .finalize(self.buffer.as_mut())
-            .map_err(|_| zerror!("{ERR}{self}"))?;
+            .finalize(buffer.as_deref_mut())
+            .map_err(|_| zerror!("{ERR}{inner}"))?;
 
         let bytes = match res {
             Finalize::Batch => batch.as_slice(),
-            Finalize::Buffer => self
-                .buffer
-                .as_ref()
+            Finalize::Buffer => buffer
                 .ok_or_else(|| zerror!("Invalid buffer finalization"))?
                 .as_slice(),
         };
 
         // Send the message on the link
-        self.inner.link.write_all(bytes).await?;
+        inner.link.write_all(bytes).await?;
 
-        Ok(())
+        Ok(bytes)
     }
 
     pub(crate) async fn send(&mut self, msg: &TransportMessage) -> ZResult<usize> {
         const ERR: &str = "Write error on link: ";
 
         // Create the batch for serializing the message
-        let mut batch = WBatch::new(self.inner.config.batch);
+        let mut batch = WBatch::new(self.inner.config.batch.clone());
         batch.encode(msg).map_err(|_| zerror!("{ERR}{self}"))?;
         let len = batch.len() as usize;
-        self.send_batch(&mut batch).await?;
+        Self::write_batch(&self.inner, self.buffer.as_mut(), &mut batch).await?;
         Ok(len)
     }
 }
@@ -176,7 +230,8 @@ impl fmt::Debug for TransportLinkMulticastTx {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut s = f.debug_struct("TransportLinkMulticastRx");
         s.field("link", &self.inner.link)
-            .field("config", &self.inner.config);
+            .field("config", &self.inner.config)
+            .field("fec", &self.fec);
         #[cfg(feature = "transport_compression")]
         {
             s.field("buffer", &self.buffer.as_ref().map(|b| b.capacity()));
@@ -190,7 +245,8 @@ pub(crate) struct TransportLinkMulticastRx {
 }
 
 impl TransportLinkMulticastRx {
-    pub async fn recv_batch<C, T>(&self, buff: C) -> ZResult<(RBatch, Locator)>
+    /// Receives a batch, along with its bytes as received for the FEC.
+    pub async fn recv_batch<C, T>(&self, buff: C) -> ZResult<(RBatch, ZSlice, Locator)>
     where
         C: Fn() -> T + Copy,
         T: AsMut<[u8]> + ZSliceBuffer + 'static,
@@ -200,9 +256,9 @@ impl TransportLinkMulticastRx {
         let mut into = (buff)();
         let (n, locator) = self.inner.link.read(into.as_mut()).await?;
         let buffer = ZSlice::new(Arc::new(into), 0, n).map_err(|_| zerror!("Error"))?;
-        let mut batch = RBatch::new(self.inner.config.batch, buffer);
+        let mut batch = RBatch::new(self.inner.config.batch.clone(), buffer.clone());
         batch.initialize(buff).map_err(|_| zerror!("{ERR}{self}"))?;
-        Ok((batch, locator.into_owned()))
+        Ok((batch, buffer, locator.into_owned()))
     }
 
     // pub async fn recv(&mut self) -> ZResult<(TransportMessage, Locator)> {
@@ -305,16 +361,29 @@ impl TransportLinkMulticastUniversal {
             .collect();
 
         if self.handle_tx.is_none() {
+            // Leave room in the batches of the pipeline for the FEC shard tags and parity headers
+            let mut batch = self.link.config.batch.clone();
+            if let Some(fec) = self.link.config.fec.as_ref() {
+                batch.mtu = fec.payload_mtu(batch.mtu);
+            }
             let tpc = TransmissionPipelineConf {
-                batch: self.link.config.batch,
+                batch,
                 queue_size: self.transport.manager.config.queue_size,
                 wait_before_drop: self.transport.manager.config.wait_before_drop,
                 wait_before_close: self.transport.manager.config.wait_before_close,
                 batching_enabled: self.transport.manager.config.batching,
                 batching_time_limit: self.transport.manager.config.queue_backoff,
+                batching_adaptive: self.transport.manager.config.batching_adaptive,
+                latency_budget: self.transport.manager.config.multicast.latency_budget,
+                rtt: None,
             };
             // The pipeline
-            let (producer, consumer) = TransmissionPipeline::make(tpc, &priority_tx);
+            let (producer, consumer) = TransmissionPipeline::make(
+                tpc,
+                &priority_tx,
+                #[cfg(feature = "stats")]
+                self.transport.stats.clone(),
+            );
             self.pipeline = Some(producer);
 
             // Spawn the TX task
@@ -421,20 +490,46 @@ async fn tx_task(
         }
     }
 
+    async fn fec(deadline: Option<Instant>) {
+        match deadline {
+            Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+            None => std::future::pending().await,
+        }
+    }
+
+    // Only the batches of the pipeline are protected by the FEC, not the Join messages
+    link.fec = link
+        .inner
+        .config
+        .fec
+        .as_ref()
+        .map(|fec| FecTx::new(fec, link.inner.config.batch.clone()))
+        .transpose()?;
+
+    // The priority of the batches of each stage of the pipeline
+    let is_qos = last_sns.len() == Priority::NUM;
+    let priority = |stage: usize| {
+        if is_qos {
+            Priority::try_from(stage as u8).unwrap_or(Priority::DEFAULT)
+        } else {
+            Priority::DEFAULT
+        }
+    };
+
     let mut last_join = Instant::now().checked_sub(config.join_interval).unwrap();
     loop {
         tokio::select! {
             res = pipeline.pull() => {
                 match res {
-                    Some((mut batch, priority)) => {
+                    Some((mut batch, stage)) => {
                         // Send the buffer on the link
-                        link.send_batch(&mut batch).await?;
+                        link.send_batch(&mut batch, priority(stage)).await?;
                         // Keep track of next SNs
                         if let Some(sn) = batch.codec.latest_sn.reliable {
-                            last_sns[priority].reliable = sn;
+                            last_sns[stage].reliable = sn;
                         }
                         if let Some(sn) = batch.codec.latest_sn.best_effort {
-                            last_sns[priority].best_effort = sn;
+                            last_sns[stage].best_effort = sn;
                         }
                         #[cfg(feature = "stats")]
                         {
@@ -442,13 +537,16 @@ async fn tx_task(
                             stats.inc_tx_bytes(batch.len() as usize);
                         }
                         // Reinsert the batch into the queue
-                        pipeline.refill(batch, priority);
+                        pipeline.refill(batch, stage);
                     }
                     None => {
                         // Drain the transmission pipeline and write remaining bytes on the wire
                         let mut batches = pipeline.drain();
-                        for (mut b, _) in batches.drain(..) {
-                            tokio::time::timeout(config.join_interval, link.send_batch(&mut b))
+                        for (mut b, stage) in batches.drain(..) {
+                            tokio::time::timeout(
+                                config.join_interval,
+                                link.send_batch(&mut b, priority(stage)),
+                            )
                                 .await
                                 .map_err(|_| {
                                     zerror!(
@@ -464,12 +562,26 @@ async fn tx_task(
                                 stats.inc_tx_bytes(b.len() as usize);
                             }
                         }
+                        tokio::time::timeout(config.join_interval, link.send_fec_parity())
+                            .await
+                            .map_err(|_| {
+                                zerror!(
+                                    "{}: flush failed after {} ms",
+                                    link,
+                                    config.join_interval.as_millis()
+                                )
+                            })??;
                         break;
                     }
 
                 }
             }
 
+            _ = fec(link.fec_deadline()) => {
+                // Close the FEC group on timeout
+                link.send_fec_parity().await?;
+            }
+
             _ = join(last_join, config.join_interval) => {
                 let next_sns = last_sns
                     .iter()
@@ -495,6 +607,7 @@ async fn tx_task(
                     next_sn,
                     ext_qos,
                     ext_shm: None,
+                    ext_fec: link.inner.config.fec.as_ref().map(FecConfig::ext),
                 }
                 .into();
 
@@ -525,16 +638,21 @@ async fn rx_task(
     async fn read<T, F>(
         link: &mut TransportLinkMulticastRx,
         pool: &RecyclingObjectPool<T, F>,
-    ) -> ZResult<(RBatch, Locator)>
+    ) -> ZResult<(RBatch, ZSlice, Locator)>
     where
         T: ZSliceBuffer + 'static,
         F: Fn() -> T,
         RecyclingObject<T>: AsMut<[u8]> + ZSliceBuffer,
     {
-        let (rbatch, locator) = link
-            .recv_batch(|| pool.try_take().unwrap_or_else(|| pool.alloc()))
-            .await?;
-        Ok((rbatch, locator))
+        link.recv_batch(|| pool.try_take().unwrap_or_else(|| pool.alloc()))
+            .await
+    }
+
+    async fn fec(deadline: Option<Instant>) {
+        match deadline {
+            Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+            None => std::future::pending().await,
+        }
     }
 
     // The pool of buffers
@@ -549,7 +667,7 @@ async fn rx_task(
         tokio::select! {
             _ = signal.wait() => break,
             res = read(&mut link, &pool) => {
-                let (batch, locator) = res?;
+                let (batch, raw, locator) = res?;
 
                 #[cfg(feature = "stats")]
                 transport.stats.inc_rx_bytes(batch.len());
@@ -557,12 +675,21 @@ async fn rx_task(
                 // Deserialize all the messages from the current ZBuf
                 transport.read_messages(
                     batch,
+                    raw,
                     locator,
                     batch_size,
                     #[cfg(feature = "stats")]
                     &transport,
                 )?;
             }
+            // The batches held by the FEC groups whose parity shards are lost
+            _ = fec(transport.fec_deadline()) => {
+                transport.expire_fec(
+                    batch_size,
+                    #[cfg(feature = "stats")]
+                    &transport,
+                )?;
+            }
         }
     }
     Ok(())
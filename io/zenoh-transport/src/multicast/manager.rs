@@ -22,13 +22,14 @@ use zenoh_config::{Config, LinkTxConf};
 use zenoh_core::zasynclock;
 use zenoh_link::*;
 use zenoh_protocol::{
-    core::{parameters, ZenohIdProto},
+    core::{parameters, Priority, ZenohIdProto},
     transport::close,
 };
 use zenoh_result::{bail, zerror, ZResult};
 
 use crate::{
-    multicast::{transport::TransportMulticastInner, TransportMulticast},
+    manager::latency_budget_from_conf,
+    multicast::{transport::TransportMulticastInner, FecConfig, TransportMulticast},
     TransportManager,
 };
 
@@ -38,6 +39,8 @@ pub struct TransportManagerConfigMulticast {
     pub join_interval: Duration,
     pub max_sessions: usize,
     pub is_qos: bool,
+    pub latency_budget: [Duration; Priority::NUM],
+    pub fec: Option<FecConfig>,
     #[cfg(feature = "shared-memory")]
     pub is_shm: bool,
     #[cfg(feature = "transport_compression")]
@@ -50,6 +53,8 @@ pub struct TransportManagerBuilderMulticast {
     join_interval: Duration,
     max_sessions: usize,
     is_qos: bool,
+    latency_budget: [Duration; Priority::NUM],
+    fec: Option<FecConfig>,
     #[cfg(feature = "shared-memory")]
     is_shm: bool,
     #[cfg(feature = "transport_compression")]
@@ -94,6 +99,16 @@ impl TransportManagerBuilderMulticast {
         self
     }
 
+    pub fn latency_budget(mut self, latency_budget: [Duration; Priority::NUM]) -> Self {
+        self.latency_budget = latency_budget;
+        self
+    }
+
+    pub fn fec(mut self, fec: Option<FecConfig>) -> Self {
+        self.fec = fec;
+        self
+    }
+
     #[cfg(feature = "shared-memory")]
     pub fn shm(mut self, is_shm: bool) -> Self {
         self.is_shm = is_shm;
@@ -116,6 +131,21 @@ impl TransportManagerBuilderMulticast {
         ));
         self = self.max_sessions(config.transport().multicast().max_sessions().unwrap());
         self = self.qos(*config.transport().multicast().qos().enabled());
+        self = self.latency_budget(latency_budget_from_conf(
+            config.transport().multicast().qos().latency_budget(),
+        ));
+        let fec = config.transport().multicast().fec();
+        self = self.fec(
+            fec.enabled()
+                .then(|| {
+                    FecConfig::new(
+                        *fec.data_shards(),
+                        *fec.parity_shards(),
+                        Duration::from_millis(*fec.timeout()),
+                    )
+                })
+                .transpose()?,
+        );
         #[cfg(feature = "shared-memory")]
         {
             self = self.shm(*config.transport().shared_memory().enabled());
@@ -131,6 +161,8 @@ impl TransportManagerBuilderMulticast {
             join_interval: self.join_interval,
             max_sessions: self.max_sessions,
             is_qos: self.is_qos,
+            latency_budget: self.latency_budget,
+            fec: self.fec,
             #[cfg(feature = "shared-memory")]
             is_shm: self.is_shm,
             #[cfg(feature = "transport_compression")]
@@ -162,6 +194,8 @@ impl Default for TransportManagerBuilderMulticast {
             join_interval: Duration::from_millis(0),
             max_sessions: 0,
             is_qos: false,
+            latency_budget: [Duration::ZERO; Priority::NUM],
+            fec: None,
             #[cfg(feature = "shared-memory")]
             is_shm: *shm.enabled(),
             #[cfg(feature = "transport_compression")]
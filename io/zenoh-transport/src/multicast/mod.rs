@@ -11,6 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 pub(crate) mod establishment;
+pub(crate) mod fec;
 pub(crate) mod link;
 pub(crate) mod manager;
 pub(crate) mod rx;
@@ -22,6 +23,7 @@ use std::{
     sync::{Arc, Weak},
 };
 
+pub use fec::FecConfig;
 pub use manager::{
     TransportManagerBuilderMulticast, TransportManagerConfigMulticast,
     TransportManagerParamsMulticast,
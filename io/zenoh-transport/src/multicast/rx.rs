@@ -11,20 +11,24 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use std::sync::MutexGuard;
+use std::{sync::MutexGuard, time::Instant};
 
+use zenoh_buffers::ZSlice;
 use zenoh_core::{zlock, zread};
 use zenoh_protocol::{
     core::{Locator, Priority, Reliability},
     network::NetworkMessage,
     transport::{
-        BatchSize, Close, Fragment, Frame, Join, KeepAlive, TransportBody, TransportMessage,
+        oam, BatchSize, Close, Fragment, Frame, Join, KeepAlive, TransportBody, TransportMessage,
         TransportSn,
     },
 };
 use zenoh_result::{bail, zerror, ZResult};
 
-use super::transport::{TransportMulticastInner, TransportMulticastPeer};
+use super::{
+    fec::{FecParity, FecShard},
+    transport::{TransportMulticastInner, TransportMulticastPeer},
+};
 use crate::common::{
     batch::{Decode, RBatch},
     priority::TransportChannelRx,
@@ -66,6 +70,7 @@ impl TransportMulticastInner {
             || join.resolution != peer.resolution
             || join.lease != peer.lease
             || join.ext_qos.is_some() != peer.is_qos()
+            || join.ext_fec != peer.fec()
         {
             let e = format!(
                 "Ignoring Join on {} of peer: {}. Inconsistent parameters.",
@@ -259,15 +264,38 @@ impl TransportMulticastInner {
 
     pub(super) fn read_messages(
         &self,
-        mut batch: RBatch,
+        batch: RBatch,
+        raw: ZSlice,
         locator: Locator,
         batch_size: BatchSize,
         #[cfg(feature = "stats")] transport: &TransportMulticastInner,
+    ) -> ZResult<()> {
+        self.read_batch(
+            batch,
+            Some(raw),
+            &locator,
+            batch_size,
+            #[cfg(feature = "stats")]
+            transport,
+        )
+    }
+
+    // Reads the messages of a batch, whose bytes as received are `raw` unless the batch has
+    // already been handled by the FEC
+    fn read_batch(
+        &self,
+        mut batch: RBatch,
+        mut raw: Option<ZSlice>,
+        locator: &Locator,
+        batch_size: BatchSize,
+        #[cfg(feature = "stats")] transport: &TransportMulticastInner,
     ) -> ZResult<()> {
         while !batch.is_empty() {
             let msg: TransportMessage = batch
                 .decode()
                 .map_err(|_| zerror!("{}: decoding error", locator))?;
+            // Only the first message of a batch may be the tag of a FEC data shard
+            let raw = raw.take();
 
             tracing::trace!("Received: {:?}", msg);
 
@@ -277,7 +305,7 @@ impl TransportMulticastInner {
             }
 
             let r_guard = zread!(self.peers);
-            match r_guard.get(&locator) {
+            match r_guard.get(locator) {
                 Some(peer) => {
                     peer.set_active();
                     match msg.body {
@@ -285,11 +313,57 @@ impl TransportMulticastInner {
                         TransportBody::Fragment(fragment) => {
                             self.handle_fragment(fragment, peer)?
                         }
-                        TransportBody::Join(join) => self.handle_join_from_peer(join, peer)?,
+                        TransportBody::Join(join) => {
+                            self.handle_join_from_peer(join, peer)?;
+                            // The batches held by the FEC are delivered at the latest on the
+                            // next Join, e.g. if all the parity shards of a group are lost
+                            if let Some(fec) = peer.fec.clone() {
+                                drop(r_guard);
+                                let ready = zlock!(fec).flush();
+                                self.read_fec_batches(
+                                    ready,
+                                    locator,
+                                    batch_size,
+                                    #[cfg(feature = "stats")]
+                                    transport,
+                                )?;
+                            }
+                        }
                         TransportBody::KeepAlive(KeepAlive { .. }) => {}
                         TransportBody::Close(Close { reason, .. }) => {
                             drop(r_guard);
-                            self.del_peer(&locator, reason)?;
+                            self.del_peer(locator, reason)?;
+                        }
+                        TransportBody::OAM(oam) if oam.id == oam::id::OAM_FEC_SHARD => {
+                            // The data shards rebuilt or held by the FEC have already been tagged
+                            let (Some(fec), Some(raw)) = (peer.fec.clone(), raw) else {
+                                continue;
+                            };
+                            drop(r_guard);
+                            let shard = FecShard::try_from(&oam)?;
+                            let ready = zlock!(fec).shard(shard, raw, batch);
+                            return self.read_fec_batches(
+                                ready,
+                                locator,
+                                batch_size,
+                                #[cfg(feature = "stats")]
+                                transport,
+                            );
+                        }
+                        TransportBody::OAM(oam) if oam.id == oam::id::OAM_FEC_PARITY => {
+                            let Some(fec) = peer.fec.clone() else {
+                                continue;
+                            };
+                            drop(r_guard);
+                            let parity = FecParity::try_from(&oam)?;
+                            let ready = zlock!(fec).parity(parity);
+                            self.read_fec_batches(
+                                ready,
+                                locator,
+                                batch_size,
+                                #[cfg(feature = "stats")]
+                                transport,
+                            )?;
                         }
                         _ => {
                             tracing::debug!(
@@ -303,7 +377,7 @@ impl TransportMulticastInner {
                 None => {
                     drop(r_guard);
                     if let TransportBody::Join(join) = msg.body {
-                        self.handle_join_from_unknown(join, &locator, batch_size)?;
+                        self.handle_join_from_unknown(join, locator, batch_size)?;
                     }
                 }
             }
@@ -311,4 +385,49 @@ impl TransportMulticastInner {
 
         Ok(())
     }
+
+    /// Reads the batches held by the expired FEC groups of the peers.
+    pub(super) fn expire_fec(
+        &self,
+        batch_size: BatchSize,
+        #[cfg(feature = "stats")] transport: &TransportMulticastInner,
+    ) -> ZResult<()> {
+        let fecs: Vec<_> = zread!(self.peers)
+            .iter()
+            .filter_map(|(locator, peer)| Some((locator.clone(), peer.fec.clone()?)))
+            .collect();
+        let now = Instant::now();
+        for (locator, fec) in fecs {
+            let ready = zlock!(fec).expire(now);
+            self.read_fec_batches(
+                ready,
+                &locator,
+                batch_size,
+                #[cfg(feature = "stats")]
+                transport,
+            )?;
+        }
+        Ok(())
+    }
+
+    // Reads the batches delivered in order by the FEC
+    fn read_fec_batches(
+        &self,
+        batches: Vec<RBatch>,
+        locator: &Locator,
+        batch_size: BatchSize,
+        #[cfg(feature = "stats")] transport: &TransportMulticastInner,
+    ) -> ZResult<()> {
+        for batch in batches {
+            self.read_batch(
+                batch,
+                None,
+                locator,
+                batch_size,
+                #[cfg(feature = "stats")]
+                transport,
+            )?;
+        }
+        Ok(())
+    }
 }
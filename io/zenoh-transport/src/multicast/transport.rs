@@ -15,23 +15,24 @@ use std::{
     collections::HashMap,
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc, RwLock,
+        Arc, Mutex, RwLock,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use tokio_util::sync::CancellationToken;
-use zenoh_core::{zcondfeat, zread, zwrite};
+use zenoh_core::{zcondfeat, zlock, zread, zwrite};
 use zenoh_link::{Link, Locator};
 use zenoh_protocol::{
     core::{Bits, Field, Priority, Resolution, WhatAmI, ZenohIdProto},
-    transport::{batch_size, close, Close, Join, TransportMessage},
+    transport::{batch_size, close, join, Close, Join, TransportMessage},
 };
 use zenoh_result::{bail, ZResult};
 use zenoh_task::TaskController;
 
 use super::{
     common::priority::{TransportPriorityRx, TransportPriorityTx},
+    fec::FecRx,
     link::{TransportLinkMulticastConfigUniversal, TransportLinkMulticastUniversal},
 };
 #[cfg(feature = "shared-memory")]
@@ -60,6 +61,8 @@ pub(super) struct TransportMulticastPeer {
     pub(super) is_active: Arc<AtomicBool>,
     token: CancellationToken,
     pub(super) priority_rx: Box<[TransportPriorityRx]>,
+    // The FEC of the batches received from the peer, if advertised in its Join
+    pub(super) fec: Option<Arc<Mutex<FecRx>>>,
     pub(super) handler: Arc<dyn TransportPeerEventHandler>,
 }
 
@@ -71,6 +74,10 @@ impl TransportMulticastPeer {
     pub(super) fn is_qos(&self) -> bool {
         self.priority_rx.len() == Priority::NUM
     }
+
+    pub(super) fn fec(&self) -> Option<join::ext::Fec> {
+        self.fec.as_ref().map(|fec| zlock!(fec).ext())
+    }
 }
 
 #[derive(Clone)]
@@ -332,6 +339,18 @@ impl TransportMulticastInner {
         let mut link = Link::new_multicast(&self.get_link().link);
         link.dst = locator.clone();
 
+        let fec = match join
+            .ext_fec
+            .map(|ext| FecRx::new(ext, self.get_link().config.batch))
+            .transpose()
+        {
+            Ok(fec) => fec.map(|fec| Arc::new(Mutex::new(fec))),
+            Err(e) => {
+                tracing::debug!("Ignoring Join on {} of peer: {}. {}", locator, join.zid, e);
+                return Ok(());
+            }
+        };
+
         let is_shm = zcondfeat!("shared-memory", join.ext_shm.is_some(), false);
         let peer = TransportPeer {
             zid: join.zid,
@@ -366,7 +385,7 @@ impl TransportMulticastInner {
         let priority_rx = priority_rx.into_boxed_slice();
 
         tracing::debug!(
-                "New transport joined on {}: zid {}, whatami {}, resolution {:?}, locator {}, is_qos {}, is_shm {}, initial sn: {:?}",
+                "New transport joined on {}: zid {}, whatami {}, resolution {:?}, locator {}, is_qos {}, is_shm {}, fec {:?}, initial sn: {:?}",
                 self.locator,
                 peer.zid,
                 peer.whatami,
@@ -374,6 +393,7 @@ impl TransportMulticastInner {
                 locator,
                 peer.is_qos,
                 is_shm,
+                join.ext_fec,
                 next_sns,
             );
 
@@ -416,6 +436,7 @@ impl TransportMulticastInner {
             is_active,
             token,
             priority_rx,
+            fec,
             handler,
         };
         zwrite!(self.peers).insert(locator.clone(), peer);
@@ -423,6 +444,14 @@ impl TransportMulticastInner {
         Ok(())
     }
 
+    /// The earliest time the batches held by the FEC of a peer have to be delivered at.
+    pub(super) fn fec_deadline(&self) -> Option<Instant> {
+        zread!(self.peers)
+            .values()
+            .filter_map(|peer| zlock!(peer.fec.as_ref()?).deadline())
+            .min()
+    }
+
     pub(super) fn del_peer(&self, locator: &Locator, reason: u8) -> ZResult<()> {
         let mut guard = zwrite!(self.peers);
         if let Some(peer) = guard.remove(locator) {
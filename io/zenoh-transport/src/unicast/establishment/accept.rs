@@ -11,7 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use rand::Rng;
@@ -40,7 +40,9 @@ use crate::shm::TransportShmConfig;
 use crate::{
     common::batch::BatchConfig,
     unicast::{
-        establishment::{compute_sn, ext, AcceptFsm, Cookie, Zenoh080Cookie},
+        establishment::{
+            compute_sn, ext, metadata_ext, metadata_from_ext, AcceptFsm, Cookie, Zenoh080Cookie,
+        },
         link::{
             LinkUnicastWithOpenAck, TransportLinkUnicast, TransportLinkUnicastConfig,
             TransportLinkUnicastDirection,
@@ -61,6 +63,7 @@ struct StateTransport {
     #[cfg(feature = "shared-memory")]
     ext_shm: ext::shm::StateAccept,
     ext_lowlatency: ext::lowlatency::StateAccept,
+    ext_arq: ext::arq::StateAccept,
 }
 
 #[cfg(any(feature = "transport_auth", feature = "transport_compression"))]
@@ -115,12 +118,14 @@ struct RecvOpenSynOut {
     other_initial_sn: TransportSn,
     #[cfg(feature = "auth_usrpwd")]
     other_auth_id: UsrPwdId,
+    other_metadata: Option<String>,
 }
 
 // OpenAck
 struct SendOpenAckIn {
     mine_zid: ZenohIdProto,
     mine_lease: Duration,
+    mine_metadata: Option<String>,
     other_zid: ZenohIdProto,
 }
 struct SendOpenAckOut {
@@ -143,6 +148,7 @@ struct AcceptLink<'a> {
     ext_lowlatency: ext::lowlatency::LowLatencyFsm<'a>,
     #[cfg(feature = "transport_compression")]
     ext_compression: ext::compression::CompressionFsm<'a>,
+    ext_arq: ext::arq::ArqFsm<'a>,
 }
 
 #[async_trait]
@@ -264,7 +270,19 @@ impl<'a, 'b: 'a> AcceptFsm for &'a mut AcceptLink<'b> {
         // Extension Compression
         #[cfg(feature = "transport_compression")]
         self.ext_compression
-            .recv_init_syn((&mut state.link.ext_compression, init_syn.ext_compression))
+            .recv_init_syn((
+                &mut state.link.ext_compression,
+                (
+                    init_syn.ext_compression,
+                    init_syn.ext_compression_algorithms,
+                ),
+            ))
+            .await
+            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+
+        // Extension Arq
+        self.ext_arq
+            .recv_init_syn((&mut state.transport.ext_arq, init_syn.ext_arq))
             .await
             .map_err(|e| (e, Some(close::reason::GENERIC)))?;
 
@@ -330,16 +348,23 @@ impl<'a, 'b: 'a> AcceptFsm for &'a mut AcceptLink<'b> {
             .await
             .map_err(|e| (e, Some(close::reason::GENERIC)))?;
 
-        // Extension MultiLink
-        let ext_compression = zcondfeat!(
+        // Extension Compression
+        let (ext_compression, ext_compression_algorithms) = zcondfeat!(
             "transport_compression",
             self.ext_compression
                 .send_init_ack(&state.link.ext_compression)
                 .await
                 .map_err(|e| (e, Some(close::reason::GENERIC)))?,
-            None
+            (None, None)
         );
 
+        // Extension Arq
+        let ext_arq = self
+            .ext_arq
+            .send_init_ack(&state.transport.ext_arq)
+            .await
+            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+
         // Create the cookie
         let cookie_nonce: u64 = zasynclock!(self.prng).gen();
         let cookie = Cookie {
@@ -358,6 +383,7 @@ impl<'a, 'b: 'a> AcceptFsm for &'a mut AcceptLink<'b> {
             ext_lowlatency: state.transport.ext_lowlatency,
             #[cfg(feature = "transport_compression")]
             ext_compression: state.link.ext_compression,
+            ext_arq: state.transport.ext_arq,
         };
 
         let mut encrypted = vec![];
@@ -391,6 +417,8 @@ impl<'a, 'b: 'a> AcceptFsm for &'a mut AcceptLink<'b> {
             ext_mlink,
             ext_lowlatency,
             ext_compression,
+            ext_arq,
+            ext_compression_algorithms,
         }
         .into();
 
@@ -491,6 +519,7 @@ impl<'a, 'b: 'a> AcceptFsm for &'a mut AcceptLink<'b> {
                 #[cfg(feature = "shared-memory")]
                 ext_shm: cookie.ext_shm,
                 ext_lowlatency: cookie.ext_lowlatency,
+                ext_arq: cookie.ext_arq,
             },
             #[cfg(any(feature = "transport_auth", feature = "transport_compression"))]
             link: StateLink {
@@ -552,6 +581,7 @@ impl<'a, 'b: 'a> AcceptFsm for &'a mut AcceptLink<'b> {
             other_initial_sn: open_syn.initial_sn,
             #[cfg(feature = "auth_usrpwd")]
             other_auth_id: user_password_id,
+            other_metadata: metadata_from_ext(open_syn.ext_metadata),
         };
         Ok((state, output))
     }
@@ -618,6 +648,9 @@ impl<'a, 'b: 'a> AcceptFsm for &'a mut AcceptLink<'b> {
             None
         );
 
+        // Extension Metadata
+        let ext_metadata = metadata_ext(input.mine_metadata.as_deref());
+
         // Build OpenAck message
         let mine_initial_sn =
             compute_sn(input.mine_zid, input.other_zid, state.transport.resolution);
@@ -631,6 +664,7 @@ impl<'a, 'b: 'a> AcceptFsm for &'a mut AcceptLink<'b> {
             ext_mlink,
             ext_lowlatency,
             ext_compression,
+            ext_metadata,
         };
 
         // Do not send the OpenAck right now since we might still incur in MAX_LINKS error
@@ -650,16 +684,26 @@ pub(crate) async fn accept_link(link: LinkUnicast, manager: &TransportManager) -
     let direction = TransportLinkUnicastDirection::Inbound;
     let mtu = link.get_mtu();
     let is_streamed = link.is_streamed();
+    let is_reliable = link.is_reliable();
+    #[cfg(feature = "transport_compression")]
+    let is_compression = manager.config.unicast.is_compression
+        && manager
+            .config
+            .unicast
+            .compression
+            .is_used_on(link.get_src().protocol().as_str());
     let config = TransportLinkUnicastConfig {
         direction,
         batch: BatchConfig {
             mtu,
             is_streamed,
             #[cfg(feature = "transport_compression")]
-            is_compression: false,
+            compression: None,
         },
         priorities: None,
         reliability: None,
+        rtt: None,
+        arq: None,
     };
     let mut link = TransportLinkUnicast::new(link, config);
     let mut fsm = AcceptLink {
@@ -680,7 +724,8 @@ pub(crate) async fn accept_link(link: LinkUnicast, manager: &TransportManager) -
         ext_auth: manager.state.unicast.authenticator.fsm(&manager.prng),
         ext_lowlatency: ext::lowlatency::LowLatencyFsm::new(),
         #[cfg(feature = "transport_compression")]
-        ext_compression: ext::compression::CompressionFsm::new(),
+        ext_compression: ext::compression::CompressionFsm::new(&manager.config.unicast.compression),
+        ext_arq: ext::arq::ArqFsm::new(),
     };
 
     // Init handshake
@@ -719,6 +764,10 @@ pub(crate) async fn accept_link(link: LinkUnicast, manager: &TransportManager) -
                 ext_lowlatency: ext::lowlatency::StateAccept::new(
                     manager.config.unicast.is_lowlatency,
                 ),
+                ext_arq: ext::arq::StateAccept::new(
+                    (manager.config.unicast.is_arq && !is_reliable)
+                        .then_some(manager.config.unicast.arq_window as u64),
+                ),
             },
             #[cfg(any(feature = "transport_auth", feature = "transport_compression"))]
             link: StateLink {
@@ -729,9 +778,7 @@ pub(crate) async fn accept_link(link: LinkUnicast, manager: &TransportManager) -
                     .authenticator
                     .accept(&mut *zasynclock!(manager.prng)),
                 #[cfg(feature = "transport_compression")]
-                ext_compression: ext::compression::StateAccept::new(
-                    manager.config.unicast.is_compression,
-                ),
+                ext_compression: ext::compression::StateAccept::new(is_compression),
             },
         };
 
@@ -754,17 +801,20 @@ pub(crate) async fn accept_link(link: LinkUnicast, manager: &TransportManager) -
         };
         step!(fsm.send_init_ack((state, iack_in)).await)
     };
+    let init_ack_sent = Instant::now();
 
     // Open handshake
     let osyn_in = RecvOpenSynIn {
         cookie_nonce: iack_out.cookie_nonce,
     };
     let (mut state, osyn_out) = step!(fsm.recv_open_syn(osyn_in).await);
+    let rtt = init_ack_sent.elapsed();
 
     // Create the OpenAck but not send it yet
     let oack_in = SendOpenAckIn {
         mine_zid: manager.config.zid,
         mine_lease: manager.config.unicast.lease,
+        mine_metadata: manager.config.metadata.clone(),
         other_zid: osyn_out.other_zid,
     };
     let oack_out = step!(fsm.send_open_ack((&mut state, oack_in)).await);
@@ -786,6 +836,7 @@ pub(crate) async fn accept_link(link: LinkUnicast, manager: &TransportManager) -
         is_lowlatency: state.transport.ext_lowlatency.is_lowlatency(),
         #[cfg(feature = "auth_usrpwd")]
         auth_id: osyn_out.other_auth_id,
+        metadata: osyn_out.other_metadata,
     };
 
     let a_config = TransportLinkUnicastConfig {
@@ -794,10 +845,17 @@ pub(crate) async fn accept_link(link: LinkUnicast, manager: &TransportManager) -
             mtu: state.transport.batch_size,
             is_streamed,
             #[cfg(feature = "transport_compression")]
-            is_compression: state.link.ext_compression.is_compression(),
+            compression: state
+                .link
+                .ext_compression
+                .algorithm()
+                .map(|a| manager.config.unicast.compression.batch(a))
+                .transpose()?,
         },
         priorities: state.transport.ext_qos.priorities(),
         reliability: state.transport.ext_qos.reliability(),
+        rtt: Some(rtt),
+        arq: state.transport.ext_arq.window(),
     };
     let a_link = link.reconfigure(a_config);
     let s_link = format!("{:?}", a_link);
@@ -0,0 +1,206 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use core::marker::PhantomData;
+
+use async_trait::async_trait;
+use zenoh_buffers::{
+    reader::{DidntRead, Reader},
+    writer::{DidntWrite, Writer},
+};
+use zenoh_codec::{RCodec, WCodec, Zenoh080};
+use zenoh_protocol::transport::init;
+use zenoh_result::Error as ZError;
+
+use crate::unicast::establishment::{AcceptFsm, OpenFsm};
+
+// Extension Fsm
+pub(crate) struct ArqFsm<'a> {
+    _a: PhantomData<&'a ()>,
+}
+
+impl<'a> ArqFsm<'a> {
+    pub(crate) const fn new() -> Self {
+        Self { _a: PhantomData }
+    }
+}
+
+/// Negotiates the smallest of the two retransmission windows. ARQ is disabled if either side
+/// did not propose a window.
+fn negotiate(mine: Option<u64>, other: Option<init::ext::Arq>) -> Option<u64> {
+    match (mine, other) {
+        (Some(mine), Some(other)) if other.value > 0 => Some(mine.min(other.value)),
+        _ => None,
+    }
+}
+
+/*************************************/
+/*              OPEN                 */
+/*************************************/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct StateOpen {
+    window: Option<u64>,
+}
+
+impl StateOpen {
+    pub(crate) const fn new(window: Option<u64>) -> Self {
+        Self { window }
+    }
+
+    pub(crate) const fn window(&self) -> Option<u64> {
+        self.window
+    }
+}
+
+#[async_trait]
+impl<'a> OpenFsm for &'a ArqFsm<'a> {
+    type Error = ZError;
+
+    type SendInitSynIn = &'a StateOpen;
+    type SendInitSynOut = Option<init::ext::Arq>;
+    async fn send_init_syn(
+        self,
+        state: Self::SendInitSynIn,
+    ) -> Result<Self::SendInitSynOut, Self::Error> {
+        let output = state.window.map(init::ext::Arq::new);
+        Ok(output)
+    }
+
+    type RecvInitAckIn = (&'a mut StateOpen, Option<init::ext::Arq>);
+    type RecvInitAckOut = ();
+    async fn recv_init_ack(
+        self,
+        input: Self::RecvInitAckIn,
+    ) -> Result<Self::RecvInitAckOut, Self::Error> {
+        let (state, other_ext) = input;
+        state.window = negotiate(state.window, other_ext);
+        Ok(())
+    }
+
+    type SendOpenSynIn = &'a StateOpen;
+    type SendOpenSynOut = ();
+    async fn send_open_syn(
+        self,
+        _state: Self::SendOpenSynIn,
+    ) -> Result<Self::SendOpenSynOut, Self::Error> {
+        Ok(())
+    }
+
+    type RecvOpenAckIn = &'a mut StateOpen;
+    type RecvOpenAckOut = ();
+    async fn recv_open_ack(
+        self,
+        _state: Self::RecvOpenAckIn,
+    ) -> Result<Self::RecvOpenAckOut, Self::Error> {
+        Ok(())
+    }
+}
+
+/*************************************/
+/*            ACCEPT                 */
+/*************************************/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct StateAccept {
+    window: Option<u64>,
+}
+
+impl StateAccept {
+    pub(crate) const fn new(window: Option<u64>) -> Self {
+        Self { window }
+    }
+
+    pub(crate) const fn window(&self) -> Option<u64> {
+        self.window
+    }
+
+    #[cfg(test)]
+    pub(crate) fn rand() -> Self {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        Self::new(
+            rng.gen_bool(0.5)
+                .then(|| rng.gen_range(1..=u16::MAX as u64)),
+        )
+    }
+}
+
+// Codec
+impl<W> WCodec<&StateAccept, &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &StateAccept) -> Self::Output {
+        // A zero window means that ARQ is not used
+        self.write(&mut *writer, x.window.unwrap_or(0))?;
+        Ok(())
+    }
+}
+
+impl<R> RCodec<StateAccept, &mut R> for Zenoh080
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<StateAccept, Self::Error> {
+        let window: u64 = self.read(&mut *reader)?;
+        let window = (window > 0).then_some(window);
+        Ok(StateAccept { window })
+    }
+}
+
+#[async_trait]
+impl<'a> AcceptFsm for &'a ArqFsm<'a> {
+    type Error = ZError;
+
+    type RecvInitSynIn = (&'a mut StateAccept, Option<init::ext::Arq>);
+    type RecvInitSynOut = ();
+    async fn recv_init_syn(
+        self,
+        input: Self::RecvInitSynIn,
+    ) -> Result<Self::RecvInitSynOut, Self::Error> {
+        let (state, other_ext) = input;
+        state.window = negotiate(state.window, other_ext);
+        Ok(())
+    }
+
+    type SendInitAckIn = &'a StateAccept;
+    type SendInitAckOut = Option<init::ext::Arq>;
+    async fn send_init_ack(
+        self,
+        state: Self::SendInitAckIn,
+    ) -> Result<Self::SendInitAckOut, Self::Error> {
+        let output = state.window.map(init::ext::Arq::new);
+        Ok(output)
+    }
+
+    type RecvOpenSynIn = &'a mut StateAccept;
+    type RecvOpenSynOut = ();
+    async fn recv_open_syn(
+        self,
+        _state: Self::RecvOpenSynIn,
+    ) -> Result<Self::RecvOpenSynOut, Self::Error> {
+        Ok(())
+    }
+
+    type SendOpenAckIn = &'a StateAccept;
+    type SendOpenAckOut = ();
+    async fn send_open_ack(
+        self,
+        _state: Self::SendOpenAckIn,
+    ) -> Result<Self::SendOpenAckOut, Self::Error> {
+        Ok(())
+    }
+}
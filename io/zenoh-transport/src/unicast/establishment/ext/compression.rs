@@ -11,8 +11,6 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use core::marker::PhantomData;
-
 use async_trait::async_trait;
 use zenoh_buffers::{
     reader::{DidntRead, Reader},
@@ -20,18 +18,56 @@ use zenoh_buffers::{
 };
 use zenoh_codec::{RCodec, WCodec, Zenoh080};
 use zenoh_protocol::transport::{init, open};
-use zenoh_result::Error as ZError;
+use zenoh_result::{zerror, Error as ZError};
+
+use crate::{
+    common::compression::{CompressionAlgorithm, CompressionConfig},
+    unicast::establishment::{AcceptFsm, OpenFsm},
+};
 
-use crate::unicast::establishment::{AcceptFsm, OpenFsm};
+type InitExts = (
+    Option<init::ext::Compression>,
+    Option<init::ext::CompressionAlgorithms>,
+);
 
 // Extension Fsm
 pub(crate) struct CompressionFsm<'a> {
-    _a: PhantomData<&'a ()>,
+    config: &'a CompressionConfig,
 }
 
 impl<'a> CompressionFsm<'a> {
-    pub(crate) const fn new() -> Self {
-        Self { _a: PhantomData }
+    pub(crate) const fn new(config: &'a CompressionConfig) -> Self {
+        Self { config }
+    }
+
+    /// Returns the extensions offering the `algorithms` with the zstd dictionary of the config.
+    /// The legacy extension is kept for the peers only supporting LZ4.
+    fn to_exts<'b>(
+        &self,
+        algorithms: impl IntoIterator<Item = &'b CompressionAlgorithm>,
+    ) -> InitExts {
+        let mask = algorithms.into_iter().fold(0, |mask, a| mask | a.mask());
+        let legacy =
+            (mask & CompressionAlgorithm::Lz4.mask() != 0).then_some(init::ext::Compression::new());
+        let ext = (mask != 0).then(|| {
+            init::ext::CompressionAlgorithms::new(
+                mask as u64 | (self.config.fingerprint() as u64) << 32,
+            )
+        });
+        (legacy, ext)
+    }
+
+    /// Returns the first algorithm of the config supported by the peer.
+    fn select(&self, (legacy, ext): InitExts) -> Option<CompressionAlgorithm> {
+        let (mask, fingerprint) = match (legacy, ext) {
+            (_, Some(ext)) => (ext.value as u8, (ext.value >> 32) as u32),
+            (Some(_), None) => (CompressionAlgorithm::Lz4.mask(), 0),
+            (None, None) => return None,
+        };
+        self.config.algorithms.iter().copied().find(|a| {
+            mask & a.mask() != 0
+                && (*a != CompressionAlgorithm::Zstd || fingerprint == self.config.fingerprint())
+        })
     }
 }
 
@@ -41,15 +77,19 @@ impl<'a> CompressionFsm<'a> {
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) struct StateOpen {
     is_compression: bool,
+    algorithm: Option<CompressionAlgorithm>,
 }
 
 impl StateOpen {
     pub(crate) const fn new(is_compression: bool) -> Self {
-        Self { is_compression }
+        Self {
+            is_compression,
+            algorithm: None,
+        }
     }
 
-    pub(crate) const fn is_compression(&self) -> bool {
-        self.is_compression
+    pub(crate) const fn algorithm(&self) -> Option<CompressionAlgorithm> {
+        self.algorithm
     }
 }
 
@@ -58,25 +98,38 @@ impl<'a> OpenFsm for &'a CompressionFsm<'a> {
     type Error = ZError;
 
     type SendInitSynIn = &'a StateOpen;
-    type SendInitSynOut = Option<init::ext::Compression>;
+    type SendInitSynOut = InitExts;
     async fn send_init_syn(
         self,
         state: Self::SendInitSynIn,
     ) -> Result<Self::SendInitSynOut, Self::Error> {
-        let output = state
-            .is_compression
-            .then_some(init::ext::Compression::new());
-        Ok(output)
+        if !state.is_compression {
+            return Ok((None, None));
+        }
+        Ok(self.to_exts(&self.config.algorithms))
     }
 
-    type RecvInitAckIn = (&'a mut StateOpen, Option<init::ext::Compression>);
+    type RecvInitAckIn = (&'a mut StateOpen, InitExts);
     type RecvInitAckOut = ();
     async fn recv_init_ack(
         self,
         input: Self::RecvInitAckIn,
     ) -> Result<Self::RecvInitAckOut, Self::Error> {
         let (state, other_ext) = input;
-        state.is_compression &= other_ext.is_some();
+        if !state.is_compression {
+            return Ok(());
+        }
+        state.algorithm = match other_ext {
+            // The accepting side selected one of the offered algorithms
+            (_, Some(ext)) => {
+                let algorithm = self.select((None, Some(ext)));
+                if algorithm.map(|a| a.mask() as u64) != Some(ext.value & 0xff) {
+                    return Err(zerror!("Invalid compression algorithm: {:#x}", ext.value).into());
+                }
+                algorithm
+            }
+            other_ext => self.select(other_ext),
+        };
         Ok(())
     }
 
@@ -105,22 +158,32 @@ impl<'a> OpenFsm for &'a CompressionFsm<'a> {
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) struct StateAccept {
     is_compression: bool,
+    algorithm: Option<CompressionAlgorithm>,
 }
 
 impl StateAccept {
     pub(crate) const fn new(is_compression: bool) -> Self {
-        Self { is_compression }
+        Self {
+            is_compression,
+            algorithm: None,
+        }
     }
 
-    pub(crate) const fn is_compression(&self) -> bool {
-        self.is_compression
+    pub(crate) const fn algorithm(&self) -> Option<CompressionAlgorithm> {
+        self.algorithm
     }
 
     #[cfg(test)]
     pub(crate) fn rand() -> Self {
-        use rand::Rng;
+        use rand::{seq::SliceRandom, Rng};
         let mut rng = rand::thread_rng();
-        Self::new(rng.gen_bool(0.5))
+        let algorithm = rng
+            .gen_bool(0.5)
+            .then(|| *CompressionAlgorithm::ALL.choose(&mut rng).unwrap());
+        Self {
+            is_compression: algorithm.is_some(),
+            algorithm,
+        }
     }
 }
 
@@ -132,8 +195,9 @@ where
     type Output = Result<(), DidntWrite>;
 
     fn write(self, writer: &mut W, x: &StateAccept) -> Self::Output {
-        let is_compression = u8::from(x.is_compression);
-        self.write(&mut *writer, is_compression)?;
+        // Zero means that compression is not used
+        let algorithm = x.algorithm.map_or(0, |a| a as u8 + 1);
+        self.write(&mut *writer, algorithm)?;
         Ok(())
     }
 }
@@ -145,9 +209,15 @@ where
     type Error = DidntRead;
 
     fn read(self, reader: &mut R) -> Result<StateAccept, Self::Error> {
-        let is_compression: u8 = self.read(&mut *reader)?;
-        let is_compression = is_compression == 1;
-        Ok(StateAccept { is_compression })
+        let algorithm: u8 = self.read(&mut *reader)?;
+        let algorithm = match algorithm {
+            0 => None,
+            id => Some(CompressionAlgorithm::try_from(id - 1).map_err(|_| DidntRead)?),
+        };
+        Ok(StateAccept {
+            is_compression: algorithm.is_some(),
+            algorithm,
+        })
     }
 }
 
@@ -155,27 +225,26 @@ where
 impl<'a> AcceptFsm for &'a CompressionFsm<'a> {
     type Error = ZError;
 
-    type RecvInitSynIn = (&'a mut StateAccept, Option<init::ext::Compression>);
+    type RecvInitSynIn = (&'a mut StateAccept, InitExts);
     type RecvInitSynOut = ();
     async fn recv_init_syn(
         self,
         input: Self::RecvInitSynIn,
     ) -> Result<Self::RecvInitSynOut, Self::Error> {
         let (state, other_ext) = input;
-        state.is_compression &= other_ext.is_some();
+        if state.is_compression {
+            state.algorithm = self.select(other_ext);
+        }
         Ok(())
     }
 
     type SendInitAckIn = &'a StateAccept;
-    type SendInitAckOut = Option<init::ext::Compression>;
+    type SendInitAckOut = InitExts;
     async fn send_init_ack(
         self,
         state: Self::SendInitAckIn,
     ) -> Result<Self::SendInitAckOut, Self::Error> {
-        let output = state
-            .is_compression
-            .then_some(init::ext::Compression::new());
-        Ok(output)
+        Ok(self.to_exts(state.algorithm.as_ref()))
     }
 
     type RecvOpenSynIn = (&'a mut StateAccept, Option<open::ext::Compression>);
@@ -11,6 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+pub(crate) mod arq;
 #[cfg(feature = "transport_auth")]
 pub mod auth;
 #[cfg(feature = "transport_compression")]
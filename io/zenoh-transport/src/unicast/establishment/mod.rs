@@ -22,9 +22,10 @@ use sha3::{
     digest::{ExtendableOutput, Update, XofReader},
     Shake128,
 };
+use zenoh_buffers::{buffer::SplitBuffer, ZBuf};
 use zenoh_protocol::{
     core::{Field, Resolution, ZenohIdProto},
-    transport::TransportSn,
+    transport::{open::ext::Metadata, TransportSn},
 };
 
 use crate::common::seq_num;
@@ -116,3 +117,22 @@ pub(super) fn compute_sn(
     hasher.finalize_xof().read(&mut array);
     TransportSn::from_le_bytes(array) & seq_num::get_mask(resolution.get(Field::FrameSN))
 }
+
+/// Builds the metadata extension advertised when the local configuration opts in.
+///
+/// Like the zid and the whatami exchanged during the establishment, the metadata is declared by the
+/// remote node itself and is not authenticated: it must not be relied upon to grant any access.
+pub(super) fn metadata_ext(metadata: Option<&str>) -> Option<Metadata> {
+    metadata.map(|m| Metadata::new(ZBuf::from(m.as_bytes().to_vec())))
+}
+
+pub(super) fn metadata_from_ext(ext: Option<Metadata>) -> Option<String> {
+    let ext = ext?;
+    match String::from_utf8(ext.value.contiguous().into_owned()) {
+        Ok(metadata) => Some(metadata),
+        Err(e) => {
+            tracing::warn!("Ignoring invalid metadata advertised by peer: {}", e);
+            None
+        }
+    }
+}
@@ -11,7 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use zenoh_buffers::ZSlice;
@@ -37,7 +37,7 @@ use crate::unicast::establishment::ext::auth::UsrPwdId;
 use crate::{
     common::batch::BatchConfig,
     unicast::{
-        establishment::{compute_sn, ext, OpenFsm},
+        establishment::{compute_sn, ext, metadata_ext, metadata_from_ext, OpenFsm},
         link::{
             LinkUnicastWithOpenAck, TransportLinkUnicast, TransportLinkUnicastConfig,
             TransportLinkUnicastDirection,
@@ -58,6 +58,7 @@ struct StateTransport {
     #[cfg(feature = "shared-memory")]
     ext_shm: ext::shm::StateOpen,
     ext_lowlatency: ext::lowlatency::StateOpen,
+    ext_arq: ext::arq::StateOpen,
 }
 
 #[cfg(any(feature = "transport_auth", feature = "transport_compression"))]
@@ -96,6 +97,7 @@ struct SendOpenSynIn {
     mine_lease: Duration,
     other_zid: ZenohIdProto,
     other_cookie: ZSlice,
+    mine_metadata: Option<String>,
     #[cfg(feature = "shared-memory")]
     ext_shm: Option<AuthSegment>,
 }
@@ -110,6 +112,7 @@ struct SendOpenSynOut {
 struct RecvOpenAckOut {
     other_lease: Duration,
     other_initial_sn: TransportSn,
+    other_metadata: Option<String>,
 }
 
 // FSM
@@ -124,6 +127,7 @@ struct OpenLink<'a> {
     ext_lowlatency: ext::lowlatency::LowLatencyFsm<'a>,
     #[cfg(feature = "transport_compression")]
     ext_compression: ext::compression::CompressionFsm<'a>,
+    ext_arq: ext::arq::ArqFsm<'a>,
 }
 
 #[async_trait]
@@ -183,15 +187,22 @@ impl<'a, 'b: 'a> OpenFsm for &'a mut OpenLink<'b> {
             .map_err(|e| (e, Some(close::reason::GENERIC)))?;
 
         // Extension Compression
-        let ext_compression = zcondfeat!(
+        let (ext_compression, ext_compression_algorithms) = zcondfeat!(
             "transport_compression",
             self.ext_compression
                 .send_init_syn(&state.link.ext_compression)
                 .await
                 .map_err(|e| (e, Some(close::reason::GENERIC)))?,
-            None
+            (None, None)
         );
 
+        // Extension Arq
+        let ext_arq = self
+            .ext_arq
+            .send_init_syn(&state.transport.ext_arq)
+            .await
+            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+
         let msg: TransportMessage = InitSyn {
             version: input.mine_version,
             whatami: input.mine_whatami,
@@ -206,6 +217,8 @@ impl<'a, 'b: 'a> OpenFsm for &'a mut OpenLink<'b> {
             ext_mlink,
             ext_lowlatency,
             ext_compression,
+            ext_arq,
+            ext_compression_algorithms,
         }
         .into();
 
@@ -343,7 +356,19 @@ impl<'a, 'b: 'a> OpenFsm for &'a mut OpenLink<'b> {
         // Extension Compression
         #[cfg(feature = "transport_compression")]
         self.ext_compression
-            .recv_init_ack((&mut state.link.ext_compression, init_ack.ext_compression))
+            .recv_init_ack((
+                &mut state.link.ext_compression,
+                (
+                    init_ack.ext_compression,
+                    init_ack.ext_compression_algorithms,
+                ),
+            ))
+            .await
+            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+
+        // Extension Arq
+        self.ext_arq
+            .recv_init_ack((&mut state.transport.ext_arq, init_ack.ext_arq))
             .await
             .map_err(|e| (e, Some(close::reason::GENERIC)))?;
 
@@ -419,6 +444,9 @@ impl<'a, 'b: 'a> OpenFsm for &'a mut OpenLink<'b> {
             None
         );
 
+        // Extension Metadata
+        let ext_metadata = metadata_ext(input.mine_metadata.as_deref());
+
         // Build and send an OpenSyn message
         let mine_initial_sn =
             compute_sn(input.mine_zid, input.other_zid, state.transport.resolution);
@@ -433,6 +461,7 @@ impl<'a, 'b: 'a> OpenFsm for &'a mut OpenLink<'b> {
             ext_mlink,
             ext_lowlatency,
             ext_compression,
+            ext_metadata,
         }
         .into();
 
@@ -535,6 +564,7 @@ impl<'a, 'b: 'a> OpenFsm for &'a mut OpenLink<'b> {
         let output = RecvOpenAckOut {
             other_initial_sn: open_ack.initial_sn,
             other_lease: open_ack.lease,
+            other_metadata: metadata_from_ext(open_ack.ext_metadata),
         };
         Ok(output)
     }
@@ -547,16 +577,26 @@ pub(crate) async fn open_link(
 ) -> ZResult<TransportUnicast> {
     let direction = TransportLinkUnicastDirection::Outbound;
     let is_streamed = link.is_streamed();
+    let is_reliable = link.is_reliable();
+    #[cfg(feature = "transport_compression")]
+    let is_compression = manager.config.unicast.is_compression
+        && manager
+            .config
+            .unicast
+            .compression
+            .is_used_on(link.get_dst().protocol().as_str());
     let config = TransportLinkUnicastConfig {
         direction,
         batch: BatchConfig {
             mtu: link.get_mtu(),
             is_streamed,
             #[cfg(feature = "transport_compression")]
-            is_compression: false, // Perform the exchange Init/Open exchange with no compression
+            compression: None, // Perform the exchange Init/Open exchange with no compression
         },
         priorities: None,
         reliability: None,
+        rtt: None,
+        arq: None,
     };
     let mut link = TransportLinkUnicast::new(link, config);
     let mut fsm = OpenLink {
@@ -574,7 +614,8 @@ pub(crate) async fn open_link(
         ext_auth: manager.state.unicast.authenticator.fsm(&manager.prng),
         ext_lowlatency: ext::lowlatency::LowLatencyFsm::new(),
         #[cfg(feature = "transport_compression")]
-        ext_compression: ext::compression::CompressionFsm::new(),
+        ext_compression: ext::compression::CompressionFsm::new(&manager.config.unicast.compression),
+        ext_arq: ext::arq::ArqFsm::new(),
     };
 
     // Clippy raises a warning because `batch_size::UNICAST` is currently equal to `BatchSize::MAX`.
@@ -601,6 +642,10 @@ pub(crate) async fn open_link(
             ext_shm: ext::shm::StateOpen::new(),
 
             ext_lowlatency: ext::lowlatency::StateOpen::new(manager.config.unicast.is_lowlatency),
+            ext_arq: ext::arq::StateOpen::new(
+                (manager.config.unicast.is_arq && !is_reliable)
+                    .then_some(manager.config.unicast.arq_window as u64),
+            ),
         },
         #[cfg(any(feature = "transport_auth", feature = "transport_compression"))]
         link: StateLink {
@@ -611,9 +656,7 @@ pub(crate) async fn open_link(
                 .authenticator
                 .open(&mut *zasynclock!(manager.prng)),
             #[cfg(feature = "transport_compression")]
-            ext_compression: ext::compression::StateOpen::new(
-                manager.config.unicast.is_compression,
-            ),
+            ext_compression: ext::compression::StateOpen::new(is_compression),
         },
     };
 
@@ -635,9 +678,11 @@ pub(crate) async fn open_link(
         mine_zid: manager.config.zid,
         mine_whatami: manager.config.whatami,
     };
+    let init_syn_sent = Instant::now();
     step!(fsm.send_init_syn((&mut link, &mut state, isyn_in)).await);
 
     let iack_out = step!(fsm.recv_init_ack((&mut link, &mut state)).await);
+    let rtt = init_syn_sent.elapsed();
 
     // Open handshake
     let osyn_in = SendOpenSynIn {
@@ -645,6 +690,7 @@ pub(crate) async fn open_link(
         other_zid: iack_out.other_zid,
         mine_lease: manager.config.unicast.lease,
         other_cookie: iack_out.other_cookie,
+        mine_metadata: manager.config.metadata.clone(),
         #[cfg(feature = "shared-memory")]
         ext_shm: iack_out.ext_shm,
     };
@@ -669,6 +715,7 @@ pub(crate) async fn open_link(
         is_lowlatency: state.transport.ext_lowlatency.is_lowlatency(),
         #[cfg(feature = "auth_usrpwd")]
         auth_id: UsrPwdId(None),
+        metadata: oack_out.other_metadata,
     };
 
     let o_config = TransportLinkUnicastConfig {
@@ -677,10 +724,17 @@ pub(crate) async fn open_link(
             mtu: state.transport.batch_size,
             is_streamed,
             #[cfg(feature = "transport_compression")]
-            is_compression: state.link.ext_compression.is_compression(),
+            compression: state
+                .link
+                .ext_compression
+                .algorithm()
+                .map(|a| manager.config.unicast.compression.batch(a))
+                .transpose()?,
         },
         priorities: state.transport.ext_qos.priorities(),
         reliability: state.transport.ext_qos.reliability(),
+        rtt: Some(rtt),
+        arq: state.transport.ext_arq.window(),
     };
     let o_link = link.reconfigure(o_config);
     let s_link = format!("{:?}", o_link);
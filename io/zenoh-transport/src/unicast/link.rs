@@ -11,7 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use std::{fmt, sync::Arc};
+use std::{fmt, sync::Arc, time::Duration};
 
 use zenoh_buffers::{BBuf, ZSlice, ZSliceBuffer};
 use zenoh_core::zcondfeat;
@@ -37,6 +37,10 @@ pub(crate) struct TransportLinkUnicastConfig {
     pub(crate) batch: BatchConfig,
     pub(crate) priorities: Option<PriorityRange>,
     pub(crate) reliability: Option<Reliability>,
+    // The round-trip time measured during the link establishment
+    pub(crate) rtt: Option<Duration>,
+    // The retransmission window negotiated for ARQ, if any
+    pub(crate) arq: Option<u64>,
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -74,9 +78,10 @@ impl TransportLinkUnicast {
                 "transport_compression",
                 self.config
                     .batch
-                    .is_compression
-                    .then_some(BBuf::with_capacity(
-                        lz4_flex::block::get_maximum_output_size(self.config.batch.mtu as usize),
+                    .compression
+                    .as_ref()
+                    .map(|c| BBuf::with_capacity(
+                        c.max_compressed_len(self.config.batch.mtu as usize)
                     )),
                 None
             ),
@@ -147,18 +152,8 @@ impl TransportLinkUnicastTx {
 
         // tracing::trace!("WBatch: {:?}", batch);
 
-        let res = batch
-            .finalize(self.buffer.as_mut())
-            .map_err(|_| zerror!("{ERR}{self}"))?;
-
-        let bytes = match res {
-            Finalize::Batch => batch.as_slice(),
-            Finalize::Buffer => self
-                .buffer
-                .as_ref()
-                .ok_or_else(|| zerror!("Invalid buffer finalization"))?
-                .as_slice(),
-        };
+        let bytes =
+            finalize(&mut self.buffer, batch).map_err(|_| zerror!("{ERR}{}", self.inner))?;
 
         // tracing::trace!("WBytes: {:02x?}", bytes);
 
@@ -168,11 +163,29 @@ impl TransportLinkUnicastTx {
         Ok(())
     }
 
+    /// Sends `batch` on the link and returns a copy of the bytes written on the link, such that
+    /// they can be retransmitted as they are with [`Self::send_bytes`].
+    pub(crate) async fn send_batch_retained(&mut self, batch: &mut WBatch) -> ZResult<Arc<[u8]>> {
+        const ERR: &str = "Write error on link: ";
+
+        let bytes =
+            finalize(&mut self.buffer, batch).map_err(|_| zerror!("{ERR}{}", self.inner))?;
+        self.inner.link.write_all(bytes).await?;
+
+        Ok(bytes.into())
+    }
+
+    /// Sends the bytes of a batch previously returned by [`Self::send_batch_retained`].
+    pub(crate) async fn send_bytes(&mut self, bytes: &[u8]) -> ZResult<()> {
+        self.inner.link.write_all(bytes).await?;
+        Ok(())
+    }
+
     pub(crate) async fn send(&mut self, msg: &TransportMessage) -> ZResult<usize> {
         const ERR: &str = "Write error on link: ";
 
         // Create the batch for serializing the message
-        let mut batch = WBatch::new(self.inner.config.batch);
+        let mut batch = WBatch::new(self.inner.config.batch.clone());
         batch.encode(msg).map_err(|_| zerror!("{ERR}{self}"))?;
         let len = batch.len() as usize;
         self.send_batch(&mut batch).await?;
@@ -180,6 +193,17 @@ impl TransportLinkUnicastTx {
     }
 }
 
+// Finalizes `batch` and returns the bytes to write on the link
+fn finalize<'a>(buffer: &'a mut Option<BBuf>, batch: &'a mut WBatch) -> ZResult<&'a [u8]> {
+    match batch.finalize(buffer.as_mut())? {
+        Finalize::Batch => Ok(batch.as_slice()),
+        Finalize::Buffer => Ok(buffer
+            .as_ref()
+            .ok_or_else(|| zerror!("Invalid buffer finalization"))?
+            .as_slice()),
+    }
+}
+
 impl fmt::Display for TransportLinkUnicastTx {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.inner)
@@ -232,7 +256,7 @@ impl TransportLinkUnicastRx {
 
         let buffer = ZSlice::new(Arc::new(into), 0, end)
             .map_err(|_| zerror!("{ERR}{self}. ZSlice index(es) out of bounds"))?;
-        let mut batch = RBatch::new(self.config.batch, buffer);
+        let mut batch = RBatch::new(self.config.batch.clone(), buffer);
         batch
             .initialize(buff)
             .map_err(|e| zerror!("{ERR}{self}. {e}."))?;
@@ -290,10 +314,9 @@ impl MaybeOpenAck {
                     // !!! Workaround !!! as the state of the link is set with compression once the OpenSyn is received.
                     // Here we are disabling the compression just to send the OpenAck (that is not supposed to be compressed).
                     // Then then we re-enable it, in case it was enabled, after the OpenAck has been sent.
-                    let compression = self.link.inner.config.batch.is_compression;
-                    self.link.inner.config.batch.is_compression = false;
+                    let compression = self.link.inner.config.batch.compression.take();
                     self.link.send(&msg.into()).await?;
-                    self.link.inner.config.batch.is_compression = compression;
+                    self.link.inner.config.batch.compression = compression;
                 },
                 {
                     self.link.send(&msg.into()).await?;
@@ -25,12 +25,14 @@ use tokio::sync::{Mutex as AsyncMutex, MutexGuard as AsyncMutexGuard};
 use zenoh_config::CompressionUnicastConf;
 #[cfg(feature = "shared-memory")]
 use zenoh_config::ShmConf;
-use zenoh_config::{Config, LinkTxConf, QoSUnicastConf, TransportUnicastConf};
+use zenoh_config::{ArqUnicastConf, Config, LinkTxConf, QoSUnicastConf, TransportUnicastConf};
+#[cfg(feature = "transport_multilink")]
+use zenoh_config::{MultilinkScheduling, MultilinkUnicastConf};
 use zenoh_core::{zasynclock, zcondfeat};
 use zenoh_crypto::PseudoRng;
 use zenoh_link::*;
 use zenoh_protocol::{
-    core::{parameters, ZenohIdProto},
+    core::{parameters, Priority, ZenohIdProto},
     transport::{close, TransportSn},
 };
 use zenoh_result::{bail, zerror, ZResult};
@@ -40,11 +42,14 @@ use zenoh_shm::reader::ShmReader;
 #[cfg(feature = "shared-memory")]
 use super::establishment::ext::shm::AuthUnicast;
 use super::{link::LinkUnicastWithOpenAck, transport_unicast_inner::InitTransportResult};
+#[cfg(feature = "transport_compression")]
+use crate::common::compression::CompressionConfig;
 #[cfg(feature = "transport_auth")]
 use crate::unicast::establishment::ext::auth::Auth;
 #[cfg(feature = "transport_multilink")]
 use crate::unicast::establishment::ext::multilink::MultiLink;
 use crate::{
+    manager::latency_budget_from_conf,
     unicast::{
         lowlatency::transport::TransportUnicastLowlatency,
         transport_unicast_inner::{InitTransportError, TransportUnicastTrait},
@@ -64,13 +69,24 @@ pub struct TransportManagerConfigUnicast {
     pub accept_pending: usize,
     pub max_sessions: usize,
     pub is_qos: bool,
+    pub latency_budget: [Duration; Priority::NUM],
     pub is_lowlatency: bool,
+    pub is_arq: bool,
+    pub arq_window: usize,
+    pub arq_ack_interval: Duration,
+    pub arq_retransmission_timeout: Duration,
     #[cfg(feature = "transport_multilink")]
     pub max_links: usize,
+    #[cfg(feature = "transport_multilink")]
+    pub multilink_scheduling: MultilinkScheduling,
+    #[cfg(feature = "transport_multilink")]
+    pub multilink_failover: bool,
     #[cfg(feature = "shared-memory")]
     pub is_shm: bool,
     #[cfg(feature = "transport_compression")]
     pub is_compression: bool,
+    #[cfg(feature = "transport_compression")]
+    pub compression: CompressionConfig,
 }
 
 pub struct TransportManagerStateUnicast {
@@ -109,15 +125,26 @@ pub struct TransportManagerBuilderUnicast {
     pub(super) accept_pending: usize,
     pub(super) max_sessions: usize,
     pub(super) is_qos: bool,
+    pub(super) latency_budget: [Duration; Priority::NUM],
     #[cfg(feature = "transport_multilink")]
     pub(super) max_links: usize,
+    #[cfg(feature = "transport_multilink")]
+    pub(super) multilink_scheduling: MultilinkScheduling,
+    #[cfg(feature = "transport_multilink")]
+    pub(super) multilink_failover: bool,
     #[cfg(feature = "shared-memory")]
     pub(super) is_shm: bool,
     #[cfg(feature = "transport_auth")]
     pub(super) authenticator: Auth,
     pub(super) is_lowlatency: bool,
+    pub(super) is_arq: bool,
+    pub(super) arq_window: usize,
+    pub(super) arq_ack_interval: Duration,
+    pub(super) arq_retransmission_timeout: Duration,
     #[cfg(feature = "transport_compression")]
     pub(super) is_compression: bool,
+    #[cfg(feature = "transport_compression")]
+    pub(super) compression: CompressionConfig,
 }
 
 impl TransportManagerBuilderUnicast {
@@ -151,17 +178,54 @@ impl TransportManagerBuilderUnicast {
         self
     }
 
+    pub fn latency_budget(mut self, latency_budget: [Duration; Priority::NUM]) -> Self {
+        self.latency_budget = latency_budget;
+        self
+    }
+
     pub fn lowlatency(mut self, is_lowlatency: bool) -> Self {
         self.is_lowlatency = is_lowlatency;
         self
     }
 
+    pub fn arq(mut self, is_arq: bool) -> Self {
+        self.is_arq = is_arq;
+        self
+    }
+
+    pub fn arq_window(mut self, arq_window: usize) -> Self {
+        self.arq_window = arq_window;
+        self
+    }
+
+    pub fn arq_ack_interval(mut self, arq_ack_interval: Duration) -> Self {
+        self.arq_ack_interval = arq_ack_interval;
+        self
+    }
+
+    pub fn arq_retransmission_timeout(mut self, arq_retransmission_timeout: Duration) -> Self {
+        self.arq_retransmission_timeout = arq_retransmission_timeout;
+        self
+    }
+
     #[cfg(feature = "transport_multilink")]
     pub fn max_links(mut self, max_links: usize) -> Self {
         self.max_links = max_links;
         self
     }
 
+    #[cfg(feature = "transport_multilink")]
+    pub fn multilink_scheduling(mut self, multilink_scheduling: MultilinkScheduling) -> Self {
+        self.multilink_scheduling = multilink_scheduling;
+        self
+    }
+
+    #[cfg(feature = "transport_multilink")]
+    pub fn multilink_failover(mut self, multilink_failover: bool) -> Self {
+        self.multilink_failover = multilink_failover;
+        self
+    }
+
     #[cfg(feature = "transport_auth")]
     pub fn authenticator(mut self, authenticator: Auth) -> Self {
         self.authenticator = authenticator;
@@ -180,6 +244,12 @@ impl TransportManagerBuilderUnicast {
         self
     }
 
+    #[cfg(feature = "transport_compression")]
+    pub fn compression_config(mut self, compression: CompressionConfig) -> Self {
+        self.compression = compression;
+        self
+    }
+
     pub async fn from_config(mut self, config: &Config) -> ZResult<TransportManagerBuilderUnicast> {
         self = self.lease(Duration::from_millis(
             *config.transport().link().tx().lease(),
@@ -191,11 +261,25 @@ impl TransportManagerBuilderUnicast {
         self = self.accept_pending(*config.transport().unicast().accept_pending());
         self = self.max_sessions(*config.transport().unicast().max_sessions());
         self = self.qos(*config.transport().unicast().qos().enabled());
+        self = self.latency_budget(latency_budget_from_conf(
+            config.transport().unicast().qos().latency_budget(),
+        ));
         self = self.lowlatency(*config.transport().unicast().lowlatency());
+        self = self.arq(*config.transport().unicast().arq().enabled());
+        self = self.arq_window(*config.transport().unicast().arq().window());
+        self = self.arq_ack_interval(Duration::from_millis(
+            *config.transport().unicast().arq().ack_interval(),
+        ));
+        self = self.arq_retransmission_timeout(Duration::from_millis(
+            *config.transport().unicast().arq().retransmission_timeout(),
+        ));
 
         #[cfg(feature = "transport_multilink")]
         {
             self = self.max_links(*config.transport().unicast().max_links());
+            self =
+                self.multilink_scheduling(*config.transport().unicast().multilink().scheduling());
+            self = self.multilink_failover(*config.transport().unicast().multilink().failover());
         }
         #[cfg(feature = "shared-memory")]
         {
@@ -208,6 +292,9 @@ impl TransportManagerBuilderUnicast {
         #[cfg(feature = "transport_compression")]
         {
             self = self.compression(*config.transport().unicast().compression().enabled());
+            self = self.compression_config(
+                CompressionConfig::from_config(config.transport().unicast().compression()).await?,
+            );
         }
 
         Ok(self)
@@ -221,6 +308,10 @@ impl TransportManagerBuilderUnicast {
         if self.is_qos && self.is_lowlatency {
             bail!("'qos' and 'lowlatency' options are incompatible");
         }
+        // NOTE: the ARQ ACKs report the missing messages of the window with a 64-bit mask
+        if self.is_arq && !(1..=u64::BITS as usize).contains(&self.arq_window) {
+            bail!("'arq.window' must be between 1 and {}", u64::BITS);
+        }
 
         let config = TransportManagerConfigUnicast {
             lease: self.lease,
@@ -229,13 +320,24 @@ impl TransportManagerBuilderUnicast {
             accept_pending: self.accept_pending,
             max_sessions: self.max_sessions,
             is_qos: self.is_qos,
+            latency_budget: self.latency_budget,
             #[cfg(feature = "transport_multilink")]
             max_links: self.max_links,
+            #[cfg(feature = "transport_multilink")]
+            multilink_scheduling: self.multilink_scheduling,
+            #[cfg(feature = "transport_multilink")]
+            multilink_failover: self.multilink_failover,
             #[cfg(feature = "shared-memory")]
             is_shm: self.is_shm,
             is_lowlatency: self.is_lowlatency,
+            is_arq: self.is_arq,
+            arq_window: self.arq_window,
+            arq_ack_interval: self.arq_ack_interval,
+            arq_retransmission_timeout: self.arq_retransmission_timeout,
             #[cfg(feature = "transport_compression")]
             is_compression: self.is_compression,
+            #[cfg(feature = "transport_compression")]
+            compression: self.compression,
         };
 
         let state = TransportManagerStateUnicast {
@@ -270,6 +372,9 @@ impl Default for TransportManagerBuilderUnicast {
         let shm = ShmConf::default();
         #[cfg(feature = "transport_compression")]
         let compression = CompressionUnicastConf::default();
+        #[cfg(feature = "transport_multilink")]
+        let multilink = MultilinkUnicastConf::default();
+        let arq = ArqUnicastConf::default();
 
         Self {
             lease: Duration::from_millis(*link_tx.lease()),
@@ -278,15 +383,26 @@ impl Default for TransportManagerBuilderUnicast {
             accept_pending: *transport.accept_pending(),
             max_sessions: *transport.max_sessions(),
             is_qos: *qos.enabled(),
+            latency_budget: latency_budget_from_conf(qos.latency_budget()),
             #[cfg(feature = "transport_multilink")]
             max_links: *transport.max_links(),
+            #[cfg(feature = "transport_multilink")]
+            multilink_scheduling: *multilink.scheduling(),
+            #[cfg(feature = "transport_multilink")]
+            multilink_failover: *multilink.failover(),
             #[cfg(feature = "shared-memory")]
             is_shm: *shm.enabled(),
             #[cfg(feature = "transport_auth")]
             authenticator: Auth::default(),
             is_lowlatency: *transport.lowlatency(),
+            is_arq: *arq.enabled(),
+            arq_window: *arq.window(),
+            arq_ack_interval: Duration::from_millis(*arq.ack_interval()),
+            arq_retransmission_timeout: Duration::from_millis(*arq.retransmission_timeout()),
             #[cfg(feature = "transport_compression")]
             is_compression: *compression.enabled(),
+            #[cfg(feature = "transport_compression")]
+            compression: CompressionConfig::default(),
         }
     }
 }
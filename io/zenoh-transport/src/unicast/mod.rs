@@ -63,6 +63,7 @@ pub(crate) struct TransportConfigUnicast {
     pub(crate) is_lowlatency: bool,
     #[cfg(feature = "auth_usrpwd")]
     pub(crate) auth_id: UsrPwdId,
+    pub(crate) metadata: Option<String>,
 }
 
 /// [`TransportUnicast`] is the transport handler returned
@@ -127,6 +128,14 @@ impl TransportUnicast {
         Ok(transport.get_auth_ids())
     }
 
+    /// Returns the metadata advertised by the remote peer, in JSON.
+    ///
+    /// The metadata is not authenticated: the remote peer can advertise any value.
+    pub fn get_metadata(&self) -> ZResult<Option<String>> {
+        let transport = self.get_inner()?;
+        Ok(transport.get_config().metadata.clone())
+    }
+
     #[inline(always)]
     pub fn schedule(&self, message: NetworkMessage) -> ZResult<()> {
         let transport = self.get_inner()?;
@@ -0,0 +1,360 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use zenoh_buffers::{reader::HasReader, writer::HasWriter, ZBuf};
+use zenoh_codec::{RCodec, WCodec, Zenoh080};
+use zenoh_core::zlock;
+use zenoh_protocol::{
+    common::ZExtBody,
+    core::{Bits, Priority},
+    transport::{
+        oam::{self, Oam},
+        TransportBody, TransportMessage, TransportSn,
+    },
+};
+use zenoh_result::{zerror, ZResult};
+
+use super::reliability::ReliabilityQueue;
+use crate::{
+    common::{
+        batch::ReliableSn,
+        seq_num::{get_mask, SeqNum},
+    },
+    unicast::link::TransportLinkUnicastTx,
+};
+
+/// The reliable messages of a priority received on the best-effort links of a transport, which
+/// wait for the missing preceding ones to be delivered in order.
+#[derive(Default)]
+pub(super) struct ArqRxQueue {
+    // Locked by the RX tasks to insert and pull the messages, and by the TX tasks to send the ACKs
+    pub(super) queue: Mutex<Option<ReliabilityQueue<TransportBody>>>,
+    // Held by the RX task delivering the messages pulled from the queue, such that they are
+    // delivered in order without holding the queue lock while delivering may wait for a TX task
+    pub(super) delivery: Mutex<()>,
+}
+
+/// Acknowledges the reliable messages of a priority received on a link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct ArqAck {
+    pub(super) priority: Priority,
+    // The next expected sequence number
+    pub(super) next: TransportSn,
+    // The bit `i` is set if `next + i` is missing while a following one has been received
+    pub(super) mask: u64,
+}
+
+impl From<ArqAck> for TransportMessage {
+    fn from(ack: ArqAck) -> Self {
+        let codec = Zenoh080::new();
+        let mut zbuf = ZBuf::empty();
+        let mut writer = zbuf.writer();
+        // Writing on a ZBuf does not fail
+        let _ = codec.write(&mut writer, ack.next);
+        let _ = codec.write(&mut writer, ack.mask);
+
+        TransportBody::OAM(Oam {
+            id: oam::id::OAM_ARQ_ACK,
+            body: ZExtBody::ZBuf(zbuf),
+            ext_qos: oam::ext::QoSType::new(ack.priority),
+        })
+        .into()
+    }
+}
+
+impl TryFrom<&Oam> for ArqAck {
+    type Error = zenoh_result::Error;
+
+    fn try_from(oam: &Oam) -> Result<Self, Self::Error> {
+        let ZExtBody::ZBuf(zbuf) = &oam.body else {
+            return Err(zerror!("Invalid ARQ ACK body: {:?}", oam.body).into());
+        };
+        let codec = Zenoh080::new();
+        let mut reader = zbuf.reader();
+        let next: TransportSn = codec
+            .read(&mut reader)
+            .map_err(|_| zerror!("Invalid ARQ ACK sequence number"))?;
+        let mask: u64 = codec
+            .read(&mut reader)
+            .map_err(|_| zerror!("Invalid ARQ ACK mask"))?;
+        Ok(ArqAck {
+            priority: oam.ext_qos.priority(),
+            next,
+            mask,
+        })
+    }
+}
+
+/// The ARQ state shared by the TX and RX tasks of a best-effort link.
+pub(super) struct Arq {
+    window: usize,
+    ack_interval: Duration,
+    retransmission_timeout: Duration,
+    // The bit `i` is set if reliable messages of priority `i` have been received since the last ACKs
+    received: AtomicU8,
+    // The ACKs received on the link, to be processed by the TX task
+    acks: (flume::Sender<ArqAck>, flume::Receiver<ArqAck>),
+}
+
+impl Arq {
+    pub(super) fn new(
+        window: usize,
+        ack_interval: Duration,
+        retransmission_timeout: Duration,
+    ) -> Self {
+        Self {
+            window,
+            ack_interval,
+            retransmission_timeout,
+            received: AtomicU8::new(0),
+            acks: flume::bounded(Priority::NUM),
+        }
+    }
+
+    pub(super) fn window(&self) -> usize {
+        self.window
+    }
+
+    pub(super) fn received(&self, priority: usize) {
+        self.received.fetch_or(1 << priority, Ordering::AcqRel);
+    }
+
+    pub(super) fn acked(&self, ack: ArqAck) {
+        // ACKs are cumulative and periodically repeated: they can be dropped if the TX task lags
+        if self.acks.0.try_send(ack).is_err() {
+            tracing::trace!("ARQ ACK dropped: {:?}", ack);
+        }
+    }
+}
+
+// A reliable message sent on the link and not acknowledged yet
+struct Unacked {
+    // The bytes of the batch the message has been sent in, shared by its reliable messages
+    batch: Arc<[u8]>,
+    sent: Instant,
+}
+
+struct ArqTxQueue {
+    queue: ReliabilityQueue<Unacked>,
+    // The sequence number following the latest sent one
+    next: SeqNum,
+}
+
+/// The ARQ state owned by the TX task of a best-effort link. It keeps the reliable messages sent
+/// on the link until they are acknowledged, retransmits the ones reported as missing or not
+/// acknowledged in time, and sends the ACKs of the reliable messages received on the link.
+pub(super) struct ArqTx {
+    arq: Arc<Arq>,
+    rx: Arc<[ArqRxQueue]>,
+    resolution: Bits,
+    // The time before a message reported as missing may be retransmitted again
+    nack_guard: Duration,
+    queues: Vec<Option<ArqTxQueue>>,
+}
+
+impl ArqTx {
+    pub(super) fn new(
+        arq: Arc<Arq>,
+        rx: Arc<[ArqRxQueue]>,
+        resolution: Bits,
+        rtt: Option<Duration>,
+    ) -> Self {
+        let nack_guard = arq.ack_interval + rtt.unwrap_or_default();
+        Self {
+            arq,
+            rx,
+            resolution,
+            nack_guard,
+            queues: (0..Priority::NUM).map(|_| None).collect(),
+        }
+    }
+
+    pub(super) fn interval(&self) -> Duration {
+        self.arq.ack_interval
+    }
+
+    pub(super) async fn recv_ack(&self) -> Option<ArqAck> {
+        self.arq.acks.1.recv_async().await.ok()
+    }
+
+    /// Returns `true` if the reliable messages of a batch can be sent without exceeding the
+    /// retransmission window.
+    pub(super) fn fits(&self, reliable: &ReliableSn) -> bool {
+        self.queues[reliable.priority as usize]
+            .as_ref()
+            .map_or(true, |q| {
+                q.queue.is_empty() || !q.queue.exceeds(reliable.latest)
+            })
+    }
+
+    /// Keeps the bytes of a batch, which has just been sent, until its reliable messages are
+    /// acknowledged.
+    pub(super) fn sent(&mut self, reliable: ReliableSn, batch: Arc<[u8]>) -> ZResult<()> {
+        let now = Instant::now();
+        let mask = get_mask(self.resolution);
+        let q = match &mut self.queues[reliable.priority as usize] {
+            Some(q) => q,
+            q @ None => q.insert(ArqTxQueue {
+                queue: ReliabilityQueue::new(self.arq.window, reliable.first, self.resolution)?,
+                next: SeqNum::make(reliable.first, self.resolution)?,
+            }),
+        };
+        // The preceding sequence numbers may have been sent on other links
+        if q.queue.is_empty() {
+            q.queue.set_base(reliable.first)?;
+        }
+        let count = reliable.latest.wrapping_sub(reliable.first) & mask;
+        for i in 0..=count {
+            let sn = reliable.first.wrapping_add(i) & mask;
+            let unacked = Unacked {
+                batch: batch.clone(),
+                sent: now,
+            };
+            if let Err(e) = q.queue.insert(unacked, sn) {
+                tracing::debug!("Message with SN {} will not be retransmitted: {}", sn, e);
+            }
+        }
+        let next = reliable.latest.wrapping_add(1) & mask;
+        if next == q.next.get() || q.next.precedes(next)? {
+            q.next.set(next)?;
+        }
+        Ok(())
+    }
+
+    /// Releases the messages acknowledged by `ack` and retransmits the ones reported as missing.
+    pub(super) async fn ack(
+        &mut self,
+        ack: ArqAck,
+        link: &mut TransportLinkUnicastTx,
+    ) -> ZResult<()> {
+        let Some(q) = self
+            .queues
+            .get_mut(ack.priority as usize)
+            .and_then(Option::as_mut)
+        else {
+            return Ok(());
+        };
+
+        let base = SeqNum::make(q.queue.get_base(), self.resolution)?;
+        if ack.next != base.get() && !base.precedes(ack.next)? {
+            // Outdated ACK
+            return Ok(());
+        }
+        // The messages following the latest sent one may have been received on other links
+        let base = if q.next.precedes(ack.next)? {
+            q.next.get()
+        } else {
+            ack.next
+        };
+        q.queue.set_base(base)?;
+
+        let now = Instant::now();
+        let mut missing = vec![];
+        for i in 0..u64::BITS {
+            if ack.mask & (1 << i) == 0 {
+                continue;
+            }
+            let sn = ack.next.wrapping_add(i) & get_mask(self.resolution);
+            if let Some(unacked) = q.queue.get_mut(sn) {
+                if now.duration_since(unacked.sent) >= self.nack_guard {
+                    unacked.sent = now;
+                    push_batch(&mut missing, &unacked.batch);
+                }
+            }
+        }
+
+        for batch in missing.iter() {
+            tracing::trace!(
+                "Retransmitting batch with missing messages: {} bytes",
+                batch.len()
+            );
+            link.send_bytes(batch).await?;
+        }
+        Ok(())
+    }
+
+    /// Sends the ACKs of the reliable messages received on the link and retransmits the messages
+    /// that have not been acknowledged in time.
+    pub(super) async fn tick(&mut self, link: &mut TransportLinkUnicastTx) -> ZResult<()> {
+        let received = self.arq.received.swap(0, Ordering::AcqRel);
+        for (priority, rx) in self.rx.iter().enumerate() {
+            let ack = zlock!(rx.queue).as_ref().and_then(|queue| {
+                let mask = queue.get_mask();
+                (received & (1 << priority) != 0 || mask != 0).then(|| ArqAck {
+                    priority: Priority::try_from(priority as u8).unwrap_or_default(),
+                    next: queue.get_base(),
+                    mask,
+                })
+            });
+            if let Some(ack) = ack {
+                link.send(&ack.into()).await?;
+            }
+        }
+
+        let now = Instant::now();
+        let mut expired = vec![];
+        for q in self.queues.iter_mut().flatten() {
+            for (_, unacked) in q.queue.iter_mut() {
+                if now.duration_since(unacked.sent) >= self.arq.retransmission_timeout {
+                    unacked.sent = now;
+                    push_batch(&mut expired, &unacked.batch);
+                }
+            }
+        }
+
+        for batch in expired.iter() {
+            tracing::trace!(
+                "Retransmitting batch with unacknowledged messages: {} bytes",
+                batch.len()
+            );
+            link.send_bytes(batch).await?;
+        }
+        Ok(())
+    }
+}
+
+// Adds `batch` to the batches to retransmit, unless it is already there because of another of
+// its messages
+fn push_batch(batches: &mut Vec<Arc<[u8]>>, batch: &Arc<[u8]>) {
+    if !batches.iter().any(|b| Arc::ptr_eq(b, batch)) {
+        batches.push(batch.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arq_ack_codec() {
+        let ack = ArqAck {
+            priority: Priority::DataHigh,
+            next: 1_234,
+            mask: 0b1011,
+        };
+        let msg: TransportMessage = ack.into();
+        let TransportBody::OAM(oam) = &msg.body else {
+            panic!("ARQ ACK is not an OAM message: {:?}", msg);
+        };
+        assert_eq!(oam.id, oam::id::OAM_ARQ_ACK);
+        assert_eq!(ArqAck::try_from(oam).unwrap(), ack);
+    }
+}
@@ -11,21 +11,33 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use std::time::Duration;
+#[cfg(feature = "transport_multilink")]
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+use std::{sync::Arc, time::Duration};
 
+use tokio::time::Interval;
 use tokio_util::{sync::CancellationToken, task::TaskTracker};
 use zenoh_buffers::ZSliceBuffer;
+#[cfg(feature = "transport_multilink")]
+use zenoh_core::zlock;
 use zenoh_link::Link;
 use zenoh_protocol::transport::{KeepAlive, TransportMessage};
 use zenoh_result::{zerror, ZResult};
 use zenoh_sync::{RecyclingObject, RecyclingObjectPool};
+
 #[cfg(feature = "stats")]
-use {crate::common::stats::TransportStats, std::sync::Arc};
+use crate::common::stats::TransportStats;
 
-use super::transport::TransportUnicastUniversal;
+use super::{
+    arq::{Arq, ArqAck, ArqTx},
+    transport::TransportUnicastUniversal,
+};
 use crate::{
     common::{
-        batch::{BatchConfig, RBatch},
+        batch::{BatchConfig, RBatch, WBatch},
         pipeline::{
             TransmissionPipeline, TransmissionPipelineConf, TransmissionPipelineConsumer,
             TransmissionPipelineProducer,
@@ -35,6 +47,9 @@ use crate::{
     unicast::link::{TransportLinkUnicast, TransportLinkUnicastRx, TransportLinkUnicastTx},
 };
 
+#[cfg(feature = "transport_multilink")]
+type PendingTx = (TransmissionPipelineConsumer, Option<WBatch>);
+
 #[derive(Clone)]
 pub(super) struct TransportLinkUnicastUniversal {
     // The underlying link
@@ -44,6 +59,13 @@ pub(super) struct TransportLinkUnicastUniversal {
     // The task handling substruct
     tracker: TaskTracker,
     token: CancellationToken,
+    // The ARQ state when retransmitting the reliable messages on a best-effort link
+    arq: Option<Arc<Arq>>,
+    // The pipeline and the batch left by the TX task when the link fails
+    #[cfg(feature = "transport_multilink")]
+    pending: Arc<Mutex<Option<PendingTx>>>,
+    #[cfg(feature = "transport_multilink")]
+    failed: Arc<AtomicBool>,
 }
 
 impl TransportLinkUnicastUniversal {
@@ -59,23 +81,45 @@ impl TransportLinkUnicastUniversal {
                 mtu: link.config.batch.mtu,
                 is_streamed: link.link.is_streamed(),
                 #[cfg(feature = "transport_compression")]
-                is_compression: link.config.batch.is_compression,
+                compression: link.config.batch.compression.clone(),
             },
             queue_size: transport.manager.config.queue_size,
             wait_before_drop: transport.manager.config.wait_before_drop,
             wait_before_close: transport.manager.config.wait_before_close,
             batching_enabled: transport.manager.config.batching,
             batching_time_limit: transport.manager.config.queue_backoff,
+            batching_adaptive: transport.manager.config.batching_adaptive,
+            latency_budget: transport.manager.config.unicast.latency_budget,
+            rtt: link.config.rtt,
         };
 
         // The pipeline
-        let (producer, consumer) = TransmissionPipeline::make(config, priority_tx);
+        let (producer, consumer) = TransmissionPipeline::make(
+            config,
+            priority_tx,
+            #[cfg(feature = "stats")]
+            transport.stats.clone(),
+        );
+
+        // The ARQ state, if negotiated on the link
+        let arq = link.config.arq.map(|window| {
+            Arc::new(Arq::new(
+                window as usize,
+                transport.manager.config.unicast.arq_ack_interval,
+                transport.manager.config.unicast.arq_retransmission_timeout,
+            ))
+        });
 
         let result = Self {
             link,
             pipeline: producer,
             tracker: TaskTracker::new(),
             token: CancellationToken::new(),
+            arq,
+            #[cfg(feature = "transport_multilink")]
+            pending: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "transport_multilink")]
+            failed: Arc::new(AtomicBool::new(false)),
         };
 
         (result, consumer)
@@ -90,17 +134,39 @@ impl TransportLinkUnicastUniversal {
         // Spawn the TX task
         let mut tx = self.link.tx();
         let token = self.token.clone();
+        let arq = self.arq.clone().map(|arq| {
+            ArqTx::new(
+                arq,
+                transport.arq_rx.clone(),
+                transport.config.sn_resolution,
+                self.link.config.rtt,
+            )
+        });
+        #[cfg(feature = "transport_multilink")]
+        let (pending, failed) = (self.pending.clone(), self.failed.clone());
         let task = async move {
+            let mut consumer = consumer;
+            let mut unsent = None;
             let res = tx_task(
-                consumer,
+                &mut consumer,
                 &mut tx,
                 keep_alive,
                 token,
+                &mut unsent,
+                arq,
+                #[cfg(feature = "transport_multilink")]
+                &failed,
                 #[cfg(feature = "stats")]
                 transport.stats.clone(),
             )
             .await;
 
+            // Keep the batches that have not been transmitted on the link
+            #[cfg(feature = "transport_multilink")]
+            if res.is_err() || failed.load(Ordering::Relaxed) {
+                *zlock!(pending) = Some((consumer, unsent));
+            }
+
             if let Err(e) = res {
                 tracing::debug!("TX task failed: {}", e);
                 // Spawn a task to avoid a deadlock waiting for this same task
@@ -119,6 +185,7 @@ impl TransportLinkUnicastUniversal {
         let reliability = self.link.config.reliability;
         let mut rx = self.link.rx();
         let token = self.token.clone();
+        let arq = self.arq.clone();
         let task = async move {
             // Start the consume task
             let res = rx_task(
@@ -127,6 +194,7 @@ impl TransportLinkUnicastUniversal {
                 lease,
                 transport.manager.config.link_rx_buffer_size,
                 token,
+                arq,
             )
             .await;
 
@@ -156,13 +224,33 @@ impl TransportLinkUnicastUniversal {
         self.tracker.spawn_on(task, &zenoh_runtime::ZRuntime::RX);
     }
 
-    pub(super) async fn close(self) -> ZResult<()> {
-        tracing::trace!("{}: closing", self.link);
-
+    async fn stop(&self) {
         self.tracker.close();
         self.token.cancel();
         self.pipeline.disable();
         self.tracker.wait().await;
+    }
+
+    /// Stops the link tasks without flushing the transmission pipeline and returns the batches
+    /// that have not been transmitted on the link.
+    #[cfg(feature = "transport_multilink")]
+    pub(super) async fn take_pending(&self) -> Vec<WBatch> {
+        self.failed.store(true, Ordering::Relaxed);
+        self.stop().await;
+
+        let Some((mut consumer, unsent)) = zlock!(self.pending).take() else {
+            return vec![];
+        };
+        unsent
+            .into_iter()
+            .chain(consumer.drain().into_iter().map(|(batch, _)| batch))
+            .collect()
+    }
+
+    pub(super) async fn close(self) -> ZResult<()> {
+        tracing::trace!("{}: closing", self.link);
+
+        self.stop().await;
 
         self.link.close(None).await
     }
@@ -171,20 +259,102 @@ impl TransportLinkUnicastUniversal {
 /*************************************/
 /*              TASKS                */
 /*************************************/
+#[allow(clippy::too_many_arguments)]
 async fn tx_task(
-    mut pipeline: TransmissionPipelineConsumer,
+    pipeline: &mut TransmissionPipelineConsumer,
     link: &mut TransportLinkUnicastTx,
     keep_alive: Duration,
     token: CancellationToken,
+    unsent: &mut Option<WBatch>,
+    mut arq: Option<ArqTx>,
+    #[cfg(feature = "transport_multilink")] failed: &AtomicBool,
     #[cfg(feature = "stats")] stats: Arc<TransportStats>,
 ) -> ZResult<()> {
+    async fn recv_ack(arq: Option<&ArqTx>) -> Option<ArqAck> {
+        match arq {
+            Some(arq) => arq.recv_ack().await,
+            None => std::future::pending().await,
+        }
+    }
+
+    async fn tick(interval: Option<&mut Interval>) {
+        match interval {
+            Some(interval) => {
+                interval.tick().await;
+            }
+            None => std::future::pending().await,
+        }
+    }
+
+    async fn send_keep_alive(
+        link: &mut TransportLinkUnicastTx,
+        #[cfg(feature = "stats")] stats: &TransportStats,
+    ) -> ZResult<()> {
+        let message: TransportMessage = KeepAlive.into();
+
+        #[allow(unused_variables)] // Used when stats feature is enabled
+        let n = link.send(&message).await?;
+
+        #[cfg(feature = "stats")]
+        {
+            stats.inc_tx_t_msgs(1);
+            stats.inc_tx_bytes(n);
+        }
+        Ok(())
+    }
+
     let mut interval =
         tokio::time::interval_at(tokio::time::Instant::now() + keep_alive, keep_alive);
+    let mut arq_interval = arq
+        .as_ref()
+        .map(|arq| tokio::time::interval(arq.interval()));
     loop {
         tokio::select! {
             res = pipeline.pull() => {
                 if let Some((mut batch, priority)) = res {
-                    link.send_batch(&mut batch).await?;
+                    let reliable = arq.as_ref().and(batch.reliable);
+                    if let (Some(arq), Some(reliable)) = (arq.as_mut(), reliable.as_ref()) {
+                        // Wait for the reliable messages to be in the retransmission window, while
+                        // keeping the link alive
+                        while !arq.fits(reliable) {
+                            tokio::select! {
+                                Some(ack) = recv_ack(Some(arq)) => arq.ack(ack, link).await?,
+                                _ = tick(arq_interval.as_mut()) => arq.tick(link).await?,
+                                _ = interval.tick() => {
+                                    send_keep_alive(
+                                        link,
+                                        #[cfg(feature = "stats")]
+                                        &stats,
+                                    )
+                                    .await?
+                                }
+                                _ = token.cancelled() => {
+                                    *unsent = Some(batch);
+                                    return Ok(());
+                                }
+                            }
+                        }
+                    }
+
+                    // The bytes of the batches with reliable messages are retained for ARQ
+                    let res = match reliable {
+                        Some(_) => link.send_batch_retained(&mut batch).await.map(Some),
+                        None => link.send_batch(&mut batch).await.map(|_| None),
+                    };
+                    let retained = match res {
+                        Ok(retained) => retained,
+                        Err(e) => {
+                            // The batch may have not been transmitted
+                            *unsent = Some(batch);
+                            return Err(e);
+                        }
+                    };
+
+                    if let (Some(arq), Some(reliable), Some(retained)) =
+                        (arq.as_mut(), reliable, retained)
+                    {
+                        arq.sent(reliable, retained)?;
+                    }
 
                     #[cfg(feature = "stats")]
                     {
@@ -200,15 +370,23 @@ async fn tx_task(
             }
 
             _ = interval.tick() => {
-                let message: TransportMessage = KeepAlive.into();
+                send_keep_alive(
+                    link,
+                    #[cfg(feature = "stats")]
+                    &stats,
+                )
+                .await?
+            }
 
-                #[allow(unused_variables)] // Used when stats feature is enabled
-                let n = link.send(&message).await?;
+            Some(ack) = recv_ack(arq.as_ref()) => {
+                if let Some(arq) = arq.as_mut() {
+                    arq.ack(ack, link).await?;
+                }
+            }
 
-                #[cfg(feature = "stats")]
-                {
-                    stats.inc_tx_t_msgs(1);
-                    stats.inc_tx_bytes(n);
+            _ = tick(arq_interval.as_mut()) => {
+                if let Some(arq) = arq.as_mut() {
+                    arq.tick(link).await?;
                 }
             }
 
@@ -216,6 +394,12 @@ async fn tx_task(
         }
     }
 
+    // The remaining batches are transmitted on the other links
+    #[cfg(feature = "transport_multilink")]
+    if failed.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+
     // Drain the transmission pipeline and write remaining bytes on the wire
     let mut batches = pipeline.drain();
     for (mut b, _) in batches.drain(..) {
@@ -239,6 +423,7 @@ async fn rx_task(
     lease: Duration,
     rx_buffer_size: usize,
     token: CancellationToken,
+    arq: Option<Arc<Arq>>,
 ) -> ZResult<()> {
     async fn read<T, F>(
         link: &mut TransportLinkUnicastRx,
@@ -278,7 +463,7 @@ async fn rx_task(
 
                     transport.stats.inc_rx_bytes(2 + batch.len()); // Account for the batch len encoding (16 bits)
                 }
-                transport.read_messages(batch, &l)?;
+                transport.read_messages(batch, &l, arq.as_deref())?;
             }
 
             _ = token.cancelled() => break
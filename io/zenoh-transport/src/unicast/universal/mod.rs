@@ -13,6 +13,8 @@
 //
 pub(crate) mod transport;
 
+mod arq;
 mod link;
+mod reliability;
 mod rx;
 mod tx;
@@ -11,13 +11,17 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use std::{convert::TryInto, fmt};
+use std::fmt;
 
-use zenoh_result::{ZError, ZErrorKind, ZResult};
-use zenoh_util::zerror;
+use zenoh_protocol::{core::Bits, transport::TransportSn};
+use zenoh_result::{bail, ZResult};
 
-use super::{common::seq_num::SeqNum, core::u64};
+use crate::common::seq_num::SeqNum;
 
+/// A window of `capacity` consecutive sequence numbers starting from a base one.
+///
+/// It is used both to keep the reliable messages sent on a link until they are acknowledged and to
+/// reorder the reliable messages received on a link before delivering them.
 pub(super) struct ReliabilityQueue<T> {
     sn: SeqNum,
     index: usize,
@@ -26,23 +30,25 @@ pub(super) struct ReliabilityQueue<T> {
 }
 
 impl<T> ReliabilityQueue<T> {
-    pub(super) fn new(capacity: usize, initial_sn: u64, sn_resolution: u64) -> ReliabilityQueue<T> {
+    pub(super) fn new(
+        capacity: usize,
+        initial_sn: TransportSn,
+        resolution: Bits,
+    ) -> ZResult<ReliabilityQueue<T>> {
         let mut inner = Vec::with_capacity(capacity);
-        for _ in 0..capacity {
-            inner.push(None);
-        }
+        inner.resize_with(capacity, || None);
 
-        ReliabilityQueue {
-            sn: SeqNum::new(initial_sn, sn_resolution),
+        Ok(ReliabilityQueue {
+            sn: SeqNum::make(initial_sn, resolution)?,
             index: 0,
             len: 0,
             inner,
-        }
+        })
     }
 
     #[inline]
     pub(super) fn capacity(&self) -> usize {
-        self.inner.capacity()
+        self.inner.len()
     }
 
     #[inline]
@@ -55,40 +61,57 @@ impl<T> ReliabilityQueue<T> {
         self.len() == 0
     }
 
+    #[cfg(test)]
     #[inline]
     pub(super) fn is_full(&self) -> bool {
         self.len() == self.capacity()
     }
 
     #[inline]
-    pub(super) fn get_base(&self) -> u64 {
+    pub(super) fn get_base(&self) -> TransportSn {
         self.sn.get()
     }
 
-    pub(super) fn set_base(&mut self, sn: u64) -> ZResult<()> {
-        let gap: usize = match self.sn.gap(sn) {
-            Ok(gap) => match gap.try_into() {
-                Ok(gap) => gap,
-                Err(_) => usize::MAX,
-            },
-            Err(e) => return Err(e),
-        };
+    /// Returns the position of `sn` in the queue, if it is within the window.
+    fn offset(&self, sn: TransportSn) -> ZResult<usize> {
+        let gap = usize::try_from(self.sn.gap(sn)?).unwrap_or(usize::MAX);
+        if gap >= self.capacity() {
+            let e = format!(
+                "Sequence number is out of sequence number window: {}. Base: {}. Capacity: {}",
+                sn,
+                self.sn.get(),
+                self.capacity()
+            );
+            tracing::trace!("{}", e);
+            bail!("{}", e);
+        }
+        Ok(gap)
+    }
+
+    /// Returns `true` if `sn` follows the sequence numbers of the window.
+    pub(super) fn exceeds(&self, sn: TransportSn) -> bool {
+        self.sn.precedes(sn).unwrap_or(false)
+            && self
+                .sn
+                .gap(sn)
+                .is_ok_and(|gap| usize::try_from(gap).map_or(true, |gap| gap >= self.capacity()))
+    }
+
+    pub(super) fn set_base(&mut self, sn: TransportSn) -> ZResult<()> {
+        let gap = usize::try_from(self.sn.gap(sn)?).unwrap_or(usize::MAX);
 
         self.sn.set(sn)?;
 
         if gap >= self.capacity() {
             // If the gap is larger than the capacity, reset the queue
-            for i in 0..self.capacity() {
-                self.inner[i] = None;
-            }
+            self.inner.iter_mut().for_each(|t| *t = None);
             self.index = 0;
             self.len = 0;
         } else {
             // Reset only a portion of the queue
             for _ in 0..gap {
-                if self.inner[self.index].is_some() {
+                if self.inner[self.index].take().is_some() {
                     self.len -= 1;
-                    self.inner[self.index] = None;
                 }
                 self.index = (self.index + 1) % self.capacity();
             }
@@ -97,77 +120,69 @@ impl<T> ReliabilityQueue<T> {
         Ok(())
     }
 
-    pub(super) fn insert(&mut self, t: T, sn: u64) -> ZResult<()> {
-        let gap: usize = match self.sn.gap(sn) {
-            Ok(gap) => match gap.try_into() {
-                Ok(gap) => gap,
-                Err(e) => {
-                    return zerror!(ZErrorKind::InvalidResolution {
-                        descr: e.to_string()
-                    })
-                }
-            },
-            Err(e) => return Err(e),
-        };
+    /// Moves the base of the queue forward to `sn`, if `sn` follows it, and returns the messages
+    /// preceding `sn` in sequence number order.
+    pub(super) fn drain_until(&mut self, sn: TransportSn) -> ZResult<Vec<T>> {
+        if !self.sn.precedes(sn)? {
+            return Ok(vec![]);
+        }
+        let gap = usize::try_from(self.sn.gap(sn)?).unwrap_or(usize::MAX);
+        let mut drained = Vec::with_capacity(self.len);
+        for _ in 0..gap.min(self.capacity()) {
+            if let Some(t) = self.inner[self.index].take() {
+                self.len -= 1;
+                drained.push(t);
+            }
+            self.index = (self.index + 1) % self.capacity();
+        }
+        self.sn.set(sn)?;
+        Ok(drained)
+    }
 
-        if gap >= self.capacity() {
-            let e = format!(
-                "Sequence number is out of sequence number window: {}.\
-                             Base: {}. Capacity: {}",
-                sn,
-                self.sn.get(),
-                self.capacity()
-            );
-            tracing::trace!("{}", e);
-            return zerror!(ZErrorKind::Other { descr: e });
+    /// Inserts `t` with `sn` in the queue. It fails if `sn` is out of the window or if a message
+    /// with the same `sn` is already in the queue.
+    pub(super) fn insert(&mut self, t: T, sn: TransportSn) -> ZResult<()> {
+        let index = (self.index + self.offset(sn)?) % self.capacity();
+        if self.inner[index].is_some() {
+            bail!("Sequence number is already in the queue: {}", sn);
         }
 
         self.len += 1;
-        let index = (self.index + gap) % self.capacity();
         self.inner[index] = Some(t);
 
         Ok(())
     }
 
-    pub(super) fn remove(&mut self, sn: u64) -> ZResult<T> {
-        let gap: usize = match self.sn.gap(sn) {
-            Ok(gap) => match gap.try_into() {
-                Ok(gap) => gap,
-                Err(e) => {
-                    return zerror!(ZErrorKind::InvalidResolution {
-                        descr: e.to_string()
-                    })
-                }
-            },
-            Err(e) => return Err(e),
-        };
-
-        if gap >= self.capacity() {
-            let e = format!(
-                "Sequence number is out of sequence number window: {}.\
-                             Base: {}. Capacity: {}",
-                sn,
-                self.sn.get(),
-                self.capacity()
-            );
-            tracing::trace!("{}", e);
-            return zerror!(ZErrorKind::Other { descr: e });
-        }
-
-        let index = (self.index + gap) % self.capacity();
-        let res = self.inner[index].take();
-
-        match res {
+    #[cfg(test)]
+    pub(super) fn remove(&mut self, sn: TransportSn) -> ZResult<T> {
+        let index = (self.index + self.offset(sn)?) % self.capacity();
+        match self.inner[index].take() {
             Some(t) => {
                 self.len -= 1;
                 Ok(t)
             }
-            None => zerror!(ZErrorKind::Other {
-                descr: "Sequence number not found: {}".to_string()
-            }),
+            None => bail!("Sequence number not found: {}", sn),
         }
     }
 
+    pub(super) fn get_mut(&mut self, sn: TransportSn) -> Option<&mut T> {
+        let index = (self.index + self.offset(sn).ok()?) % self.capacity();
+        self.inner[index].as_mut()
+    }
+
+    /// Iterates over the messages in the queue, in sequence number order.
+    pub(super) fn iter_mut(&mut self) -> impl Iterator<Item = (TransportSn, &mut T)> {
+        let (base, index, resolution) = (self.sn.get(), self.index, self.sn.resolution());
+        let (tail, head) = self.inner.split_at_mut(index);
+        head.iter_mut()
+            .chain(tail.iter_mut())
+            .enumerate()
+            .filter_map(move |(i, t)| {
+                let sn = base.wrapping_add(i as TransportSn) & resolution;
+                t.as_mut().map(|t| (sn, t))
+            })
+    }
+
     pub(super) fn pull(&mut self) -> Option<T> {
         let t = self.inner[self.index].take();
         if t.is_some() {
@@ -181,11 +196,12 @@ impl<T> ReliabilityQueue<T> {
     /// Returns a bitmask of surely missed messages.
     /// A bit is set to 1 iff the position in the queue is empty and
     /// there is at least one message with a higher sequence number.
+    /// Only the first 64 positions of the queue are covered by the mask.
     pub(super) fn get_mask(&self) -> u64 {
         let mut mask: u64 = 0;
         let mut count = 0;
         let mut i = 0;
-        while count < self.len() {
+        while count < self.len() && i < u64::BITS as usize {
             let index = (self.index + i) % self.capacity();
             if self.inner[index].is_none() {
                 mask |= 1 << i;
@@ -198,44 +214,6 @@ impl<T> ReliabilityQueue<T> {
     }
 }
 
-impl<T: Clone> ReliabilityQueue<T> {
-    pub(super) fn get(&mut self, sn: u64) -> ZResult<T> {
-        let gap: usize = match self.sn.gap(sn) {
-            Ok(gap) => match gap.try_into() {
-                Ok(gap) => gap,
-                Err(e) => {
-                    return zerror!(ZErrorKind::InvalidResolution {
-                        descr: e.to_string()
-                    })
-                }
-            },
-            Err(e) => return Err(e),
-        };
-
-        if gap >= self.capacity() {
-            let e = format!(
-                "Sequence number is out of sequence number window: {}.\
-                             Base: {}. Capacity: {}",
-                sn,
-                self.sn.get(),
-                self.capacity()
-            );
-            tracing::trace!("{}", e);
-            return zerror!(ZErrorKind::Other { descr: e });
-        }
-
-        let index = (self.index + gap) % self.capacity();
-        let res = self.inner[index].clone();
-
-        match res {
-            Some(t) => Ok(t),
-            None => zerror!(ZErrorKind::Other {
-                descr: "Sequence number not found: {}".to_string()
-            }),
-        }
-    }
-}
-
 impl<T: fmt::Debug> fmt::Debug for ReliabilityQueue<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ReliabilityQueue")
@@ -250,13 +228,15 @@ mod tests {
     use rand::{thread_rng, Rng};
 
     use super::*;
+    use crate::common::seq_num::get_mask;
 
     #[test]
     fn reliability_queue_simple() {
         let size = 2;
-        let mut queue: ReliabilityQueue<u64> = ReliabilityQueue::new(size, 0, 2);
+        let mut queue: ReliabilityQueue<TransportSn> =
+            ReliabilityQueue::new(size, 0, Bits::U8).unwrap();
 
-        let mut sn: u64 = 0;
+        let mut sn: TransportSn = 0;
         // Add the first element
         let res = queue.insert(0, sn);
         assert!(res.is_ok());
@@ -264,7 +244,7 @@ mod tests {
         assert_eq!(res, Some(0));
 
         // Add the second element
-        sn = sn + 1;
+        sn += 1;
         let res = queue.insert(1, sn);
         assert!(res.is_ok());
         let res = queue.pull();
@@ -277,9 +257,10 @@ mod tests {
     #[test]
     fn reliability_queue_order() {
         let size = 2;
-        let mut queue: ReliabilityQueue<u64> = ReliabilityQueue::new(size, 0, 3);
+        let mut queue: ReliabilityQueue<TransportSn> =
+            ReliabilityQueue::new(size, 0, Bits::U8).unwrap();
 
-        let sn: u64 = 0;
+        let sn: TransportSn = 0;
 
         // Add the second element
         let res = queue.insert(1, sn + 1);
@@ -301,12 +282,35 @@ mod tests {
         assert!(queue.is_empty());
     }
 
+    #[test]
+    fn reliability_queue_duplicate() {
+        let size = 2;
+        let mut queue: ReliabilityQueue<TransportSn> =
+            ReliabilityQueue::new(size, 0, Bits::U8).unwrap();
+
+        // Add an element twice
+        let res = queue.insert(1, 1);
+        assert!(res.is_ok());
+        let res = queue.insert(1, 1);
+        assert!(res.is_err());
+        assert_eq!(queue.len(), 1);
+
+        // Drain the elements preceding the new base
+        let res = queue.drain_until(2).unwrap();
+        assert_eq!(res, vec![1]);
+        assert_eq!(queue.get_base(), 2);
+
+        // Verify that the queue is empty
+        assert!(queue.is_empty());
+    }
+
     #[test]
     fn reliability_queue_full() {
         let size = 2;
-        let mut queue: ReliabilityQueue<u64> = ReliabilityQueue::new(size, 0, 3);
+        let mut queue: ReliabilityQueue<TransportSn> =
+            ReliabilityQueue::new(size, 0, Bits::U8).unwrap();
 
-        let mut sn: u64 = 0;
+        let mut sn: TransportSn = 0;
 
         // Fill the queue
         let res = queue.insert(0, sn);
@@ -331,9 +335,10 @@ mod tests {
     #[test]
     fn reliability_queue_out_of_sync() {
         let size = 2;
-        let mut queue: ReliabilityQueue<u64> = ReliabilityQueue::new(size, 0, 2);
+        let mut queue: ReliabilityQueue<TransportSn> =
+            ReliabilityQueue::new(size, 0, Bits::U8).unwrap();
 
-        let sn: u64 = 3;
+        let sn: TransportSn = 3;
 
         let res = queue.insert(sn, sn);
         assert!(res.is_err());
@@ -346,10 +351,11 @@ mod tests {
     fn reliability_queue_overflow() {
         // Test the overflow case
         let size = 4;
-        let mut queue: ReliabilityQueue<u64> = ReliabilityQueue::new(size, 0, 4);
+        let mut queue: ReliabilityQueue<TransportSn> =
+            ReliabilityQueue::new(size, 0, Bits::U8).unwrap();
 
-        let min: u64 = 0;
-        let max: u64 = 3;
+        let min: TransportSn = 0;
+        let max: TransportSn = get_mask(Bits::U8);
 
         let res = queue.set_base(max - 1);
         assert!(res.is_ok());
@@ -380,13 +386,14 @@ mod tests {
     fn reliability_queue_mask() {
         // Test the deterministic insertion of elements and mask
         let size = 8;
-        let mut queue: ReliabilityQueue<u64> = ReliabilityQueue::new(size, 0, 8);
+        let mut queue: ReliabilityQueue<TransportSn> =
+            ReliabilityQueue::new(size, 0, Bits::U8).unwrap();
 
-        let mut sn: u64 = 0;
-        while sn < size as u64 {
+        let mut sn: TransportSn = 0;
+        while sn < size as TransportSn {
             let res = queue.insert(sn, sn);
             assert!(res.is_ok());
-            sn = sn + 2;
+            sn += 2;
         }
 
         // Verify that the mask is correct
@@ -394,11 +401,11 @@ mod tests {
         assert_eq!(queue.get_mask(), mask);
 
         // Insert the missing elements
-        let mut sn: u64 = 1;
-        while sn < size as u64 {
+        let mut sn: TransportSn = 1;
+        while sn < size as TransportSn {
             let res = queue.insert(sn, sn);
             assert!(res.is_ok());
-            sn = sn + 2;
+            sn += 2;
         }
 
         // Verify that the mask is correct
@@ -406,7 +413,7 @@ mod tests {
         assert_eq!(queue.get_mask(), mask);
 
         // Drain the queue
-        while let Some(_) = queue.pull() {}
+        while queue.pull().is_some() {}
         // Verify that the queue is empty
         assert!(queue.is_empty());
     }
@@ -415,10 +422,11 @@ mod tests {
     fn reliability_queue_random_mask() {
         // Test the random insertion of elements and the mask
         let size = 64;
-        let mut queue: ReliabilityQueue<u64> = ReliabilityQueue::new(size, 0, 64);
+        let mut queue: ReliabilityQueue<TransportSn> =
+            ReliabilityQueue::new(size, 0, Bits::U8).unwrap();
 
-        let mut sequence = Vec::<u64>::new();
-        for i in 0..size as u64 {
+        let mut sequence = Vec::<TransportSn>::new();
+        for i in 0..size as TransportSn {
             sequence.push(i);
         }
 
@@ -426,7 +434,7 @@ mod tests {
         let mut tail = 0;
         let mut mask: u64 = 0;
         let mut rng = thread_rng();
-        while sequence.len() > 0 {
+        while !sequence.is_empty() {
             // Get random sequence number
             let index = rng.gen_range(0..sequence.len());
             let sn = sequence.remove(index);
@@ -438,9 +446,9 @@ mod tests {
             let res = queue.insert(sn, sn);
             assert!(res.is_ok());
             // Locally compute the mask
-            mask = mask | (1 << sn);
-            let shift: u32 = tail.wrapping_sub(head) as u32;
-            let window = !u64::max_value().wrapping_shl(shift);
+            mask |= 1 << sn;
+            let shift: u32 = tail.wrapping_sub(head);
+            let window = !u64::MAX.wrapping_shl(shift);
             // Verify that the mask is correct
             assert_eq!(queue.get_mask(), !mask & window);
         }
@@ -448,10 +456,10 @@ mod tests {
         // Verify that we have filled the queue
         assert!(queue.is_full());
         // Verify that no elements are marked for retransmission
-        assert_eq!(queue.get_mask(), !u64::max_value());
+        assert_eq!(queue.get_mask(), !u64::MAX);
 
         // Drain the queue
-        while let Some(_) = queue.pull() {}
+        while queue.pull().is_some() {}
         // Verify that the queue is empty
         assert!(queue.is_empty());
 
@@ -463,10 +471,11 @@ mod tests {
     #[test]
     fn reliability_queue_rebase() {
         let size = 8;
-        let mut queue: ReliabilityQueue<u64> = ReliabilityQueue::new(size, 0, 32);
+        let mut queue: ReliabilityQueue<TransportSn> =
+            ReliabilityQueue::new(size, 0, Bits::U8).unwrap();
 
         // Fill the queue
-        for i in 0..size as u64 {
+        for i in 0..size as TransportSn {
             // Push the element on the queue
             let res = queue.insert(i, i);
             assert!(res.is_ok());
@@ -518,7 +527,7 @@ mod tests {
         assert_eq!(queue.get_base(), 0);
 
         // Fill the queue
-        for i in 0..size as u64 {
+        for i in 0..size as TransportSn {
             // Push the element on the queue is correct
             let res = queue.insert(i, i);
             assert!(res.is_ok());
@@ -528,7 +537,7 @@ mod tests {
         assert!(queue.is_full());
 
         // Rebase beyond the current boundaries triggering a reset
-        let base = 2 * size as u64;
+        let base = 2 * size as TransportSn;
         let res = queue.set_base(base);
         assert!(res.is_ok());
         assert_eq!(queue.get_base(), base);
@@ -544,10 +553,11 @@ mod tests {
     #[test]
     fn reliability_queue_remove() {
         let size = 8;
-        let mut queue: ReliabilityQueue<u64> = ReliabilityQueue::new(size, 0, 8);
+        let mut queue: ReliabilityQueue<TransportSn> =
+            ReliabilityQueue::new(size, 0, Bits::U8).unwrap();
 
         // Fill the queue
-        for i in 0..size as u64 {
+        for i in 0..size as TransportSn {
             // Push the element on the queue
             let res = queue.insert(i, i);
             assert!(res.is_ok());
@@ -590,7 +600,7 @@ mod tests {
         assert!(queue.is_empty());
 
         // Check that everything is None
-        for i in 0..size as u64 {
+        for i in 0..size as TransportSn {
             // Remove the element from the queue
             let res = queue.remove(i);
             assert!(res.is_err());
@@ -13,20 +13,26 @@
 //
 use std::sync::MutexGuard;
 
-use zenoh_core::{zlock, zread};
+use zenoh_core::{zcondfeat, zlock, zread};
 use zenoh_link::Link;
 use zenoh_protocol::{
     core::{Priority, Reliability},
     network::NetworkMessage,
-    transport::{Close, Fragment, Frame, KeepAlive, TransportBody, TransportMessage, TransportSn},
+    transport::{
+        oam, Close, Fragment, Frame, KeepAlive, TransportBody, TransportMessage, TransportSn,
+    },
 };
 use zenoh_result::{bail, zerror, ZResult};
 
-use super::transport::TransportUnicastUniversal;
+use super::{
+    arq::{Arq, ArqAck},
+    reliability::ReliabilityQueue,
+    transport::TransportUnicastUniversal,
+};
 use crate::{
     common::{
         batch::{Decode, RBatch},
-        priority::TransportChannelRx,
+        priority::{TransportChannelRx, TransportPriorityRx},
     },
     unicast::transport_unicast_inner::TransportUnicastTrait,
     TransportPeerEventHandler,
@@ -71,6 +77,20 @@ impl TransportUnicastUniversal {
         Ok(())
     }
 
+    fn priority_rx(&self, priority: Priority) -> ZResult<&TransportPriorityRx> {
+        if self.is_qos() {
+            Ok(&self.priority_rx[priority as usize])
+        } else if priority == Priority::DEFAULT {
+            Ok(&self.priority_rx[0])
+        } else {
+            bail!(
+                "Transport: {}. Unknown priority: {:?}.",
+                self.config.zid,
+                priority
+            );
+        }
+    }
+
     fn handle_frame(&self, frame: Frame) -> ZResult<()> {
         let Frame {
             reliability,
@@ -79,25 +99,14 @@ impl TransportUnicastUniversal {
             mut payload,
         } = frame;
 
-        let priority = ext_qos.priority();
-        let c = if self.is_qos() {
-            &self.priority_rx[priority as usize]
-        } else if priority == Priority::DEFAULT {
-            &self.priority_rx[0]
-        } else {
-            bail!(
-                "Transport: {}. Unknown priority: {:?}.",
-                self.config.zid,
-                priority
-            );
-        };
+        let c = self.priority_rx(ext_qos.priority())?;
 
         let mut guard = match reliability {
             Reliability::Reliable => zlock!(c.reliable),
             Reliability::BestEffort => zlock!(c.best_effort),
         };
 
-        if !self.verify_sn(sn, &mut guard)? {
+        if !self.verify_sn(sn, reliability, &mut guard)? {
             // Drop invalid message and continue
             return Ok(());
         }
@@ -126,24 +135,14 @@ impl TransportUnicastUniversal {
             payload,
         } = fragment;
 
-        let c = if self.is_qos() {
-            &self.priority_rx[qos.priority() as usize]
-        } else if qos.priority() == Priority::DEFAULT {
-            &self.priority_rx[0]
-        } else {
-            bail!(
-                "Transport: {}. Unknown priority: {:?}.",
-                self.config.zid,
-                qos.priority()
-            );
-        };
+        let c = self.priority_rx(qos.priority())?;
 
         let mut guard = match reliability {
             Reliability::Reliable => zlock!(c.reliable),
             Reliability::BestEffort => zlock!(c.best_effort),
         };
 
-        if !self.verify_sn(sn, &mut guard)? {
+        if !self.verify_sn(sn, reliability, &mut guard)? {
             // Drop invalid message and continue
             return Ok(());
         }
@@ -176,13 +175,85 @@ impl TransportUnicastUniversal {
         Ok(())
     }
 
+    fn deliver(&self, body: TransportBody) -> ZResult<()> {
+        match body {
+            TransportBody::Frame(frame) => self.handle_frame(frame),
+            TransportBody::Fragment(fragment) => self.handle_fragment(fragment),
+            _ => Ok(()),
+        }
+    }
+
+    /// Handles a reliable frame or fragment received on a best-effort link using ARQ: the
+    /// messages are delivered in sequence number order and the duplicates are dropped.
+    fn handle_arq(
+        &self,
+        body: TransportBody,
+        priority: Priority,
+        sn: TransportSn,
+        arq: &Arq,
+    ) -> ZResult<()> {
+        let c = self.priority_rx(priority)?;
+        let rx = &self.arq_rx[priority as usize];
+
+        // The queue is not locked while delivering the messages: delivering may wait for the TX
+        // task of a link, which locks the queue to send the ACKs.
+        let _delivery = zlock!(rx.delivery);
+        let ready = {
+            let mut guard = zlock!(rx.queue);
+            let next = zlock!(c.reliable).sn.next();
+            if guard.is_none() {
+                *guard = Some(ReliabilityQueue::new(
+                    arq.window(),
+                    next,
+                    self.config.sn_resolution,
+                )?);
+            }
+            let Some(queue) = guard.as_mut() else {
+                return Ok(());
+            };
+
+            // The messages preceding the next expected one may have been received on other links
+            let mut ready = queue.drain_until(next)?;
+            if let Err(e) = queue.insert(body, sn) {
+                tracing::trace!(
+                    "Transport: {}. Reliable message with SN {} dropped: {}",
+                    self.config.zid,
+                    sn,
+                    e
+                );
+            }
+            while let Some(body) = queue.pull() {
+                ready.push(body);
+            }
+            ready
+        };
+        arq.received(priority as usize);
+
+        for body in ready {
+            self.deliver(body)?;
+        }
+        Ok(())
+    }
+
     fn verify_sn(
         &self,
         sn: TransportSn,
+        reliability: Reliability,
         guard: &mut MutexGuard<'_, TransportChannelRx>,
     ) -> ZResult<bool> {
-        let precedes = guard.sn.roll(sn)?;
-        if !precedes {
+        // The best-effort messages of a multilink transport may be balanced among its links and
+        // received out of order, while the reliable ones are always received in order
+        let multilink = zcondfeat!(
+            "transport_multilink",
+            self.manager.config.unicast.max_links > 1,
+            false
+        );
+        let valid = if multilink && reliability == Reliability::BestEffort {
+            guard.roll(sn)?
+        } else {
+            guard.sn.roll(sn)?
+        };
+        if !valid {
             tracing::trace!(
                 "Transport: {}. Frame with invalid SN dropped: {}. Expected: {}.",
                 self.config.zid,
@@ -195,7 +266,12 @@ impl TransportUnicastUniversal {
         Ok(true)
     }
 
-    pub(super) fn read_messages(&self, mut batch: RBatch, link: &Link) -> ZResult<()> {
+    pub(super) fn read_messages(
+        &self,
+        mut batch: RBatch,
+        link: &Link,
+        arq: Option<&Arq>,
+    ) -> ZResult<()> {
         while !batch.is_empty() {
             let msg: TransportMessage = batch
                 .decode()
@@ -209,8 +285,31 @@ impl TransportUnicastUniversal {
             }
 
             match msg.body {
-                TransportBody::Frame(msg) => self.handle_frame(msg)?,
-                TransportBody::Fragment(fragment) => self.handle_fragment(fragment)?,
+                TransportBody::Frame(frame) => match arq {
+                    Some(arq) if frame.reliability == Reliability::Reliable => {
+                        let (priority, sn) = (frame.ext_qos.priority(), frame.sn);
+                        self.handle_arq(TransportBody::Frame(frame), priority, sn, arq)?
+                    }
+                    _ => self.handle_frame(frame)?,
+                },
+                TransportBody::Fragment(fragment) => match arq {
+                    Some(arq) if fragment.reliability == Reliability::Reliable => {
+                        let (priority, sn) = (fragment.ext_qos.priority(), fragment.sn);
+                        self.handle_arq(TransportBody::Fragment(fragment), priority, sn, arq)?
+                    }
+                    _ => self.handle_fragment(fragment)?,
+                },
+                TransportBody::OAM(msg) if msg.id == oam::id::OAM_ARQ_ACK => {
+                    match (arq, ArqAck::try_from(&msg)) {
+                        (Some(arq), Ok(ack)) => arq.acked(ack),
+                        (None, _) => tracing::debug!(
+                            "Transport: {}. ARQ ACK received on {} without ARQ",
+                            self.config.zid,
+                            link
+                        ),
+                        (_, Err(e)) => tracing::debug!("Transport: {}. {}", self.config.zid, e),
+                    }
+                }
                 TransportBody::Close(Close { reason, session }) => {
                     self.handle_close(link, reason, session)?
                 }
@@ -11,6 +11,8 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+#[cfg(feature = "transport_multilink")]
+use std::sync::atomic::AtomicUsize;
 use std::{
     fmt::DebugStruct,
     sync::{Arc, RwLock},
@@ -36,7 +38,7 @@ use crate::{
         authentication::AuthId,
         link::{LinkUnicastWithOpenAck, TransportLinkUnicastDirection},
         transport_unicast_inner::{AddLinkResult, TransportUnicastTrait},
-        universal::link::TransportLinkUnicastUniversal,
+        universal::{arq::ArqRxQueue, link::TransportLinkUnicastUniversal},
         TransportConfigUnicast,
     },
     TransportManager, TransportPeerEventHandler,
@@ -55,8 +57,13 @@ pub(crate) struct TransportUnicastUniversal {
     pub(super) priority_tx: Arc<[TransportPriorityTx]>,
     // Rx priorities
     pub(super) priority_rx: Arc<[TransportPriorityRx]>,
+    // Rx reliable messages received on the links using ARQ, per priority
+    pub(super) arq_rx: Arc<[ArqRxQueue]>,
     // The links associated to the channel
     pub(super) links: Arc<RwLock<Box<[TransportLinkUnicastUniversal]>>>,
+    // The counter used to balance the messages among the links of the same class
+    #[cfg(feature = "transport_multilink")]
+    pub(super) scheduled: Arc<AtomicUsize>,
     // The callback
     pub(super) callback: Arc<RwLock<Option<Arc<dyn TransportPeerEventHandler>>>>,
     // Lock used to ensure no race in add_link method
@@ -104,7 +111,10 @@ impl TransportUnicastUniversal {
             config,
             priority_tx: priority_tx.into_boxed_slice().into(),
             priority_rx: priority_rx.into_boxed_slice().into(),
+            arq_rx: (0..Priority::NUM).map(|_| ArqRxQueue::default()).collect(),
             links: Arc::new(RwLock::new(vec![].into_boxed_slice())),
+            #[cfg(feature = "transport_multilink")]
+            scheduled: Arc::new(AtomicUsize::new(0)),
             add_link_lock: Arc::new(AsyncMutex::new(())),
             callback: Arc::new(RwLock::new(None)),
             alive: Arc::new(AsyncMutex::new(false)),
@@ -201,7 +211,14 @@ impl TransportUnicastUniversal {
 
         match target {
             Target::Transport => self.delete().await,
-            Target::Link(stl) => stl.close().await,
+            Target::Link(stl) => {
+                #[cfg(feature = "transport_multilink")]
+                if self.manager.config.unicast.multilink_failover {
+                    let batches = stl.take_pending().await;
+                    self.failover(batches);
+                }
+                stl.close().await
+            }
         }
     }
 
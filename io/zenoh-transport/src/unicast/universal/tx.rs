@@ -11,17 +11,34 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+#[cfg(feature = "transport_multilink")]
+use std::sync::atomic::Ordering;
+
+#[cfg(feature = "transport_multilink")]
+use zenoh_config::MultilinkScheduling;
+#[cfg(feature = "transport_multilink")]
+use zenoh_core::zread;
+#[cfg(feature = "transport_multilink")]
+use zenoh_protocol::transport::{TransportBody, TransportMessage};
 use zenoh_protocol::{
     core::{Priority, PriorityRange, Reliability},
     network::NetworkMessage,
     transport::close,
 };
 
+#[cfg(feature = "transport_multilink")]
+use super::link::TransportLinkUnicastUniversal;
 use super::transport::TransportUnicastUniversal;
+#[cfg(feature = "transport_multilink")]
+use crate::common::batch::WBatch;
 #[cfg(feature = "shared-memory")]
 use crate::shm::map_zmsg_to_partner;
 use crate::unicast::transport_unicast_inner::TransportUnicastTrait;
 
+/// The maximum weight of a link with the [`MultilinkScheduling::WeightedByRtt`] policy.
+#[cfg(feature = "transport_multilink")]
+const MAX_RTT_WEIGHT: u128 = 16;
+
 impl TransportUnicastUniversal {
     /// Returns the index of the best matching [`Reliability`]-[`PriorityRange`] pair.
     ///
@@ -68,6 +85,137 @@ impl TransportUnicastUniversal {
         match_.full.or(match_.partial).or(match_.any)
     }
 
+    /// Returns the index of the link among `links` of the same class as `links[index]`, i.e.
+    /// with the same [`Reliability`] and [`PriorityRange`], on which to schedule the next message
+    /// according to the configured [`MultilinkScheduling`] policy.
+    ///
+    /// The reliable messages are not balanced: they are always scheduled on `links[index]` such
+    /// that the messages of a same priority are received in order.
+    #[cfg(feature = "transport_multilink")]
+    fn balance(
+        &self,
+        links: &[TransportLinkUnicastUniversal],
+        index: usize,
+        reliability: Reliability,
+    ) -> usize {
+        let scheduling = self.manager.config.unicast.multilink_scheduling;
+        if scheduling == MultilinkScheduling::First || reliability == Reliability::Reliable {
+            return index;
+        }
+        let class = |tl: &TransportLinkUnicastUniversal| {
+            (
+                tl.link.config.reliability,
+                tl.link.config.priorities.clone(),
+            )
+        };
+        let candidates = links
+            .iter()
+            .enumerate()
+            .filter(|(_, tl)| class(tl) == class(&links[index]))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        if candidates.len() < 2 {
+            return index;
+        }
+
+        let n = self.scheduled.fetch_add(1, Ordering::Relaxed);
+        match scheduling {
+            MultilinkScheduling::First => index,
+            MultilinkScheduling::RoundRobin => candidates[n % candidates.len()],
+            MultilinkScheduling::LeastQueued => {
+                // Rotate the starting point to spread the messages among equally loaded links
+                let start = n % candidates.len();
+                candidates[start..]
+                    .iter()
+                    .chain(&candidates[..start])
+                    .copied()
+                    .min_by_key(|i| links[*i].pipeline.queued())
+                    .unwrap_or(index)
+            }
+            MultilinkScheduling::WeightedByRtt => {
+                // Each link is weighted by the ratio between the largest RTT and its own RTT
+                let rtts = candidates
+                    .iter()
+                    .map(|i| links[*i].link.config.rtt.map(|rtt| rtt.as_micros().max(1)))
+                    .collect::<Vec<_>>();
+                let max = rtts.iter().flatten().copied().max().unwrap_or(1);
+                let weights = rtts
+                    .iter()
+                    .map(|rtt| (max / rtt.unwrap_or(max)).clamp(1, MAX_RTT_WEIGHT) as usize)
+                    .collect::<Vec<_>>();
+                let mut slot = n % weights.iter().sum::<usize>();
+                for (i, weight) in candidates.iter().zip(weights) {
+                    if slot < weight {
+                        return *i;
+                    }
+                    slot -= weight;
+                }
+                index
+            }
+        }
+    }
+
+    /// Schedules the messages of `batches`, which have not been transmitted on a removed link,
+    /// on the remaining links of the transport. The messages keep their original sequence numbers:
+    /// the reliable ones are discarded by the peer if it already received following messages of
+    /// the same priority on the remaining links.
+    #[cfg(feature = "transport_multilink")]
+    pub(super) fn failover(&self, batches: Vec<WBatch>) {
+        let mut messages: Vec<TransportMessage> = vec![];
+        for batch in batches {
+            match batch.decode() {
+                Ok(msgs) => messages.extend(msgs),
+                Err(_) => tracing::debug!("Unable to decode pending batch on failover"),
+            }
+        }
+
+        let transport_links = zread!(self.links);
+        for msg in messages {
+            let (reliability, priority) = match &msg.body {
+                TransportBody::Frame(frame) => (frame.reliability, frame.ext_qos.priority()),
+                TransportBody::Fragment(fragment) => {
+                    (fragment.reliability, fragment.ext_qos.priority())
+                }
+                _ => continue,
+            };
+            let Some(index) = Self::select(
+                transport_links.iter().map(|tl| {
+                    (
+                        tl.link
+                            .config
+                            .reliability
+                            .unwrap_or(Reliability::from(tl.link.link.is_reliable())),
+                        tl.link.config.priorities.clone(),
+                    )
+                }),
+                reliability,
+                priority,
+            ) else {
+                tracing::debug!(
+                    "Pending messages dropped on failover because the transport has no links"
+                );
+                return;
+            };
+            let transport_link =
+                &transport_links[self.balance(&transport_links, index, reliability)];
+            tracing::trace!(
+                "Failed over {:?} to {} ({})",
+                msg,
+                transport_link.link.link.get_dst(),
+                self.get_zid()
+            );
+            if !transport_link
+                .pipeline
+                .push_transport_message(msg, priority)
+            {
+                tracing::debug!(
+                    "Unable to push pending message on failover to {}",
+                    self.config.zid
+                );
+            }
+        }
+    }
+
     fn schedule_on_link(&self, msg: NetworkMessage) -> bool {
         let transport_links = self
             .links
@@ -96,6 +244,13 @@ impl TransportUnicastUniversal {
             return false;
         };
 
+        #[cfg(feature = "transport_multilink")]
+        let transport_link_index = self.balance(
+            &transport_links,
+            transport_link_index,
+            Reliability::from(msg.is_reliable()),
+        );
+
         let transport_link = transport_links
             .get(transport_link_index)
             .expect("transport link index should be valid");
@@ -0,0 +1,229 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+pub mod common;
+
+// Restricting to macos by default because of no IPv6 support
+// on GitHub CI actions on Linux and Windows.
+#[cfg(target_family = "unix")]
+mod tests {
+    use std::{
+        net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4},
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use socket2::{Domain, Protocol, Socket, Type};
+    use tokio::net::UdpSocket;
+    use zenoh_core::ztimeout;
+    use zenoh_protocol::core::{Channel, EndPoint, Priority, Reliability};
+    use zenoh_transport::multicast::{FecConfig, TransportManagerBuilderMulticast};
+
+    use super::common::{
+        close_transport, message, open_transport, run, MSG_COUNT, SLEEP, SLEEP_COUNT, TIMEOUT,
+    };
+
+    const MSG_SIZE_ALL: [usize; 2] = [1_024, 131_072];
+    // One datagram out of LOSS is dropped by the relay
+    const LOSS: usize = 10;
+
+    fn fec() -> TransportManagerBuilderMulticast {
+        TransportManagerBuilderMulticast::default().fec(Some(
+            FecConfig::new(4, 2, Duration::from_millis(10)).unwrap(),
+        ))
+    }
+
+    // The address of the interface the multicast links are bound to
+    fn interface() -> Ipv4Addr {
+        zenoh_util::net::get_unicast_addresses_of_multicast_interfaces()
+            .into_iter()
+            .find_map(|addr| match addr {
+                IpAddr::V4(addr) if !addr.is_loopback() => Some(addr),
+                _ => None,
+            })
+            .unwrap_or(Ipv4Addr::UNSPECIFIED)
+    }
+
+    // A socket receiving the datagrams sent to `group`, alongside the multicast link of the peer
+    fn group_socket(group: SocketAddrV4, interface: Ipv4Addr) -> UdpSocket {
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP)).unwrap();
+        socket.set_reuse_address(true).unwrap();
+        socket.set_reuse_port(true).unwrap();
+        socket
+            .bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, group.port())).into())
+            .unwrap();
+        socket.join_multicast_v4(group.ip(), &interface).unwrap();
+        socket.set_nonblocking(true).unwrap();
+        UdpSocket::from_std(socket.into()).unwrap()
+    }
+
+    // A socket sending datagrams to a multicast group
+    fn sender_socket(interface: Ipv4Addr) -> UdpSocket {
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP)).unwrap();
+        socket.set_multicast_if_v4(&interface).unwrap();
+        socket
+            .bind(&SocketAddr::from((interface, 0)).into())
+            .unwrap();
+        socket.set_nonblocking(true).unwrap();
+        UdpSocket::from_std(socket.into()).unwrap()
+    }
+
+    // Forwards the datagrams received by `from` to `to`, but the ones sent by the relay itself
+    // from the `relayed` port, unless `drop` returns true.
+    fn forward(
+        from: UdpSocket,
+        to: SocketAddrV4,
+        sender: UdpSocket,
+        relayed: u16,
+        drop: impl Fn() -> bool + Send + 'static,
+    ) {
+        tokio::spawn(async move {
+            let mut buffer = vec![0u8; 65_536];
+            while let Ok((n, addr)) = from.recv_from(&mut buffer).await {
+                if addr.port() != relayed && !drop() {
+                    let _ = sender.send_to(&buffer[..n], to).await;
+                }
+            }
+        });
+    }
+
+    // A UDP relay forwarding the datagrams between the multicast groups `group01` and `group02`,
+    // and dropping one datagram out of LOSS from `group01` to `group02` once `lossy` is set.
+    // Returns the number of dropped datagrams.
+    fn lossy_relay(
+        group01: SocketAddrV4,
+        group02: SocketAddrV4,
+        lossy: Arc<AtomicBool>,
+    ) -> Arc<AtomicUsize> {
+        let interface = interface();
+        let (sender01, sender02) = (sender_socket(interface), sender_socket(interface));
+        let (relayed01, relayed02) = (
+            sender01.local_addr().unwrap().port(),
+            sender02.local_addr().unwrap().port(),
+        );
+
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let c_dropped = dropped.clone();
+        let forwarded = AtomicUsize::new(0);
+        let drop = move || {
+            let drop = lossy.load(Ordering::SeqCst)
+                && forwarded.fetch_add(1, Ordering::SeqCst) % LOSS == LOSS - 1;
+            if drop {
+                c_dropped.fetch_add(1, Ordering::SeqCst);
+            }
+            drop
+        };
+        forward(
+            group_socket(group01, interface),
+            group02,
+            sender02,
+            relayed01,
+            drop,
+        );
+        forward(
+            group_socket(group02, interface),
+            group01,
+            sender01,
+            relayed02,
+            || false,
+        );
+
+        dropped
+    }
+
+    #[cfg(feature = "transport_udp")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn transport_multicast_fec_udp_only() {
+        zenoh_util::init_log_from_env_or("error");
+
+        // Define the locator
+        let endpoints: Vec<EndPoint> = vec![
+            format!(
+                "udp/224.{}.{}.{}:22000",
+                rand::random::<u8>(),
+                rand::random::<u8>(),
+                rand::random::<u8>()
+            )
+            .parse()
+            .unwrap(),
+            // Disabling by default because of no IPv6 support
+            // on GitHub CI actions.
+            // format!("udp/{}", ZN_MULTICAST_IPV6_ADDRESS_DEFAULT)
+            //     .parse()
+            //     .unwrap(),
+        ];
+        // Define the reliability and congestion control
+        let channel = [
+            Channel {
+                priority: Priority::DEFAULT,
+                reliability: Reliability::BestEffort,
+            },
+            Channel {
+                priority: Priority::RealTime,
+                reliability: Reliability::BestEffort,
+            },
+        ];
+        // Run
+        run(&endpoints, &channel, &MSG_SIZE_ALL, fec).await;
+    }
+
+    #[cfg(feature = "transport_udp")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn transport_multicast_fec_lossy_udp_only() {
+        zenoh_util::init_log_from_env_or("error");
+
+        // The peers are on distinct multicast groups, the relay forwarding the datagrams between
+        // them
+        let group = |port| {
+            SocketAddrV4::new(
+                Ipv4Addr::new(224, rand::random(), rand::random(), rand::random()),
+                port,
+            )
+        };
+        let (group01, group02) = (group(22010), group(22011));
+        let lossy = Arc::new(AtomicBool::new(false));
+        let dropped = lossy_relay(group01, group02, lossy.clone());
+
+        let endpoint01: EndPoint = format!("udp/{group01}").parse().unwrap();
+        let endpoint02: EndPoint = format!("udp/{group02}").parse().unwrap();
+        let (peer01, peer02) = open_transport(&endpoint01, &endpoint02, &fec).await;
+
+        // The best-effort messages of the priorities protected by the FEC are all delivered
+        // despite the lost datagrams
+        let channel = Channel {
+            priority: Priority::DEFAULT,
+            reliability: Reliability::BestEffort,
+        };
+        let message = message(channel, 1_024);
+        lossy.store(true, Ordering::SeqCst);
+        for _ in 0..MSG_COUNT {
+            peer01.transport.schedule(message.clone()).unwrap();
+        }
+        ztimeout!(async {
+            while peer02.handler.get_count() < MSG_COUNT {
+                tokio::time::sleep(SLEEP_COUNT).await;
+            }
+        });
+        tokio::time::sleep(SLEEP).await;
+        lossy.store(false, Ordering::SeqCst);
+        println!("Dropped {} datagrams", dropped.load(Ordering::SeqCst));
+        assert!(dropped.load(Ordering::SeqCst) > 0);
+        assert_eq!(peer02.handler.get_count(), MSG_COUNT);
+
+        close_transport(peer01, peer02, &endpoint01).await;
+    }
+}
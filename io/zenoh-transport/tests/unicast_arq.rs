@@ -0,0 +1,225 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#[cfg(feature = "transport_udp")]
+mod tests {
+    use std::{
+        any::Any,
+        convert::TryFrom,
+        net::SocketAddr,
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+
+    use tokio::net::UdpSocket;
+    use zenoh_buffers::buffer::SplitBuffer;
+    use zenoh_core::ztimeout;
+    use zenoh_link::{EndPoint, Link};
+    use zenoh_protocol::{
+        core::{CongestionControl, Encoding, Priority, WhatAmI, ZenohIdProto},
+        network::{
+            push::{
+                ext::{NodeIdType, QoSType},
+                Push,
+            },
+            NetworkBody, NetworkMessage,
+        },
+        zenoh::{PushBody, Put},
+    };
+    use zenoh_result::ZResult;
+    use zenoh_transport::{
+        multicast::TransportMulticast, unicast::TransportUnicast, DummyTransportEventHandler,
+        TransportEventHandler, TransportManager, TransportMulticastEventHandler, TransportPeer,
+        TransportPeerEventHandler,
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_millis(100);
+
+    const MSG_COUNT: usize = 1_000;
+    const MSG_SIZE: usize = 1_024;
+    // One datagram out of LOSS is dropped by the proxy
+    const LOSS: usize = 5;
+
+    // Transport Handler for the router keeping the indexes of the received messages
+    struct SHRouterArq {
+        received: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl TransportEventHandler for SHRouterArq {
+        fn new_unicast(
+            &self,
+            _peer: TransportPeer,
+            _transport: TransportUnicast,
+        ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+            Ok(Arc::new(MHRouterArq {
+                received: self.received.clone(),
+            }))
+        }
+
+        fn new_multicast(
+            &self,
+            _transport: TransportMulticast,
+        ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+            panic!();
+        }
+    }
+
+    struct MHRouterArq {
+        received: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl TransportPeerEventHandler for MHRouterArq {
+        fn handle_message(&self, msg: NetworkMessage) -> ZResult<()> {
+            if let NetworkBody::Push(Push {
+                payload: PushBody::Put(put),
+                ..
+            }) = msg.body
+            {
+                let payload = put.payload.contiguous();
+                let index = usize::from_le_bytes(payload[..8].try_into().unwrap());
+                self.received.lock().unwrap().push(index);
+            }
+            Ok(())
+        }
+
+        fn new_link(&self, _link: Link) {}
+        fn del_link(&self, _link: Link) {}
+        fn closed(&self) {}
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    // A UDP proxy forwarding the datagrams of a single client to the router and dropping one
+    // datagram out of LOSS in each direction once `lossy` is set.
+    async fn lossy_proxy(port: u16, router_port: u16, lossy: Arc<AtomicBool>) {
+        let socket = Arc::new(UdpSocket::bind(("127.0.0.1", port)).await.unwrap());
+        let upstream = Arc::new(UdpSocket::bind(("127.0.0.1", 0)).await.unwrap());
+        upstream.connect(("127.0.0.1", router_port)).await.unwrap();
+        let client: Arc<Mutex<Option<SocketAddr>>> = Arc::new(Mutex::new(None));
+        let forwarded = Arc::new(AtomicUsize::new(0));
+        let drop = move || {
+            lossy.load(Ordering::SeqCst) && forwarded.fetch_add(1, Ordering::SeqCst) % LOSS == 0
+        };
+
+        let (c_socket, c_upstream, c_client, c_drop) = (
+            socket.clone(),
+            upstream.clone(),
+            client.clone(),
+            drop.clone(),
+        );
+        tokio::spawn(async move {
+            let mut buffer = vec![0u8; 65_536];
+            while let Ok((n, addr)) = c_socket.recv_from(&mut buffer).await {
+                *c_client.lock().unwrap() = Some(addr);
+                if !c_drop() {
+                    let _ = c_upstream.send(&buffer[..n]).await;
+                }
+            }
+        });
+        tokio::spawn(async move {
+            let mut buffer = vec![0u8; 65_536];
+            while let Ok(n) = upstream.recv(&mut buffer).await {
+                let addr = *client.lock().unwrap();
+                if let Some(addr) = addr {
+                    if !drop() {
+                        let _ = socket.send_to(&buffer[..n], addr).await;
+                    }
+                }
+            }
+        });
+    }
+
+    fn message(index: usize) -> NetworkMessage {
+        let mut payload = vec![0u8; MSG_SIZE];
+        payload[..8].copy_from_slice(&index.to_le_bytes());
+        Push {
+            wire_expr: "test".into(),
+            ext_qos: QoSType::new(Priority::DEFAULT, CongestionControl::Block, false),
+            ext_tstamp: None,
+            ext_nodeid: NodeIdType::DEFAULT,
+            payload: Put {
+                payload: payload.into(),
+                timestamp: None,
+                encoding: Encoding::empty(),
+                ext_sinfo: None,
+                #[cfg(feature = "shared-memory")]
+                ext_shm: None,
+                ext_attachment: None,
+                ext_unknown: vec![],
+            }
+            .into(),
+        }
+        .into()
+    }
+
+    async fn arq_udp(port: u16) {
+        let router_id = ZenohIdProto::try_from([1]).unwrap();
+        let client_id = ZenohIdProto::try_from([2]).unwrap();
+
+        let received = Arc::new(Mutex::new(vec![]));
+        let router_manager = TransportManager::builder()
+            .whatami(WhatAmI::Router)
+            .zid(router_id)
+            .unicast(TransportManager::config_unicast().arq(true))
+            .build(Arc::new(SHRouterArq {
+                received: received.clone(),
+            }))
+            .unwrap();
+        let router_endpoint: EndPoint = format!("udp/127.0.0.1:{}", port + 1).parse().unwrap();
+        ztimeout!(router_manager.add_listener(router_endpoint)).unwrap();
+
+        let lossy = Arc::new(AtomicBool::new(false));
+        lossy_proxy(port, port + 1, lossy.clone()).await;
+
+        let client_manager = TransportManager::builder()
+            .whatami(WhatAmI::Client)
+            .zid(client_id)
+            .unicast(TransportManager::config_unicast().arq(true))
+            .build(Arc::new(DummyTransportEventHandler))
+            .unwrap();
+        let endpoint: EndPoint = format!("udp/127.0.0.1:{port}").parse().unwrap();
+        let transport = ztimeout!(client_manager.open_transport_unicast(endpoint)).unwrap();
+
+        // The reliable messages are all delivered in order despite the lost datagrams
+        lossy.store(true, Ordering::SeqCst);
+        for index in 0..MSG_COUNT {
+            transport.schedule(message(index)).unwrap();
+        }
+        ztimeout!(async {
+            while received.lock().unwrap().len() < MSG_COUNT {
+                tokio::time::sleep(SLEEP).await;
+            }
+        });
+        tokio::time::sleep(SLEEP).await;
+        assert_eq!(
+            *received.lock().unwrap(),
+            (0..MSG_COUNT).collect::<Vec<_>>()
+        );
+
+        ztimeout!(client_manager.close());
+        ztimeout!(router_manager.close());
+        tokio::time::sleep(SLEEP).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn transport_unicast_arq_udp() {
+        zenoh_util::init_log_from_env_or("error");
+        arq_udp(18200).await;
+    }
+}
@@ -39,6 +39,7 @@ mod tests {
     };
     use zenoh_result::ZResult;
     use zenoh_transport::{
+        common::compression::{CompressionAlgorithm, CompressionConfig, CompressionDictionary},
         multicast::TransportMulticast,
         unicast::{test_helpers::make_transport_manager_builder, TransportUnicast},
         TransportEventHandler, TransportManager, TransportMulticastEventHandler, TransportPeer,
@@ -160,6 +161,7 @@ mod tests {
         client_endpoints: &[EndPoint],
         server_endpoints: &[EndPoint],
         lowlatency_transport: bool,
+        compression: &CompressionConfig,
     ) -> (
         TransportManager,
         Arc<SHRouter>,
@@ -179,7 +181,8 @@ mod tests {
             false,
             lowlatency_transport,
         )
-        .compression(true);
+        .compression(true)
+        .compression_config(compression.clone());
         let router_manager = TransportManager::builder()
             .zid(router_id)
             .whatami(WhatAmI::Router)
@@ -201,7 +204,8 @@ mod tests {
             false,
             lowlatency_transport,
         )
-        .compression(true);
+        .compression(true)
+        .compression_config(compression.clone());
         let client_manager = TransportManager::builder()
             .whatami(WhatAmI::Client)
             .zid(client_id)
@@ -333,6 +337,7 @@ mod tests {
         channel: Channel,
         msg_size: usize,
         lowlatency_transport: bool,
+        compression: &CompressionConfig,
     ) {
         println!(
             "\n>>> Running test for:  {:?}, {:?}, {:?}, {}",
@@ -341,7 +346,13 @@ mod tests {
 
         #[allow(unused_variables)] // Used when stats feature is enabled
         let (router_manager, router_handler, client_manager, client_transport) =
-            open_transport_unicast(client_endpoints, server_endpoints, lowlatency_transport).await;
+            open_transport_unicast(
+                client_endpoints,
+                server_endpoints,
+                lowlatency_transport,
+                compression,
+            )
+            .await;
 
         test_transport(
             router_handler.clone(),
@@ -379,6 +390,7 @@ mod tests {
         channel: &[Channel],
         msg_size: &[usize],
         lowlatency_transport: bool,
+        compression: &CompressionConfig,
     ) {
         for ch in channel.iter() {
             for ms in msg_size.iter() {
@@ -388,6 +400,7 @@ mod tests {
                     *ch,
                     *ms,
                     lowlatency_transport,
+                    compression,
                 )
                 .await;
             }
@@ -399,8 +412,17 @@ mod tests {
         server_endpoints: &[EndPoint],
         channel: &[Channel],
         msg_size: &[usize],
+        compression: &CompressionConfig,
     ) {
-        run_internal(client_endpoints, server_endpoints, channel, msg_size, false).await;
+        run_internal(
+            client_endpoints,
+            server_endpoints,
+            channel,
+            msg_size,
+            false,
+            compression,
+        )
+        .await;
     }
 
     async fn run_with_lowlatency_transport(
@@ -413,7 +435,15 @@ mod tests {
             println!("LowLatency transport doesn't support more than one link, so this test would produce MAX_LINKS error!");
             panic!();
         }
-        run_internal(client_endpoints, server_endpoints, channel, msg_size, true).await;
+        run_internal(
+            client_endpoints,
+            server_endpoints,
+            channel,
+            msg_size,
+            true,
+            &CompressionConfig::default(),
+        )
+        .await;
     }
 
     #[cfg(feature = "transport_tcp")]
@@ -438,7 +468,52 @@ mod tests {
             },
         ];
         // Run
-        run_with_universal_transport(&endpoints, &endpoints, &channel, &MSG_SIZE_ALL).await;
+        run_with_universal_transport(
+            &endpoints,
+            &endpoints,
+            &channel,
+            &MSG_SIZE_ALL,
+            &CompressionConfig::default(),
+        )
+        .await;
+    }
+
+    #[cfg(all(feature = "transport_tcp", feature = "transport_compression_zstd"))]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn transport_unicast_compression_zstd_tcp_only() {
+        zenoh_util::init_log_from_env_or("error");
+
+        // Define the locators
+        let endpoints: Vec<EndPoint> = vec![format!("tcp/127.0.0.1:{}", 19020).parse().unwrap()];
+        // Define the reliability and congestion control
+        let channel = [
+            Channel {
+                priority: Priority::DEFAULT,
+                reliability: Reliability::Reliable,
+            },
+            Channel {
+                priority: Priority::RealTime,
+                reliability: Reliability::Reliable,
+            },
+        ];
+        // Compress with zstd and a dictionary the data priorities only
+        let compression = CompressionConfig {
+            algorithms: vec![CompressionAlgorithm::Zstd, CompressionAlgorithm::Lz4],
+            zstd_level: 3,
+            zstd_dictionary: Some(CompressionDictionary::new(vec![0u8; 1_024])),
+            priorities: Some("4-7".parse().unwrap()),
+            protocols: None,
+            threshold: 256,
+        };
+        // Run
+        run_with_universal_transport(
+            &endpoints,
+            &endpoints,
+            &channel,
+            &MSG_SIZE_ALL,
+            &compression,
+        )
+        .await;
     }
 
     #[cfg(feature = "transport_tcp")]
@@ -485,7 +560,14 @@ mod tests {
             },
         ];
         // Run
-        run_with_universal_transport(&endpoints, &endpoints, &channel, &MSG_SIZE_NOFRAG).await;
+        run_with_universal_transport(
+            &endpoints,
+            &endpoints,
+            &channel,
+            &MSG_SIZE_NOFRAG,
+            &CompressionConfig::default(),
+        )
+        .await;
     }
 
     #[cfg(feature = "transport_udp")]
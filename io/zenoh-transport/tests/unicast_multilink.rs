@@ -484,6 +484,404 @@ mod tests {
         multilink_transport(&endpoint).await;
     }
 
+    #[cfg(feature = "transport_tcp")]
+    mod balancing {
+        use std::{
+            any::Any,
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Mutex,
+            },
+        };
+
+        use tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::{TcpListener, TcpStream},
+        };
+        use tokio_util::sync::CancellationToken;
+        use zenoh_buffers::buffer::SplitBuffer;
+        use zenoh_config::MultilinkScheduling;
+        use zenoh_link::Link;
+        use zenoh_protocol::{
+            core::{CongestionControl, Encoding, Priority, Reliability},
+            network::{
+                push::{
+                    ext::{NodeIdType, QoSType},
+                    Push,
+                },
+                NetworkBody, NetworkMessage,
+            },
+            zenoh::{PushBody, Put},
+        };
+
+        use super::*;
+
+        const MSG_COUNT: usize = 1_000;
+        const MSG_SIZE: usize = 1_024;
+
+        // Transport Handler for the router recording the index of the received messages
+        struct SHRouterCount {
+            received: Arc<Mutex<Vec<usize>>>,
+        }
+
+        impl TransportEventHandler for SHRouterCount {
+            fn new_unicast(
+                &self,
+                _peer: TransportPeer,
+                _transport: TransportUnicast,
+            ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+                Ok(Arc::new(MHRouterCount {
+                    received: self.received.clone(),
+                }))
+            }
+
+            fn new_multicast(
+                &self,
+                _transport: TransportMulticast,
+            ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+                panic!();
+            }
+        }
+
+        struct MHRouterCount {
+            received: Arc<Mutex<Vec<usize>>>,
+        }
+
+        impl TransportPeerEventHandler for MHRouterCount {
+            fn handle_message(&self, msg: NetworkMessage) -> ZResult<()> {
+                if let NetworkBody::Push(Push {
+                    payload: PushBody::Put(put),
+                    ..
+                }) = msg.body
+                {
+                    let payload = put.payload.contiguous();
+                    let index = usize::from_le_bytes(payload[..8].try_into().unwrap());
+                    self.received.lock().unwrap().push(index);
+                }
+                Ok(())
+            }
+
+            fn new_link(&self, _link: Link) {}
+            fn del_link(&self, _link: Link) {}
+            fn closed(&self) {}
+
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
+        }
+
+        // A TCP proxy forwarding the connections to the router, counting the bytes sent by the
+        // client on each connection and allowing to cut them. The bytes sent by the router on the
+        // i-th connection are delayed by `delays[i]`, if any, to increase the RTT of the link.
+        type Connections = Arc<Mutex<Vec<(Arc<AtomicUsize>, CancellationToken)>>>;
+
+        async fn proxy(port: u16, router_port: u16, delays: Vec<Duration>) -> Connections {
+            let listener = TcpListener::bind(("127.0.0.1", port)).await.unwrap();
+            let connections: Connections = Arc::new(Mutex::new(vec![]));
+            let c_connections = connections.clone();
+            tokio::spawn(async move {
+                while let Ok((mut client, _)) = listener.accept().await {
+                    let mut router = TcpStream::connect(("127.0.0.1", router_port))
+                        .await
+                        .unwrap();
+                    let bytes = Arc::new(AtomicUsize::new(0));
+                    let token = CancellationToken::new();
+                    let delay = {
+                        let mut connections = c_connections.lock().unwrap();
+                        let delay = delays.get(connections.len()).copied();
+                        connections.push((bytes.clone(), token.clone()));
+                        delay
+                    };
+                    tokio::spawn(async move {
+                        let (mut client_r, mut client_w) = client.split();
+                        let (mut router_r, mut router_w) = router.split();
+                        let upstream = async {
+                            let mut buffer = vec![0u8; 65_536];
+                            loop {
+                                let n = client_r.read(&mut buffer).await?;
+                                if n == 0 {
+                                    break;
+                                }
+                                bytes.fetch_add(n, Ordering::SeqCst);
+                                router_w.write_all(&buffer[..n]).await?;
+                            }
+                            Ok::<_, std::io::Error>(())
+                        };
+                        let downstream = async {
+                            let mut buffer = vec![0u8; 65_536];
+                            loop {
+                                let n = router_r.read(&mut buffer).await?;
+                                if n == 0 {
+                                    break;
+                                }
+                                if let Some(delay) = delay {
+                                    tokio::time::sleep(delay).await;
+                                }
+                                client_w.write_all(&buffer[..n]).await?;
+                            }
+                            Ok::<_, std::io::Error>(())
+                        };
+                        tokio::select! {
+                            _ = upstream => {}
+                            _ = downstream => {}
+                            _ = token.cancelled() => {}
+                        }
+                    });
+                }
+            });
+            connections
+        }
+
+        async fn setup(
+            port: u16,
+            scheduling: MultilinkScheduling,
+            delays: Vec<Duration>,
+        ) -> (
+            TransportManager,
+            TransportManager,
+            TransportUnicast,
+            Arc<Mutex<Vec<usize>>>,
+            Connections,
+        ) {
+            let router_id = ZenohIdProto::try_from([1]).unwrap();
+            let client_id = ZenohIdProto::try_from([2]).unwrap();
+
+            let received = Arc::new(Mutex::new(vec![]));
+            let unicast = TransportManager::config_unicast().max_links(2);
+            let router_manager = TransportManager::builder()
+                .whatami(WhatAmI::Router)
+                .zid(router_id)
+                .unicast(unicast)
+                .build(Arc::new(SHRouterCount {
+                    received: received.clone(),
+                }))
+                .unwrap();
+            let router_endpoint: EndPoint = format!("tcp/127.0.0.1:{}", port + 1).parse().unwrap();
+            ztimeout!(router_manager.add_listener(router_endpoint)).unwrap();
+
+            let connections = proxy(port, port + 1, delays).await;
+
+            let unicast = TransportManager::config_unicast()
+                .max_links(2)
+                .multilink_scheduling(scheduling)
+                .multilink_failover(true);
+            let client_manager = TransportManager::builder()
+                .whatami(WhatAmI::Client)
+                .zid(client_id)
+                .unicast(unicast)
+                .build(Arc::new(SHClientOpenClose::new()))
+                .unwrap();
+
+            // Open two links through the proxy
+            let endpoint: EndPoint = format!("tcp/127.0.0.1:{port}").parse().unwrap();
+            let transport =
+                ztimeout!(client_manager.open_transport_unicast(endpoint.clone())).unwrap();
+            ztimeout!(client_manager.open_transport_unicast(endpoint)).unwrap();
+            assert_eq!(transport.get_links().unwrap().len(), 2);
+
+            (
+                router_manager,
+                client_manager,
+                transport,
+                received,
+                connections,
+            )
+        }
+
+        fn send(
+            transport: &TransportUnicast,
+            indexes: std::ops::Range<usize>,
+            reliability: Reliability,
+        ) {
+            for index in indexes {
+                let mut payload = vec![0u8; MSG_SIZE];
+                payload[..8].copy_from_slice(&index.to_le_bytes());
+                let mut message: NetworkMessage = Push {
+                    wire_expr: "test".into(),
+                    ext_qos: QoSType::new(Priority::DEFAULT, CongestionControl::Block, false),
+                    ext_tstamp: None,
+                    ext_nodeid: NodeIdType::DEFAULT,
+                    payload: Put {
+                        payload: payload.into(),
+                        timestamp: None,
+                        encoding: Encoding::empty(),
+                        ext_sinfo: None,
+                        #[cfg(feature = "shared-memory")]
+                        ext_shm: None,
+                        ext_attachment: None,
+                        ext_unknown: vec![],
+                    }
+                    .into(),
+                }
+                .into();
+                message.reliability = reliability;
+                transport.schedule(message).unwrap();
+            }
+        }
+
+        async fn wait_count(received: &Mutex<Vec<usize>>, expected: usize) {
+            ztimeout!(async {
+                while received.lock().unwrap().len() < expected {
+                    tokio::time::sleep(SLEEP).await;
+                }
+            });
+            tokio::time::sleep(SLEEP).await;
+            assert_eq!(received.lock().unwrap().len(), expected);
+        }
+
+        fn bytes(connections: &Connections) -> Vec<usize> {
+            connections
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(bytes, _)| bytes.load(Ordering::SeqCst))
+                .collect()
+        }
+
+        async fn multilink_scheduling(
+            port: u16,
+            scheduling: MultilinkScheduling,
+            delays: Vec<Duration>,
+        ) -> Vec<usize> {
+            let (router_manager, client_manager, transport, received, connections) =
+                setup(port, scheduling, delays).await;
+
+            // The best-effort messages balanced among the links are all received
+            let before = bytes(&connections);
+            send(&transport, 0..MSG_COUNT, Reliability::BestEffort);
+            wait_count(&received, MSG_COUNT).await;
+            let sent = bytes(&connections)
+                .into_iter()
+                .zip(before)
+                .map(|(after, before)| after - before)
+                .collect::<Vec<_>>();
+            println!("The links transmitted {sent:?} bytes");
+
+            ztimeout!(client_manager.close());
+            ztimeout!(router_manager.close());
+            tokio::time::sleep(SLEEP).await;
+
+            sent
+        }
+
+        #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+        async fn multilink_round_robin_tcp() {
+            zenoh_util::init_log_from_env_or("error");
+
+            // Each link transmitted about half of the messages
+            let sent = multilink_scheduling(18100, MultilinkScheduling::RoundRobin, vec![]).await;
+            assert_eq!(sent.len(), 2);
+            for sent in sent {
+                assert!(sent > MSG_COUNT * MSG_SIZE / 4);
+            }
+        }
+
+        #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+        async fn multilink_least_queued_tcp() {
+            zenoh_util::init_log_from_env_or("error");
+
+            // The links being equally loaded, each one transmitted about half of the messages
+            let sent = multilink_scheduling(18102, MultilinkScheduling::LeastQueued, vec![]).await;
+            assert_eq!(sent.len(), 2);
+            for sent in sent {
+                assert!(sent > MSG_COUNT * MSG_SIZE / 4);
+            }
+        }
+
+        #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+        async fn multilink_weighted_by_rtt_tcp() {
+            zenoh_util::init_log_from_env_or("error");
+
+            // The RTT of the second link is increased by at least 50 ms: the first link, whose
+            // RTT is much smaller, transmitted most of the messages
+            let sent = multilink_scheduling(
+                18104,
+                MultilinkScheduling::WeightedByRtt,
+                vec![Duration::ZERO, Duration::from_millis(50)],
+            )
+            .await;
+            assert_eq!(sent.len(), 2);
+            assert!(sent[0] > MSG_COUNT * MSG_SIZE / 2);
+            assert!(sent[0] > 4 * sent[1]);
+        }
+
+        #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+        async fn multilink_round_robin_reliable_in_order_tcp() {
+            zenoh_util::init_log_from_env_or("error");
+
+            let (router_manager, client_manager, transport, received, _connections) =
+                setup(18108, MultilinkScheduling::RoundRobin, vec![]).await;
+
+            // The reliable messages are not balanced among the links: they are received in order
+            send(&transport, 0..MSG_COUNT, Reliability::Reliable);
+            wait_count(&received, MSG_COUNT).await;
+            assert_eq!(
+                *received.lock().unwrap(),
+                (0..MSG_COUNT).collect::<Vec<_>>()
+            );
+
+            ztimeout!(client_manager.close());
+            ztimeout!(router_manager.close());
+            tokio::time::sleep(SLEEP).await;
+        }
+
+        #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+        async fn multilink_failover_tcp() {
+            zenoh_util::init_log_from_env_or("error");
+
+            let (router_manager, client_manager, transport, received, connections) =
+                setup(18106, MultilinkScheduling::RoundRobin, vec![]).await;
+
+            // The reliable messages are all transmitted on the same link
+            let before = bytes(&connections);
+            send(&transport, 0..MSG_COUNT, Reliability::Reliable);
+            wait_count(&received, MSG_COUNT).await;
+            let sent = bytes(&connections)
+                .into_iter()
+                .zip(before)
+                .map(|(after, before)| after - before)
+                .collect::<Vec<_>>();
+            let reliable = if sent[0] > sent[1] { 0 } else { 1 };
+
+            // Cut this link while it transmits messages: the transport keeps running on the
+            // other one, on which the pending messages are failed over
+            send(&transport, MSG_COUNT..2 * MSG_COUNT, Reliability::Reliable);
+            connections.lock().unwrap()[reliable].1.cancel();
+            ztimeout!(async {
+                while transport.get_links().unwrap().len() != 1 {
+                    tokio::time::sleep(SLEEP).await;
+                }
+            });
+
+            send(
+                &transport,
+                2 * MSG_COUNT..3 * MSG_COUNT,
+                Reliability::Reliable,
+            );
+            ztimeout!(async {
+                while received.lock().unwrap().last() != Some(&(3 * MSG_COUNT - 1)) {
+                    tokio::time::sleep(SLEEP).await;
+                }
+            });
+
+            // Only the messages already written on the cut link may be lost: the messages are
+            // received in order, without duplicates, and the ones sent after the cut are all
+            // received
+            let received = received.lock().unwrap().clone();
+            println!(
+                "Lost {} messages on the cut link",
+                3 * MSG_COUNT - received.len()
+            );
+            assert!(received.windows(2).all(|w| w[0] < w[1]));
+            assert_eq!(received[..MSG_COUNT], (0..MSG_COUNT).collect::<Vec<_>>());
+            assert!((2 * MSG_COUNT..3 * MSG_COUNT).all(|i| received.binary_search(&i).is_ok()));
+
+            ztimeout!(client_manager.close());
+            ztimeout!(router_manager.close());
+            tokio::time::sleep(SLEEP).await;
+        }
+    }
+
     #[cfg(feature = "transport_udp")]
     #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
     async fn multilink_udp_only() {
@@ -18,8 +18,8 @@ use tokio::sync::RwLock;
 use zenoh::{internal::Value, key_expr::OwnedKeyExpr, time::Timestamp, Result as ZResult};
 use zenoh_backend_traits::{
     config::{StorageConfig, VolumeConfig},
-    Capability, History, Persistence, Storage, StorageInsertionResult, StoredData, Volume,
-    VolumeInstance,
+    Capability, History, Persistence, Storage, StorageInsertionResult, StorageQuery, StoredData,
+    Volume, VolumeInstance,
 };
 use zenoh_plugin_trait::{plugin_long_version, plugin_version, Plugin};
 
@@ -102,14 +102,10 @@ impl Storage for ExampleStorage {
         Ok(StorageInsertionResult::Deleted)
     }
 
-    async fn get(
-        &mut self,
-        key: Option<OwnedKeyExpr>,
-        _parameters: &str,
-    ) -> ZResult<Vec<StoredData>> {
-        match self.map.read().await.get(&key) {
-            Some(v) => Ok(vec![v.clone()]),
-            None => Err(format!("Key {:?} is not present", key).into()),
+    async fn get(&mut self, query: StorageQuery) -> ZResult<Vec<StoredData>> {
+        match self.map.read().await.get(&query.key) {
+            Some(v) => Ok(query.select([v.clone()])),
+            None => Err(format!("Key {:?} is not present", query.key).into()),
         }
     }
 
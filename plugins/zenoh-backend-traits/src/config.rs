@@ -11,7 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use std::{convert::TryFrom, time::Duration};
+use std::{convert::TryFrom, path::PathBuf, time::Duration};
 
 use const_format::concatcp;
 use derive_more::{AsMut, AsRef};
@@ -67,6 +67,19 @@ pub struct StorageConfig {
     pub garbage_collection_config: GarbageCollectionConfig,
     // Note: ReplicaConfig is optional. Alignment will be performed only if it is a replica
     pub replication: Option<ReplicaConfig>,
+    // Note: BatchingConfig is optional. Samples are grouped in batches only if it is set and if the
+    //       Storage supports batches
+    pub batching: Option<BatchingConfig>,
+    // Note: RetentionConfig is optional. The content of the storage is only bounded if it is set
+    pub retention: Option<RetentionConfig>,
+    // Note: the secondary indexes are optional. Queries can only filter the keys with the indexes
+    //       declared here
+    pub indexes: Vec<IndexConfig>,
+    // Note: the snapshots directory is optional. Snapshots can only be exported and imported if it
+    //       is set, the paths of the snapshots being relative to it
+    pub snapshots_dir: Option<PathBuf>,
+    // Note: the changes applied to the storage are only published if it is set
+    pub change_feed: bool,
 }
 // Note: All parameters should be same for replicas, else will result on huge overhead
 #[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
@@ -169,6 +182,78 @@ impl Default for GarbageCollectionConfig {
     }
 }
 
+// The configuration for grouping the samples received by a storage in batches, applied atomically
+#[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub struct BatchingConfig {
+    // The maximum duration during which samples are accumulated before the batch is applied, it
+    // can't be zero
+    pub window: Duration,
+    // The maximum number of samples in a batch. A batch is applied as soon as it is reached
+    pub max_samples: usize,
+}
+
+impl Default for BatchingConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_millis(10),
+            max_samples: 1_000,
+        }
+    }
+}
+
+// The configuration of the rules bounding the content of a storage. The keys breaking a rule are
+// deleted by the storage manager, the oldest first
+#[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub struct RetentionConfig {
+    // The duration between two enforcements of the rules
+    // The enforcement will be scheduled as a periodic event with this period
+    pub period: Duration,
+    // The maximum age of the keys matching a key expression
+    pub max_age: Vec<MaxAgeRule>,
+    // The maximum number of keys stored
+    pub max_keys: Option<usize>,
+    // The maximum number of bytes of the payloads stored
+    pub max_bytes: Option<u64>,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            period: Duration::from_secs(30),
+            max_age: Vec::new(),
+            max_keys: None,
+            max_bytes: None,
+        }
+    }
+}
+
+// The maximum age of the keys included in a key expression. If several rules apply to a key, the
+// shortest age is used
+#[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub struct MaxAgeRule {
+    pub key_expr: OwnedKeyExpr,
+    pub age: Duration,
+}
+
+// A secondary index of a storage, associating values derived from the samples it receives to
+// their keys. Queries can select the keys through the index with the `_filter` parameter
+#[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub struct IndexConfig {
+    // The name of the index, used to refer to it in the `_filter` parameter of the queries
+    pub name: String,
+    // The field of the samples the values of the index are derived from
+    pub field: IndexedField,
+}
+
+#[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub enum IndexedField {
+    // The encoding of the sample, e.g. `application/json`
+    Encoding,
+    // The attachment of the sample, read as UTF-8 and split with the separator, if any, into
+    // several values (e.g. tags)
+    Attachment { separator: Option<String> },
+}
+
 #[derive(Debug)]
 pub enum ConfigDiff {
     DeleteVolume(VolumeConfig),
@@ -624,6 +709,191 @@ impl StorageConfig {
             }
             None => None,
         };
+        let batching = match config.get("batching") {
+            Some(s) => {
+                let mut batching = BatchingConfig::default();
+                if let Some(window) = s.get("window") {
+                    let window = window.to_string().parse::<u64>();
+                    match window {
+                        Ok(window) if window > 0 => batching.window = Duration::from_millis(window),
+                        _ => bail!(
+                            "Invalid value for field `window` in `batching` of storage `{}`. Only \
+                             strictly positive integer values are accepted.",
+                            plugin_name
+                        ),
+                    }
+                }
+                if let Some(max_samples) = s.get("max_samples") {
+                    let max_samples = max_samples.to_string().parse::<usize>();
+                    match max_samples {
+                        Ok(max_samples) if max_samples > 0 => batching.max_samples = max_samples,
+                        _ => bail!(
+                            "Invalid value for field `max_samples` in `batching` of storage `{}`. \
+                             Only strictly positive integer values are accepted.",
+                            plugin_name
+                        ),
+                    }
+                }
+                Some(batching)
+            }
+            None => None,
+        };
+        let retention = match config.get("retention") {
+            Some(s) => {
+                let mut retention = RetentionConfig::default();
+                if let Some(period) = s.get("period") {
+                    let period = period.to_string().parse::<u64>();
+                    match period {
+                        Ok(period) if period > 0 => retention.period = Duration::from_secs(period),
+                        _ => bail!(
+                            "Invalid value for field `period` in `retention` of storage `{}`. \
+                             Only strictly positive integer values are accepted.",
+                            plugin_name
+                        ),
+                    }
+                }
+                if let Some(max_age) = s.get("max_age") {
+                    let Some(rules) = max_age.as_array() else {
+                        bail!(
+                            "Invalid type for field `max_age` in `retention` of storage `{}`. \
+                             Only arrays of objects with `key_expr` and `age` fields are accepted.",
+                            plugin_name
+                        )
+                    };
+                    for rule in rules {
+                        let rule_key_expr = match rule.get("key_expr").and_then(|x| x.as_str()) {
+                            Some(s) => match keyexpr::new(s) {
+                                Ok(ke) => ke.to_owned(),
+                                Err(e) => {
+                                    bail!("key_expr='{}' is not a valid key-expression: {}", s, e)
+                                }
+                            },
+                            None => bail!(
+                                "Elements of the field `max_age` in `retention` of storage `{}` \
+                                 must have a `key_expr` string-typed field",
+                                plugin_name
+                            ),
+                        };
+                        if !rule_key_expr.intersects(&key_expr) {
+                            bail!(
+                                "The key_expr='{}' of a `max_age` rule does not intersect with \
+                                 the key_expr='{}' of the storage",
+                                rule_key_expr,
+                                key_expr
+                            )
+                        }
+                        let age = match rule.get("age").map(|x| x.to_string().parse::<u64>()) {
+                            Some(Ok(age)) => Duration::from_secs(age),
+                            _ => bail!(
+                                "Elements of the field `max_age` in `retention` of storage `{}` \
+                                 must have an `age` integer field",
+                                plugin_name
+                            ),
+                        };
+                        retention.max_age.push(MaxAgeRule {
+                            key_expr: rule_key_expr,
+                            age,
+                        });
+                    }
+                }
+                if let Some(max_keys) = s.get("max_keys") {
+                    let max_keys = max_keys.to_string().parse::<usize>();
+                    if let Ok(max_keys) = max_keys {
+                        retention.max_keys = Some(max_keys);
+                    } else {
+                        bail!(
+                            "Invalid type for field `max_keys` in `retention` of storage `{}`. \
+                             Only integer values are accepted.",
+                            plugin_name
+                        )
+                    }
+                }
+                if let Some(max_bytes) = s.get("max_bytes") {
+                    let max_bytes = max_bytes.to_string().parse::<u64>();
+                    if let Ok(max_bytes) = max_bytes {
+                        retention.max_bytes = Some(max_bytes);
+                    } else {
+                        bail!(
+                            "Invalid type for field `max_bytes` in `retention` of storage `{}`. \
+                             Only integer values are accepted.",
+                            plugin_name
+                        )
+                    }
+                }
+                Some(retention)
+            }
+            None => None,
+        };
+        let indexes = match config.get("indexes") {
+            Some(indexes) => {
+                let Some(indexes) = indexes.as_object() else {
+                    bail!(
+                        "Invalid type for field `indexes` of storage `{}`. Only objects mapping \
+                         the names of the indexes to their configuration are accepted.",
+                        plugin_name
+                    )
+                };
+                let mut index_configs = Vec::with_capacity(indexes.len());
+                for (name, index) in indexes {
+                    if name.is_empty()
+                        || !name
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                    {
+                        bail!(
+                            "Invalid index name `{}` in `indexes` of storage `{}`. Only \
+                             alphanumeric characters, `_` and `-` are accepted.",
+                            name,
+                            plugin_name
+                        )
+                    }
+                    let field = match index.get("field").and_then(|x| x.as_str()) {
+                        Some("encoding") => IndexedField::Encoding,
+                        Some("attachment") => IndexedField::Attachment {
+                            separator: match index.get("separator") {
+                                Some(Value::String(s)) if !s.is_empty() => Some(s.clone()),
+                                None => None,
+                                _ => bail!(
+                                    "Invalid value for field `separator` of index `{}` of \
+                                     storage `{}`. Only non-empty strings are accepted.",
+                                    name,
+                                    plugin_name
+                                ),
+                            },
+                        },
+                        _ => bail!(
+                            "Invalid value for field `field` of index `{}` of storage `{}`. \
+                             Accepted values: ['encoding', 'attachment']",
+                            name,
+                            plugin_name
+                        ),
+                    };
+                    index_configs.push(IndexConfig {
+                        name: name.clone(),
+                        field,
+                    });
+                }
+                index_configs
+            }
+            None => Vec::new(),
+        };
+        let snapshots_dir = match config.get("snapshots_dir") {
+            Some(Value::String(dir)) if !dir.is_empty() => Some(PathBuf::from(dir)),
+            None => None,
+            _ => bail!(
+                "Invalid type for field `snapshots_dir` of storage `{}`. Only non-empty strings \
+                 are accepted.",
+                plugin_name
+            ),
+        };
+        let change_feed = match config.get("change_feed") {
+            Some(Value::Bool(change_feed)) => *change_feed,
+            None => false,
+            _ => bail!(
+                "Invalid type for field `change_feed` of storage `{}`. Only booleans are accepted.",
+                plugin_name
+            ),
+        };
         Ok(StorageConfig {
             name: storage_name.into(),
             key_expr,
@@ -633,6 +903,11 @@ impl StorageConfig {
             volume_cfg,
             garbage_collection_config,
             replication,
+            batching,
+            retention,
+            indexes,
+            snapshots_dir,
+            change_feed,
         })
     }
 }
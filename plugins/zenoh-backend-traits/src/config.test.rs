@@ -12,12 +12,16 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
-use std::time::Duration;
+use std::{str::FromStr, time::Duration};
 
 use serde_json::json;
 
 use super::StorageConfig;
-use crate::config::ReplicaConfig;
+use zenoh::key_expr::OwnedKeyExpr;
+
+use crate::config::{
+    BatchingConfig, IndexConfig, IndexedField, MaxAgeRule, ReplicaConfig, RetentionConfig,
+};
 
 #[test]
 fn test_replica_config() {
@@ -76,3 +80,199 @@ Actual message: {err}",
         })
     );
 }
+
+#[test]
+fn test_batching_config() {
+    let config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+    });
+    let storage_config = StorageConfig::try_from("test-plugin", "test-storage", &config).unwrap();
+    assert_eq!(storage_config.batching, None);
+
+    let empty_config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+        "batching": {}
+    });
+    let storage_config =
+        StorageConfig::try_from("test-plugin", "test-storage", &empty_config).unwrap();
+    assert_eq!(storage_config.batching, Some(BatchingConfig::default()));
+
+    let batching_config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+        "batching": {
+            "window": 50,
+            "max_samples": 200,
+        }
+    });
+    let storage_config =
+        StorageConfig::try_from("test-plugin", "test-storage", &batching_config).unwrap();
+    assert_eq!(
+        storage_config.batching,
+        Some(BatchingConfig {
+            window: Duration::from_millis(50),
+            max_samples: 200,
+        })
+    );
+
+    let incorrect_max_samples_config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+        "batching": {
+            "max_samples": 0,
+        }
+    });
+    assert!(
+        StorageConfig::try_from("test-plugin", "test-storage", &incorrect_max_samples_config)
+            .is_err()
+    );
+    let incorrect_window_config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+        "batching": {
+            "window": 0,
+        }
+    });
+    assert!(
+        StorageConfig::try_from("test-plugin", "test-storage", &incorrect_window_config).is_err()
+    );
+}
+
+#[test]
+fn test_retention_config() {
+    let empty_config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+        "retention": {}
+    });
+    let storage_config =
+        StorageConfig::try_from("test-plugin", "test-storage", &empty_config).unwrap();
+    assert_eq!(storage_config.retention, Some(RetentionConfig::default()));
+
+    let retention_config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+        "retention": {
+            "period": 5,
+            "max_age": [
+                { "key_expr": "test/tmp/**", "age": 60 },
+                { "key_expr": "**", "age": 3600 },
+            ],
+            "max_keys": 100,
+            "max_bytes": 4096,
+        }
+    });
+    let storage_config =
+        StorageConfig::try_from("test-plugin", "test-storage", &retention_config).unwrap();
+    assert_eq!(
+        storage_config.retention,
+        Some(RetentionConfig {
+            period: Duration::from_secs(5),
+            max_age: vec![
+                MaxAgeRule {
+                    key_expr: OwnedKeyExpr::from_str("test/tmp/**").unwrap(),
+                    age: Duration::from_secs(60),
+                },
+                MaxAgeRule {
+                    key_expr: OwnedKeyExpr::from_str("**").unwrap(),
+                    age: Duration::from_secs(3600),
+                },
+            ],
+            max_keys: Some(100),
+            max_bytes: Some(4096),
+        })
+    );
+
+    let disjoint_rule_config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+        "retention": {
+            "max_age": [{ "key_expr": "other/**", "age": 60 }],
+        }
+    });
+    assert!(StorageConfig::try_from("test-plugin", "test-storage", &disjoint_rule_config).is_err());
+
+    let missing_age_config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+        "retention": {
+            "max_age": [{ "key_expr": "test/**" }],
+        }
+    });
+    assert!(StorageConfig::try_from("test-plugin", "test-storage", &missing_age_config).is_err());
+}
+
+#[test]
+fn test_indexes_config() {
+    let no_indexes_config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+    });
+    let storage_config =
+        StorageConfig::try_from("test-plugin", "test-storage", &no_indexes_config).unwrap();
+    assert!(storage_config.indexes.is_empty());
+
+    let indexes_config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+        "indexes": {
+            "format": { "field": "encoding" },
+            "tags": { "field": "attachment", "separator": "," },
+            "owner": { "field": "attachment" },
+        }
+    });
+    let storage_config =
+        StorageConfig::try_from("test-plugin", "test-storage", &indexes_config).unwrap();
+    let mut indexes = storage_config.indexes;
+    indexes.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(
+        indexes,
+        vec![
+            IndexConfig {
+                name: "format".to_string(),
+                field: IndexedField::Encoding,
+            },
+            IndexConfig {
+                name: "owner".to_string(),
+                field: IndexedField::Attachment { separator: None },
+            },
+            IndexConfig {
+                name: "tags".to_string(),
+                field: IndexedField::Attachment {
+                    separator: Some(",".to_string())
+                },
+            },
+        ]
+    );
+
+    let unknown_field_config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+        "indexes": {
+            "size": { "field": "payload" },
+        }
+    });
+    assert!(StorageConfig::try_from("test-plugin", "test-storage", &unknown_field_config).is_err());
+
+    let invalid_name_config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+        "indexes": {
+            "a:b": { "field": "encoding" },
+        }
+    });
+    assert!(StorageConfig::try_from("test-plugin", "test-storage", &invalid_name_config).is_err());
+
+    let empty_separator_config = json!({
+        "key_expr": "test/**",
+        "volume": "memory",
+        "indexes": {
+            "tags": { "field": "attachment", "separator": "" },
+        }
+    });
+    assert!(
+        StorageConfig::try_from("test-plugin", "test-storage", &empty_separator_config).is_err()
+    );
+}
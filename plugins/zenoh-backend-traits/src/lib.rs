@@ -105,10 +105,11 @@
 //!     }
 //!
 //!     // When receiving a GET operation
-//!     async fn get(&mut self, key_expr: Option<OwnedKeyExpr>, parameters: &str) -> zenoh::Result<Vec<StoredData>> {
+//!     async fn get(&mut self, query: StorageQuery) -> zenoh::Result<Vec<StoredData>> {
 //!         // @TODO:
-//!         // get the data associated with key_expr and return it
-//!         // NOTE: in case parameters is not empty something smarter should be done with returned data...
+//!         // get the data associated with query.key and return it
+//!         // NOTE: `query.select()` can be used to filter the data according to the time range,
+//!         //       limit and order of the query.
 //!         Ok(Vec::new())
 //!     }
 //!
@@ -123,7 +124,7 @@
 use async_trait::async_trait;
 use const_format::concatcp;
 use zenoh::{
-    internal::Value,
+    internal::{bail, Value},
     key_expr::{keyexpr, OwnedKeyExpr},
     time::Timestamp,
     Result as ZResult,
@@ -133,6 +134,8 @@ use zenoh_util::concat_enabled_features;
 
 pub mod config;
 use config::StorageConfig;
+pub mod query;
+pub use query::{QueryOrder, StorageQuery};
 
 // No features are actually used in this crate, but this dummy list allows to demonstrate how to combine feature lists
 // from multiple crates. See impl `PluginStructVersion` for `VolumeConfig` below.
@@ -180,6 +183,34 @@ pub struct StoredData {
     pub timestamp: Timestamp,
 }
 
+/// A write operation, part of a batch applied through [`Storage::put_batch`].
+#[derive(Debug, Clone)]
+pub enum StorageWrite {
+    Put {
+        key: Option<OwnedKeyExpr>,
+        value: Value,
+        timestamp: Timestamp,
+    },
+    Delete {
+        key: Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+    },
+}
+
+/// Opaque position in the content of a storage, from which [`Storage::get_entries_page`] resumes.
+///
+/// Its content is only meaningful to the storage that generated it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntriesCursor(pub Vec<u8>);
+
+/// A page of the content of a storage (key, timestamp), returned by [`Storage::get_entries_page`].
+#[derive(Debug, Clone, Default)]
+pub struct EntriesPage {
+    pub entries: Vec<(Option<OwnedKeyExpr>, Timestamp)>,
+    /// The cursor to provide to retrieve the next page, `None` if this page is the last one.
+    pub next: Option<EntriesCursor>,
+}
+
 /// Trait to be implemented by a Backend.
 #[async_trait]
 pub trait Volume: Send + Sync {
@@ -198,7 +229,7 @@ pub type VolumeInstance = Box<dyn Volume + 'static>;
 
 impl StructVersion for VolumeInstance {
     fn struct_version() -> u64 {
-        1
+        2
     }
     fn struct_features() -> &'static str {
         concatcp!(zenoh::FEATURES, crate::FEATURES)
@@ -241,18 +272,91 @@ pub trait Storage: Send + Sync {
         timestamp: Timestamp,
     ) -> ZResult<StorageInsertionResult>;
 
-    /// Function to retrieve the sample associated with a single key.
-    /// A key can be `None` if it matches the `strip_prefix` exactly.
-    /// In order to avoid data loss, the storage must retrieve the `value` and `timestamp` associated with the `None` key
-    /// in a manner suitable for the given backend technology
-    async fn get(
+    /// Returns `true` if this storage natively implements [`Storage::put_batch`].
+    ///
+    /// The storage manager only groups the incoming samples in batches (see the `batching` configuration of the
+    /// storage) if this function returns `true`. Otherwise, each sample is stored individually.
+    fn supports_batch(&self) -> bool {
+        false
+    }
+
+    /// Function called with a batch of puts and deletes to apply *atomically* to this storage: either all the writes
+    /// are applied, or none is and an error is returned.
+    /// The writes must be applied in the order of the batch, which may contain several writes on the same key.
+    /// On success, the result of each write is returned, in the same order.
+    ///
+    /// The default implementation applies the writes one by one through [`Storage::put`] and [`Storage::delete`],
+    /// hence without any atomicity guarantee. Storages implementing this function must also override
+    /// [`Storage::supports_batch`].
+    async fn put_batch(
+        &mut self,
+        batch: Vec<StorageWrite>,
+    ) -> ZResult<Vec<StorageInsertionResult>> {
+        let mut results = Vec::with_capacity(batch.len());
+        for write in batch {
+            results.push(match write {
+                StorageWrite::Put {
+                    key,
+                    value,
+                    timestamp,
+                } => self.put(key, value, timestamp).await?,
+                StorageWrite::Delete { key, timestamp } => self.delete(key, timestamp).await?,
+            });
+        }
+        Ok(results)
+    }
+
+    /// Function called to permanently remove the updates (values and deletes) of a key stamped up to the provided
+    /// `timestamp` (included), returning the timestamps of the updates removed.
+    ///
+    /// The storage manager only calls it on storages with the [`History::All`] capability, when enforcing the
+    /// retention rules of the storage: the versions of a key older than its maximum age, or the oldest versions if
+    /// the storage exceeds its maximum size, are removed from its history.
+    ///
+    /// The default implementation returns an error: the values are then kept.
+    async fn purge(
         &mut self,
         key: Option<OwnedKeyExpr>,
-        parameters: &str,
-    ) -> ZResult<Vec<StoredData>>;
+        _timestamp: Timestamp,
+    ) -> ZResult<Vec<Timestamp>> {
+        bail!("Storage does not support purging the values of {key:?}")
+    }
+
+    /// Function to retrieve the samples associated with a single key, as described by the [`StorageQuery`].
+    /// The key of the query can be `None` if it matches the `strip_prefix` exactly.
+    /// In order to avoid data loss, the storage must retrieve the `value` and `timestamp` associated with the `None` key
+    /// in a manner suitable for the given backend technology
+    ///
+    /// If the query has no time range, only the latest value is expected. Otherwise, storages with the
+    /// [`History::All`] capability should return all the values stamped within that time range, ordered and limited
+    /// as requested (see [`StorageQuery::select`]).
+    async fn get(&mut self, query: StorageQuery) -> ZResult<Vec<StoredData>>;
 
     /// Function called to get the list of all storage content (key, timestamp)
     /// The latest Timestamp corresponding to each key is either the timestamp of the delete or put whichever is the latest.
     /// Remember to fetch the entry corresponding to the `None` key
     async fn get_all_entries(&self) -> ZResult<Vec<(Option<OwnedKeyExpr>, Timestamp)>>;
+
+    /// Function called to get a page of the storage content (key, timestamp), starting from the provided `cursor` or
+    /// from the beginning if it is `None`. A native implementation returns at most `page_size` entries per page.
+    /// The storage manager calls it repeatedly, with the cursor of the previous page, until a page without a `next`
+    /// cursor is returned. The same entry must not be returned twice.
+    ///
+    /// Storages holding large datasets should implement it with a native cursor, such that going through their
+    /// content does not require loading it all in memory. The default implementation falls back to
+    /// [`Storage::get_all_entries`] and returns the whole content of the storage as a single page.
+    async fn get_entries_page(
+        &self,
+        _cursor: Option<EntriesCursor>,
+        _page_size: usize,
+    ) -> ZResult<EntriesPage> {
+        Ok(EntriesPage {
+            entries: self.get_all_entries().await?,
+            next: None,
+        })
+    }
 }
+
+#[cfg(test)]
+#[path = "lib.test.rs"]
+mod tests;
@@ -0,0 +1,82 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::{str::FromStr, time::Duration};
+
+use async_trait::async_trait;
+use zenoh::{
+    internal::{bail, Value},
+    key_expr::OwnedKeyExpr,
+    time::{Timestamp, TimestampId, NTP64},
+    Result as ZResult,
+};
+
+use super::{Storage, StorageInsertionResult, StorageQuery, StoredData};
+
+/// A Storage that only knows how to list all its entries at once.
+struct EntriesOnlyStorage {
+    entries: Vec<(Option<OwnedKeyExpr>, Timestamp)>,
+}
+
+#[async_trait]
+impl Storage for EntriesOnlyStorage {
+    fn get_admin_status(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+
+    async fn put(
+        &mut self,
+        _key: Option<OwnedKeyExpr>,
+        _value: Value,
+        _timestamp: Timestamp,
+    ) -> ZResult<StorageInsertionResult> {
+        bail!("EntriesOnlyStorage is read-only")
+    }
+
+    async fn delete(
+        &mut self,
+        _key: Option<OwnedKeyExpr>,
+        _timestamp: Timestamp,
+    ) -> ZResult<StorageInsertionResult> {
+        bail!("EntriesOnlyStorage is read-only")
+    }
+
+    async fn get(&mut self, _query: StorageQuery) -> ZResult<Vec<StoredData>> {
+        bail!("EntriesOnlyStorage only lists its entries")
+    }
+
+    async fn get_all_entries(&self) -> ZResult<Vec<(Option<OwnedKeyExpr>, Timestamp)>> {
+        Ok(self.entries.clone())
+    }
+}
+
+#[tokio::test]
+async fn test_default_get_entries_page() {
+    let timestamp = Timestamp::new(
+        NTP64::from(Duration::from_secs(1)),
+        TimestampId::try_from([1]).unwrap(),
+    );
+    let mut entries = vec![(None, timestamp)];
+    for key in ["test/c", "test/a", "test/e", "test/b", "test/d"] {
+        entries.push((Some(OwnedKeyExpr::from_str(key).unwrap()), timestamp));
+    }
+    let storage = EntriesOnlyStorage {
+        entries: entries.clone(),
+    };
+
+    // The whole content is returned as a single page, whatever the page size
+    let page = storage.get_entries_page(None, 4).await.unwrap();
+    assert_eq!(page.entries, entries);
+    assert!(page.next.is_none());
+}
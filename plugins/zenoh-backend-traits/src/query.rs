@@ -0,0 +1,220 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{str::FromStr, time::SystemTime};
+
+use zenoh::{
+    key_expr::OwnedKeyExpr,
+    query::{Parameters, TimeBound, TimeRange, ZenohParameters},
+    time::Timestamp,
+    Result as ZResult,
+};
+use zenoh_result::bail;
+
+use crate::StoredData;
+
+/// The order in which the [StoredData] matching a [StorageQuery] should be returned, based on
+/// their [Timestamp].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QueryOrder {
+    /// From the oldest to the most recent value (default).
+    #[default]
+    Ascending,
+    /// From the most recent to the oldest value.
+    Descending,
+}
+
+impl FromStr for QueryOrder {
+    type Err = zenoh::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "asc" => Ok(QueryOrder::Ascending),
+            "desc" => Ok(QueryOrder::Descending),
+            _ => bail!(
+                "`{}` is not a valid ordering. Accepted values: ['asc', 'desc']",
+                s
+            ),
+        }
+    }
+}
+
+/// A `StorageQuery` is the typed representation of a query received by a Storage, for a single
+/// key.
+///
+/// It is built by the storage manager from the selector of the query it received such that
+/// backends do not have to parse the selector parameters themselves:
+/// - `_time=<time range>`: the time range in which the returned values must have been stamped
+///   (see [TimeRange] for the syntax). If absent, only the latest value is requested.
+/// - `_limit=<usize>`: the maximum number of values to return, which must be strictly positive.
+/// - `_order=asc|desc`: the order, based on their timestamp, in which the values are returned.
+///
+/// Backends with the [History::All](crate::History::All) capability are expected to return all
+/// the values stamped in the `time_range`. To facilitate doing so consistently across backends,
+/// the [StorageQuery::select] method can be used to filter, order and limit a set of values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageQuery {
+    /// The stripped key targeted by the query. It is `None` if it matched the `strip_prefix`
+    /// exactly.
+    pub key: Option<OwnedKeyExpr>,
+    /// The time range, resolved at the reception of the query. `None` means that only the latest
+    /// value is requested.
+    pub time_range: Option<TimeRange<SystemTime>>,
+    /// The maximum number of values to return.
+    pub limit: Option<usize>,
+    /// The order in which the values are returned.
+    pub order: QueryOrder,
+    /// The parameters of the query, including the ones parsed above. Backends can use them to
+    /// support additional, backend specific, parameters.
+    pub parameters: Parameters<'static>,
+}
+
+impl StorageQuery {
+    /// Name of the selector parameter used to limit the number of values returned.
+    pub const LIMIT_KEY: &'static str = "_limit";
+    /// Name of the selector parameter used to order the values returned.
+    pub const ORDER_KEY: &'static str = "_order";
+
+    /// Creates a `StorageQuery` requesting the latest value associated with the provided key.
+    pub fn latest(key: Option<OwnedKeyExpr>) -> Self {
+        Self {
+            key,
+            time_range: None,
+            limit: None,
+            order: QueryOrder::default(),
+            parameters: Parameters::empty(),
+        }
+    }
+
+    /// Creates a `StorageQuery` requesting the value associated with the provided key that has
+    /// exactly the provided [Timestamp].
+    pub fn at(key: Option<OwnedKeyExpr>, timestamp: &Timestamp) -> Self {
+        let instant = timestamp.get_time().to_system_time();
+        Self {
+            time_range: Some(TimeRange {
+                start: TimeBound::Inclusive(instant),
+                end: TimeBound::Inclusive(instant),
+            }),
+            ..Self::latest(key)
+        }
+    }
+
+    /// Creates a `StorageQuery` requesting all the values associated with the provided key,
+    /// regardless of their [Timestamp].
+    pub fn all(key: Option<OwnedKeyExpr>) -> Self {
+        Self {
+            time_range: Some(TimeRange {
+                start: TimeBound::Unbounded,
+                end: TimeBound::Unbounded,
+            }),
+            ..Self::latest(key)
+        }
+    }
+
+    /// Creates a `StorageQuery` for the provided key, extracting the time range, limit and order
+    /// from the provided selector parameters.
+    ///
+    /// Time expressions relative to "now" are resolved when this method is called.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if any of the `_time`, `_limit` or `_order` parameters
+    /// has an invalid value.
+    pub fn from_parameters(key: Option<OwnedKeyExpr>, parameters: &Parameters) -> ZResult<Self> {
+        let time_range = match parameters.time_range() {
+            Some(Ok(time_range)) => Some(time_range.resolve()),
+            Some(Err(e)) => bail!(
+                "Invalid value for parameter `{}`: {e}",
+                Parameters::TIME_RANGE_KEY
+            ),
+            None => None,
+        };
+        let limit = match parameters.get(Self::LIMIT_KEY) {
+            Some(limit) => match limit.parse::<usize>() {
+                Ok(0) => bail!(
+                    "Invalid value for parameter `{}`: 0. Only positive integers are accepted.",
+                    Self::LIMIT_KEY
+                ),
+                Ok(limit) => Some(limit),
+                Err(e) => bail!(
+                    "Invalid value for parameter `{}`: {e}. Only positive integers are accepted.",
+                    Self::LIMIT_KEY
+                ),
+            },
+            None => None,
+        };
+        let order = match parameters.get(Self::ORDER_KEY) {
+            Some(order) => order.parse()?,
+            None => QueryOrder::default(),
+        };
+
+        Ok(Self {
+            key,
+            time_range,
+            limit,
+            order,
+            parameters: parameters.clone().into_owned(),
+        })
+    }
+
+    /// Returns `true` if only the latest value is requested, i.e. no time range was specified.
+    pub fn is_latest_only(&self) -> bool {
+        self.time_range.is_none()
+    }
+
+    /// Returns `true` if the provided [Timestamp] belongs to the time range of this query.
+    ///
+    /// If no time range was specified, this method always returns `true`.
+    pub fn contains(&self, timestamp: &Timestamp) -> bool {
+        match &self.time_range {
+            Some(time_range) => time_range.contains(timestamp.get_time().to_system_time()),
+            None => true,
+        }
+    }
+
+    /// Filters, orders and limits the provided values according to this query.
+    ///
+    /// - If no time range was specified, only the value with the most recent timestamp is kept.
+    /// - Otherwise, all the values stamped within the time range are kept.
+    ///
+    /// The values are then sorted according to the `order` and truncated to the `limit`.
+    pub fn select(&self, data: impl IntoIterator<Item = StoredData>) -> Vec<StoredData> {
+        let mut selected = if self.is_latest_only() {
+            data.into_iter()
+                .max_by_key(|stored_data| stored_data.timestamp)
+                .into_iter()
+                .collect::<Vec<_>>()
+        } else {
+            data.into_iter()
+                .filter(|stored_data| self.contains(&stored_data.timestamp))
+                .collect::<Vec<_>>()
+        };
+
+        match self.order {
+            QueryOrder::Ascending => selected.sort_by_key(|stored_data| stored_data.timestamp),
+            QueryOrder::Descending => {
+                selected.sort_by(|lhs, rhs| rhs.timestamp.cmp(&lhs.timestamp))
+            }
+        }
+
+        if let Some(limit) = self.limit {
+            selected.truncate(limit);
+        }
+
+        selected
+    }
+}
+
+#[cfg(test)]
+#[path = "query.test.rs"]
+mod tests;
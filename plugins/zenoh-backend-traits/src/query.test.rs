@@ -0,0 +1,107 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::{
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use zenoh::{
+    internal::Value,
+    key_expr::OwnedKeyExpr,
+    query::Parameters,
+    time::{Timestamp, TimestampId, NTP64},
+};
+
+use super::{QueryOrder, StorageQuery};
+use crate::StoredData;
+
+fn stored_data_at(secs: u64) -> StoredData {
+    StoredData {
+        value: Value::empty(),
+        timestamp: Timestamp::new(
+            NTP64::from(Duration::from_secs(secs)),
+            TimestampId::try_from([1]).unwrap(),
+        ),
+    }
+}
+
+fn seconds(data: &[StoredData]) -> Vec<u64> {
+    data.iter()
+        .map(|d| d.timestamp.get_time().to_duration().as_secs())
+        .collect()
+}
+
+#[test]
+fn test_from_parameters() {
+    let key = Some(OwnedKeyExpr::from_str("test/a").unwrap());
+
+    let query = StorageQuery::from_parameters(key.clone(), &Parameters::empty()).unwrap();
+    assert_eq!(query, StorageQuery::latest(key.clone()));
+    assert!(query.is_latest_only());
+
+    let query = StorageQuery::from_parameters(
+        key.clone(),
+        &Parameters::from("_time=[1970-01-01T00:00:10Z..];_limit=2;_order=desc;custom=42"),
+    )
+    .unwrap();
+    assert!(!query.is_latest_only());
+    assert_eq!(query.limit, Some(2));
+    assert_eq!(query.order, QueryOrder::Descending);
+    assert_eq!(query.parameters.get("custom"), Some("42"));
+    assert!(query.contains(&stored_data_at(10).timestamp));
+    assert!(!query.contains(&stored_data_at(9).timestamp));
+
+    assert!(StorageQuery::from_parameters(key.clone(), &Parameters::from("_limit=-1")).is_err());
+    assert!(StorageQuery::from_parameters(key.clone(), &Parameters::from("_limit=0")).is_err());
+    assert!(StorageQuery::from_parameters(key.clone(), &Parameters::from("_order=up")).is_err());
+    assert!(StorageQuery::from_parameters(key, &Parameters::from("_time=[now(")).is_err());
+}
+
+#[test]
+fn test_select() {
+    let data = vec![
+        stored_data_at(3),
+        stored_data_at(1),
+        stored_data_at(4),
+        stored_data_at(2),
+    ];
+
+    let query = StorageQuery::latest(None);
+    assert_eq!(seconds(&query.select(data.clone())), vec![4]);
+
+    let query = StorageQuery::from_parameters(None, &Parameters::from("_time=[..]")).unwrap();
+    assert_eq!(seconds(&query.select(data.clone())), vec![1, 2, 3, 4]);
+
+    let query = StorageQuery::from_parameters(
+        None,
+        &Parameters::from("_time=[1970-01-01T00:00:02Z..];_order=desc;_limit=2"),
+    )
+    .unwrap();
+    assert_eq!(seconds(&query.select(data.clone())), vec![4, 3]);
+
+    let query = StorageQuery::all(None);
+    assert_eq!(seconds(&query.select(data.clone())), vec![1, 2, 3, 4]);
+
+    let query = StorageQuery::at(None, &stored_data_at(2).timestamp);
+    assert_eq!(seconds(&query.select(data)), vec![2]);
+
+    // Sanity check: the time range is resolved, hence `now` is in the past.
+    let query = StorageQuery::from_parameters(None, &Parameters::from("_time=[..now()]")).unwrap();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    assert!(!query.contains(&Timestamp::new(
+        NTP64::from(now + Duration::from_secs(60)),
+        TimestampId::try_from([1]).unwrap(),
+    )));
+}
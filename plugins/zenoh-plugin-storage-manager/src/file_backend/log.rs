@@ -0,0 +1,422 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufWriter, ErrorKind, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use advisory_lock::{AdvisoryFileLock, FileLockError, FileLockMode};
+use serde::{Deserialize, Serialize};
+use xxhash_rust::xxh3::xxh3_64;
+use zenoh::{
+    bytes::{Encoding, ZBytes},
+    internal::{bail, zerror, Value},
+    key_expr::OwnedKeyExpr,
+    time::Timestamp,
+    Result as ZResult,
+};
+use zenoh_backend_traits::StoredData;
+
+/// The bytes every log file starts with, identifying its format.
+const MAGIC: &[u8; 8] = b"ZLOG\0\0\0\x01";
+
+/// The size of the header of each record: its length (u32) followed by its checksum (u64).
+const RECORD_HEADER_SIZE: usize = 4 + 8;
+
+/// A `Record` is an entry of the log: it is appended for every put or delete received by the
+/// Storage, and for every purge of the values of a key, and is never modified afterwards.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum Record {
+    Put {
+        key: Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+        encoding: String,
+        payload: Vec<u8>,
+    },
+    Delete {
+        key: Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+    },
+    /// The removal of the updates of the key stamped up to the timestamp (included).
+    Purge {
+        key: Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+    },
+}
+
+impl Record {
+    pub(crate) fn put(key: Option<OwnedKeyExpr>, value: &Value, timestamp: Timestamp) -> Self {
+        Record::Put {
+            key,
+            timestamp,
+            encoding: value.encoding().to_string(),
+            payload: value.payload().to_bytes().into_owned(),
+        }
+    }
+
+    pub(crate) fn key(&self) -> &Option<OwnedKeyExpr> {
+        match self {
+            Record::Put { key, .. } | Record::Delete { key, .. } | Record::Purge { key, .. } => key,
+        }
+    }
+
+    pub(crate) fn timestamp(&self) -> &Timestamp {
+        match self {
+            Record::Put { timestamp, .. }
+            | Record::Delete { timestamp, .. }
+            | Record::Purge { timestamp, .. } => timestamp,
+        }
+    }
+
+    /// Returns the [StoredData] of a `Put` record, `None` for a `Delete` or a `Purge`.
+    pub(crate) fn into_stored_data(self) -> Option<StoredData> {
+        match self {
+            Record::Put {
+                timestamp,
+                encoding,
+                payload,
+                ..
+            } => Some(StoredData {
+                value: Value::new(ZBytes::from(payload), Encoding::from(encoding)),
+                timestamp,
+            }),
+            Record::Delete { .. } | Record::Purge { .. } => None,
+        }
+    }
+
+    /// Serialises this record, prefixed with its header, at the end of the provided buffer.
+    fn encode_into(&self, buffer: &mut Vec<u8>) -> ZResult<()> {
+        let body = bincode::serialize(self).map_err(|e| zerror!("Failed to encode record: {e}"))?;
+        let length = u32::try_from(body.len())
+            .map_err(|_| zerror!("Record of {} bytes is too large", body.len()))?;
+        buffer.extend_from_slice(&length.to_le_bytes());
+        buffer.extend_from_slice(&xxh3_64(&body).to_le_bytes());
+        buffer.extend_from_slice(&body);
+        Ok(())
+    }
+}
+
+/// The position of a [Record] in a [LogFile].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Position {
+    pub(crate) offset: u64,
+    /// The number of bytes the record occupies, header included.
+    pub(crate) len: u64,
+}
+
+/// An append-only file of [Record]s.
+///
+/// Records are framed with their length and a checksum such that a record partially written
+/// (e.g. because the process crashed while appending it) is detected, and discarded, when the
+/// file is opened again.
+pub(crate) struct LogFile {
+    path: PathBuf,
+    file: File,
+    len: u64,
+}
+
+impl LogFile {
+    /// Opens, or creates if it does not exist, the log file at the provided path and calls `f` on
+    /// every valid record it contains, in the order they were appended, along with their position.
+    ///
+    /// The file is locked exclusively for as long as the `LogFile` is alive: two Storages, of the
+    /// same process or not, can't write to the same log file.
+    ///
+    /// If the file ends with an invalid or incomplete record, it is truncated to the last valid
+    /// one.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be opened, if it is already locked or
+    /// if it is not a log file.
+    pub(crate) fn open(path: &Path, mut f: impl FnMut(Position, Record)) -> ZResult<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|e| zerror!("Failed to open log file {}: {e}", path.display()))?;
+        lock(&file, path)?;
+
+        let file_len = file.metadata()?.len();
+        if file_len == 0 {
+            file.write_all(MAGIC)?;
+            file.sync_all()?;
+            return Ok(Self {
+                path: path.to_path_buf(),
+                file,
+                len: MAGIC.len() as u64,
+            });
+        }
+
+        let mut magic = [0u8; MAGIC.len()];
+        if file.read_exact(&mut magic).is_err() || &magic != MAGIC {
+            bail!("File {} is not a valid log file", path.display());
+        }
+
+        let mut len = MAGIC.len() as u64;
+        loop {
+            match read_record(&mut file) {
+                Ok(Some((record, record_len))) => {
+                    f(
+                        Position {
+                            offset: len,
+                            len: record_len,
+                        },
+                        record,
+                    );
+                    len += record_len;
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::warn!(
+                        "Discarding the end of log file {} after offset {len}: {e:?}",
+                        path.display()
+                    );
+                    break;
+                }
+            }
+        }
+
+        if len != file_len {
+            file.set_len(len)?;
+            file.sync_all()?;
+        }
+        file.seek(SeekFrom::Start(len))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            len,
+        })
+    }
+
+    /// Returns the path of the log file.
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the size, in bytes, of the log file.
+    pub(crate) fn size(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns the size, in bytes, of the records of the log file.
+    pub(crate) fn records_size(&self) -> u64 {
+        self.len - MAGIC.len() as u64
+    }
+
+    /// Appends the provided records and waits for them to be written on disk.
+    ///
+    /// Returns the positions at which the records were written.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the records could not be written. In that case, the
+    /// log file is left unchanged.
+    pub(crate) fn append(&mut self, records: &[Record]) -> ZResult<Vec<Position>> {
+        if records.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut buffer = Vec::new();
+        let mut positions = Vec::with_capacity(records.len());
+        for record in records {
+            let start = buffer.len();
+            record.encode_into(&mut buffer)?;
+            positions.push(Position {
+                offset: self.len + start as u64,
+                len: (buffer.len() - start) as u64,
+            });
+        }
+
+        let result = self
+            .file
+            .seek(SeekFrom::Start(self.len))
+            .and_then(|_| self.file.write_all(&buffer))
+            .and_then(|_| self.file.sync_data());
+        if let Err(e) = result {
+            // Do not leave a partially written record that would be followed by valid ones.
+            let _ = self.file.set_len(self.len);
+            bail!("Failed to append to log file {}: {e}", self.path.display());
+        }
+
+        self.len += buffer.len() as u64;
+        Ok(positions)
+    }
+
+    /// Rewrites the log file with only the records at the provided offsets, dropping all the
+    /// others.
+    ///
+    /// Returns the new positions of the records, in the order of the provided offsets.
+    ///
+    /// The records are written in a temporary file, locked like the log file, that then replaces
+    /// it: if the process crashes while compacting, the log file is left unchanged. The directory
+    /// of the log file is then synchronised on disk, for the replacement to survive a crash.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if a record could not be read or if the new log file could
+    /// not be written. In that case, the log file is left unchanged.
+    pub(crate) fn compact(&mut self, offsets: &[u64]) -> ZResult<Vec<Position>> {
+        let tmp_path = self.path.with_extension("compact");
+        let result = self.write_compacted(&tmp_path, offsets);
+        if result.is_err() {
+            let _ = std::fs::remove_file(&tmp_path);
+        }
+        let (file, len, positions) = result?;
+
+        std::fs::rename(&tmp_path, &self.path).map_err(|e| {
+            let _ = std::fs::remove_file(&tmp_path);
+            zerror!("Failed to replace log file {}: {e}", self.path.display())
+        })?;
+        self.file = file;
+        self.len = len;
+        sync_parent_dir(&self.path)?;
+        Ok(positions)
+    }
+
+    /// Writes the records at the provided offsets, in the order they appear in this log file, in a
+    /// new log file at `path`, returning it along with its size and the new positions of the
+    /// records.
+    fn write_compacted(
+        &mut self,
+        path: &Path,
+        offsets: &[u64],
+    ) -> ZResult<(File, u64, Vec<Position>)> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .map_err(|e| zerror!("Failed to create log file {}: {e}", path.display()))?;
+        // NOTE: The lock follows the file when it replaces the log file.
+        lock(&file, path)?;
+
+        let mut order = (0..offsets.len()).collect::<Vec<_>>();
+        order.sort_unstable_by_key(|&i| offsets[i]);
+
+        let mut writer = BufWriter::new(file);
+        writer.write_all(MAGIC)?;
+        let mut len = MAGIC.len() as u64;
+        let mut positions = vec![Position { offset: 0, len: 0 }; offsets.len()];
+        let mut buffer = Vec::new();
+        for i in order {
+            buffer.clear();
+            self.read_at(offsets[i])?.encode_into(&mut buffer)?;
+            writer.write_all(&buffer)?;
+            positions[i] = Position {
+                offset: len,
+                len: buffer.len() as u64,
+            };
+            len += buffer.len() as u64;
+        }
+
+        let mut file = writer
+            .into_inner()
+            .map_err(|e| zerror!("Failed to write log file {}: {e}", path.display()))?;
+        file.sync_all()?;
+        file.seek(SeekFrom::Start(len))?;
+        Ok((file, len, positions))
+    }
+
+    /// Reads the record written at the provided offset.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if there is no valid record at this offset.
+    pub(crate) fn read_at(&mut self, offset: u64) -> ZResult<Record> {
+        self.file.seek(SeekFrom::Start(offset))?;
+        match read_record(&mut self.file)? {
+            Some((record, _)) => Ok(record),
+            None => bail!(
+                "No record at offset {offset} of log file {}",
+                self.path.display()
+            ),
+        }
+    }
+}
+
+/// Reads the record at the current position of the reader, returning it along with the number of
+/// bytes it occupies.
+///
+/// Returns `Ok(None)` if the reader is at the end of the file.
+/// Locks the log file at `path` exclusively, without waiting for the lock.
+///
+/// The lock is released when the file is closed.
+fn lock(file: &File, path: &Path) -> ZResult<()> {
+    match file.try_lock(FileLockMode::Exclusive) {
+        Ok(()) => Ok(()),
+        Err(FileLockError::AlreadyLocked) => bail!(
+            "Log file {} is already used by another Storage",
+            path.display()
+        ),
+        Err(FileLockError::Io(e)) => bail!("Failed to lock log file {}: {e}", path.display()),
+    }
+}
+
+/// Synchronises on disk the directory containing the file at `path`, such that the creation or
+/// the replacement of the file is durable.
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> ZResult<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .map_err(|e| zerror!("Failed to synchronise directory {}: {e}", dir.display()).into())
+}
+
+// NOTE: The standard library can't open a directory, hence synchronise it, on Windows.
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> ZResult<()> {
+    Ok(())
+}
+
+fn read_record(reader: &mut impl Read) -> ZResult<Option<(Record, u64)>> {
+    let mut header = [0u8; RECORD_HEADER_SIZE];
+    match reader.read_exact(&mut header[..1]) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    reader
+        .read_exact(&mut header[1..])
+        .map_err(|e| zerror!("Incomplete record header: {e}"))?;
+
+    let length = u32::from_le_bytes(header[..4].try_into().unwrap()) as usize;
+    let checksum = u64::from_le_bytes(header[4..].try_into().unwrap());
+
+    // Not allocating the announced length upfront: it could be garbage.
+    let mut body = Vec::new();
+    reader
+        .take(length as u64)
+        .read_to_end(&mut body)
+        .map_err(|e| zerror!("Failed to read record body: {e}"))?;
+    if body.len() != length {
+        bail!("Incomplete record body");
+    }
+    if xxh3_64(&body) != checksum {
+        bail!("Checksum mismatch");
+    }
+
+    let record = bincode::deserialize::<Record>(&body)
+        .map_err(|e| zerror!("Failed to decode record: {e}"))?;
+    Ok(Some((record, (RECORD_HEADER_SIZE + length) as u64)))
+}
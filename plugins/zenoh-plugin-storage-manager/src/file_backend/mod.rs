@@ -0,0 +1,633 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The file backend is a durable backend, embedded in the storage manager, that keeps every value
+//! it receives.
+//!
+//! Each Storage is backed by an append-only log file, in which all the puts and deletes received
+//! are written (and synchronised on disk) before being acknowledged. The values purged to enforce
+//! the retention rules of the Storage are recorded in the log as well. An index of the content of
+//! the log, mapping each key to the timestamps and offsets of its updates, is kept in memory and
+//! rebuilt from the log when the Storage is (re)created.
+//!
+//! The purged values are only removed from the disk when the log is compacted: it is rewritten
+//! with the updates still indexed once the purged ones exceed both [COMPACTION_MIN_GARBAGE] and
+//! the size of the updates still indexed. Hence, the log never exceeds about twice the size of the
+//! updates it holds plus [COMPACTION_MIN_GARBAGE]. Note that, without retention rules, nothing is
+//! ever purged: the log then grows with every update received.
+//!
+//! The deletes that no longer hide any value, i.e. all but the latest one of each key and the
+//! latest one if no older value of the key is left, are dropped when the log is compacted.
+//!
+//! The log file is locked exclusively by its Storage: creating a Storage on a directory already
+//! used by another one, of this process or of another, fails.
+//!
+//! The volume must be configured with the directory under which the Storages will store their log:
+//!
+//! ```json5
+//! volumes: {
+//!   file: {
+//!     root: "/var/lib/zenoh",
+//!   },
+//! },
+//! storages: {
+//!   demo: {
+//!     key_expr: "demo/**",
+//!     volume: {
+//!       id: "file",
+//!       // Optional, defaults to the name of the storage.
+//!       dir: "demo",
+//!     },
+//!   },
+//! },
+//! ```
+
+mod log;
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashSet},
+    ops::Bound,
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use zenoh::{
+    internal::{bail, zerror, zlock, Value},
+    key_expr::OwnedKeyExpr,
+    time::Timestamp,
+    Result as ZResult,
+};
+use zenoh_backend_traits::{
+    config::{StorageConfig, VolumeConfig},
+    *,
+};
+use zenoh_plugin_trait::{plugin_long_version, plugin_version, Plugin};
+
+use self::log::{LogFile, Position, Record};
+use crate::FILE_BACKEND_NAME;
+
+/// Name of the volume configuration entry holding the directory under which Storages are created.
+const PROP_ROOT: &str = "root";
+/// Name of the storage configuration entry holding the directory, relative to the root of the
+/// volume, in which the Storage writes its log.
+const PROP_DIR: &str = "dir";
+/// Name of the log file of a Storage.
+const LOG_FILE_NAME: &str = "data.log";
+/// The minimum size, in bytes, of the records no longer indexed for the log to be compacted.
+const COMPACTION_MIN_GARBAGE: u64 = 1024 * 1024;
+
+pub struct FileBackend {
+    config: VolumeConfig,
+    root: PathBuf,
+}
+
+impl Plugin for FileBackend {
+    type StartArgs = VolumeConfig;
+    type Instance = VolumeInstance;
+
+    const DEFAULT_NAME: &'static str = FILE_BACKEND_NAME;
+    const PLUGIN_VERSION: &'static str = plugin_version!();
+    const PLUGIN_LONG_VERSION: &'static str = plugin_long_version!();
+
+    fn start(_: &str, args: &VolumeConfig) -> ZResult<VolumeInstance> {
+        let root = match args.rest.get(PROP_ROOT) {
+            Some(serde_json::Value::String(root)) => PathBuf::from(root),
+            Some(_) => bail!(
+                "Invalid configuration of volume '{}': `{PROP_ROOT}` must be a string",
+                args.name()
+            ),
+            None => bail!(
+                "Invalid configuration of volume '{}': missing `{PROP_ROOT}` directory",
+                args.name()
+            ),
+        };
+        std::fs::create_dir_all(&root)
+            .map_err(|e| zerror!("Cannot create directory {}: {e}", root.display()))?;
+
+        Ok(Box::new(FileBackend {
+            config: args.clone(),
+            root,
+        }))
+    }
+}
+
+#[async_trait]
+impl Volume for FileBackend {
+    fn get_admin_status(&self) -> serde_json::Value {
+        self.config.to_json_value()
+    }
+
+    fn get_capability(&self) -> Capability {
+        Capability {
+            persistence: Persistence::Durable,
+            history: History::All,
+        }
+    }
+
+    async fn create_storage(&self, properties: StorageConfig) -> ZResult<Box<dyn Storage>> {
+        tracing::debug!("Create File Storage with configuration: {:?}", properties);
+        let dir = match properties.volume_cfg.get(PROP_DIR) {
+            Some(serde_json::Value::String(dir)) => PathBuf::from(dir),
+            Some(_) => bail!(
+                "Invalid configuration of storage '{}': `{PROP_DIR}` must be a string",
+                properties.name
+            ),
+            None => PathBuf::from(&properties.name),
+        };
+        // The directory of a Storage must remain under the root of the volume.
+        if !dir
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            bail!(
+                "Invalid configuration of storage '{}': `{PROP_DIR}` must be a relative path \
+                 without '..', found: {}",
+                properties.name,
+                dir.display()
+            );
+        }
+
+        // Loading the log (and compacting it) blocks on its file I/O.
+        let dir = self.root.join(dir);
+        let storage = tokio::task::spawn_blocking(move || FileStorage::new(properties, &dir))
+            .await
+            .map_err(|e| zerror!("Failed to create File Storage: {e}"))??;
+        Ok(Box::new(storage))
+    }
+}
+
+impl Drop for FileBackend {
+    fn drop(&mut self) {
+        // nothing to do: the Storages synchronise their log on each write
+        tracing::trace!("FileBackend::drop()");
+    }
+}
+
+/// The position, in the log, of an update of a key.
+#[derive(Debug, Clone, Copy)]
+struct IndexEntry {
+    timestamp: Timestamp,
+    position: Position,
+    is_put: bool,
+    /// The size of the payload of a put.
+    size: u64,
+}
+
+impl IndexEntry {
+    fn new(record: &Record, position: Position) -> Self {
+        IndexEntry {
+            timestamp: *record.timestamp(),
+            position,
+            is_put: matches!(record, Record::Put { .. }),
+            size: match record {
+                Record::Put { payload, .. } => payload.len() as u64,
+                _ => 0,
+            },
+        }
+    }
+}
+
+/// A key of the [Index], ordered by its bytes such that the content of the Storage can be paged
+/// through. The `None` key, whose bytes are empty, is the first.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct IndexKey(Option<OwnedKeyExpr>);
+
+impl IndexKey {
+    fn as_bytes(&self) -> &[u8] {
+        self.0.as_ref().map_or(&[], |key| key.as_bytes())
+    }
+
+    /// Returns the cursor resuming a page of entries after this key.
+    fn to_cursor(&self) -> EntriesCursor {
+        EntriesCursor(self.as_bytes().to_vec())
+    }
+
+    fn from_cursor(cursor: &EntriesCursor) -> ZResult<Self> {
+        if cursor.0.is_empty() {
+            return Ok(IndexKey(None));
+        }
+        let key =
+            std::str::from_utf8(&cursor.0).map_err(|e| zerror!("Invalid entries cursor: {e}"))?;
+        Ok(IndexKey(Some(OwnedKeyExpr::from_str(key)?)))
+    }
+}
+
+impl Ord for IndexKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+}
+
+impl PartialOrd for IndexKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The updates of each key, sorted by timestamp.
+#[derive(Default)]
+struct Index {
+    keys: BTreeMap<IndexKey, Vec<IndexEntry>>,
+    /// The size, in the log, of the records indexed.
+    records_size: u64,
+}
+
+impl Index {
+    fn history(&self, key: &Option<OwnedKeyExpr>) -> Option<&Vec<IndexEntry>> {
+        self.keys.get(&IndexKey(key.clone()))
+    }
+
+    fn contains(&self, key: &Option<OwnedKeyExpr>, timestamp: &Timestamp) -> bool {
+        self.history(key).is_some_and(|history| {
+            history
+                .binary_search_by_key(timestamp, |entry| entry.timestamp)
+                .is_ok()
+        })
+    }
+
+    /// Returns the most recent update of the provided key.
+    fn latest(&self, key: &Option<OwnedKeyExpr>) -> Option<&IndexEntry> {
+        self.history(key).and_then(|history| history.last())
+    }
+
+    /// Returns the keys whose most recent update is a put, along with its timestamp, in order and
+    /// starting after the provided key.
+    fn entries(
+        &self,
+        after: Option<IndexKey>,
+    ) -> impl Iterator<Item = (&IndexKey, Timestamp)> + '_ {
+        let start = after.map_or(Bound::Unbounded, Bound::Excluded);
+        self.keys
+            .range((start, Bound::Unbounded))
+            .filter_map(|(key, history)| {
+                history
+                    .last()
+                    .filter(|latest| latest.is_put)
+                    .map(|latest| (key, latest.timestamp))
+            })
+    }
+
+    /// Inserts the entry in the history of the key, unless an update with the same timestamp was
+    /// already recorded.
+    ///
+    /// Returns the result of the insertion as expected by the storage manager.
+    fn insert(&mut self, key: Option<OwnedKeyExpr>, entry: IndexEntry) -> StorageInsertionResult {
+        let history = self.keys.entry(IndexKey(key)).or_default();
+        let had_value = history.last().is_some_and(|latest| latest.is_put);
+        match history.binary_search_by_key(&entry.timestamp, |entry| entry.timestamp) {
+            Ok(_) => return StorageInsertionResult::Outdated,
+            Err(position) => history.insert(position, entry),
+        }
+        self.records_size += entry.position.len;
+
+        match (entry.is_put, had_value) {
+            (false, _) => StorageInsertionResult::Deleted,
+            (true, true) => StorageInsertionResult::Replaced,
+            (true, false) => StorageInsertionResult::Inserted,
+        }
+    }
+
+    /// Removes the updates of the key stamped up to the provided timestamp (included), returning
+    /// their timestamps.
+    fn purge(&mut self, key: &Option<OwnedKeyExpr>, timestamp: &Timestamp) -> Vec<Timestamp> {
+        let key = IndexKey(key.clone());
+        let Some(history) = self.keys.get_mut(&key) else {
+            return Vec::new();
+        };
+        let end = history.partition_point(|entry| entry.timestamp <= *timestamp);
+        let mut purged = Vec::with_capacity(end);
+        for entry in history.drain(..end) {
+            purged.push(entry.timestamp);
+            self.records_size -= entry.position.len;
+        }
+        if history.is_empty() {
+            self.keys.remove(&key);
+        }
+        purged
+    }
+
+    /// Removes the deletes that no longer hide any value: all but the latest one of each key, and
+    /// the latest one if no older put of the key is left.
+    ///
+    /// The puts are only ever removed by [Index::purge]: the history of the keys is kept as is.
+    fn drop_tombstones(&mut self) {
+        self.keys.retain(|_, history| {
+            let latest = history.len() - 1;
+            let has_put = history.iter().any(|entry| entry.is_put);
+            let mut index = 0;
+            history.retain(|entry| {
+                let keep = entry.is_put || (index == latest && has_put);
+                if !keep {
+                    self.records_size -= entry.position.len;
+                }
+                index += 1;
+                keep
+            });
+            !history.is_empty()
+        });
+    }
+
+    /// Returns `true` if the records of the log that are not indexed exceed both
+    /// [COMPACTION_MIN_GARBAGE] and the size of the records indexed.
+    fn needs_compaction(&self, log: &LogFile) -> bool {
+        let garbage = log.records_size().saturating_sub(self.records_size);
+        garbage > COMPACTION_MIN_GARBAGE && garbage > self.records_size
+    }
+
+    /// Returns the offsets of all the records indexed.
+    fn offsets(&self) -> Vec<u64> {
+        self.keys
+            .values()
+            .flatten()
+            .map(|entry| entry.position.offset)
+            .collect()
+    }
+
+    /// Updates the positions of the records indexed, after the log was compacted.
+    ///
+    /// The `positions` are those of the records at the [Index::offsets], in the same order.
+    fn relocate(&mut self, positions: Vec<Position>) {
+        for (entry, position) in self.keys.values_mut().flatten().zip(positions) {
+            entry.position = position;
+        }
+    }
+}
+
+struct FileStorage {
+    config: StorageConfig,
+    /// The log is only accessed from blocking tasks, its file I/O blocking the thread.
+    log: Arc<Mutex<LogFile>>,
+    index: Index,
+}
+
+impl FileStorage {
+    /// Creates the Storage, loading its log from the provided directory.
+    ///
+    /// ⚠️ This function blocks on the file I/O of the log.
+    fn new(config: StorageConfig, dir: &Path) -> ZResult<FileStorage> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| zerror!("Cannot create directory {}: {e}", dir.display()))?;
+
+        let mut index = Index::default();
+        let mut log = LogFile::open(&dir.join(LOG_FILE_NAME), |position, record| {
+            if let Record::Purge { key, timestamp } = &record {
+                index.purge(key, timestamp);
+            } else {
+                index.insert(record.key().clone(), IndexEntry::new(&record, position));
+            }
+        })?;
+        tracing::debug!(
+            "Storage '{}' loaded {} keys from {} ({} bytes)",
+            config.name,
+            index.keys.len(),
+            log.path().display(),
+            log.size()
+        );
+
+        if index.needs_compaction(&log) {
+            index.drop_tombstones();
+            let positions = log.compact(&index.offsets())?;
+            index.relocate(positions);
+        }
+
+        Ok(FileStorage {
+            config,
+            log: Arc::new(Mutex::new(log)),
+            index,
+        })
+    }
+
+    /// Runs `f` on the log in a blocking task.
+    async fn with_log<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut LogFile) -> ZResult<T> + Send + 'static,
+    ) -> ZResult<T> {
+        let log = self.log.clone();
+        tokio::task::spawn_blocking(move || f(&mut zlock!(log)))
+            .await
+            .map_err(|e| {
+                zerror!(
+                    "Log file task of Storage '{}' failed: {e}",
+                    self.config.name
+                )
+            })?
+    }
+
+    /// Compacts the log if the records that are no longer indexed take too much space.
+    async fn compact_if_needed(&mut self) -> ZResult<()> {
+        if !self.index.needs_compaction(&zlock!(self.log)) {
+            return Ok(());
+        }
+        self.index.drop_tombstones();
+        let offsets = self.index.offsets();
+        let positions = self.with_log(move |log| log.compact(&offsets)).await?;
+        self.index.relocate(positions);
+        Ok(())
+    }
+
+    /// Appends the records that are not already present to the log and indexes them.
+    ///
+    /// An update is considered as already present if the log contains an update, for the same
+    /// key, with the same timestamp. This happens, for instance, when the same Sample is received
+    /// twice.
+    async fn write(&mut self, records: Vec<Record>) -> ZResult<Vec<StorageInsertionResult>> {
+        let mut batch_updates = HashSet::new();
+        let is_new = records
+            .iter()
+            .map(|record| {
+                !self.index.contains(record.key(), record.timestamp())
+                    && batch_updates.insert((record.key().clone(), *record.timestamp()))
+            })
+            .collect::<Vec<_>>();
+
+        let new_records = records
+            .iter()
+            .zip(&is_new)
+            .filter_map(|(record, is_new)| is_new.then_some(record.clone()))
+            .collect::<Vec<_>>();
+        let mut positions = self
+            .with_log(move |log| log.append(&new_records))
+            .await?
+            .into_iter();
+
+        Ok(records
+            .into_iter()
+            .zip(is_new)
+            .map(|(record, is_new)| {
+                if !is_new {
+                    return StorageInsertionResult::Outdated;
+                }
+                // `append` returns exactly one position per record.
+                let entry = IndexEntry::new(&record, positions.next().unwrap());
+                self.index.insert(record.key().clone(), entry)
+            })
+            .collect())
+    }
+
+    /// Reads the puts recorded at the provided offsets.
+    async fn read(&self, offsets: Vec<u64>) -> ZResult<Vec<StoredData>> {
+        self.with_log(move |log| {
+            let mut data = Vec::with_capacity(offsets.len());
+            for offset in offsets {
+                match log.read_at(offset)?.into_stored_data() {
+                    Some(stored_data) => data.push(stored_data),
+                    None => bail!("Record at offset {offset} is not a put"),
+                }
+            }
+            Ok(data)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl Storage for FileStorage {
+    fn get_admin_status(&self) -> serde_json::Value {
+        self.config.to_json_value()
+    }
+
+    async fn put(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        value: Value,
+        timestamp: Timestamp,
+    ) -> ZResult<StorageInsertionResult> {
+        tracing::trace!("put for {:?}", key);
+        let mut results = self
+            .write(vec![Record::put(key, &value, timestamp)])
+            .await?;
+        Ok(results.remove(0))
+    }
+
+    async fn delete(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+    ) -> ZResult<StorageInsertionResult> {
+        tracing::trace!("delete for {:?}", key);
+        let mut results = self.write(vec![Record::Delete { key, timestamp }]).await?;
+        Ok(results.remove(0))
+    }
+
+    fn supports_batch(&self) -> bool {
+        true
+    }
+
+    async fn put_batch(
+        &mut self,
+        batch: Vec<StorageWrite>,
+    ) -> ZResult<Vec<StorageInsertionResult>> {
+        tracing::trace!("put_batch of {} writes", batch.len());
+        // The whole batch is synchronised on disk at once.
+        let records = batch
+            .into_iter()
+            .map(|write| match write {
+                StorageWrite::Put {
+                    key,
+                    value,
+                    timestamp,
+                } => Record::put(key, &value, timestamp),
+                StorageWrite::Delete { key, timestamp } => Record::Delete { key, timestamp },
+            })
+            .collect();
+        self.write(records).await
+    }
+
+    async fn purge(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+    ) -> ZResult<Vec<Timestamp>> {
+        tracing::trace!("purge for {:?} up to {}", key, timestamp);
+        let record = Record::Purge {
+            key: key.clone(),
+            timestamp,
+        };
+        self.with_log(move |log| log.append(&[record])).await?;
+        let purged = self.index.purge(&key, &timestamp);
+        if let Err(e) = self.compact_if_needed().await {
+            tracing::warn!(
+                "Storage '{}' failed to compact its log: {e:?}",
+                self.config.name
+            );
+        }
+        Ok(purged)
+    }
+
+    async fn get(&mut self, query: StorageQuery) -> ZResult<Vec<StoredData>> {
+        tracing::trace!("get for {:?}", query.key);
+        let offsets = if query.is_latest_only() {
+            match self.index.latest(&query.key) {
+                Some(entry) if entry.is_put => vec![entry.position.offset],
+                _ => bail!("Key {:?} is not present", query.key),
+            }
+        } else {
+            match self.index.history(&query.key) {
+                Some(history) => history
+                    .iter()
+                    .filter(|entry| entry.is_put && query.contains(&entry.timestamp))
+                    .map(|entry| entry.position.offset)
+                    .collect(),
+                None => bail!("Key {:?} is not present", query.key),
+            }
+        };
+
+        let data = self.read(offsets).await?;
+        Ok(query.select(data))
+    }
+
+    async fn get_all_entries(&self) -> ZResult<Vec<(Option<OwnedKeyExpr>, Timestamp)>> {
+        // Keys whose most recent update is a delete are not part of the content of the Storage.
+        Ok(self
+            .index
+            .entries(None)
+            .map(|(key, timestamp)| (key.0.clone(), timestamp))
+            .collect())
+    }
+
+    async fn get_entries_page(
+        &self,
+        cursor: Option<EntriesCursor>,
+        page_size: usize,
+    ) -> ZResult<EntriesPage> {
+        let after = cursor.as_ref().map(IndexKey::from_cursor).transpose()?;
+        let mut entries = self.index.entries(after);
+        let page = entries.by_ref().take(page_size.max(1)).collect::<Vec<_>>();
+        let next = match (page.last(), entries.next()) {
+            (Some((last_key, _)), Some(_)) => Some(last_key.to_cursor()),
+            _ => None,
+        };
+        Ok(EntriesPage {
+            entries: page
+                .into_iter()
+                .map(|(key, timestamp)| (key.0.clone(), timestamp))
+                .collect(),
+            next,
+        })
+    }
+}
+
+impl Drop for FileStorage {
+    fn drop(&mut self) {
+        // nothing to do: the log is synchronised on each write
+        tracing::trace!("FileStorage::drop()");
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/file_backend.test.rs"]
+mod tests;
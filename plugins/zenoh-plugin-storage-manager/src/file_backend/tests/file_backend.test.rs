@@ -0,0 +1,372 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::{future::Future, io::Write, path::PathBuf, str::FromStr, sync::OnceLock, time::Duration};
+
+use serde_json::json;
+use tokio::runtime::Runtime;
+use zenoh::{
+    bytes::Encoding,
+    internal::Value,
+    key_expr::OwnedKeyExpr,
+    query::Parameters,
+    time::{Timestamp, TimestampId, NTP64},
+};
+use zenoh_backend_traits::{
+    config::{PluginConfig, StorageConfig},
+    EntriesCursor, Storage, StorageInsertionResult, StorageQuery, StorageWrite,
+};
+
+use super::{FileStorage, COMPACTION_MIN_GARBAGE, LOG_FILE_NAME};
+
+/// The Storage does its file I/O in blocking tasks, which require a Tokio runtime.
+fn block_on<F: Future>(future: F) -> F::Output {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME
+        .get_or_init(|| Runtime::new().unwrap())
+        .block_on(future)
+}
+
+struct TestDir(PathBuf);
+
+impl TestDir {
+    fn new() -> Self {
+        let dir = std::env::temp_dir().join(format!("zenoh-file-backend-{}", uuid::Uuid::new_v4()));
+        Self(dir)
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn storage_config() -> StorageConfig {
+    let config = json!({
+        "storages": {
+            "test-storage": {
+                "key_expr": "test/**",
+                "volume": "file",
+            }
+        }
+    });
+    PluginConfig::try_from(("test-plugin", &config))
+        .unwrap()
+        .storages
+        .remove(0)
+}
+
+fn timestamp(secs: u64) -> Timestamp {
+    Timestamp::new(
+        NTP64::from(Duration::from_secs(secs)),
+        TimestampId::try_from([1]).unwrap(),
+    )
+}
+
+fn value(payload: &str) -> Value {
+    Value::new(payload.to_string(), Encoding::TEXT_PLAIN)
+}
+
+fn key(key: &str) -> Option<OwnedKeyExpr> {
+    Some(OwnedKeyExpr::from_str(key).unwrap())
+}
+
+fn payloads(storage: &mut FileStorage, query: StorageQuery) -> Vec<String> {
+    block_on(storage.get(query))
+        .unwrap()
+        .into_iter()
+        .map(|data| data.value.payload().try_to_string().unwrap().into_owned())
+        .collect()
+}
+
+#[test]
+fn test_history_survives_restart() {
+    let dir = TestDir::new();
+    let all = Parameters::from("_time=[..]");
+
+    let mut storage = FileStorage::new(storage_config(), &dir.0).unwrap();
+    assert!(matches!(
+        block_on(storage.put(key("test/a"), value("1"), timestamp(1))),
+        Ok(StorageInsertionResult::Inserted)
+    ));
+    assert!(matches!(
+        block_on(storage.put(key("test/a"), value("3"), timestamp(3))),
+        Ok(StorageInsertionResult::Replaced)
+    ));
+    // Received late: kept in the history but not the latest value.
+    assert!(matches!(
+        block_on(storage.put(key("test/a"), value("2"), timestamp(2))),
+        Ok(StorageInsertionResult::Replaced)
+    ));
+    // Received twice.
+    assert!(matches!(
+        block_on(storage.put(key("test/a"), value("2"), timestamp(2))),
+        Ok(StorageInsertionResult::Outdated)
+    ));
+    let results = block_on(storage.put_batch(vec![
+        StorageWrite::Put {
+            key: None,
+            value: value("none"),
+            timestamp: timestamp(1),
+        },
+        StorageWrite::Put {
+            key: key("test/b"),
+            value: value("1"),
+            timestamp: timestamp(1),
+        },
+        StorageWrite::Delete {
+            key: key("test/b"),
+            timestamp: timestamp(2),
+        },
+    ]))
+    .unwrap();
+    assert!(matches!(
+        results.as_slice(),
+        [
+            StorageInsertionResult::Inserted,
+            StorageInsertionResult::Inserted,
+            StorageInsertionResult::Deleted
+        ]
+    ));
+    drop(storage);
+
+    let mut storage = FileStorage::new(storage_config(), &dir.0).unwrap();
+    assert_eq!(
+        payloads(&mut storage, StorageQuery::latest(key("test/a"))),
+        vec!["3"]
+    );
+    assert_eq!(
+        payloads(
+            &mut storage,
+            StorageQuery::from_parameters(key("test/a"), &all).unwrap()
+        ),
+        vec!["1", "2", "3"]
+    );
+    assert_eq!(
+        payloads(&mut storage, StorageQuery::at(key("test/a"), &timestamp(2))),
+        vec!["2"]
+    );
+    assert_eq!(
+        payloads(&mut storage, StorageQuery::latest(None)),
+        vec!["none"]
+    );
+
+    // The latest update of `test/b` is a delete, its history is still available.
+    assert!(block_on(storage.get(StorageQuery::latest(key("test/b")))).is_err());
+    assert_eq!(
+        payloads(
+            &mut storage,
+            StorageQuery::from_parameters(key("test/b"), &all).unwrap()
+        ),
+        vec!["1"]
+    );
+
+    let mut entries = block_on(storage.get_all_entries()).unwrap();
+    entries.sort_unstable_by_key(|(key, _)| key.as_ref().map(|key| key.to_string()));
+    assert_eq!(
+        entries,
+        vec![(None, timestamp(1)), (key("test/a"), timestamp(3))]
+    );
+}
+
+#[test]
+fn test_incomplete_record_is_discarded() {
+    let dir = TestDir::new();
+
+    let mut storage = FileStorage::new(storage_config(), &dir.0).unwrap();
+    block_on(storage.put(key("test/a"), value("1"), timestamp(1))).unwrap();
+    let size = storage.log.lock().unwrap().size();
+    drop(storage);
+
+    // Simulates a crash while a record was being appended.
+    let log_path = dir.0.join(LOG_FILE_NAME);
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&log_path)
+        .unwrap();
+    file.write_all(&[42, 0, 0, 0, 1, 2, 3]).unwrap();
+    drop(file);
+
+    let mut storage = FileStorage::new(storage_config(), &dir.0).unwrap();
+    assert_eq!(storage.log.lock().unwrap().size(), size);
+    assert_eq!(std::fs::metadata(&log_path).unwrap().len(), size);
+
+    block_on(storage.put(key("test/a"), value("2"), timestamp(2))).unwrap();
+    drop(storage);
+
+    let mut storage = FileStorage::new(storage_config(), &dir.0).unwrap();
+    assert_eq!(
+        payloads(
+            &mut storage,
+            StorageQuery::from_parameters(key("test/a"), &Parameters::from("_time=[..]")).unwrap()
+        ),
+        vec!["1", "2"]
+    );
+}
+
+#[test]
+fn test_purge_survives_restart() {
+    let dir = TestDir::new();
+    let all = Parameters::from("_time=[..]");
+
+    let mut storage = FileStorage::new(storage_config(), &dir.0).unwrap();
+    block_on(storage.put(key("test/a"), value("1"), timestamp(1))).unwrap();
+    block_on(storage.put(key("test/a"), value("22"), timestamp(2))).unwrap();
+    block_on(storage.put(key("test/a"), value("333"), timestamp(3))).unwrap();
+    assert_eq!(
+        block_on(storage.purge(key("test/a"), timestamp(2))).unwrap(),
+        vec![timestamp(1), timestamp(2)]
+    );
+    // Nothing left to purge.
+    assert!(block_on(storage.purge(key("test/a"), timestamp(2)))
+        .unwrap()
+        .is_empty());
+    drop(storage);
+
+    let mut storage = FileStorage::new(storage_config(), &dir.0).unwrap();
+    assert_eq!(
+        payloads(
+            &mut storage,
+            StorageQuery::from_parameters(key("test/a"), &all).unwrap()
+        ),
+        vec!["333"]
+    );
+
+    // Purging every version removes the key.
+    assert_eq!(
+        block_on(storage.purge(key("test/a"), timestamp(3))).unwrap(),
+        vec![timestamp(3)]
+    );
+    assert!(block_on(storage.get_all_entries()).unwrap().is_empty());
+}
+
+#[test]
+fn test_entries_pages() {
+    let dir = TestDir::new();
+
+    let mut storage = FileStorage::new(storage_config(), &dir.0).unwrap();
+    block_on(storage.put(None, value("none"), timestamp(1))).unwrap();
+    for (i, name) in ["test/c", "test/a", "test/d", "test/b"].iter().enumerate() {
+        block_on(storage.put(key(name), value("1"), timestamp(i as u64 + 1))).unwrap();
+    }
+    block_on(storage.delete(key("test/b"), timestamp(10))).unwrap();
+
+    let mut pages = Vec::new();
+    let mut cursor: Option<EntriesCursor> = None;
+    loop {
+        let page = block_on(storage.get_entries_page(cursor, 2)).unwrap();
+        pages.push(
+            page.entries
+                .into_iter()
+                .map(|(key, _)| key.map(|key| key.to_string()))
+                .collect::<Vec<_>>(),
+        );
+        cursor = page.next;
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    // The deleted key is skipped and no empty page is returned.
+    assert_eq!(
+        pages,
+        vec![
+            vec![None, Some("test/a".to_string())],
+            vec![Some("test/c".to_string()), Some("test/d".to_string())],
+        ]
+    );
+}
+
+#[test]
+fn test_log_is_compacted() {
+    let dir = TestDir::new();
+    let all = Parameters::from("_time=[..]");
+    let large = "x".repeat(2 * COMPACTION_MIN_GARBAGE as usize);
+
+    let mut storage = FileStorage::new(storage_config(), &dir.0).unwrap();
+    block_on(storage.put(key("test/a"), value(&large), timestamp(1))).unwrap();
+    block_on(storage.put(key("test/a"), value("2"), timestamp(2))).unwrap();
+    block_on(storage.put(key("test/b"), value("1"), timestamp(1))).unwrap();
+    assert!(storage.log.lock().unwrap().size() > 2 * COMPACTION_MIN_GARBAGE);
+
+    // The purged value exceeds the records still indexed: the log is compacted.
+    block_on(storage.purge(key("test/a"), timestamp(1))).unwrap();
+    let size = storage.log.lock().unwrap().size();
+    assert!(size < 1024);
+    assert_eq!(
+        std::fs::metadata(dir.0.join(LOG_FILE_NAME)).unwrap().len(),
+        size
+    );
+
+    block_on(storage.put(key("test/b"), value("2"), timestamp(2))).unwrap();
+    drop(storage);
+
+    let mut storage = FileStorage::new(storage_config(), &dir.0).unwrap();
+    assert_eq!(
+        payloads(
+            &mut storage,
+            StorageQuery::from_parameters(key("test/a"), &all).unwrap()
+        ),
+        vec!["2"]
+    );
+    assert_eq!(
+        payloads(
+            &mut storage,
+            StorageQuery::from_parameters(key("test/b"), &all).unwrap()
+        ),
+        vec!["1", "2"]
+    );
+}
+
+#[test]
+fn test_tombstones_are_dropped_by_compaction() {
+    let dir = TestDir::new();
+    let large = "x".repeat(2 * COMPACTION_MIN_GARBAGE as usize);
+    let history_len = |storage: &FileStorage, key| storage.index.history(&key).map(Vec::len);
+
+    let mut storage = FileStorage::new(storage_config(), &dir.0).unwrap();
+    block_on(storage.put(key("test/a"), value(&large), timestamp(1))).unwrap();
+    block_on(storage.delete(key("test/a"), timestamp(2))).unwrap();
+    block_on(storage.delete(key("test/b"), timestamp(1))).unwrap();
+    block_on(storage.put(key("test/b"), value("2"), timestamp(2))).unwrap();
+    block_on(storage.delete(key("test/b"), timestamp(3))).unwrap();
+    block_on(storage.put(key("test/b"), value("4"), timestamp(4))).unwrap();
+    block_on(storage.put(key("test/c"), value("1"), timestamp(1))).unwrap();
+    block_on(storage.delete(key("test/c"), timestamp(2))).unwrap();
+
+    // The purge triggers a compaction: the delete of `test/a` no longer hides any value, neither
+    // do the deletes of `test/b` followed by a put, while the latest one of `test/c` does.
+    block_on(storage.purge(key("test/a"), timestamp(1))).unwrap();
+    assert_eq!(history_len(&storage, key("test/a")), None);
+    assert_eq!(history_len(&storage, key("test/b")), Some(2));
+    assert_eq!(history_len(&storage, key("test/c")), Some(2));
+    drop(storage);
+
+    let storage = FileStorage::new(storage_config(), &dir.0).unwrap();
+    assert_eq!(history_len(&storage, key("test/a")), None);
+    assert_eq!(history_len(&storage, key("test/b")), Some(2));
+    assert_eq!(history_len(&storage, key("test/c")), Some(2));
+}
+
+#[test]
+fn test_log_file_is_locked() {
+    let dir = TestDir::new();
+
+    let storage = FileStorage::new(storage_config(), &dir.0).unwrap();
+    assert!(FileStorage::new(storage_config(), &dir.0).is_err());
+    drop(storage);
+
+    assert!(FileStorage::new(storage_config(), &dir.0).is_ok());
+}
@@ -26,9 +26,9 @@ use std::{
     sync::{Arc, Mutex},
 };
 
+use file_backend::FileBackend;
 use memory_backend::MemoryBackend;
-use storages_mgt::StorageMessage;
-use tokio::sync::broadcast::Sender;
+use storages_mgt::{StorageHandle, StorageMessage};
 use zenoh::{
     internal::{
         bail,
@@ -48,6 +48,7 @@ use zenoh_plugin_trait::{
     plugin_long_version, plugin_version, Plugin, PluginControl, PluginReport, PluginStatusRec,
 };
 
+mod file_backend;
 mod memory_backend;
 mod replication;
 mod storages_mgt;
@@ -97,7 +98,7 @@ struct StorageRuntimeInner {
     name: String,
     runtime: Runtime,
     session: Arc<Session>,
-    storages: HashMap<String, HashMap<String, Sender<StorageMessage>>>,
+    storages: HashMap<String, HashMap<String, StorageHandle>>,
     plugins_manager: PluginsManager,
 }
 impl StorageRuntimeInner {
@@ -125,6 +126,7 @@ impl StorageRuntimeInner {
 
         let mut plugins_manager = PluginsManager::dynamic(lib_loader.clone(), BACKEND_LIB_PREFIX);
         plugins_manager.declare_static_plugin::<MemoryBackend, &str>(MEMORY_BACKEND_NAME, true);
+        plugins_manager.declare_static_plugin::<FileBackend, &str>(FILE_BACKEND_NAME, true);
 
         let session = Arc::new(zenoh::session::init(runtime.clone()).wait()?);
 
@@ -154,24 +156,28 @@ impl StorageRuntimeInner {
             storages: Default::default(),
             plugins_manager,
         };
-        new_self
-            .spawn_volume(&VolumeConfig {
-                name: MEMORY_BACKEND_NAME.into(),
-                backend: None,
-                paths: None,
-                required: false,
-                rest: Default::default(),
-            })
-            .map_or_else(
-                |e| {
-                    tracing::error!(
-                        "Cannot spawn static volume '{}': {}",
-                        MEMORY_BACKEND_NAME,
-                        e
-                    )
-                },
-                |_| (),
-            );
+        // NOTE: The "memory" volume is always spawned. If it is configured in the `volumes`, it is
+        //       spawned with that configuration (e.g. to keep the history of the values).
+        let (memory, volumes): (Vec<_>, Vec<_>) = volumes
+            .into_iter()
+            .partition(|volume| volume.name() == MEMORY_BACKEND_NAME);
+        let memory = memory.into_iter().next().unwrap_or_else(|| VolumeConfig {
+            name: MEMORY_BACKEND_NAME.into(),
+            backend: None,
+            paths: None,
+            required: false,
+            rest: Default::default(),
+        });
+        new_self.spawn_volume(&memory).map_or_else(
+            |e| {
+                tracing::error!(
+                    "Cannot spawn static volume '{}': {}",
+                    MEMORY_BACKEND_NAME,
+                    e
+                )
+            },
+            |_| (),
+        );
         for volume in &volumes {
             new_self.spawn_volume(volume).map_or_else(
                 |e| tracing::error!("Cannot spawn volume '{}': {}", volume.name(), e),
@@ -207,7 +213,7 @@ impl StorageRuntimeInner {
                 TOKIO_RUNTIME.block_on(futures::future::join_all(
                     storages
                         .into_values()
-                        .map(|s| async move { s.send(StorageMessage::Stop) }),
+                        .map(|s| async move { s.tx.send(StorageMessage::Stop) }),
                 ))
             });
         }
@@ -256,7 +262,7 @@ impl StorageRuntimeInner {
                     config.volume_id
                 );
                 // let _ = async_std::task::block_on(storage.send(StorageMessage::Stop));
-                let _ = storage.send(StorageMessage::Stop); // TODO: was previously spawning a task. do we need that?
+                let _ = storage.tx.send(StorageMessage::Stop); // TODO: was previously spawning a task. do we need that?
             }
         }
     }
@@ -277,7 +283,7 @@ impl StorageRuntimeInner {
             volume_id,
             backend.name()
         );
-        let stopper = tokio::task::block_in_place(|| {
+        let handle = tokio::task::block_in_place(|| {
             TOKIO_RUNTIME.block_on(create_and_start_storage(
                 admin_key,
                 storage.clone(),
@@ -288,7 +294,7 @@ impl StorageRuntimeInner {
         self.storages
             .entry(volume_id)
             .or_default()
-            .insert(storage_name, stopper);
+            .insert(storage_name, handle);
         Ok(())
     }
 }
@@ -375,13 +381,24 @@ impl RunningPluginTrait for StorageRuntime {
                             if let Some(value) = tokio::task::block_in_place(|| {
                                 TOKIO_RUNTIME.block_on(async {
                                     let (tx, mut rx) = tokio::sync::mpsc::channel(1);
-                                    let _ = handle.send(StorageMessage::GetStatus(tx));
+                                    let _ = handle.tx.send(StorageMessage::GetStatus(tx));
                                     rx.recv().await
                                 })
                             }) {
                                 responses.push(Response::new(key.clone(), value))
                             }
                         }
+                        with_extended_string(key, &["/replication"], |key| {
+                            if keyexpr::new(key.as_str()).unwrap().intersects(key_expr) {
+                                if let Some(value) = handle
+                                    .replication_status
+                                    .as_ref()
+                                    .and_then(|status| status.read().ok()?.clone())
+                                {
+                                    responses.push(Response::new(key.clone(), value))
+                                }
+                            }
+                        });
                     })
                 }
             }
@@ -392,6 +409,7 @@ impl RunningPluginTrait for StorageRuntime {
 
 const BACKEND_LIB_PREFIX: &str = "zenoh_backend_";
 const MEMORY_BACKEND_NAME: &str = "memory";
+const FILE_BACKEND_NAME: &str = "file";
 
 fn with_extended_string<R, F: FnMut(&mut String) -> R>(
     prefix: &mut String,
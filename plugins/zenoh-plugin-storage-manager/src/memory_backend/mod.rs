@@ -15,7 +15,12 @@ use std::{collections::HashMap, sync::Arc};
 
 use async_trait::async_trait;
 use tokio::sync::RwLock;
-use zenoh::{internal::Value, key_expr::OwnedKeyExpr, time::Timestamp, Result as ZResult};
+use zenoh::{
+    internal::{bail, Value},
+    key_expr::OwnedKeyExpr,
+    time::Timestamp,
+    Result as ZResult,
+};
 use zenoh_backend_traits::{
     config::{StorageConfig, VolumeConfig},
     *,
@@ -24,8 +29,13 @@ use zenoh_plugin_trait::{plugin_long_version, plugin_version, Plugin};
 
 use crate::MEMORY_BACKEND_NAME;
 
+/// The property of the volume configuring the [History] capability of its storages: "latest" (the
+/// default) or "all".
+const PROP_HISTORY: &str = "history";
+
 pub struct MemoryBackend {
     config: VolumeConfig,
+    history: History,
 }
 
 impl Plugin for MemoryBackend {
@@ -37,8 +47,20 @@ impl Plugin for MemoryBackend {
     const PLUGIN_LONG_VERSION: &'static str = plugin_long_version!();
 
     fn start(_: &str, args: &VolumeConfig) -> ZResult<VolumeInstance> {
+        let history = match args.rest.get(PROP_HISTORY) {
+            None => History::Latest,
+            Some(serde_json::Value::String(history)) if history == "latest" => History::Latest,
+            Some(serde_json::Value::String(history)) if history == "all" => History::All,
+            Some(_) => bail!(
+                "Invalid configuration of volume '{}': `{PROP_HISTORY}` must be either \"latest\" \
+                 or \"all\"",
+                args.name()
+            ),
+        };
+
         Ok(Box::new(MemoryBackend {
             config: args.clone(),
+            history,
         }))
     }
 }
@@ -52,13 +74,15 @@ impl Volume for MemoryBackend {
     fn get_capability(&self) -> Capability {
         Capability {
             persistence: Persistence::Volatile,
-            history: History::Latest,
+            history: self.history.clone(),
         }
     }
 
     async fn create_storage(&self, properties: StorageConfig) -> ZResult<Box<dyn Storage>> {
         tracing::debug!("Create Memory Storage with configuration: {:?}", properties);
-        Ok(Box::new(MemoryStorage::new(properties).await?))
+        Ok(Box::new(
+            MemoryStorage::new(properties, self.history.clone()).await?,
+        ))
     }
 }
 
@@ -69,16 +93,68 @@ impl Drop for MemoryBackend {
     }
 }
 
+/// An update of a key: the value of a put, `None` for a delete.
+#[derive(Clone)]
+struct Update {
+    timestamp: Timestamp,
+    value: Option<Value>,
+}
+
+/// The updates of each key, sorted by timestamp.
+///
+/// With [History::Latest], only the latest put of each key is kept. With [History::All], all the
+/// updates are kept until they are purged.
+#[derive(Default)]
+struct Updates(HashMap<Option<OwnedKeyExpr>, Vec<Update>>);
+
+impl Updates {
+    fn insert(
+        &mut self,
+        history: &History,
+        key: Option<OwnedKeyExpr>,
+        update: Update,
+    ) -> StorageInsertionResult {
+        if *history == History::Latest {
+            return match update.value {
+                Some(_) => match self.0.insert(key, vec![update]) {
+                    Some(_) => StorageInsertionResult::Replaced,
+                    None => StorageInsertionResult::Inserted,
+                },
+                None => {
+                    self.0.remove(&key);
+                    StorageInsertionResult::Deleted
+                }
+            };
+        }
+
+        let updates = self.0.entry(key).or_default();
+        let had_value = updates.last().is_some_and(|latest| latest.value.is_some());
+        let is_put = update.value.is_some();
+        match updates.binary_search_by_key(&update.timestamp, |update| update.timestamp) {
+            Ok(_) => return StorageInsertionResult::Outdated,
+            Err(position) => updates.insert(position, update),
+        }
+
+        match (is_put, had_value) {
+            (false, _) => StorageInsertionResult::Deleted,
+            (true, true) => StorageInsertionResult::Replaced,
+            (true, false) => StorageInsertionResult::Inserted,
+        }
+    }
+}
+
 struct MemoryStorage {
     config: StorageConfig,
-    map: Arc<RwLock<HashMap<Option<OwnedKeyExpr>, StoredData>>>,
+    history: History,
+    map: Arc<RwLock<Updates>>,
 }
 
 impl MemoryStorage {
-    async fn new(properties: StorageConfig) -> ZResult<MemoryStorage> {
+    async fn new(properties: StorageConfig, history: History) -> ZResult<MemoryStorage> {
         Ok(MemoryStorage {
             config: properties,
-            map: Arc::new(RwLock::new(HashMap::new())),
+            history,
+            map: Arc::new(RwLock::new(Updates::default())),
         })
     }
 }
@@ -96,49 +172,119 @@ impl Storage for MemoryStorage {
         timestamp: Timestamp,
     ) -> ZResult<StorageInsertionResult> {
         tracing::trace!("put for {:?}", key);
-        let mut map = self.map.write().await;
-        match map.entry(key) {
-            std::collections::hash_map::Entry::Occupied(mut e) => {
-                e.insert(StoredData { value, timestamp });
-                return Ok(StorageInsertionResult::Replaced);
-            }
-            std::collections::hash_map::Entry::Vacant(e) => {
-                e.insert(StoredData { value, timestamp });
-                return Ok(StorageInsertionResult::Inserted);
-            }
-        }
+        let update = Update {
+            timestamp,
+            value: Some(value),
+        };
+        Ok(self.map.write().await.insert(&self.history, key, update))
     }
 
     async fn delete(
         &mut self,
         key: Option<OwnedKeyExpr>,
-        _timestamp: Timestamp,
+        timestamp: Timestamp,
     ) -> ZResult<StorageInsertionResult> {
         tracing::trace!("delete for {:?}", key);
-        self.map.write().await.remove_entry(&key);
-        return Ok(StorageInsertionResult::Deleted);
+        let update = Update {
+            timestamp,
+            value: None,
+        };
+        Ok(self.map.write().await.insert(&self.history, key, update))
     }
 
-    async fn get(
+    fn supports_batch(&self) -> bool {
+        true
+    }
+
+    async fn put_batch(
+        &mut self,
+        batch: Vec<StorageWrite>,
+    ) -> ZResult<Vec<StorageInsertionResult>> {
+        tracing::trace!("put_batch of {} writes", batch.len());
+        // Holding the lock for the whole batch makes it atomic for any reader.
+        let mut map = self.map.write().await;
+        let results = batch
+            .into_iter()
+            .map(|write| match write {
+                StorageWrite::Put {
+                    key,
+                    value,
+                    timestamp,
+                } => {
+                    let update = Update {
+                        timestamp,
+                        value: Some(value),
+                    };
+                    map.insert(&self.history, key, update)
+                }
+                StorageWrite::Delete { key, timestamp } => {
+                    let update = Update {
+                        timestamp,
+                        value: None,
+                    };
+                    map.insert(&self.history, key, update)
+                }
+            })
+            .collect();
+        Ok(results)
+    }
+
+    async fn purge(
         &mut self,
         key: Option<OwnedKeyExpr>,
-        _parameters: &str,
-    ) -> ZResult<Vec<StoredData>> {
-        tracing::trace!("get for {:?}", key);
-        // @TODO: use parameters???
-        match self.map.read().await.get(&key) {
-            Some(v) => Ok(vec![v.clone()]),
-            None => Err(format!("Key {:?} is not present", key).into()),
+        timestamp: Timestamp,
+    ) -> ZResult<Vec<Timestamp>> {
+        tracing::trace!("purge for {:?} up to {}", key, timestamp);
+        let mut map = self.map.write().await;
+        let Some(updates) = map.0.get_mut(&key) else {
+            return Ok(Vec::new());
+        };
+        let end = updates.partition_point(|update| update.timestamp <= timestamp);
+        let purged = updates
+            .drain(..end)
+            .map(|update| update.timestamp)
+            .collect();
+        if updates.is_empty() {
+            map.0.remove(&key);
         }
+        Ok(purged)
     }
 
-    async fn get_all_entries(&self) -> ZResult<Vec<(Option<OwnedKeyExpr>, Timestamp)>> {
+    async fn get(&mut self, query: StorageQuery) -> ZResult<Vec<StoredData>> {
+        tracing::trace!("get for {:?}", query.key);
         let map = self.map.read().await;
-        let mut result = Vec::with_capacity(map.len());
-        for (k, v) in map.iter() {
-            result.push((k.clone(), v.timestamp));
+        let updates = match map.0.get(&query.key) {
+            Some(updates) => updates,
+            None => bail!("Key {:?} is not present", query.key),
+        };
+        // NOTE: The latest value is only returned if the key was not deleted since, whereas the
+        //       history of a deleted key remains available until it is purged.
+        if query.is_latest_only() && updates.last().map_or(true, |latest| latest.value.is_none()) {
+            bail!("Key {:?} is not present", query.key);
         }
-        Ok(result)
+
+        Ok(query.select(updates.iter().filter_map(|update| {
+            update.value.as_ref().map(|value| StoredData {
+                value: value.clone(),
+                timestamp: update.timestamp,
+            })
+        })))
+    }
+
+    async fn get_all_entries(&self) -> ZResult<Vec<(Option<OwnedKeyExpr>, Timestamp)>> {
+        // Keys whose most recent update is a delete are not part of the content of the Storage.
+        let map = self.map.read().await;
+        Ok(map
+            .0
+            .iter()
+            .filter_map(|(key, updates)| match updates.last() {
+                Some(Update {
+                    timestamp,
+                    value: Some(_),
+                }) => Some((key.clone(), *timestamp)),
+                _ => None,
+            })
+            .collect())
     }
 }
 
@@ -148,3 +294,7 @@ impl Drop for MemoryStorage {
         tracing::trace!("MemoryStorage::drop()");
     }
 }
+
+#[cfg(test)]
+#[path = "tests/memory_backend.test.rs"]
+mod tests;
@@ -0,0 +1,199 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::{str::FromStr, time::Duration};
+
+use futures::executor::block_on;
+use serde_json::json;
+use zenoh::{
+    bytes::Encoding,
+    internal::Value,
+    key_expr::OwnedKeyExpr,
+    query::Parameters,
+    time::{Timestamp, TimestampId, NTP64},
+};
+use zenoh_backend_traits::{
+    config::{PluginConfig, StorageConfig},
+    History, Storage, StorageInsertionResult, StorageQuery, StorageWrite,
+};
+
+use super::MemoryStorage;
+
+fn storage(history: History) -> MemoryStorage {
+    let config = json!({
+        "storages": {
+            "test-storage": {
+                "key_expr": "test/**",
+                "volume": "memory",
+            }
+        }
+    });
+    let config: StorageConfig = PluginConfig::try_from(("test-plugin", &config))
+        .unwrap()
+        .storages
+        .remove(0);
+    block_on(MemoryStorage::new(config, history)).unwrap()
+}
+
+fn timestamp(secs: u64) -> Timestamp {
+    Timestamp::new(
+        NTP64::from(Duration::from_secs(secs)),
+        TimestampId::try_from([1]).unwrap(),
+    )
+}
+
+fn value(payload: &str) -> Value {
+    Value::new(payload.to_string(), Encoding::TEXT_PLAIN)
+}
+
+fn key(key: &str) -> Option<OwnedKeyExpr> {
+    Some(OwnedKeyExpr::from_str(key).unwrap())
+}
+
+fn query(key_expr: &str, parameters: &str) -> StorageQuery {
+    StorageQuery::from_parameters(key(key_expr), &Parameters::from(parameters)).unwrap()
+}
+
+fn payloads(storage: &mut MemoryStorage, query: StorageQuery) -> Vec<String> {
+    block_on(storage.get(query))
+        .unwrap()
+        .into_iter()
+        .map(|data| data.value.payload().try_to_string().unwrap().into_owned())
+        .collect()
+}
+
+#[test]
+fn test_history_latest() {
+    let mut storage = storage(History::Latest);
+    assert!(matches!(
+        block_on(storage.put(key("test/a"), value("1"), timestamp(1))),
+        Ok(StorageInsertionResult::Inserted)
+    ));
+    assert!(matches!(
+        block_on(storage.put(key("test/a"), value("2"), timestamp(2))),
+        Ok(StorageInsertionResult::Replaced)
+    ));
+
+    // Only the latest value is kept.
+    assert_eq!(payloads(&mut storage, query("test/a", "")), vec!["2"]);
+    assert_eq!(
+        payloads(&mut storage, query("test/a", "_time=[..]")),
+        vec!["2"]
+    );
+
+    assert!(matches!(
+        block_on(storage.delete(key("test/a"), timestamp(3))),
+        Ok(StorageInsertionResult::Deleted)
+    ));
+    assert!(block_on(storage.get(query("test/a", ""))).is_err());
+    assert!(block_on(storage.get_all_entries()).unwrap().is_empty());
+}
+
+#[test]
+fn test_history_all() {
+    let mut storage = storage(History::All);
+    let results = block_on(
+        storage.put_batch(
+            (1..=5)
+                .map(|secs| StorageWrite::Put {
+                    key: key("test/a"),
+                    value: value(&secs.to_string()),
+                    timestamp: timestamp(secs),
+                })
+                .collect(),
+        ),
+    )
+    .unwrap();
+    assert!(matches!(
+        results.as_slice(),
+        [
+            StorageInsertionResult::Inserted,
+            StorageInsertionResult::Replaced,
+            StorageInsertionResult::Replaced,
+            StorageInsertionResult::Replaced,
+            StorageInsertionResult::Replaced
+        ]
+    ));
+    // Received twice.
+    assert!(matches!(
+        block_on(storage.put(key("test/a"), value("3"), timestamp(3))),
+        Ok(StorageInsertionResult::Outdated)
+    ));
+
+    assert_eq!(payloads(&mut storage, query("test/a", "")), vec!["5"]);
+    assert_eq!(
+        payloads(&mut storage, query("test/a", "_time=[..]")),
+        vec!["1", "2", "3", "4", "5"]
+    );
+    assert_eq!(
+        payloads(&mut storage, query("test/a", "_time=[..];_limit=2")),
+        vec!["1", "2"]
+    );
+    assert_eq!(
+        payloads(&mut storage, query("test/a", "_time=[..];_order=desc")),
+        vec!["5", "4", "3", "2", "1"]
+    );
+    assert_eq!(
+        payloads(
+            &mut storage,
+            query(
+                "test/a",
+                "_time=[1970-01-01T00:00:02Z..1970-01-01T00:00:04Z];_order=desc;_limit=2"
+            )
+        ),
+        vec!["4", "3"]
+    );
+    assert_eq!(
+        payloads(&mut storage, StorageQuery::at(key("test/a"), &timestamp(2))),
+        vec!["2"]
+    );
+}
+
+#[test]
+fn test_history_all_delete_and_purge() {
+    let mut storage = storage(History::All);
+    block_on(storage.put(key("test/a"), value("1"), timestamp(1))).unwrap();
+    block_on(storage.put(key("test/a"), value("2"), timestamp(2))).unwrap();
+    block_on(storage.put(key("test/b"), value("1"), timestamp(1))).unwrap();
+    assert!(matches!(
+        block_on(storage.delete(key("test/a"), timestamp(3))),
+        Ok(StorageInsertionResult::Deleted)
+    ));
+
+    // The latest update of `test/a` is a delete, its history is still available.
+    assert!(block_on(storage.get(query("test/a", ""))).is_err());
+    assert_eq!(
+        payloads(&mut storage, query("test/a", "_time=[..];_order=desc")),
+        vec!["2", "1"]
+    );
+    assert_eq!(
+        block_on(storage.get_all_entries()).unwrap(),
+        vec![(key("test/b"), timestamp(1))]
+    );
+
+    assert_eq!(
+        block_on(storage.purge(key("test/a"), timestamp(2))).unwrap(),
+        vec![timestamp(1), timestamp(2)]
+    );
+    assert!(payloads(&mut storage, query("test/a", "_time=[..]")).is_empty());
+    assert_eq!(
+        block_on(storage.purge(key("test/a"), timestamp(3))).unwrap(),
+        vec![timestamp(3)]
+    );
+    assert!(block_on(storage.get(query("test/a", "_time=[..]"))).is_err());
+    assert_eq!(
+        payloads(&mut storage, query("test/b", "_time=[..]")),
+        vec!["1"]
+    );
+}
@@ -22,7 +22,7 @@ use zenoh::{key_expr::OwnedKeyExpr, sample::SampleKind, time::Timestamp};
 
 use super::{
     digest::Fingerprint,
-    log::{Event, EventMetadata, LogLatestKey},
+    log::{Action, Event, EventMetadata, LogLatestKey},
 };
 
 /// The `EventRemoval` enumeration lists the possible outcomes when searching for an older [Event]
@@ -233,6 +233,29 @@ impl Interval {
         removed_event
     }
 
+    /// Removes the versions of the key expressions present in `newer_versions` from this
+    /// `Interval`, if `prune` is true, and adds the key expressions of the remaining versions to
+    /// `newer_versions`, returning the number of versions removed.
+    ///
+    /// The `Interval`s must be visited from the newest to the oldest, see
+    /// [LogLatest::prune_cold_versions].
+    ///
+    /// [LogLatest::prune_cold_versions]: super::log::LogLatest::prune_cold_versions
+    pub(crate) fn prune_versions(
+        &mut self,
+        newer_versions: &mut HashSet<Option<OwnedKeyExpr>>,
+        prune: bool,
+    ) -> usize {
+        let mut pruned = 0;
+        for sub_interval in self.sub_intervals.values_mut().rev() {
+            self.fingerprint ^= sub_interval.fingerprint;
+            pruned += sub_interval.prune_versions(newer_versions, prune);
+            self.fingerprint ^= sub_interval.fingerprint;
+        }
+
+        pruned
+    }
+
     /// Removes and returns the [Event] present in this `Interval` that are overridden by the
     /// provided Wildcard Update.
     ///
@@ -410,6 +433,37 @@ impl SubInterval {
         removed_event
     }
 
+    /// See [Interval::prune_versions].
+    fn prune_versions(
+        &mut self,
+        newer_versions: &mut HashSet<Option<OwnedKeyExpr>>,
+        prune: bool,
+    ) -> usize {
+        let mut versions = self
+            .events
+            .iter()
+            .filter(|(_, event)| {
+                event.versioned && matches!(event.action, Action::Put | Action::Delete)
+            })
+            .map(|(log_key, event)| (event.timestamp, log_key.clone(), event.stripped_key.clone()))
+            .collect::<Vec<_>>();
+        // NOTE: Within a SubInterval, the versions must also be visited from the newest to the
+        //       oldest.
+        versions.sort_unstable_by(|(lhs, _, _), (rhs, _, _)| rhs.cmp(lhs));
+
+        let mut pruned = 0;
+        for (_, log_key, stripped_key) in versions {
+            if !newer_versions.insert(stripped_key) && prune {
+                if let Some(event) = self.events.remove(&log_key) {
+                    self.fingerprint ^= event.fingerprint();
+                    pruned += 1;
+                }
+            }
+        }
+
+        pruned
+    }
+
     /// Removes and returns the [Event] present in this `SubInterval` that are overridden by the
     /// provided Wildcard Update.
     ///
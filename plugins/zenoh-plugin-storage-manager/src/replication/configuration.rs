@@ -18,13 +18,21 @@ use std::{
 };
 
 use zenoh::{internal::bail, key_expr::OwnedKeyExpr, time::Timestamp, Result};
-use zenoh_backend_traits::config::ReplicaConfig;
+use zenoh_backend_traits::{config::ReplicaConfig, History};
 
 use super::{
     classification::{IntervalIdx, SubIntervalIdx},
     digest::Fingerprint,
 };
 
+/// The version of the format of the replication messages, to increment whenever the serialised
+/// layout of the messages exchanged by the Replicas changes.
+///
+/// Version 1 added the `versioned` field to the [EventMetadata].
+///
+/// [EventMetadata]: super::log::EventMetadata
+const FORMAT_VERSION: u32 = 1;
+
 /// The [Configuration] is, mostly, a thin wrapper around the [ReplicaConfig].
 ///
 /// It exposes its fingerprint: a 64 bits hash of its inner fields. The `storage_key_expr` (and
@@ -33,13 +41,22 @@ use super::{
 /// a Replica active on "replication/**" to receive and process the Digests emitted by a Replica
 /// active on "replication/a/*".
 ///
+/// Similarly, the [History] capability of the Storage is part of the fingerprint: a Replica that
+/// keeps all the values of a key expression cannot align with one that only keeps the latest.
+///
+/// The version of the format of the replication messages is also part of the fingerprint: the
+/// Replicas serialising their [EventMetadata] differently cannot decode each other's messages.
+///
 /// Using the newtype pattern allows us to add methods to compute the time classification of
 /// events.
+///
+/// [EventMetadata]: super::log::EventMetadata
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub(crate) struct Configuration {
     storage_key_expr: OwnedKeyExpr,
     prefix: Option<OwnedKeyExpr>,
     replica_config: ReplicaConfig,
+    history: History,
     fingerprint: Fingerprint,
 }
 
@@ -52,15 +69,17 @@ impl Deref for Configuration {
 }
 
 impl Configuration {
-    /// Creates a new [Configuration] based on the provided [ReplicaConfig].
+    /// Creates a new [Configuration] based on the provided [ReplicaConfig] and [History].
     ///
     /// This constructor also computes its [Fingerprint].
     pub fn new(
         storage_key_expr: OwnedKeyExpr,
         prefix: Option<OwnedKeyExpr>,
         replica_config: ReplicaConfig,
+        history: History,
     ) -> Self {
         let mut hasher = xxhash_rust::xxh3::Xxh3::default();
+        hasher.update(&FORMAT_VERSION.to_le_bytes());
         hasher.update(storage_key_expr.as_bytes());
         if let Some(prefix) = &prefix {
             hasher.update(prefix.as_bytes());
@@ -70,11 +89,17 @@ impl Configuration {
         hasher.update(&replica_config.hot.to_le_bytes());
         hasher.update(&replica_config.warm.to_le_bytes());
         hasher.update(&replica_config.propagation_delay.as_millis().to_le_bytes());
+        // NOTE: Only hashed for `History::All` such that the fingerprint of the Storage that keep
+        //       the latest value is not affected.
+        if history == History::All {
+            hasher.update(b"history:all");
+        }
 
         Self {
             storage_key_expr,
             prefix,
             replica_config,
+            history,
             fingerprint: Fingerprint::from(hasher.digest()),
         }
     }
@@ -83,6 +108,11 @@ impl Configuration {
         self.prefix.as_ref()
     }
 
+    /// Returns the [History] capability of the Storage.
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
     /// Returns the [Fingerprint] of the `Configuration`.
     ///
     /// The fingerprint is the hash of all its fields, using the `xxhash_rust` crate.
@@ -32,11 +32,16 @@ use zenoh::{
     query::{ConsolidationMode, Selector},
     sample::{Locality, SampleKind},
     time::Timestamp,
-    Session,
+    Result as ZResult, Session,
 };
 
 use self::aligner_reply::AlignmentReply;
-use super::{digest::Digest, log::LogLatest, Action, Event, LogLatestKey};
+use super::{
+    digest::Digest,
+    log::LogLatest,
+    status::{ReplicationStatus, ReplicationStatusSnapshot},
+    Action, Event, LogLatestKey,
+};
 use crate::{
     replication::core::aligner_query::AlignmentQuery,
     storages_mgt::{LatestUpdates, StorageService},
@@ -54,6 +59,8 @@ pub(crate) struct Replication {
     pub(crate) storage_key_expr: OwnedKeyExpr,
     pub(crate) latest_updates: Arc<RwLock<LatestUpdates>>,
     pub(crate) storage_service: Arc<StorageService>,
+    pub(crate) status: Arc<RwLock<ReplicationStatus>>,
+    pub(crate) status_snapshot: ReplicationStatusSnapshot,
 }
 
 impl Replication {
@@ -63,8 +70,8 @@ impl Replication {
     /// # ⚠️ Assumption: empty Storage
     ///
     /// We assume that this method will only be called if the underlying Storage is empty. This has
-    /// at least one consequence: if the Aligner receives a `delete` event from the Replica, it will
-    /// not attempt to delete anything from the Storage.
+    /// at least one consequence: if the Aligner receives a `delete` event from the Replica, there
+    /// is nothing to delete from the Storage.
     ///
     /// # Replica discovery
     ///
@@ -114,6 +121,125 @@ impl Replication {
         }
     }
 
+    /// Requests the entire content of all the Replicas, regardless of the Digest they published.
+    ///
+    /// Contrary to the initial alignment, the Storage is not assumed to be empty: the `delete`
+    /// events received from the Replicas are applied to the Storage.
+    pub(crate) async fn force_alignment(&self) {
+        let ke_all_replicas = match keformat!(
+            aligner_key_expr_formatter::formatter(),
+            hash_configuration = *self
+                .replication_log
+                .read()
+                .await
+                .configuration
+                .fingerprint(),
+            zid = "*",
+        ) {
+            Ok(ke) => ke,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to generate key expression to query all Replicas: {e:?}. Skipping \
+                     forced alignment."
+                );
+                return;
+            }
+        };
+
+        tracing::info!("Forcing alignment with all Replicas");
+        self.status
+            .write()
+            .await
+            .record_forced_alignment(SystemTime::now());
+        self.refresh_status_snapshot().await;
+        self.spawn_query_replica_aligner(ke_all_replicas, AlignmentQuery::All);
+    }
+
+    /// Returns the replication status of the Storage, as exposed in its admin space.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the local [Digest] could not be computed.
+    pub(crate) async fn status(&self) -> ZResult<serde_json::Value> {
+        let (configuration, digest) = {
+            let replication_log_guard = self.replication_log.read().await;
+            (
+                replication_log_guard.configuration.clone(),
+                replication_log_guard.digest()?,
+            )
+        };
+
+        self.status
+            .read()
+            .await
+            .to_json(&configuration, &digest, SystemTime::now())
+    }
+
+    /// Refreshes the [ReplicationStatusSnapshot] read by the admin space with the current
+    /// replication status of the Storage.
+    pub(crate) async fn refresh_status_snapshot(&self) {
+        match self.status().await {
+            Ok(status) => match self.status_snapshot.write() {
+                Ok(mut snapshot) => *snapshot = Some(status),
+                Err(e) => tracing::error!("Failed to refresh the replication status: {e:?}"),
+            },
+            Err(e) => tracing::error!("Failed to compute the replication status: {e:?}"),
+        }
+    }
+
+    /// Spawns a task that forces an alignment with all the Replicas each time a Sample is
+    /// published on the control key expression of the Storage.
+    ///
+    /// The control key expression is `<admin_key>/replication/align`, where `admin_key` is the
+    /// key expression of the Storage in the admin space. As any write in the admin space, it is
+    /// only honoured if the `adminspace.permissions.write` option of the router is enabled.
+    pub(crate) fn spawn_control_subscriber(&self, admin_key: String) -> JoinHandle<()> {
+        let replication = self.clone();
+
+        tokio::task::spawn(async move {
+            let control_key = format!("{admin_key}/replication/align");
+            let subscriber = match replication
+                .zenoh_session
+                .declare_subscriber(&control_key)
+                .await
+            {
+                Ok(subscriber) => subscriber,
+                Err(e) => {
+                    tracing::error!(
+                        "Could not declare subscriber on < {control_key} >: {e:?}. The alignment \
+                         of the storage cannot be forced."
+                    );
+                    return;
+                }
+            };
+
+            tracing::debug!("Subscribed to {control_key}");
+
+            while let Ok(sample) = subscriber.recv_async().await {
+                if sample.kind() != SampleKind::Put {
+                    continue;
+                }
+
+                if !replication
+                    .zenoh_session
+                    .config()
+                    .lock()
+                    .adminspace
+                    .permissions()
+                    .write
+                {
+                    tracing::error!(
+                        "Received a request to force the alignment on < {control_key} > but write \
+                         permissions on the admin space are disabled. Ignoring it."
+                    );
+                    continue;
+                }
+
+                replication.force_alignment().await;
+            }
+        })
+    }
+
     /// Spawns a task that periodically publishes the [Digest] of the Replication [Log].
     ///
     /// This task will perform the following steps:
@@ -220,6 +346,13 @@ impl Replication {
                 {
                     let mut replication_guard = replication.replication_log.write().await;
                     replication_guard.update(events.drain().map(|(_, event)| event));
+                    match replication_guard.prune_cold_versions() {
+                        Ok(0) => {}
+                        Ok(pruned) => {
+                            tracing::debug!("Pruned {pruned} version(s) from the cold era")
+                        }
+                        Err(e) => tracing::error!("Failed to prune the cold era: {e:?}"),
+                    }
                     digest = match replication_guard.digest() {
                         Ok(digest) => digest,
                         Err(e) => {
@@ -258,6 +391,7 @@ impl Replication {
                     Ok(_) => tracing::trace!("Published Digest: {digest:?}"),
                     Err(e) => tracing::error!("Failed to publish the replication Digest: {e:?}"),
                 }
+                replication.refresh_status_snapshot().await;
 
                 let digest_update_duration = digest_update_start.elapsed();
                 if digest_update_duration > configuration.interval {
@@ -378,7 +512,15 @@ impl Replication {
                             }
                         };
 
-                        if let Some(digest_diff) = digest.diff(other_digest) {
+                        let digest_diff = digest.diff(other_digest);
+                        replication.status.write().await.record_digest(
+                            source_zid.as_str(),
+                            digest_diff.as_ref(),
+                            SystemTime::now(),
+                        );
+                        replication.refresh_status_snapshot().await;
+
+                        if let Some(digest_diff) = digest_diff {
                             tracing::debug!("Potential misalignment detected: {digest_diff:?}");
 
                             let replica_aligner_ke = match keformat!(
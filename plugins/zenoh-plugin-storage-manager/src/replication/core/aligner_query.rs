@@ -16,6 +16,7 @@ use std::collections::{HashMap, HashSet};
 
 use serde::{Deserialize, Serialize};
 use zenoh::{bytes::ZBytes, internal::Value, key_expr::keyexpr_tree::IKeyExprTree, query::Query};
+use zenoh_backend_traits::StorageQuery;
 
 use super::aligner_reply::AlignmentReply;
 use crate::replication::{
@@ -286,7 +287,10 @@ impl Replication {
                 let stored_data = {
                     let mut storage = self.storage_service.storage.lock().await;
                     match storage
-                        .get(event_to_retrieve.stripped_key.clone(), "")
+                        .get(StorageQuery::at(
+                            event_to_retrieve.stripped_key.clone(),
+                            event_to_retrieve.timestamp(),
+                        ))
                         .await
                     {
                         Ok(stored_data) => stored_data,
@@ -268,6 +268,17 @@ impl Replication {
             // A Delete can be applied right away, we have all the information we need.
             Action::Delete => {
                 match replication_log_guard.remove_older(&replica_event) {
+                    // NOTE: A versioned Delete is recorded by the Storage, as any other version.
+                    EventRemoval::NotFound if replica_event.versioned => {
+                        let _ = self
+                            .storage_service
+                            .store_aligned_delete(
+                                replica_event.stripped_key.clone(),
+                                replica_event.timestamp,
+                                &replica_event.action,
+                            )
+                            .await;
+                    }
                     EventRemoval::NotFound => {}
                     EventRemoval::KeptNewer => return None,
                     EventRemoval::RemovedOlder(older_event) => {
@@ -281,10 +292,11 @@ impl Replication {
                             //        errors.
                             let _ = self
                                 .storage_service
-                                .storage
-                                .lock()
-                                .await
-                                .delete(replica_event.stripped_key.clone(), replica_event.timestamp)
+                                .store_aligned_delete(
+                                    replica_event.stripped_key.clone(),
+                                    replica_event.timestamp,
+                                    &replica_event.action,
+                                )
                                 .await;
                         }
                     }
@@ -308,16 +320,16 @@ impl Replication {
     /// Processes the [EventMetadata] and [Sample] sent by the Replica, adding it to our Storage if
     /// needed.
     ///
-    /// # Special case: initial alignment
+    /// # Special case: initial and forced alignments
     ///
     /// Outside of the initial alignment, an [EventMetadata] with an action set to `Delete` will be
     /// processed during the previous step, i.e. in the `AlignmentReply::EventsMetadata` as, as
     /// explained there, we already have at that stage all the required information to perform the
     /// deletion.
     ///
-    /// That fact is true except for the initial alignment: the initial alignment bypasses all these
-    /// steps and the Replica goes straight to sending all its Replication Log and data in its
-    /// Storage. Including for the deleted events.
+    /// That fact is true except for the initial and forced alignments: they bypass all these steps
+    /// and the Replica goes straight to sending all its Replication Log and data in its Storage.
+    /// Including for the deleted events.
     async fn process_event_retrieval(&self, replica_event: EventMetadata, sample: Sample) {
         tracing::trace!("Processing `AlignmentReply::Retrieval` for < {replica_event:?} >");
 
@@ -342,16 +354,41 @@ impl Replication {
 
         // The Event is newer than what we have and is not overridden by a Wildcard Update, we
         // need to process it.
-        replication_log_guard.remove_older(&replica_event);
+        let removal = replication_log_guard.remove_older(&replica_event);
 
         match &replica_event.action {
             // NOTE: This code can only be called with `action` set to `Delete` or `WildcardDelete`
-            // on an initial alignment, in which case the Storage of the receiving Replica is empty
-            // => there is no need to actually call `storage.delete`.
+            // on an initial or a forced alignment. On an initial alignment the Storage of the
+            // receiving Replica is empty but, on a forced alignment, it is not: the older value,
+            // if any, has to be deleted.
             //
-            // Outside of an initial alignment, the `Delete` or `WildcardDelete` actions will be
+            // Outside of these alignments, the `Delete` or `WildcardDelete` actions will be
             // performed at the step above, in `AlignmentReply::EventsMetadata`.
-            Action::Delete => {}
+            Action::Delete => {
+                if replica_event.versioned {
+                    // NOTE: A versioned Delete is recorded by the Storage, as any other version.
+                    let _ = self
+                        .storage_service
+                        .store_aligned_delete(
+                            replica_event.stripped_key.clone(),
+                            replica_event.timestamp,
+                            &replica_event.action,
+                        )
+                        .await;
+                } else if let EventRemoval::RemovedOlder(older_event) = removal {
+                    if older_event.action == Action::Put {
+                        // NOTE: See the comment in `process_event_metadata` regarding errors.
+                        let _ = self
+                            .storage_service
+                            .store_aligned_delete(
+                                replica_event.stripped_key.clone(),
+                                replica_event.timestamp,
+                                &replica_event.action,
+                            )
+                            .await;
+                    }
+                }
+            }
             Action::WildcardDelete(wildcard_delete_ke) => {
                 self.storage_service
                     .register_wildcard_update(
@@ -365,13 +402,11 @@ impl Replication {
             Action::Put => {
                 if matches!(
                     self.storage_service
-                        .storage
-                        .lock()
-                        .await
-                        .put(
+                        .store_aligned_put(
                             replica_event.stripped_key.clone(),
                             sample.into(),
                             replica_event.timestamp,
+                            &replica_event.action
                         )
                         .await,
                     Ok(StorageInsertionResult::Outdated) | Err(_)
@@ -412,6 +447,12 @@ impl Replication {
         replication_log_guard: &mut RwLockWriteGuard<'_, LogLatest>,
         replica_event: &EventMetadata,
     ) -> bool {
+        // A version is self-contained: the Wildcard Updates that applied to it were already
+        // applied by the Replica that generated it. We only need to check if we have it.
+        if replica_event.versioned {
+            return replication_log_guard.lookup(replica_event).is_none();
+        }
+
         // We received an EventMetadata, we need to check if we don't have:
         // 1. a Wildcard Update that overrides it,
         // 2. a more recent Event on that same key expression already in the Replication Log.
@@ -484,10 +525,11 @@ impl Replication {
                 if log_event.action == Action::Put {
                     let _ = self
                         .storage_service
-                        .storage
-                        .lock()
-                        .await
-                        .delete(replica_event.stripped_key.clone(), *log_event.timestamp())
+                        .store_aligned_delete(
+                            replica_event.stripped_key.clone(),
+                            *log_event.timestamp(),
+                            &replica_event.action,
+                        )
                         .await;
                 }
 
@@ -560,6 +602,21 @@ impl Replication {
             Action::WildcardDelete(wildcard_ke) => (wildcard_ke, SampleKind::Delete),
         };
 
+        // The versions generated by a versioned Wildcard Update are replicated on their own: the
+        // previous versions are kept, the Wildcard Update only has to be registered such that the
+        // Storage can apply it on late-comers.
+        if replica_event.versioned {
+            self.storage_service
+                .register_wildcard_update(
+                    wildcard_ke.clone(),
+                    wildcard_kind,
+                    replica_event.timestamp,
+                    value,
+                )
+                .await;
+            return;
+        }
+
         if matches!(
             replication_log_guard.remove_older(replica_event),
             EventRemoval::KeptNewer
@@ -606,13 +663,11 @@ impl Replication {
                 (Action::Put, SampleKind::Put) => {
                     if matches!(
                         self.storage_service
-                            .storage
-                            .lock()
-                            .await
-                            .put(
+                            .store_aligned_put(
                                 overridden_event.key_expr().clone(),
                                 value.clone(),
                                 replica_event.timestamp,
+                                &replica_event.action
                             )
                             .await,
                         Ok(StorageInsertionResult::Outdated) | Err(_)
@@ -623,12 +678,10 @@ impl Replication {
                 (Action::Put, SampleKind::Delete) => {
                     if matches!(
                         self.storage_service
-                            .storage
-                            .lock()
-                            .await
-                            .delete(
+                            .store_aligned_delete(
                                 overridden_event.key_expr().clone(),
                                 *overridden_event.timestamp(),
+                                &replica_event.action
                             )
                             .await,
                         Ok(StorageInsertionResult::Outdated)
@@ -757,13 +810,11 @@ impl Replication {
         if wildcard_update.kind() == SampleKind::Put
             && matches!(
                 self.storage_service
-                    .storage
-                    .lock()
-                    .await
-                    .put(
+                    .store_aligned_put(
                         replica_event.stripped_key.clone(),
                         wildcard_update.into_value(),
-                        wildcard_timestamp
+                        wildcard_timestamp,
+                        &wildcard_action
                     )
                     .await,
                 Ok(StorageInsertionResult::Outdated) | Err(_)
@@ -20,7 +20,7 @@ use std::{
 use bloomfilter::Bloom;
 use serde::{Deserialize, Serialize};
 use zenoh::{key_expr::OwnedKeyExpr, sample::SampleKind, time::Timestamp, Result as ZResult};
-use zenoh_backend_traits::config::ReplicaConfig;
+use zenoh_backend_traits::{config::ReplicaConfig, History};
 
 use super::{
     classification::{EventRemoval, Interval, IntervalIdx},
@@ -101,12 +101,19 @@ impl From<&Action> for ActionKind {
 ///
 /// Associating the `action` allows only sending the metadata when the associate action is
 /// [SampleKind::Delete].
+///
+/// An `EventMetadata` is `versioned` if it was generated by a Storage that keeps all the values
+/// of a key expression (i.e. with the [History::All] capability): it then identifies one version
+/// among others and not the latest publication.
+///
+/// [History::All]: zenoh_backend_traits::History::All
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, Hash)]
 pub struct EventMetadata {
     pub(crate) stripped_key: Option<OwnedKeyExpr>,
     pub(crate) timestamp: Timestamp,
     pub(crate) timestamp_last_non_wildcard_update: Option<Timestamp>,
     pub(crate) action: Action,
+    pub(crate) versioned: bool,
 }
 
 impl EventMetadata {
@@ -123,10 +130,14 @@ impl EventMetadata {
     }
 
     /// Returns the [LogLatestKey] corresponding to this [Event].
+    ///
+    /// The [LogLatestKey] of a versioned [Event] includes its [Timestamp]: each version of a key
+    /// expression is thus tracked separately.
     pub fn log_key(&self) -> LogLatestKey {
         LogLatestKey {
             maybe_stripped_key: self.stripped_key.clone(),
             action: (&self.action).into(),
+            version: self.versioned.then_some(self.timestamp),
         }
     }
 }
@@ -138,6 +149,7 @@ impl From<&Event> for EventMetadata {
             timestamp: event.timestamp,
             timestamp_last_non_wildcard_update: event.timestamp_last_non_wildcard_update,
             action: event.action.clone(),
+            versioned: event.versioned,
         }
     }
 }
@@ -223,10 +235,38 @@ impl Event {
                 timestamp,
                 timestamp_last_non_wildcard_update,
                 action: actual_action,
+                versioned: false,
             },
         }
     }
 
+    /// Creates a new versioned [Event], i.e. one of the versions of the key expression kept by a
+    /// Storage with the [History::All] capability.
+    ///
+    /// [History::All]: zenoh_backend_traits::History::All
+    pub fn new_version(
+        key_expr: Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+        action: &Action,
+    ) -> Self {
+        let mut event = Event::new(key_expr, timestamp, action);
+        event.metadata.versioned = true;
+        event
+    }
+
+    /// Creates a new [Event], versioned if the provided [History] is [History::All].
+    pub fn new_for_history(
+        history: &History,
+        key_expr: Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+        action: &Action,
+    ) -> Self {
+        match history {
+            History::Latest => Event::new(key_expr, timestamp, action),
+            History::All => Event::new_version(key_expr, timestamp, action),
+        }
+    }
+
     /// Computes the [Fingerprint] of the [Event], which is equal to the hash of its fields
     /// `timestamp` and `maybe_stripped_key`.
     ///
@@ -290,10 +330,11 @@ pub enum EventInsertion {
 
 /// The `LogLatest` keeps track of the last publication that happened on a key expression.
 ///
-/// By definition, the `LogLatest` is only compatible with storage that have the capability
-/// `History::Latest` (the default value). For instance, that means that it will *not* work for
-/// time-series storage that keep track of all the publications that happen for a given key
-/// expression.
+/// For Storage that have the capability `History::All` (i.e. time-series storage that keep track
+/// of all the publications that happen for a given key expression), the `LogLatest` keeps track of
+/// all the publications instead: their [Event]s are versioned, see [Event::new_version], and are
+/// identified by their key expression *and* their [Timestamp]. Hence, a versioned [Event] never
+/// replaces another.
 ///
 /// Internally, the `LogLatest` groups publications (i.e. [Event]s) according to their [Timestamp]
 /// in [Interval]s and [SubInterval]s. The purpose of this grouping is to facilitate the alignment
@@ -317,6 +358,8 @@ pub struct LogLatest {
 pub(crate) struct LogLatestKey {
     maybe_stripped_key: Option<OwnedKeyExpr>,
     action: ActionKind,
+    /// The [Timestamp] of a versioned [Event], `None` otherwise.
+    version: Option<Timestamp>,
 }
 
 impl LogLatest {
@@ -338,14 +381,16 @@ impl LogLatest {
         true
     }
 
-    /// Creates a new [LogLatest] configured with the provided [ReplicaConfig].
+    /// Creates a new [LogLatest] configured with the provided [ReplicaConfig], for a Storage with
+    /// the provided [History] capability.
     pub fn new(
         storage_key_expr: OwnedKeyExpr,
         prefix: Option<OwnedKeyExpr>,
         replica_config: ReplicaConfig,
+        history: History,
     ) -> Self {
         Self {
-            configuration: Configuration::new(storage_key_expr, prefix, replica_config),
+            configuration: Configuration::new(storage_key_expr, prefix, replica_config, history),
             intervals: BTreeMap::default(),
             // TODO Should these be configurable?
             //
@@ -564,6 +609,38 @@ impl LogLatest {
         }
     }
 
+    /// Removes, for a Storage with the [History::All] capability, the versions of the cold era
+    /// that are superseded by a more recent version of the same key expression, returning the
+    /// number of versions removed.
+    ///
+    /// Only the versions of the hot and warm eras are exchanged individually by the Replicas: the
+    /// cold era only tracks the latest version of each key expression, the older ones remaining in
+    /// the Storage without being replicated. Otherwise, the Replication Log would keep every
+    /// version forever.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the index of the last elapsed interval is superior to
+    /// [u64::MAX], see [Configuration::last_elapsed_interval].
+    pub(crate) fn prune_cold_versions(&mut self) -> ZResult<usize> {
+        if *self.configuration.history() != History::All {
+            return Ok(0);
+        }
+
+        let warm_era_lower_bound = self
+            .configuration
+            .warm_era_lower_bound(self.configuration.last_elapsed_interval()?);
+
+        let mut newer_versions = HashSet::new();
+        let mut pruned = 0;
+        for (interval_idx, interval) in self.intervals.iter_mut().rev() {
+            pruned +=
+                interval.prune_versions(&mut newer_versions, *interval_idx < warm_era_lower_bound);
+        }
+
+        Ok(pruned)
+    }
+
     /// Removes and returns the [Event]s overridden by the provided Wildcard Update from the
     /// Replication Log.
     ///
@@ -15,8 +15,9 @@
 //! This module exposes the [ReplicationService] structure needed by the storage manager to
 //! replicate the content of storage across a Zenoh network.
 //!
-//! This structure, and thus the replication, only works for storage that have the [History::Latest]
-//! capability.
+//! This structure, and thus the replication, works for storage that have the [History::Latest] or
+//! the [History::All] capability. For the latter, every version of a key expression is replicated:
+//! each is tracked separately in the Replication Log, see [LogLatest].
 //!
 //! From a high-level, the replication works by generating a concise view of the state of the
 //! storage at regular time intervals. To do so, the time is divided in 'intervals' (themselves
@@ -24,7 +25,14 @@
 //! of each group is computed and these fingerprints are sent over the network to other storage for
 //! comparison.
 //!
+//! The replication status of a storage — its Digest, the bounds of its eras and the alignment
+//! state with each Replica — is exposed in the admin space under
+//! `@/<zid>/<whatami>/status/plugins/<plugin>/storages/<storage>/replication`. It is a snapshot,
+//! refreshed each time the Digest is published, a Digest of a Replica is received or an alignment
+//! is forced. A put on `.../replication/align` forces a full alignment with all the Replicas.
+//!
 //! [History::Latest]: zenoh_backend_traits::History::Latest
+//! [History::All]: zenoh_backend_traits::History::All
 
 mod classification;
 mod configuration;
@@ -32,6 +40,8 @@ mod core;
 mod digest;
 mod log;
 mod service;
+mod status;
 
 pub(crate) use log::{Action, Event, LogLatest, LogLatestKey};
 pub(crate) use service::ReplicationService;
+pub(crate) use status::ReplicationStatusSnapshot;
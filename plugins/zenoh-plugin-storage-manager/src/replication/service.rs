@@ -15,18 +15,26 @@
 use std::sync::Arc;
 
 use tokio::{
-    sync::{broadcast::Receiver, RwLock},
+    sync::{
+        broadcast::{error::RecvError, Receiver},
+        RwLock,
+    },
     task::JoinHandle,
 };
 use zenoh::{key_expr::OwnedKeyExpr, session::Session};
 
-use super::{core::Replication, LogLatest};
+use super::{
+    core::Replication,
+    status::{ReplicationStatus, ReplicationStatusSnapshot},
+    LogLatest,
+};
 use crate::storages_mgt::{LatestUpdates, StorageMessage, StorageService};
 
 pub(crate) struct ReplicationService {
     digest_publisher_handle: JoinHandle<()>,
     digest_subscriber_handle: JoinHandle<()>,
     aligner_queryable_handle: JoinHandle<()>,
+    control_subscriber_handle: JoinHandle<()>,
 }
 
 impl ReplicationService {
@@ -40,19 +48,25 @@ impl ReplicationService {
     ///
     /// # Tasks spawned
     ///
-    /// This function will spawn four long-lived tasks:
+    /// This function will spawn five long-lived tasks:
     /// 1. One to publish the [Digest].
     /// 2. One to receive the [Digest] of other Replica.
     /// 3. One to receive alignment queries of other Replica.
-    /// 4. One to wait on the provided [Receiver] in order to stop the Replication Service,
+    /// 4. One to receive, on the admin space of the Storage, the requests to force an alignment.
+    /// 5. One to wait on the provided [Receiver] in order to stop the Replication Service,
     ///    attempting to abort all the tasks that were spawned, once a Stop message has been
     ///    received.
+    ///
+    /// The replication status of the Storage is kept up to date in the provided
+    /// [ReplicationStatusSnapshot], from which the admin space reads it.
     pub async fn spawn_start(
         zenoh_session: Arc<Session>,
         storage_service: Arc<StorageService>,
         storage_key_expr: OwnedKeyExpr,
+        admin_key: String,
         replication_log: Arc<RwLock<LogLatest>>,
         latest_updates: Arc<RwLock<LatestUpdates>>,
+        status_snapshot: ReplicationStatusSnapshot,
         mut rx: Receiver<StorageMessage>,
     ) {
         let replication = Replication {
@@ -61,6 +75,8 @@ impl ReplicationService {
             storage_key_expr,
             latest_updates,
             storage_service,
+            status: Arc::new(RwLock::new(ReplicationStatus::default())),
+            status_snapshot,
         };
 
         if replication
@@ -72,18 +88,39 @@ impl ReplicationService {
         {
             replication.initial_alignment().await;
         }
+        replication.refresh_status_snapshot().await;
 
         tokio::task::spawn(async move {
             let replication_service = Self {
                 digest_publisher_handle: replication.spawn_digest_publisher(),
                 digest_subscriber_handle: replication.spawn_digest_subscriber(),
                 aligner_queryable_handle: replication.spawn_aligner_queryable(),
+                control_subscriber_handle: replication.spawn_control_subscriber(admin_key),
             };
 
-            while let Ok(storage_message) = rx.recv().await {
-                if matches!(storage_message, StorageMessage::Stop) {
-                    replication_service.stop();
-                    return;
+            loop {
+                let storage_message = match rx.recv().await {
+                    Ok(storage_message) => storage_message,
+                    // NOTE: The skipped messages were status requests, answered by the Storage
+                    //       Service.
+                    Err(RecvError::Lagged(nb_skipped)) => {
+                        tracing::warn!(
+                            "Replication Service skipped {nb_skipped} storage message(s)"
+                        );
+                        continue;
+                    }
+                    Err(RecvError::Closed) => {
+                        replication_service.stop();
+                        return;
+                    }
+                };
+
+                match storage_message {
+                    StorageMessage::Stop => {
+                        replication_service.stop();
+                        return;
+                    }
+                    StorageMessage::GetStatus(_) => {}
                 }
             }
         });
@@ -94,5 +131,6 @@ impl ReplicationService {
         self.digest_publisher_handle.abort();
         self.digest_subscriber_handle.abort();
         self.aligner_queryable_handle.abort();
+        self.control_subscriber_handle.abort();
     }
 }
@@ -0,0 +1,215 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use serde_json::{json, Map, Value};
+use zenoh::Result as ZResult;
+
+use super::{
+    configuration::Configuration,
+    digest::{Digest, DigestDiff, Fingerprint},
+};
+
+/// The state of the alignment with a Replica, as perceived through the last [Digest] it published.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AlignmentState {
+    /// The last [Digest] published by the Replica is identical to the local one.
+    Aligned,
+    /// The last [Digest] published by the Replica differs from the local one: an alignment was
+    /// requested.
+    Misaligned,
+}
+
+impl AlignmentState {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AlignmentState::Aligned => "aligned",
+            AlignmentState::Misaligned => "misaligned",
+        }
+    }
+}
+
+/// The last replication status of a Storage, in JSON, `None` until it was first computed.
+///
+/// The Replication Service refreshes it and the admin space reads it: a query on the admin space
+/// never waits on the Replication Service.
+pub(crate) type ReplicationStatusSnapshot = Arc<RwLock<Option<Value>>>;
+
+/// What this Storage knows about a Replica.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ReplicaStatus {
+    pub(crate) state: AlignmentState,
+    /// When the first [Digest] of the Replica was received.
+    pub(crate) first_digest: SystemTime,
+    /// When the last [Digest] of the Replica was received.
+    pub(crate) last_digest: SystemTime,
+    /// When a [Digest] of the Replica was last found identical to the local one.
+    pub(crate) last_aligned: Option<SystemTime>,
+    /// The differences found with the last [Digest] of the Replica, if any.
+    pub(crate) last_diff: Option<DigestDiff>,
+}
+
+impl ReplicaStatus {
+    /// Returns for how long this Storage has not been aligned with the Replica.
+    ///
+    /// If it never was, the lag is counted from the reception of the first [Digest] of the Replica.
+    pub(crate) fn lag(&self, now: SystemTime) -> Duration {
+        match self.state {
+            AlignmentState::Aligned => Duration::ZERO,
+            AlignmentState::Misaligned => now
+                .duration_since(self.last_aligned.unwrap_or(self.first_digest))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// The `ReplicationStatus` keeps track of the alignment of this Storage with the other Replicas.
+///
+/// It is exposed, along with the local [Digest], in the admin space of the Storage.
+#[derive(Debug, Default)]
+pub(crate) struct ReplicationStatus {
+    pub(crate) replicas: HashMap<String, ReplicaStatus>,
+    pub(crate) last_forced_alignment: Option<SystemTime>,
+}
+
+impl ReplicationStatus {
+    /// Records the reception of a [Digest] from the Replica and the result of its comparison with
+    /// the local one.
+    pub(crate) fn record_digest(
+        &mut self,
+        replica_zid: &str,
+        digest_diff: Option<&DigestDiff>,
+        now: SystemTime,
+    ) {
+        let replica = self
+            .replicas
+            .entry(replica_zid.to_string())
+            .or_insert_with(|| ReplicaStatus {
+                state: AlignmentState::Misaligned,
+                first_digest: now,
+                last_digest: now,
+                last_aligned: None,
+                last_diff: None,
+            });
+
+        replica.last_digest = now;
+        replica.last_diff = digest_diff.cloned();
+        match digest_diff {
+            Some(_) => replica.state = AlignmentState::Misaligned,
+            None => {
+                replica.state = AlignmentState::Aligned;
+                replica.last_aligned = Some(now);
+            }
+        }
+    }
+
+    /// Records that a full alignment with all the Replicas was requested.
+    pub(crate) fn record_forced_alignment(&mut self, now: SystemTime) {
+        self.last_forced_alignment = Some(now);
+    }
+
+    /// Returns the JSON representation of the replication state of the Storage: its configuration,
+    /// the bounds of its eras, its [Digest] and the alignment state with each known Replica.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the last elapsed interval could not be computed, see
+    /// [Configuration::last_elapsed_interval].
+    pub(crate) fn to_json(
+        &self,
+        configuration: &Configuration,
+        digest: &Digest,
+        now: SystemTime,
+    ) -> ZResult<Value> {
+        let hot_upper_bound = configuration.last_elapsed_interval()?;
+        let hot_lower_bound = configuration.hot_era_lower_bound(hot_upper_bound);
+        let warm_lower_bound = configuration.warm_era_lower_bound(hot_upper_bound);
+
+        let replicas = self
+            .replicas
+            .iter()
+            .map(|(zid, replica)| {
+                let status = json!({
+                    "state": replica.state.as_str(),
+                    "lag_ms": replica.lag(now).as_millis() as u64,
+                    "last_digest": format_time(replica.last_digest),
+                    "last_aligned": replica.last_aligned.map(format_time),
+                    "differences": replica.last_diff.as_ref().map(|diff| json!({
+                        "cold": diff.cold_eras_differ,
+                        "warm": diff.warm_eras_differences.len(),
+                        "hot": diff.hot_eras_differences.values().map(|s| s.len()).sum::<usize>(),
+                    })),
+                });
+                (zid.clone(), status)
+            })
+            .collect::<Map<_, _>>();
+
+        Ok(json!({
+            "configuration": {
+                "fingerprint": format_fingerprint(configuration.fingerprint()),
+                "interval_ms": configuration.interval.as_millis() as u64,
+                "sub_intervals": configuration.sub_intervals,
+                "hot": configuration.hot,
+                "warm": configuration.warm,
+                "propagation_delay_ms": configuration.propagation_delay.as_millis() as u64,
+                "history": format!("{:?}", configuration.history()),
+            },
+            "eras": {
+                "hot": { "first": *hot_lower_bound, "last": *hot_upper_bound },
+                "warm": { "first": *warm_lower_bound, "last": hot_lower_bound.saturating_sub(1) },
+                "cold": { "last": warm_lower_bound.saturating_sub(1) },
+            },
+            "digest": {
+                "cold": format_fingerprint(digest.cold_era_fingerprint),
+                "warm": digest
+                    .warm_era_fingerprints
+                    .iter()
+                    .map(|(idx, fp)| (idx.to_string(), format_fingerprint(*fp).into()))
+                    .collect::<Map<_, _>>(),
+                "hot": digest
+                    .hot_era_fingerprints
+                    .iter()
+                    .map(|(idx, sub_intervals)| {
+                        let sub_intervals = sub_intervals
+                            .iter()
+                            .map(|(sub_idx, fp)| {
+                                (sub_idx.to_string(), format_fingerprint(*fp).into())
+                            })
+                            .collect::<Map<_, _>>();
+                        (idx.to_string(), sub_intervals.into())
+                    })
+                    .collect::<Map<_, _>>(),
+            },
+            "replicas": replicas,
+            "last_forced_alignment": self.last_forced_alignment.map(format_time),
+        }))
+    }
+}
+
+fn format_fingerprint(fingerprint: Fingerprint) -> String {
+    format!("{:016x}", *fingerprint)
+}
+
+fn format_time(time: SystemTime) -> String {
+    humantime::format_rfc3339_millis(time).to_string()
+}
+
+#[cfg(test)]
+#[path = "tests/status.test.rs"]
+mod tests;
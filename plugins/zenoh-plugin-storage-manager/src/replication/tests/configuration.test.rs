@@ -30,6 +30,7 @@ fn test_lower_bounds() {
             warm: 5,
             propagation_delay: Duration::from_millis(250),
         },
+        History::Latest,
     );
 
     assert_eq!(
@@ -56,23 +57,50 @@ fn test_difference() {
         OwnedKeyExpr::from_str("replication/test/**").unwrap(),
         None,
         identical_replica_config.clone(),
+        History::Latest,
     );
 
     let configuration_b = Configuration::new(
         OwnedKeyExpr::from_str("replication/test/a/*").unwrap(),
         None,
         identical_replica_config.clone(),
+        History::Latest,
     );
 
     assert_ne!(configuration_a.fingerprint, configuration_b.fingerprint);
 
     let configuration_c = Configuration::new(
-        configuration_a.storage_key_expr,
+        configuration_a.storage_key_expr.clone(),
         Some(OwnedKeyExpr::from_str("replication/test").unwrap()),
         identical_replica_config,
+        History::Latest,
     );
 
     assert_ne!(configuration_a.fingerprint, configuration_c.fingerprint);
+
+    let configuration_d = Configuration::new(
+        configuration_a.storage_key_expr.clone(),
+        None,
+        configuration_a.replica_config.clone(),
+        History::All,
+    );
+
+    assert_ne!(configuration_a.fingerprint, configuration_d.fingerprint);
+
+    // The Replicas predating the versioning of the format of the replication messages hashed the
+    // same fields, without the version.
+    let mut hasher = xxhash_rust::xxh3::Xxh3::default();
+    hasher.update(configuration_a.storage_key_expr.as_bytes());
+    hasher.update(&configuration_a.interval.as_millis().to_le_bytes());
+    hasher.update(&configuration_a.sub_intervals.to_le_bytes());
+    hasher.update(&configuration_a.hot.to_le_bytes());
+    hasher.update(&configuration_a.warm.to_le_bytes());
+    hasher.update(&configuration_a.propagation_delay.as_millis().to_le_bytes());
+
+    assert_ne!(
+        configuration_a.fingerprint,
+        Fingerprint::from(hasher.digest())
+    );
 }
 
 #[test]
@@ -87,6 +115,7 @@ fn test_get_classification() {
             warm: 5,
             propagation_delay: Duration::from_millis(250),
         },
+        History::Latest,
     );
 
     let hlc = HLC::default();
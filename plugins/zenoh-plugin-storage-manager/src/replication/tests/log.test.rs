@@ -16,7 +16,7 @@ use std::{collections::HashMap, str::FromStr, time::Duration};
 
 use uhlc::{Timestamp, HLC, NTP64};
 use zenoh::key_expr::OwnedKeyExpr;
-use zenoh_backend_traits::config::ReplicaConfig;
+use zenoh_backend_traits::{config::ReplicaConfig, History};
 
 use super::{Event, EventMetadata, LogLatest};
 use crate::replication::{
@@ -55,6 +55,7 @@ fn test_insert() {
             warm: 5,
             propagation_delay: Duration::from_millis(250),
         },
+        History::Latest,
     );
 
     let event_10_0_0 = Event::new(
@@ -106,6 +107,157 @@ fn test_insert() {
     assert_eq!(event_10_0_0_new.fingerprint(), interval_10.fingerprint());
 }
 
+#[test]
+fn test_insert_versions() {
+    let hlc = HLC::default();
+    let mut log = LogLatest::new(
+        OwnedKeyExpr::from_str("replication/test/**").unwrap(),
+        None,
+        ReplicaConfig {
+            interval: Duration::from_secs(10),
+            sub_intervals: 2,
+            hot: 1,
+            warm: 5,
+            propagation_delay: Duration::from_millis(250),
+        },
+        History::All,
+    );
+
+    let key_expr = Some(OwnedKeyExpr::from_str("a").unwrap());
+    let version_10_0_0 = Event::new_version(
+        key_expr.clone(),
+        generate_timestamp_matching(&log, &hlc, 10, 0, 0),
+        &Action::Put,
+    );
+    let version_10_0_1 = Event::new_version(
+        key_expr.clone(),
+        generate_timestamp_matching(&log, &hlc, 10, 0, 1),
+        &Action::Delete,
+    );
+    let version_11_1_0 = Event::new_version(
+        key_expr,
+        generate_timestamp_matching(&log, &hlc, 11, 1, 0),
+        &Action::Put,
+    );
+    assert_ne!(version_10_0_0.log_key(), version_10_0_1.log_key());
+
+    // Versions never replace each other, regardless of the order in which they are received.
+    for version in [&version_11_1_0, &version_10_0_0, &version_10_0_1] {
+        assert_eq!(
+            EventInsertion::New(version.clone()),
+            log.insert_event(version.clone())
+        );
+    }
+
+    // Inserting the same version a second time -> NotInsertedAsOlder.
+    assert_eq!(
+        EventInsertion::NotInsertedAsOlder,
+        log.insert_event(version_10_0_0.clone())
+    );
+
+    let expected_interval = Interval::from([(
+        SubIntervalIdx(0),
+        SubInterval::from([version_10_0_0.clone(), version_10_0_1.clone()]),
+    )]);
+    let interval_10 = log.intervals.get(&IntervalIdx(10)).unwrap();
+    assert_eq!(&expected_interval, interval_10);
+    assert_eq!(
+        version_10_0_0.fingerprint() ^ version_10_0_1.fingerprint(),
+        interval_10.fingerprint()
+    );
+    assert_eq!(Some(&version_10_0_1), log.lookup(&(&version_10_0_1).into()));
+}
+
+#[test]
+fn test_prune_cold_versions() {
+    let hlc = HLC::default();
+    let mut log = LogLatest::new(
+        OwnedKeyExpr::from_str("replication/test/**").unwrap(),
+        None,
+        ReplicaConfig {
+            interval: Duration::from_secs(10),
+            sub_intervals: 2,
+            hot: 1,
+            warm: 5,
+            propagation_delay: Duration::from_millis(250),
+        },
+        History::All,
+    );
+    let last_elapsed_interval = *log.configuration.last_elapsed_interval().unwrap() as u32;
+
+    // Versions of "a" in the cold era only, of "b" in the cold and hot eras and of "c" in the
+    // cold era only.
+    let key_a = Some(OwnedKeyExpr::from_str("a").unwrap());
+    let key_b = Some(OwnedKeyExpr::from_str("b").unwrap());
+    let key_c = Some(OwnedKeyExpr::from_str("c").unwrap());
+    let version_a_10_0_0 = Event::new_version(
+        key_a.clone(),
+        generate_timestamp_matching(&log, &hlc, 10, 0, 0),
+        &Action::Put,
+    );
+    let version_a_10_0_1 = Event::new_version(
+        key_a.clone(),
+        generate_timestamp_matching(&log, &hlc, 10, 0, 1),
+        &Action::Put,
+    );
+    let version_a_11_1_0 = Event::new_version(
+        key_a,
+        generate_timestamp_matching(&log, &hlc, 11, 1, 0),
+        &Action::Delete,
+    );
+    let version_b_10_0_0 = Event::new_version(
+        key_b.clone(),
+        generate_timestamp_matching(&log, &hlc, 10, 0, 0),
+        &Action::Put,
+    );
+    let version_b_hot = Event::new_version(
+        key_b,
+        generate_timestamp_matching(&log, &hlc, last_elapsed_interval, 0, 0),
+        &Action::Put,
+    );
+    let version_c_10_1_0 = Event::new_version(
+        key_c,
+        generate_timestamp_matching(&log, &hlc, 10, 1, 0),
+        &Action::Put,
+    );
+    log.update(
+        [
+            &version_a_10_0_0,
+            &version_a_10_0_1,
+            &version_a_11_1_0,
+            &version_b_10_0_0,
+            &version_b_hot,
+            &version_c_10_1_0,
+        ]
+        .into_iter()
+        .cloned(),
+    );
+
+    // Only the latest version of each key expression is kept in the cold era.
+    assert_eq!(3, log.prune_cold_versions().unwrap());
+    for pruned in [&version_a_10_0_0, &version_a_10_0_1, &version_b_10_0_0] {
+        assert_eq!(None, log.lookup(&pruned.into()));
+    }
+    for kept in [&version_a_11_1_0, &version_b_hot, &version_c_10_1_0] {
+        assert_eq!(Some(kept), log.lookup(&kept.into()));
+    }
+    assert_eq!(
+        version_c_10_1_0.fingerprint(),
+        log.intervals.get(&IntervalIdx(10)).unwrap().fingerprint()
+    );
+    assert_eq!(0, log.prune_cold_versions().unwrap());
+
+    // The Replication Log of a Storage keeping only the latest value is never pruned.
+    let mut log = LogLatest::new(
+        OwnedKeyExpr::from_str("replication/test/**").unwrap(),
+        None,
+        ReplicaConfig::default(),
+        History::Latest,
+    );
+    log.update([version_c_10_1_0].into_iter());
+    assert_eq!(0, log.prune_cold_versions().unwrap());
+}
+
 #[test]
 fn test_digest() {
     let hlc = HLC::default();
@@ -119,6 +271,7 @@ fn test_digest() {
             warm: 5,
             propagation_delay: Duration::from_millis(250),
         },
+        History::Latest,
     );
 
     let event_warm_5_1_0 = Event::new(
@@ -310,6 +463,7 @@ fn test_event() {
             timestamp: ts,
             timestamp_last_non_wildcard_update: Some(ts),
             action: Action::Put,
+            versioned: false,
         },
         fingerprint: Event::compute_fingerprint(&Some(ke.clone()), &ts),
     };
@@ -327,6 +481,7 @@ fn test_event() {
             timestamp: wildcard_ts,
             timestamp_last_non_wildcard_update: None,
             action: Action::WildcardPut(wildcard_ke.clone()),
+            versioned: false,
         },
         fingerprint: Event::compute_fingerprint(&Some(wildcard_ke.clone()), &wildcard_ts),
     };
@@ -353,6 +508,7 @@ fn test_convert_metadata_to_event() {
         timestamp: wildcard_timestamp,
         timestamp_last_non_wildcard_update: None,
         action: Action::WildcardPut(wildcard_ke.clone()),
+        versioned: false,
     };
 
     let expected_wildcard_event = Event::new(
@@ -373,6 +529,7 @@ fn test_convert_metadata_to_event() {
         timestamp: put_timestamp,
         timestamp_last_non_wildcard_update: Some(put_timestamp),
         action: Action::Put,
+        versioned: false,
     };
 
     let expected_put_event = Event::new(Some(put_ke.clone()), put_timestamp, &Action::Put);
@@ -389,6 +546,7 @@ fn test_convert_metadata_to_event() {
         timestamp: overridden_timestamp,
         timestamp_last_non_wildcard_update: Some(put_timestamp),
         action: Action::Put,
+        versioned: false,
     };
 
     let expected_put_event = Event {
@@ -0,0 +1,132 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+
+use zenoh::key_expr::OwnedKeyExpr;
+use zenoh_backend_traits::{config::ReplicaConfig, History};
+
+use super::{AlignmentState, ReplicationStatus};
+use crate::replication::{
+    classification::{IntervalIdx, SubIntervalIdx},
+    configuration::Configuration,
+    digest::{Digest, DigestDiff, Fingerprint},
+};
+
+fn digest_diff() -> DigestDiff {
+    DigestDiff {
+        cold_eras_differ: true,
+        warm_eras_differences: HashSet::from([IntervalIdx(1), IntervalIdx(2)]),
+        hot_eras_differences: HashMap::from([(
+            IntervalIdx(3),
+            HashSet::from([SubIntervalIdx(1), SubIntervalIdx(2), SubIntervalIdx(4)]),
+        )]),
+    }
+}
+
+#[test]
+fn test_alignment_state_and_lag() {
+    let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+    let at = |secs| start + Duration::from_secs(secs);
+    let mut status = ReplicationStatus::default();
+
+    // Never aligned: the lag is counted from the first Digest received.
+    status.record_digest("replica", Some(&digest_diff()), start);
+    status.record_digest("replica", Some(&digest_diff()), at(5));
+    let replica = &status.replicas["replica"];
+    assert_eq!(replica.state, AlignmentState::Misaligned);
+    assert_eq!(replica.last_digest, at(5));
+    assert_eq!(replica.last_aligned, None);
+    assert_eq!(replica.lag(at(10)), Duration::from_secs(10));
+
+    status.record_digest("replica", None, at(20));
+    let replica = &status.replicas["replica"];
+    assert_eq!(replica.state, AlignmentState::Aligned);
+    assert_eq!(replica.last_diff, None);
+    assert_eq!(replica.lag(at(30)), Duration::ZERO);
+
+    // Misaligned again: the lag is counted from the last time it was aligned.
+    status.record_digest("replica", Some(&digest_diff()), at(25));
+    let replica = &status.replicas["replica"];
+    assert_eq!(replica.state, AlignmentState::Misaligned);
+    assert_eq!(replica.last_aligned, Some(at(20)));
+    assert_eq!(replica.lag(at(30)), Duration::from_secs(10));
+}
+
+#[test]
+fn test_to_json() {
+    let configuration = Configuration::new(
+        OwnedKeyExpr::from_str("replication/test/**").unwrap(),
+        None,
+        ReplicaConfig {
+            interval: Duration::from_secs(10),
+            sub_intervals: 5,
+            hot: 2,
+            warm: 5,
+            propagation_delay: Duration::from_millis(250),
+        },
+        History::Latest,
+    );
+    let digest = Digest {
+        configuration_fingerprint: configuration.fingerprint(),
+        cold_era_fingerprint: Fingerprint::from(0xff),
+        warm_era_fingerprints: HashMap::from([(IntervalIdx(7), Fingerprint::from(7))]),
+        hot_era_fingerprints: HashMap::from([(
+            IntervalIdx(9),
+            HashMap::from([(SubIntervalIdx(2), Fingerprint::from(2))]),
+        )]),
+    };
+
+    let now = SystemTime::now();
+    let mut status = ReplicationStatus::default();
+    status.record_digest(
+        "replica",
+        Some(&digest_diff()),
+        now - Duration::from_secs(3),
+    );
+    status.record_forced_alignment(now);
+
+    let json = status.to_json(&configuration, &digest, now).unwrap();
+
+    assert_eq!(json["configuration"]["interval_ms"], 10_000);
+    assert_eq!(json["configuration"]["hot"], 2);
+    assert_eq!(
+        json["configuration"]["fingerprint"],
+        format!("{:016x}", *configuration.fingerprint())
+    );
+
+    let hot_last = json["eras"]["hot"]["last"].as_u64().unwrap();
+    assert_eq!(json["eras"]["hot"]["first"], hot_last - 1);
+    assert_eq!(json["eras"]["warm"]["last"], hot_last - 2);
+    assert_eq!(json["eras"]["warm"]["first"], hot_last - 6);
+    assert_eq!(json["eras"]["cold"]["last"], hot_last - 7);
+
+    assert_eq!(json["digest"]["cold"], "00000000000000ff");
+    assert_eq!(json["digest"]["warm"]["7"], "0000000000000007");
+    assert_eq!(json["digest"]["hot"]["9"]["2"], "0000000000000002");
+
+    let replica = &json["replicas"]["replica"];
+    assert_eq!(replica["state"], "misaligned");
+    assert_eq!(replica["lag_ms"], 3_000);
+    assert_eq!(replica["last_aligned"], serde_json::Value::Null);
+    assert_eq!(replica["differences"]["cold"], true);
+    assert_eq!(replica["differences"]["warm"], 2);
+    assert_eq!(replica["differences"]["hot"], 3);
+
+    assert!(json["last_forced_alignment"].is_string());
+}
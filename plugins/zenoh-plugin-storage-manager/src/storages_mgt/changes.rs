@@ -0,0 +1,176 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The change feed of a Storage publishes every mutation applied to it, whatever its origin.
+//! It is only enabled for the Storages configured with `change_feed: true`.
+//!
+//! A change on the key `<key>` is published on `<admin_key>/changes/<key>`, once the Storage
+//! applied it. Its payload is a JSON object:
+//!
+//! ```text
+//! {
+//!   "seq": 42,                                  // increases with each change of the Storage
+//!   "key": "demo/a",
+//!   "action": "wildcard_put",                   // "put", "delete", "wildcard_put", "wildcard_delete" or "purge"
+//!   "wildcard": "demo/**",                      // only for "wildcard_put" and "wildcard_delete"
+//!   "timestamp": "7386690599959157260/33",
+//!   "origin": "alignment"                       // "live", "alignment", "retention" or "snapshot"
+//! }
+//! ```
+//!
+//! A Wildcard Update is published once for each key it was applied to.
+//!
+//! A "purge" removed, to enforce the retention rules of a Storage keeping the history of its keys,
+//! the versions of the key stamped up to its `timestamp` (included). Its origin is "retention".
+//!
+//! The changes are published with [CongestionControl::Drop]: a slow subscriber cannot slow down
+//! the Storage, but may miss changes, which it can detect thanks to the gaps in the `seq`.
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use serde_json::{json, Value};
+use zenoh::{key_expr::OwnedKeyExpr, qos::CongestionControl, session::Session, time::Timestamp};
+
+use crate::replication::Action;
+
+/// The key, relative to the admin key of a Storage, under which its changes are published.
+pub(crate) const CHANGES_KEY: &str = "changes";
+
+/// Where a change applied to a Storage comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ChangeOrigin {
+    /// A publication received by the Storage.
+    Live,
+    /// An alignment with another Replica.
+    Alignment,
+    /// The enforcement of the retention rules of the Storage.
+    Retention,
+    /// The import of a snapshot.
+    Snapshot,
+}
+
+impl ChangeOrigin {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ChangeOrigin::Live => "live",
+            ChangeOrigin::Alignment => "alignment",
+            ChangeOrigin::Retention => "retention",
+            ChangeOrigin::Snapshot => "snapshot",
+        }
+    }
+}
+
+/// Returns the JSON representation of a change, see the [module documentation](self).
+pub(crate) fn change_to_json(
+    seq: u64,
+    key: &OwnedKeyExpr,
+    action: &Action,
+    timestamp: &Timestamp,
+    origin: ChangeOrigin,
+) -> Value {
+    let (action, wildcard) = match action {
+        Action::Put => ("put", None),
+        Action::Delete => ("delete", None),
+        Action::WildcardPut(wildcard) => ("wildcard_put", Some(wildcard)),
+        Action::WildcardDelete(wildcard) => ("wildcard_delete", Some(wildcard)),
+    };
+
+    let mut change = json!({
+        "seq": seq,
+        "key": key.as_str(),
+        "action": action,
+        "timestamp": timestamp.to_string(),
+        "origin": origin.as_str(),
+    });
+    if let Some(wildcard) = wildcard {
+        change["wildcard"] = wildcard.as_str().into();
+    }
+
+    change
+}
+
+/// Returns the JSON representation of a purge, see the [module documentation](self).
+pub(crate) fn purge_to_json(seq: u64, key: &OwnedKeyExpr, timestamp: &Timestamp) -> Value {
+    json!({
+        "seq": seq,
+        "key": key.as_str(),
+        "action": "purge",
+        "timestamp": timestamp.to_string(),
+        "origin": ChangeOrigin::Retention.as_str(),
+    })
+}
+
+/// Publishes the changes of a Storage, see the [module documentation](self).
+pub(crate) struct ChangeFeed {
+    session: Arc<Session>,
+    key_prefix: String,
+    seq: AtomicU64,
+}
+
+impl ChangeFeed {
+    pub(crate) fn new(session: Arc<Session>, admin_key: &str) -> Self {
+        Self {
+            session,
+            key_prefix: format!("{admin_key}/{CHANGES_KEY}"),
+            seq: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the sequence number of the next change.
+    ///
+    /// It must be called while holding the lock on the Storage that applied the change, such that
+    /// the sequence numbers follow the order in which the changes were applied.
+    pub(crate) fn next_seq(&self) -> u64 {
+        self.seq.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Publishes the change `seq` applied to the provided (complete) key.
+    pub(crate) async fn publish(
+        &self,
+        seq: u64,
+        key: &OwnedKeyExpr,
+        action: &Action,
+        timestamp: Timestamp,
+        origin: ChangeOrigin,
+    ) {
+        let change = change_to_json(seq, key, action, &timestamp, origin);
+        self.put(key, change, timestamp).await;
+    }
+
+    /// Publishes the purge `seq` of the versions of the provided (complete) key stamped up to
+    /// `timestamp` (included).
+    pub(crate) async fn publish_purge(&self, seq: u64, key: &OwnedKeyExpr, timestamp: Timestamp) {
+        let change = purge_to_json(seq, key, &timestamp);
+        self.put(key, change, timestamp).await;
+    }
+
+    async fn put(&self, key: &OwnedKeyExpr, change: Value, timestamp: Timestamp) {
+        if let Err(e) = self
+            .session
+            .put(format!("{}/{key}", self.key_prefix), change.to_string())
+            .timestamp(timestamp)
+            .congestion_control(CongestionControl::Drop)
+            .await
+        {
+            tracing::warn!("Failed to publish change < {change} >: {e:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/changes.test.rs"]
+mod tests;
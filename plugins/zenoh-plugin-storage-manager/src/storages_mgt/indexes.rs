@@ -0,0 +1,344 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The secondary indexes of a Storage associate values derived from the Samples it receives (their
+//! encoding or their attachment) to their keys.
+//!
+//! Queries select the keys through the indexes with the `_filter` selector parameter:
+//!
+//! ```text
+//! demo/**?_filter=format:application/json,tags:X
+//! ```
+//!
+//! A filter is a comma-separated list of `<index>:<value>` terms. A key is selected if, for each
+//! term, the index associates the value to the key.
+//!
+//! The indexes are kept in memory and only reflect the most recent value of each key. As backends
+//! do not store the attachments, the values derived from them are only known for the Samples
+//! received by the Storage since it started. The values derived from the encoding are restored
+//! from the content of the Storage when it starts.
+//!
+//! The keys whose most recent value has an unknown attachment (restored when the Storage started,
+//! obtained through an alignment or imported from a snapshot) are tracked: a filter on an index
+//! derived from the attachments can't be evaluated for them, the queries using such a filter on
+//! a key expression intersecting them are thus rejected.
+
+use std::collections::{HashMap, HashSet};
+
+use zenoh::{
+    bytes::{Encoding, ZBytes},
+    internal::bail,
+    key_expr::OwnedKeyExpr,
+    query::Parameters,
+    time::Timestamp,
+    Result as ZResult,
+};
+use zenoh_backend_traits::config::{IndexConfig, IndexedField};
+
+/// Name of the selector parameter used to filter the keys with the secondary indexes.
+pub(crate) const FILTER_KEY: &str = "_filter";
+
+/// A parsed `_filter` expression, validated against the indexes of a Storage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Filter {
+    /// The position of the index in the [SecondaryIndexes] and the value it must associate to the
+    /// selected keys.
+    terms: Vec<(usize, String)>,
+    /// Whether one of the terms refers to an index derived from the attachments.
+    uses_attachment: bool,
+}
+
+impl Filter {
+    /// Returns `true` if one of the terms of the filter refers to an index derived from the
+    /// attachments, which can't be evaluated for the keys whose attachment is unknown.
+    pub(crate) fn uses_attachment(&self) -> bool {
+        self.uses_attachment
+    }
+}
+
+/// The attachment of a value to index.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum IndexedAttachment<'a> {
+    /// The value was received in a Sample, with this attachment if it had one.
+    Known(Option<&'a ZBytes>),
+    /// The value was not received in a Sample: it was restored from the Storage, obtained through
+    /// an alignment or imported from a snapshot.
+    Unknown,
+}
+
+/// The values derived from a Sample by a single index, and the keys each value is associated to.
+#[derive(Debug, Default)]
+struct SecondaryIndex {
+    by_key: HashMap<Option<OwnedKeyExpr>, Vec<String>>,
+    by_value: HashMap<String, HashSet<Option<OwnedKeyExpr>>>,
+}
+
+impl SecondaryIndex {
+    fn insert(&mut self, stripped_key: &Option<OwnedKeyExpr>, values: Vec<String>) {
+        self.remove(stripped_key);
+        for value in &values {
+            self.by_value
+                .entry(value.clone())
+                .or_default()
+                .insert(stripped_key.clone());
+        }
+        if !values.is_empty() {
+            self.by_key.insert(stripped_key.clone(), values);
+        }
+    }
+
+    fn remove(&mut self, stripped_key: &Option<OwnedKeyExpr>) {
+        let Some(values) = self.by_key.remove(stripped_key) else {
+            return;
+        };
+        for value in values {
+            if let Some(keys) = self.by_value.get_mut(&value) {
+                keys.remove(stripped_key);
+                if keys.is_empty() {
+                    self.by_value.remove(&value);
+                }
+            }
+        }
+    }
+
+    fn contains(&self, stripped_key: &Option<OwnedKeyExpr>, value: &str) -> bool {
+        self.by_key
+            .get(stripped_key)
+            .is_some_and(|values| values.iter().any(|v| v == value))
+    }
+}
+
+/// The secondary indexes of a Storage, see the [module documentation](self).
+#[derive(Debug, Default)]
+pub(crate) struct SecondaryIndexes {
+    configs: Vec<IndexConfig>,
+    indexes: Vec<SecondaryIndex>,
+    /// The [Timestamp] of the most recent Sample indexed or deleted for each key.
+    ///
+    /// The [Timestamp] of a delete is kept as a tombstone, such that a value older than the delete
+    /// received afterwards does not index the key again.
+    timestamps: HashMap<Option<OwnedKeyExpr>, Timestamp>,
+    /// The keys whose most recent value has an unknown attachment, if some indexes are derived
+    /// from the attachments.
+    unknown_attachments: HashSet<Option<OwnedKeyExpr>>,
+}
+
+impl SecondaryIndexes {
+    pub(crate) fn new(configs: &[IndexConfig]) -> Self {
+        Self {
+            configs: configs.to_vec(),
+            indexes: configs.iter().map(|_| SecondaryIndex::default()).collect(),
+            timestamps: HashMap::default(),
+            unknown_attachments: HashSet::default(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.configs.is_empty()
+    }
+
+    /// Returns `true` if at least one index is derived from the attachment of the Samples.
+    fn indexes_attachment(&self) -> bool {
+        self.configs
+            .iter()
+            .any(|config| matches!(config.field, IndexedField::Attachment { .. }))
+    }
+
+    /// Indexes a value of the provided key, replacing the values previously indexed for it unless
+    /// they are more recent.
+    ///
+    /// If the attachment of the value is unknown, no value is derived from it and the key is
+    /// tracked as such until a more recent value is indexed.
+    pub(crate) fn insert(
+        &mut self,
+        stripped_key: &Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+        encoding: &Encoding,
+        attachment: IndexedAttachment,
+    ) {
+        if self.is_empty() || !self.update_timestamp(stripped_key, timestamp) {
+            return;
+        }
+
+        let known_attachment = match attachment {
+            IndexedAttachment::Known(attachment) => {
+                self.unknown_attachments.remove(stripped_key);
+                Some(attachment)
+            }
+            IndexedAttachment::Unknown => {
+                if self.indexes_attachment() {
+                    self.unknown_attachments.insert(stripped_key.clone());
+                }
+                None
+            }
+        };
+        for (config, index) in self.configs.iter().zip(self.indexes.iter_mut()) {
+            let values = match (&config.field, known_attachment) {
+                (IndexedField::Attachment { .. }, None) => Vec::new(),
+                (field, attachment) => derive_values(field, encoding, attachment.flatten()),
+            };
+            index.insert(stripped_key, values);
+        }
+    }
+
+    /// Returns the keys whose most recent value has an unknown attachment.
+    pub(crate) fn unknown_attachments(&self) -> impl Iterator<Item = &Option<OwnedKeyExpr>> {
+        self.unknown_attachments.iter()
+    }
+
+    /// Removes the provided key from the indexes, unless the values indexed for it are more recent.
+    pub(crate) fn remove(&mut self, stripped_key: &Option<OwnedKeyExpr>, timestamp: Timestamp) {
+        if self.is_empty() || !self.update_timestamp(stripped_key, timestamp) {
+            return;
+        }
+
+        self.unknown_attachments.remove(stripped_key);
+        for index in self.indexes.iter_mut() {
+            index.remove(stripped_key);
+        }
+    }
+
+    /// Records the provided [Timestamp] for the key, returning `false` if a more recent one was
+    /// already recorded.
+    fn update_timestamp(
+        &mut self,
+        stripped_key: &Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+    ) -> bool {
+        match self.timestamps.get_mut(stripped_key) {
+            Some(latest) if *latest > timestamp => false,
+            Some(latest) => {
+                *latest = timestamp;
+                true
+            }
+            None => {
+                self.timestamps.insert(stripped_key.clone(), timestamp);
+                true
+            }
+        }
+    }
+
+    /// Parses the `_filter` parameter, if present in the provided parameters.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the expression is malformed or if it refers to an
+    /// unknown index.
+    pub(crate) fn filter_from_parameters(
+        &self,
+        parameters: &Parameters,
+    ) -> ZResult<Option<Filter>> {
+        parameters
+            .get(FILTER_KEY)
+            .map(|expression| self.parse_filter(expression))
+            .transpose()
+    }
+
+    /// Parses a filter expression, see the [module documentation](self) for its syntax.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the expression is malformed or if it refers to an
+    /// unknown index.
+    pub(crate) fn parse_filter(&self, expression: &str) -> ZResult<Filter> {
+        let mut terms = Vec::new();
+        let mut uses_attachment = false;
+        for term in expression.split(',') {
+            let Some((name, value)) = term.split_once(':') else {
+                bail!(
+                    "Invalid term `{term}` in parameter `{FILTER_KEY}`: expected `<index>:<value>`"
+                );
+            };
+            let Some(position) = self.configs.iter().position(|config| config.name == name) else {
+                bail!(
+                    "Unknown index `{name}` in parameter `{FILTER_KEY}`. Known indexes: {:?}",
+                    self.configs
+                        .iter()
+                        .map(|config| config.name.as_str())
+                        .collect::<Vec<_>>()
+                );
+            };
+            uses_attachment |= matches!(
+                self.configs[position].field,
+                IndexedField::Attachment { .. }
+            );
+            terms.push((position, value.to_string()));
+        }
+
+        Ok(Filter {
+            terms,
+            uses_attachment,
+        })
+    }
+
+    /// Returns `true` if the provided key is selected by the filter.
+    pub(crate) fn matches(&self, filter: &Filter, stripped_key: &Option<OwnedKeyExpr>) -> bool {
+        filter
+            .terms
+            .iter()
+            .all(|(position, value)| self.indexes[*position].contains(stripped_key, value))
+    }
+
+    /// Returns the keys selected by the filter.
+    pub(crate) fn select(&self, filter: &Filter) -> HashSet<Option<OwnedKeyExpr>> {
+        let mut terms = filter.terms.iter();
+        let Some((position, value)) = terms.next() else {
+            return HashSet::default();
+        };
+
+        let mut selected = self.indexes[*position]
+            .by_value
+            .get(value)
+            .cloned()
+            .unwrap_or_default();
+        for (position, value) in terms {
+            selected.retain(|stripped_key| self.indexes[*position].contains(stripped_key, value));
+        }
+
+        selected
+    }
+}
+
+/// Returns the values the provided field of a Sample is indexed with.
+fn derive_values(
+    field: &IndexedField,
+    encoding: &Encoding,
+    attachment: Option<&ZBytes>,
+) -> Vec<String> {
+    match field {
+        IndexedField::Encoding => vec![encoding.to_string()],
+        IndexedField::Attachment { separator } => {
+            let Some(attachment) =
+                attachment.and_then(|attachment| attachment.try_to_string().ok())
+            else {
+                return Vec::new();
+            };
+            let mut values = match separator {
+                Some(separator) => attachment
+                    .split(separator.as_str())
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty())
+                    .collect::<Vec<_>>(),
+                None => vec![attachment.into_owned()],
+            };
+            values.sort();
+            values.dedup();
+            values
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/indexes.test.rs"]
+mod tests;
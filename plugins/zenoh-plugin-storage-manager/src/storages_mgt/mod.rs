@@ -15,12 +15,23 @@
 use std::{collections::HashMap, sync::Arc};
 
 use tokio::sync::{broadcast::Sender, Mutex, RwLock};
-use zenoh::{internal::bail, session::Session, Result as ZResult};
-use zenoh_backend_traits::{config::StorageConfig, History, VolumeInstance};
+use zenoh::{
+    internal::bail, key_expr::OwnedKeyExpr, session::Session, time::Timestamp, Result as ZResult,
+};
+use zenoh_backend_traits::{
+    config::StorageConfig, EntriesCursor, History, Storage, StorageQuery, VolumeInstance,
+};
 
-use crate::replication::{Action, Event, LogLatest, LogLatestKey, ReplicationService};
+use self::indexes::{IndexedAttachment, SecondaryIndexes};
+use crate::replication::{
+    Action, Event, LogLatest, LogLatestKey, ReplicationService, ReplicationStatusSnapshot,
+};
 
+mod changes;
+mod indexes;
+mod retention;
 pub(crate) mod service;
+mod snapshot;
 pub(crate) use service::StorageService;
 
 #[derive(Clone)]
@@ -29,6 +40,13 @@ pub enum StorageMessage {
     GetStatus(tokio::sync::mpsc::Sender<serde_json::Value>),
 }
 
+/// The handle through which the storage manager interacts with a running Storage.
+pub(crate) struct StorageHandle {
+    pub(crate) tx: Sender<StorageMessage>,
+    /// The last replication status of the Storage, if it is replicated.
+    pub(crate) replication_status: Option<ReplicationStatusSnapshot>,
+}
+
 pub(crate) type LatestUpdates = HashMap<LogLatestKey, Event>;
 
 #[derive(Clone)]
@@ -49,15 +67,76 @@ impl CacheLatest {
     }
 }
 
+/// The maximum number of entries retrieved at once when going through the content of a Storage.
+pub(crate) const ENTRIES_PAGE_SIZE: usize = 10_000;
+
+/// Goes through the content of the provided Storage, page by page, calling `f` on each page.
+///
+/// Contrary to `get_all_entries`, at most [ENTRIES_PAGE_SIZE] entries are kept in memory at any
+/// time, provided that the Storage implements `get_entries_page` natively. Otherwise, its whole
+/// content is returned as a single page by the default implementation of `get_entries_page`.
+///
+/// # Errors
+///
+/// This function will return an error if retrieving a page of the Storage failed.
+pub(crate) async fn for_each_entries_page(
+    storage: &dyn Storage,
+    mut f: impl FnMut(Vec<(Option<OwnedKeyExpr>, Timestamp)>),
+) -> ZResult<()> {
+    let mut pages = EntriesPages::default();
+    while let Some(page) = pages.next(storage).await? {
+        f(page);
+    }
+    Ok(())
+}
+
+/// The position in the content of a Storage going through it page by page, see
+/// [for_each_entries_page].
+///
+/// Contrary to [for_each_entries_page], the Storage is provided for each page: it does not have to
+/// remain locked while the pages are processed.
+#[derive(Default)]
+pub(crate) struct EntriesPages {
+    cursor: Option<EntriesCursor>,
+    done: bool,
+}
+
+impl EntriesPages {
+    /// Returns the next page of the content of the Storage, `None` once it was all returned.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if retrieving the page failed.
+    pub(crate) async fn next(
+        &mut self,
+        storage: &dyn Storage,
+    ) -> ZResult<Option<Vec<(Option<OwnedKeyExpr>, Timestamp)>>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let page = match storage
+            .get_entries_page(self.cursor.take(), ENTRIES_PAGE_SIZE)
+            .await
+        {
+            Ok(page) => page,
+            Err(e) => bail!("`get_entries_page` failed with: {e:?}"),
+        };
+        self.cursor = page.next;
+        self.done = self.cursor.is_none();
+        Ok(Some(page.entries))
+    }
+}
+
 pub(crate) async fn create_and_start_storage(
     admin_key: String,
     config: StorageConfig,
     backend: &VolumeInstance,
     zenoh_session: Arc<Session>,
-) -> ZResult<Sender<StorageMessage>> {
+) -> ZResult<StorageHandle> {
     tracing::trace!("Create storage '{}'", &admin_key);
     let capability = backend.get_capability();
-    let storage = backend.create_storage(config.clone()).await?;
+    let mut storage = backend.create_storage(config.clone()).await?;
 
     // Ex: @/390CEC11A1E34977A1C609A35BC015E6/router/status/plugins/storage_manager/storages/demo1
     // -> 390CEC11A1E34977A1C609A35BC015E6/demo1 (/<type> needed????)
@@ -68,44 +147,96 @@ pub(crate) async fn create_and_start_storage(
 
     let (tx, rx_storage) = tokio::sync::broadcast::channel(1);
     let rx_replication = tx.subscribe();
-
-    let mut entries = match storage.get_all_entries().await {
-        Ok(entries) => entries
-            .into_iter()
-            .map(|(stripped_key, ts)| {
-                let event = Event::new(stripped_key, ts, &Action::Put);
-                (event.log_key(), event)
-            })
-            .collect::<HashMap<_, _>>(),
-        Err(e) => bail!("`get_all_entries` failed with: {e:?}"),
-    };
+    let replication_status = config
+        .replication
+        .as_ref()
+        .map(|_| ReplicationStatusSnapshot::default());
 
     let mut replication_log = None;
     let mut latest_updates = HashMap::default();
     if let Some(replica_config) = &config.replication {
-        if capability.history != History::Latest {
-            bail!(
-                "Replication was enabled for storage {name} but its history capability is not \
-                 supported: found < {:?} >, expected < {:?} >",
-                capability.history,
-                History::Latest
-            );
-        }
         let mut log_latest = LogLatest::new(
             config.key_expr.clone(),
             config.strip_prefix.clone(),
             replica_config.clone(),
+            capability.history.clone(),
         );
-        log_latest.update(entries.drain().map(|(_, event)| event));
+        match capability.history {
+            History::Latest => {
+                for_each_entries_page(storage.as_ref(), |entries| {
+                    log_latest.update(
+                        entries
+                            .into_iter()
+                            .map(|(stripped_key, ts)| Event::new(stripped_key, ts, &Action::Put)),
+                    )
+                })
+                .await?;
+            }
+            // All the versions of each key must be part of the Replication Log.
+            History::All => {
+                let mut pages = EntriesPages::default();
+                while let Some(entries) = pages.next(storage.as_ref()).await? {
+                    for (stripped_key, _) in entries {
+                        let versions = match storage
+                            .get(StorageQuery::all(stripped_key.clone()))
+                            .await
+                        {
+                            Ok(versions) => versions,
+                            Err(e) => {
+                                bail!("Failed to retrieve the values of {stripped_key:?}: {e:?}")
+                            }
+                        };
+                        log_latest.update(versions.into_iter().map(|data| {
+                            Event::new_version(stripped_key.clone(), data.timestamp, &Action::Put)
+                        }));
+                    }
+                }
+            }
+        }
 
         replication_log = Some(Arc::new(RwLock::new(log_latest)));
     } else {
-        latest_updates = entries;
+        for_each_entries_page(storage.as_ref(), |entries| {
+            latest_updates.extend(entries.into_iter().map(|(stripped_key, ts)| {
+                let event = Event::new(stripped_key, ts, &Action::Put);
+                (event.log_key(), event)
+            }))
+        })
+        .await?;
     }
 
     let latest_updates = Arc::new(RwLock::new(latest_updates));
 
+    // The values derived from the attachments are lost when the Storage stops, only the ones
+    // derived from the encodings can be restored: the keys are tracked as having an unknown
+    // attachment, such that the filters on the attachments are not evaluated on them.
+    let mut indexes = SecondaryIndexes::new(&config.indexes);
+    if !indexes.is_empty() {
+        let mut pages = EntriesPages::default();
+        while let Some(entries) = pages.next(storage.as_ref()).await? {
+            for (stripped_key, timestamp) in entries {
+                match storage
+                    .get(StorageQuery::at(stripped_key.clone(), &timestamp))
+                    .await
+                {
+                    Ok(stored_data) => {
+                        for data in stored_data {
+                            indexes.insert(
+                                &stripped_key,
+                                data.timestamp,
+                                data.value.encoding(),
+                                IndexedAttachment::Unknown,
+                            );
+                        }
+                    }
+                    Err(e) => bail!("Failed to retrieve the value of {stripped_key:?}: {e:?}"),
+                }
+            }
+        }
+    }
+
     let storage = Arc::new(Mutex::new(storage));
+    let replication_status_snapshot = replication_status.clone();
 
     // NOTE The StorageService method `start_storage_queryable_subscriber` does not spawn its own
     //      task to loop/wait on the Subscriber and Queryable it creates. Thus we spawn the task
@@ -123,16 +254,20 @@ pub(crate) async fn create_and_start_storage(
                 zenoh_session.clone(),
                 config.clone(),
                 &name,
+                &admin_key,
                 storage,
                 capability,
                 CacheLatest::new(latest_updates.clone(), replication_log.clone()),
+                indexes,
             )
             .await,
         );
 
         // Testing if the `replication_log` is set is equivalent to testing if the `replication` is
         // set: the `replication_log` is only set when the latter is.
-        if let Some(replication_log) = replication_log {
+        if let (Some(replication_log), Some(replication_status)) =
+            (replication_log, replication_status_snapshot)
+        {
             tracing::debug!(
                 "Starting replication of storage '{}' on keyexpr '{}'",
                 name,
@@ -143,8 +278,10 @@ pub(crate) async fn create_and_start_storage(
                 zenoh_session,
                 storage_service.clone(),
                 config.key_expr,
+                admin_key,
                 replication_log,
                 latest_updates,
+                replication_status,
                 rx_replication,
             )
             .await;
@@ -155,5 +292,8 @@ pub(crate) async fn create_and_start_storage(
             .await;
     });
 
-    Ok(tx)
+    Ok(StorageHandle {
+        tx,
+        replication_status,
+    })
 }
@@ -0,0 +1,293 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The retention rules bound the content of a Storage: its keys can have a maximum age and the
+//! Storage can be limited to a maximum number of keys and bytes.
+//!
+//! The rules are enforced periodically by the storage manager, regardless of the backend, on a
+//! task of its own: the content of the Storage is gone through page by page, and only the entries
+//! to expire are kept in memory.
+//!
+//! The keys breaking the rules (the oldest first) are deleted. Such a deletion, an "expiry", is
+//! processed like any other Delete and is thus recorded in the Replication Log. For the Replicas
+//! to converge, an expiry must be the same on all of them: it is stamped with the timestamp of the
+//! value it expires, increased by one NTP64 unit. Hence, two Replicas expiring the same value
+//! generate the same Event, while any value published after the expired one still overrides the
+//! expiry.
+//!
+//! A Storage keeping the history of its keys is trimmed instead: the versions older than the
+//! maximum age of their key and, to enforce `max_bytes`, the oldest versions of all the keys are
+//! purged from it. A key is only expired if its latest version is purged. The purged versions are
+//! removed from the Replication Log and published on the change feed.
+//!
+//! To enforce `max_bytes`, the size of the payloads stored for each version of each key is
+//! accounted as the Storage is written, see [StoredBytes].
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BinaryHeap, HashMap},
+    time::Duration,
+};
+
+use zenoh::{
+    key_expr::OwnedKeyExpr,
+    time::{Timestamp, NTP64},
+};
+use zenoh_backend_traits::{config::RetentionConfig, History};
+
+/// An entry of a Storage, as seen by the retention rules: a key and the timestamp of its latest
+/// version or, when trimming the history of a Storage, one of its versions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RetainedEntry {
+    pub(crate) stripped_key: Option<OwnedKeyExpr>,
+    pub(crate) timestamp: Timestamp,
+    /// The size of the payloads stored for the entry. It is only accounted if `max_bytes` is
+    /// configured.
+    pub(crate) size: u64,
+}
+
+impl RetainedEntry {
+    /// The key used to order the entries that have the same timestamp.
+    fn sort_key(&self) -> Option<&str> {
+        self.stripped_key.as_ref().map(|key| key.as_str())
+    }
+}
+
+// NOTE: The key is used as a tiebreaker such that all the Replicas select the same entries.
+impl Ord for RetainedEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.timestamp
+            .cmp(&other.timestamp)
+            .then_with(|| self.sort_key().cmp(&other.sort_key()))
+            .then_with(|| self.size.cmp(&other.size))
+    }
+}
+
+impl PartialOrd for RetainedEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The size of the payloads stored for each version of each key of a Storage.
+///
+/// If the Storage keeps only the latest value of each key, a put replaces the version of the key
+/// and a delete removes it. If it keeps their history, a put adds a version to the key and a delete
+/// leaves it unchanged: the versions are only removed when they are purged.
+#[derive(Debug)]
+pub(crate) struct StoredBytes {
+    history: History,
+    versions: HashMap<Option<OwnedKeyExpr>, BTreeMap<Timestamp, u64>>,
+    total: u64,
+}
+
+impl StoredBytes {
+    pub(crate) fn new(history: History) -> Self {
+        Self {
+            history,
+            versions: HashMap::new(),
+            total: 0,
+        }
+    }
+
+    /// Accounts a value of `size` bytes put on the key.
+    pub(crate) fn put(
+        &mut self,
+        stripped_key: Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+        size: u64,
+    ) {
+        if self.history == History::Latest {
+            self.delete(&stripped_key);
+        }
+        let versions = self.versions.entry(stripped_key).or_default();
+        if let Some(replaced) = versions.insert(timestamp, size) {
+            self.total -= replaced;
+        }
+        self.total += size;
+    }
+
+    /// Accounts a delete of the key.
+    pub(crate) fn delete(&mut self, stripped_key: &Option<OwnedKeyExpr>) {
+        if self.history == History::Latest {
+            if let Some(versions) = self.versions.remove(stripped_key) {
+                self.total -= versions.values().sum::<u64>();
+            }
+        }
+    }
+
+    /// Replaces the versions accounted for the key by the provided ones, i.e. all the values
+    /// stored for the key.
+    pub(crate) fn set(
+        &mut self,
+        stripped_key: Option<OwnedKeyExpr>,
+        versions: impl IntoIterator<Item = (Timestamp, u64)>,
+    ) {
+        if let Some(replaced) = self.versions.remove(&stripped_key) {
+            self.total -= replaced.values().sum::<u64>();
+        }
+        let versions = versions.into_iter().collect::<BTreeMap<_, _>>();
+        if !versions.is_empty() {
+            self.total += versions.values().sum::<u64>();
+            self.versions.insert(stripped_key, versions);
+        }
+    }
+
+    /// Accounts the removal of the versions of the key stamped up to the provided timestamp
+    /// (included).
+    pub(crate) fn purge(&mut self, stripped_key: &Option<OwnedKeyExpr>, timestamp: &Timestamp) {
+        let Some(versions) = self.versions.get_mut(stripped_key) else {
+            return;
+        };
+        let purged = versions
+            .range(..=*timestamp)
+            .map(|(timestamp, _)| *timestamp)
+            .collect::<Vec<_>>();
+        for timestamp in purged {
+            if let Some(size) = versions.remove(&timestamp) {
+                self.total -= size;
+            }
+        }
+        if versions.is_empty() {
+            self.versions.remove(stripped_key);
+        }
+    }
+
+    /// Returns the size of the payloads stored for the key.
+    pub(crate) fn get(&self, stripped_key: &Option<OwnedKeyExpr>) -> u64 {
+        self.versions
+            .get(stripped_key)
+            .map_or(0, |versions| versions.values().sum())
+    }
+
+    /// Returns the size of the payloads stored.
+    pub(crate) fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Returns the timestamp of the latest version of the key.
+    pub(crate) fn latest(&self, stripped_key: &Option<OwnedKeyExpr>) -> Option<Timestamp> {
+        self.versions
+            .get(stripped_key)
+            .and_then(|versions| versions.keys().next_back().copied())
+    }
+
+    /// Returns the oldest versions to purge, across all the keys, such that the payloads stored
+    /// no longer exceed `max_bytes`.
+    pub(crate) fn select_oldest_versions(&self, max_bytes: u64) -> Vec<RetainedEntry> {
+        let mut oldest = OldestEntries::new(0, self.total.saturating_sub(max_bytes));
+        if oldest.is_empty_selection() {
+            return Vec::new();
+        }
+        for (stripped_key, versions) in &self.versions {
+            for (timestamp, size) in versions {
+                oldest.push(RetainedEntry {
+                    stripped_key: stripped_key.clone(),
+                    timestamp: *timestamp,
+                    size: *size,
+                });
+            }
+        }
+        oldest.into_sorted_vec()
+    }
+}
+
+/// Returns the timestamp of the Delete expiring a value that has the provided timestamp.
+pub(crate) fn expiry_timestamp(timestamp: &Timestamp) -> Timestamp {
+    Timestamp::new(*timestamp.get_time() + 1, *timestamp.get_id())
+}
+
+/// Returns the maximum age of the key: the shortest of the `max_age` rules that apply to it.
+///
+/// The `prefix` is the `strip_prefix` of the Storage: the rules apply to the full key expressions.
+pub(crate) fn max_age(
+    config: &RetentionConfig,
+    prefix: Option<&OwnedKeyExpr>,
+    stripped_key: &Option<OwnedKeyExpr>,
+) -> Option<Duration> {
+    match crate::prefix(prefix, stripped_key.as_ref()) {
+        Ok(key_expr) => config
+            .max_age
+            .iter()
+            .filter(|rule| rule.key_expr.includes(&key_expr))
+            .map(|rule| rule.age)
+            .min(),
+        Err(e) => {
+            tracing::error!("{e:?}");
+            None
+        }
+    }
+}
+
+/// Returns `true` if a value stamped with the provided timestamp is older than `max_age`.
+pub(crate) fn is_aged(timestamp: &Timestamp, max_age: Duration, now: NTP64) -> bool {
+    *timestamp.get_time() + NTP64::from(max_age) < now
+}
+
+/// Selects, among the entries it is given, the oldest ones such that removing them removes at
+/// least `excess_keys` entries and `excess_bytes` bytes.
+///
+/// Only the selected entries are kept in memory: the entries of a Storage can be pushed page by
+/// page.
+pub(crate) struct OldestEntries {
+    // NOTE: A max-heap: the most recent entry selected is the first to be deselected.
+    selected: BinaryHeap<RetainedEntry>,
+    excess_keys: usize,
+    excess_bytes: u64,
+    selected_bytes: u64,
+}
+
+impl OldestEntries {
+    pub(crate) fn new(excess_keys: usize, excess_bytes: u64) -> Self {
+        Self {
+            selected: BinaryHeap::new(),
+            excess_keys,
+            excess_bytes,
+            selected_bytes: 0,
+        }
+    }
+
+    /// Returns `true` if no entry has to be selected.
+    pub(crate) fn is_empty_selection(&self) -> bool {
+        self.excess_keys == 0 && self.excess_bytes == 0
+    }
+
+    pub(crate) fn push(&mut self, entry: RetainedEntry) {
+        if self.is_empty_selection() {
+            return;
+        }
+        self.selected_bytes += entry.size;
+        self.selected.push(entry);
+
+        while let Some(newest) = self.selected.peek() {
+            if self.selected.len() > self.excess_keys
+                && self.selected_bytes - newest.size >= self.excess_bytes
+            {
+                self.selected_bytes -= newest.size;
+                self.selected.pop();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Returns the selected entries, the oldest first.
+    pub(crate) fn into_sorted_vec(self) -> Vec<RetainedEntry> {
+        self.selected.into_sorted_vec()
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/retention.test.rs"]
+mod tests;
@@ -13,16 +13,23 @@
 //
 
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
+    num::NonZeroU128,
+    path::Path,
     str::{self},
     sync::Arc,
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use async_trait::async_trait;
-use tokio::sync::{broadcast::Receiver, Mutex, RwLock, RwLockWriteGuard};
+use tokio::{
+    sync::{broadcast::Receiver, Mutex, RwLock, RwLockWriteGuard},
+    task::JoinHandle,
+    time::{Instant, MissedTickBehavior},
+};
 use zenoh::{
-    internal::{bail, Timed, TimedEvent, Timer, Value},
+    bytes::{Encoding, ZBytes},
+    internal::{bail, zerror, Timed, TimedEvent, Timer, Value},
     key_expr::{
         keyexpr_tree::{
             IKeyExprTree, IKeyExprTreeMut, KeBoxTree, KeyedSetProvider, UnknownWildness,
@@ -31,20 +38,36 @@ use zenoh::{
     },
     sample::{Sample, SampleBuilder, SampleKind},
     session::Session,
-    time::{Timestamp, NTP64},
+    time::{Timestamp, TimestampId, NTP64},
     Result as ZResult,
 };
 use zenoh_backend_traits::{
-    config::{GarbageCollectionConfig, StorageConfig},
-    Capability, History, StorageInsertionResult, StoredData,
+    config::{GarbageCollectionConfig, RetentionConfig, StorageConfig},
+    Capability, History, StorageInsertionResult, StorageQuery, StorageWrite, StoredData,
 };
 
 use super::LatestUpdates;
 use crate::{
-    replication::{Action, Event},
-    storages_mgt::{CacheLatest, StorageMessage},
+    replication::{Action, Event, LogLatestKey},
+    storages_mgt::{
+        changes::{ChangeFeed, ChangeOrigin},
+        for_each_entries_page,
+        indexes::{Filter, IndexedAttachment, SecondaryIndexes, FILTER_KEY},
+        retention::{self, RetainedEntry, StoredBytes},
+        snapshot::{self, SnapshotEntry, SnapshotHeader, SnapshotItem},
+        CacheLatest, EntriesPages, StorageMessage, ENTRIES_PAGE_SIZE,
+    },
 };
 
+/// The key, relative to the admin key of a Storage, under which its snapshots are exported (put on
+/// `<admin_key>/snapshot/export`) and imported (put on `<admin_key>/snapshot/import`). The payload
+/// of the put is the path of the snapshot file, relative to the `snapshots_dir` of the Storage.
+pub(crate) const SNAPSHOT_CONTROL_KEY: &str = "snapshot";
+
+/// The number of snapshot entries buffered between the Storage and the task reading or writing
+/// the snapshot file.
+const SNAPSHOT_CHANNEL_SIZE: usize = 1_024;
+
 #[derive(Clone)]
 pub(crate) struct Update {
     kind: SampleKind,
@@ -69,6 +92,18 @@ impl Update {
     }
 }
 
+/// The update applied to a key of the Storage for a received Sample: the Sample itself or, if
+/// one is more recent, the Wildcard Update overriding it.
+struct KeyUpdate {
+    /// The action recorded in the Cache, the Replication Log and the change feed.
+    action: Action,
+    kind: SampleKind,
+    value: Value,
+    timestamp: Timestamp,
+    /// The attachment to index with the value, `None` if it is unknown.
+    attachment: Option<Option<ZBytes>>,
+}
+
 #[derive(Clone)]
 pub struct StorageService {
     session: Arc<Session>,
@@ -79,18 +114,39 @@ pub struct StorageService {
     pub(crate) wildcard_deletes: Arc<RwLock<KeBoxTree<Update, UnknownWildness, KeyedSetProvider>>>,
     pub(crate) wildcard_puts: Arc<RwLock<KeBoxTree<Update, UnknownWildness, KeyedSetProvider>>>,
     cache_latest: CacheLatest,
+    pub(crate) indexes: Arc<RwLock<SecondaryIndexes>>,
+    admin_key: String,
+    changes: Option<Arc<ChangeFeed>>,
+    /// The size of the payloads stored for each version of each key, only accounted if the
+    /// retention rules of the Storage set `max_bytes`. The writes are accounted as soon as the
+    /// Storage starts, the values stored before are accounted by [init_stored_bytes].
+    ///
+    /// [init_stored_bytes]: StorageService::init_stored_bytes
+    stored_bytes: Arc<Mutex<Option<StoredBytes>>>,
 }
 
 impl StorageService {
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         session: Arc<Session>,
         config: StorageConfig,
         name: &str,
+        admin_key: &str,
         storage: Arc<Mutex<Box<dyn zenoh_backend_traits::Storage>>>,
         capability: Capability,
         cache_latest: CacheLatest,
+        indexes: SecondaryIndexes,
     ) -> Self {
+        let stored_bytes = config
+            .retention
+            .as_ref()
+            .and_then(|retention| retention.max_bytes)
+            .map(|_| StoredBytes::new(capability.history.clone()));
         StorageService {
+            changes: config
+                .change_feed
+                .then(|| Arc::new(ChangeFeed::new(session.clone(), admin_key))),
+            admin_key: admin_key.to_string(),
             session,
             configuration: config,
             name: name.to_string(),
@@ -99,6 +155,8 @@ impl StorageService {
             wildcard_deletes: Arc::new(RwLock::new(KeBoxTree::default())),
             wildcard_puts: Arc::new(RwLock::new(KeBoxTree::default())),
             cache_latest,
+            indexes: Arc::new(RwLock::new(indexes)),
+            stored_bytes: Arc::new(Mutex::new(stored_bytes)),
         }
     }
 
@@ -153,13 +211,51 @@ impl StorageService {
             }
         };
 
+        // export / import snapshots on a put on the control keys
+        let snapshot_key = format!("{}/{SNAPSHOT_CONTROL_KEY}/*", self.admin_key);
+        let snapshot_sub = match self.session.declare_subscriber(&snapshot_key).await {
+            Ok(snapshot_sub) => snapshot_sub,
+            Err(e) => {
+                tracing::error!("Error starting storage '{}': {}", self.name, e);
+                return;
+            }
+        };
+
         tracing::debug!(
             "Starting storage '{}' on keyexpr '{}'",
             self.name,
             storage_key_expr
         );
 
+        let batching = match &self.configuration.batching {
+            Some(batching) if self.storage.lock().await.supports_batch() => Some(batching.clone()),
+            Some(_) => {
+                tracing::warn!(
+                    "Storage '{}' does not support batches, its samples will be stored one by one",
+                    self.name
+                );
+                None
+            }
+            None => None,
+        };
+
+        // NOTE: The retention rules are enforced on their own task, which stops once the sender is
+        //       dropped, i.e. once the storage is stopped.
+        let mut stop_retention = None;
+        if let Some(retention) = self.configuration.retention.clone() {
+            let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+            stop_retention = Some(tx);
+            let service = self.clone();
+            tokio::task::spawn(async move { service.run_retention(retention, rx).await });
+        }
+
         tokio::task::spawn(async move {
+            let _stop_retention = stop_retention;
+            let mut batch = Vec::default();
+            let batch_timer = tokio::time::sleep(Duration::ZERO);
+            tokio::pin!(batch_timer);
+            let mut snapshot_task: Option<JoinHandle<()>> = None;
+
             loop {
                 tokio::select!(
                     // on sample for key_expr
@@ -172,13 +268,57 @@ impl StorageService {
                             }
                         };
                         let timestamp = sample.timestamp().cloned().unwrap_or(self.session.new_timestamp());
-                        let sample = SampleBuilder::from(sample).timestamp(timestamp).into();
-                        if let Err(e) = self.process_sample(sample).await {
-                            tracing::error!("{e:?}");
+                        let sample: Sample = SampleBuilder::from(sample).timestamp(timestamp).into();
+                        match &batching {
+                            Some(batching) if !sample.key_expr().is_wild() => {
+                                if batch.is_empty() {
+                                    batch_timer.as_mut().reset(Instant::now() + batching.window);
+                                }
+                                batch.push(sample);
+                                if batch.len() >= batching.max_samples {
+                                    self.flush_batch(&mut batch).await;
+                                }
+                            }
+                            _ => {
+                                // A Wildcard Update must be processed after the Samples received
+                                // before it.
+                                self.flush_batch(&mut batch).await;
+                                if let Err(e) = self.process_sample(sample, ChangeOrigin::Live).await {
+                                    tracing::error!("{e:?}");
+                                }
+                            }
                         }
                     },
+                    // on expiration of the batching window
+                    _ = &mut batch_timer, if !batch.is_empty() => {
+                        self.flush_batch(&mut batch).await;
+                    },
+                    // on snapshot request
+                    Ok(sample) = snapshot_sub.recv_async() => {
+                        if snapshot_task.as_ref().is_some_and(|task| !task.is_finished()) {
+                            tracing::error!(
+                                "Storage '{}' received a snapshot request on < {} > while another \
+                                 one is in progress. Ignoring it.",
+                                self.name,
+                                sample.key_expr()
+                            );
+                            continue;
+                        }
+                        // Flushing ensures that an export contains all the Samples received before
+                        // the request.
+                        self.flush_batch(&mut batch).await;
+                        // NOTE: The snapshot is exported or imported on its own task so that the
+                        //       Samples, queries and messages received meanwhile are still
+                        //       processed.
+                        let service = self.clone();
+                        snapshot_task = Some(tokio::task::spawn(async move {
+                            service.process_snapshot_request(sample).await
+                        }));
+                    },
                     // on query on key_expr
                     query = storage_queryable.recv_async() => {
+                        // Flushing ensures that a query observes all the Samples received before it.
+                        self.flush_batch(&mut batch).await;
                         self.reply_query(query).await;
                     },
                     // on storage handle drop
@@ -186,6 +326,10 @@ impl StorageService {
                         match message {
                             StorageMessage::Stop => {
                                 tracing::trace!("Dropping storage '{}'", self.name);
+                                self.flush_batch(&mut batch).await;
+                                if let Some(snapshot_task) = snapshot_task.take() {
+                                    snapshot_task.abort();
+                                }
                                 return
                             },
                             StorageMessage::GetStatus(tx) => {
@@ -202,7 +346,7 @@ impl StorageService {
 
     // The storage should only simply save the key, sample pair while put and retrieve the same
     // during get the trimming during PUT and GET should be handled by the plugin
-    pub(crate) async fn process_sample(&self, sample: Sample) -> ZResult<()> {
+    pub(crate) async fn process_sample(&self, sample: Sample, origin: ChangeOrigin) -> ZResult<()> {
         tracing::trace!("[STORAGE] Processing sample: {:?}", sample.key_expr());
 
         // A Sample, in theory, will not arrive to a Storage without a Timestamp. This check (which,
@@ -232,7 +376,12 @@ impl StorageService {
                 SampleKind::Delete => Action::WildcardDelete(sample_key_expr.clone()),
             };
 
-            let event = Event::new(Some(sample_key_expr.clone()), *sample_timestamp, &action);
+            let event = Event::new_for_history(
+                &self.capability.history,
+                Some(sample_key_expr.clone()),
+                *sample_timestamp,
+                &action,
+            );
 
             self.cache_latest
                 .latest_updates
@@ -255,46 +404,11 @@ impl StorageService {
         let prefix = self.configuration.strip_prefix.as_ref();
 
         for k in matching_keys {
-            // there might be the case that the actual update was outdated due to a wild card
-            // update, but not stored yet in the storage. get the relevant wild
-            // card entry and use that value and timestamp to update the storage
-            let sample_to_store: Sample = if let Some((_, update)) = self
-                .overriding_wild_update(&k, sample_timestamp, &None, &sample.kind().into())
-                .await
-            {
-                match update.kind {
-                    SampleKind::Put => {
-                        SampleBuilder::put(k.clone(), update.data.value.payload().clone())
-                            .encoding(update.data.value.encoding().clone())
-                            .timestamp(update.data.timestamp)
-                            .into()
-                    }
-                    SampleKind::Delete => SampleBuilder::delete(k.clone())
-                        .timestamp(update.data.timestamp)
-                        .into(),
-                }
-            } else {
-                SampleBuilder::from(sample.clone())
-                    .keyexpr(k.clone())
-                    .into()
-            };
-
-            // A Sample that is to be stored **must** have a Timestamp. In theory, the Sample
-            // generated should have a Timestamp and, in theory, this check is
-            // unneeded.
-            let sample_to_store_timestamp = match sample_to_store.timestamp() {
-                Some(timestamp) => *timestamp,
-                None => {
-                    tracing::error!(
-                        "Discarding `Sample` generated through `SampleBuilder` that has no \
-                         Timestamp: {:?}",
-                        sample_to_store
-                    );
-                    continue;
-                }
-            };
+            let update = self
+                .key_update(&k, &sample, sample_timestamp, &action, origin)
+                .await;
 
-            let stripped_key = match crate::strip_prefix(prefix, sample_to_store.key_expr()) {
+            let stripped_key = match crate::strip_prefix(prefix, &KeyExpr::from(k.clone())) {
                 Ok(stripped) => stripped,
                 Err(e) => {
                     bail!("{e:?}");
@@ -303,7 +417,12 @@ impl StorageService {
 
             // If the Storage was declared as only keeping the Latest value, we ensure that, for
             // each received Sample, it is indeed the Latest value that is processed.
-            let new_event = Event::new(stripped_key.clone(), sample_to_store_timestamp, &action);
+            let new_event = Event::new_for_history(
+                &self.capability.history,
+                stripped_key.clone(),
+                update.timestamp,
+                &update.action,
+            );
             let mut cache_guard = None;
             if self.capability.history == History::Latest {
                 match self.guard_cache_if_latest(&new_event).await {
@@ -317,26 +436,27 @@ impl StorageService {
                 }
             }
 
+            let size = match update.kind {
+                SampleKind::Put => Some(update.value.payload().len() as u64),
+                SampleKind::Delete => None,
+            };
             let mut storage = self.storage.lock().await;
-            let storage_result = match sample.kind() {
+            let storage_result = match update.kind {
                 SampleKind::Put => {
                     storage
-                        .put(
-                            stripped_key.clone(),
-                            Value::new(
-                                sample_to_store.payload().clone(),
-                                sample_to_store.encoding().clone(),
-                            ),
-                            sample_to_store_timestamp,
-                        )
-                        .await
-                }
-                SampleKind::Delete => {
-                    storage
-                        .delete(stripped_key.clone(), sample_to_store_timestamp)
+                        .put(stripped_key.clone(), update.value.clone(), update.timestamp)
                         .await
                 }
+                SampleKind::Delete => storage.delete(stripped_key.clone(), update.timestamp).await,
             };
+            let change_seq = storage_result
+                .as_ref()
+                .ok()
+                .and_then(|result| self.next_change_seq(result));
+            if let Ok(result) = &storage_result {
+                self.account_write(&stripped_key, update.timestamp, size, result)
+                    .await;
+            }
 
             drop(storage);
 
@@ -345,9 +465,36 @@ impl StorageService {
                     tracing::trace!("Ignoring `Outdated` sample < {} >", k);
                 }
                 Ok(_) => {
+                    match update.kind {
+                        SampleKind::Put => {
+                            self.index_put(
+                                &stripped_key,
+                                update.timestamp,
+                                update.value.encoding(),
+                                match &update.attachment {
+                                    Some(attachment) => {
+                                        IndexedAttachment::Known(attachment.as_ref())
+                                    }
+                                    None => IndexedAttachment::Unknown,
+                                },
+                            )
+                            .await
+                        }
+                        SampleKind::Delete => {
+                            self.index_delete(&stripped_key, update.timestamp).await
+                        }
+                    }
                     if let Some(mut cache_guard) = cache_guard {
                         cache_guard.insert(new_event.log_key(), new_event);
+                    } else if self.records_versions() {
+                        self.cache_latest
+                            .latest_updates
+                            .write()
+                            .await
+                            .insert(new_event.log_key(), new_event);
                     }
+                    self.publish_change(change_seq, &k, &update.action, update.timestamp, origin)
+                        .await;
                 }
                 Err(e) => {
                     // TODO In case of a wildcard update, multiple keys can be updated. What should
@@ -360,6 +507,773 @@ impl StorageService {
         Ok(())
     }
 
+    /// Enforces periodically the provided retention rules, see the [retention] module, until `stop`
+    /// is resolved, i.e. until the Storage is stopped.
+    ///
+    /// If the rules set `max_bytes`, the size of the values stored before the Storage started is
+    /// first accounted, see [init_stored_bytes].
+    ///
+    /// [init_stored_bytes]: StorageService::init_stored_bytes
+    async fn run_retention(
+        &self,
+        config: RetentionConfig,
+        mut stop: tokio::sync::oneshot::Receiver<()>,
+    ) {
+        if config.max_bytes.is_some() {
+            tokio::select!(
+                result = self.init_stored_bytes() => {
+                    if let Err(e) = result {
+                        tracing::error!(
+                            "Storage '{}' failed to account the size of its values, `max_bytes` \
+                             will only account the values stored from now on: {e:?}",
+                            self.name
+                        );
+                    }
+                },
+                _ = &mut stop => return,
+            );
+        }
+
+        let mut interval = tokio::time::interval(config.period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select!(
+                _ = interval.tick() => {
+                    if let Err(e) = self.enforce_retention(&config).await {
+                        tracing::error!("{e:?}");
+                    }
+                },
+                _ = &mut stop => return,
+            );
+        }
+    }
+
+    /// Accounts the size of all the values stored, page by page.
+    ///
+    /// ⚠️ This requires retrieving every stored value: it is only done once, when the Storage
+    /// starts, the writes are then accounted as they are performed.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the content of the Storage could not be retrieved.
+    async fn init_stored_bytes(&self) -> ZResult<()> {
+        let mut pages = EntriesPages::default();
+        loop {
+            let page = {
+                let storage = self.storage.lock().await;
+                pages.next(storage.as_ref()).await?
+            };
+            let Some(page) = page else {
+                return Ok(());
+            };
+
+            for (stripped_key, _) in page {
+                let query = match self.capability.history {
+                    History::Latest => StorageQuery::latest(stripped_key.clone()),
+                    History::All => StorageQuery::all(stripped_key.clone()),
+                };
+                // NOTE: The Storage remains locked until the values of the key are accounted: the
+                //       writes of the key are accounted either before (and replaced) or after.
+                let mut storage = self.storage.lock().await;
+                match storage.get(query).await {
+                    Ok(stored_data) => {
+                        if let Some(stored_bytes) = self.stored_bytes.lock().await.as_mut() {
+                            stored_bytes.set(
+                                stripped_key,
+                                stored_data.iter().map(|data| {
+                                    (data.timestamp, data.value.payload().len() as u64)
+                                }),
+                            );
+                        }
+                    }
+                    Err(e) => tracing::warn!(
+                        "Storage '{}' failed to retrieve < {:?} >: {e:?}",
+                        self.name,
+                        stripped_key
+                    ),
+                }
+            }
+        }
+    }
+
+    /// Expires the keys and, if the Storage keeps their history, purges the versions breaking the
+    /// provided retention rules, see the [retention] module.
+    ///
+    /// The content of the Storage is gone through page by page: first to enforce the `max_age`
+    /// rules and count the keys, then, if `max_keys` or `max_bytes` are exceeded, to select the
+    /// oldest keys.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the content of the Storage could not be retrieved.
+    async fn enforce_retention(&self, config: &RetentionConfig) -> ZResult<()> {
+        tracing::trace!(
+            "[STORAGE] Enforcing retention rules of storage '{}'",
+            self.name
+        );
+        let prefix = self.configuration.strip_prefix.as_ref();
+        let keeps_history = self.capability.history == History::All;
+        let now = NTP64::from(SystemTime::now().duration_since(UNIX_EPOCH).unwrap());
+
+        let mut nb_keys = 0;
+        let mut nb_expired = 0;
+        let mut pages = EntriesPages::default();
+        while let Some(page) = self.next_entries_page(&mut pages).await? {
+            for (stripped_key, timestamp) in page {
+                match retention::max_age(config, prefix, &stripped_key) {
+                    Some(max_age) if retention::is_aged(&timestamp, max_age, now) => {
+                        self.expire(stripped_key, timestamp).await;
+                        nb_expired += 1;
+                        continue;
+                    }
+                    Some(max_age) if keeps_history => {
+                        let cutoff = now - NTP64::from(max_age);
+                        // NOTE: The versions stamped with a time strictly lower than the cutoff,
+                        //       whatever their ID, are aged.
+                        let cutoff =
+                            Timestamp::new(cutoff - 1, TimestampId::from(NonZeroU128::MAX));
+                        self.purge_versions(&stripped_key, cutoff).await;
+                    }
+                    _ => {}
+                }
+                nb_keys += 1;
+            }
+        }
+
+        // With the history of the keys, the bytes are bounded by trimming versions, not keys.
+        let excess_bytes = match config.max_bytes {
+            Some(max_bytes) if !keeps_history => self
+                .stored_bytes
+                .lock()
+                .await
+                .as_ref()
+                .map_or(0, |stored_bytes| {
+                    stored_bytes.total().saturating_sub(max_bytes)
+                }),
+            _ => 0,
+        };
+        let excess_keys = config
+            .max_keys
+            .map_or(0, |max_keys| nb_keys.saturating_sub(max_keys));
+        let mut oldest = retention::OldestEntries::new(excess_keys, excess_bytes);
+        if !oldest.is_empty_selection() {
+            let mut pages = EntriesPages::default();
+            while let Some(page) = self.next_entries_page(&mut pages).await? {
+                let stored_bytes = self.stored_bytes.lock().await;
+                for (stripped_key, timestamp) in page {
+                    let size = stored_bytes
+                        .as_ref()
+                        .map_or(0, |stored_bytes| stored_bytes.get(&stripped_key));
+                    oldest.push(RetainedEntry {
+                        stripped_key,
+                        timestamp,
+                        size,
+                    });
+                }
+            }
+        }
+        for entry in oldest.into_sorted_vec() {
+            self.expire(entry.stripped_key, entry.timestamp).await;
+            nb_expired += 1;
+        }
+
+        if let (Some(max_bytes), true) = (config.max_bytes, keeps_history) {
+            let mut trims = HashMap::<Option<OwnedKeyExpr>, (Timestamp, bool)>::new();
+            {
+                let stored_bytes = self.stored_bytes.lock().await;
+                if let Some(stored_bytes) = stored_bytes.as_ref() {
+                    // NOTE: The versions are selected the oldest first: the last one selected for
+                    //       a key is the most recent.
+                    for version in stored_bytes.select_oldest_versions(max_bytes) {
+                        let is_latest =
+                            stored_bytes.latest(&version.stripped_key) == Some(version.timestamp);
+                        trims.insert(version.stripped_key, (version.timestamp, is_latest));
+                    }
+                }
+            }
+            for (stripped_key, (timestamp, is_latest)) in trims {
+                if is_latest {
+                    self.expire(stripped_key, timestamp).await;
+                    nb_expired += 1;
+                } else {
+                    self.purge_versions(&stripped_key, timestamp).await;
+                }
+            }
+        }
+
+        if nb_expired > 0 {
+            tracing::debug!(
+                "Storage '{}' expired {nb_expired} key(s) breaking its retention rules",
+                self.name,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Returns the next page of the content of the Storage, only locking it while retrieving the
+    /// page.
+    async fn next_entries_page(
+        &self,
+        pages: &mut EntriesPages,
+    ) -> ZResult<Option<Vec<(Option<OwnedKeyExpr>, Timestamp)>>> {
+        let storage = self.storage.lock().await;
+        pages.next(storage.as_ref()).await
+    }
+
+    /// Deletes the key whose latest value is stamped with the provided timestamp, see the
+    /// [retention] module.
+    ///
+    /// The expiry is processed as any received Delete: it is hence recorded in the Cache and in the
+    /// Replication Log and ignored if a more recent value was received in the meantime. If the
+    /// Storage keeps the history of its keys, the expired versions are then purged from it.
+    async fn expire(&self, stripped_key: Option<OwnedKeyExpr>, timestamp: Timestamp) {
+        let key_expr = match crate::prefix(
+            self.configuration.strip_prefix.as_ref(),
+            stripped_key.as_ref(),
+        ) {
+            Ok(key_expr) => key_expr,
+            Err(e) => {
+                tracing::error!("{e:?}");
+                return;
+            }
+        };
+        let expiry = SampleBuilder::delete(key_expr)
+            .timestamp(retention::expiry_timestamp(&timestamp))
+            .into();
+        if let Err(e) = self.process_sample(expiry, ChangeOrigin::Retention).await {
+            tracing::error!("{e:?}");
+            return;
+        }
+
+        // The expired versions would otherwise still be kept in the history of the key.
+        if self.capability.history == History::All {
+            self.purge_versions(&stripped_key, timestamp).await;
+        }
+    }
+
+    /// Purges the versions of the key stamped up to the provided timestamp (included) from the
+    /// Storage, which must keep the history of its keys.
+    ///
+    /// The purged versions are removed from the Cache and the Replication Log, such that their
+    /// Digest reflects the content of the Storage, and the purge is published on the change feed.
+    async fn purge_versions(&self, stripped_key: &Option<OwnedKeyExpr>, timestamp: Timestamp) {
+        let mut storage = self.storage.lock().await;
+        let purged = match storage.purge(stripped_key.clone(), timestamp).await {
+            Ok(purged) => purged,
+            Err(e) => {
+                tracing::warn!(
+                    "Storage '{}' failed to purge the versions of < {stripped_key:?} >: {e:?}",
+                    self.name,
+                );
+                return;
+            }
+        };
+        if purged.is_empty() {
+            return;
+        }
+        if let Some(stored_bytes) = self.stored_bytes.lock().await.as_mut() {
+            stored_bytes.purge(stripped_key, &timestamp);
+        }
+        let change_seq = self.changes.as_ref().map(|changes| changes.next_seq());
+        drop(storage);
+
+        if self.records_versions() {
+            let mut cache_guard = self.cache_latest.latest_updates.write().await;
+            if let Some(replication_log) = &self.cache_latest.replication_log {
+                let mut replication_log = replication_log.write().await;
+                for version in purged {
+                    // NOTE: A Put and a Delete have the same key in the Cache and in the Log.
+                    let event = Event::new_version(stripped_key.clone(), version, &Action::Put);
+                    cache_guard.remove(&event.log_key());
+                    replication_log.remove_event(&(&event).into());
+                }
+            }
+        }
+
+        if let (Some(changes), Some(seq)) = (&self.changes, change_seq) {
+            match crate::prefix(
+                self.configuration.strip_prefix.as_ref(),
+                stripped_key.as_ref(),
+            ) {
+                Ok(key) => changes.publish_purge(seq, &key, timestamp).await,
+                Err(e) => tracing::error!("{e:?}"),
+            }
+        }
+    }
+
+    /// Exports or imports a snapshot of the Storage, following a put on one of its control keys.
+    ///
+    /// The payload of the put is the path of the snapshot file, relative to the `snapshots_dir` of
+    /// the Storage: the request is ignored if no such directory is configured. As for any other
+    /// write on the admin space, the request is also ignored if write permissions on the admin
+    /// space are disabled.
+    ///
+    /// The request is processed on its own task, while the Storage keeps processing Samples and
+    /// queries: a request received while another one is in progress is ignored.
+    async fn process_snapshot_request(&self, sample: Sample) {
+        if sample.kind() != SampleKind::Put {
+            return;
+        }
+
+        if !self.session.config().lock().adminspace.permissions().write {
+            tracing::error!(
+                "Storage '{}' received a snapshot request on < {} > but write permissions on the \
+                 admin space are disabled. Ignoring it.",
+                self.name,
+                sample.key_expr()
+            );
+            return;
+        }
+
+        let Some(snapshots_dir) = &self.configuration.snapshots_dir else {
+            tracing::error!(
+                "Storage '{}' received a snapshot request on < {} > but no `snapshots_dir` is \
+                 configured. Ignoring it.",
+                self.name,
+                sample.key_expr()
+            );
+            return;
+        };
+
+        let path = match sample.payload().try_to_string() {
+            Ok(path) => snapshot::resolve_path(snapshots_dir, path.trim()),
+            Err(e) => Err(zerror!("Invalid snapshot path: {e}").into()),
+        };
+        let path = match path {
+            Ok(path) => path,
+            Err(e) => {
+                tracing::error!(
+                    "Storage '{}' received a snapshot request on < {} > without a valid path: \
+                     {e}",
+                    self.name,
+                    sample.key_expr()
+                );
+                return;
+            }
+        };
+
+        let (operation, result) = match sample.key_expr().as_str().rsplit('/').next() {
+            Some("export") => ("export", self.export_snapshot(&path).await),
+            Some("import") => ("import", self.import_snapshot(&path).await),
+            _ => {
+                tracing::error!(
+                    "Storage '{}' received an unknown snapshot request < {} >",
+                    self.name,
+                    sample.key_expr()
+                );
+                return;
+            }
+        };
+
+        match result {
+            Ok(nb_entries) => tracing::info!(
+                "Storage '{}': {operation} of {nb_entries} value(s) in snapshot {path:?} succeeded",
+                self.name,
+            ),
+            Err(e) => tracing::error!(
+                "Storage '{}': {operation} of snapshot {path:?} failed: {e:?}",
+                self.name,
+            ),
+        }
+    }
+
+    /// Writes the content of the Storage in a snapshot at the provided path, returning the number
+    /// of values it contains. See the [snapshot] module for a description of the format.
+    ///
+    /// All the values of each key are exported if the Storage keeps its history. Deleted keys are,
+    /// by definition, not part of the content of the Storage and are thus not exported.
+    ///
+    /// The snapshot is written by a blocking task, to which the values are sent as they are
+    /// retrieved: the Storage is only locked while retrieving them and never during file I/O.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the content of the Storage could not be retrieved or if
+    /// the snapshot could not be written.
+    async fn export_snapshot(&self, path: &Path) -> ZResult<usize> {
+        let header = SnapshotHeader::new(&self.name, self.configuration.key_expr.clone());
+        let (tx, rx) = tokio::sync::mpsc::channel(SNAPSHOT_CHANNEL_SIZE);
+        let path = path.to_path_buf();
+        let writer = tokio::task::spawn_blocking(move || snapshot::write_file(&path, &header, rx));
+
+        // NOTE: The writer only commits the snapshot if it receives `SnapshotItem::Commit`: on
+        //       error, dropping `tx` makes it discard the partially written snapshot.
+        let produced = self.send_snapshot_entries(&tx).await;
+        if produced.is_ok() {
+            // NOTE: The writer only stops receiving on error, returned when awaiting it.
+            let _ = tx.send(SnapshotItem::Commit).await;
+        }
+        drop(tx);
+
+        let written = writer
+            .await
+            .map_err(|e| zerror!("Snapshot writer task failed: {e}"))?;
+        produced?;
+        written
+    }
+
+    /// Sends all the values of the Storage to the snapshot writer, see [export_snapshot].
+    ///
+    /// [export_snapshot]: StorageService::export_snapshot
+    async fn send_snapshot_entries(
+        &self,
+        tx: &tokio::sync::mpsc::Sender<SnapshotItem>,
+    ) -> ZResult<()> {
+        let prefix = self.configuration.strip_prefix.as_ref();
+        let mut pages = EntriesPages::default();
+        loop {
+            // NOTE: The Storage is only locked while retrieving the page, the values of its keys
+            //       being retrieved one key at a time.
+            let page = {
+                let storage = self.storage.lock().await;
+                pages.next(storage.as_ref()).await?
+            };
+            let Some(page) = page else {
+                return Ok(());
+            };
+
+            for (stripped_key, _) in page {
+                let key = crate::prefix(prefix, stripped_key.as_ref())?;
+                let query = match self.capability.history {
+                    History::Latest => StorageQuery::latest(stripped_key),
+                    History::All => StorageQuery::all(stripped_key),
+                };
+                let stored_data = match self.storage.lock().await.get(query).await {
+                    Ok(stored_data) => stored_data,
+                    Err(e) => bail!("Failed to retrieve the value(s) of < {key} >: {e:?}"),
+                };
+
+                for data in stored_data {
+                    let entry = SnapshotEntry {
+                        key: key.clone(),
+                        timestamp: data.timestamp,
+                        encoding: data.value.encoding().clone(),
+                        payload: data.value.payload().clone(),
+                    };
+                    // NOTE: The writer only stops receiving on error, returned when awaiting it.
+                    if tx.send(SnapshotItem::Entry(entry)).await.is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Loads the snapshot at the provided path in the Storage, returning the number of values
+    /// processed.
+    ///
+    /// Each value of the snapshot is processed as a put received with its original [Timestamp]:
+    /// it is hence recorded in the Cache and in the Replication Log, and ignored if the Storage
+    /// holds a more recent value. Importing the same snapshot several times is thus harmless.
+    ///
+    /// The values whose key does not belong to the key expression of the Storage are skipped.
+    ///
+    /// The snapshot is read by a blocking task, which sends its values as they are parsed.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the snapshot could not be read or if it is invalid.
+    /// The values processed before the error was encountered are kept in the Storage.
+    async fn import_snapshot(&self, path: &Path) -> ZResult<usize> {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(SNAPSHOT_CHANNEL_SIZE);
+        let path = path.to_path_buf();
+        let reader = tokio::task::spawn_blocking(move || snapshot::read_file(&path, tx));
+
+        let supports_batch = self.storage.lock().await.supports_batch();
+        let mut batch = Vec::new();
+        let mut nb_entries = 0;
+        while let Some(entry) = rx.recv().await {
+            if !self.configuration.key_expr.includes(&entry.key) {
+                tracing::trace!(
+                    "Storage '{}' skipping < {} > from snapshot: not included in < {} >",
+                    self.name,
+                    entry.key,
+                    self.configuration.key_expr
+                );
+                continue;
+            }
+
+            let sample: Sample = SampleBuilder::put(entry.key, entry.payload)
+                .encoding(entry.encoding)
+                .timestamp(entry.timestamp)
+                .into();
+            nb_entries += 1;
+
+            if supports_batch {
+                batch.push(sample);
+                if batch.len() >= ENTRIES_PAGE_SIZE {
+                    self.process_samples_batch(std::mem::take(&mut batch), ChangeOrigin::Snapshot)
+                        .await?;
+                }
+            } else {
+                self.process_sample(sample, ChangeOrigin::Snapshot).await?;
+            }
+        }
+
+        if !batch.is_empty() {
+            self.process_samples_batch(batch, ChangeOrigin::Snapshot)
+                .await?;
+        }
+
+        let header = reader
+            .await
+            .map_err(|e| zerror!("Snapshot reader task failed: {e}"))??;
+        tracing::debug!(
+            "Storage '{}' imported snapshot of storage '{}' on < {} >",
+            self.name,
+            header.storage,
+            header.key_expr
+        );
+
+        Ok(nb_entries)
+    }
+
+    /// Processes, if any, the Samples accumulated in the provided batch, leaving it empty.
+    async fn flush_batch(&self, batch: &mut Vec<Sample>) {
+        if batch.is_empty() {
+            return;
+        }
+
+        if let Err(e) = self
+            .process_samples_batch(std::mem::take(batch), ChangeOrigin::Live)
+            .await
+        {
+            tracing::error!("{e:?}");
+        }
+    }
+
+    /// Processes a batch of Samples, applying them to the Storage with a single call to
+    /// `put_batch`.
+    ///
+    /// The batch must not contain Wildcard Updates: as they can affect any number of keys, they are
+    /// processed individually through [process_sample].
+    ///
+    /// The Cache is locked until the Storage has processed the batch and the Cache has been updated
+    /// accordingly, see [guard_cache_if_latest] for the rationale.
+    ///
+    /// [process_sample]: StorageService::process_sample()
+    /// [guard_cache_if_latest]: StorageService::guard_cache_if_latest()
+    pub(crate) async fn process_samples_batch(
+        &self,
+        samples: Vec<Sample>,
+        origin: ChangeOrigin,
+    ) -> ZResult<()> {
+        tracing::trace!("[STORAGE] Processing batch of {} samples", samples.len());
+
+        let prefix = self.configuration.strip_prefix.as_ref();
+        let mut cache_guard = self.cache_latest.latest_updates.write().await;
+        // Timestamps of the Events already part of the batch, to only keep the latest when
+        // several Samples of the batch concern the same key.
+        let mut batch_timestamps: HashMap<LogLatestKey, Timestamp> = HashMap::default();
+        let mut writes = Vec::with_capacity(samples.len());
+        let mut events = Vec::with_capacity(samples.len());
+        // The attachments of the Samples, to index the values once stored: `None` if unknown.
+        let mut attachments = Vec::with_capacity(samples.len());
+        // The keys and actions of the Samples, to publish the changes once stored.
+        let mut changes = Vec::with_capacity(samples.len());
+
+        for sample in samples {
+            if sample.key_expr().is_wild() {
+                bail!(
+                    "Wildcard Update < {} > cannot be processed in a batch",
+                    sample.key_expr()
+                );
+            }
+
+            let Some(sample_timestamp) = sample.timestamp().cloned() else {
+                tracing::error!("Discarding Sample without a Timestamp: {:?}", sample);
+                continue;
+            };
+
+            let k: OwnedKeyExpr = sample.key_expr().clone().into();
+            let KeyUpdate {
+                action,
+                kind,
+                value,
+                timestamp,
+                attachment,
+            } = self
+                .key_update(
+                    &k,
+                    &sample,
+                    &sample_timestamp,
+                    &sample.kind().into(),
+                    origin,
+                )
+                .await;
+
+            let stripped_key = match crate::strip_prefix(prefix, sample.key_expr()) {
+                Ok(stripped) => stripped,
+                Err(e) => {
+                    tracing::error!("{e:?}");
+                    continue;
+                }
+            };
+
+            let new_event = Event::new_for_history(
+                &self.capability.history,
+                stripped_key.clone(),
+                timestamp,
+                &action,
+            );
+            if self.capability.history == History::Latest {
+                let is_latest = match batch_timestamps.get(&new_event.log_key()) {
+                    Some(batch_timestamp) => new_event.timestamp > *batch_timestamp,
+                    None => self.is_latest(&cache_guard, &new_event).await,
+                };
+                if !is_latest {
+                    tracing::trace!("Skipping outdated Sample < {} >", k);
+                    continue;
+                }
+                batch_timestamps.insert(new_event.log_key(), timestamp);
+            }
+
+            attachments.push(attachment);
+            changes.push((k, action, timestamp));
+            writes.push(match kind {
+                SampleKind::Put => StorageWrite::Put {
+                    key: stripped_key,
+                    value,
+                    timestamp,
+                },
+                SampleKind::Delete => StorageWrite::Delete {
+                    key: stripped_key,
+                    timestamp,
+                },
+            });
+            events.push(new_event);
+        }
+
+        if writes.is_empty() {
+            return Ok(());
+        }
+
+        let nb_writes = writes.len();
+        // The values are cloned only if they have to be indexed.
+        let indexed_writes = if self.configuration.indexes.is_empty() {
+            Vec::new()
+        } else {
+            writes.clone()
+        };
+        let mut storage = self.storage.lock().await;
+        // The keys and sizes of the writes are only kept if the bytes stored are accounted.
+        let accounted_writes = if self.stored_bytes.lock().await.is_some() {
+            writes
+                .iter()
+                .map(|write| match write {
+                    StorageWrite::Put {
+                        key,
+                        value,
+                        timestamp,
+                    } => (key.clone(), *timestamp, Some(value.payload().len() as u64)),
+                    StorageWrite::Delete { key, timestamp } => (key.clone(), *timestamp, None),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let results = match storage.put_batch(writes).await {
+            Ok(results) => results,
+            Err(e) => bail!("Batch of {nb_writes} writes failed with: {e:?}"),
+        };
+        let change_seqs = results
+            .iter()
+            .map(|result| self.next_change_seq(result))
+            .collect::<Vec<_>>();
+        for (result, (key, timestamp, size)) in results.iter().zip(accounted_writes) {
+            self.account_write(&key, timestamp, size, result).await;
+        }
+        drop(storage);
+
+        if !indexed_writes.is_empty() {
+            let mut indexes = self.indexes.write().await;
+            for ((result, write), attachment) in results.iter().zip(indexed_writes).zip(attachments)
+            {
+                match (result, write) {
+                    (StorageInsertionResult::Outdated, _) => {}
+                    (
+                        _,
+                        StorageWrite::Put {
+                            key,
+                            value,
+                            timestamp,
+                        },
+                    ) => indexes.insert(
+                        &key,
+                        timestamp,
+                        value.encoding(),
+                        match &attachment {
+                            Some(attachment) => IndexedAttachment::Known(attachment.as_ref()),
+                            None => IndexedAttachment::Unknown,
+                        },
+                    ),
+                    (_, StorageWrite::Delete { key, timestamp }) => indexes.remove(&key, timestamp),
+                }
+            }
+        }
+
+        if self.capability.history == History::Latest || self.records_versions() {
+            for (result, event) in results.iter().zip(events) {
+                match result {
+                    StorageInsertionResult::Outdated => {
+                        tracing::trace!("Ignoring `Outdated` sample < {:?} >", event.stripped_key);
+                    }
+                    _ => {
+                        cache_guard.insert(event.log_key(), event);
+                    }
+                }
+            }
+        }
+        drop(cache_guard);
+
+        for (change_seq, (key, action, timestamp)) in change_seqs.into_iter().zip(changes) {
+            self.publish_change(change_seq, &key, &action, timestamp, origin)
+                .await;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the update to apply to the key `k` for the received `sample`, recorded with
+    /// `action` unless a more recent Wildcard Update overrides it.
+    ///
+    /// The update might be outdated by a Wildcard Update that is not stored yet: the value,
+    /// timestamp and action of this Wildcard Update are then applied instead.
+    async fn key_update(
+        &self,
+        k: &OwnedKeyExpr,
+        sample: &Sample,
+        sample_timestamp: &Timestamp,
+        action: &Action,
+        origin: ChangeOrigin,
+    ) -> KeyUpdate {
+        match self
+            .overriding_wild_update(k, sample_timestamp, &None, &sample.kind().into())
+            .await
+        {
+            Some((wildcard_ke, update)) => KeyUpdate {
+                action: match update.kind {
+                    SampleKind::Put => Action::WildcardPut(wildcard_ke),
+                    SampleKind::Delete => Action::WildcardDelete(wildcard_ke),
+                },
+                kind: update.kind,
+                value: update.data.value,
+                timestamp: update.data.timestamp,
+                // The attachment of a Wildcard Update is not kept.
+                attachment: None,
+            },
+            None => KeyUpdate {
+                action: action.clone(),
+                kind: sample.kind(),
+                value: Value::new(sample.payload().clone(), sample.encoding().clone()),
+                timestamp: *sample_timestamp,
+                // The attachments of the aligned or restored values are not known.
+                attachment: (origin == ChangeOrigin::Live).then(|| sample.attachment().cloned()),
+            },
+        }
+    }
+
     /// Registers a Wildcard Update, storing it in a dedicated in-memory structure and on disk if
     /// the Storage persistence capability is set to `Durable`.
     ///
@@ -482,6 +1396,187 @@ impl StorageService {
         None
     }
 
+    /// Indexes the value of the provided key in the secondary indexes of the Storage, if any.
+    ///
+    /// It must be called once the value is stored, by any code path writing in the Storage.
+    pub(crate) async fn index_put(
+        &self,
+        stripped_key: &Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+        encoding: &Encoding,
+        attachment: IndexedAttachment<'_>,
+    ) {
+        if self.configuration.indexes.is_empty() {
+            return;
+        }
+
+        self.indexes
+            .write()
+            .await
+            .insert(stripped_key, timestamp, encoding, attachment);
+    }
+
+    /// Removes the provided key from the secondary indexes of the Storage, if any.
+    ///
+    /// It must be called once the key is deleted, by any code path writing in the Storage.
+    pub(crate) async fn index_delete(
+        &self,
+        stripped_key: &Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+    ) {
+        if self.configuration.indexes.is_empty() {
+            return;
+        }
+
+        self.indexes.write().await.remove(stripped_key, timestamp);
+    }
+
+    /// Puts a value obtained through an alignment in the Storage and, if it was stored, indexes it
+    /// and publishes the change.
+    ///
+    /// The `action` is the one that led to this value: a `Put` or a `WildcardPut`.
+    pub(crate) async fn store_aligned_put(
+        &self,
+        stripped_key: Option<OwnedKeyExpr>,
+        value: Value,
+        timestamp: Timestamp,
+        action: &Action,
+    ) -> ZResult<StorageInsertionResult> {
+        let encoding = value.encoding().clone();
+        let size = value.payload().len() as u64;
+        let mut storage = self.storage.lock().await;
+        let result = storage.put(stripped_key.clone(), value, timestamp).await;
+        let change_seq = result
+            .as_ref()
+            .ok()
+            .and_then(|result| self.next_change_seq(result));
+        if let Ok(result) = &result {
+            self.account_write(&stripped_key, timestamp, Some(size), result)
+                .await;
+        }
+        drop(storage);
+        if !matches!(result, Ok(StorageInsertionResult::Outdated) | Err(_)) {
+            self.index_put(
+                &stripped_key,
+                timestamp,
+                &encoding,
+                IndexedAttachment::Unknown,
+            )
+            .await;
+            self.publish_aligned_change(change_seq, &stripped_key, action, timestamp)
+                .await;
+        }
+        result
+    }
+
+    /// Deletes a key from the Storage following an alignment and, if it was deleted, removes it
+    /// from the indexes and publishes the change.
+    ///
+    /// The `action` is the one that led to this deletion: a `Delete` or a `WildcardDelete`.
+    pub(crate) async fn store_aligned_delete(
+        &self,
+        stripped_key: Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+        action: &Action,
+    ) -> ZResult<StorageInsertionResult> {
+        let mut storage = self.storage.lock().await;
+        let result = storage.delete(stripped_key.clone(), timestamp).await;
+        let change_seq = result
+            .as_ref()
+            .ok()
+            .and_then(|result| self.next_change_seq(result));
+        if let Ok(result) = &result {
+            self.account_write(&stripped_key, timestamp, None, result)
+                .await;
+        }
+        drop(storage);
+        if !matches!(result, Ok(StorageInsertionResult::Outdated) | Err(_)) {
+            self.index_delete(&stripped_key, timestamp).await;
+            self.publish_aligned_change(change_seq, &stripped_key, action, timestamp)
+                .await;
+        }
+        result
+    }
+
+    async fn publish_aligned_change(
+        &self,
+        change_seq: Option<u64>,
+        stripped_key: &Option<OwnedKeyExpr>,
+        action: &Action,
+        timestamp: Timestamp,
+    ) {
+        if change_seq.is_none() {
+            return;
+        }
+        match crate::prefix(
+            self.configuration.strip_prefix.as_ref(),
+            stripped_key.as_ref(),
+        ) {
+            Ok(key) => {
+                self.publish_change(change_seq, &key, action, timestamp, ChangeOrigin::Alignment)
+                    .await
+            }
+            Err(e) => tracing::error!("{e:?}"),
+        }
+    }
+
+    /// Returns the sequence number of the change resulting from a write on the Storage, if the
+    /// write changed the Storage and its change feed is enabled.
+    ///
+    /// It must be called while holding the lock on the Storage, see [ChangeFeed::next_seq].
+    fn next_change_seq(&self, result: &StorageInsertionResult) -> Option<u64> {
+        match result {
+            StorageInsertionResult::Outdated => None,
+            _ => self.changes.as_ref().map(|changes| changes.next_seq()),
+        }
+    }
+
+    /// Accounts, if the bytes stored are accounted, a write of the Storage: a put of `size` bytes or,
+    /// if `size` is `None`, a delete.
+    ///
+    /// It must be called while holding the lock on the Storage, such that the writes are accounted
+    /// in the order they were performed.
+    async fn account_write(
+        &self,
+        stripped_key: &Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+        size: Option<u64>,
+        result: &StorageInsertionResult,
+    ) {
+        if matches!(result, StorageInsertionResult::Outdated) {
+            return;
+        }
+        if let Some(stored_bytes) = self.stored_bytes.lock().await.as_mut() {
+            match size {
+                Some(size) => stored_bytes.put(stripped_key.clone(), timestamp, size),
+                None => stored_bytes.delete(stripped_key),
+            }
+        }
+    }
+
+    /// Publishes, if the change feed of the Storage is enabled, the change `change_seq`.
+    async fn publish_change(
+        &self,
+        change_seq: Option<u64>,
+        key: &OwnedKeyExpr,
+        action: &Action,
+        timestamp: Timestamp,
+        origin: ChangeOrigin,
+    ) {
+        if let (Some(changes), Some(seq)) = (&self.changes, change_seq) {
+            changes.publish(seq, key, action, timestamp, origin).await;
+        }
+    }
+
+    /// Returns `true` if every version of the keys must be recorded in the Cache, i.e. if the
+    /// Storage keeps all the values of a key expression and is replicated.
+    ///
+    /// The Cache is then emptied into the Replication Log at each publication of the Digest.
+    /// Without replication, nothing would empty it, hence the versions are not recorded.
+    fn records_versions(&self) -> bool {
+        self.capability.history == History::All && self.cache_latest.replication_log.is_some()
+    }
+
     /// Returns a guard over the cache if the provided [Timestamp] is more recent than what is kept
     /// in the Storage for the `stripped_key`. Otherwise returns `None`.
     ///
@@ -501,32 +1596,46 @@ impl StorageService {
         new_event: &Event,
     ) -> Option<RwLockWriteGuard<'_, LatestUpdates>> {
         let cache_guard = self.cache_latest.latest_updates.write().await;
-        if let Some(event) = cache_guard.get(&new_event.log_key()) {
+        if self.is_latest(&cache_guard, new_event).await {
+            return Some(cache_guard);
+        }
+
+        None
+    }
+
+    /// Returns `true` if the provided [Event] is more recent than what is kept for its
+    /// `stripped_key`, looking up first in the provided cache then in the Replication Log or in the
+    /// Storage.
+    async fn is_latest(&self, cache: &LatestUpdates, new_event: &Event) -> bool {
+        if let Some(event) = cache.get(&new_event.log_key()) {
             if new_event.timestamp > event.timestamp {
-                return Some(cache_guard);
+                return true;
             }
         }
 
         if let Some(replication_log) = &self.cache_latest.replication_log {
             if let Some(event) = replication_log.read().await.lookup(new_event) {
                 if new_event.timestamp <= event.timestamp {
-                    return None;
+                    return false;
                 }
             }
         } else {
             let mut storage = self.storage.lock().await;
             // FIXME: An actual error from the underlying Storage cannot be distinguished from a
             //        missing entry.
-            if let Ok(stored_data) = storage.get(new_event.stripped_key.clone(), "").await {
+            if let Ok(stored_data) = storage
+                .get(StorageQuery::latest(new_event.stripped_key.clone()))
+                .await
+            {
                 for data in stored_data {
                     if data.timestamp > new_event.timestamp {
-                        return None;
+                        return false;
                     }
                 }
             }
         }
 
-        Some(cache_guard)
+        true
     }
 
     async fn reply_query(&self, query: ZResult<zenoh::query::Query>) {
@@ -541,9 +1650,60 @@ impl StorageService {
 
         let prefix = self.configuration.strip_prefix.as_ref();
 
+        // The key is set, for each matching key, right before requesting the Storage.
+        let parameters = match StorageQuery::from_parameters(None, q.parameters()) {
+            Ok(storage_query) => self
+                .indexes
+                .read()
+                .await
+                .filter_from_parameters(q.parameters())
+                .map(|filter| (storage_query, filter)),
+            Err(e) => Err(e),
+        };
+        let (storage_query, filter) = match parameters {
+            Ok(parameters) => parameters,
+            Err(e) => {
+                tracing::warn!("Storage '{}' received an invalid query: {e}", self.name);
+                if let Err(e) = q.reply_err(e.to_string()).await {
+                    tracing::warn!(
+                        "Storage '{}' raised an error replying a query: {}",
+                        self.name,
+                        e
+                    )
+                }
+                return;
+            }
+        };
+
+        // The filters on the attachments can't be evaluated on the values whose attachment is
+        // unknown: rather than silently ignoring them, the query is rejected.
+        if filter.as_ref().is_some_and(Filter::uses_attachment) {
+            let nb_unknown = self.count_unknown_attachments(q.key_expr()).await;
+            if nb_unknown > 0 {
+                let e = format!(
+                    "The attachments of {nb_unknown} value(s) matching < {} > are unknown (restored \
+                     when the Storage started, obtained through an alignment or imported from a \
+                     snapshot): the `{FILTER_KEY}` on the attachments can't be evaluated",
+                    q.key_expr()
+                );
+                tracing::warn!("Storage '{}' rejected a query: {e}", self.name);
+                if let Err(e) = q.reply_err(e).await {
+                    tracing::warn!(
+                        "Storage '{}' raised an error replying a query: {}",
+                        self.name,
+                        e
+                    )
+                }
+                return;
+            }
+        }
+
         if q.key_expr().is_wild() {
             // resolve key expr into individual keys
-            let matching_keys = self.get_matching_keys(q.key_expr()).await;
+            let matching_keys = match &filter {
+                Some(filter) => self.get_filtered_keys(q.key_expr(), filter).await,
+                None => self.get_matching_keys(q.key_expr()).await,
+            };
             let mut storage = self.storage.lock().await;
             for key in matching_keys {
                 let stripped_key = match crate::strip_prefix(prefix, &key.clone().into()) {
@@ -554,7 +1714,11 @@ impl StorageService {
                         return;
                     }
                 };
-                match storage.get(stripped_key, q.parameters().as_str()).await {
+                let storage_query = StorageQuery {
+                    key: stripped_key,
+                    ..storage_query.clone()
+                };
+                match storage.get(storage_query).await {
                     Ok(stored_data) => {
                         for entry in stored_data {
                             if let Err(e) = q
@@ -586,8 +1750,17 @@ impl StorageService {
                     return;
                 }
             };
+            if let Some(filter) = &filter {
+                if !self.indexes.read().await.matches(filter, &stripped_key) {
+                    return;
+                }
+            }
+            let storage_query = StorageQuery {
+                key: stripped_key,
+                ..storage_query
+            };
             let mut storage = self.storage.lock().await;
-            match storage.get(stripped_key, q.parameters().as_str()).await {
+            match storage.get(storage_query).await {
                 Ok(stored_data) => {
                     for entry in stored_data {
                         if let Err(e) = q
@@ -611,6 +1784,37 @@ impl StorageService {
         }
     }
 
+    /// Returns the keys intersecting the provided key expression that are selected by the filter,
+    /// looking them up in the secondary indexes instead of going through the Storage.
+    async fn get_filtered_keys(
+        &self,
+        key_expr: &KeyExpr<'_>,
+        filter: &Filter,
+    ) -> Vec<OwnedKeyExpr> {
+        let prefix = self.configuration.strip_prefix.as_ref();
+        self.indexes
+            .read()
+            .await
+            .select(filter)
+            .into_iter()
+            .filter_map(|stripped_key| crate::prefix(prefix, stripped_key.as_ref()).ok())
+            .filter(|full_key| key_expr.intersects(full_key))
+            .collect()
+    }
+
+    /// Returns the number of keys intersecting the provided key expression whose most recent value
+    /// has an unknown attachment.
+    async fn count_unknown_attachments(&self, key_expr: &KeyExpr<'_>) -> usize {
+        let prefix = self.configuration.strip_prefix.as_ref();
+        self.indexes
+            .read()
+            .await
+            .unknown_attachments()
+            .filter_map(|stripped_key| crate::prefix(prefix, stripped_key.as_ref()).ok())
+            .filter(|full_key| key_expr.intersects(full_key))
+            .count()
+    }
+
     async fn get_matching_keys(&self, key_expr: &KeyExpr<'_>) -> Vec<OwnedKeyExpr> {
         let mut result = Vec::new();
         // @TODO: if cache exists, use that to get the list
@@ -618,27 +1822,26 @@ impl StorageService {
 
         let prefix = self.configuration.strip_prefix.as_ref();
 
-        match storage.get_all_entries().await {
-            Ok(entries) => {
-                for (k, _ts) in entries {
-                    // @TODO: optimize adding back the prefix (possible inspiration from https://github.com/eclipse-zenoh/zenoh/blob/0.5.0-beta.9/backends/traits/src/utils.rs#L79)
-                    let Ok(full_key) = crate::prefix(prefix, k.as_ref()) else {
-                        tracing::error!(
-                            "Internal error: empty key with no `strip_prefix` configured"
-                        );
-                        continue;
-                    };
+        if let Err(e) = for_each_entries_page(storage.as_ref(), |entries| {
+            for (k, _ts) in entries {
+                // @TODO: optimize adding back the prefix (possible inspiration from https://github.com/eclipse-zenoh/zenoh/blob/0.5.0-beta.9/backends/traits/src/utils.rs#L79)
+                let Ok(full_key) = crate::prefix(prefix, k.as_ref()) else {
+                    tracing::error!("Internal error: empty key with no `strip_prefix` configured");
+                    continue;
+                };
 
-                    if key_expr.intersects(&full_key.clone()) {
-                        result.push(full_key);
-                    }
+                if key_expr.intersects(&full_key.clone()) {
+                    result.push(full_key);
                 }
             }
-            Err(e) => tracing::warn!(
+        })
+        .await
+        {
+            tracing::warn!(
                 "Storage '{}' raised an error while retrieving keys: {}",
                 self.name,
                 e
-            ),
+            );
         }
         result
     }
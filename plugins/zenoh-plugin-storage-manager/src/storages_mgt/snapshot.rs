@@ -0,0 +1,408 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! A snapshot is a portable dump of the content of a Storage.
+//!
+//! It is a UTF-8 text file in the [JSON Lines](https://jsonlines.org) format: its first line is a
+//! [SnapshotHeader] and each following line is a [SnapshotEntry], i.e. a value stored for a key.
+//!
+//! ```text
+//! {"format":"zenoh-storage-snapshot","version":1,"storage":"demo","key_expr":"demo/**"}
+//! {"key":"demo/a","timestamp":"7386690599959157260/33","encoding":"text/plain","payload":"YQ=="}
+//! ```
+//!
+//! The keys are complete (i.e. the `strip_prefix` of the Storage is not removed) and the payloads
+//! are encoded in base64, such that a snapshot does not depend on the Storage nor on the backend
+//! it was exported from.
+//!
+//! The snapshots are files of the `snapshots_dir` of the Storage: the path of a snapshot is
+//! relative to that directory and cannot leave it.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, ErrorKind, Lines, Write},
+    path::{Component, Path, PathBuf},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{Receiver, Sender};
+use zenoh::{
+    bytes::{Encoding, ZBytes},
+    internal::{bail, zerror},
+    key_expr::OwnedKeyExpr,
+    time::Timestamp,
+    Result as ZResult,
+};
+
+/// The value of the `format` field of every [SnapshotHeader].
+pub(crate) const SNAPSHOT_FORMAT: &str = "zenoh-storage-snapshot";
+
+/// The version of the snapshot format produced by this plugin.
+pub(crate) const SNAPSHOT_VERSION: u32 = 1;
+
+/// The first line of a snapshot, describing where it comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SnapshotHeader {
+    pub(crate) format: String,
+    pub(crate) version: u32,
+    /// The name of the Storage the snapshot was exported from.
+    pub(crate) storage: String,
+    /// The key expression of the Storage the snapshot was exported from.
+    pub(crate) key_expr: OwnedKeyExpr,
+}
+
+impl SnapshotHeader {
+    pub(crate) fn new(storage: &str, key_expr: OwnedKeyExpr) -> Self {
+        Self {
+            format: SNAPSHOT_FORMAT.to_string(),
+            version: SNAPSHOT_VERSION,
+            storage: storage.to_string(),
+            key_expr,
+        }
+    }
+}
+
+/// A value of a key, as it was stored in the Storage.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SnapshotEntry {
+    pub(crate) key: OwnedKeyExpr,
+    pub(crate) timestamp: Timestamp,
+    pub(crate) encoding: Encoding,
+    pub(crate) payload: ZBytes,
+}
+
+/// The textual representation of a [SnapshotEntry], as written in a snapshot.
+#[derive(Serialize, Deserialize)]
+struct SnapshotLine {
+    key: OwnedKeyExpr,
+    timestamp: String,
+    encoding: String,
+    payload: String,
+}
+
+impl From<&SnapshotEntry> for SnapshotLine {
+    fn from(entry: &SnapshotEntry) -> Self {
+        Self {
+            key: entry.key.clone(),
+            timestamp: entry.timestamp.to_string(),
+            encoding: entry.encoding.to_string(),
+            payload: STANDARD.encode(entry.payload.to_bytes()),
+        }
+    }
+}
+
+impl TryFrom<SnapshotLine> for SnapshotEntry {
+    type Error = zenoh::Error;
+
+    fn try_from(line: SnapshotLine) -> ZResult<Self> {
+        let timestamp = line
+            .timestamp
+            .parse::<Timestamp>()
+            .map_err(|e| zerror!("Invalid timestamp < {} >: {e:?}", line.timestamp))?;
+        let payload = STANDARD
+            .decode(&line.payload)
+            .map_err(|e| zerror!("Invalid payload of < {} >: {e}", line.key))?;
+
+        Ok(Self {
+            key: line.key,
+            timestamp,
+            encoding: Encoding::from(line.encoding),
+            payload: ZBytes::from(payload),
+        })
+    }
+}
+
+/// Writes a snapshot, entry by entry, into the provided writer.
+pub(crate) struct SnapshotWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> SnapshotWriter<W> {
+    /// Creates a new `SnapshotWriter`, writing the provided header.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the header could not be written.
+    pub(crate) fn new(mut writer: W, header: &SnapshotHeader) -> ZResult<Self> {
+        write_line(&mut writer, header)?;
+        Ok(Self { writer })
+    }
+
+    /// Appends the provided entry to the snapshot.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the entry could not be written.
+    pub(crate) fn write_entry(&mut self, entry: &SnapshotEntry) -> ZResult<()> {
+        write_line(&mut self.writer, &SnapshotLine::from(entry))
+    }
+
+    /// Flushes the snapshot and returns the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the writer could not be flushed.
+    pub(crate) fn finish(mut self) -> ZResult<W> {
+        self.writer
+            .flush()
+            .map_err(|e| zerror!("Failed to flush snapshot: {e}"))?;
+        Ok(self.writer)
+    }
+}
+
+fn write_line(writer: &mut impl Write, line: &impl Serialize) -> ZResult<()> {
+    serde_json::to_writer(&mut *writer, line)
+        .map_err(|e| zerror!("Failed to write snapshot: {e}"))?;
+    writer
+        .write_all(b"\n")
+        .map_err(|e| zerror!("Failed to write snapshot: {e}"))?;
+    Ok(())
+}
+
+/// Reads a snapshot, entry by entry, from the provided reader.
+///
+/// The entries are obtained by iterating over the `SnapshotReader`. Empty lines are ignored.
+pub(crate) struct SnapshotReader<R: BufRead> {
+    header: SnapshotHeader,
+    lines: Lines<R>,
+    line_number: usize,
+}
+
+impl<R: BufRead> SnapshotReader<R> {
+    /// Creates a new `SnapshotReader`, reading and checking the header of the snapshot.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the header could not be read, or if it does not
+    /// describe a snapshot in a version supported by this plugin.
+    pub(crate) fn new(reader: R) -> ZResult<Self> {
+        let mut lines = reader.lines();
+        let header = match lines.next() {
+            Some(Ok(line)) => serde_json::from_str::<SnapshotHeader>(&line)
+                .map_err(|e| zerror!("Invalid snapshot header: {e}"))?,
+            Some(Err(e)) => bail!("Failed to read snapshot header: {e}"),
+            None => bail!("Empty snapshot: missing header"),
+        };
+
+        if header.format != SNAPSHOT_FORMAT {
+            bail!("Unsupported snapshot format < {} >", header.format);
+        }
+        if header.version != SNAPSHOT_VERSION {
+            bail!(
+                "Unsupported snapshot version {} (supported: {SNAPSHOT_VERSION})",
+                header.version
+            );
+        }
+
+        Ok(Self {
+            header,
+            lines,
+            line_number: 1,
+        })
+    }
+
+    pub(crate) fn header(&self) -> &SnapshotHeader {
+        &self.header
+    }
+}
+
+impl<R: BufRead> Iterator for SnapshotReader<R> {
+    type Item = ZResult<SnapshotEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = self.lines.next()?;
+            self.line_number += 1;
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    return Some(Err(zerror!(
+                        "Failed to read line {} of snapshot: {e}",
+                        self.line_number
+                    )
+                    .into()))
+                }
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            return Some(
+                serde_json::from_str::<SnapshotLine>(&line)
+                    .map_err(|e| {
+                        zerror!(
+                            "Invalid entry at line {} of snapshot: {e}",
+                            self.line_number
+                        )
+                        .into()
+                    })
+                    .and_then(SnapshotEntry::try_from),
+            );
+        }
+    }
+}
+
+/// Returns the path of the snapshot `path` within the directory `dir`.
+///
+/// The symbolic links in the returned path are resolved, up to the first component that does not
+/// exist (e.g. the snapshot to export).
+///
+/// # Errors
+///
+/// This function will return an error if `dir` can't be resolved, if `path` is empty, absolute, if
+/// any of its components is not a plain file or directory name (e.g. `..`) or if it resolves,
+/// through a symbolic link, outside of `dir`: a snapshot can only be located in `dir`.
+pub(crate) fn resolve_path(dir: &Path, path: &str) -> ZResult<PathBuf> {
+    let path = Path::new(path);
+    if path.as_os_str().is_empty() {
+        bail!("Empty snapshot path");
+    }
+    if !path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        bail!("Invalid snapshot path {path:?}: only paths relative to the snapshots directory are accepted");
+    }
+
+    let dir = dir
+        .canonicalize()
+        .map_err(|e| zerror!("Invalid snapshots directory {dir:?}: {e}"))?;
+    let mut resolved = dir.clone();
+    let mut components = path.components();
+    for component in components.by_ref() {
+        let candidate = resolved.join(component);
+        match candidate.canonicalize() {
+            Ok(candidate) => resolved = candidate,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                resolved = candidate;
+                break;
+            }
+            Err(e) => bail!("Invalid snapshot path {path:?}: {e}"),
+        }
+        if !resolved.starts_with(&dir) {
+            bail!("Invalid snapshot path {path:?}: it resolves outside of the snapshots directory");
+        }
+    }
+
+    Ok(resolved.join(components.as_path()))
+}
+
+/// An item sent to [write_file].
+#[derive(Debug)]
+pub(crate) enum SnapshotItem {
+    /// An entry to append to the snapshot.
+    Entry(SnapshotEntry),
+    /// The end of the entries: the snapshot is complete and can replace any existing one.
+    Commit,
+}
+
+/// Writes the snapshot at the provided path, with the entries received on the `items` channel,
+/// returning the number of entries written.
+///
+/// The snapshot is first written in a temporary file, renamed only once a [SnapshotItem::Commit]
+/// is received: an existing snapshot is never left partially overwritten. If the `items` channel
+/// is closed before, or if the snapshot could not be written, the temporary file is removed.
+///
+/// This function performs blocking I/O: it must be called in a blocking task.
+///
+/// # Errors
+///
+/// This function will return an error if the snapshot could not be written or if the `items`
+/// channel was closed without a [SnapshotItem::Commit].
+pub(crate) fn write_file(
+    path: &Path,
+    header: &SnapshotHeader,
+    items: Receiver<SnapshotItem>,
+) -> ZResult<usize> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".part");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    let nb_entries = match write_tmp_file(&tmp_path, header, items) {
+        Ok(nb_entries) => nb_entries,
+        Err(e) => {
+            if let Err(remove_err) = std::fs::remove_file(&tmp_path) {
+                if remove_err.kind() != std::io::ErrorKind::NotFound {
+                    tracing::warn!(
+                        "Failed to remove incomplete snapshot file {tmp_path:?}: {remove_err}"
+                    );
+                }
+            }
+            return Err(e);
+        }
+    };
+
+    std::fs::rename(&tmp_path, path)
+        .map_err(|e| zerror!("Failed to rename {tmp_path:?} to {path:?}: {e}"))?;
+
+    Ok(nb_entries)
+}
+
+fn write_tmp_file(
+    tmp_path: &Path,
+    header: &SnapshotHeader,
+    mut items: Receiver<SnapshotItem>,
+) -> ZResult<usize> {
+    let file = File::create(tmp_path)
+        .map_err(|e| zerror!("Failed to create snapshot file {tmp_path:?}: {e}"))?;
+    let mut writer = SnapshotWriter::new(BufWriter::new(file), header)?;
+    let mut nb_entries = 0;
+    loop {
+        match items.blocking_recv() {
+            Some(SnapshotItem::Entry(entry)) => {
+                writer.write_entry(&entry)?;
+                nb_entries += 1;
+            }
+            Some(SnapshotItem::Commit) => break,
+            None => bail!("Snapshot aborted before all the entries were written"),
+        }
+    }
+
+    writer
+        .finish()?
+        .into_inner()
+        .map_err(|e| zerror!("Failed to flush snapshot file {tmp_path:?}: {e}"))?
+        .sync_all()
+        .map_err(|e| zerror!("Failed to sync snapshot file {tmp_path:?}: {e}"))?;
+
+    Ok(nb_entries)
+}
+
+/// Reads the snapshot at the provided path, sending its entries on the `entries` channel, and
+/// returns its header once all the entries were read.
+///
+/// The reading stops early, without error, if the `entries` channel is closed.
+///
+/// This function performs blocking I/O: it must be called in a blocking task.
+///
+/// # Errors
+///
+/// This function will return an error if the snapshot could not be read or if it is invalid.
+pub(crate) fn read_file(path: &Path, entries: Sender<SnapshotEntry>) -> ZResult<SnapshotHeader> {
+    let file = File::open(path).map_err(|e| zerror!("Failed to open snapshot {path:?}: {e}"))?;
+    let reader = SnapshotReader::new(BufReader::new(file))?;
+    let header = reader.header().clone();
+    for entry in reader {
+        if entries.blocking_send(entry?).is_err() {
+            break;
+        }
+    }
+
+    Ok(header)
+}
+
+#[cfg(test)]
+#[path = "tests/snapshot.test.rs"]
+mod tests;
@@ -0,0 +1,93 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::{str::FromStr, time::Duration};
+
+use serde_json::json;
+use zenoh::{
+    key_expr::OwnedKeyExpr,
+    time::{Timestamp, TimestampId, NTP64},
+};
+
+use super::{change_to_json, purge_to_json, ChangeOrigin};
+use crate::replication::Action;
+
+#[test]
+fn test_change_to_json() {
+    let key = OwnedKeyExpr::from_str("demo/a").unwrap();
+    let timestamp = Timestamp::new(
+        NTP64::from(Duration::from_secs(1)),
+        TimestampId::try_from([1]).unwrap(),
+    );
+
+    assert_eq!(
+        change_to_json(0, &key, &Action::Put, &timestamp, ChangeOrigin::Live),
+        json!({
+            "seq": 0,
+            "key": "demo/a",
+            "action": "put",
+            "timestamp": timestamp.to_string(),
+            "origin": "live",
+        })
+    );
+
+    assert_eq!(
+        change_to_json(
+            1,
+            &key,
+            &Action::WildcardDelete(OwnedKeyExpr::from_str("demo/**").unwrap()),
+            &timestamp,
+            ChangeOrigin::Alignment
+        ),
+        json!({
+            "seq": 1,
+            "key": "demo/a",
+            "action": "wildcard_delete",
+            "wildcard": "demo/**",
+            "timestamp": timestamp.to_string(),
+            "origin": "alignment",
+        })
+    );
+
+    assert_eq!(
+        change_to_json(
+            2,
+            &key,
+            &Action::Delete,
+            &timestamp,
+            ChangeOrigin::Retention
+        )["origin"],
+        "retention"
+    );
+}
+
+#[test]
+fn test_purge_to_json() {
+    let key = OwnedKeyExpr::from_str("demo/a").unwrap();
+    let timestamp = Timestamp::new(
+        NTP64::from(Duration::from_secs(1)),
+        TimestampId::try_from([1]).unwrap(),
+    );
+
+    assert_eq!(
+        purge_to_json(3, &key, &timestamp),
+        json!({
+            "seq": 3,
+            "key": "demo/a",
+            "action": "purge",
+            "timestamp": timestamp.to_string(),
+            "origin": "retention",
+        })
+    );
+}
@@ -0,0 +1,233 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::{collections::HashSet, str::FromStr, time::Duration};
+
+use zenoh::{
+    bytes::{Encoding, ZBytes},
+    key_expr::OwnedKeyExpr,
+    query::Parameters,
+    time::{Timestamp, TimestampId, NTP64},
+};
+use zenoh_backend_traits::config::{IndexConfig, IndexedField};
+
+use super::{IndexedAttachment, SecondaryIndexes};
+
+fn key(key: &str) -> Option<OwnedKeyExpr> {
+    Some(OwnedKeyExpr::from_str(key).unwrap())
+}
+
+fn timestamp(secs: u64) -> Timestamp {
+    Timestamp::new(
+        NTP64::from(Duration::from_secs(secs)),
+        TimestampId::try_from([1]).unwrap(),
+    )
+}
+
+fn indexes() -> SecondaryIndexes {
+    SecondaryIndexes::new(&[
+        IndexConfig {
+            name: "format".to_string(),
+            field: IndexedField::Encoding,
+        },
+        IndexConfig {
+            name: "tags".to_string(),
+            field: IndexedField::Attachment {
+                separator: Some(",".to_string()),
+            },
+        },
+    ])
+}
+
+fn select(indexes: &SecondaryIndexes, expression: &str) -> HashSet<Option<OwnedKeyExpr>> {
+    indexes.select(&indexes.parse_filter(expression).unwrap())
+}
+
+#[test]
+fn test_filter_parsing() {
+    let indexes = indexes();
+
+    assert!(indexes.parse_filter("format:application/json").is_ok());
+    assert!(indexes
+        .parse_filter("format:application/json,tags:a:b")
+        .is_ok());
+    assert!(indexes.parse_filter("format").is_err());
+    assert!(indexes.parse_filter("size:12").is_err());
+    assert!(indexes.parse_filter("").is_err());
+
+    let parameters = Parameters::from("_time=[..];_filter=tags:a");
+    assert!(indexes
+        .filter_from_parameters(&parameters)
+        .unwrap()
+        .is_some());
+    assert!(indexes
+        .filter_from_parameters(&Parameters::from("_time=[..]"))
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_insert_select_remove() {
+    let mut indexes = indexes();
+
+    indexes.insert(
+        &key("a"),
+        timestamp(1),
+        &Encoding::APPLICATION_JSON,
+        IndexedAttachment::Known(Some(&ZBytes::from("x, y"))),
+    );
+    indexes.insert(
+        &key("b"),
+        timestamp(1),
+        &Encoding::APPLICATION_JSON,
+        IndexedAttachment::Known(Some(&ZBytes::from("y"))),
+    );
+    indexes.insert(
+        &key("c"),
+        timestamp(1),
+        &Encoding::TEXT_PLAIN,
+        IndexedAttachment::Known(None),
+    );
+
+    assert_eq!(
+        select(&indexes, "format:application/json"),
+        HashSet::from([key("a"), key("b")])
+    );
+    assert_eq!(
+        select(&indexes, "format:application/json,tags:x"),
+        HashSet::from([key("a")])
+    );
+    assert_eq!(
+        select(&indexes, "tags:y"),
+        HashSet::from([key("a"), key("b")])
+    );
+    assert!(select(&indexes, "format:text/plain,tags:y").is_empty());
+
+    let filter = indexes.parse_filter("tags:x").unwrap();
+    assert!(indexes.matches(&filter, &key("a")));
+    assert!(!indexes.matches(&filter, &key("b")));
+
+    // An older value does not replace the values indexed for a key.
+    indexes.insert(
+        &key("a"),
+        timestamp(0),
+        &Encoding::TEXT_PLAIN,
+        IndexedAttachment::Known(None),
+    );
+    assert!(indexes.matches(&filter, &key("a")));
+
+    // A more recent value does.
+    indexes.insert(
+        &key("a"),
+        timestamp(2),
+        &Encoding::TEXT_PLAIN,
+        IndexedAttachment::Known(Some(&ZBytes::from("z"))),
+    );
+    assert!(!indexes.matches(&filter, &key("a")));
+    assert_eq!(
+        select(&indexes, "format:text/plain"),
+        HashSet::from([key("a"), key("c")])
+    );
+
+    // An older delete is ignored, a more recent one removes the key.
+    indexes.remove(&key("b"), timestamp(0));
+    assert_eq!(select(&indexes, "tags:y"), HashSet::from([key("b")]));
+    indexes.remove(&key("b"), timestamp(2));
+    assert!(select(&indexes, "tags:y").is_empty());
+
+    // A value older than the delete does not index the key again, a more recent one does.
+    indexes.insert(
+        &key("b"),
+        timestamp(1),
+        &Encoding::APPLICATION_JSON,
+        IndexedAttachment::Known(Some(&ZBytes::from("y"))),
+    );
+    assert!(select(&indexes, "tags:y").is_empty());
+    indexes.insert(
+        &key("b"),
+        timestamp(3),
+        &Encoding::APPLICATION_JSON,
+        IndexedAttachment::Known(Some(&ZBytes::from("y"))),
+    );
+    assert_eq!(select(&indexes, "tags:y"), HashSet::from([key("b")]));
+}
+
+#[test]
+fn test_unknown_attachments() {
+    let mut indexes = indexes();
+
+    // The values derived from the encoding of a value with an unknown attachment are indexed.
+    indexes.insert(
+        &key("a"),
+        timestamp(1),
+        &Encoding::APPLICATION_JSON,
+        IndexedAttachment::Unknown,
+    );
+    indexes.insert(
+        &key("b"),
+        timestamp(1),
+        &Encoding::APPLICATION_JSON,
+        IndexedAttachment::Known(Some(&ZBytes::from("x"))),
+    );
+    assert_eq!(
+        select(&indexes, "format:application/json"),
+        HashSet::from([key("a"), key("b")])
+    );
+    assert_eq!(select(&indexes, "tags:x"), HashSet::from([key("b")]));
+    assert_eq!(
+        indexes.unknown_attachments().collect::<Vec<_>>(),
+        vec![&key("a")]
+    );
+
+    assert!(!indexes.parse_filter("format:a").unwrap().uses_attachment());
+    assert!(indexes
+        .parse_filter("format:a,tags:x")
+        .unwrap()
+        .uses_attachment());
+
+    // A more recent value with a known attachment, or a delete, makes the attachment known again.
+    indexes.insert(
+        &key("a"),
+        timestamp(2),
+        &Encoding::APPLICATION_JSON,
+        IndexedAttachment::Known(Some(&ZBytes::from("x"))),
+    );
+    assert_eq!(indexes.unknown_attachments().count(), 0);
+    assert_eq!(
+        select(&indexes, "tags:x"),
+        HashSet::from([key("a"), key("b")])
+    );
+    indexes.insert(
+        &key("b"),
+        timestamp(2),
+        &Encoding::APPLICATION_JSON,
+        IndexedAttachment::Unknown,
+    );
+    assert_eq!(select(&indexes, "tags:x"), HashSet::from([key("a")]));
+    indexes.remove(&key("b"), timestamp(3));
+    assert_eq!(indexes.unknown_attachments().count(), 0);
+
+    // Without any index derived from the attachments, no key is tracked.
+    let mut indexes = SecondaryIndexes::new(&[IndexConfig {
+        name: "format".to_string(),
+        field: IndexedField::Encoding,
+    }]);
+    indexes.insert(
+        &key("a"),
+        timestamp(1),
+        &Encoding::APPLICATION_JSON,
+        IndexedAttachment::Unknown,
+    );
+    assert_eq!(indexes.unknown_attachments().count(), 0);
+}
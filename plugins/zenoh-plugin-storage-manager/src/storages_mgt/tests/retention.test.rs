@@ -0,0 +1,178 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::{str::FromStr, time::Duration};
+
+use zenoh::{
+    key_expr::OwnedKeyExpr,
+    time::{Timestamp, TimestampId, NTP64},
+};
+use zenoh_backend_traits::{
+    config::{MaxAgeRule, RetentionConfig},
+    History,
+};
+
+use super::{expiry_timestamp, is_aged, max_age, OldestEntries, RetainedEntry, StoredBytes};
+
+fn timestamp(secs: u64) -> Timestamp {
+    Timestamp::new(
+        NTP64::from(Duration::from_secs(secs)),
+        TimestampId::try_from([1]).unwrap(),
+    )
+}
+
+fn entry(key: &str, secs: u64, size: u64) -> RetainedEntry {
+    RetainedEntry {
+        stripped_key: Some(OwnedKeyExpr::from_str(key).unwrap()),
+        timestamp: timestamp(secs),
+        size,
+    }
+}
+
+fn keys(entries: &[RetainedEntry]) -> Vec<String> {
+    entries
+        .iter()
+        .map(|entry| entry.stripped_key.as_ref().unwrap().to_string())
+        .collect()
+}
+
+fn select_oldest(excess_keys: usize, excess_bytes: u64, entries: &[RetainedEntry]) -> Vec<String> {
+    let mut oldest = OldestEntries::new(excess_keys, excess_bytes);
+    for entry in entries {
+        oldest.push(entry.clone());
+    }
+    keys(&oldest.into_sorted_vec())
+}
+
+#[test]
+fn test_max_age() {
+    let prefix = OwnedKeyExpr::from_str("test").unwrap();
+    let config = RetentionConfig {
+        max_age: vec![
+            MaxAgeRule {
+                key_expr: OwnedKeyExpr::from_str("test/**").unwrap(),
+                age: Duration::from_secs(100),
+            },
+            MaxAgeRule {
+                key_expr: OwnedKeyExpr::from_str("test/tmp/*").unwrap(),
+                age: Duration::from_secs(10),
+            },
+        ],
+        ..Default::default()
+    };
+    let key = |key: &str| Some(OwnedKeyExpr::from_str(key).unwrap());
+
+    assert_eq!(
+        max_age(&config, Some(&prefix), &key("a")),
+        Some(Duration::from_secs(100))
+    );
+    // The shortest age applies.
+    assert_eq!(
+        max_age(&config, Some(&prefix), &key("tmp/a")),
+        Some(Duration::from_secs(10))
+    );
+    assert_eq!(max_age(&config, None, &key("a")), None);
+
+    let now = NTP64::from(Duration::from_secs(200));
+    assert!(is_aged(&timestamp(50), Duration::from_secs(100), now));
+    assert!(!is_aged(&timestamp(100), Duration::from_secs(100), now));
+    assert!(!is_aged(&timestamp(150), Duration::from_secs(100), now));
+}
+
+#[test]
+fn test_oldest_entries() {
+    let entries = vec![
+        entry("c", 30, 10),
+        entry("a", 10, 10),
+        entry("d", 30, 10),
+        entry("b", 20, 10),
+    ];
+
+    assert_eq!(select_oldest(1, 0, &entries), vec!["a"]);
+    // "c" and "d" have the same timestamp: the key decides.
+    assert_eq!(select_oldest(0, 25, &entries), vec!["a", "b", "c"]);
+    // Both bounds must be satisfied.
+    assert_eq!(select_oldest(2, 5, &entries), vec!["a", "b"]);
+    assert_eq!(select_oldest(1, 15, &entries), vec!["a", "b"]);
+    assert!(select_oldest(0, 0, &entries).is_empty());
+    // The selection can't exceed the entries.
+    assert_eq!(select_oldest(5, 0, &entries), vec!["a", "b", "c", "d"]);
+}
+
+#[test]
+fn test_expiry_timestamp() {
+    let expired = entry("a", 10, 0);
+    let expiry = expiry_timestamp(&expired.timestamp);
+    assert!(expiry > expired.timestamp);
+    assert_eq!(expiry.get_id(), expired.timestamp.get_id());
+    assert!(expiry < entry("a", 11, 0).timestamp);
+}
+
+#[test]
+fn test_stored_bytes() {
+    let a = Some(OwnedKeyExpr::from_str("a").unwrap());
+    let b = Some(OwnedKeyExpr::from_str("b").unwrap());
+
+    let mut latest = StoredBytes::new(History::Latest);
+    latest.put(a.clone(), timestamp(1), 10);
+    latest.put(a.clone(), timestamp(2), 4);
+    latest.put(b.clone(), timestamp(3), 7);
+    assert_eq!(latest.get(&a), 4);
+    assert_eq!(latest.total(), 11);
+    latest.delete(&b);
+    assert_eq!(latest.get(&b), 0);
+    assert_eq!(latest.total(), 4);
+
+    let mut all = StoredBytes::new(History::All);
+    all.put(a.clone(), timestamp(1), 10);
+    all.put(a.clone(), timestamp(2), 4);
+    assert_eq!(all.get(&a), 14);
+    assert_eq!(all.latest(&a), Some(timestamp(2)));
+    // The deleted versions are kept until they are purged.
+    all.delete(&a);
+    assert_eq!(all.get(&a), 14);
+    all.purge(&a, &timestamp(1));
+    assert_eq!(all.get(&a), 4);
+    all.purge(&a, &timestamp(2));
+    assert_eq!(all.get(&a), 0);
+    assert_eq!(all.total(), 0);
+
+    // The versions retrieved from the Storage replace the ones accounted.
+    all.put(b.clone(), timestamp(3), 5);
+    all.set(b.clone(), [(timestamp(1), 1), (timestamp(3), 5)]);
+    assert_eq!(all.get(&b), 6);
+    assert_eq!(all.total(), 6);
+}
+
+#[test]
+fn test_select_oldest_versions() {
+    let a = Some(OwnedKeyExpr::from_str("a").unwrap());
+    let b = Some(OwnedKeyExpr::from_str("b").unwrap());
+
+    let mut all = StoredBytes::new(History::All);
+    all.put(a.clone(), timestamp(1), 10);
+    all.put(b.clone(), timestamp(2), 10);
+    all.put(a.clone(), timestamp(3), 10);
+    all.put(b.clone(), timestamp(4), 10);
+
+    assert!(all.select_oldest_versions(40).is_empty());
+    let oldest = all.select_oldest_versions(25);
+    assert_eq!(
+        oldest
+            .iter()
+            .map(|version| (version.stripped_key.clone(), version.timestamp))
+            .collect::<Vec<_>>(),
+        vec![(a.clone(), timestamp(1)), (b.clone(), timestamp(2))]
+    );
+}
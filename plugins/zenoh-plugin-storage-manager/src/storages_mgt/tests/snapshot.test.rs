@@ -0,0 +1,179 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::{fs, io::Cursor, str::FromStr, time::Duration};
+
+use zenoh::{
+    bytes::{Encoding, ZBytes},
+    key_expr::OwnedKeyExpr,
+    time::{Timestamp, TimestampId, NTP64},
+};
+
+use super::{
+    resolve_path, write_file, SnapshotEntry, SnapshotHeader, SnapshotItem, SnapshotReader,
+    SnapshotWriter,
+};
+
+fn entry(key: &str, secs: u64, encoding: Encoding, payload: &[u8]) -> SnapshotEntry {
+    SnapshotEntry {
+        key: OwnedKeyExpr::from_str(key).unwrap(),
+        timestamp: Timestamp::new(
+            NTP64::from(Duration::from_secs(secs)),
+            TimestampId::try_from([1]).unwrap(),
+        ),
+        encoding,
+        payload: ZBytes::from(payload.to_vec()),
+    }
+}
+
+#[test]
+fn test_snapshot_round_trip() {
+    let header = SnapshotHeader::new("demo", OwnedKeyExpr::from_str("demo/**").unwrap());
+    let entries = vec![
+        entry("demo/a", 1, Encoding::TEXT_PLAIN, b"a"),
+        entry("demo/b", 2, Encoding::ZENOH_BYTES, &[0, 159, 146, 150]),
+        entry("demo/b", 3, Encoding::from("application/custom;v=2"), b""),
+    ];
+
+    let mut writer = SnapshotWriter::new(Vec::new(), &header).unwrap();
+    for entry in &entries {
+        writer.write_entry(entry).unwrap();
+    }
+    let bytes = writer.finish().unwrap();
+
+    // One line for the header, one line per entry.
+    assert_eq!(bytes.iter().filter(|&&b| b == b'\n').count(), 4);
+
+    let reader = SnapshotReader::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(reader.header(), &header);
+    let read_entries = reader.collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(read_entries, entries);
+}
+
+#[test]
+fn test_snapshot_invalid() {
+    // Empty snapshot.
+    assert!(SnapshotReader::new(Cursor::new(b"")).is_err());
+
+    // Unknown format.
+    assert!(SnapshotReader::new(Cursor::new(
+        br#"{"format":"other","version":1,"storage":"demo","key_expr":"demo/**"}"#
+    ))
+    .is_err());
+
+    // Unsupported version.
+    assert!(SnapshotReader::new(Cursor::new(
+        br#"{"format":"zenoh-storage-snapshot","version":42,"storage":"demo","key_expr":"demo/**"}"#
+    ))
+    .is_err());
+
+    // Empty lines are skipped, invalid entries are reported without stopping the iteration.
+    let snapshot =
+        br#"{"format":"zenoh-storage-snapshot","version":1,"storage":"demo","key_expr":"demo/**"}
+
+{"key":"demo/a","timestamp":"not-a-timestamp","encoding":"text/plain","payload":"YQ=="}
+{"key":"demo/b","timestamp":"0/1","encoding":"text/plain","payload":"Yg=="}
+"#;
+    let results = SnapshotReader::new(Cursor::new(snapshot))
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_err());
+    assert_eq!(
+        results[1].as_ref().unwrap().payload,
+        ZBytes::from(b"b".to_vec())
+    );
+}
+
+#[test]
+fn test_resolve_path() {
+    let dir = std::env::temp_dir().join(format!("zenoh-snapshot-path-{}", std::process::id()));
+    fs::create_dir_all(dir.join("demo")).unwrap();
+    let canonical_dir = dir.canonicalize().unwrap();
+    assert_eq!(
+        resolve_path(&dir, "demo/snapshot.jsonl").unwrap(),
+        canonical_dir.join("demo/snapshot.jsonl")
+    );
+    assert_eq!(
+        resolve_path(&dir, "new/snapshot.jsonl").unwrap(),
+        canonical_dir.join("new/snapshot.jsonl")
+    );
+
+    for path in [
+        "",
+        "/tmp/snapshot.jsonl",
+        "../snapshot.jsonl",
+        "demo/../../snapshot.jsonl",
+        "./snapshot.jsonl",
+    ] {
+        assert!(resolve_path(&dir, path).is_err(), "{path:?} was accepted");
+    }
+
+    // A symbolic link can't be followed outside of the directory, but can be within it.
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(std::env::temp_dir(), dir.join("escape")).unwrap();
+        std::os::unix::fs::symlink(dir.join("demo"), dir.join("inside")).unwrap();
+        assert!(resolve_path(&dir, "escape/snapshot.jsonl").is_err());
+        assert_eq!(
+            resolve_path(&dir, "inside/snapshot.jsonl").unwrap(),
+            canonical_dir.join("demo/snapshot.jsonl")
+        );
+    }
+
+    // The directory itself must exist.
+    assert!(resolve_path(&dir.join("missing"), "snapshot.jsonl").is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_write_file_commit_or_abort() {
+    let dir = std::env::temp_dir().join(format!("zenoh-snapshot-test-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("demo.jsonl");
+    let part_path = dir.join("demo.jsonl.part");
+    let header = SnapshotHeader::new("demo", OwnedKeyExpr::from_str("demo/**").unwrap());
+
+    // A committed snapshot replaces the file.
+    let (tx, rx) = tokio::sync::mpsc::channel(8);
+    tx.blocking_send(SnapshotItem::Entry(entry(
+        "demo/a",
+        1,
+        Encoding::TEXT_PLAIN,
+        b"a",
+    )))
+    .unwrap();
+    tx.blocking_send(SnapshotItem::Commit).unwrap();
+    drop(tx);
+    assert_eq!(write_file(&path, &header, rx).unwrap(), 1);
+    let committed = fs::read(&path).unwrap();
+    assert!(!part_path.exists());
+
+    // An aborted snapshot leaves the existing one untouched and removes the temporary file.
+    let (tx, rx) = tokio::sync::mpsc::channel(8);
+    tx.blocking_send(SnapshotItem::Entry(entry(
+        "demo/b",
+        2,
+        Encoding::TEXT_PLAIN,
+        b"b",
+    )))
+    .unwrap();
+    drop(tx);
+    assert!(write_file(&path, &header, rx).is_err());
+    assert_eq!(fs::read(&path).unwrap(), committed);
+    assert!(!part_path.exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,160 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test batched updates -
+// 1. a burst of puts, smaller than the batch size, is stored once the batching window elapsed
+// 2. a burst of puts and deletes, larger than the batch size, only keeps the latest updates
+// 3. a put overridden by an earlier received, but more recent, wildcard delete is recorded as a
+//    delete in the change feed
+
+use std::thread::sleep;
+
+use serde_json::Value;
+use tokio::runtime::Runtime;
+use zenoh::{internal::zasync_executor_init, query::Reply, sample::Sample, Config, Session};
+use zenoh_plugin_trait::Plugin;
+
+async fn get_data(session: &Session, key_expr: &str) -> Vec<Sample> {
+    let replies: Vec<Reply> = session.get(key_expr).await.unwrap().into_iter().collect();
+    let mut samples = Vec::new();
+    for reply in replies {
+        if let Ok(sample) = reply.into_result() {
+            samples.push(sample);
+        }
+    }
+    println!("Getting Data on '{key_expr}': '{samples:?}'...");
+    samples
+}
+
+async fn test_batched_updates() {
+    async {
+        zasync_executor_init!();
+    }
+    .await;
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        batching_test: {
+                            key_expr: "batching/test/**",
+                            change_feed: true,
+                            volume: {
+                                id: "memory"
+                            },
+                            batching: {
+                                window: 100,
+                                max_samples: 8
+                            }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+    config
+        .insert_json5(
+            "timestamping",
+            r#"{
+                    enabled: {
+                        router: true,
+                        peer: true,
+                        client: true
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::internal::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::session::init(runtime.clone()).await.unwrap();
+
+    let changes_key = format!(
+        "@/{}/{}/status/plugins/storage-manager/storages/batching_test/changes/**",
+        runtime.zid(),
+        runtime.whatami().to_str()
+    );
+    let subscriber = session.declare_subscriber(&changes_key).await.unwrap();
+
+    sleep(std::time::Duration::from_secs(1));
+
+    for i in 0..4 {
+        session
+            .put(format!("batching/test/{i}"), i.to_string())
+            .await
+            .unwrap();
+    }
+
+    sleep(std::time::Duration::from_millis(200));
+
+    let data = get_data(&session, "batching/test/**").await;
+    assert_eq!(data.len(), 4);
+
+    for i in 0..20 {
+        session.put("batching/test/a", i.to_string()).await.unwrap();
+    }
+    session.delete("batching/test/0").await.unwrap();
+
+    sleep(std::time::Duration::from_millis(200));
+
+    let data = get_data(&session, "batching/test/a").await;
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0].payload().try_to_string().unwrap(), "19");
+
+    let data = get_data(&session, "batching/test/0").await;
+    assert_eq!(data.len(), 0);
+
+    // Only keep the changes of the last scenario.
+    while let Ok(Some(_)) = subscriber.try_recv() {}
+
+    let put_timestamp = session.new_timestamp();
+    session.delete("batching/test/*").await.unwrap();
+    sleep(std::time::Duration::from_millis(200));
+    // The put is published with the timestamp taken before the wildcard delete.
+    zenoh::internal::traits::TimestampBuilderTrait::timestamp(
+        session.put("batching/test/w", "w"),
+        put_timestamp,
+    )
+    .await
+    .unwrap();
+    sleep(std::time::Duration::from_millis(200));
+
+    let data = get_data(&session, "batching/test/w").await;
+    assert_eq!(data.len(), 0);
+
+    let mut change = None;
+    while let Ok(Some(sample)) = subscriber.try_recv() {
+        let value: Value =
+            serde_json::from_slice(&sample.payload().to_bytes()).expect("change must be JSON");
+        if value["key"] == "batching/test/w" {
+            change = Some(value);
+        }
+    }
+    let change = change.expect("the overridden put must be published");
+    assert_eq!(change["action"], "wildcard_delete");
+    assert_eq!(change["wildcard"], "batching/test/*");
+
+    drop(storage);
+}
+
+#[test]
+fn batching_test() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async { test_batched_updates().await });
+}
@@ -0,0 +1,150 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the change feed of a storage -
+// 1. every put and delete applied to the storage is published, in order
+// 2. a wildcard delete is published once for each key it was applied to
+// 3. the changes of a storage without `change_feed` are not published
+
+use std::{thread::sleep, time::Duration};
+
+use serde_json::Value;
+use tokio::runtime::Runtime;
+use zenoh::{internal::zasync_executor_init, Config};
+use zenoh_plugin_trait::Plugin;
+
+async fn test_changes() {
+    async {
+        zasync_executor_init!();
+    }
+    .await;
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        changes_test: {
+                            key_expr: "changes/test/**",
+                            change_feed: true,
+                            volume: {
+                                id: "memory"
+                            }
+                        },
+                        no_changes_test: {
+                            key_expr: "changes/other/**",
+                            volume: {
+                                id: "memory"
+                            }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+    config
+        .insert_json5(
+            "timestamping",
+            r#"{
+                    enabled: {
+                        router: true,
+                        peer: true,
+                        client: true
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::internal::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::session::init(runtime.clone()).await.unwrap();
+
+    let changes_key = format!(
+        "@/{}/{}/status/plugins/storage-manager/storages/changes_test/changes/**",
+        runtime.zid(),
+        runtime.whatami().to_str()
+    );
+    let subscriber = session.declare_subscriber(&changes_key).await.unwrap();
+    let other_changes_key = format!(
+        "@/{}/{}/status/plugins/storage-manager/storages/no_changes_test/changes/**",
+        runtime.zid(),
+        runtime.whatami().to_str()
+    );
+    let other_subscriber = session
+        .declare_subscriber(&other_changes_key)
+        .await
+        .unwrap();
+
+    sleep(Duration::from_secs(1));
+
+    session.put("changes/test/a", "a").await.unwrap();
+    session.put("changes/test/b", "b").await.unwrap();
+    session.delete("changes/test/a").await.unwrap();
+    session.delete("changes/test/**").await.unwrap();
+    session.put("changes/other/a", "a").await.unwrap();
+    sleep(Duration::from_millis(500));
+
+    assert!(other_subscriber.try_recv().unwrap().is_none());
+
+    let mut changes = Vec::new();
+    while let Ok(Some(sample)) = subscriber.try_recv() {
+        let change: Value =
+            serde_json::from_slice(&sample.payload().to_bytes()).expect("change must be JSON");
+        // A change is published on the key it was applied to.
+        assert!(sample
+            .key_expr()
+            .as_str()
+            .ends_with(&format!("/changes/{}", change["key"].as_str().unwrap())));
+        changes.push(change);
+    }
+    println!("Changes: {changes:?}");
+
+    let summary = changes
+        .iter()
+        .map(|change| {
+            (
+                change["key"].as_str().unwrap().to_string(),
+                change["action"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            ("changes/test/a".to_string(), "put".to_string()),
+            ("changes/test/b".to_string(), "put".to_string()),
+            ("changes/test/a".to_string(), "delete".to_string()),
+            // The wildcard delete only applies to the keys still stored.
+            ("changes/test/b".to_string(), "wildcard_delete".to_string()),
+        ]
+    );
+    for (seq, change) in changes.iter().enumerate() {
+        assert_eq!(change["seq"], seq as u64);
+        assert_eq!(change["origin"], "live");
+        assert!(change["timestamp"].is_string());
+    }
+    assert_eq!(changes[3]["wildcard"], "changes/test/**");
+
+    drop(storage);
+}
+
+#[test]
+fn changes_test() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async { test_changes().await });
+}
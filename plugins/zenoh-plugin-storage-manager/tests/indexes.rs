@@ -0,0 +1,161 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the secondary indexes -
+// 1. a query with a `_filter` only obtains the keys selected by the indexes
+// 2. a query with a `_filter` referring to an unknown index is answered with an error
+
+use std::thread::sleep;
+
+use tokio::runtime::Runtime;
+use zenoh::{
+    bytes::Encoding, internal::zasync_executor_init, query::Reply, sample::Sample, Config, Session,
+};
+use zenoh_plugin_trait::Plugin;
+
+async fn get_data(session: &Session, selector: &str) -> (Vec<Sample>, usize) {
+    let replies: Vec<Reply> = session.get(selector).await.unwrap().into_iter().collect();
+    let mut samples = Vec::new();
+    let mut errors = 0;
+    for reply in replies {
+        match reply.into_result() {
+            Ok(sample) => samples.push(sample),
+            Err(_) => errors += 1,
+        }
+    }
+    println!("Getting Data on '{selector}': '{samples:?}'...");
+    (samples, errors)
+}
+
+async fn get_keys(session: &Session, selector: &str) -> Vec<String> {
+    let mut keys = get_data(session, selector)
+        .await
+        .0
+        .into_iter()
+        .map(|sample| sample.key_expr().to_string())
+        .collect::<Vec<_>>();
+    keys.sort();
+    keys
+}
+
+async fn test_indexes() {
+    async {
+        zasync_executor_init!();
+    }
+    .await;
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        indexes_test: {
+                            key_expr: "indexes/test/**",
+                            strip_prefix: "indexes/test",
+                            volume: {
+                                id: "memory"
+                            },
+                            indexes: {
+                                format: { field: "encoding" },
+                                tags: { field: "attachment", separator: "," }
+                            }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+    config
+        .insert_json5(
+            "timestamping",
+            r#"{
+                    enabled: {
+                        router: true,
+                        peer: true,
+                        client: true
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::internal::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::session::init(runtime).await.unwrap();
+
+    sleep(std::time::Duration::from_secs(1));
+
+    session
+        .put("indexes/test/a", "{}")
+        .encoding(Encoding::APPLICATION_JSON)
+        .attachment("x,y")
+        .await
+        .unwrap();
+    session
+        .put("indexes/test/b", "{}")
+        .encoding(Encoding::APPLICATION_JSON)
+        .attachment("y")
+        .await
+        .unwrap();
+    session
+        .put("indexes/test/c", "c")
+        .encoding(Encoding::TEXT_PLAIN)
+        .attachment("x")
+        .await
+        .unwrap();
+    sleep(std::time::Duration::from_millis(100));
+
+    assert_eq!(get_keys(&session, "indexes/test/**").await.len(), 3);
+    assert_eq!(
+        get_keys(&session, "indexes/test/**?_filter=format:application/json").await,
+        vec!["indexes/test/a", "indexes/test/b"]
+    );
+    assert_eq!(
+        get_keys(
+            &session,
+            "indexes/test/**?_filter=format:application/json,tags:x"
+        )
+        .await,
+        vec!["indexes/test/a"]
+    );
+    assert_eq!(
+        get_keys(&session, "indexes/test/c?_filter=tags:x").await,
+        vec!["indexes/test/c"]
+    );
+    assert!(get_keys(&session, "indexes/test/c?_filter=tags:y")
+        .await
+        .is_empty());
+
+    // A deleted key is removed from the indexes.
+    session.delete("indexes/test/a").await.unwrap();
+    sleep(std::time::Duration::from_millis(100));
+    assert!(get_keys(&session, "indexes/test/**?_filter=tags:x,tags:y")
+        .await
+        .is_empty());
+
+    let (samples, errors) = get_data(&session, "indexes/test/**?_filter=size:12").await;
+    assert!(samples.is_empty());
+    assert_eq!(errors, 1);
+
+    drop(storage);
+}
+
+#[test]
+fn indexes_test() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async { test_indexes().await });
+}
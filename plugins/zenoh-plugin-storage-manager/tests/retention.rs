@@ -0,0 +1,119 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the retention rules -
+// 1. the keys older than the max age configured for them are deleted
+// 2. the oldest keys are deleted when there are more keys than the max number of keys
+
+use std::thread::sleep;
+
+use tokio::runtime::Runtime;
+use zenoh::{internal::zasync_executor_init, query::Reply, sample::Sample, Config, Session};
+use zenoh_plugin_trait::Plugin;
+
+async fn get_data(session: &Session, key_expr: &str) -> Vec<Sample> {
+    let replies: Vec<Reply> = session.get(key_expr).await.unwrap().into_iter().collect();
+    let mut samples = Vec::new();
+    for reply in replies {
+        if let Ok(sample) = reply.into_result() {
+            samples.push(sample);
+        }
+    }
+    println!("Getting Data on '{key_expr}': '{samples:?}'...");
+    samples
+}
+
+async fn test_retention() {
+    async {
+        zasync_executor_init!();
+    }
+    .await;
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        retention_test: {
+                            key_expr: "retention/test/**",
+                            strip_prefix: "retention/test",
+                            volume: {
+                                id: "memory"
+                            },
+                            retention: {
+                                period: 1,
+                                max_age: [
+                                    { key_expr: "retention/test/tmp/**", age: 1 }
+                                ],
+                                max_keys: 2
+                            }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+    config
+        .insert_json5(
+            "timestamping",
+            r#"{
+                    enabled: {
+                        router: true,
+                        peer: true,
+                        client: true
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::internal::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::session::init(runtime).await.unwrap();
+
+    sleep(std::time::Duration::from_secs(1));
+
+    session.put("retention/test/tmp/a", "tmp").await.unwrap();
+    for key in ["a", "b", "c"] {
+        session
+            .put(format!("retention/test/{key}"), key)
+            .await
+            .unwrap();
+        sleep(std::time::Duration::from_millis(10));
+    }
+
+    let data = get_data(&session, "retention/test/**").await;
+    assert_eq!(data.len(), 4);
+
+    sleep(std::time::Duration::from_millis(2500));
+
+    let mut keys = get_data(&session, "retention/test/**")
+        .await
+        .into_iter()
+        .map(|sample| sample.key_expr().to_string())
+        .collect::<Vec<_>>();
+    keys.sort();
+    assert_eq!(keys, vec!["retention/test/b", "retention/test/c"]);
+
+    drop(storage);
+}
+
+#[test]
+fn retention_test() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async { test_retention().await });
+}
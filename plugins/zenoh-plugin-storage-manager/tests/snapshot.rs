@@ -0,0 +1,204 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the snapshots of a storage -
+// 1. a put on the `snapshot/export` control key dumps the content of the storage in a file
+// 2. a put on the `snapshot/import` control key loads such a file in another storage, with the
+//    original timestamps and encodings of the values
+// 3. the paths leaving the `snapshots_dir` of the storage are rejected
+
+use std::{path::Path, thread::sleep, time::Duration};
+
+use tokio::runtime::Runtime;
+use zenoh::{
+    bytes::Encoding,
+    internal::{runtime::Runtime as ZRuntime, zasync_executor_init},
+    query::Reply,
+    sample::Sample,
+    Config, Session,
+};
+use zenoh_plugin_trait::Plugin;
+
+async fn get_data(session: &Session, key_expr: &str) -> Vec<Sample> {
+    let replies: Vec<Reply> = session.get(key_expr).await.unwrap().into_iter().collect();
+    let mut samples = Vec::new();
+    for reply in replies {
+        if let Ok(sample) = reply.into_result() {
+            samples.push(sample);
+        }
+    }
+    samples.sort_by(|a, b| a.key_expr().as_str().cmp(b.key_expr().as_str()));
+    println!("Getting Data on '{key_expr}': '{samples:?}'...");
+    samples
+}
+
+async fn start_runtime(storage_name: &str, snapshots_dir: &Path) -> ZRuntime {
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            &format!(
+                r#"{{
+                    storages: {{
+                        {storage_name}: {{
+                            key_expr: "snapshot/test/**",
+                            strip_prefix: "snapshot",
+                            snapshots_dir: {snapshots_dir:?},
+                            volume: {{
+                                id: "memory"
+                            }}
+                        }}
+                    }}
+                }}"#
+            ),
+        )
+        .unwrap();
+    config
+        .insert_json5(
+            "timestamping",
+            r#"{
+                    enabled: {
+                        router: true,
+                        peer: true,
+                        client: true
+                    }
+                }"#,
+        )
+        .unwrap();
+    config
+        .insert_json5("adminspace/permissions", r#"{ read: true, write: true }"#)
+        .unwrap();
+    // The runtimes must not discover each other: the second storage must only obtain its content
+    // from the snapshot.
+    config
+        .insert_json5("scouting/multicast/enabled", "false")
+        .unwrap();
+
+    zenoh::internal::runtime::RuntimeBuilder::new(config)
+        .build()
+        .await
+        .unwrap()
+}
+
+fn control_key(runtime: &ZRuntime, storage_name: &str, operation: &str) -> String {
+    format!(
+        "@/{}/{}/status/plugins/storage-manager/storages/{storage_name}/snapshot/{operation}",
+        runtime.zid(),
+        runtime.whatami().to_str()
+    )
+}
+
+async fn test_snapshot(snapshots_dir: &Path) {
+    async {
+        zasync_executor_init!();
+    }
+    .await;
+
+    // 1. Export.
+    let runtime_a = start_runtime("snapshot_a", snapshots_dir).await;
+    let storage_a =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime_a).unwrap();
+    let session_a = zenoh::session::init(runtime_a.clone()).await.unwrap();
+
+    sleep(Duration::from_secs(1));
+
+    session_a.put("snapshot/test/a", "a").await.unwrap();
+    session_a
+        .put("snapshot/test/b", vec![0u8, 159, 146, 150])
+        .encoding(Encoding::ZENOH_BYTES)
+        .await
+        .unwrap();
+    session_a.put("snapshot/test/c", "c").await.unwrap();
+    session_a.delete("snapshot/test/c").await.unwrap();
+    sleep(Duration::from_millis(100));
+
+    let data_a = get_data(&session_a, "snapshot/test/**").await;
+    assert_eq!(data_a.len(), 2);
+
+    session_a
+        .put(
+            control_key(&runtime_a, "snapshot_a", "export"),
+            "snapshot.jsonl",
+        )
+        .await
+        .unwrap();
+    sleep(Duration::from_millis(500));
+    assert!(snapshots_dir.join("snapshot.jsonl").exists());
+
+    // 2. Import.
+    let runtime_b = start_runtime("snapshot_b", snapshots_dir).await;
+    let storage_b =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime_b).unwrap();
+    let session_b = zenoh::session::init(runtime_b.clone()).await.unwrap();
+
+    sleep(Duration::from_secs(1));
+    assert!(get_data(&session_b, "snapshot/test/**").await.is_empty());
+
+    // 3. Paths leaving the snapshots directory.
+    let outside = snapshots_dir.with_extension("jsonl");
+    for path in [outside.to_str().unwrap(), "../snapshot.jsonl"] {
+        session_a
+            .put(control_key(&runtime_a, "snapshot_a", "export"), path)
+            .await
+            .unwrap();
+    }
+    sleep(Duration::from_millis(500));
+    assert!(!outside.exists());
+    assert!(!snapshots_dir
+        .parent()
+        .unwrap()
+        .join("snapshot.jsonl")
+        .exists());
+
+    session_b
+        .put(
+            control_key(&runtime_b, "snapshot_b", "import"),
+            outside.to_str().unwrap(),
+        )
+        .await
+        .unwrap();
+    sleep(Duration::from_millis(500));
+    assert!(get_data(&session_b, "snapshot/test/**").await.is_empty());
+
+    session_b
+        .put(
+            control_key(&runtime_b, "snapshot_b", "import"),
+            "snapshot.jsonl",
+        )
+        .await
+        .unwrap();
+    sleep(Duration::from_millis(500));
+
+    let data_b = get_data(&session_b, "snapshot/test/**").await;
+    assert_eq!(data_b.len(), 2);
+    for (a, b) in data_a.iter().zip(data_b.iter()) {
+        assert_eq!(a.key_expr(), b.key_expr());
+        assert_eq!(a.payload(), b.payload());
+        assert_eq!(a.encoding(), b.encoding());
+        assert_eq!(a.timestamp(), b.timestamp());
+    }
+
+    drop(storage_a);
+    drop(storage_b);
+}
+
+#[test]
+fn snapshot_test() {
+    let snapshots_dir =
+        std::env::temp_dir().join(format!("zenoh-storage-snapshots-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir(&snapshots_dir).unwrap();
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async { test_snapshot(&snapshots_dir).await });
+    let _ = std::fs::remove_dir_all(&snapshots_dir);
+}
@@ -82,7 +82,9 @@ pub(crate) fn start_plugins(runtime: &Runtime) {
             name = plugin.id()
         );
         match plugin.start(runtime) {
-            Ok(_) => {
+            Ok(started) => {
+                let factories = started.instance().interceptor_factories();
+                runtime.register_plugin_interceptor_factories(started.id(), factories);
                 tracing::info!(
                     "Successfully started plugin {} from {:?}",
                     plugin.id(),
@@ -21,6 +21,8 @@ use zenoh_plugin_trait::{
 use zenoh_protocol::core::key_expr::keyexpr;
 use zenoh_result::ZResult;
 
+#[zenoh_macros::unstable]
+use crate::net::routing::interceptor::InterceptorFactory;
 use crate::{api::key_expr::KeyExpr, net::runtime::Runtime};
 
 zconfigurable! {
@@ -34,7 +36,7 @@ pub trait ZenohPlugin: Plugin<StartArgs = Runtime, Instance = RunningPlugin> {}
 
 impl StructVersion for RunningPlugin {
     fn struct_version() -> u64 {
-        1
+        2
     }
     fn struct_features() -> &'static str {
         crate::FEATURES
@@ -117,6 +119,20 @@ pub trait RunningPluginTrait: Send + Sync + PluginControl {
     ) -> ZResult<Vec<Response>> {
         Ok(Vec::new())
     }
+
+    /// Returns the factories of the interceptors applied to the messages routed by zenohd.
+    ///
+    /// This function is called once, right after the plugin is started. The interceptors of the
+    /// returned factories are instantiated for each face created afterwards, after the interceptors
+    /// configured for zenohd and before the egress key remapping.
+    ///
+    /// The factories are unregistered when the plugin is stopped, and replaced by the ones
+    /// returned by its new instance when it is restarted. The faces created while the plugin was
+    /// running keep the interceptors it provided them.
+    #[zenoh_macros::unstable]
+    fn interceptor_factories(&self) -> Vec<InterceptorFactory> {
+        Vec::new()
+    }
 }
 
 /// The zenoh plugins manager. It handles the full lifetime of plugins, from loading to destruction.
@@ -371,6 +371,31 @@ pub mod config {
     pub use crate::api::config::Notifier;
 }
 
+/// Interception of the messages routed by a zenoh router.
+///
+/// The [`InterceptorFactory`](interceptor::InterceptorFactory)s provided by the plugins
+/// (see `RunningPluginTrait::interceptor_factories`) are called for each new face and provide
+/// its ingress and egress [`Interceptor`](interceptor::Interceptor)s. A factory only applies to
+/// the faces created after it is registered: the faces already open keep their interceptors.
+///
+/// This API remains unstable as interceptors handle the [`network`](interceptor::network)
+/// messages of the protocol, which are not part of the stable API and may change with it. For
+/// the same reason, the factories are provided through `RunningPluginTrait`, defined by `zenoh`,
+/// rather than through the `zenoh-plugin-trait` crate, which does not depend on `zenoh`.
+#[zenoh_macros::unstable]
+pub mod interceptor {
+    pub use zenoh_protocol::network;
+    pub use zenoh_transport::{multicast::TransportMulticast, unicast::TransportUnicast};
+
+    pub use crate::net::routing::{
+        interceptor::{
+            EgressInterceptor, IngressInterceptor, Interceptor, InterceptorFactory,
+            InterceptorFactoryTrait, InterceptorTrait,
+        },
+        RoutingContext,
+    };
+}
+
 #[cfg(all(
     feature = "plugins",
     not(all(feature = "unstable", feature = "internal"))
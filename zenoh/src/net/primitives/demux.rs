@@ -11,8 +11,12 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use std::{any::Any, sync::Arc};
+use std::{
+    any::Any,
+    sync::{Arc, OnceLock},
+};
 
+use tokio::time::Instant;
 use zenoh_link::Link;
 use zenoh_protocol::network::{NetworkBody, NetworkMessage};
 use zenoh_result::ZResult;
@@ -21,14 +25,23 @@ use zenoh_transport::{unicast::TransportUnicast, TransportPeerEventHandler};
 use super::Primitives;
 use crate::net::routing::{
     dispatcher::face::Face,
-    interceptor::{InterceptorTrait, InterceptorsChain},
+    interceptor::{Interception, InterceptorsChain},
     RoutingContext,
 };
 
+/// A message delayed by an ingress interceptor.
+struct DelayedMessage {
+    ctx: RoutingContext<NetworkMessage>,
+    next: usize,
+    until: Instant,
+}
+
 pub struct DeMux {
     face: Face,
     pub(crate) transport: Option<TransportUnicast>,
     pub(crate) interceptor: Arc<InterceptorsChain>,
+    /// The messages delayed by the interceptors, routed in order by a task of the face.
+    delayed: OnceLock<flume::Sender<DelayedMessage>>,
 }
 
 impl DeMux {
@@ -41,8 +54,86 @@ impl DeMux {
             face,
             transport,
             interceptor,
+            delayed: OnceLock::new(),
         }
     }
+
+    /// Routes the message once `until` is reached, after the messages delayed before it.
+    ///
+    /// The message is held back by a task of the face, such that the routing of the messages
+    /// received meanwhile is not blocked.
+    fn delay(&self, message: DelayedMessage) {
+        let delayed = self.delayed.get_or_init(|| {
+            // NOTE: The queue is not bounded: the interceptors delaying the messages bound the
+            //       number of messages delayed at once.
+            let (sender, receiver) = flume::unbounded::<DelayedMessage>();
+            let face = self.face.clone();
+            let transport = self.transport.clone();
+            let interceptor = self.interceptor.clone();
+            // The task stops once the demux, holding the only sender, is dropped.
+            zenoh_runtime::ZRuntime::Net.spawn(async move {
+                while let Ok(DelayedMessage {
+                    mut ctx,
+                    mut next,
+                    mut until,
+                }) = receiver.recv_async().await
+                {
+                    loop {
+                        tokio::time::sleep_until(until).await;
+                        match interceptor.intercept_from(next, ctx, None) {
+                            Interception::Route(ctx) => {
+                                if let Err(e) = route(&face, transport.as_ref(), ctx.msg) {
+                                    tracing::debug!("Couldn't route a delayed message: {}", e);
+                                }
+                                break;
+                            }
+                            Interception::Delay {
+                                ctx: delayed,
+                                next: delayed_next,
+                                delay,
+                            } => {
+                                ctx = delayed;
+                                next = delayed_next;
+                                until = Instant::now() + delay;
+                            }
+                            Interception::Drop => break,
+                        }
+                    }
+                }
+            });
+            sender
+        });
+        let _ = delayed.send(message);
+    }
+}
+
+/// Routes a message received on `face`.
+fn route(face: &Face, transport: Option<&TransportUnicast>, msg: NetworkMessage) -> ZResult<()> {
+    match msg.body {
+        NetworkBody::Push(m) => face.send_push(m, msg.reliability),
+        NetworkBody::Declare(m) => face.send_declare(m),
+        NetworkBody::Interest(m) => face.send_interest(m),
+        NetworkBody::Request(m) => face.send_request(m),
+        NetworkBody::Response(m) => face.send_response(m),
+        NetworkBody::ResponseFinal(m) => face.send_response_final(m),
+        NetworkBody::OAM(m) => {
+            if let Some(transport) = transport {
+                let mut declares = vec![];
+                let ctrl_lock = zlock!(face.tables.ctrl_lock);
+                let mut tables = zwrite!(face.tables.tables);
+                ctrl_lock.handle_oam(&mut tables, &face.tables, m, transport, &mut |p, m| {
+                    declares.push((p.clone(), m))
+                })?;
+                drop(tables);
+                drop(ctrl_lock);
+                for (p, m) in declares {
+                    p.send_declare(m);
+                }
+            }
+        }
+    }
+
+    Ok(())
 }
 
 impl TransportPeerEventHandler for DeMux {
@@ -58,42 +149,21 @@ impl TransportPeerEventHandler for DeMux {
             let cache = prefix
                 .as_ref()
                 .and_then(|p| p.get_ingress_cache(&self.face));
-            let ctx = match self.interceptor.intercept(ctx, cache) {
-                Some(ctx) => ctx,
-                None => return Ok(()),
-            };
-            msg = ctx.msg;
-        }
-
-        match msg.body {
-            NetworkBody::Push(m) => self.face.send_push(m, msg.reliability),
-            NetworkBody::Declare(m) => self.face.send_declare(m),
-            NetworkBody::Interest(m) => self.face.send_interest(m),
-            NetworkBody::Request(m) => self.face.send_request(m),
-            NetworkBody::Response(m) => self.face.send_response(m),
-            NetworkBody::ResponseFinal(m) => self.face.send_response_final(m),
-            NetworkBody::OAM(m) => {
-                if let Some(transport) = self.transport.as_ref() {
-                    let mut declares = vec![];
-                    let ctrl_lock = zlock!(self.face.tables.ctrl_lock);
-                    let mut tables = zwrite!(self.face.tables.tables);
-                    ctrl_lock.handle_oam(
-                        &mut tables,
-                        &self.face.tables,
-                        m,
-                        transport,
-                        &mut |p, m| declares.push((p.clone(), m)),
-                    )?;
-                    drop(tables);
-                    drop(ctrl_lock);
-                    for (p, m) in declares {
-                        p.send_declare(m);
-                    }
+            match self.interceptor.intercept_from(0, ctx, cache) {
+                Interception::Route(ctx) => msg = ctx.msg,
+                Interception::Delay { ctx, next, delay } => {
+                    self.delay(DelayedMessage {
+                        ctx,
+                        next,
+                        until: Instant::now() + delay,
+                    });
+                    return Ok(());
                 }
+                Interception::Drop => return Ok(()),
             }
         }
 
-        Ok(())
+        route(&self.face, self.transport.as_ref(), msg)
     }
 
     fn new_link(&self, _link: Link) {}
@@ -216,6 +216,7 @@ impl EPrimitives for Mux {
             outface: ctx.outface,
             prefix: ctx.prefix,
             full_expr: ctx.full_expr,
+            delay: ctx.delay,
         };
         let prefix = ctx
             .wire_expr()
@@ -242,6 +243,7 @@ impl EPrimitives for Mux {
             outface: ctx.outface,
             prefix: ctx.prefix,
             full_expr: ctx.full_expr,
+            delay: ctx.delay,
         };
         let prefix = ctx
             .wire_expr()
@@ -544,6 +546,7 @@ impl EPrimitives for McastMux {
             outface: ctx.outface,
             prefix: ctx.prefix,
             full_expr: ctx.full_expr,
+            delay: ctx.delay,
         };
         let prefix = ctx
             .wire_expr()
@@ -570,6 +573,7 @@ impl EPrimitives for McastMux {
             outface: ctx.outface,
             prefix: ctx.prefix,
             full_expr: ctx.full_expr,
+            delay: ctx.delay,
         };
         let prefix = ctx
             .wire_expr()
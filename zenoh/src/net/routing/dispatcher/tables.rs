@@ -31,7 +31,9 @@ use super::face::FaceState;
 pub use super::{pubsub::*, queries::*, resource::*};
 use crate::net::routing::{
     hat::{self, HatTrait},
-    interceptor::{interceptor_factories, InterceptorFactory},
+    interceptor::{
+        interceptor_factories, shared_policy_enforcer, InterceptorFactory, SharedPolicyEnforcer,
+    },
 };
 
 pub(crate) struct RoutingExpr<'a> {
@@ -72,6 +74,14 @@ pub struct Tables {
     pub(crate) mcast_groups: Vec<Arc<FaceState>>,
     pub(crate) mcast_faces: Vec<Arc<FaceState>>,
     pub(crate) interceptors: Vec<InterceptorFactory>,
+    /// The number of factories at the end of `interceptors` that are kept after the ones added at
+    /// runtime (the egress key remapping).
+    #[cfg(feature = "unstable")]
+    pub(crate) last_interceptors: usize,
+    /// The factories added at runtime, along with the id of the plugin that provided them, if any.
+    #[cfg(feature = "unstable")]
+    pub(crate) added_interceptors: Vec<(Option<String>, InterceptorFactory)>,
+    pub(crate) acl: Option<Arc<SharedPolicyEnforcer>>,
     pub(crate) hat: Box<dyn Any + Send + Sync>,
     pub(crate) hat_code: Arc<dyn HatTrait + Send + Sync>, // @TODO make this a Box
 }
@@ -90,6 +100,8 @@ impl Tables {
         let queries_default_timeout =
             Duration::from_millis(unwrap_or_default!(config.queries_default_timeout()));
         let hat_code = hat::new_hat(whatami, config);
+        let acl = shared_policy_enforcer(config.access_control())?;
+        let (interceptors, _last_interceptors) = interceptor_factories(config, acl.as_ref())?;
         Ok(Tables {
             zid,
             whatami,
@@ -101,12 +113,38 @@ impl Tables {
             faces: HashMap::new(),
             mcast_groups: vec![],
             mcast_faces: vec![],
-            interceptors: interceptor_factories(config)?,
+            interceptors,
+            #[cfg(feature = "unstable")]
+            last_interceptors: _last_interceptors,
+            #[cfg(feature = "unstable")]
+            added_interceptors: vec![],
+            acl,
             hat: hat_code.new_tables(router_peers_failover_brokering),
             hat_code: hat_code.into(),
         })
     }
 
+    /// Returns the factories of the interceptors of a new face, in the order their interceptors
+    /// are applied: the configured ones, then the ones added at runtime and finally the egress
+    /// key remapping.
+    #[cfg(feature = "unstable")]
+    pub(crate) fn interceptor_factories(&self) -> impl Iterator<Item = &InterceptorFactory> {
+        let (first, last) = self
+            .interceptors
+            .split_at(self.interceptors.len() - self.last_interceptors);
+        first
+            .iter()
+            .chain(self.added_interceptors.iter().map(|(_, factory)| factory))
+            .chain(last.iter())
+    }
+
+    /// Returns the factories of the interceptors of a new face, in the order their interceptors
+    /// are applied.
+    #[cfg(not(feature = "unstable"))]
+    pub(crate) fn interceptor_factories(&self) -> impl Iterator<Item = &InterceptorFactory> {
+        self.interceptors.iter()
+    }
+
     #[doc(hidden)]
     pub fn _get_root(&self) -> &Arc<Resource> {
         &self.root_res
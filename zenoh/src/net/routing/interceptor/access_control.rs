@@ -18,15 +18,27 @@
 //!
 //! [Click here for Zenoh's documentation](https://docs.rs/zenoh/latest/zenoh)
 
-use std::{any::Any, collections::HashSet, iter, sync::Arc};
+use std::{
+    any::Any,
+    collections::HashSet,
+    iter,
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+};
 
 use itertools::Itertools;
+use serde_json::Value;
 use zenoh_config::{
-    AclConfig, AclMessage, CertCommonName, InterceptorFlow, Interface, Permission, Username,
+    AclConfig, AclMessage, CertCommonName, InterceptorFlow, Interface, LinkProtocol, Permission,
+    Username,
 };
+use zenoh_core::{zread, zwrite};
 use zenoh_protocol::{
     core::ZenohIdProto,
-    network::{Declare, DeclareBody, NetworkBody, NetworkMessage, Push, Request, Response},
+    network::{
+        interest::InterestMode, Declare, DeclareBody, Interest, NetworkBody, NetworkMessage, Push,
+        Request, Response,
+    },
     zenoh::{PushBody, RequestBody},
 };
 use zenoh_result::ZResult;
@@ -36,55 +48,230 @@ use zenoh_transport::{
 };
 
 use super::{
-    authorization::PolicyEnforcer, EgressInterceptor, IngressInterceptor, InterceptorFactory,
-    InterceptorFactoryTrait, InterceptorTrait,
+    audit::{now_ms, AclAudit, AclAuditRecord, SubjectAttributes, AUDIT_RECORDS_CAPACITY},
+    authorization::{PolicyDecision, PolicyEnforcer},
+    EgressInterceptor, IngressInterceptor, InterceptorFactory, InterceptorFactoryTrait,
+    InterceptorTrait,
 };
 use crate::{
     api::key_expr::KeyExpr,
     net::routing::{interceptor::authorization::SubjectQuery, RoutingContext},
 };
+
+/// The access control policy enforced by the ACL interceptors of all the faces.
+///
+/// The policy can be replaced at runtime with [`SharedPolicyEnforcer::update`]. The faces
+/// re-evaluate their subjects against the new policy before their next decision.
+pub(crate) struct SharedPolicyEnforcer {
+    enforcer: RwLock<Arc<PolicyEnforcer>>,
+    audit: RwLock<Option<Arc<AclAudit>>>,
+    audit_records: (
+        flume::Sender<AclAuditRecord>,
+        flume::Receiver<AclAuditRecord>,
+    ),
+}
+
+impl SharedPolicyEnforcer {
+    fn current(&self) -> Arc<PolicyEnforcer> {
+        zread!(self.enforcer).clone()
+    }
+
+    fn audit(&self) -> Option<Arc<AclAudit>> {
+        zread!(self.audit).clone()
+    }
+
+    fn new_audit(&self, acl_config: &AclConfig) -> ZResult<Option<Arc<AclAudit>>> {
+        acl_config
+            .audit
+            .as_ref()
+            .map(|conf| AclAudit::new(conf, &self.audit_records.0).map(Arc::new))
+            .transpose()
+    }
+
+    /// Checks that a policy can be built from `acl_config`, without enforcing it.
+    pub(crate) fn validate(acl_config: &AclConfig) -> ZResult<()> {
+        if let Err(e) = PolicyEnforcer::new().init(acl_config) {
+            bail!("Invalid access control configuration: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Replaces the enforced policy with the one built from `acl_config`.
+    ///
+    /// The current policy is left untouched if `acl_config` is invalid.
+    pub(crate) fn update(&self, acl_config: &AclConfig) -> ZResult<()> {
+        let mut policy_enforcer = PolicyEnforcer::new();
+        if let Err(e) = policy_enforcer.init(acl_config) {
+            bail!("Access control not updated due to: {}", e);
+        }
+        let audit = match self.new_audit(acl_config) {
+            Ok(audit) => audit,
+            Err(e) => bail!("Access control not updated due to: {}", e),
+        };
+        // The audit is replaced first such that the faces observing the new policy record their
+        // decisions with the new audit.
+        *zwrite!(self.audit) = audit;
+        *zwrite!(self.enforcer) = Arc::new(policy_enforcer);
+        tracing::info!("Access control policy updated");
+        Ok(())
+    }
+
+    /// Returns the receiver of the audit records to be published on the admin space.
+    pub(crate) fn audit_records(&self) -> flume::Receiver<AclAuditRecord> {
+        self.audit_records.1.clone()
+    }
+}
+
+/// Returns the shared access control policy built from `acl_config`, if access control is enabled.
+pub(crate) fn shared_policy_enforcer(
+    acl_config: &AclConfig,
+) -> ZResult<Option<Arc<SharedPolicyEnforcer>>> {
+    if !acl_config.enabled {
+        tracing::debug!("Access control is disabled");
+        return Ok(None);
+    }
+    let mut policy_enforcer = PolicyEnforcer::new();
+    match policy_enforcer.init(acl_config) {
+        Ok(_) => {
+            tracing::debug!("Access control is enabled");
+            let shared_enforcer = SharedPolicyEnforcer {
+                enforcer: RwLock::new(Arc::new(policy_enforcer)),
+                audit: RwLock::new(None),
+                audit_records: flume::bounded(AUDIT_RECORDS_CAPACITY),
+            };
+            *zwrite!(shared_enforcer.audit) = shared_enforcer.new_audit(acl_config)?;
+            Ok(Some(Arc::new(shared_enforcer)))
+        }
+        Err(e) => bail!("Access control not enabled due to: {}", e),
+    }
+}
+
 pub struct AclEnforcer {
-    enforcer: Arc<PolicyEnforcer>,
+    enforcer: Arc<SharedPolicyEnforcer>,
 }
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct AuthSubject {
     id: usize,
     name: String,
+    attributes: SubjectAttributes,
 }
 
-struct EgressAclEnforcer {
+/// The subjects of a face under a given policy.
+pub struct FacePolicy {
     policy_enforcer: Arc<PolicyEnforcer>,
     subject: Vec<AuthSubject>,
+    audit: Option<Arc<AclAudit>>,
+}
+
+/// The access control state of a face, shared by its ingress and egress interceptors.
+struct FaceAcl {
+    shared_enforcer: Arc<SharedPolicyEnforcer>,
+    queries: Vec<SubjectQuery>,
     zid: ZenohIdProto,
+    policy: RwLock<Arc<FacePolicy>>,
+}
+
+impl FaceAcl {
+    fn new(
+        shared_enforcer: Arc<SharedPolicyEnforcer>,
+        queries: Vec<SubjectQuery>,
+        zid: ZenohIdProto,
+    ) -> Self {
+        let policy = Self::evaluate(
+            &queries,
+            zid,
+            shared_enforcer.current(),
+            shared_enforcer.audit(),
+        );
+        FaceAcl {
+            shared_enforcer,
+            queries,
+            zid,
+            policy: RwLock::new(Arc::new(policy)),
+        }
+    }
+
+    fn evaluate(
+        queries: &[SubjectQuery],
+        zid: ZenohIdProto,
+        policy_enforcer: Arc<PolicyEnforcer>,
+        audit: Option<Arc<AclAudit>>,
+    ) -> FacePolicy {
+        let mut auth_subjects = HashSet::new();
+        for query in queries {
+            if let Some(entry) = policy_enforcer.subject_store.query(query) {
+                auth_subjects.insert(AuthSubject {
+                    id: entry.id,
+                    name: format!("{query}"),
+                    attributes: query.attributes(),
+                });
+            }
+        }
+        // FIXME: Investigate if `AuthSubject` can have duplicates above and try to avoid this conversion
+        let subject = auth_subjects.into_iter().collect::<Vec<AuthSubject>>();
+        if subject.is_empty() && policy_enforcer.acl_enabled {
+            tracing::info!(
+                "{zid} did not match any configured ACL subject. Default permission `{:?}` will be applied on all messages",
+                policy_enforcer.default_permission
+            );
+        }
+        FacePolicy {
+            policy_enforcer,
+            subject,
+            audit,
+        }
+    }
+
+    /// Returns the subjects of the face under the current policy, re-evaluating them if the
+    /// policy was replaced since the last call.
+    fn policy(&self) -> Arc<FacePolicy> {
+        let current = self.shared_enforcer.current();
+        let policy = zread!(self.policy).clone();
+        if Arc::ptr_eq(&policy.policy_enforcer, &current) {
+            return policy;
+        }
+        let mut guard = zwrite!(self.policy);
+        if !Arc::ptr_eq(&guard.policy_enforcer, &current) {
+            tracing::debug!("Re-evaluating the ACL subjects of {}", self.zid);
+            *guard = Arc::new(Self::evaluate(
+                &self.queries,
+                self.zid,
+                current,
+                self.shared_enforcer.audit(),
+            ));
+        }
+        guard.clone()
+    }
+
+    /// The subject attributes of the face, for the decisions made by the default permission.
+    fn attributes(&self) -> SubjectAttributes {
+        self.queries
+            .first()
+            .map(SubjectQuery::attributes)
+            .unwrap_or_default()
+    }
+}
+
+struct EgressAclEnforcer {
+    face: Arc<FaceAcl>,
 }
 
 struct IngressAclEnforcer {
-    policy_enforcer: Arc<PolicyEnforcer>,
-    subject: Vec<AuthSubject>,
-    zid: ZenohIdProto,
+    face: Arc<FaceAcl>,
 }
 
 pub(crate) fn acl_interceptor_factories(
-    acl_config: &AclConfig,
-) -> ZResult<Vec<InterceptorFactory>> {
+    shared_enforcer: Option<&Arc<SharedPolicyEnforcer>>,
+) -> Vec<InterceptorFactory> {
     let mut res: Vec<InterceptorFactory> = vec![];
 
-    if acl_config.enabled {
-        let mut policy_enforcer = PolicyEnforcer::new();
-        match policy_enforcer.init(acl_config) {
-            Ok(_) => {
-                tracing::debug!("Access control is enabled");
-                res.push(Box::new(AclEnforcer {
-                    enforcer: Arc::new(policy_enforcer),
-                }))
-            }
-            Err(e) => bail!("Access control not enabled due to: {}", e),
-        }
-    } else {
-        tracing::debug!("Access control is disabled");
+    if let Some(shared_enforcer) = shared_enforcer {
+        res.push(Box::new(AclEnforcer {
+            enforcer: shared_enforcer.clone(),
+        }))
     }
 
-    Ok(res)
+    res
 }
 
 impl InterceptorFactoryTrait for AclEnforcer {
@@ -129,38 +316,42 @@ impl InterceptorFactoryTrait for AclEnforcer {
                 return (None, None);
             }
         };
-        let mut interfaces = links
+        if links
+            .iter()
+            .map(|link| link.interfaces.len())
+            .sum::<usize>()
+            > 1
+        {
+            tracing::warn!("Transport returned multiple network interfaces, current ACL logic might incorrectly apply filters in this case!");
+        }
+        // The interfaces, protocol and remote address of each link
+        let mut link_attributes = links
             .into_iter()
             .flat_map(|link| {
-                link.interfaces
+                let link_protocol = link.dst.protocol().as_str().parse::<LinkProtocol>().ok();
+                let source_ip = link
+                    .dst
+                    .address()
+                    .as_str()
+                    .parse::<SocketAddr>()
+                    .ok()
+                    .map(|addr| addr.ip());
+                let mut interfaces = link
+                    .interfaces
                     .into_iter()
                     .map(|interface| Some(Interface(interface)))
+                    .collect::<Vec<_>>();
+                if interfaces.is_empty() {
+                    interfaces.push(None);
+                }
+                interfaces
+                    .into_iter()
+                    .map(move |interface| (interface, link_protocol, source_ip))
             })
+            .unique()
             .collect::<Vec<_>>();
-        if interfaces.is_empty() {
-            interfaces.push(None);
-        } else if interfaces.len() > 1 {
-            tracing::warn!("Transport returned multiple network interfaces, current ACL logic might incorrectly apply filters in this case!");
-        }
-
-        let mut auth_subjects = HashSet::new();
-
-        for ((username, interface), cert_common_name) in iter::once(username)
-            .cartesian_product(interfaces.into_iter())
-            .cartesian_product(cert_common_names.into_iter())
-        {
-            let query = SubjectQuery {
-                interface,
-                cert_common_name,
-                username,
-            };
-
-            if let Some(entry) = self.enforcer.subject_store.query(&query) {
-                auth_subjects.insert(AuthSubject {
-                    id: entry.id,
-                    name: format!("{query}"),
-                });
-            }
+        if link_attributes.is_empty() {
+            link_attributes.push((None, None, None));
         }
 
         let zid = match transport.get_zid() {
@@ -170,34 +361,54 @@ impl InterceptorFactoryTrait for AclEnforcer {
                 return (None, None);
             }
         };
-        // FIXME: Investigate if `AuthSubject` can have duplicates above and try to avoid this conversion
-        let auth_subjects = auth_subjects.into_iter().collect::<Vec<AuthSubject>>();
-        if auth_subjects.is_empty() {
-            tracing::info!(
-                "{zid} did not match any configured ACL subject. Default permission `{:?}` will be applied on all messages",
-                self.enforcer.default_permission
-            );
-        }
-        let ingress_interceptor = Box::new(IngressAclEnforcer {
-            policy_enforcer: self.enforcer.clone(),
-            zid,
-            subject: auth_subjects.clone(),
-        });
-        let egress_interceptor = Box::new(EgressAclEnforcer {
-            policy_enforcer: self.enforcer.clone(),
-            zid,
-            subject: auth_subjects,
-        });
-        (
-            self.enforcer
-                .interface_enabled
-                .ingress
-                .then_some(ingress_interceptor),
-            self.enforcer
-                .interface_enabled
-                .egress
-                .then_some(egress_interceptor),
-        )
+        let whatami = match transport.get_whatami() {
+            Ok(whatami) => whatami,
+            Err(err) => {
+                tracing::error!("Couldn't get Transport whatami: {}", err);
+                return (None, None);
+            }
+        };
+        let metadata = match transport.get_metadata() {
+            Ok(metadata) => {
+                metadata.and_then(|metadata| match serde_json::from_str::<Value>(&metadata) {
+                    Ok(Value::Object(metadata)) => Some(metadata),
+                    Ok(_) => None,
+                    Err(err) => {
+                        tracing::warn!("Couldn't parse the metadata of {}: {}", zid, err);
+                        None
+                    }
+                })
+            }
+            Err(err) => {
+                tracing::error!("Couldn't get Transport metadata: {}", err);
+                return (None, None);
+            }
+        };
+
+        let queries = iter::once(username)
+            .cartesian_product(link_attributes)
+            .cartesian_product(cert_common_names)
+            .map(
+                |((username, (interface, link_protocol, source_ip)), cert_common_name)| {
+                    SubjectQuery {
+                        interface,
+                        cert_common_name,
+                        username,
+                        whatami: Some(whatami),
+                        zid: Some(zid.into()),
+                        link_protocol,
+                        source_ip,
+                        metadata: metadata.clone(),
+                    }
+                },
+            )
+            .collect::<Vec<_>>();
+        // Both interceptors are always instantiated, as the flows the policy applies to can change
+        // when the policy is updated.
+        let face = Arc::new(FaceAcl::new(self.enforcer.clone(), queries, zid));
+        let ingress_interceptor = Box::new(IngressAclEnforcer { face: face.clone() });
+        let egress_interceptor = Box::new(EgressAclEnforcer { face });
+        (Some(ingress_interceptor), Some(egress_interceptor))
     }
 
     fn new_transport_multicast(
@@ -294,6 +505,40 @@ impl InterceptorTrait for IngressAclEnforcer {
                     return None;
                 }
             }
+            NetworkBody::Declare(Declare {
+                body: DeclareBody::DeclareToken(_),
+                ..
+            }) => {
+                if self.action(
+                    AclMessage::LivelinessToken,
+                    "Liveliness Token (ingress)",
+                    key_expr?,
+                ) == Permission::Deny
+                {
+                    return None;
+                }
+            }
+            // The Final Interests only undeclare the previous ones
+            NetworkBody::Interest(interest) if interest.mode != InterestMode::Final => {
+                // An Interest without key expression is an Interest in all key expressions
+                let key_expr = match interest.wire_expr {
+                    Some(_) => key_expr?,
+                    None => "**",
+                };
+                if self.action(AclMessage::Interest, "Interest (ingress)", key_expr)
+                    == Permission::Deny
+                {
+                    return None;
+                }
+                if let Some((message, log_msg)) = interest_acl_message(interest) {
+                    if self.action(message, &format!("{log_msg} (ingress)"), key_expr)
+                        == Permission::Deny
+                    {
+                        return None;
+                    }
+                }
+            }
+            NetworkBody::Interest(_) => {}
             // Unfiltered Declare messages
             NetworkBody::Declare(Declare {
                 body: DeclareBody::DeclareKeyExpr(_),
@@ -302,10 +547,6 @@ impl InterceptorTrait for IngressAclEnforcer {
             | NetworkBody::Declare(Declare {
                 body: DeclareBody::DeclareFinal(_),
                 ..
-            })
-            | NetworkBody::Declare(Declare {
-                body: DeclareBody::DeclareToken(_),
-                ..
             }) => {}
             // Unfiltered Undeclare messages
             NetworkBody::Declare(Declare {
@@ -325,7 +566,7 @@ impl InterceptorTrait for IngressAclEnforcer {
                 ..
             }) => {}
             // Unfiltered remaining message types
-            NetworkBody::Interest(_) | NetworkBody::OAM(_) | NetworkBody::ResponseFinal(_) => {}
+            NetworkBody::OAM(_) | NetworkBody::ResponseFinal(_) => {}
         }
         Some(ctx)
     }
@@ -408,6 +649,40 @@ impl InterceptorTrait for EgressAclEnforcer {
                     return None;
                 }
             }
+            NetworkBody::Declare(Declare {
+                body: DeclareBody::DeclareToken(_),
+                ..
+            }) => {
+                if self.action(
+                    AclMessage::LivelinessToken,
+                    "Liveliness Token (egress)",
+                    key_expr?,
+                ) == Permission::Deny
+                {
+                    return None;
+                }
+            }
+            // The Final Interests only undeclare the previous ones
+            NetworkBody::Interest(interest) if interest.mode != InterestMode::Final => {
+                // An Interest without key expression is an Interest in all key expressions
+                let key_expr = match interest.wire_expr {
+                    Some(_) => key_expr?,
+                    None => "**",
+                };
+                if self.action(AclMessage::Interest, "Interest (egress)", key_expr)
+                    == Permission::Deny
+                {
+                    return None;
+                }
+                if let Some((message, log_msg)) = interest_acl_message(interest) {
+                    if self.action(message, &format!("{log_msg} (egress)"), key_expr)
+                        == Permission::Deny
+                    {
+                        return None;
+                    }
+                }
+            }
+            NetworkBody::Interest(_) => {}
             // Unfiltered Declare messages
             NetworkBody::Declare(Declare {
                 body: DeclareBody::DeclareKeyExpr(_),
@@ -416,10 +691,6 @@ impl InterceptorTrait for EgressAclEnforcer {
             | NetworkBody::Declare(Declare {
                 body: DeclareBody::DeclareFinal(_),
                 ..
-            })
-            | NetworkBody::Declare(Declare {
-                body: DeclareBody::DeclareToken(_),
-                ..
             }) => {}
             // Unfiltered Undeclare messages
             NetworkBody::Declare(Declare {
@@ -439,24 +710,58 @@ impl InterceptorTrait for EgressAclEnforcer {
                 ..
             }) => {}
             // Unfiltered remaining message types
-            NetworkBody::Interest(_) | NetworkBody::OAM(_) | NetworkBody::ResponseFinal(_) => {}
+            NetworkBody::OAM(_) | NetworkBody::ResponseFinal(_) => {}
         }
         Some(ctx)
     }
 }
+/// Returns the liveliness ACL message an Interest is subject to, besides
+/// [`AclMessage::Interest`], along with its description, if any.
+///
+/// The Interests in liveliness tokens only are liveliness subscriptions, or liveliness queries
+/// when they are only about the current tokens.
+fn interest_acl_message(interest: &Interest) -> Option<(AclMessage, &'static str)> {
+    let options = interest.options;
+    if !options.tokens() || options.subscribers() || options.queryables() {
+        return None;
+    }
+    match interest.mode {
+        InterestMode::Current => Some((AclMessage::LivelinessQuery, "Liveliness Query")),
+        _ => Some((
+            AclMessage::DeclareLivelinessSubscriber,
+            "Declare Liveliness Subscriber",
+        )),
+    }
+}
+
 pub trait AclActionMethods {
-    fn policy_enforcer(&self) -> Arc<PolicyEnforcer>;
+    fn policy(&self) -> Arc<FacePolicy>;
     fn zid(&self) -> ZenohIdProto;
     fn flow(&self) -> InterceptorFlow;
-    fn authn_ids(&self) -> Vec<AuthSubject>;
+    fn attributes(&self) -> SubjectAttributes;
     fn action(&self, action: AclMessage, log_msg: &str, key_expr: &str) -> Permission {
-        let policy_enforcer = self.policy_enforcer();
-        let authn_ids: Vec<AuthSubject> = self.authn_ids();
+        // The policy and the subjects are fetched together such that a decision is never made
+        // against a policy the subjects were not evaluated with.
+        let policy = self.policy();
+        let policy_enforcer = &policy.policy_enforcer;
+        let authn_ids = &policy.subject;
         let zid = self.zid();
+        let flow_enabled = match self.flow() {
+            InterceptorFlow::Ingress => policy_enforcer.interface_enabled.ingress,
+            InterceptorFlow::Egress => policy_enforcer.interface_enabled.egress,
+        };
+        if !policy_enforcer.acl_enabled || !flow_enabled {
+            return Permission::Allow;
+        }
         let mut decision = policy_enforcer.default_permission;
-        for subject in &authn_ids {
+        // The subject and the rule the decision was made by, none for the default permission
+        let mut decided_by = None;
+        for subject in authn_ids {
             match policy_enforcer.policy_decision_point(subject.id, self.flow(), action, key_expr) {
-                Ok(Permission::Allow) => {
+                Ok(PolicyDecision {
+                    permission: Permission::Allow,
+                    rule_id,
+                }) => {
                     tracing::trace!(
                         "{} on {} is authorized to {} on {}",
                         zid,
@@ -465,9 +770,13 @@ pub trait AclActionMethods {
                         key_expr
                     );
                     decision = Permission::Allow;
+                    decided_by = Some((subject, rule_id));
                     break;
                 }
-                Ok(Permission::Deny) => {
+                Ok(PolicyDecision {
+                    permission: Permission::Deny,
+                    rule_id,
+                }) => {
                     tracing::debug!(
                         "{} on {} is unauthorized to {} on {}",
                         zid,
@@ -477,6 +786,7 @@ pub trait AclActionMethods {
                     );
 
                     decision = Permission::Deny;
+                    decided_by = Some((subject, rule_id));
                     continue;
                 }
                 Err(e) => {
@@ -488,46 +798,69 @@ pub trait AclActionMethods {
                         key_expr,
                         e
                     );
-                    return Permission::Deny;
+                    decision = Permission::Deny;
+                    decided_by = Some((subject, None));
+                    break;
                 }
             }
         }
+        if let Some(audit) = policy
+            .audit
+            .as_ref()
+            .filter(|audit| audit.records(decision))
+        {
+            audit.record(AclAuditRecord {
+                timestamp: now_ms(),
+                zid: zid.to_string(),
+                subject: decided_by
+                    .as_ref()
+                    .map(|(subject, _)| subject.attributes.clone())
+                    .unwrap_or_else(|| self.attributes()),
+                message: action,
+                key_expr: key_expr.to_string(),
+                flow: self.flow(),
+                decision,
+                rule_id: decided_by
+                    .and_then(|(_, rule_id)| rule_id)
+                    .map(|id| id.to_string()),
+            });
+        }
         decision
     }
 }
 
 impl AclActionMethods for EgressAclEnforcer {
-    fn policy_enforcer(&self) -> Arc<PolicyEnforcer> {
-        self.policy_enforcer.clone()
+    fn policy(&self) -> Arc<FacePolicy> {
+        self.face.policy()
     }
 
     fn zid(&self) -> ZenohIdProto {
-        self.zid
+        self.face.zid
     }
 
     fn flow(&self) -> InterceptorFlow {
         InterceptorFlow::Egress
     }
 
-    fn authn_ids(&self) -> Vec<AuthSubject> {
-        self.subject.clone()
+    fn attributes(&self) -> SubjectAttributes {
+        self.face.attributes()
     }
 }
 
 impl AclActionMethods for IngressAclEnforcer {
-    fn policy_enforcer(&self) -> Arc<PolicyEnforcer> {
-        self.policy_enforcer.clone()
+    fn policy(&self) -> Arc<FacePolicy> {
+        self.face.policy()
     }
 
     fn zid(&self) -> ZenohIdProto {
-        self.zid
+        self.face.zid
     }
 
     fn flow(&self) -> InterceptorFlow {
         InterceptorFlow::Ingress
     }
 
-    fn authn_ids(&self) -> Vec<AuthSubject> {
-        self.subject.clone()
+    fn attributes(&self) -> SubjectAttributes {
+        self.face.attributes()
     }
 }
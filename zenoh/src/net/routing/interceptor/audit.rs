@@ -0,0 +1,189 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This module is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](https://docs.rs/zenoh/latest/zenoh)
+
+use std::{
+    fs::{File, OpenOptions},
+    hash::{Hash, Hasher},
+    io::{BufWriter, Write},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use zenoh_config::{
+    AclAuditConf, AclAuditDecisions, AclAuditSink, AclMessage, InterceptorFlow, Permission,
+};
+use zenoh_result::ZResult;
+
+/// The maximum number of audit records waiting to be published on the admin space, or to be
+/// written in the audit file.
+pub(crate) const AUDIT_RECORDS_CAPACITY: usize = 1024;
+
+/// The suffix of the admin space key the audit records are published on.
+pub(crate) const AUDIT_KEY_SUFFIX: &str = "access_control/audit";
+
+/// The subject attributes of a face an access control decision applies to.
+///
+/// The zid of the face is not repeated here, it is the `zid` of the record.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize)]
+pub struct SubjectAttributes {
+    pub(crate) interface: Option<String>,
+    pub(crate) cert_common_name: Option<String>,
+    pub(crate) username: Option<String>,
+    pub(crate) whatami: Option<String>,
+    pub(crate) link_protocol: Option<String>,
+    pub(crate) source_ip: Option<String>,
+    pub(crate) metadata: Option<AuditMetadata>,
+}
+
+/// The metadata advertised by the remote node of a face.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub(crate) struct AuditMetadata(pub(crate) Map<String, Value>);
+
+impl Hash for AuditMetadata {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // JSON values are not hashable, their serialization is hashed instead
+        for (key, value) in self.0.iter() {
+            key.hash(state);
+            value.to_string().hash(state);
+        }
+    }
+}
+
+/// A record of an access control decision.
+#[derive(Debug, Serialize)]
+pub(crate) struct AclAuditRecord {
+    /// The time of the decision, in milliseconds since the UNIX epoch.
+    pub(crate) timestamp: u64,
+    pub(crate) zid: String,
+    #[serde(flatten)]
+    pub(crate) subject: SubjectAttributes,
+    pub(crate) message: AclMessage,
+    pub(crate) key_expr: String,
+    pub(crate) flow: InterceptorFlow,
+    pub(crate) decision: Permission,
+    /// The id of the rule the decision was made by, none if the default permission applied.
+    pub(crate) rule_id: Option<String>,
+}
+
+/// The audit trail of the access control decisions.
+///
+/// The records are never written by the faces making the decisions: they are sent to a bounded
+/// queue, emptied by a dedicated writer thread for a `file` sink or by the admin space for an
+/// `admin_space` sink. The records are dropped, and counted, while the queue is full.
+pub(crate) struct AclAudit {
+    decisions: AclAuditDecisions,
+    records: flume::Sender<AclAuditRecord>,
+    dropped: AtomicU64,
+}
+
+impl AclAudit {
+    /// Creates the audit trail configured by `conf`, the records of an `admin_space` sink being
+    /// sent to `records`.
+    pub(crate) fn new(
+        conf: &AclAuditConf,
+        records: &flume::Sender<AclAuditRecord>,
+    ) -> ZResult<Self> {
+        let records = match &conf.sink {
+            AclAuditSink::File(path) => {
+                let file = match OpenOptions::new().create(true).append(true).open(path) {
+                    Ok(file) => file,
+                    Err(e) => bail!("Couldn't open ACL audit file '{}': {}", path, e),
+                };
+                let (sender, receiver) = flume::bounded(AUDIT_RECORDS_CAPACITY);
+                // The thread stops once the audit, holding the only sender, is dropped.
+                if let Err(e) = std::thread::Builder::new()
+                    .name("acl-audit".to_string())
+                    .spawn(move || write_records(receiver, file))
+                {
+                    bail!("Couldn't start ACL audit file writer: {}", e);
+                }
+                sender
+            }
+            AclAuditSink::AdminSpace => records.clone(),
+        };
+        Ok(AclAudit {
+            decisions: conf.decisions,
+            records,
+            dropped: AtomicU64::new(0),
+        })
+    }
+
+    /// Whether the decisions resulting in `permission` are recorded.
+    pub(crate) fn records(&self, permission: Permission) -> bool {
+        match self.decisions {
+            AclAuditDecisions::All => true,
+            AclAuditDecisions::Deny => permission == Permission::Deny,
+        }
+    }
+
+    pub(crate) fn record(&self, record: AclAuditRecord) {
+        // The decisions on the audit records publications are not recorded, as recording them
+        // would lead to new publications.
+        if record.key_expr.starts_with("@/") && record.key_expr.ends_with(AUDIT_KEY_SUFFIX) {
+            return;
+        }
+        if self.records.try_send(record).is_err() {
+            // Only warning on powers of two to not flood the logs while the queue is full.
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            if dropped.is_power_of_two() {
+                tracing::warn!(
+                    "ACL audit records queue is full, {} record(s) dropped so far",
+                    dropped
+                );
+            }
+        }
+    }
+}
+
+/// Writes the received records in the audit file, one JSON object per line, until all the senders
+/// are dropped.
+///
+/// The file is flushed whenever no more records are waiting.
+fn write_records(records: flume::Receiver<AclAuditRecord>, file: File) {
+    let mut writer = BufWriter::new(file);
+    while let Ok(record) = records.recv() {
+        for record in std::iter::once(record).chain(records.try_iter()) {
+            let line = match serde_json::to_string(&record) {
+                Ok(line) => line,
+                Err(e) => {
+                    tracing::error!("Couldn't serialize ACL audit record: {}", e);
+                    continue;
+                }
+            };
+            if let Err(e) = writeln!(writer, "{line}") {
+                tracing::error!("Couldn't write ACL audit record: {}", e);
+            }
+        }
+        if let Err(e) = writer.flush() {
+            tracing::error!("Couldn't write ACL audit records: {}", e);
+        }
+    }
+}
+
+/// Returns the current time in milliseconds since the UNIX epoch.
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
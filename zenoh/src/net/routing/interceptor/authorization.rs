@@ -17,28 +17,51 @@
 //! This module is intended for Zenoh's internal use.
 //!
 //! [Click here for Zenoh's documentation](https://docs.rs/zenoh/latest/zenoh)
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{Hash, Hasher},
+    net::IpAddr,
+    sync::Arc,
+};
 
 use ahash::RandomState;
 use itertools::Itertools;
+use serde_json::{Map, Value};
 use zenoh_config::{
     AclConfig, AclConfigPolicyEntry, AclConfigRule, AclConfigSubjects, AclMessage, CertCommonName,
-    InterceptorFlow, Interface, Permission, PolicyRule, Username,
+    InterceptorFlow, Interface, IpNetwork, LinkProtocol, Permission, PolicyRule, Username, WhatAmI,
+    ZenohId,
 };
 use zenoh_keyexpr::{
     keyexpr,
-    keyexpr_tree::{IKeyExprTree, IKeyExprTreeMut, KeBoxTree},
+    keyexpr_tree::{IKeyExprTree, IKeyExprTreeMut, IKeyExprTreeNode, KeBoxTree},
 };
 use zenoh_result::ZResult;
+
+use super::audit::{AuditMetadata, SubjectAttributes};
 type PolicyForSubject = FlowPolicy;
 
 type PolicyMap = HashMap<usize, PolicyForSubject, RandomState>;
 
+/// A combination of an interface, a certificate common name and a username, further restricted
+/// by the attributes a remote node must match one value of.
+///
+/// Only the interfaces, certificate common names and usernames are expanded into combinations:
+/// the other attributes are matched independently, so that the number of subjects doesn't grow
+/// with the product of their lists.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct Subject {
     pub(crate) interface: SubjectProperty<Interface>,
     pub(crate) cert_common_name: SubjectProperty<CertCommonName>,
     pub(crate) username: SubjectProperty<Username>,
+    /// Declared by the remote node during the transport establishment, not authenticated.
+    pub(crate) whatamis: SubjectProperty<Vec<WhatAmI>>,
+    /// Declared by the remote node during the transport establishment, not authenticated.
+    pub(crate) zids: SubjectProperty<Vec<ZenohId>>,
+    pub(crate) link_protocols: SubjectProperty<Vec<LinkProtocol>>,
+    pub(crate) source_ips: SubjectProperty<Vec<IpNetwork>>,
+    /// Advertised by the remote node if its configuration opts in, not authenticated.
+    pub(crate) metadata: SubjectProperty<SubjectMetadata>,
 }
 
 impl Subject {
@@ -48,6 +71,39 @@ impl Subject {
             && self
                 .cert_common_name
                 .matches(query.cert_common_name.as_ref())
+            && self.whatamis.contains(query.whatami.as_ref())
+            && self.zids.contains(query.zid.as_ref())
+            && self.link_protocols.contains(query.link_protocol.as_ref())
+            && self
+                .source_ips
+                .matches_with(query.source_ip.as_ref(), |networks, ip| {
+                    networks.iter().any(|network| network.contains(*ip))
+                })
+            && self
+                .metadata
+                .matches_with(query.metadata.as_ref(), SubjectMetadata::matches)
+    }
+}
+
+/// The metadata entries a subject requires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SubjectMetadata(Map<String, Value>);
+
+impl SubjectMetadata {
+    fn matches(&self, metadata: &Map<String, Value>) -> bool {
+        self.0
+            .iter()
+            .all(|(key, value)| metadata.get(key) == Some(value))
+    }
+}
+
+impl Hash for SubjectMetadata {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // JSON values are not hashable, their serialization is hashed instead
+        for (key, value) in self.0.iter() {
+            key.hash(state);
+            value.to_string().hash(state);
+        }
     }
 }
 
@@ -57,23 +113,57 @@ pub(crate) enum SubjectProperty<T> {
     Exactly(T),
 }
 
-impl<T: PartialEq + Eq> SubjectProperty<T> {
-    fn matches(&self, other: Option<&T>) -> bool {
+impl<T> SubjectProperty<T> {
+    fn matches_with<Q>(&self, other: Option<&Q>, f: impl FnOnce(&T, &Q) -> bool) -> bool {
         match (self, other) {
             (SubjectProperty::Wildcard, None) => true,
             // NOTE: This match arm is the reason why `SubjectProperty` cannot simply be `Option`
             (SubjectProperty::Wildcard, Some(_)) => true,
             (SubjectProperty::Exactly(_), None) => false,
-            (SubjectProperty::Exactly(lhs), Some(rhs)) => lhs == rhs,
+            (SubjectProperty::Exactly(lhs), Some(rhs)) => f(lhs, rhs),
         }
     }
 }
 
+impl<T: PartialEq + Eq> SubjectProperty<T> {
+    fn matches(&self, other: Option<&T>) -> bool {
+        self.matches_with(other, |lhs, rhs| lhs == rhs)
+    }
+}
+
+impl<T: PartialEq + Eq> SubjectProperty<Vec<T>> {
+    fn contains(&self, other: Option<&T>) -> bool {
+        self.matches_with(other, |values, value| values.contains(value))
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct SubjectQuery {
     pub(crate) interface: Option<Interface>,
     pub(crate) cert_common_name: Option<CertCommonName>,
     pub(crate) username: Option<Username>,
+    pub(crate) whatami: Option<WhatAmI>,
+    pub(crate) zid: Option<ZenohId>,
+    pub(crate) link_protocol: Option<LinkProtocol>,
+    pub(crate) source_ip: Option<IpAddr>,
+    pub(crate) metadata: Option<Map<String, Value>>,
+}
+
+impl SubjectQuery {
+    pub(crate) fn attributes(&self) -> SubjectAttributes {
+        SubjectAttributes {
+            interface: self.interface.as_ref().map(|face| face.0.clone()),
+            cert_common_name: self.cert_common_name.as_ref().map(|ccn| ccn.0.clone()),
+            username: self.username.as_ref().map(|username| username.0.clone()),
+            whatami: self.whatami.as_ref().map(|whatami| whatami.to_string()),
+            link_protocol: self
+                .link_protocol
+                .as_ref()
+                .map(|protocol| protocol.as_str().to_string()),
+            source_ip: self.source_ip.as_ref().map(|ip| ip.to_string()),
+            metadata: self.metadata.clone().map(AuditMetadata),
+        }
+    }
 }
 
 impl std::fmt::Display for SubjectQuery {
@@ -82,6 +172,14 @@ impl std::fmt::Display for SubjectQuery {
             self.interface.as_ref().map(|face| format!("{face}")),
             self.cert_common_name.as_ref().map(|ccn| format!("{ccn}")),
             self.username.as_ref().map(|username| format!("{username}")),
+            self.whatami
+                .as_ref()
+                .map(|whatami| format!("WhatAmI({whatami})")),
+            self.zid.as_ref().map(|zid| format!("ZenohId({zid})")),
+            self.link_protocol
+                .as_ref()
+                .map(|protocol| format!("{protocol}")),
+            self.source_ip.as_ref().map(|ip| format!("SourceIp({ip})")),
         ];
         write!(
             f,
@@ -157,7 +255,8 @@ impl SubjectMapBuilder {
     }
 }
 
-type KeTreeRule = KeBoxTree<bool>;
+/// The rules matching the key expressions, identified by the id of the first one inserted.
+type KeTreeRule = KeBoxTree<Arc<str>>;
 
 #[derive(Default)]
 struct PermissionPolicy {
@@ -188,6 +287,10 @@ struct ActionPolicy {
     declare_subscriber: PermissionPolicy,
     declare_queryable: PermissionPolicy,
     reply: PermissionPolicy,
+    liveliness_token: PermissionPolicy,
+    declare_liveliness_subscriber: PermissionPolicy,
+    liveliness_query: PermissionPolicy,
+    interest: PermissionPolicy,
 }
 
 impl ActionPolicy {
@@ -199,6 +302,10 @@ impl ActionPolicy {
             AclMessage::Delete => &self.delete,
             AclMessage::DeclareSubscriber => &self.declare_subscriber,
             AclMessage::DeclareQueryable => &self.declare_queryable,
+            AclMessage::LivelinessToken => &self.liveliness_token,
+            AclMessage::DeclareLivelinessSubscriber => &self.declare_liveliness_subscriber,
+            AclMessage::LivelinessQuery => &self.liveliness_query,
+            AclMessage::Interest => &self.interest,
         }
     }
     fn action_mut(&mut self, action: AclMessage) -> &mut PermissionPolicy {
@@ -209,6 +316,10 @@ impl ActionPolicy {
             AclMessage::Delete => &mut self.delete,
             AclMessage::DeclareSubscriber => &mut self.declare_subscriber,
             AclMessage::DeclareQueryable => &mut self.declare_queryable,
+            AclMessage::LivelinessToken => &mut self.liveliness_token,
+            AclMessage::DeclareLivelinessSubscriber => &mut self.declare_liveliness_subscriber,
+            AclMessage::LivelinessQuery => &mut self.liveliness_query,
+            AclMessage::Interest => &mut self.interest,
         }
     }
 }
@@ -329,6 +440,26 @@ impl PolicyEnforcer {
                         if subject.interfaces.as_ref().is_some_and(Vec::is_empty) {
                             bail!("Subject property `interfaces` cannot be empty");
                         }
+
+                        if subject.whatamis.as_ref().is_some_and(Vec::is_empty) {
+                            bail!("Subject property `whatamis` cannot be empty");
+                        }
+
+                        if subject.zids.as_ref().is_some_and(Vec::is_empty) {
+                            bail!("Subject property `zids` cannot be empty");
+                        }
+
+                        if subject.link_protocols.as_ref().is_some_and(Vec::is_empty) {
+                            bail!("Subject property `link_protocols` cannot be empty");
+                        }
+
+                        if subject.source_ips.as_ref().is_some_and(Vec::is_empty) {
+                            bail!("Subject property `source_ips` cannot be empty");
+                        }
+
+                        if subject.metadata.as_ref().is_some_and(Map::is_empty) {
+                            bail!("Subject property `metadata` cannot be empty");
+                        }
                     }
                     let policy_information =
                         self.policy_information_point(subjects, rules, policies)?;
@@ -336,11 +467,14 @@ impl PolicyEnforcer {
                     let mut main_policy: PolicyMap = PolicyMap::default();
                     for rule in policy_information.policy_rules {
                         let subject_policy = main_policy.entry(rule.subject_id).or_default();
-                        subject_policy
+                        let ke_tree = subject_policy
                             .flow_mut(rule.flow)
                             .action_mut(rule.message)
-                            .permission_mut(rule.permission)
-                            .insert(keyexpr::new(&rule.key_expr)?, true);
+                            .permission_mut(rule.permission);
+                        let key_expr = keyexpr::new(&rule.key_expr)?;
+                        if ke_tree.weight_at(key_expr).is_none() {
+                            ke_tree.insert(key_expr, rule.rule_id.into());
+                        }
 
                         if self.default_permission == Permission::Deny {
                             self.interface_enabled = InterfaceEnabled {
@@ -380,6 +514,9 @@ impl PolicyEnforcer {
         let mut policy_rules: Vec<PolicyRule> = Vec::new();
         let mut rule_map = HashMap::new();
         let mut subject_id_map = HashMap::<String, Vec<usize>>::new();
+        // the subjects matching on properties declared by the remote nodes themselves only
+        let mut unauthenticated_subjects = HashSet::new();
+        let mut warned_subjects = HashSet::new();
         let mut subject_map_builder = SubjectMapBuilder::new();
 
         // validate rules config and insert them in hashmaps
@@ -452,6 +589,14 @@ impl PolicyEnforcer {
                     config_subject.id
                 );
             }
+            if (config_subject.whatamis.is_some()
+                || config_subject.zids.is_some()
+                || config_subject.metadata.is_some())
+                && config_subject.cert_common_names.is_none()
+                && config_subject.usernames.is_none()
+            {
+                unauthenticated_subjects.insert(config_subject.id.clone());
+            }
             // Map properties to SubjectProperty type
             // FIXME: Unnecessary .collect() because of different iterator types
             let interfaces = config_subject
@@ -483,6 +628,11 @@ impl PolicyEnforcer {
                         .collect::<Vec<_>>()
                 })
                 .unwrap_or(vec![SubjectProperty::Wildcard]);
+            let whatamis = Self::subject_property(config_subject.whatamis);
+            let zids = Self::subject_property(config_subject.zids);
+            let link_protocols = Self::subject_property(config_subject.link_protocols);
+            let source_ips = Self::subject_property(config_subject.source_ips);
+            let metadata = Self::subject_property(config_subject.metadata.map(SubjectMetadata));
 
             // create ACL subject combinations
             let subject_combination_ids = interfaces
@@ -494,6 +644,11 @@ impl PolicyEnforcer {
                         interface,
                         cert_common_name,
                         username,
+                        whatamis: whatamis.clone(),
+                        zids: zids.clone(),
+                        link_protocols: link_protocols.clone(),
+                        source_ips: source_ips.clone(),
+                        metadata: metadata.clone(),
                     };
                     subject_map_builder.insert_or_get(subject)
                 })
@@ -532,6 +687,20 @@ impl PolicyEnforcer {
                     entry_id
                 ))?;
                 for subject_config_id in &entry.subjects {
+                    // NOTE: The zid, whatami and metadata can be spoofed by any remote node: allowing
+                    // a subject matched only on them is permitted, as they may be trusted in a
+                    // closed network, but it is reported.
+                    if rule.permission == Permission::Allow
+                        && unauthenticated_subjects.contains(subject_config_id)
+                        && warned_subjects.insert(subject_config_id.clone())
+                    {
+                        tracing::warn!(
+                            "Rule '{}' in policy #{} allows subject '{}', whose zids, whatamis and metadata are not authenticated: any remote node can claim them unless cert_common_names or usernames are also matched",
+                            rule_id,
+                            entry_id,
+                            subject_config_id
+                        );
+                    }
                     let subject_combination_ids = subject_id_map
                         .get(subject_config_id)
                         .expect("config subject id should exist in subject_id_map");
@@ -544,6 +713,7 @@ impl PolicyEnforcer {
                             for message in &rule.messages {
                                 for key_expr in &rule.key_exprs {
                                     policy_rules.push(PolicyRule {
+                                        rule_id: rule.id.clone(),
                                         subject_id: *subject_id,
                                         key_expr: key_expr.clone(),
                                         message: *message,
@@ -563,6 +733,10 @@ impl PolicyEnforcer {
         })
     }
 
+    fn subject_property<T>(value: Option<T>) -> SubjectProperty<T> {
+        value.map_or(SubjectProperty::Wildcard, SubjectProperty::Exactly)
+    }
+
     /**
      * Check each msg against the ACL ruleset for allow/deny
      */
@@ -572,40 +746,50 @@ impl PolicyEnforcer {
         flow: InterceptorFlow,
         message: AclMessage,
         key_expr: &str,
-    ) -> ZResult<Permission> {
+    ) -> ZResult<PolicyDecision> {
         let policy_map = &self.policy_map;
+        let default_decision = PolicyDecision {
+            permission: self.default_permission,
+            rule_id: None,
+        };
         if policy_map.is_empty() {
-            return Ok(self.default_permission);
+            return Ok(default_decision);
         }
         match policy_map.get(&subject) {
             Some(single_policy) => {
-                let deny_result = single_policy
-                    .flow(flow)
-                    .action(message)
-                    .deny
-                    .nodes_including(keyexpr::new(&key_expr)?)
-                    .count();
-                if deny_result != 0 {
-                    return Ok(Permission::Deny);
+                let key_expr = keyexpr::new(&key_expr)?;
+                let action_policy = single_policy.flow(flow).action(message);
+                if let Some(rule_id) = Self::matching_rule(&action_policy.deny, key_expr) {
+                    return Ok(PolicyDecision {
+                        permission: Permission::Deny,
+                        rule_id: Some(rule_id),
+                    });
                 }
                 if self.default_permission == Permission::Allow {
-                    Ok(Permission::Allow)
+                    Ok(default_decision)
                 } else {
-                    let allow_result = single_policy
-                        .flow(flow)
-                        .action(message)
-                        .allow
-                        .nodes_including(keyexpr::new(&key_expr)?)
-                        .count();
-
-                    if allow_result != 0 {
-                        Ok(Permission::Allow)
-                    } else {
-                        Ok(Permission::Deny)
+                    match Self::matching_rule(&action_policy.allow, key_expr) {
+                        Some(rule_id) => Ok(PolicyDecision {
+                            permission: Permission::Allow,
+                            rule_id: Some(rule_id),
+                        }),
+                        None => Ok(default_decision),
                     }
                 }
             }
-            None => Ok(self.default_permission),
+            None => Ok(default_decision),
         }
     }
+
+    fn matching_rule(ke_tree: &KeTreeRule, key_expr: &keyexpr) -> Option<Arc<str>> {
+        ke_tree
+            .nodes_including(key_expr)
+            .find_map(|node| node.weight().cloned())
+    }
+}
+
+/// The permission of a message, along with the id of the rule it was decided by, if any.
+pub struct PolicyDecision {
+    pub permission: Permission,
+    pub rule_id: Option<Arc<str>>,
 }
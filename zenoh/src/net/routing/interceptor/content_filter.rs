@@ -0,0 +1,346 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This module is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](https://docs.rs/zenoh/latest/zenoh)
+
+use std::{cmp::Ordering, sync::Arc};
+
+use serde_json::Value;
+use zenoh_buffers::{
+    buffer::{Buffer, SplitBuffer},
+    reader::{HasReader, Reader},
+    ZBuf, ZBufReader, ZSlice,
+};
+use zenoh_codec::{RCodec, Zenoh080};
+use zenoh_config::{
+    ContentFilterAction, ContentFilterItemConf, ContentFilterOperator, ContentPredicateConf,
+    InterceptorFlow,
+};
+use zenoh_keyexpr::keyexpr_tree::{
+    impls::KeyedSetProvider, support::UnknownWildness, IKeyExprTree, IKeyExprTreeMut, KeBoxTree,
+};
+use zenoh_protocol::{
+    core::Encoding,
+    network::{NetworkBody, Push},
+    zenoh::{PushBody, Put},
+};
+use zenoh_result::{bail, ZResult};
+
+use crate::net::routing::interceptor::*;
+
+/// The default maximum size of the payloads parsed by the JSON predicates.
+const DEFAULT_MAX_PAYLOAD_SIZE: usize = 64 * 1024;
+
+pub(crate) fn content_filter_interceptor_factories(
+    config: &Vec<ContentFilterItemConf>,
+) -> ZResult<Vec<InterceptorFactory>> {
+    let mut res: Vec<InterceptorFactory> = vec![];
+
+    for filter in config {
+        res.push(Box::new(ContentFilterInterceptorFactory::new(
+            filter.clone(),
+        )?));
+    }
+
+    Ok(res)
+}
+
+pub struct ContentFilterInterceptorFactory {
+    interfaces: Option<Vec<String>>,
+    cert_common_names: Option<Vec<String>>,
+    usernames: Option<Vec<String>>,
+    flows: Option<Vec<InterceptorFlow>>,
+    filter: Arc<ContentFilter>,
+}
+
+impl ContentFilterInterceptorFactory {
+    pub fn new(conf: ContentFilterItemConf) -> ZResult<Self> {
+        if conf.key_exprs.is_empty() {
+            bail!("Content filter key_exprs list is empty");
+        }
+        if conf.encodings.is_none() && conf.predicates.is_empty() {
+            bail!(
+                "Content filter for {:?} must have encodings and/or predicates",
+                conf.key_exprs
+            );
+        }
+
+        let mut key_exprs = KeBoxTree::default();
+        for key_expr in &conf.key_exprs {
+            key_exprs.insert(key_expr, ());
+        }
+        let encodings = conf.encodings.map(|encodings| {
+            encodings
+                .iter()
+                .map(|encoding| crate::api::encoding::Encoding::from(encoding.as_str()).into())
+                .collect()
+        });
+        let predicates = conf
+            .predicates
+            .into_iter()
+            .map(Predicate::new)
+            .collect::<ZResult<_>>()?;
+        Ok(Self {
+            interfaces: conf.interfaces,
+            cert_common_names: conf.cert_common_names,
+            usernames: conf.usernames,
+            flows: conf.flows,
+            filter: Arc::new(ContentFilter {
+                key_exprs,
+                encodings,
+                predicates,
+                max_payload_size: conf.max_payload_size.unwrap_or(DEFAULT_MAX_PAYLOAD_SIZE),
+                action: conf.action,
+            }),
+        })
+    }
+
+    fn new_interceptor(&self, flow: InterceptorFlow) -> Option<Interceptor> {
+        self.flows
+            .as_ref()
+            .map_or(true, |flows| flows.contains(&flow))
+            .then(|| {
+                Box::new(ComputeOnMiss::new(ContentFilterInterceptor {
+                    filter: self.filter.clone(),
+                })) as Interceptor
+            })
+    }
+}
+
+impl InterceptorFactoryTrait for ContentFilterInterceptorFactory {
+    fn new_transport_unicast(
+        &self,
+        transport: &TransportUnicast,
+    ) -> (Option<IngressInterceptor>, Option<EgressInterceptor>) {
+        tracing::debug!("New content filter transport unicast {:?}", transport);
+        if !transport_matches(
+            transport,
+            self.interfaces.as_deref(),
+            self.cert_common_names.as_deref(),
+            self.usernames.as_deref(),
+        ) {
+            return (None, None);
+        }
+
+        (
+            self.new_interceptor(InterceptorFlow::Ingress),
+            self.new_interceptor(InterceptorFlow::Egress),
+        )
+    }
+
+    fn new_transport_multicast(
+        &self,
+        _transport: &TransportMulticast,
+    ) -> Option<EgressInterceptor> {
+        None
+    }
+
+    fn new_peer_multicast(&self, _transport: &TransportMulticast) -> Option<IngressInterceptor> {
+        None
+    }
+}
+
+enum Predicate {
+    Json {
+        pointer: String,
+        operator: ContentFilterOperator,
+        value: Value,
+    },
+    AttachmentKey(String),
+}
+
+impl Predicate {
+    fn new(conf: ContentPredicateConf) -> ZResult<Self> {
+        match conf {
+            ContentPredicateConf::Json(conf) => {
+                if !conf.pointer.is_empty() && !conf.pointer.starts_with('/') {
+                    bail!(
+                        "Content filter JSON pointer '{}' must be empty or start with '/'",
+                        conf.pointer
+                    );
+                }
+                let value = match (conf.operator, conf.value) {
+                    (ContentFilterOperator::Exists, None) => Value::Null,
+                    (ContentFilterOperator::Exists, Some(_)) => bail!(
+                        "Content filter on JSON pointer '{}' has a value with the exists operator",
+                        conf.pointer
+                    ),
+                    (_, Some(value)) => value,
+                    (operator, None) => bail!(
+                        "Content filter on JSON pointer '{}' has no value to compare with the {:?} operator",
+                        conf.pointer,
+                        operator
+                    ),
+                };
+                Ok(Predicate::Json {
+                    pointer: conf.pointer,
+                    operator: conf.operator,
+                    value,
+                })
+            }
+            ContentPredicateConf::AttachmentKey(key) => Ok(Predicate::AttachmentKey(key)),
+        }
+    }
+}
+
+/// Compares JSON numbers by value and JSON strings lexicographically.
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64()?.partial_cmp(&right.as_f64()?),
+        (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+        _ => None,
+    }
+}
+
+fn evaluate(operator: ContentFilterOperator, left: &Value, right: &Value) -> bool {
+    let equals = || compare(left, right).map_or(left == right, Ordering::is_eq);
+    match operator {
+        ContentFilterOperator::Exists => true,
+        ContentFilterOperator::Eq => equals(),
+        ContentFilterOperator::Ne => !equals(),
+        ContentFilterOperator::Lt => compare(left, right).is_some_and(Ordering::is_lt),
+        ContentFilterOperator::Le => compare(left, right).is_some_and(Ordering::is_le),
+        ContentFilterOperator::Gt => compare(left, right).is_some_and(Ordering::is_gt),
+        ContentFilterOperator::Ge => compare(left, right).is_some_and(Ordering::is_ge),
+    }
+}
+
+/// Returns `true` if `attachment` is a map of strings or bytes serialized by `zenoh_ext`, one of
+/// its keys being `key`. Any other attachment, including one with trailing bytes, doesn't match.
+///
+/// NOTE: `zenoh_ext` can't be used here as it depends on this crate. Its serialization of a map is
+///       the number of entries followed by the length prefixed keys and values, the numbers being
+///       LEB128 encoded, which are read with the variable length integers of the zenoh codec.
+fn attachment_has_key(attachment: &ZBuf, key: &str) -> bool {
+    fn read_bytes(reader: &mut ZBufReader<'_>) -> Option<ZSlice> {
+        let len: usize = Zenoh080::new().read(&mut *reader).ok()?;
+        if len > reader.remaining() {
+            return None;
+        }
+        reader.read_zslice(len).ok()
+    }
+
+    fn has_key(reader: &mut ZBufReader<'_>, key: &str) -> Option<bool> {
+        let len: usize = Zenoh080::new().read(&mut *reader).ok()?;
+        let mut found = false;
+        for _ in 0..len {
+            found |= read_bytes(reader)?.as_slice() == key.as_bytes();
+            read_bytes(reader)?;
+        }
+        Some(found && !reader.can_read())
+    }
+
+    has_key(&mut attachment.reader(), key).unwrap_or(false)
+}
+
+struct ContentFilter {
+    key_exprs: KeBoxTree<(), UnknownWildness, KeyedSetProvider>,
+    encodings: Option<Vec<Encoding>>,
+    predicates: Vec<Predicate>,
+    max_payload_size: usize,
+    action: ContentFilterAction,
+}
+
+impl ContentFilter {
+    fn intersects(&self, key_expr: &KeyExpr<'_>) -> bool {
+        self.key_exprs.intersecting_nodes(key_expr).next().is_some()
+    }
+
+    fn matches_encoding(&self, encoding: &Encoding) -> bool {
+        self.encodings.as_ref().map_or(true, |encodings| {
+            encodings
+                .iter()
+                .any(|e| e.id == encoding.id && (e.schema.is_none() || e.schema == encoding.schema))
+        })
+    }
+
+    fn matches(&self, put: &Put) -> bool {
+        if !self.matches_encoding(&put.encoding) {
+            return false;
+        }
+        // the payloads too large to be parsed don't match the JSON predicates
+        let parse = || {
+            if put.payload.len() > self.max_payload_size {
+                return None;
+            }
+            serde_json::from_slice(&put.payload.contiguous()).ok()
+        };
+        let mut json: Option<Option<Value>> = None;
+        self.predicates.iter().all(|predicate| match predicate {
+            Predicate::Json {
+                pointer,
+                operator,
+                value,
+            } => json
+                .get_or_insert_with(parse)
+                .as_ref()
+                .and_then(|json| json.pointer(pointer))
+                .is_some_and(|v| evaluate(*operator, v, value)),
+            Predicate::AttachmentKey(key) => put
+                .ext_attachment
+                .as_ref()
+                .is_some_and(|attachment| attachment_has_key(&attachment.buffer, key)),
+        })
+    }
+
+    /// Returns `true` if the publication is forwarded.
+    fn forwards(&self, put: &Put) -> bool {
+        match self.action {
+            ContentFilterAction::Forward => self.matches(put),
+            ContentFilterAction::Drop => !self.matches(put),
+        }
+    }
+}
+
+/// Forwards or drops the publications on the configured key expressions depending on their
+/// content. Deletions are always forwarded.
+pub(crate) struct ContentFilterInterceptor {
+    filter: Arc<ContentFilter>,
+}
+
+impl InterceptorTrait for ContentFilterInterceptor {
+    fn compute_keyexpr_cache(&self, key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>> {
+        Some(Box::new(self.filter.intersects(key_expr)))
+    }
+
+    fn intercept(
+        &self,
+        ctx: RoutingContext<NetworkMessage>,
+        cache: Option<&Box<dyn Any + Send + Sync>>,
+    ) -> Option<RoutingContext<NetworkMessage>> {
+        let NetworkBody::Push(Push {
+            payload: PushBody::Put(put),
+            ..
+        }) = &ctx.msg.body
+        else {
+            return Some(ctx);
+        };
+        let Some(intersects) = cache.and_then(|cache| cache.downcast_ref::<bool>()) else {
+            tracing::debug!("unexpected cache type {:?}", ctx.full_expr());
+            return Some(ctx);
+        };
+        if *intersects && !self.filter.forwards(put) {
+            tracing::trace!(
+                "Content filter dropped publication on {:?}",
+                ctx.full_expr()
+            );
+            return None;
+        }
+        Some(ctx)
+    }
+}
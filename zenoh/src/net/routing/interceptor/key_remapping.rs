@@ -0,0 +1,454 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The remapping of the prefixes of the key expressions exchanged with the remote nodes.
+//!
+//! The key expressions that can't be remapped unambiguously are rejected: the wildcard key
+//! expressions intersecting a prefix to replace without starting with it, and the key expressions
+//! intersecting a replacement prefix without being remapped to it, which would be confused with
+//! the remapped ones on the receiving side.
+
+use std::sync::Arc;
+
+use zenoh_config::{InterceptorFlow, KeyRemappingItemConf};
+use zenoh_keyexpr::{keyexpr, OwnedKeyExpr};
+use zenoh_result::{bail, ZResult};
+
+use crate::net::routing::interceptor::*;
+
+/// Returns the factories of the interceptors remapping the key expressions in the provided flow.
+///
+/// The ingress and egress remapping are provided by distinct factories such that the remapping
+/// can be the first interceptor on ingress and the last one on egress.
+pub(crate) fn key_remapping_interceptor_factories(
+    config: &Vec<KeyRemappingItemConf>,
+    flow: InterceptorFlow,
+) -> ZResult<Vec<InterceptorFactory>> {
+    let mut res: Vec<InterceptorFactory> = vec![];
+
+    for kr in config {
+        if kr
+            .flows
+            .as_ref()
+            .map_or(true, |flows| flows.contains(&flow))
+        {
+            res.push(Box::new(KeyRemappingInterceptorFactory::new(
+                kr.clone(),
+                flow,
+            )?));
+        }
+    }
+
+    Ok(res)
+}
+
+/// Checks that a remapping prefix is either empty or a key expression without wildcards.
+fn validate_prefix(prefix: &str) -> ZResult<()> {
+    if prefix.is_empty() {
+        return Ok(());
+    }
+    let ke = match keyexpr::new(prefix) {
+        Ok(ke) => ke,
+        Err(e) => bail!("Invalid key remapping prefix '{}': {}", prefix, e),
+    };
+    if ke.is_wild() {
+        bail!(
+            "Invalid key remapping prefix '{}': wildcards are not allowed",
+            prefix
+        );
+    }
+    Ok(())
+}
+
+/// Returns `expr` with its prefix `from` replaced by `to`, if `expr` starts with the chunks of
+/// `from`.
+pub(crate) fn replace_prefix(expr: &str, from: &str, to: &str) -> Option<String> {
+    let rest = if from.is_empty() {
+        expr
+    } else {
+        let rest = expr.strip_prefix(from)?;
+        if rest.is_empty() {
+            rest
+        } else {
+            rest.strip_prefix('/')?
+        }
+    };
+    match (to.is_empty(), rest.is_empty()) {
+        (true, true) => None,
+        (true, false) => Some(rest.to_string()),
+        (false, true) => Some(to.to_string()),
+        (false, false) => Some(format!("{to}/{rest}")),
+    }
+}
+
+/// A key remapping rule, in the direction of the flow.
+struct Rule {
+    /// The prefix to replace.
+    from: String,
+    /// The replacement of the prefix.
+    to: String,
+    /// The key expressions under the prefix to replace.
+    from_keys: OwnedKeyExpr,
+    /// The key expressions under the replacement prefix.
+    to_keys: OwnedKeyExpr,
+}
+
+impl Rule {
+    fn new(from: String, to: String) -> Self {
+        Self {
+            from_keys: prefix_keys(&from),
+            to_keys: prefix_keys(&to),
+            from,
+            to,
+        }
+    }
+}
+
+/// Returns the key expressions under a valid prefix, all of them if it is empty.
+fn prefix_keys(prefix: &str) -> OwnedKeyExpr {
+    let keys = if prefix.is_empty() {
+        "**".to_string()
+    } else {
+        format!("{prefix}/**")
+    };
+    OwnedKeyExpr::new(keys).expect("prefix should have been validated")
+}
+
+pub struct KeyRemappingInterceptorFactory {
+    interfaces: Option<Vec<String>>,
+    flow: InterceptorFlow,
+    rules: Arc<Vec<Rule>>,
+}
+
+impl KeyRemappingInterceptorFactory {
+    pub fn new(conf: KeyRemappingItemConf, flow: InterceptorFlow) -> ZResult<Self> {
+        let mut rules = Vec::with_capacity(conf.rules.len());
+        for rule in conf.rules {
+            validate_prefix(&rule.remote_prefix)?;
+            validate_prefix(&rule.local_prefix)?;
+            if rule.remote_prefix == rule.local_prefix {
+                bail!(
+                    "Invalid key remapping rule: identical remote and local prefixes '{}'",
+                    rule.remote_prefix
+                );
+            }
+            rules.push(match flow {
+                InterceptorFlow::Ingress => Rule::new(rule.remote_prefix, rule.local_prefix),
+                InterceptorFlow::Egress => Rule::new(rule.local_prefix, rule.remote_prefix),
+            });
+        }
+
+        Ok(Self {
+            interfaces: conf.interfaces,
+            flow,
+            rules: Arc::new(rules),
+        })
+    }
+}
+
+impl InterceptorFactoryTrait for KeyRemappingInterceptorFactory {
+    fn new_transport_unicast(
+        &self,
+        transport: &TransportUnicast,
+    ) -> (Option<IngressInterceptor>, Option<EgressInterceptor>) {
+        tracing::debug!("New key remapper transport unicast {:?}", transport);
+        if let Some(interfaces) = &self.interfaces {
+            if let Ok(links) = transport.get_links() {
+                for link in links {
+                    if !link.interfaces.iter().any(|x| interfaces.contains(x)) {
+                        return (None, None);
+                    }
+                }
+            }
+        };
+
+        let interceptor: Interceptor = Box::new(KeyRemappingInterceptor {
+            flow: self.flow,
+            rules: self.rules.clone(),
+        });
+        match self.flow {
+            InterceptorFlow::Ingress => (Some(interceptor), None),
+            InterceptorFlow::Egress => (None, Some(interceptor)),
+        }
+    }
+
+    fn new_transport_multicast(
+        &self,
+        _transport: &TransportMulticast,
+    ) -> Option<EgressInterceptor> {
+        None
+    }
+
+    fn new_peer_multicast(&self, _transport: &TransportMulticast) -> Option<IngressInterceptor> {
+        None
+    }
+}
+
+/// The remapping of a key expression.
+#[derive(Debug, PartialEq, Eq)]
+enum Remapping {
+    Remap(String),
+    Keep,
+    Reject,
+}
+
+/// What to do with a message, according to the remapping of its key expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Decision {
+    /// The message is forwarded as is: its wire expression is resolved to the expected key
+    /// expression by the receiver.
+    Forward,
+    /// The key expression of the message is replaced.
+    Replace(String),
+    /// The message is dropped.
+    Drop,
+}
+
+pub(crate) struct KeyRemappingInterceptor {
+    flow: InterceptorFlow,
+    rules: Arc<Vec<Rule>>,
+}
+
+impl KeyRemappingInterceptor {
+    fn resolve(&self, expr: &str) -> Remapping {
+        // The invalid key expressions are left to the router.
+        let Ok(ke) = keyexpr::new(expr) else {
+            return Remapping::Keep;
+        };
+        if let Some(remapped) = self
+            .rules
+            .iter()
+            .find_map(|rule| replace_prefix(expr, &rule.from, &rule.to))
+        {
+            return Remapping::Remap(remapped);
+        }
+        if self
+            .rules
+            .iter()
+            .any(|rule| ke.intersects(&rule.from_keys) || ke.intersects(&rule.to_keys))
+        {
+            return Remapping::Reject;
+        }
+        Remapping::Keep
+    }
+
+    /// Returns the key expression on the sending side of a key expression declared on the
+    /// receiving side, reverting its remapping.
+    fn unmap(&self, expr: &str) -> String {
+        self.rules
+            .iter()
+            .find_map(|rule| replace_prefix(expr, &rule.to, &rule.from))
+            .unwrap_or_else(|| expr.to_string())
+    }
+
+    /// Returns the key expression on the receiving side of a key expression declared on the
+    /// sending side.
+    fn map(&self, expr: &str) -> String {
+        match self.resolve(expr) {
+            Remapping::Remap(remapped) => remapped,
+            Remapping::Keep | Remapping::Reject => expr.to_string(),
+        }
+    }
+
+    /// Decides how to forward a message whose key expression is `source` on the sending side,
+    /// and whose wire expression is resolved to `receiver` by the receiver.
+    ///
+    /// If `replace`, the wire expression is always replaced.
+    fn decide(&self, source: &str, receiver: &str, replace: bool) -> Decision {
+        let target = match self.resolve(source) {
+            Remapping::Remap(remapped) => remapped,
+            Remapping::Keep => source.to_string(),
+            Remapping::Reject => return Decision::Drop,
+        };
+        if !replace && target == receiver {
+            Decision::Forward
+        } else {
+            Decision::Replace(target)
+        }
+    }
+
+    /// Decides how to forward a message whose wire expression is the key expression `prefix`,
+    /// declared on the face, followed by `suffix`.
+    ///
+    /// The key expressions declarations go through the interceptor too: on ingress, `prefix` was
+    /// remapped when declared by the remote node, while on egress it is remapped by the remote
+    /// node. The wire expressions are thus only replaced if the receiver would not resolve them to
+    /// the remapped key expressions, which preserves the declared key expressions and the caches
+    /// of the interceptors.
+    fn decide_mapped(&self, prefix: &str, suffix: &str) -> Decision {
+        match self.flow {
+            InterceptorFlow::Ingress => self.decide(
+                &format!("{}{suffix}", self.unmap(prefix)),
+                &format!("{prefix}{suffix}"),
+                false,
+            ),
+            InterceptorFlow::Egress => self.decide(
+                &format!("{prefix}{suffix}"),
+                &format!("{}{suffix}", self.map(prefix)),
+                // The declaration of a rejected prefix was not sent to the remote node.
+                self.resolve(prefix) == Remapping::Reject,
+            ),
+        }
+    }
+
+    fn decide_message(&self, ctx: &RoutingContext<NetworkMessage>) -> Option<Decision> {
+        let wire_expr = ctx.wire_expr()?;
+        let suffix = wire_expr.suffix.as_ref();
+        if wire_expr.scope == 0 {
+            return Some(self.decide(suffix, suffix, false));
+        }
+        let prefix = ctx.prefix()?.expr();
+        Some(self.decide_mapped(&prefix, suffix))
+    }
+}
+
+impl InterceptorTrait for KeyRemappingInterceptor {
+    fn compute_keyexpr_cache(&self, key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>> {
+        // The caches are only provided for the wire expressions without suffix.
+        Some(Box::new(self.decide_mapped(key_expr.as_str(), "")))
+    }
+
+    fn intercept(
+        &self,
+        mut ctx: RoutingContext<NetworkMessage>,
+        cache: Option<&Box<dyn Any + Send + Sync>>,
+    ) -> Option<RoutingContext<NetworkMessage>> {
+        let decision = match cache.and_then(|cache| cache.downcast_ref::<Decision>()) {
+            Some(decision) => decision.clone(),
+            None => match self.decide_message(&ctx) {
+                Some(decision) => decision,
+                None => return Some(ctx),
+            },
+        };
+        match decision {
+            Decision::Forward => Some(ctx),
+            Decision::Replace(expr) => {
+                tracing::trace!("Remapping {:?} to {}", ctx.full_expr(), expr);
+                ctx.set_full_expr(expr);
+                Some(ctx)
+            }
+            Decision::Drop => {
+                tracing::debug!(
+                    "Dropping {} on {:?}: its key expression can't be remapped unambiguously",
+                    ctx.msg,
+                    ctx.full_expr()
+                );
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_prefix() {
+        assert_eq!(
+            replace_prefix("robot1/**", "robot1", "fleet/robot1").as_deref(),
+            Some("fleet/robot1/**")
+        );
+        assert_eq!(
+            replace_prefix("robot1", "robot1", "fleet/robot1").as_deref(),
+            Some("fleet/robot1")
+        );
+        assert_eq!(replace_prefix("robot10/a", "robot1", "fleet/robot1"), None);
+        assert_eq!(replace_prefix("**", "robot1", "fleet/robot1"), None);
+
+        // An empty prefix adds or strips the other one.
+        assert_eq!(
+            replace_prefix("a/b", "", "site").as_deref(),
+            Some("site/a/b")
+        );
+        assert_eq!(
+            replace_prefix("site/a/b", "site", "").as_deref(),
+            Some("a/b")
+        );
+        assert_eq!(replace_prefix("site", "site", ""), None);
+    }
+
+    fn interceptor(flow: InterceptorFlow) -> KeyRemappingInterceptor {
+        let factory = KeyRemappingInterceptorFactory::new(
+            KeyRemappingItemConf {
+                interfaces: None,
+                flows: None,
+                rules: vec![zenoh_config::KeyRemappingRuleConf {
+                    remote_prefix: "robot1".to_string(),
+                    local_prefix: "fleet/robot1".to_string(),
+                }],
+            },
+            flow,
+        )
+        .unwrap();
+        KeyRemappingInterceptor {
+            flow,
+            rules: factory.rules,
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        let ingress = interceptor(InterceptorFlow::Ingress);
+        assert_eq!(
+            ingress.resolve("robot1/**"),
+            Remapping::Remap("fleet/robot1/**".to_string())
+        );
+        assert_eq!(ingress.resolve("robot2/a"), Remapping::Keep);
+        // The wildcards intersecting the prefix can't be remapped.
+        assert_eq!(ingress.resolve("**"), Remapping::Reject);
+        assert_eq!(ingress.resolve("robot*/**"), Remapping::Reject);
+        // The remote key expressions under the local prefix would be confused with the remapped
+        // ones.
+        assert_eq!(ingress.resolve("fleet/robot1/a"), Remapping::Reject);
+        assert_eq!(ingress.resolve("fleet/*/a"), Remapping::Reject);
+        assert_eq!(ingress.resolve("fleet/robot2/a"), Remapping::Keep);
+
+        let egress = interceptor(InterceptorFlow::Egress);
+        assert_eq!(
+            egress.resolve("fleet/robot1/a"),
+            Remapping::Remap("robot1/a".to_string())
+        );
+        assert_eq!(egress.resolve("robot1/a"), Remapping::Reject);
+    }
+
+    #[test]
+    fn test_decide_mapped() {
+        // The prefixes declared by the remote node were remapped when declared.
+        let ingress = interceptor(InterceptorFlow::Ingress);
+        assert_eq!(
+            ingress.decide_mapped("fleet/robot1", "/a"),
+            Decision::Forward
+        );
+        assert_eq!(ingress.decide_mapped("robot2", "/a"), Decision::Forward);
+        assert_eq!(
+            ingress.decide_mapped("robot", "1/a"),
+            Decision::Replace("fleet/robot1/a".to_string())
+        );
+        assert_eq!(ingress.decide_mapped("robot", "*/a"), Decision::Drop);
+
+        // The prefixes declared to the remote node are remapped by it, unless they were rejected.
+        let egress = interceptor(InterceptorFlow::Egress);
+        assert_eq!(
+            egress.decide_mapped("fleet/robot1", "/a"),
+            Decision::Forward
+        );
+        assert_eq!(
+            egress.decide_mapped("fleet/robot", "1/a"),
+            Decision::Replace("robot1/a".to_string())
+        );
+        assert_eq!(egress.decide_mapped("robot1", "/a"), Decision::Drop);
+        assert_eq!(egress.decide_mapped("fleet/*", "/b"), Decision::Drop);
+    }
+}
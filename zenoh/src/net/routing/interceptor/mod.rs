@@ -12,22 +12,28 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
-//! ⚠️ WARNING ⚠️
-//!
-//! This module is intended for Zenoh's internal use.
-//!
-//! [Click here for Zenoh's documentation](https://docs.rs/zenoh/latest/zenoh)
+//! The interceptors of the messages routed by Zenoh.
 //!
+//! The traits defined here are exposed, behind the `unstable` feature, as the public
+//! `zenoh::interceptor` API through which plugins provide their own interceptors. The
+//! interceptors configured for a Runtime (key remapping, downsampling, rate limiting, access
+//! control...) are built on the same traits.
 mod access_control;
 use access_control::acl_interceptor_factories;
+pub(crate) use access_control::{shared_policy_enforcer, SharedPolicyEnforcer};
 
+mod audit;
+pub(crate) use audit::{AclAuditRecord, AUDIT_KEY_SUFFIX};
 mod authorization;
-use std::any::Any;
+use std::{any::Any, sync::Arc, time::Duration};
 
-use zenoh_config::Config;
+use zenoh_config::{Config, InterceptorFlow};
 use zenoh_protocol::network::NetworkMessage;
 use zenoh_result::ZResult;
-use zenoh_transport::{multicast::TransportMulticast, unicast::TransportUnicast};
+use zenoh_transport::{
+    multicast::TransportMulticast,
+    unicast::{authentication::AuthId, TransportUnicast},
+};
 
 use super::RoutingContext;
 use crate::api::key_expr::KeyExpr;
@@ -35,9 +41,32 @@ use crate::api::key_expr::KeyExpr;
 pub mod downsampling;
 use crate::net::routing::interceptor::downsampling::downsampling_interceptor_factories;
 
-pub(crate) trait InterceptorTrait {
+pub mod rate_limiting;
+use crate::net::routing::interceptor::rate_limiting::rate_limiting_interceptor_factories;
+
+pub mod key_remapping;
+use crate::net::routing::interceptor::key_remapping::key_remapping_interceptor_factories;
+
+pub mod quotas;
+use crate::net::routing::interceptor::quotas::quotas_interceptor_factories;
+
+pub mod qos_overwrite;
+use crate::net::routing::interceptor::qos_overwrite::qos_overwrite_interceptor_factories;
+
+pub mod content_filter;
+use crate::net::routing::interceptor::content_filter::content_filter_interceptor_factories;
+
+/// An interceptor of the messages received from (ingress) or sent to (egress) a face.
+pub trait InterceptorTrait {
+    /// Computes a value associated to a key expression declared on the face.
+    ///
+    /// The value is computed once per declared key expression and provided to
+    /// [`intercept`](InterceptorTrait::intercept) for the messages on this key expression, such
+    /// that the per-key expression work is not repeated for each message.
     fn compute_keyexpr_cache(&self, key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>>;
 
+    /// Intercepts a message, returning the (possibly modified) message to route or `None` to drop
+    /// it.
     fn intercept(
         &self,
         ctx: RoutingContext<NetworkMessage>,
@@ -45,11 +74,14 @@ pub(crate) trait InterceptorTrait {
     ) -> Option<RoutingContext<NetworkMessage>>;
 }
 
-pub(crate) type Interceptor = Box<dyn InterceptorTrait + Send + Sync>;
-pub(crate) type IngressInterceptor = Interceptor;
-pub(crate) type EgressInterceptor = Interceptor;
+pub type Interceptor = Box<dyn InterceptorTrait + Send + Sync>;
+pub type IngressInterceptor = Interceptor;
+pub type EgressInterceptor = Interceptor;
 
-pub(crate) trait InterceptorFactoryTrait {
+/// A factory of the [`Interceptor`]s of the faces created by the router.
+///
+/// Each method is called once when the corresponding face is created.
+pub trait InterceptorFactoryTrait {
     fn new_transport_unicast(
         &self,
         transport: &TransportUnicast,
@@ -58,21 +90,99 @@ pub(crate) trait InterceptorFactoryTrait {
     fn new_peer_multicast(&self, transport: &TransportMulticast) -> Option<IngressInterceptor>;
 }
 
-pub(crate) type InterceptorFactory = Box<dyn InterceptorFactoryTrait + Send + Sync>;
+pub type InterceptorFactory = Box<dyn InterceptorFactoryTrait + Send + Sync>;
+
+/// Returns `true` if the transport has one of the `interfaces`, one of the `cert_common_names`
+/// and one of the `usernames`, each condition being met if the corresponding list is `None`.
+pub(crate) fn transport_matches(
+    transport: &TransportUnicast,
+    interfaces: Option<&[String]>,
+    cert_common_names: Option<&[String]>,
+    usernames: Option<&[String]>,
+) -> bool {
+    if let Some(interfaces) = interfaces {
+        let Ok(links) = transport.get_links() else {
+            return false;
+        };
+        if !links
+            .iter()
+            .any(|link| link.interfaces.iter().any(|x| interfaces.contains(x)))
+        {
+            return false;
+        }
+    }
 
-pub(crate) fn interceptor_factories(config: &Config) -> ZResult<Vec<InterceptorFactory>> {
+    if cert_common_names.is_none() && usernames.is_none() {
+        return true;
+    }
+    let Ok(auth_ids) = transport.get_auth_ids() else {
+        return false;
+    };
+    let (mut cert_common_name_matches, mut username_matches) =
+        (cert_common_names.is_none(), usernames.is_none());
+    for auth_id in auth_ids {
+        match auth_id {
+            AuthId::CertCommonName(value) => {
+                cert_common_name_matches |=
+                    cert_common_names.is_some_and(|names| names.contains(&value));
+            }
+            AuthId::Username(value) => {
+                username_matches |= usernames.is_some_and(|names| names.contains(&value));
+            }
+            AuthId::None => {}
+        }
+    }
+    cert_common_name_matches && username_matches
+}
+
+/// Returns the factories of the interceptors configured in `config`, along with the number of
+/// them, at the end of the list, that must remain last when factories are added at runtime.
+pub(crate) fn interceptor_factories(
+    config: &Config,
+    acl: Option<&Arc<SharedPolicyEnforcer>>,
+) -> ZResult<(Vec<InterceptorFactory>, usize)> {
     let mut res: Vec<InterceptorFactory> = vec![];
     // Uncomment to log the interceptors initialisation
     // res.push(Box::new(LoggerInterceptor {}));
+    // The other interceptors see the local key expressions: the key expressions are remapped
+    // first on ingress and last on egress.
+    res.extend(key_remapping_interceptor_factories(
+        config.key_remapping(),
+        InterceptorFlow::Ingress,
+    )?);
     res.extend(downsampling_interceptor_factories(config.downsampling())?);
-    res.extend(acl_interceptor_factories(config.access_control())?);
-    Ok(res)
+    res.extend(rate_limiting_interceptor_factories(config.rate_limiting())?);
+    res.extend(acl_interceptor_factories(acl));
+    res.extend(quotas_interceptor_factories(config.quotas()));
+    res.extend(qos_overwrite_interceptor_factories(config.qos_overwrite())?);
+    res.extend(content_filter_interceptor_factories(
+        config.content_filters(),
+    )?);
+    let egress_key_remapping =
+        key_remapping_interceptor_factories(config.key_remapping(), InterceptorFlow::Egress)?;
+    let last = egress_key_remapping.len();
+    res.extend(egress_key_remapping);
+    Ok((res, last))
 }
 
 pub(crate) struct InterceptorsChain {
     pub(crate) interceptors: Vec<Interceptor>,
 }
 
+/// The outcome of the interceptors of a chain for a message.
+pub(crate) enum Interception {
+    /// The message is routed.
+    Route(RoutingContext<NetworkMessage>),
+    /// The message goes through the interceptors from `next` once `delay` has elapsed.
+    Delay {
+        ctx: RoutingContext<NetworkMessage>,
+        next: usize,
+        delay: Duration,
+    },
+    /// The message is dropped.
+    Drop,
+}
+
 impl InterceptorsChain {
     #[allow(dead_code)]
     pub(crate) fn empty() -> Self {
@@ -80,6 +190,46 @@ impl InterceptorsChain {
             interceptors: vec![],
         }
     }
+
+    /// Intercepts a message with the interceptors of the chain from `start`, stopping at the
+    /// first one delaying it.
+    pub(crate) fn intercept_from(
+        &self,
+        start: usize,
+        mut ctx: RoutingContext<NetworkMessage>,
+        caches: Option<&Box<dyn Any + Send + Sync>>,
+    ) -> Interception {
+        let mut caches =
+            caches.and_then(|i| i.downcast_ref::<Vec<Option<Box<dyn Any + Send + Sync>>>>());
+        for (idx, interceptor) in self.interceptors.iter().enumerate().skip(start) {
+            let cache = caches
+                .and_then(|caches| caches.get(idx).map(|k| k.as_ref()))
+                .flatten();
+            match interceptor.intercept(ctx, cache) {
+                Some(newctx) => {
+                    ctx = newctx;
+                    // The caches are only provided for the messages whose wire expression has no
+                    // suffix: if one now has a suffix, its key expression was remapped and the
+                    // caches do not apply to it anymore.
+                    if caches.is_some() && ctx.wire_expr().is_some_and(|we| we.has_suffix()) {
+                        caches = None;
+                    }
+                    if let Some(delay) = ctx.delay.take() {
+                        return Interception::Delay {
+                            ctx,
+                            next: idx + 1,
+                            delay,
+                        };
+                    }
+                }
+                None => {
+                    tracing::trace!("Msg intercepted!");
+                    return Interception::Drop;
+                }
+            }
+        }
+        Interception::Route(ctx)
+    }
 }
 
 impl From<Vec<Interceptor>> for InterceptorsChain {
@@ -100,24 +250,21 @@ impl InterceptorTrait for InterceptorsChain {
 
     fn intercept<'a>(
         &self,
-        mut ctx: RoutingContext<NetworkMessage>,
+        ctx: RoutingContext<NetworkMessage>,
         caches: Option<&Box<dyn Any + Send + Sync>>,
     ) -> Option<RoutingContext<NetworkMessage>> {
-        let caches =
-            caches.and_then(|i| i.downcast_ref::<Vec<Option<Box<dyn Any + Send + Sync>>>>());
-        for (idx, interceptor) in self.interceptors.iter().enumerate() {
-            let cache = caches
-                .and_then(|caches| caches.get(idx).map(|k| k.as_ref()))
-                .flatten();
-            match interceptor.intercept(ctx, cache) {
-                Some(newctx) => ctx = newctx,
-                None => {
-                    tracing::trace!("Msg intercepted!");
-                    return None;
+        // NOTE: The messages are only delayed by the `DeMux` of their face, a delay requested
+        //       anywhere else is ignored.
+        let mut interception = self.intercept_from(0, ctx, caches);
+        loop {
+            match interception {
+                Interception::Route(ctx) => return Some(ctx),
+                Interception::Delay { ctx, next, .. } => {
+                    interception = self.intercept_from(next, ctx, None)
                 }
+                Interception::Drop => return None,
             }
         }
-        Some(ctx)
     }
 }
 
@@ -0,0 +1,213 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This module is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](https://docs.rs/zenoh/latest/zenoh)
+
+use std::sync::Arc;
+
+use zenoh_config::{
+    InterceptorFlow, QosOverwriteItemConf, QosOverwriteMessage, QosOverwrites, WhatAmI,
+};
+use zenoh_keyexpr::keyexpr_tree::{
+    impls::KeyedSetProvider, support::UnknownWildness, IKeyExprTree, IKeyExprTreeMut, KeBoxTree,
+};
+use zenoh_protocol::network::{ext::QoSType, NetworkBody};
+use zenoh_result::{bail, ZResult};
+
+use crate::net::routing::interceptor::*;
+
+pub(crate) fn qos_overwrite_interceptor_factories(
+    config: &Vec<QosOverwriteItemConf>,
+) -> ZResult<Vec<InterceptorFactory>> {
+    let mut res: Vec<InterceptorFactory> = vec![];
+
+    for qos in config {
+        res.push(Box::new(QosOverwriteInterceptorFactory::new(qos.clone())?));
+    }
+
+    Ok(res)
+}
+
+pub struct QosOverwriteInterceptorFactory {
+    interfaces: Option<Vec<String>>,
+    cert_common_names: Option<Vec<String>>,
+    usernames: Option<Vec<String>>,
+    whatamis: Option<Vec<WhatAmI>>,
+    flows: Option<Vec<InterceptorFlow>>,
+    state: Arc<QosOverwriteState>,
+}
+
+impl QosOverwriteInterceptorFactory {
+    pub fn new(conf: QosOverwriteItemConf) -> ZResult<Self> {
+        if conf.key_exprs.is_empty() {
+            bail!("QoS overwrite key_exprs list is empty");
+        }
+        let overwrite = &conf.overwrite;
+        if overwrite.priority.is_none()
+            && overwrite.congestion_control.is_none()
+            && overwrite.express.is_none()
+        {
+            bail!(
+                "QoS overwrite for {:?} must overwrite the priority, congestion_control and/or express",
+                conf.key_exprs
+            );
+        }
+
+        let mut key_exprs = KeBoxTree::default();
+        for key_expr in &conf.key_exprs {
+            key_exprs.insert(key_expr, ());
+        }
+        Ok(Self {
+            interfaces: conf.interfaces,
+            cert_common_names: conf.cert_common_names,
+            usernames: conf.usernames,
+            whatamis: conf.whatamis,
+            flows: conf.flows,
+            state: Arc::new(QosOverwriteState {
+                key_exprs,
+                messages: conf.messages,
+                overwrite: conf.overwrite,
+            }),
+        })
+    }
+
+    /// Returns `true` if the messages of the transport are subject to the QoS overwrite.
+    fn applies_to(&self, transport: &TransportUnicast) -> bool {
+        if let Some(whatamis) = &self.whatamis {
+            if !transport
+                .get_whatami()
+                .is_ok_and(|whatami| whatamis.contains(&whatami))
+            {
+                return false;
+            }
+        }
+        transport_matches(
+            transport,
+            self.interfaces.as_deref(),
+            self.cert_common_names.as_deref(),
+            self.usernames.as_deref(),
+        )
+    }
+
+    fn new_interceptor(&self, flow: InterceptorFlow) -> Option<Interceptor> {
+        self.flows
+            .as_ref()
+            .map_or(true, |flows| flows.contains(&flow))
+            .then(|| {
+                Box::new(ComputeOnMiss::new(QosOverwriteInterceptor {
+                    state: self.state.clone(),
+                })) as Interceptor
+            })
+    }
+}
+
+impl InterceptorFactoryTrait for QosOverwriteInterceptorFactory {
+    fn new_transport_unicast(
+        &self,
+        transport: &TransportUnicast,
+    ) -> (Option<IngressInterceptor>, Option<EgressInterceptor>) {
+        tracing::debug!("New QoS overwriter transport unicast {:?}", transport);
+        if !self.applies_to(transport) {
+            return (None, None);
+        }
+
+        (
+            self.new_interceptor(InterceptorFlow::Ingress),
+            self.new_interceptor(InterceptorFlow::Egress),
+        )
+    }
+
+    fn new_transport_multicast(
+        &self,
+        _transport: &TransportMulticast,
+    ) -> Option<EgressInterceptor> {
+        None
+    }
+
+    fn new_peer_multicast(&self, _transport: &TransportMulticast) -> Option<IngressInterceptor> {
+        None
+    }
+}
+
+struct QosOverwriteState {
+    key_exprs: KeBoxTree<(), UnknownWildness, KeyedSetProvider>,
+    messages: Option<Vec<QosOverwriteMessage>>,
+    overwrite: QosOverwrites,
+}
+
+impl QosOverwriteState {
+    fn matches(&self, key_expr: &KeyExpr<'_>) -> bool {
+        self.key_exprs.intersecting_nodes(key_expr).next().is_some()
+    }
+
+    fn overwrite<const ID: u8>(&self, qos: &mut QoSType<ID>) {
+        if let Some(priority) = self.overwrite.priority {
+            qos.set_priority(priority.into());
+        }
+        if let Some(congestion_control) = self.overwrite.congestion_control {
+            qos.set_congestion_control(congestion_control.into());
+        }
+        if let Some(express) = self.overwrite.express {
+            qos.set_is_express(express);
+        }
+    }
+}
+
+/// Overwrites the QoS of the messages on the configured key expressions.
+pub(crate) struct QosOverwriteInterceptor {
+    state: Arc<QosOverwriteState>,
+}
+
+impl InterceptorTrait for QosOverwriteInterceptor {
+    fn compute_keyexpr_cache(&self, key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>> {
+        Some(Box::new(self.state.matches(key_expr)))
+    }
+
+    fn intercept(
+        &self,
+        mut ctx: RoutingContext<NetworkMessage>,
+        cache: Option<&Box<dyn Any + Send + Sync>>,
+    ) -> Option<RoutingContext<NetworkMessage>> {
+        let message = match &ctx.msg.body {
+            NetworkBody::Push(_) => QosOverwriteMessage::Push,
+            NetworkBody::Request(_) => QosOverwriteMessage::Request,
+            _ => return Some(ctx),
+        };
+        if !self
+            .state
+            .messages
+            .as_ref()
+            .map_or(true, |messages| messages.contains(&message))
+        {
+            return Some(ctx);
+        }
+        let Some(matches) = cache.and_then(|cache| cache.downcast_ref::<bool>()) else {
+            tracing::debug!("unexpected cache type {:?}", ctx.full_expr());
+            return Some(ctx);
+        };
+        if *matches {
+            tracing::trace!("Overwriting QoS of {:?}", ctx.full_expr());
+            match &mut ctx.msg.body {
+                NetworkBody::Push(push) => self.state.overwrite(&mut push.ext_qos),
+                NetworkBody::Request(request) => self.state.overwrite(&mut request.ext_qos),
+                _ => {}
+            }
+        }
+        Some(ctx)
+    }
+}
@@ -0,0 +1,291 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The quotas limiting the declarations of the remote nodes.
+//!
+//! A declaration exceeding a quota, or whose key expression can't be resolved and thus can't be
+//! accounted, is dropped and logged: the face stays open, with its other declarations. The
+//! declarations count per face or, with the `subject` scope, across the faces matching a quota.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use zenoh_config::{DeclarationLimitsConf, QuotaItemConf, QuotaScope};
+use zenoh_core::zlock;
+use zenoh_protocol::{
+    core::{EntityId, ExprId, ZenohIdProto},
+    network::{Declare, DeclareBody, NetworkBody},
+};
+
+use crate::net::routing::interceptor::*;
+
+pub(crate) fn quotas_interceptor_factories(config: &Vec<QuotaItemConf>) -> Vec<InterceptorFactory> {
+    let mut res: Vec<InterceptorFactory> = vec![];
+
+    for quota in config {
+        res.push(Box::new(QuotaInterceptorFactory::new(quota.clone())));
+    }
+
+    res
+}
+
+pub struct QuotaInterceptorFactory {
+    interfaces: Option<Vec<String>>,
+    cert_common_names: Option<Vec<String>>,
+    usernames: Option<Vec<String>>,
+    scope: QuotaScope,
+    limits: Arc<DeclarationLimitsConf>,
+    /// The declarations count shared by the faces with the `subject` scope.
+    count: Arc<Mutex<DeclarationsCount>>,
+}
+
+impl QuotaInterceptorFactory {
+    pub fn new(conf: QuotaItemConf) -> Self {
+        Self {
+            interfaces: conf.interfaces,
+            cert_common_names: conf.cert_common_names,
+            usernames: conf.usernames,
+            scope: conf.scope,
+            limits: Arc::new(conf.limits),
+            count: Arc::default(),
+        }
+    }
+}
+
+impl InterceptorFactoryTrait for QuotaInterceptorFactory {
+    fn new_transport_unicast(
+        &self,
+        transport: &TransportUnicast,
+    ) -> (Option<IngressInterceptor>, Option<EgressInterceptor>) {
+        tracing::debug!("New quota transport unicast {:?}", transport);
+        if !transport_matches(
+            transport,
+            self.interfaces.as_deref(),
+            self.cert_common_names.as_deref(),
+            self.usernames.as_deref(),
+        ) {
+            return (None, None);
+        }
+        let zid = match transport.get_zid() {
+            Ok(zid) => zid,
+            Err(err) => {
+                tracing::error!("Couldn't get Transport zid: {}", err);
+                return (None, None);
+            }
+        };
+
+        let count = match self.scope {
+            QuotaScope::Face => Arc::default(),
+            QuotaScope::Subject => self.count.clone(),
+        };
+        (
+            Some(Box::new(QuotaInterceptor {
+                zid,
+                limits: self.limits.clone(),
+                count,
+                declarations: Mutex::default(),
+            })),
+            None,
+        )
+    }
+
+    fn new_transport_multicast(
+        &self,
+        _transport: &TransportMulticast,
+    ) -> Option<EgressInterceptor> {
+        None
+    }
+
+    fn new_peer_multicast(&self, _transport: &TransportMulticast) -> Option<IngressInterceptor> {
+        None
+    }
+}
+
+/// A declaration received from a face, identified by its kind and id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Declaration {
+    KeyExpr(ExprId),
+    Subscriber(EntityId),
+    Queryable(EntityId),
+    Token(EntityId),
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct DeclarationsCount {
+    subscribers: usize,
+    queryables: usize,
+    tokens: usize,
+    /// The number of declarations per key expression.
+    resources: HashMap<String, usize>,
+}
+
+impl DeclarationsCount {
+    fn entities(&mut self, declaration: Declaration) -> Option<&mut usize> {
+        match declaration {
+            Declaration::KeyExpr(_) => None,
+            Declaration::Subscriber(_) => Some(&mut self.subscribers),
+            Declaration::Queryable(_) => Some(&mut self.queryables),
+            Declaration::Token(_) => Some(&mut self.tokens),
+        }
+    }
+
+    /// Returns the name of the exceeded limit and its value if `declaration` of `key_expr`
+    /// exceeds `limits`.
+    fn exceeded(
+        &self,
+        limits: &DeclarationLimitsConf,
+        declaration: Declaration,
+        key_expr: &str,
+    ) -> Option<(&'static str, usize)> {
+        let (name, count, limit) = match declaration {
+            Declaration::KeyExpr(_) => ("key expressions", 0, None),
+            Declaration::Subscriber(_) => ("subscribers", self.subscribers, limits.subscribers),
+            Declaration::Queryable(_) => ("queryables", self.queryables, limits.queryables),
+            Declaration::Token(_) => ("tokens", self.tokens, limits.tokens),
+        };
+        if let Some(limit) = limit.filter(|limit| count >= *limit) {
+            return Some((name, limit));
+        }
+        limits
+            .resources
+            .filter(|limit| {
+                !self.resources.contains_key(key_expr) && self.resources.len() >= *limit
+            })
+            .map(|limit| ("resources", limit))
+    }
+
+    fn add(&mut self, declaration: Declaration, key_expr: &str) {
+        if let Some(entities) = self.entities(declaration) {
+            *entities += 1;
+        }
+        *self.resources.entry(key_expr.to_string()).or_default() += 1;
+    }
+
+    fn remove(&mut self, declaration: Declaration, key_expr: &str) {
+        if let Some(entities) = self.entities(declaration) {
+            *entities = entities.saturating_sub(1);
+        }
+        if let Some(count) = self.resources.get_mut(key_expr) {
+            *count -= 1;
+            if *count == 0 {
+                self.resources.remove(key_expr);
+            }
+        }
+    }
+}
+
+/// Drops the declarations of a face exceeding the limits of a quota.
+///
+/// As the protocol has no way to refuse a declaration, the remote node isn't notified: the
+/// rejected declaration is only logged, and the entity it declares receives nothing.
+pub(crate) struct QuotaInterceptor {
+    zid: ZenohIdProto,
+    limits: Arc<DeclarationLimitsConf>,
+    count: Arc<Mutex<DeclarationsCount>>,
+    /// The key expressions of the current declarations of the face.
+    declarations: Mutex<HashMap<Declaration, String>>,
+}
+
+impl QuotaInterceptor {
+    /// Returns `false` if the declaration is rejected and must be dropped.
+    fn declare(&self, declaration: Declaration, key_expr: Option<&str>) -> bool {
+        // A declaration that can't be accounted can't be allowed.
+        let Some(key_expr) = key_expr else {
+            tracing::error!(
+                "{} declared {:?} on an unknown key expression: dropping it",
+                self.zid,
+                declaration
+            );
+            return false;
+        };
+        let mut declarations = zlock!(self.declarations);
+        if declarations.contains_key(&declaration) {
+            return true;
+        }
+        let mut count = zlock!(self.count);
+        if let Some((name, limit)) = count.exceeded(&self.limits, declaration, key_expr) {
+            tracing::error!(
+                "{} exceeded its quota of {} {}: dropping declaration of {:?} on {}",
+                self.zid,
+                limit,
+                name,
+                declaration,
+                key_expr
+            );
+            return false;
+        }
+        count.add(declaration, key_expr);
+        declarations.insert(declaration, key_expr.to_string());
+        true
+    }
+
+    fn undeclare(&self, declaration: Declaration) {
+        if let Some(key_expr) = zlock!(self.declarations).remove(&declaration) {
+            zlock!(self.count).remove(declaration, &key_expr);
+        }
+    }
+}
+
+impl Drop for QuotaInterceptor {
+    fn drop(&mut self) {
+        // The declarations of a closed face do not count anymore.
+        let declarations = std::mem::take(&mut *zlock!(self.declarations));
+        let mut count = zlock!(self.count);
+        for (declaration, key_expr) in declarations {
+            count.remove(declaration, &key_expr);
+        }
+    }
+}
+
+impl InterceptorTrait for QuotaInterceptor {
+    fn compute_keyexpr_cache(&self, _key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>> {
+        None
+    }
+
+    fn intercept(
+        &self,
+        ctx: RoutingContext<NetworkMessage>,
+        _cache: Option<&Box<dyn Any + Send + Sync>>,
+    ) -> Option<RoutingContext<NetworkMessage>> {
+        let NetworkBody::Declare(Declare { body, .. }) = &ctx.msg.body else {
+            return Some(ctx);
+        };
+        let declared = match body {
+            DeclareBody::DeclareKeyExpr(m) => Declaration::KeyExpr(m.id),
+            DeclareBody::DeclareSubscriber(m) => Declaration::Subscriber(m.id),
+            DeclareBody::DeclareQueryable(m) => Declaration::Queryable(m.id),
+            DeclareBody::DeclareToken(m) => Declaration::Token(m.id),
+            DeclareBody::UndeclareKeyExpr(m) => {
+                self.undeclare(Declaration::KeyExpr(m.id));
+                return Some(ctx);
+            }
+            DeclareBody::UndeclareSubscriber(m) => {
+                self.undeclare(Declaration::Subscriber(m.id));
+                return Some(ctx);
+            }
+            DeclareBody::UndeclareQueryable(m) => {
+                self.undeclare(Declaration::Queryable(m.id));
+                return Some(ctx);
+            }
+            DeclareBody::UndeclareToken(m) => {
+                self.undeclare(Declaration::Token(m.id));
+                return Some(ctx);
+            }
+            DeclareBody::DeclareFinal(_) => return Some(ctx),
+        };
+        self.declare(declared, ctx.full_expr()).then_some(ctx)
+    }
+}
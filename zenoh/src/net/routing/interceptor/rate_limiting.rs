@@ -0,0 +1,676 @@
+//
+// Copyright (c) 2024 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This module is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](https://docs.rs/zenoh/latest/zenoh)
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+use zenoh_buffers::buffer::Buffer;
+use zenoh_config::{
+    InterceptorFlow, RateLimitAction, RateLimitBudgetConf, RateLimitScope, RateLimitedMessage,
+    RateLimitingItemConf, RateLimitingRuleConf,
+};
+use zenoh_core::zlock;
+use zenoh_keyexpr::keyexpr_tree::{
+    impls::KeyedSetProvider, support::UnknownWildness, IKeyExprTree, IKeyExprTreeMut,
+    IKeyExprTreeNode, KeBoxTree,
+};
+use zenoh_protocol::{
+    network::{NetworkBody, Push, Request},
+    zenoh::{PushBody, RequestBody},
+};
+use zenoh_result::{bail, ZResult};
+
+use crate::net::routing::interceptor::*;
+
+const DEFAULT_MAX_DELAY: Duration = Duration::from_millis(100);
+const MAX_DELAY: Duration = Duration::from_millis(1000);
+
+pub(crate) fn rate_limiting_interceptor_factories(
+    config: &Vec<RateLimitingItemConf>,
+) -> ZResult<Vec<InterceptorFactory>> {
+    let mut res: Vec<InterceptorFactory> = vec![];
+
+    for rl in config {
+        res.push(Box::new(RateLimitingInterceptorFactory::new(rl.clone())?));
+    }
+
+    Ok(res)
+}
+
+/// The budgets of the rules of an item, shared by the faces of the same scope.
+type SharedRules = Arc<Mutex<Vec<RuleState>>>;
+
+/// The identity of the remote node of a face, with the `subject` scope.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Subject {
+    Username(String),
+    CertCommonName(String),
+}
+
+impl Subject {
+    /// Returns the subject of the transport: its username or, if it has none, its certificate
+    /// common name.
+    fn of(transport: &TransportUnicast) -> Option<Self> {
+        let auth_ids = transport.get_auth_ids().ok()?;
+        let mut cert_common_name = None;
+        for auth_id in auth_ids {
+            match auth_id {
+                AuthId::Username(value) => return Some(Subject::Username(value)),
+                AuthId::CertCommonName(value) => cert_common_name = Some(value),
+                AuthId::None => {}
+            }
+        }
+        cert_common_name.map(Subject::CertCommonName)
+    }
+}
+
+pub struct RateLimitingInterceptorFactory {
+    interfaces: Option<Vec<String>>,
+    cert_common_names: Option<Vec<String>>,
+    usernames: Option<Vec<String>>,
+    messages: Option<Vec<RateLimitedMessage>>,
+    flow: InterceptorFlow,
+    action: RateLimitAction,
+    max_delay: Duration,
+    scope: RateLimitScope,
+    ke_id: Arc<KeBoxTree<usize, UnknownWildness, KeyedSetProvider>>,
+    rules: Vec<RateLimitingRuleConf>,
+    /// The budgets shared by all the faces with the `shared` scope.
+    shared: SharedRules,
+    /// The budgets of each subject with the `subject` scope, dropped with the last face of the
+    /// subject.
+    subjects: Mutex<HashMap<Subject, Weak<Mutex<Vec<RuleState>>>>>,
+}
+
+impl RateLimitingInterceptorFactory {
+    pub fn new(conf: RateLimitingItemConf) -> ZResult<Self> {
+        for rule in &conf.rules {
+            if rule.messages.is_none() && rule.bytes.is_none() {
+                bail!(
+                    "Rate limiting rule for '{}' must define a budget of messages and/or bytes",
+                    rule.key_expr
+                );
+            }
+            for budget in rule.messages.iter().chain(rule.bytes.iter()) {
+                if !(budget.rate.is_finite() && budget.rate > 0.0) {
+                    bail!(
+                        "Invalid rate limiting rate for '{}': {} (must be strictly positive)",
+                        rule.key_expr,
+                        budget.rate
+                    );
+                }
+                if let Some(burst) = budget.burst {
+                    if !(burst.is_finite() && burst >= 1.0) {
+                        bail!(
+                            "Invalid rate limiting burst for '{}': {} (must be at least 1)",
+                            rule.key_expr,
+                            burst
+                        );
+                    }
+                }
+            }
+        }
+
+        let max_delay = conf
+            .max_delay_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_MAX_DELAY);
+        if conf.action == RateLimitAction::Delay {
+            // NOTE: A delayed message is held back by a task of its face, without blocking the
+            //       routing of the other messages: only the ingress flow has such a task.
+            if conf.flow != InterceptorFlow::Ingress {
+                bail!("The rate limiting delay action is only supported on the ingress flow");
+            }
+            if max_delay.is_zero() || max_delay > MAX_DELAY {
+                bail!(
+                    "Invalid rate limiting max_delay_ms: {} (must be between 1 and {})",
+                    max_delay.as_millis(),
+                    MAX_DELAY.as_millis()
+                );
+            }
+        }
+
+        let mut ke_id = KeBoxTree::default();
+        for (id, rule) in conf.rules.iter().enumerate() {
+            ke_id.insert(&rule.key_expr, id);
+            tracing::debug!(
+                "New rate limiter rule enabled: key_expr={:?}, messages={:?}, bytes={:?}",
+                rule.key_expr,
+                rule.messages,
+                rule.bytes
+            );
+        }
+
+        Ok(Self {
+            interfaces: conf.interfaces,
+            cert_common_names: conf.cert_common_names,
+            usernames: conf.usernames,
+            messages: conf.messages,
+            flow: conf.flow,
+            action: conf.action,
+            max_delay,
+            scope: conf.scope,
+            ke_id: Arc::new(ke_id),
+            shared: Arc::new(Mutex::new(new_rule_states(&conf.rules))),
+            rules: conf.rules,
+            subjects: Mutex::default(),
+        })
+    }
+
+    /// Returns `true` if the messages of the transport are subject to the rate limiting.
+    fn applies_to(&self, transport: &TransportUnicast) -> bool {
+        transport_matches(
+            transport,
+            self.interfaces.as_deref(),
+            self.cert_common_names.as_deref(),
+            self.usernames.as_deref(),
+        )
+    }
+
+    /// Returns the budgets of a face of the given subject, according to the scope of the item.
+    fn budgets(&self, subject: Option<Subject>) -> SharedRules {
+        let new_budgets = || Arc::new(Mutex::new(new_rule_states(&self.rules)));
+        match (self.scope, subject) {
+            (RateLimitScope::Shared, _) => self.shared.clone(),
+            (RateLimitScope::Subject, Some(subject)) => {
+                let mut subjects = zlock!(self.subjects);
+                subjects.retain(|_, budgets| budgets.strong_count() > 0);
+                match subjects.get(&subject).and_then(Weak::upgrade) {
+                    Some(budgets) => budgets,
+                    None => {
+                        let budgets = new_budgets();
+                        subjects.insert(subject, Arc::downgrade(&budgets));
+                        budgets
+                    }
+                }
+            }
+            (RateLimitScope::Subject, None) | (RateLimitScope::Face, _) => new_budgets(),
+        }
+    }
+
+    fn rate_limiter(&self, rules: SharedRules) -> RateLimitingInterceptor {
+        RateLimitingInterceptor {
+            ke_id: self.ke_id.clone(),
+            rules,
+            messages: self.messages.clone(),
+            action: self.action,
+            max_delay: self.max_delay,
+        }
+    }
+
+    fn new_interceptor(&self, transport: &TransportUnicast) -> Interceptor {
+        let subject = match self.scope {
+            RateLimitScope::Subject => Subject::of(transport),
+            RateLimitScope::Face | RateLimitScope::Shared => None,
+        };
+        Box::new(ComputeOnMiss::new(self.rate_limiter(self.budgets(subject))))
+    }
+}
+
+fn new_rule_states(rules: &[RateLimitingRuleConf]) -> Vec<RuleState> {
+    let now = Instant::now();
+    rules
+        .iter()
+        .map(|rule| RuleState {
+            messages: rule
+                .messages
+                .as_ref()
+                .map(|conf| TokenBucket::new(conf, now)),
+            bytes: rule.bytes.as_ref().map(|conf| TokenBucket::new(conf, now)),
+        })
+        .collect()
+}
+
+impl InterceptorFactoryTrait for RateLimitingInterceptorFactory {
+    fn new_transport_unicast(
+        &self,
+        transport: &TransportUnicast,
+    ) -> (Option<IngressInterceptor>, Option<EgressInterceptor>) {
+        tracing::debug!("New rate limiter transport unicast {:?}", transport);
+        if !self.applies_to(transport) {
+            return (None, None);
+        }
+
+        match self.flow {
+            InterceptorFlow::Ingress => (Some(self.new_interceptor(transport)), None),
+            InterceptorFlow::Egress => (None, Some(self.new_interceptor(transport))),
+        }
+    }
+
+    fn new_transport_multicast(
+        &self,
+        _transport: &TransportMulticast,
+    ) -> Option<EgressInterceptor> {
+        None
+    }
+
+    fn new_peer_multicast(&self, _transport: &TransportMulticast) -> Option<IngressInterceptor> {
+        None
+    }
+}
+
+/// A budget of tokens refilled at a constant rate, up to a maximum allowing bursts.
+#[derive(Debug)]
+pub(crate) struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub(crate) fn new(conf: &RateLimitBudgetConf, now: Instant) -> Self {
+        let capacity = conf.burst.unwrap_or(conf.rate).max(1.0);
+        Self {
+            rate: conf.rate,
+            capacity,
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        if now > self.last_refill {
+            let elapsed = now - self.last_refill;
+            self.tokens = (self.tokens + elapsed.as_secs_f64() * self.rate).min(self.capacity);
+            self.last_refill = now;
+        }
+    }
+
+    /// Returns the time to wait before `amount` tokens are available, or `None` if they never
+    /// will be as `amount` exceeds the capacity of the bucket.
+    pub(crate) fn wait_time(&mut self, amount: f64, now: Instant) -> Option<Duration> {
+        if amount > self.capacity {
+            return None;
+        }
+        self.refill(now);
+        if self.tokens >= amount {
+            Some(Duration::ZERO)
+        } else {
+            Some(Duration::from_secs_f64((amount - self.tokens) / self.rate))
+        }
+    }
+
+    pub(crate) fn consume(&mut self, amount: f64) {
+        self.tokens -= amount;
+    }
+}
+
+struct RuleState {
+    messages: Option<TokenBucket>,
+    bytes: Option<TokenBucket>,
+}
+
+impl RuleState {
+    /// Returns the time to wait before the budgets of the rule allow a message of `size` bytes,
+    /// or `None` if they never will.
+    fn wait_time(&mut self, size: usize, now: Instant) -> Option<Duration> {
+        let messages = match &mut self.messages {
+            Some(bucket) => bucket.wait_time(1.0, now)?,
+            None => Duration::ZERO,
+        };
+        let bytes = match &mut self.bytes {
+            Some(bucket) => bucket.wait_time(size as f64, now)?,
+            None => Duration::ZERO,
+        };
+        Some(messages.max(bytes))
+    }
+
+    fn consume(&mut self, size: usize) {
+        if let Some(bucket) = &mut self.messages {
+            bucket.consume(1.0);
+        }
+        if let Some(bucket) = &mut self.bytes {
+            bucket.consume(size as f64);
+        }
+    }
+}
+
+pub(crate) struct RateLimitingInterceptor {
+    ke_id: Arc<KeBoxTree<usize, UnknownWildness, KeyedSetProvider>>,
+    rules: SharedRules,
+    messages: Option<Vec<RateLimitedMessage>>,
+    action: RateLimitAction,
+    max_delay: Duration,
+}
+
+impl RateLimitingInterceptor {
+    /// Reserves the budgets of the rules `ids` for a message of `size` bytes at `now`, if they
+    /// allow it within `max_wait`.
+    ///
+    /// Returns the time to wait before routing the message, zero if it can be routed right away,
+    /// or `None` if it is not admitted: it must wait longer than `max_wait` or it exceeds the
+    /// burst of a budget.
+    fn admit(
+        &self,
+        ids: &[usize],
+        size: usize,
+        now: Instant,
+        max_wait: Duration,
+    ) -> Option<Duration> {
+        let mut rules = zlock!(self.rules);
+        let mut wait = Duration::ZERO;
+        for id in ids {
+            wait = wait.max(rules[*id].wait_time(size, now)?);
+        }
+        if wait > max_wait {
+            return None;
+        }
+        // The budgets of a delayed message are reserved, such that the messages following it are
+        // delayed after it.
+        for id in ids {
+            rules[*id].consume(size);
+        }
+        Some(wait)
+    }
+
+    /// Returns the payload size of the message if it is subject to the rate limiting.
+    fn limited_size(&self, body: &NetworkBody) -> Option<usize> {
+        let (message, size) = match body {
+            NetworkBody::Push(Push { payload, .. }) => (
+                RateLimitedMessage::Push,
+                match payload {
+                    PushBody::Put(put) => put.payload.len(),
+                    PushBody::Del(_) => 0,
+                },
+            ),
+            NetworkBody::Request(Request {
+                payload: RequestBody::Query(query),
+                ..
+            }) => (
+                RateLimitedMessage::Request,
+                query
+                    .ext_body
+                    .as_ref()
+                    .map(|body| body.payload.len())
+                    .unwrap_or(0),
+            ),
+            _ => return None,
+        };
+        self.messages
+            .as_ref()
+            .map_or(true, |messages| messages.contains(&message))
+            .then_some(size)
+    }
+}
+
+impl InterceptorTrait for RateLimitingInterceptor {
+    fn compute_keyexpr_cache(&self, key_expr: &KeyExpr<'_>) -> Option<Box<dyn Any + Send + Sync>> {
+        let ids = self
+            .ke_id
+            .intersecting_nodes(key_expr)
+            .filter_map(|node| node.weight().copied())
+            .collect::<Vec<usize>>();
+        Some(Box::new(ids))
+    }
+
+    fn intercept(
+        &self,
+        mut ctx: RoutingContext<NetworkMessage>,
+        cache: Option<&Box<dyn Any + Send + Sync>>,
+    ) -> Option<RoutingContext<NetworkMessage>> {
+        let Some(size) = self.limited_size(&ctx.msg.body) else {
+            return Some(ctx);
+        };
+        let Some(ids) = cache.and_then(|cache| cache.downcast_ref::<Vec<usize>>()) else {
+            tracing::debug!("unexpected cache type {:?}", ctx.full_expr());
+            return Some(ctx);
+        };
+        if ids.is_empty() {
+            return Some(ctx);
+        }
+
+        let max_wait = match self.action {
+            RateLimitAction::Drop => Duration::ZERO,
+            RateLimitAction::Delay => self.max_delay,
+        };
+        match self.admit(ids, size, Instant::now(), max_wait) {
+            Some(wait) if wait.is_zero() => Some(ctx),
+            Some(wait) => {
+                tracing::trace!(
+                    "Rate limit exceeded on {:?}, delaying the message by {:?}",
+                    ctx.full_expr(),
+                    wait
+                );
+                ctx.delay = Some(wait);
+                Some(ctx)
+            }
+            None => {
+                tracing::trace!("Rate limit exceeded on {:?}", ctx.full_expr());
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_bucket() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(
+            &RateLimitBudgetConf {
+                rate: 10.0,
+                burst: Some(20.0),
+            },
+            now,
+        );
+
+        // The bucket starts full, allowing a burst.
+        for _ in 0..20 {
+            assert_eq!(bucket.wait_time(1.0, now), Some(Duration::ZERO));
+            bucket.consume(1.0);
+        }
+        assert_eq!(bucket.wait_time(1.0, now), Some(Duration::from_millis(100)));
+
+        // The bucket is refilled at the configured rate, up to its capacity.
+        let later = now + Duration::from_millis(500);
+        assert_eq!(bucket.wait_time(5.0, later), Some(Duration::ZERO));
+        assert_eq!(
+            bucket.wait_time(20.0, later + Duration::from_secs(10)),
+            Some(Duration::ZERO)
+        );
+
+        // An amount above the capacity is never allowed.
+        assert_eq!(bucket.wait_time(21.0, later), None);
+    }
+
+    fn new_factory(scope: RateLimitScope) -> RateLimitingInterceptorFactory {
+        RateLimitingInterceptorFactory::new(RateLimitingItemConf {
+            interfaces: None,
+            cert_common_names: None,
+            usernames: None,
+            messages: None,
+            flow: InterceptorFlow::Ingress,
+            action: RateLimitAction::Drop,
+            max_delay_ms: None,
+            scope,
+            rules: vec![
+                zenoh_config::RateLimitingRuleConf {
+                    key_expr: "test/10msgs".try_into().unwrap(),
+                    messages: Some(RateLimitBudgetConf {
+                        rate: 10.0,
+                        burst: None,
+                    }),
+                    bytes: None,
+                },
+                zenoh_config::RateLimitingRuleConf {
+                    key_expr: "test/70bytes".try_into().unwrap(),
+                    messages: None,
+                    bytes: Some(RateLimitBudgetConf {
+                        rate: 70.0,
+                        burst: Some(7.0),
+                    }),
+                },
+            ],
+        })
+        .unwrap()
+    }
+
+    fn admitted(
+        limiter: &RateLimitingInterceptor,
+        ids: &[usize],
+        size: usize,
+        now: Instant,
+    ) -> bool {
+        limiter.admit(ids, size, now, Duration::ZERO) == Some(Duration::ZERO)
+    }
+
+    #[test]
+    fn budgets_shared_by_faces() {
+        let factory = new_factory(RateLimitScope::Shared);
+        let faces = [
+            factory.rate_limiter(factory.budgets(None)),
+            factory.rate_limiter(factory.budgets(None)),
+        ];
+        let now = Instant::now();
+
+        // The faces consume the same budget of one second worth of messages.
+        let admitted_count = (0..20)
+            .filter(|i| admitted(&faces[i % 2], &[0], 7, now))
+            .count();
+        assert_eq!(admitted_count, 10);
+        assert!(!admitted(
+            &faces[0],
+            &[0],
+            7,
+            now + Duration::from_millis(50)
+        ));
+        assert!(admitted(
+            &faces[1],
+            &[0],
+            7,
+            now + Duration::from_millis(150)
+        ));
+
+        // A message must fit in the budgets of all the rules it matches.
+        let later = now + Duration::from_secs(1);
+        assert!(admitted(&faces[0], &[0, 1], 7, later));
+        assert!(!admitted(&faces[1], &[0, 1], 7, later));
+        assert!(admitted(&faces[1], &[0], 7, later));
+
+        // A message exceeding the burst of a budget is never admitted.
+        assert_eq!(
+            faces[0].admit(&[1], 8, later + Duration::from_secs(1), Duration::MAX),
+            None
+        );
+    }
+
+    #[test]
+    fn budgets_per_face_and_subject() {
+        let now = Instant::now();
+        let alice = || Some(Subject::Username("alice".to_string()));
+        let bob = || Some(Subject::Username("bob".to_string()));
+
+        // Each face has its own budgets.
+        let factory = new_factory(RateLimitScope::Face);
+        let faces = [
+            factory.rate_limiter(factory.budgets(alice())),
+            factory.rate_limiter(factory.budgets(alice())),
+        ];
+        assert_eq!(
+            (0..20)
+                .filter(|_| admitted(&faces[0], &[0], 7, now))
+                .count(),
+            10
+        );
+        assert!(admitted(&faces[1], &[0], 7, now));
+
+        // The faces of the same subject share their budgets, the other subjects have their own.
+        let factory = new_factory(RateLimitScope::Subject);
+        let faces = [
+            factory.rate_limiter(factory.budgets(alice())),
+            factory.rate_limiter(factory.budgets(alice())),
+            factory.rate_limiter(factory.budgets(bob())),
+            factory.rate_limiter(factory.budgets(None)),
+        ];
+        assert_eq!(
+            (0..20)
+                .filter(|_| admitted(&faces[0], &[0], 7, now))
+                .count(),
+            10
+        );
+        assert!(!admitted(&faces[1], &[0], 7, now));
+        assert!(admitted(&faces[2], &[0], 7, now));
+        assert!(admitted(&faces[3], &[0], 7, now));
+
+        // The budgets of a subject are dropped with its last face.
+        drop(faces);
+        let _bob = factory.budgets(bob());
+        assert_eq!(zlock!(factory.subjects).len(), 1);
+        let face = factory.rate_limiter(factory.budgets(alice()));
+        assert!(admitted(&face, &[0], 7, now));
+    }
+
+    #[test]
+    fn delayed_messages_reserve_budgets() {
+        let factory = new_factory(RateLimitScope::Face);
+        let face = factory.rate_limiter(factory.budgets(None));
+        let now = Instant::now();
+        let max_wait = Duration::from_millis(250);
+
+        // The messages beyond the budget are delayed one after the other, up to the maximum wait.
+        let waits = (0..14)
+            .map(|_| face.admit(&[0], 7, now, max_wait))
+            .collect::<Vec<_>>();
+        assert!(waits[..10].iter().all(|wait| *wait == Some(Duration::ZERO)));
+        assert_eq!(waits[10], Some(Duration::from_millis(100)));
+        assert_eq!(waits[11], Some(Duration::from_millis(200)));
+        assert_eq!(waits[12], None);
+        assert_eq!(waits[13], None);
+
+        // The reserved budgets are not available to the messages that follow.
+        assert!(!admitted(&face, &[0], 7, now + Duration::from_millis(150)));
+        assert!(admitted(&face, &[0], 7, now + Duration::from_millis(400)));
+    }
+
+    #[test]
+    fn delay_action_config() {
+        let conf = |flow, max_delay_ms| RateLimitingItemConf {
+            interfaces: None,
+            cert_common_names: None,
+            usernames: None,
+            messages: None,
+            flow,
+            action: RateLimitAction::Delay,
+            max_delay_ms,
+            scope: RateLimitScope::Face,
+            rules: vec![],
+        };
+        assert!(RateLimitingInterceptorFactory::new(conf(InterceptorFlow::Ingress, None)).is_ok());
+        assert!(
+            RateLimitingInterceptorFactory::new(conf(InterceptorFlow::Ingress, Some(1000))).is_ok()
+        );
+        assert!(
+            RateLimitingInterceptorFactory::new(conf(InterceptorFlow::Ingress, Some(1001)))
+                .is_err()
+        );
+        assert!(
+            RateLimitingInterceptorFactory::new(conf(InterceptorFlow::Ingress, Some(0))).is_err()
+        );
+        assert!(RateLimitingInterceptorFactory::new(conf(InterceptorFlow::Egress, None)).is_err());
+    }
+}
@@ -22,7 +22,7 @@ pub mod hat;
 pub mod interceptor;
 pub mod router;
 
-use std::{cell::OnceCell, sync::Arc};
+use std::{cell::OnceCell, sync::Arc, time::Duration};
 
 use zenoh_protocol::{
     core::{key_expr::OwnedKeyExpr, WireExpr},
@@ -32,12 +32,16 @@ use zenoh_protocol::{
 use self::{dispatcher::face::Face, router::Resource};
 use super::runtime;
 
-pub(crate) struct RoutingContext<Msg> {
+/// A message being routed, along with the faces it is received from or sent to.
+pub struct RoutingContext<Msg> {
     pub(crate) msg: Msg,
     pub(crate) inface: OnceCell<Face>,
     pub(crate) outface: OnceCell<Face>,
     pub(crate) prefix: OnceCell<Arc<Resource>>,
     pub(crate) full_expr: OnceCell<String>,
+    /// The delay an ingress interceptor requested before the message goes through the next
+    /// interceptors and is routed.
+    pub(crate) delay: Option<Duration>,
 }
 
 impl<Msg> RoutingContext<Msg> {
@@ -49,6 +53,7 @@ impl<Msg> RoutingContext<Msg> {
             outface: OnceCell::new(),
             prefix: OnceCell::new(),
             full_expr: OnceCell::new(),
+            delay: None,
         }
     }
 
@@ -60,6 +65,7 @@ impl<Msg> RoutingContext<Msg> {
             outface: OnceCell::new(),
             prefix: OnceCell::new(),
             full_expr: OnceCell::new(),
+            delay: None,
         }
     }
 
@@ -71,6 +77,7 @@ impl<Msg> RoutingContext<Msg> {
             outface: OnceCell::from(outface),
             prefix: OnceCell::new(),
             full_expr: OnceCell::new(),
+            delay: None,
         }
     }
 
@@ -82,9 +89,28 @@ impl<Msg> RoutingContext<Msg> {
             outface: OnceCell::new(),
             prefix: OnceCell::new(),
             full_expr: OnceCell::from(expr),
+            delay: None,
         }
     }
 
+    /// The routed message.
+    #[inline]
+    #[zenoh_macros::unstable]
+    pub fn msg(&self) -> &Msg {
+        &self.msg
+    }
+
+    /// The routed message, for modification.
+    ///
+    /// The key expression returned by [`full_expr`](RoutingContext::full_expr) is resolved once,
+    /// thus it does not reflect a change of the key expression of the message after it was
+    /// resolved. Use [`set_full_expr`](RoutingContext::set_full_expr) to change it.
+    #[inline]
+    #[zenoh_macros::unstable]
+    pub fn msg_mut(&mut self) -> &mut Msg {
+        &mut self.msg
+    }
+
     #[allow(dead_code)]
     pub(crate) fn inface(&self) -> Option<&Face> {
         self.inface.get()
@@ -97,8 +123,9 @@ impl<Msg> RoutingContext<Msg> {
 }
 
 impl RoutingContext<NetworkMessage> {
+    /// The key expression of the message, as received or sent on the face.
     #[inline]
-    pub(crate) fn wire_expr(&self) -> Option<&WireExpr> {
+    pub fn wire_expr(&self) -> Option<&WireExpr> {
         use zenoh_protocol::network::{DeclareBody, NetworkBody};
         match &self.msg.body {
             NetworkBody::Push(m) => Some(&m.wire_expr),
@@ -154,9 +181,45 @@ impl RoutingContext<NetworkMessage> {
         None
     }
 
+    /// Replaces the key expression of the message, if any, by the provided complete key
+    /// expression.
+    ///
+    /// Returns `false` if the message has no key expression.
+    pub fn set_full_expr(&mut self, expr: String) -> bool {
+        use zenoh_protocol::network::{DeclareBody, NetworkBody};
+        let wire_expr = match &mut self.msg.body {
+            NetworkBody::Push(m) => Some(&mut m.wire_expr),
+            NetworkBody::Request(m) => Some(&mut m.wire_expr),
+            NetworkBody::Response(m) => Some(&mut m.wire_expr),
+            NetworkBody::ResponseFinal(_) => None,
+            NetworkBody::Interest(m) => m.wire_expr.as_mut(),
+            NetworkBody::Declare(m) => match &mut m.body {
+                DeclareBody::DeclareKeyExpr(m) => Some(&mut m.wire_expr),
+                DeclareBody::UndeclareKeyExpr(_) => None,
+                DeclareBody::DeclareSubscriber(m) => Some(&mut m.wire_expr),
+                DeclareBody::UndeclareSubscriber(m) => Some(&mut m.ext_wire_expr.wire_expr),
+                DeclareBody::DeclareQueryable(m) => Some(&mut m.wire_expr),
+                DeclareBody::UndeclareQueryable(m) => Some(&mut m.ext_wire_expr.wire_expr),
+                DeclareBody::DeclareToken(m) => Some(&mut m.wire_expr),
+                DeclareBody::UndeclareToken(m) => Some(&mut m.ext_wire_expr.wire_expr),
+                DeclareBody::DeclareFinal(_) => None,
+            },
+            NetworkBody::OAM(_) => None,
+        };
+        let Some(wire_expr) = wire_expr else {
+            return false;
+        };
+        wire_expr.scope = 0;
+        wire_expr.suffix = expr.clone().into();
+        self.prefix = OnceCell::new();
+        self.full_expr = OnceCell::from(expr);
+        true
+    }
+
+    /// The complete key expression of the message, if any.
     #[inline]
     #[allow(dead_code)]
-    pub(crate) fn full_expr(&self) -> Option<&str> {
+    pub fn full_expr(&self) -> Option<&str> {
         if self.full_expr.get().is_some() {
             return Some(self.full_expr.get().as_ref().unwrap());
         }
@@ -169,8 +232,9 @@ impl RoutingContext<NetworkMessage> {
         None
     }
 
+    /// The complete key expression of the message, if any.
     #[inline]
-    pub(crate) fn full_key_expr(&self) -> Option<OwnedKeyExpr> {
+    pub fn full_key_expr(&self) -> Option<OwnedKeyExpr> {
         let full_expr = self.full_expr()?;
         OwnedKeyExpr::new(full_expr).ok()
     }
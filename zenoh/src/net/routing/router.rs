@@ -17,7 +17,7 @@ use std::{
 };
 
 use uhlc::HLC;
-use zenoh_config::Config;
+use zenoh_config::{AclConfig, Config};
 use zenoh_protocol::core::{WhatAmI, ZenohIdProto};
 // use zenoh_collections::Timer;
 use zenoh_result::ZResult;
@@ -31,7 +31,7 @@ use super::{
         tables::{Tables, TablesLock},
     },
     hat,
-    interceptor::{EgressInterceptor, InterceptorsChain},
+    interceptor::{AclAuditRecord, EgressInterceptor, InterceptorsChain, SharedPolicyEnforcer},
     runtime::Runtime,
 };
 use crate::net::{
@@ -67,6 +67,78 @@ impl Router {
         ctrl_lock.init(&mut tables, runtime)
     }
 
+    /// Adds an [`InterceptorFactory`](super::interceptor::InterceptorFactory) to the ones
+    /// instantiated for each new face, before the egress key remapping.
+    ///
+    /// The factories provided by a plugin are registered with its id, to be removed with
+    /// [`Router::remove_interceptor_factories`] when it stops. The faces created before this call
+    /// are not affected.
+    #[cfg(feature = "unstable")]
+    pub(crate) fn add_interceptor_factory(
+        &self,
+        plugin: Option<&str>,
+        factory: super::interceptor::InterceptorFactory,
+    ) {
+        zwrite!(self.tables.tables)
+            .added_interceptors
+            .push((plugin.map(str::to_string), factory));
+    }
+
+    /// Removes the [`InterceptorFactory`](super::interceptor::InterceptorFactory)s registered with
+    /// the id of the given plugin, returning how many were removed.
+    ///
+    /// The faces created before this call keep the interceptors these factories provided them.
+    #[cfg(all(feature = "unstable", any(feature = "plugins", test)))]
+    pub(crate) fn remove_interceptor_factories(&self, plugin: &str) -> usize {
+        let mut tables = zwrite!(self.tables.tables);
+        let count = tables.added_interceptors.len();
+        tables
+            .added_interceptors
+            .retain(|(id, _)| id.as_deref() != Some(plugin));
+        count - tables.added_interceptors.len()
+    }
+
+    /// Checks that `acl_config` can replace the access control configuration of this router,
+    /// without replacing it.
+    ///
+    /// Access control can't be enabled nor disabled at runtime: `enabled` must remain the value
+    /// this router was created with.
+    pub(crate) fn validate_access_control(&self, acl_config: &AclConfig) -> ZResult<()> {
+        let enabled = zread!(self.tables.tables).acl.is_some();
+        match (enabled, acl_config.enabled) {
+            (true, true) => SharedPolicyEnforcer::validate(acl_config),
+            (true, false) => bail!("Access control can't be disabled at runtime"),
+            (false, true) => {
+                bail!("Access control was not enabled at startup and can't be enabled at runtime")
+            }
+            (false, false) => Ok(()),
+        }
+    }
+
+    /// Replaces the access control policy enforced on all the faces, existing ones included.
+    ///
+    /// The new policy applies to the messages received or sent after the update: the
+    /// declarations (subscribers, queryables, tokens...) made before it are not checked again.
+    ///
+    /// See [`Router::validate_access_control`] for the configurations that are rejected.
+    pub(crate) fn update_access_control(&self, acl_config: &AclConfig) -> ZResult<()> {
+        self.validate_access_control(acl_config)?;
+        let acl = zread!(self.tables.tables).acl.clone();
+        match acl {
+            Some(acl) => acl.update(acl_config),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the receiver of the access control audit records to be published on the admin
+    /// space, if access control is enabled.
+    pub(crate) fn acl_audit_records(&self) -> Option<flume::Receiver<AclAuditRecord>> {
+        zread!(self.tables.tables)
+            .acl
+            .as_ref()
+            .map(|acl| acl.audit_records())
+    }
+
     pub(crate) fn new_primitives(
         &self,
         primitives: Arc<dyn EPrimitives + Send + Sync>,
@@ -125,8 +197,7 @@ impl Router {
         #[cfg(feature = "stats")]
         let stats = transport.get_stats()?;
         let (ingress, egress): (Vec<_>, Vec<_>) = tables
-            .interceptors
-            .iter()
+            .interceptor_factories()
             .map(|itor| itor.new_transport_unicast(&transport))
             .unzip();
         let (ingress, egress) = (
@@ -186,8 +257,7 @@ impl Router {
         tables.face_counter += 1;
         let interceptor = InterceptorsChain::from(
             tables
-                .interceptors
-                .iter()
+                .interceptor_factories()
                 .filter_map(|itor| itor.new_transport_multicast(&transport))
                 .collect::<Vec<EgressInterceptor>>(),
         );
@@ -226,8 +296,7 @@ impl Router {
         tables.face_counter += 1;
         let interceptor = Arc::new(InterceptorsChain::from(
             tables
-                .interceptors
-                .iter()
+                .interceptor_factories()
                 .filter_map(|itor| itor.new_peer_multicast(&transport))
                 .collect::<Vec<IngressInterceptor>>(),
         ));
@@ -18,7 +18,7 @@ use std::{
 
 use serde_json::json;
 use tracing::{error, trace};
-use zenoh_buffers::buffer::SplitBuffer;
+use zenoh_buffers::{buffer::SplitBuffer, ZBuf};
 use zenoh_config::{unwrap_or_default, wrappers::ZenohId, ConfigValidator, WhatAmI};
 use zenoh_core::Wait;
 #[cfg(feature = "plugins")]
@@ -32,7 +32,7 @@ use zenoh_protocol::{
         ext, Declare, DeclareBody, DeclareQueryable, DeclareSubscriber, Interest, Push, Request,
         Response, ResponseFinal,
     },
-    zenoh::{PushBody, RequestBody},
+    zenoh::{PushBody, Put, RequestBody},
 };
 use zenoh_result::ZResult;
 use zenoh_transport::unicast::TransportUnicast;
@@ -48,7 +48,10 @@ use crate::{
         value::Value,
     },
     bytes::Encoding,
-    net::primitives::Primitives,
+    net::{
+        primitives::{DummyPrimitives, Primitives},
+        routing::interceptor::{AclAuditRecord, AUDIT_KEY_SUFFIX},
+    },
 };
 
 pub struct AdminContext {
@@ -137,6 +140,8 @@ impl AdminSpace {
             tracing::warn!("Plugin `{}` was already started", started.id());
         } else {
             let started = loaded.start(start_args)?;
+            let factories = started.instance().interceptor_factories();
+            start_args.register_plugin_interceptor_factories(started.id(), factories);
             tracing::info!(
                 "Successfully started plugin `{}` from {}",
                 started.id(),
@@ -275,6 +280,10 @@ impl AdminSpace {
                                     if let Some(running) = plugins_mgr.started_plugin_mut(&id) {
                                         running.stop()
                                     }
+                                    admin
+                                        .context
+                                        .runtime
+                                        .unregister_plugin_interceptor_factories(&id);
                                 }
                                 PluginDiff::Start(plugin) => {
                                     if let Err(e) = Self::start_plugin(
@@ -391,6 +400,25 @@ impl Primitives for AdminSpace {
                             key,
                             json
                         );
+                        if key.starts_with("access_control") {
+                            if let Err(e) =
+                                self.context
+                                    .runtime
+                                    .validate_access_control_change(|config| {
+                                        config.insert_json5(key, json)
+                                    })
+                            {
+                                error!(
+                                    "Rejected conf value @/{}/{}/config/{} : {} - {}",
+                                    self.context.runtime.state.zid,
+                                    self.context.runtime.state.whatami,
+                                    key,
+                                    json,
+                                    e
+                                );
+                                return;
+                            }
+                        }
                         if let Err(e) = self.context.runtime.state.config.insert_json5(key, json) {
                             error!(
                                 "Error inserting conf value @/{}/{}/config/{} : {} - {}",
@@ -868,3 +896,43 @@ fn with_extended_string<R, F: FnMut(&mut String) -> R>(
     prefix.truncate(prefix_len);
     result
 }
+
+/// Publishes the access control audit records on `@/<zid>/<whatami>/access_control/audit`.
+pub(crate) async fn publish_acl_audit_records(
+    runtime: Runtime,
+    records: flume::Receiver<AclAuditRecord>,
+) {
+    let face = runtime.router().new_primitives(Arc::new(DummyPrimitives));
+    let key_expr = format!(
+        "@/{}/{}/{}",
+        runtime.state.zid, runtime.state.whatami, AUDIT_KEY_SUFFIX
+    );
+    while let Ok(record) = records.recv_async().await {
+        let payload = match serde_json::to_vec(&record) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("Couldn't serialize ACL audit record: {}", e);
+                continue;
+            }
+        };
+        face.send_push(
+            Push {
+                wire_expr: key_expr.clone().into(),
+                ext_qos: ext::QoSType::DEFAULT,
+                ext_tstamp: None,
+                ext_nodeid: ext::NodeIdType::DEFAULT,
+                payload: PushBody::Put(Put {
+                    timestamp: runtime.new_timestamp(),
+                    encoding: Encoding::APPLICATION_JSON.into(),
+                    ext_sinfo: None,
+                    #[cfg(feature = "shared-memory")]
+                    ext_shm: None,
+                    ext_unknown: vec![],
+                    payload: ZBuf::from(payload),
+                    ext_attachment: None,
+                }),
+            },
+            Reliability::Reliable,
+        );
+    }
+}
@@ -37,7 +37,7 @@ use futures::{stream::StreamExt, Future};
 use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 use uhlc::{HLCBuilder, HLC};
-use zenoh_config::{unwrap_or_default, ModeDependent, ZenohId};
+use zenoh_config::{unwrap_or_default, AclConfig, ModeDependent, ZenohId};
 use zenoh_link::{EndPoint, Link};
 use zenoh_plugin_trait::{PluginStartArgs, StructVersion};
 use zenoh_protocol::{
@@ -73,6 +73,8 @@ pub(crate) struct RuntimeState {
     next_id: AtomicU32,
     router: Arc<Router>,
     config: Notifier<Config>,
+    /// The access control configuration last applied, restored if an update is rejected.
+    access_control: std::sync::Mutex<AclConfig>,
     manager: TransportManager,
     transport_handlers: std::sync::RwLock<Vec<Arc<dyn TransportEventHandler>>>,
     locators: std::sync::RwLock<Vec<Locator>>,
@@ -168,6 +170,7 @@ impl RuntimeBuilder {
         // Admin space creation flag
         let start_admin_space = *config.adminspace.enabled();
 
+        let access_control = std::sync::Mutex::new(config.access_control().clone());
         let config = Notifier::new(crate::config::Config(config));
         let runtime = Runtime {
             state: Arc::new(RuntimeState {
@@ -176,6 +179,7 @@ impl RuntimeBuilder {
                 next_id: AtomicU32::new(1), // 0 is reserved for routing core
                 router,
                 config: config.clone(),
+                access_control,
                 manager: transport_manager,
                 transport_handlers: std::sync::RwLock::new(vec![]),
                 locators: std::sync::RwLock::new(vec![]),
@@ -195,6 +199,14 @@ impl RuntimeBuilder {
             AdminSpace::start(&runtime, LONG_VERSION.clone()).await;
         }
 
+        // Publish the access control audit records
+        if let Some(records) = runtime.state.router.acl_audit_records() {
+            runtime.spawn_abortable(adminspace::publish_acl_audit_records(
+                runtime.clone(),
+                records,
+            ));
+        }
+
         // Start plugins
         #[cfg(feature = "plugins")]
         start_plugins(&runtime);
@@ -211,10 +223,13 @@ impl RuntimeBuilder {
                         res = stream.next() => {
                             match res {
                                 Some(event) => {
-                                    if &*event == "connect/endpoints" {
+                                    let event = event.strip_prefix('/').unwrap_or(&event);
+                                    if event == "connect/endpoints" {
                                         if let Err(e) = runtime2.update_peers().await {
                                             tracing::error!("Error updating peers: {}", e);
                                         }
+                                    } else if event.starts_with("access_control") {
+                                        runtime2.update_access_control();
                                     }
                                 },
                                 None => { break; }
@@ -262,6 +277,79 @@ impl Runtime {
         zwrite!(self.state.transport_handlers).push(handler);
     }
 
+    /// Registers an [`InterceptorFactory`](routing::interceptor::InterceptorFactory) whose
+    /// interceptors are applied to the messages routed by this Runtime.
+    ///
+    /// The factory is called for each face created after the registration, after the factories
+    /// configured for this Runtime (ingress key remapping, downsampling, rate limiting, access
+    /// control...) and before the egress key remapping. The factories returned by the plugins
+    /// started with this Runtime are registered when they start, before it opens its transports,
+    /// and unregistered when they stop.
+    #[zenoh_macros::unstable]
+    pub fn register_interceptor_factory(&self, factory: routing::interceptor::InterceptorFactory) {
+        self.state.router.add_interceptor_factory(None, factory);
+    }
+
+    /// Registers the [`InterceptorFactory`](routing::interceptor::InterceptorFactory)s provided by
+    /// the given plugin, replacing the ones it previously provided, if any.
+    #[cfg(feature = "plugins")]
+    pub(crate) fn register_plugin_interceptor_factories(
+        &self,
+        plugin: &str,
+        factories: Vec<routing::interceptor::InterceptorFactory>,
+    ) {
+        self.unregister_plugin_interceptor_factories(plugin);
+        for factory in factories {
+            self.state
+                .router
+                .add_interceptor_factory(Some(plugin), factory);
+        }
+    }
+
+    /// Unregisters the [`InterceptorFactory`](routing::interceptor::InterceptorFactory)s provided
+    /// by the given plugin: the faces created afterwards no longer intercept messages with them.
+    #[cfg(feature = "plugins")]
+    pub(crate) fn unregister_plugin_interceptor_factories(&self, plugin: &str) {
+        let count = self.state.router.remove_interceptor_factories(plugin);
+        if count > 0 {
+            tracing::debug!("Unregistered {count} interceptor factories of plugin `{plugin}`");
+        }
+    }
+
+    /// Checks that applying `change` to the configuration of this Runtime results in a valid
+    /// `access_control` configuration, without applying it.
+    pub(crate) fn validate_access_control_change(
+        &self,
+        change: impl FnOnce(&mut Config) -> ZResult<()>,
+    ) -> ZResult<()> {
+        let mut config = self.config().lock().clone();
+        change(&mut config)?;
+        self.state
+            .router
+            .validate_access_control(config.0.access_control())
+    }
+
+    /// Applies the `access_control` configuration to the faces of this Runtime.
+    ///
+    /// An invalid configuration is logged, the current policy is kept and the `access_control`
+    /// configuration is restored to the one last applied.
+    fn update_access_control(&self) {
+        let acl_config = self.config().lock().0.access_control().clone();
+        match self.state.router.update_access_control(&acl_config) {
+            Ok(()) => *zlock!(self.state.access_control) = acl_config,
+            Err(e) => {
+                tracing::error!(
+                    "Error updating access control, restoring the previous configuration: {}",
+                    e
+                );
+                let applied = zlock!(self.state.access_control).clone();
+                if let Err(e) = self.config().lock().0.set_access_control(applied) {
+                    tracing::error!("Error restoring the access control configuration: {:?}", e);
+                }
+            }
+        }
+    }
+
     #[inline]
     pub fn next_id(&self) -> u32 {
         self.state.next_id.fetch_add(1, Ordering::SeqCst)
@@ -905,3 +905,66 @@ fn client_test() {
     // mapping strategy check
     // assert_eq!(primitives2.get_last_key().unwrap(), KeyExpr::IdWithSuffix(31, "/z2_pub1".to_string()));
 }
+
+#[cfg(feature = "unstable")]
+#[test]
+fn plugin_interceptor_factories_test() {
+    use zenoh_transport::{multicast::TransportMulticast, unicast::TransportUnicast};
+
+    use crate::net::routing::interceptor::{
+        EgressInterceptor, IngressInterceptor, InterceptorFactoryTrait,
+    };
+
+    struct NoopInterceptorFactory;
+
+    impl InterceptorFactoryTrait for NoopInterceptorFactory {
+        fn new_transport_unicast(
+            &self,
+            _transport: &TransportUnicast,
+        ) -> (Option<IngressInterceptor>, Option<EgressInterceptor>) {
+            (None, None)
+        }
+
+        fn new_transport_multicast(
+            &self,
+            _transport: &TransportMulticast,
+        ) -> Option<EgressInterceptor> {
+            None
+        }
+
+        fn new_peer_multicast(
+            &self,
+            _transport: &TransportMulticast,
+        ) -> Option<IngressInterceptor> {
+            None
+        }
+    }
+
+    let config = Config::default();
+    let router = Router::new(
+        ZenohIdProto::try_from([1]).unwrap(),
+        WhatAmI::Client,
+        Some(Arc::new(HLC::default())),
+        &config,
+    )
+    .unwrap();
+    let nb_factories = || {
+        zenoh_core::zread!(router.tables.tables)
+            .interceptor_factories()
+            .count()
+    };
+    let configured = nb_factories();
+
+    router.add_interceptor_factory(None, Box::new(NoopInterceptorFactory));
+    router.add_interceptor_factory(Some("a"), Box::new(NoopInterceptorFactory));
+    router.add_interceptor_factory(Some("a"), Box::new(NoopInterceptorFactory));
+    router.add_interceptor_factory(Some("b"), Box::new(NoopInterceptorFactory));
+    assert_eq!(nb_factories(), configured + 4);
+
+    // Only the factories of the stopped plugin are removed.
+    assert_eq!(router.remove_interceptor_factories("a"), 2);
+    assert_eq!(nb_factories(), configured + 2);
+    assert_eq!(router.remove_interceptor_factories("a"), 0);
+    assert_eq!(router.remove_interceptor_factories("b"), 1);
+    assert_eq!(nb_factories(), configured + 1);
+}
@@ -37,6 +37,10 @@ mod test {
         test_pub_sub_allow(27447).await;
         test_pub_sub_deny_then_allow(27447).await;
         test_pub_sub_allow_then_deny(27447).await;
+        #[cfg(feature = "unstable")]
+        test_pub_sub_deny_then_allow_publisher_interest(27447).await;
+        #[cfg(feature = "unstable")]
+        test_pub_sub_deny_publisher_interest(27447).await;
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
@@ -56,6 +60,37 @@ mod test {
         test_reply_allow_then_deny(27449).await;
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_acl_liveliness() {
+        zenoh::init_log_from_env_or("error");
+        test_liveliness_deny(27450).await;
+        test_liveliness_allow(27450).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_acl_update() {
+        zenoh::init_log_from_env_or("error");
+        test_pub_sub_update(27451).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_acl_audit() {
+        zenoh::init_log_from_env_or("error");
+        test_audit_file(27452).await;
+        test_audit_admin_space(27452).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_acl_subject_attributes() {
+        zenoh::init_log_from_env_or("error");
+        test_pub_sub_subject_attributes(27453, "sensor", true, true).await;
+        test_pub_sub_subject_attributes(27453, "actuator", true, false).await;
+        // The metadata is not advertised unless the configuration opts in
+        test_pub_sub_subject_attributes(27453, "actuator", false, true).await;
+        test_allow_unauthenticated_subject(27453, "sensor", true).await;
+        test_allow_unauthenticated_subject(27453, "actuator", false).await;
+    }
+
     async fn get_basic_router_config(port: u16) -> Config {
         let mut config = Config::default();
         config.set_mode(Some(WhatAmI::Router)).unwrap();
@@ -381,6 +416,154 @@ mod test {
         close_router_session(session).await;
     }
 
+    #[cfg(feature = "unstable")]
+    async fn test_pub_sub_deny_then_allow_publisher_interest(port: u16) {
+        println!("test_pub_sub_deny_then_allow_publisher_interest");
+
+        let mut config_router = get_basic_router_config(port).await;
+        config_router
+            .insert_json5(
+                "access_control",
+                r#"{
+                    "enabled": true,
+                    "default_permission": "deny",
+                    "rules": [
+                        {
+                            "id": "r1",
+                            "permission": "allow",
+                            "flows": ["egress", "ingress"],
+                            "messages": [
+                                "put",
+                                "declare_subscriber",
+                                "interest"
+                            ],
+                            "key_exprs": [
+                                "test/demo"
+                            ],
+                        },
+                    ],
+                    "subjects": [
+                        {
+                            "id": "s1",
+                            "interfaces": [
+                                "lo", "lo0"
+                            ],
+                        }
+                    ],
+                    "policies": [
+                        {
+                            "rules": ["r1"],
+                            "subjects": ["s1"],
+                        }
+                    ]
+                }"#,
+            )
+            .unwrap();
+        println!("Opening router session");
+
+        let session = ztimeout!(zenoh::open(config_router)).unwrap();
+        let (sub_session, pub_session) = get_client_sessions(port).await;
+        {
+            let received_value = Arc::new(Mutex::new(String::new()));
+            let temp_recv_value = received_value.clone();
+            let subscriber = sub_session
+                .declare_subscriber(KEY_EXPR)
+                .callback(move |sample| {
+                    let mut temp_value = zlock!(temp_recv_value);
+                    *temp_value = sample.payload().try_to_string().unwrap().into_owned();
+                })
+                .await
+                .unwrap();
+
+            tokio::time::sleep(SLEEP).await;
+            // The publisher learns the matching subscribers through the Interest it sends to the
+            // router.
+            let publisher = ztimeout!(pub_session.declare_publisher(KEY_EXPR)).unwrap();
+            tokio::time::sleep(SLEEP).await;
+            assert!(ztimeout!(publisher.matching_status())
+                .unwrap()
+                .matching_subscribers());
+            publisher.put(VALUE).await.unwrap();
+            tokio::time::sleep(SLEEP).await;
+            assert_eq!(*zlock!(received_value), VALUE);
+            ztimeout!(subscriber.undeclare()).unwrap();
+        }
+        close_sessions(sub_session, pub_session).await;
+        close_router_session(session).await;
+    }
+
+    #[cfg(feature = "unstable")]
+    async fn test_pub_sub_deny_publisher_interest(port: u16) {
+        println!("test_pub_sub_deny_publisher_interest");
+
+        let mut config_router = get_basic_router_config(port).await;
+        config_router
+            .insert_json5(
+                "access_control",
+                r#"{
+                    "enabled": true,
+                    "default_permission": "allow",
+                    "rules": [
+                        {
+                            "id": "r1",
+                            "permission": "deny",
+                            "flows": ["egress", "ingress"],
+                            "messages": ["interest"],
+                            "key_exprs": [
+                                "test/demo"
+                            ],
+                        },
+                    ],
+                    "subjects": [
+                        {
+                            "id": "s1",
+                            "interfaces": [
+                                "lo", "lo0"
+                            ],
+                        }
+                    ],
+                    "policies": [
+                        {
+                            "rules": ["r1"],
+                            "subjects": ["s1"],
+                        }
+                    ]
+                }"#,
+            )
+            .unwrap();
+        println!("Opening router session");
+
+        let session = ztimeout!(zenoh::open(config_router)).unwrap();
+        let (sub_session, pub_session) = get_client_sessions(port).await;
+        {
+            let received_value = Arc::new(Mutex::new(String::new()));
+            let temp_recv_value = received_value.clone();
+            let subscriber = sub_session
+                .declare_subscriber(KEY_EXPR)
+                .callback(move |sample| {
+                    let mut temp_value = zlock!(temp_recv_value);
+                    *temp_value = sample.payload().try_to_string().unwrap().into_owned();
+                })
+                .await
+                .unwrap();
+
+            tokio::time::sleep(SLEEP).await;
+            // The Interest of the publisher is dropped: it doesn't learn the matching subscribers,
+            // but still sends its publications to the router.
+            let publisher = ztimeout!(pub_session.declare_publisher(KEY_EXPR)).unwrap();
+            tokio::time::sleep(SLEEP).await;
+            assert!(!ztimeout!(publisher.matching_status())
+                .unwrap()
+                .matching_subscribers());
+            publisher.put(VALUE).await.unwrap();
+            tokio::time::sleep(SLEEP).await;
+            assert_eq!(*zlock!(received_value), VALUE);
+            ztimeout!(subscriber.undeclare()).unwrap();
+        }
+        close_sessions(sub_session, pub_session).await;
+        close_router_session(session).await;
+    }
+
     async fn test_get_qbl_deny(port: u16) {
         println!("test_get_qbl_deny");
 
@@ -805,4 +988,547 @@ mod test {
         close_sessions(get_session, qbl_session).await;
         close_router_session(session).await;
     }
+
+    async fn test_liveliness(port: u16, acl_config: &str) -> (bool, bool) {
+        let mut config_router = get_basic_router_config(port).await;
+        config_router
+            .insert_json5("access_control", acl_config)
+            .unwrap();
+        println!("Opening router session");
+
+        let session = ztimeout!(zenoh::open(config_router)).unwrap();
+
+        let (token_session, sub_session) = get_client_sessions(port).await;
+        let (subscribed, queried) = {
+            let subscribed = Arc::new(Mutex::new(false));
+            let temp_subscribed = subscribed.clone();
+            let sub = ztimeout!(sub_session
+                .liveliness()
+                .declare_subscriber(KEY_EXPR)
+                .callback(move |sample| {
+                    if sample.kind() == SampleKind::Put {
+                        *zlock!(temp_subscribed) = true;
+                    }
+                }))
+            .unwrap();
+            tokio::time::sleep(SLEEP).await;
+
+            let token = ztimeout!(token_session.liveliness().declare_token(KEY_EXPR)).unwrap();
+            tokio::time::sleep(SLEEP).await;
+
+            let mut queried = false;
+            let replies = ztimeout!(sub_session.liveliness().get(KEY_EXPR)).unwrap();
+            while let Ok(reply) = ztimeout!(replies.recv_async()) {
+                queried |= reply.result().is_ok();
+            }
+
+            ztimeout!(token.undeclare()).unwrap();
+            ztimeout!(sub.undeclare()).unwrap();
+            let subscribed = *zlock!(subscribed);
+            (subscribed, queried)
+        };
+        close_sessions(token_session, sub_session).await;
+        close_router_session(session).await;
+        (subscribed, queried)
+    }
+
+    async fn test_liveliness_deny(port: u16) {
+        println!("test_liveliness_deny");
+
+        let (subscribed, queried) = test_liveliness(
+            port,
+            r#"{
+                "enabled": true,
+                "default_permission": "allow",
+                "rules": [
+                    {
+                        "id": "deny liveliness",
+                        "permission": "deny",
+                        "messages": [
+                            "liveliness_token",
+                            "declare_liveliness_subscriber",
+                            "liveliness_query",
+                        ],
+                        "flows": ["ingress"],
+                        "key_exprs": ["test/demo"],
+                    }
+                ],
+                "subjects": [
+                    { "id": "all" }
+                ],
+                "policies": [
+                    {
+                        "rules": ["deny liveliness"],
+                        "subjects": ["all"],
+                    }
+                ],
+            }"#,
+        )
+        .await;
+        assert!(!subscribed);
+        assert!(!queried);
+    }
+
+    async fn test_liveliness_allow(port: u16) {
+        println!("test_liveliness_allow");
+
+        let (subscribed, queried) = test_liveliness(
+            port,
+            r#"{
+                "enabled": true,
+                "default_permission": "deny",
+                "rules": [
+                    {
+                        "id": "allow liveliness",
+                        "permission": "allow",
+                        "messages": [
+                            "liveliness_token",
+                            "declare_liveliness_subscriber",
+                            "liveliness_query",
+                            "interest",
+                        ],
+                        "flows": ["egress", "ingress"],
+                        "key_exprs": ["test/demo"],
+                    }
+                ],
+                "subjects": [
+                    { "id": "all" }
+                ],
+                "policies": [
+                    {
+                        "rules": ["allow liveliness"],
+                        "subjects": ["all"],
+                    }
+                ],
+            }"#,
+        )
+        .await;
+        assert!(subscribed);
+        assert!(queried);
+    }
+
+    async fn test_pub_sub_update(port: u16) {
+        println!("test_pub_sub_update");
+
+        let mut config_router = get_basic_router_config(port).await;
+        config_router
+            .insert_json5(
+                "adminspace",
+                r#"{ enabled: true, permissions: { write: true } }"#,
+            )
+            .unwrap();
+        config_router
+            .insert_json5(
+                "access_control",
+                r#"{
+                    "enabled": true,
+                    "default_permission": "deny",
+                    "rules": [
+                        {
+                            "id": "r1",
+                            "permission": "allow",
+                            "flows": ["egress", "ingress"],
+                            "messages": ["declare_subscriber"],
+                            "key_exprs": ["test/demo"],
+                        },
+                    ],
+                    "subjects": [
+                        { "id": "all" }
+                    ],
+                    "policies": [
+                        {
+                            "rules": ["r1"],
+                            "subjects": ["all"],
+                        }
+                    ]
+                }"#,
+            )
+            .unwrap();
+        println!("Opening router session");
+
+        let session = ztimeout!(zenoh::open(config_router)).unwrap();
+        let rules_key = format!("@/{}/router/config/access_control/rules", session.zid());
+        let (sub_session, pub_session) = get_client_sessions(port).await;
+        {
+            let publisher = ztimeout!(pub_session.declare_publisher(KEY_EXPR)).unwrap();
+            let received_value = Arc::new(Mutex::new(String::new()));
+
+            let temp_recv_value = received_value.clone();
+            let subscriber = sub_session
+                .declare_subscriber(KEY_EXPR)
+                .callback(move |sample| {
+                    let mut temp_value = zlock!(temp_recv_value);
+                    *temp_value = sample.payload().try_to_string().unwrap().into_owned();
+                })
+                .await
+                .unwrap();
+
+            tokio::time::sleep(SLEEP).await;
+            publisher.put("denied").await.unwrap();
+            tokio::time::sleep(SLEEP).await;
+            assert_eq!(*zlock!(received_value), "");
+
+            // Allow the puts without reopening the client sessions
+            ztimeout!(session.put(
+                &rules_key,
+                r#"[
+                    {
+                        "id": "r1",
+                        "permission": "allow",
+                        "flows": ["egress", "ingress"],
+                        "messages": ["put", "declare_subscriber"],
+                        "key_exprs": ["test/demo"],
+                    },
+                ]"#
+            ))
+            .unwrap();
+            tokio::time::sleep(SLEEP).await;
+            publisher.put(VALUE).await.unwrap();
+            tokio::time::sleep(SLEEP).await;
+            assert_eq!(*zlock!(received_value), VALUE);
+
+            // An invalid update (the policy refers to an unknown rule) keeps the current policy
+            ztimeout!(session.put(
+                &rules_key,
+                r#"[
+                    {
+                        "id": "r2",
+                        "permission": "allow",
+                        "flows": ["egress", "ingress"],
+                        "messages": ["declare_subscriber"],
+                        "key_exprs": ["test/demo"],
+                    },
+                ]"#
+            ))
+            .unwrap();
+            tokio::time::sleep(SLEEP).await;
+            publisher.put("still allowed").await.unwrap();
+            tokio::time::sleep(SLEEP).await;
+            assert_eq!(*zlock!(received_value), "still allowed");
+            // ... and is not committed in the configuration
+            let rules = session
+                .config()
+                .lock()
+                .get_json("access_control/rules")
+                .unwrap();
+            assert!(rules.contains("r1") && !rules.contains("r2"));
+
+            // Access control can't be disabled at runtime
+            ztimeout!(session.put(
+                format!("@/{}/router/config/access_control/enabled", session.zid()),
+                "false"
+            ))
+            .unwrap();
+            tokio::time::sleep(SLEEP).await;
+            let enabled = session
+                .config()
+                .lock()
+                .get_json("access_control/enabled")
+                .unwrap();
+            assert_eq!(enabled, "true");
+
+            // An invalid update through the API is rolled back
+            session
+                .config()
+                .insert_json5("access_control/enabled", "false")
+                .unwrap();
+            tokio::time::sleep(SLEEP).await;
+            let enabled = session
+                .config()
+                .lock()
+                .get_json("access_control/enabled")
+                .unwrap();
+            assert_eq!(enabled, "true");
+            publisher.put("allowed again").await.unwrap();
+            tokio::time::sleep(SLEEP).await;
+            assert_eq!(*zlock!(received_value), "allowed again");
+
+            ztimeout!(subscriber.undeclare()).unwrap();
+        }
+        close_sessions(sub_session, pub_session).await;
+        close_router_session(session).await;
+    }
+
+    fn get_audit_router_config(config_router: &mut Config, sink: &str) {
+        config_router
+            .insert_json5(
+                "access_control",
+                &format!(
+                    r#"{{
+                    "enabled": true,
+                    "default_permission": "allow",
+                    "rules": [
+                        {{
+                            "id": "deny put",
+                            "permission": "deny",
+                            "flows": ["ingress"],
+                            "messages": ["put"],
+                            "key_exprs": ["test/demo"],
+                        }},
+                    ],
+                    "subjects": [
+                        {{ "id": "all" }}
+                    ],
+                    "policies": [
+                        {{
+                            "rules": ["deny put"],
+                            "subjects": ["all"],
+                        }}
+                    ],
+                    "audit": {{
+                        "decisions": "deny",
+                        "sink": {sink},
+                    }},
+                }}"#
+                ),
+            )
+            .unwrap();
+    }
+
+    fn check_audit_record(record: &serde_json::Value) {
+        assert_eq!(record["message"], "put");
+        assert_eq!(record["key_expr"], KEY_EXPR);
+        assert_eq!(record["flow"], "ingress");
+        assert_eq!(record["decision"], "deny");
+        assert_eq!(record["rule_id"], "deny put");
+        assert!(record["cert_common_name"].is_null());
+        assert!(record["username"].is_null());
+        assert_eq!(record["whatami"], "client");
+        assert_eq!(record["link_protocol"], "tcp");
+        assert_eq!(record["source_ip"], "127.0.0.1");
+        assert!(record["metadata"].is_null());
+    }
+
+    async fn test_audit_file(port: u16) {
+        println!("test_audit_file");
+
+        let path = std::env::temp_dir().join(format!("zenoh_acl_audit_{port}.jsonl"));
+        let _ = std::fs::remove_file(&path);
+        let mut config_router = get_basic_router_config(port).await;
+        get_audit_router_config(
+            &mut config_router,
+            &serde_json::json!({ "file": path }).to_string(),
+        );
+        println!("Opening router session");
+
+        let session = ztimeout!(zenoh::open(config_router)).unwrap();
+        let (sub_session, pub_session) = get_client_sessions(port).await;
+        {
+            tokio::time::sleep(SLEEP).await;
+            ztimeout!(pub_session.put(KEY_EXPR, VALUE)).unwrap();
+            // Allowed decisions are not recorded
+            ztimeout!(pub_session.put("test/other", VALUE)).unwrap();
+            tokio::time::sleep(SLEEP).await;
+
+            let records = std::fs::read_to_string(&path)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(records.len(), 1);
+            check_audit_record(&records[0]);
+            assert_eq!(records[0]["zid"], pub_session.zid().to_string());
+        }
+        close_sessions(sub_session, pub_session).await;
+        close_router_session(session).await;
+        let _ = std::fs::remove_file(&path);
+    }
+
+    async fn test_audit_admin_space(port: u16) {
+        println!("test_audit_admin_space");
+
+        let mut config_router = get_basic_router_config(port).await;
+        get_audit_router_config(&mut config_router, r#""admin_space""#);
+        println!("Opening router session");
+
+        let session = ztimeout!(zenoh::open(config_router)).unwrap();
+        let (sub_session, pub_session) = get_client_sessions(port).await;
+        {
+            let audit_subscriber = ztimeout!(session
+                .declare_subscriber(format!("@/{}/router/access_control/audit", session.zid())))
+            .unwrap();
+            tokio::time::sleep(SLEEP).await;
+            ztimeout!(pub_session.put(KEY_EXPR, VALUE)).unwrap();
+            tokio::time::sleep(SLEEP).await;
+
+            let sample = audit_subscriber.try_recv().unwrap().unwrap();
+            let record: serde_json::Value =
+                serde_json::from_slice(&sample.payload().to_bytes()).unwrap();
+            check_audit_record(&record);
+            assert!(audit_subscriber.try_recv().unwrap().is_none());
+            ztimeout!(audit_subscriber.undeclare()).unwrap();
+        }
+        close_sessions(sub_session, pub_session).await;
+        close_router_session(session).await;
+    }
+
+    async fn get_client_session_with_metadata(port: u16, role: &str, advertise: bool) -> Session {
+        let mut config = zenoh::Config::default();
+        config.set_mode(Some(WhatAmI::Client)).unwrap();
+        config
+            .connect
+            .set_endpoints(ModeDependentValue::Unique(vec![format!(
+                "tcp/127.0.0.1:{port}"
+            )
+            .parse::<EndPoint>()
+            .unwrap()]))
+            .unwrap();
+        config
+            .insert_json5("metadata", &format!(r#"{{ "role": "{role}" }}"#))
+            .unwrap();
+        config
+            .insert_json5(
+                "transport/unicast/advertise_metadata",
+                &advertise.to_string(),
+            )
+            .unwrap();
+        ztimeout!(zenoh::open(config)).unwrap()
+    }
+
+    async fn test_pub_sub_subject_attributes(
+        port: u16,
+        role: &str,
+        advertise: bool,
+        allowed: bool,
+    ) {
+        println!("test_pub_sub_subject_attributes: {role}, advertised: {advertise}");
+
+        let mut config_router = get_basic_router_config(port).await;
+        config_router
+            .insert_json5(
+                "access_control",
+                r#"{
+                    "enabled": true,
+                    "default_permission": "allow",
+                    "rules": [
+                        {
+                            "id": "r1",
+                            "permission": "deny",
+                            "flows": ["egress", "ingress"],
+                            "messages": [
+                                "put",
+                                "declare_subscriber"
+                            ],
+                            "key_exprs": [
+                                "test/demo"
+                            ],
+                        },
+                    ],
+                    "subjects": [
+                        {
+                            "id": "local tcp actuators",
+                            "whatamis": ["client"],
+                            "link_protocols": ["tcp"],
+                            "source_ips": ["127.0.0.0/8"],
+                            "metadata": { "role": "actuator" },
+                        }
+                    ],
+                    "policies": [
+                        {
+                            "rules": ["r1"],
+                            "subjects": ["local tcp actuators"],
+                        }
+                    ]
+                }"#,
+            )
+            .unwrap();
+        println!("Opening router session");
+
+        let session = ztimeout!(zenoh::open(config_router)).unwrap();
+        let sub_session = get_client_session_with_metadata(port, role, advertise).await;
+        let pub_session = get_client_session_with_metadata(port, role, advertise).await;
+        {
+            let received_value = Arc::new(Mutex::new(String::new()));
+            let temp_recv_value = received_value.clone();
+            let subscriber =
+                ztimeout!(sub_session
+                    .declare_subscriber(KEY_EXPR)
+                    .callback(move |sample| {
+                        let mut temp_value = zlock!(temp_recv_value);
+                        *temp_value = sample.payload().try_to_string().unwrap().into_owned();
+                    }))
+                .unwrap();
+
+            tokio::time::sleep(SLEEP).await;
+            ztimeout!(pub_session.put(KEY_EXPR, VALUE)).unwrap();
+            tokio::time::sleep(SLEEP).await;
+            if allowed {
+                assert_eq!(*zlock!(received_value), VALUE);
+            } else {
+                assert_ne!(*zlock!(received_value), VALUE);
+            }
+            ztimeout!(subscriber.undeclare()).unwrap();
+        }
+        close_sessions(sub_session, pub_session).await;
+        close_router_session(session).await;
+    }
+
+    async fn test_allow_unauthenticated_subject(port: u16, role: &str, allowed: bool) {
+        println!("test_allow_unauthenticated_subject: {role}");
+
+        let mut config_router = get_basic_router_config(port).await;
+        config_router
+            .insert_json5(
+                "access_control",
+                r#"{
+                    "enabled": true,
+                    "default_permission": "deny",
+                    "rules": [
+                        {
+                            "id": "r1",
+                            "permission": "allow",
+                            "flows": ["egress", "ingress"],
+                            "messages": [
+                                "put",
+                                "declare_subscriber"
+                            ],
+                            "key_exprs": ["test/demo"],
+                        },
+                    ],
+                    "subjects": [
+                        {
+                            "id": "sensors",
+                            "metadata": { "role": "sensor" },
+                        }
+                    ],
+                    "policies": [
+                        {
+                            "rules": ["r1"],
+                            "subjects": ["sensors"],
+                        }
+                    ]
+                }"#,
+            )
+            .unwrap();
+        println!("Opening router session");
+
+        // The subjects matched only on unauthenticated attributes can be allowed.
+        let session = ztimeout!(zenoh::open(config_router)).unwrap();
+        let sub_session = get_client_session_with_metadata(port, role, true).await;
+        let pub_session = get_client_session_with_metadata(port, role, true).await;
+        {
+            let received_value = Arc::new(Mutex::new(String::new()));
+            let temp_recv_value = received_value.clone();
+            let subscriber =
+                ztimeout!(sub_session
+                    .declare_subscriber(KEY_EXPR)
+                    .callback(move |sample| {
+                        let mut temp_value = zlock!(temp_recv_value);
+                        *temp_value = sample.payload().try_to_string().unwrap().into_owned();
+                    }))
+                .unwrap();
+
+            tokio::time::sleep(SLEEP).await;
+            ztimeout!(pub_session.put(KEY_EXPR, VALUE)).unwrap();
+            tokio::time::sleep(SLEEP).await;
+            if allowed {
+                assert_eq!(*zlock!(received_value), VALUE);
+            } else {
+                assert_ne!(*zlock!(received_value), VALUE);
+            }
+            ztimeout!(subscriber.undeclare()).unwrap();
+        }
+        close_sessions(sub_session, pub_session).await;
+        close_router_session(session).await;
+    }
 }
@@ -24,7 +24,10 @@ use std::{
 };
 
 use zenoh::{key_expr::KeyExpr, Config, Wait};
-use zenoh_config::{DownsamplingItemConf, DownsamplingRuleConf, InterceptorFlow};
+use zenoh_config::{
+    DownsamplingItemConf, DownsamplingRuleConf, InterceptorFlow, RateLimitAction,
+    RateLimitBudgetConf, RateLimitScope, RateLimitingItemConf, RateLimitingRuleConf,
+};
 
 // Tokio's time granularity on different platforms
 #[cfg(target_os = "windows")]
@@ -273,3 +276,868 @@ fn downsampling_config_error_wrong_strategy() {
 
     zenoh::open(config).wait().unwrap();
 }
+
+fn rate_limiting_by_keyexpr_impl(flow: InterceptorFlow) {
+    let ke_prefix = "test/rate_limiting_by_keyexpr";
+    let locator = "tcp/127.0.0.1:31449";
+
+    let ke_10msgs: KeyExpr = format!("{ke_prefix}/10msgs").try_into().unwrap();
+    // The payload "message" is 7 bytes long.
+    let ke_70bytes: KeyExpr = format!("{ke_prefix}/70bytes").try_into().unwrap();
+
+    let rl_config = RateLimitingItemConf {
+        interfaces: None,
+        cert_common_names: None,
+        usernames: None,
+        messages: None,
+        flow,
+        action: RateLimitAction::Drop,
+        max_delay_ms: None,
+        // The budgets are shared by the faces, should the publisher reconnect during the test.
+        scope: RateLimitScope::Shared,
+        rules: vec![
+            RateLimitingRuleConf {
+                key_expr: ke_10msgs.clone().into(),
+                messages: Some(RateLimitBudgetConf {
+                    rate: 10.0,
+                    burst: None,
+                }),
+                bytes: None,
+            },
+            RateLimitingRuleConf {
+                key_expr: ke_70bytes.clone().into(),
+                messages: None,
+                bytes: Some(RateLimitBudgetConf {
+                    rate: 70.0,
+                    burst: Some(7.0),
+                }),
+            },
+        ],
+    };
+    // The messages per second and the burst of messages allowed on each key expression.
+    let budgets = [(ke_10msgs, 10.0, 10.0), (ke_70bytes, 10.0, 1.0)];
+
+    let (mut pub_config, mut sub_config) = build_config(locator, vec![], flow);
+    match flow {
+        InterceptorFlow::Egress => pub_config.set_rate_limiting(vec![rl_config]).unwrap(),
+        InterceptorFlow::Ingress => sub_config.set_rate_limiting(vec![rl_config]).unwrap(),
+    };
+
+    // The budgets are created after the start, so that whatever the scheduling of the test
+    // no more than `burst + rate * elapsed` messages can be received on a key expression.
+    let start = std::time::Instant::now();
+    let counters: Arc<HashMap<KeyExpr, AtomicUsize>> = Arc::new(
+        budgets
+            .iter()
+            .map(|(ke, _, _)| (ke.clone(), AtomicUsize::new(0)))
+            .collect(),
+    );
+
+    let sub_session = zenoh::open(sub_config).wait().unwrap();
+    let _sub = sub_session
+        .declare_subscriber(format!("{ke_prefix}/*"))
+        .callback({
+            let counters = counters.clone();
+            move |sample| {
+                counters
+                    .get(sample.key_expr())
+                    .map(|ctr| ctr.fetch_add(1, Ordering::SeqCst));
+            }
+        })
+        .wait()
+        .unwrap();
+
+    let is_terminated = Arc::new(AtomicBool::new(false));
+    let c_is_terminated = is_terminated.clone();
+    let kes = budgets
+        .iter()
+        .map(|(ke, _, _)| ke.clone())
+        .collect::<Vec<_>>();
+    let handle = std::thread::spawn(move || {
+        let pub_session = zenoh::open(pub_config).wait().unwrap();
+        let publishers: Vec<_> = kes
+            .into_iter()
+            .map(|ke| pub_session.declare_publisher(ke).wait().unwrap())
+            .collect();
+        let interval = std::time::Duration::from_millis(MINIMAL_SLEEP_INTERVAL_MS);
+        while !c_is_terminated.load(Ordering::SeqCst) {
+            publishers.iter().for_each(|publ| {
+                publ.put("message").wait().unwrap();
+            });
+            std::thread::sleep(interval);
+        }
+    });
+
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+    std::thread::sleep(std::time::Duration::from_secs(REPEAT as u64));
+    let counts = budgets
+        .iter()
+        .map(|(ke, _, _)| counters[ke].load(Ordering::SeqCst))
+        .collect::<Vec<_>>();
+    let elapsed = start.elapsed().as_secs_f64();
+
+    let _ = is_terminated.swap(true, Ordering::SeqCst);
+    if let Err(err) = handle.join() {
+        panic!("Failed to join the handle due to {err:?}");
+    }
+
+    for ((ke, rate, burst), count) in budgets.iter().zip(counts) {
+        tracing::info!("keyexpr: {ke}, count: {count}, elapsed: {elapsed}s");
+        assert!(
+            count as f64 <= burst + rate * elapsed,
+            "{count} messages received on {ke} in {elapsed}s"
+        );
+        // The publisher sends far more messages than the budget allows during the test.
+        assert!(
+            count as f64 >= rate * REPEAT as f64 / 2.0,
+            "only {count} messages received on {ke} in {elapsed}s"
+        );
+    }
+}
+
+#[test]
+fn rate_limiting_by_keyexpr() {
+    zenoh::init_log_from_env_or("error");
+    rate_limiting_by_keyexpr_impl(InterceptorFlow::Ingress);
+    rate_limiting_by_keyexpr_impl(InterceptorFlow::Egress);
+}
+
+#[test]
+fn rate_limiting_delay() {
+    zenoh::init_log_from_env_or("error");
+    let ke = "test/rate_limiting_delay";
+    let locator = "tcp/127.0.0.1:31455";
+
+    let (pub_config, mut sub_config) = build_config(locator, vec![], InterceptorFlow::Ingress);
+    sub_config
+        .insert_json5(
+            "rate_limiting",
+            &format!(
+                r#"
+                  [
+                    {{
+                      flow: "ingress",
+                      action: "delay",
+                      max_delay_ms: 500,
+                      rules: [
+                        {{ key_expr: "{ke}", messages: {{ rate: 20, burst: 1 }} }},
+                      ],
+                    }},
+                  ]
+                "#
+            ),
+        )
+        .unwrap();
+
+    let received = Arc::new(AtomicUsize::new(0));
+    let sub_session = zenoh::open(sub_config).wait().unwrap();
+    let _sub = sub_session
+        .declare_subscriber(ke)
+        .callback({
+            let received = received.clone();
+            move |_| {
+                received.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .wait()
+        .unwrap();
+    let pub_session = zenoh::open(pub_config).wait().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+
+    // The messages exceeding the budget are delayed rather than dropped: each of them waits for
+    // its token, well within the maximum delay.
+    for _ in 0..5 {
+        pub_session.put(ke, "message").wait().unwrap();
+    }
+    std::thread::sleep(std::time::Duration::from_secs(1));
+    assert_eq!(received.load(Ordering::SeqCst), 5);
+}
+
+#[test]
+#[should_panic(expected = "only supported on the ingress flow")]
+fn rate_limiting_config_error_delay_on_egress() {
+    zenoh::init_log_from_env_or("error");
+
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "rate_limiting",
+            r#"
+              [
+                {
+                  flow: "egress",
+                  action: "delay",
+                  rules: [
+                    { key_expr: "test/rate_limiting/delay", messages: { rate: 10 } },
+                  ],
+                },
+              ]
+            "#,
+        )
+        .unwrap();
+
+    zenoh::open(config).wait().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "must be strictly positive")]
+fn rate_limiting_config_error_invalid_rate() {
+    zenoh::init_log_from_env_or("error");
+
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "rate_limiting",
+            r#"
+              [
+                {
+                  flow: "ingress",
+                  rules: [
+                    { key_expr: "test/rate_limiting/invalid", messages: { rate: 0 } },
+                  ],
+                },
+              ]
+            "#,
+        )
+        .unwrap();
+
+    zenoh::open(config).wait().unwrap();
+}
+
+#[test]
+fn key_remapping() {
+    zenoh::init_log_from_env_or("error");
+    let locator = "tcp/127.0.0.1:31450";
+
+    let (mut remote_config, mut local_config) =
+        build_config(locator, vec![], InterceptorFlow::Ingress);
+    local_config
+        .insert_json5(
+            "key_remapping",
+            r#"
+              [
+                {
+                  rules: [
+                    { remote_prefix: "robot1", local_prefix: "fleet/robot1" },
+                  ],
+                },
+              ]
+            "#,
+        )
+        .unwrap();
+    remote_config.insert_json5("mode", r#""client""#).unwrap();
+
+    let local_session = zenoh::open(local_config).wait().unwrap();
+    let remote_session = zenoh::open(remote_config).wait().unwrap();
+
+    let local_sub = local_session
+        .declare_subscriber("fleet/robot1/**")
+        .wait()
+        .unwrap();
+    let remote_sub = remote_session
+        .declare_subscriber("robot1/**")
+        .wait()
+        .unwrap();
+    let _local_queryable = local_session
+        .declare_queryable("fleet/robot1/query")
+        .callback(|query| {
+            let key_expr = query.key_expr().clone();
+            query.reply(key_expr, "reply").wait().unwrap();
+        })
+        .wait()
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+
+    // Ingress: the remote key expressions are received with the local prefix.
+    remote_session.put("robot1/a", "a").wait().unwrap();
+    // Egress: the local key expressions are sent with the remote prefix.
+    local_session.put("fleet/robot1/b", "b").wait().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+
+    let local_keys = local_sub
+        .drain()
+        .map(|sample| sample.key_expr().to_string())
+        .collect::<Vec<_>>();
+    assert!(local_keys.contains(&"fleet/robot1/a".to_string()));
+    let remote_keys = remote_sub
+        .drain()
+        .map(|sample| sample.key_expr().to_string())
+        .collect::<Vec<_>>();
+    assert!(remote_keys.contains(&"robot1/b".to_string()));
+    assert!(!remote_keys.iter().any(|key| key.starts_with("fleet")));
+
+    // Queries are remapped on ingress and their replies on egress.
+    let replies = remote_session
+        .get("robot1/query")
+        .wait()
+        .unwrap()
+        .into_iter()
+        .map(|reply| reply.into_result().unwrap().key_expr().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(replies, vec!["robot1/query".to_string()]);
+
+    remote_session.close().wait().unwrap();
+    local_session.close().wait().unwrap();
+}
+
+/// Returns whether each of the subscribers receives a publication on its key expression.
+fn received_publications(
+    publisher: &zenoh::Session,
+    subscribers: &[(
+        &str,
+        zenoh::pubsub::Subscriber<zenoh::handlers::FifoChannelHandler<zenoh::sample::Sample>>,
+    )],
+) -> Vec<bool> {
+    for (key_expr, _) in subscribers {
+        publisher.put(*key_expr, "value").wait().unwrap();
+    }
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+    subscribers
+        .iter()
+        .map(|(_, subscriber)| subscriber.drain().count() > 0)
+        .collect()
+}
+
+/// Subscribes to the events of the unicast transports of the session, without declaring the
+/// subscriber to the remote nodes so it doesn't count in their quotas.
+fn transport_events(
+    session: &zenoh::Session,
+) -> zenoh::pubsub::Subscriber<zenoh::handlers::FifoChannelHandler<zenoh::sample::Sample>> {
+    session
+        .declare_subscriber(format!("@/{}/session/transport/unicast/*", session.zid()))
+        .allowed_origin(zenoh::sample::Locality::SessionLocal)
+        .wait()
+        .unwrap()
+}
+
+/// Returns whether a transport of the session was closed, as observed from its events within a
+/// few seconds.
+fn transport_closed(
+    events: &zenoh::pubsub::Subscriber<zenoh::handlers::FifoChannelHandler<zenoh::sample::Sample>>,
+) -> bool {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while let Ok(Some(sample)) = events.recv_timeout(deadline - std::time::Instant::now()) {
+        if sample.kind() == zenoh::sample::SampleKind::Delete {
+            return true;
+        }
+    }
+    false
+}
+
+#[test]
+fn quotas_face_scope() {
+    zenoh::init_log_from_env_or("error");
+    let locator = "tcp/127.0.0.1:31451";
+
+    let (mut remote_config, mut local_config) =
+        build_config(locator, vec![], InterceptorFlow::Ingress);
+    local_config
+        .insert_json5("quotas", r#"[ { limits: { subscribers: 2 } } ]"#)
+        .unwrap();
+    remote_config.insert_json5("mode", r#""client""#).unwrap();
+
+    let local_session = zenoh::open(local_config).wait().unwrap();
+    let remote_session = zenoh::open(remote_config).wait().unwrap();
+    let events = transport_events(&remote_session);
+
+    let mut subscribers = ["test/quotas/1", "test/quotas/2"]
+        .into_iter()
+        .map(|key_expr| {
+            let subscriber = remote_session.declare_subscriber(key_expr).wait().unwrap();
+            (key_expr, subscriber)
+        })
+        .collect::<Vec<_>>();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+    assert_eq!(
+        received_publications(&local_session, &subscribers),
+        vec![true, true]
+    );
+
+    // Undeclaring a subscriber frees its slot in the quota.
+    let (_, subscriber) = subscribers.remove(0);
+    subscriber.undeclare().wait().unwrap();
+    let subscriber = remote_session
+        .declare_subscriber("test/quotas/3")
+        .wait()
+        .unwrap();
+    subscribers.push(("test/quotas/3", subscriber));
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+    assert_eq!(
+        received_publications(&local_session, &subscribers),
+        vec![true, true]
+    );
+
+    // The third subscriber exceeds the quota: only its declaration is dropped.
+    let subscriber = remote_session
+        .declare_subscriber("test/quotas/4")
+        .wait()
+        .unwrap();
+    subscribers.push(("test/quotas/4", subscriber));
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+    assert_eq!(
+        received_publications(&local_session, &subscribers),
+        vec![true, true, false]
+    );
+    assert!(!transport_closed(&events));
+
+    remote_session.close().wait().unwrap();
+    local_session.close().wait().unwrap();
+}
+
+#[test]
+fn quotas_subject_scope() {
+    zenoh::init_log_from_env_or("error");
+    let locator = "tcp/127.0.0.1:31452";
+
+    let (mut remote_config, mut local_config) =
+        build_config(locator, vec![], InterceptorFlow::Ingress);
+    local_config
+        .insert_json5(
+            "quotas",
+            r#"[ { scope: "subject", limits: { resources: 1 } } ]"#,
+        )
+        .unwrap();
+    remote_config.insert_json5("mode", r#""client""#).unwrap();
+    let (mut remote_config2, _) = build_config(locator, vec![], InterceptorFlow::Ingress);
+    remote_config2.insert_json5("mode", r#""client""#).unwrap();
+
+    let local_session = zenoh::open(local_config).wait().unwrap();
+    let remote_session1 = zenoh::open(remote_config).wait().unwrap();
+    let remote_session2 = zenoh::open(remote_config2).wait().unwrap();
+    let events1 = transport_events(&remote_session1);
+    let events2 = transport_events(&remote_session2);
+
+    // The faces share the quota: the same key expression can be declared by both, but not a
+    // second one, whose declaration only is dropped.
+    let subscribers = [
+        (&remote_session1, "test/quotas/a"),
+        (&remote_session2, "test/quotas/a"),
+        (&remote_session2, "test/quotas/b"),
+    ]
+    .into_iter()
+    .map(|(session, key_expr)| {
+        let subscriber = session.declare_subscriber(key_expr).wait().unwrap();
+        (key_expr, subscriber)
+    })
+    .collect::<Vec<_>>();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+    assert_eq!(
+        received_publications(&local_session, &subscribers),
+        vec![true, true, false]
+    );
+    assert!(!transport_closed(&events1));
+    assert!(!transport_closed(&events2));
+
+    remote_session2.close().wait().unwrap();
+    remote_session1.close().wait().unwrap();
+    local_session.close().wait().unwrap();
+}
+
+#[test]
+fn qos_overwrite() {
+    use zenoh::qos::{CongestionControl, Priority};
+
+    zenoh::init_log_from_env_or("error");
+    let locator = "tcp/127.0.0.1:31453";
+
+    let (mut remote_config, mut local_config) =
+        build_config(locator, vec![], InterceptorFlow::Ingress);
+    local_config
+        .insert_json5(
+            "qos_overwrite",
+            r#"
+              [
+                {
+                  whatamis: ["client"],
+                  flows: ["ingress"],
+                  key_exprs: ["test/qos/logs/**"],
+                  overwrite: { priority: "background", congestion_control: "block", express: true },
+                },
+              ]
+            "#,
+        )
+        .unwrap();
+    remote_config.insert_json5("mode", r#""client""#).unwrap();
+
+    let local_session = zenoh::open(local_config).wait().unwrap();
+    let remote_session = zenoh::open(remote_config).wait().unwrap();
+
+    let sub = local_session
+        .declare_subscriber("test/qos/**")
+        .wait()
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+
+    remote_session.put("test/qos/logs/a", "a").wait().unwrap();
+    remote_session.put("test/qos/other", "b").wait().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+
+    let samples = sub
+        .drain()
+        .map(|sample| (sample.key_expr().to_string(), sample))
+        .collect::<HashMap<_, _>>();
+    let overwritten = &samples["test/qos/logs/a"];
+    assert_eq!(overwritten.priority(), Priority::Background);
+    assert_eq!(overwritten.congestion_control(), CongestionControl::Block);
+    assert!(overwritten.express());
+    let unchanged = &samples["test/qos/other"];
+    assert_eq!(unchanged.priority(), Priority::default());
+    assert_eq!(unchanged.congestion_control(), CongestionControl::Drop);
+    assert!(!unchanged.express());
+
+    remote_session.close().wait().unwrap();
+    local_session.close().wait().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "must overwrite the priority")]
+fn qos_overwrite_config_error_no_overwrite() {
+    zenoh::init_log_from_env_or("error");
+
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "qos_overwrite",
+            r#"[ { key_exprs: ["test/qos/**"], overwrite: {} } ]"#,
+        )
+        .unwrap();
+    zenoh::open(config).wait().unwrap();
+}
+
+#[test]
+fn content_filter() {
+    use zenoh::bytes::Encoding;
+
+    zenoh::init_log_from_env_or("error");
+    let locator = "tcp/127.0.0.1:31454";
+
+    let (remote_config, mut local_config) = build_config(locator, vec![], InterceptorFlow::Ingress);
+    local_config
+        .insert_json5(
+            "content_filters",
+            r#"
+              [
+                {
+                  flows: ["ingress"],
+                  key_exprs: ["test/content/telemetry/**"],
+                  encodings: ["application/json"],
+                  predicates: [ { json: { pointer: "/level", operator: "ge", value: 3 } } ],
+                  max_payload_size: 64,
+                  action: "forward",
+                },
+                {
+                  flows: ["ingress"],
+                  key_exprs: ["test/content/events/**"],
+                  predicates: [ { attachment_key: "debug" } ],
+                  action: "drop",
+                },
+              ]
+            "#,
+        )
+        .unwrap();
+
+    let local_session = zenoh::open(local_config).wait().unwrap();
+    let remote_session = zenoh::open(remote_config).wait().unwrap();
+
+    let sub = local_session
+        .declare_subscriber("test/content/**")
+        .wait()
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+
+    let put_json = |key_expr: &str, payload: &str| {
+        remote_session
+            .put(key_expr, payload)
+            .encoding(Encoding::APPLICATION_JSON)
+            .wait()
+            .unwrap();
+    };
+    put_json("test/content/telemetry/high", r#"{"level": 5}"#);
+    put_json("test/content/telemetry/low", r#"{"level": 1}"#);
+    put_json("test/content/telemetry/missing", r#"{"other": 5}"#);
+    put_json("test/content/telemetry/invalid", "level: 5");
+    // Too large to be parsed
+    put_json(
+        "test/content/telemetry/large",
+        &format!(r#"{{"level": 5, "padding": "{}"}}"#, "x".repeat(64)),
+    );
+    remote_session
+        .put("test/content/telemetry/text", r#"{"level": 5}"#)
+        .encoding(Encoding::TEXT_PLAIN)
+        .wait()
+        .unwrap();
+    put_json("test/content/other", r#"{"level": 1}"#);
+    // Attachments serialized as maps of strings: the number of entries, then the length
+    // prefixed keys and values.
+    remote_session
+        .put("test/content/events/debug", "a")
+        .attachment(b"\x01\x05debug\x02on".as_slice())
+        .wait()
+        .unwrap();
+    remote_session
+        .put("test/content/events/info", "b")
+        .attachment(b"\x01\x04info\x02on".as_slice())
+        .wait()
+        .unwrap();
+    // Not a map: the trailing bytes don't match
+    remote_session
+        .put("test/content/events/trailing", "d")
+        .attachment(b"\x01\x05debug\x02on!".as_slice())
+        .wait()
+        .unwrap();
+    remote_session
+        .put("test/content/events/none", "c")
+        .wait()
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(WARMUP_MS));
+
+    let mut received = sub
+        .drain()
+        .map(|sample| sample.key_expr().to_string())
+        .collect::<Vec<_>>();
+    received.sort();
+    assert_eq!(
+        received,
+        [
+            "test/content/events/info",
+            "test/content/events/none",
+            "test/content/events/trailing",
+            "test/content/other",
+            "test/content/telemetry/high",
+        ]
+    );
+
+    remote_session.close().wait().unwrap();
+    local_session.close().wait().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "has no value to compare")]
+fn content_filter_config_error_missing_value() {
+    zenoh::init_log_from_env_or("error");
+
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "content_filters",
+            r#"
+              [
+                {
+                  key_exprs: ["test/content/**"],
+                  predicates: [ { json: { pointer: "/level", operator: "gt" } } ],
+                  action: "drop",
+                },
+              ]
+            "#,
+        )
+        .unwrap();
+    zenoh::open(config).wait().unwrap();
+}
+
+#[cfg(all(feature = "unstable", feature = "internal"))]
+mod registered_factory {
+    use std::any::Any;
+
+    use zenoh::{
+        interceptor::{
+            network::{NetworkBody, NetworkMessage},
+            EgressInterceptor, IngressInterceptor, InterceptorFactoryTrait, InterceptorTrait,
+            RoutingContext, TransportMulticast, TransportUnicast,
+        },
+        internal::runtime::RuntimeBuilder,
+    };
+
+    use super::*;
+
+    /// Drops the publications on the key expressions matching `dropped` and counts the others.
+    struct DropInterceptor {
+        dropped: KeyExpr<'static>,
+        forwarded: Arc<AtomicUsize>,
+    }
+
+    impl InterceptorTrait for DropInterceptor {
+        fn compute_keyexpr_cache(
+            &self,
+            key_expr: &KeyExpr<'_>,
+        ) -> Option<Box<dyn Any + Send + Sync>> {
+            Some(Box::new(self.dropped.intersects(key_expr)))
+        }
+
+        fn intercept(
+            &self,
+            ctx: RoutingContext<NetworkMessage>,
+            cache: Option<&Box<dyn Any + Send + Sync>>,
+        ) -> Option<RoutingContext<NetworkMessage>> {
+            if !matches!(ctx.msg().body, NetworkBody::Push(_)) {
+                return Some(ctx);
+            }
+            let dropped = match cache.and_then(|cache| cache.downcast_ref::<bool>()) {
+                Some(dropped) => *dropped,
+                None => ctx
+                    .full_key_expr()
+                    .is_some_and(|key_expr| self.dropped.intersects(&KeyExpr::from(key_expr))),
+            };
+            if dropped {
+                None
+            } else {
+                self.forwarded.fetch_add(1, Ordering::SeqCst);
+                Some(ctx)
+            }
+        }
+    }
+
+    struct DropInterceptorFactory {
+        dropped: KeyExpr<'static>,
+        forwarded: Arc<AtomicUsize>,
+        egress: bool,
+    }
+
+    impl DropInterceptorFactory {
+        fn interceptor(&self) -> DropInterceptor {
+            DropInterceptor {
+                dropped: self.dropped.clone(),
+                forwarded: self.forwarded.clone(),
+            }
+        }
+    }
+
+    impl InterceptorFactoryTrait for DropInterceptorFactory {
+        fn new_transport_unicast(
+            &self,
+            _transport: &TransportUnicast,
+        ) -> (Option<IngressInterceptor>, Option<EgressInterceptor>) {
+            if self.egress {
+                (None, Some(Box::new(self.interceptor())))
+            } else {
+                (Some(Box::new(self.interceptor())), None)
+            }
+        }
+
+        fn new_transport_multicast(
+            &self,
+            _transport: &TransportMulticast,
+        ) -> Option<EgressInterceptor> {
+            self.egress
+                .then(|| Box::new(self.interceptor()) as EgressInterceptor)
+        }
+
+        fn new_peer_multicast(
+            &self,
+            _transport: &TransportMulticast,
+        ) -> Option<IngressInterceptor> {
+            (!self.egress).then(|| Box::new(self.interceptor()) as IngressInterceptor)
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn registered_interceptor_factory() {
+        zenoh::init_log_from_env_or("error");
+        let ke_prefix = "test/registered_interceptor_factory";
+        let locator = "tcp/127.0.0.1:31448";
+
+        let (pub_config, sub_config) = build_config(locator, vec![], InterceptorFlow::Ingress);
+
+        let forwarded = Arc::new(AtomicUsize::new(0));
+        let mut runtime = RuntimeBuilder::new(sub_config).build().await.unwrap();
+        runtime.register_interceptor_factory(Box::new(DropInterceptorFactory {
+            dropped: format!("{ke_prefix}/dropped").try_into().unwrap(),
+            forwarded: forwarded.clone(),
+            egress: false,
+        }));
+        runtime.start().await.unwrap();
+        let sub_session = zenoh::session::init(runtime).await.unwrap();
+
+        let received = Arc::new(std::sync::Mutex::new(vec![]));
+        let _sub = sub_session
+            .declare_subscriber(format!("{ke_prefix}/*"))
+            .callback({
+                let received = received.clone();
+                move |sample| received.lock().unwrap().push(sample.key_expr().to_string())
+            })
+            .await
+            .unwrap();
+
+        let pub_session = zenoh::open(pub_config).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(WARMUP_MS)).await;
+
+        pub_session
+            .put(format!("{ke_prefix}/dropped"), "dropped")
+            .await
+            .unwrap();
+        pub_session
+            .put(format!("{ke_prefix}/forwarded"), "forwarded")
+            .await
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![format!("{ke_prefix}/forwarded")]
+        );
+        assert_eq!(forwarded.load(Ordering::SeqCst), 1);
+
+        pub_session.close().await.unwrap();
+        sub_session.close().await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn registered_interceptor_factory_before_egress_key_remapping() {
+        zenoh::init_log_from_env_or("error");
+        let ke_prefix = "test/registered_interceptor_factory_before_egress_key_remapping";
+        let locator = "tcp/127.0.0.1:31449";
+
+        let (mut pub_config, sub_config) = build_config(locator, vec![], InterceptorFlow::Ingress);
+        pub_config
+            .insert_json5(
+                "key_remapping",
+                &format!(
+                    r#"[{{ rules: [{{ remote_prefix: "remote", local_prefix: "{ke_prefix}" }}] }}]"#
+                ),
+            )
+            .unwrap();
+
+        // The registered factory sees the local key expressions: it is called before the egress
+        // key remapping.
+        let forwarded = Arc::new(AtomicUsize::new(0));
+        let mut runtime = RuntimeBuilder::new(pub_config).build().await.unwrap();
+        runtime.register_interceptor_factory(Box::new(DropInterceptorFactory {
+            dropped: format!("{ke_prefix}/dropped").try_into().unwrap(),
+            forwarded: forwarded.clone(),
+            egress: true,
+        }));
+
+        let sub_session = zenoh::open(sub_config).await.unwrap();
+        let received = Arc::new(std::sync::Mutex::new(vec![]));
+        let _sub = sub_session
+            .declare_subscriber("remote/*")
+            .callback({
+                let received = received.clone();
+                move |sample| received.lock().unwrap().push(sample.key_expr().to_string())
+            })
+            .await
+            .unwrap();
+
+        runtime.start().await.unwrap();
+        let pub_session = zenoh::session::init(runtime).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(WARMUP_MS)).await;
+
+        pub_session
+            .put(format!("{ke_prefix}/dropped"), "dropped")
+            .await
+            .unwrap();
+        pub_session
+            .put(format!("{ke_prefix}/forwarded"), "forwarded")
+            .await
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec!["remote/forwarded".to_string()]
+        );
+        assert_eq!(forwarded.load(Ordering::SeqCst), 1);
+
+        pub_session.close().await.unwrap();
+        sub_session.close().await.unwrap();
+    }
+}